@@ -0,0 +1,6 @@
+// `sqlx::migrate!` embeds the migration files at compile time; rebuild when
+// one is added or changed so the binary never ships a stale schema
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+    println!("cargo:rerun-if-changed=migrations_sqlite");
+}
@@ -0,0 +1,30 @@
+-- User-defined task statuses and the transitions allowed between them
+ALTER TABLE tasks DROP CONSTRAINT status_check;
+
+ALTER TABLE tasks
+ADD CONSTRAINT status_check CHECK (
+    status IN (0, 1, 2)
+    OR status >= 10
+);
+
+COMMENT ON COLUMN tasks.status IS '0: Pending, 1: In Progress, 2: Completed, >= 10: custom status from statuses.code';
+
+CREATE TABLE statuses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code SMALLINT NOT NULL CONSTRAINT custom_code_check CHECK (code >= 10),
+    name VARCHAR(50) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, code),
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX idx_statuses_user_id ON statuses (user_id);
+
+CREATE TABLE status_transitions (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    from_status SMALLINT NOT NULL,
+    to_status SMALLINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, from_status, to_status)
+);
@@ -0,0 +1,22 @@
+-- Start/stop timers recorded against tasks
+CREATE TABLE time_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    task_id UUID NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ended_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT time_entry_range_check CHECK (
+        ended_at IS NULL
+        OR ended_at >= started_at
+    )
+);
+
+CREATE INDEX idx_time_entries_task_id ON time_entries (task_id);
+
+CREATE INDEX idx_time_entries_user_started ON time_entries (user_id, started_at);
+
+-- A user can only have one running timer at a time
+CREATE UNIQUE INDEX idx_time_entries_running ON time_entries (user_id)
+WHERE
+    ended_at IS NULL;
@@ -0,0 +1,3 @@
+-- Estimated effort per task, in minutes
+ALTER TABLE tasks
+ADD COLUMN estimate_minutes INTEGER CONSTRAINT estimate_minutes_check CHECK (estimate_minutes > 0);
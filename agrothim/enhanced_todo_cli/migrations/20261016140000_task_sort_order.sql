@@ -0,0 +1,5 @@
+-- Manual ranking of tasks; NULL means not ranked yet (listed after ranked tasks)
+ALTER TABLE tasks
+ADD COLUMN sort_order DOUBLE PRECISION;
+
+CREATE INDEX idx_tasks_user_sort_order ON tasks (user_id, sort_order);
@@ -0,0 +1,5 @@
+-- Client a task is billed to, used by billable time reports
+ALTER TABLE tasks
+ADD COLUMN client VARCHAR(100);
+
+CREATE INDEX idx_tasks_user_client ON tasks (user_id, client);
@@ -0,0 +1,5 @@
+-- Where a task can be done: a free-form label and optional coordinates
+ALTER TABLE tasks
+ADD COLUMN location VARCHAR(100),
+ADD COLUMN latitude DOUBLE PRECISION CONSTRAINT latitude_check CHECK (latitude BETWEEN -90 AND 90),
+ADD COLUMN longitude DOUBLE PRECISION CONSTRAINT longitude_check CHECK (longitude BETWEEN -180 AND 180);
@@ -0,0 +1,5 @@
+-- Tasks with a future start date are hidden from default lists until then
+ALTER TABLE tasks
+ADD COLUMN start_date TIMESTAMPTZ;
+
+CREATE INDEX idx_tasks_user_start_date ON tasks (user_id, start_date);
@@ -0,0 +1,5 @@
+-- GTD context a task can be done in, e.g. @home or @errands
+ALTER TABLE tasks
+ADD COLUMN context VARCHAR(50);
+
+CREATE INDEX idx_tasks_user_context ON tasks (user_id, context);
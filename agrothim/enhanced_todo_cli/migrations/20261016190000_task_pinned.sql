@@ -0,0 +1,3 @@
+-- Pinned tasks are listed before all other tasks
+ALTER TABLE tasks
+ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT FALSE;
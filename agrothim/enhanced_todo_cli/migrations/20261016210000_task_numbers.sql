@@ -0,0 +1,16 @@
+-- Per-user sequential task numbers, shown with `config set display.ids number`
+ALTER TABLE tasks
+ADD COLUMN number INTEGER;
+
+UPDATE tasks
+SET number = numbered.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY created_at, id) AS position
+    FROM tasks
+) AS numbered
+WHERE tasks.id = numbered.id;
+
+ALTER TABLE tasks
+ALTER COLUMN number SET NOT NULL;
+
+CREATE UNIQUE INDEX idx_tasks_user_number ON tasks (user_id, number);
@@ -0,0 +1,11 @@
+-- History of username changes, used to rate-limit renames and to show
+-- users which names their account went by
+CREATE TABLE username_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    old_username VARCHAR(50) NOT NULL,
+    new_username VARCHAR(50) NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_username_changes_user_id ON username_changes (user_id, changed_at DESC);
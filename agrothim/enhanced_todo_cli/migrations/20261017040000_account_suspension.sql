@@ -0,0 +1,3 @@
+-- Suspended accounts keep their data but can neither log in nor use their tokens
+ALTER TABLE users
+ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,25 @@
+-- Workspaces share a task list between their members
+CREATE TABLE workspaces (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    name VARCHAR(100) NOT NULL,
+    owner_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (owner_id, name)
+);
+
+-- The owner is a member too, with role 'owner'
+CREATE TABLE workspace_members (
+    workspace_id UUID NOT NULL REFERENCES workspaces (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL DEFAULT 'member' CONSTRAINT role_check CHECK (role IN ('owner', 'member')),
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (workspace_id, user_id)
+);
+
+CREATE INDEX idx_workspace_members_user_id ON workspace_members (user_id);
+
+-- Tasks stay owned by their creator; a workspace makes them visible to its members
+ALTER TABLE tasks
+ADD COLUMN workspace_id UUID REFERENCES workspaces (id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tasks_workspace_id ON tasks (workspace_id) WHERE workspace_id IS NOT NULL;
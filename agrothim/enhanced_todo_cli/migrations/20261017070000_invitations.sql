@@ -0,0 +1,16 @@
+-- Invite codes for joining a workspace, created with `workspace invite create`.
+-- Only a hash of the code is stored; `prefix` identifies it in listings.
+CREATE TABLE invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    workspace_id UUID NOT NULL REFERENCES workspaces (id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    prefix VARCHAR(20) NOT NULL UNIQUE,
+    code_hash TEXT NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    max_uses INTEGER CHECK (max_uses > 0),
+    use_count INTEGER NOT NULL DEFAULT 0,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_invitations_workspace_id ON invitations (workspace_id);
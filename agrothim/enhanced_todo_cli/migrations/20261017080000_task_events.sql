@@ -0,0 +1,19 @@
+-- What happened to tasks and who did it, shown by `activity`
+CREATE TABLE task_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    task_id UUID NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    actor_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    -- The other user involved, e.g. who a task was shared with
+    subject_id UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_task_events_task_id ON task_events (task_id);
+CREATE INDEX idx_task_events_created_at ON task_events (created_at);
+
+-- When each user last looked at their activity feed
+CREATE TABLE activity_views (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    seen_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,13 @@
+-- Outcome of each webhook delivery, shown by `webhook deliveries`
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    webhook_id UUID NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event VARCHAR(50) NOT NULL,
+    attempts INTEGER NOT NULL CHECK (attempts > 0),
+    -- HTTP status of the last attempt; NULL if no response was received
+    status_code INTEGER,
+    error TEXT,
+    delivered_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries (webhook_id, delivered_at);
@@ -0,0 +1,32 @@
+-- Announce every task row change on the `task_changes` channel, for
+-- `ChangeListener`. Notifications are limited to 8000 bytes, so the payload
+-- names the row and listeners read the task itself.
+CREATE OR REPLACE FUNCTION notify_task_change() RETURNS TRIGGER AS $$
+DECLARE
+    task RECORD;
+    completed BOOLEAN := FALSE;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        task := OLD;
+    ELSE
+        task := NEW;
+    END IF;
+    IF TG_OP = 'UPDATE' THEN
+        completed := NEW.status = 2 AND OLD.status <> 2;
+    END IF;
+
+    PERFORM pg_notify('task_changes', json_build_object(
+        'operation', lower(TG_OP),
+        'task_id', task.id,
+        'user_id', task.user_id,
+        'workspace_id', task.workspace_id,
+        'completed', completed,
+        'origin', current_setting('application_name')
+    )::TEXT);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER tasks_notify_change
+AFTER INSERT OR UPDATE OR DELETE ON tasks
+FOR EACH ROW EXECUTE FUNCTION notify_task_change();
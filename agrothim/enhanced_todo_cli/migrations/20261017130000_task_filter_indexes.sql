@@ -0,0 +1,29 @@
+-- Indexes for the per-user filters behind task lists. (user_id, status)
+-- already exists as idx_tasks_user_status.
+CREATE INDEX idx_tasks_user_due_date ON tasks (user_id, due_date)
+WHERE
+    due_date IS NOT NULL;
+
+CREATE INDEX idx_tasks_user_updated_at ON tasks (user_id, updated_at DESC);
+
+-- Every query filters by user, so these are covered by the ones above
+DROP INDEX idx_tasks_user_id;
+
+DROP INDEX idx_tasks_due_date;
+
+-- A task cannot start after it is due. The constraint holds for new rows
+-- at once; rows written before it lose their start date, then all are checked.
+ALTER TABLE tasks
+ADD CONSTRAINT start_before_due_check CHECK (
+    start_date IS NULL
+    OR due_date IS NULL
+    OR start_date <= due_date
+) NOT VALID;
+
+UPDATE tasks
+SET
+    start_date = NULL
+WHERE
+    start_date > due_date;
+
+ALTER TABLE tasks VALIDATE CONSTRAINT start_before_due_check;
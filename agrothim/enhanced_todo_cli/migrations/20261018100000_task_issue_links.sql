@@ -0,0 +1,12 @@
+-- GitHub issues `sync github` created tasks from, so each issue gets one task
+-- and is closed once that task is completed. A link outlives its task: an
+-- issue whose task was deleted is not turned into a task again.
+CREATE TABLE task_issue_links (
+    task_id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    repository VARCHAR(140) NOT NULL,
+    issue_number INTEGER NOT NULL,
+    closed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, repository, issue_number)
+);
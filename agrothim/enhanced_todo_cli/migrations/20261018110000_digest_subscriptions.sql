@@ -0,0 +1,11 @@
+-- Users who get the daily digest email, and at what local time. Runs of
+-- `digest run` claim a subscription for the day by setting last_sent_on, so
+-- a digest goes out once even when several runners overlap.
+CREATE TABLE digest_subscriptions (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    send_at TIME NOT NULL,
+    last_sent_on DATE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_digest_subscriptions_send_at ON digest_subscriptions (send_at);
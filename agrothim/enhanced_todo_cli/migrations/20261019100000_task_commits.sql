@@ -0,0 +1,11 @@
+-- Commits `git scan` completed tasks from, so a task shows the commits that
+-- closed it and rescanning a commit does not complete its tasks again.
+CREATE TABLE task_commits (
+    task_id UUID NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    commit_hash VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (task_id, commit_hash)
+);
+
+CREATE INDEX idx_task_commits_user_commit ON task_commits (user_id, commit_hash);
@@ -0,0 +1,18 @@
+-- Tasks created on a schedule, e.g. "Weekly review" every Monday at 09:00.
+-- `cron` fires in server-local time; `tick` claims a due schedule by moving
+-- next_run_at on, so each firing creates one task even when runs overlap.
+CREATE TABLE schedules (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    priority SMALLINT NOT NULL DEFAULT 1,
+    context VARCHAR(50),
+    cron VARCHAR(100) NOT NULL,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_schedules_user_id ON schedules (user_id);
+CREATE INDEX idx_schedules_next_run_at ON schedules (next_run_at);
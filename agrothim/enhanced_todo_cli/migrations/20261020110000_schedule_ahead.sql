@@ -0,0 +1,4 @@
+-- How many upcoming firings of a schedule exist as tasks ahead of time, due
+-- when they fire, so they show up in agenda views early. 0 creates each
+-- task when its firing comes instead.
+ALTER TABLE schedules ADD COLUMN ahead SMALLINT NOT NULL DEFAULT 0 CHECK (ahead BETWEEN 0 AND 20);
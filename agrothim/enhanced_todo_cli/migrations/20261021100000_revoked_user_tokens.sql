@@ -0,0 +1,6 @@
+-- Every token of a user issued before `issued_before` is revoked, e.g. all
+-- tokens from before a password change. One row per user, only moved forward.
+CREATE TABLE revoked_user_tokens (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    issued_before TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,13 @@
+-- Next per-user task number, taken with one UPDATE when a task is created, so
+-- concurrent creates never get the same number and numbers of deleted tasks
+-- are not handed out again
+ALTER TABLE users ADD COLUMN next_task_number INTEGER NOT NULL DEFAULT 1;
+
+UPDATE users
+SET next_task_number = numbered.max_number + 1
+FROM (
+    SELECT user_id, MAX(number) AS max_number
+    FROM tasks
+    GROUP BY user_id
+) AS numbered
+WHERE users.id = numbered.user_id;
@@ -0,0 +1,60 @@
+-- Users and tasks for local SQLite databases, matching the PostgreSQL schema.
+-- UUIDs are stored as 16-byte BLOBs and timestamps as RFC 3339 TEXT in UTC,
+-- the encodings sqlx uses for SQLite; the repositories always set timestamps.
+
+CREATE TABLE users (
+    id BLOB PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    email_verified_at TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE username_changes (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_id BLOB NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    old_username TEXT NOT NULL,
+    new_username TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_username_changes_user_id ON username_changes (user_id, changed_at DESC);
+
+-- 0: Pending, 1: In Progress, 2: Completed, 100 and up: custom statuses
+-- workspace_id has no foreign key: workspaces are PostgreSQL only
+CREATE TABLE tasks (
+    id BLOB PRIMARY KEY NOT NULL,
+    number INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT,
+    status INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER NOT NULL DEFAULT 1 CHECK (priority IN (0, 1, 2)),
+    due_date TEXT,
+    start_date TEXT,
+    completed_at TEXT,
+    estimate_minutes INTEGER CHECK (estimate_minutes > 0),
+    sort_order REAL,
+    pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    client TEXT,
+    context TEXT,
+    location TEXT,
+    latitude REAL CHECK (latitude BETWEEN -90 AND 90),
+    longitude REAL CHECK (longitude BETWEEN -180 AND 180),
+    user_id BLOB NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    workspace_id BLOB,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_tasks_user_number ON tasks (user_id, number);
+
+CREATE INDEX idx_tasks_user_status ON tasks (user_id, status);
+
+CREATE INDEX idx_tasks_user_due_date ON tasks (user_id, due_date);
+
+CREATE INDEX idx_tasks_user_context ON tasks (user_id, context);
+
+CREATE INDEX idx_tasks_workspace_id ON tasks (workspace_id) WHERE workspace_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Incremented on every write, so an update can require the version it read
+ALTER TABLE tasks
+ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
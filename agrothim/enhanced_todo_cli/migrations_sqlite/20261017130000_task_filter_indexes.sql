@@ -0,0 +1,23 @@
+-- (user_id, status) and (user_id, due_date) are in the initial schema
+CREATE INDEX idx_tasks_user_updated_at ON tasks (user_id, updated_at DESC);
+
+-- A task cannot start after it is due, as the PostgreSQL schema's
+-- start_before_due_check requires. SQLite cannot add a CHECK to an existing
+-- table, so triggers enforce it; rows written before it lose their start date.
+UPDATE tasks
+SET
+    start_date = NULL
+WHERE
+    julianday(start_date) > julianday(due_date);
+
+CREATE TRIGGER start_before_due_check_insert BEFORE INSERT ON tasks
+WHEN julianday(NEW.start_date) > julianday(NEW.due_date)
+BEGIN
+    SELECT RAISE(ABORT, 'start_before_due_check');
+END;
+
+CREATE TRIGGER start_before_due_check_update BEFORE UPDATE OF start_date, due_date ON tasks
+WHEN julianday(NEW.start_date) > julianday(NEW.due_date)
+BEGIN
+    SELECT RAISE(ABORT, 'start_before_due_check');
+END;
@@ -0,0 +1,12 @@
+-- Local-first mode: the versions each task had here and in the remote
+-- database when it was last synced. Rows outlive their task, so a local
+-- delete can still be pushed.
+CREATE TABLE task_sync_state (
+    task_id BLOB PRIMARY KEY NOT NULL,
+    user_id BLOB NOT NULL,
+    local_version INTEGER NOT NULL,
+    remote_version INTEGER NOT NULL,
+    synced_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_task_sync_state_user_id ON task_sync_state (user_id);
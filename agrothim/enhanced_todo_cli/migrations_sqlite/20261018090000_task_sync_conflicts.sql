@@ -0,0 +1,10 @@
+-- Local-first mode with `sync.conflicts = ask`: tasks changed on both sides
+-- since the last sync, which are left alone until `todo-cli sync conflicts`
+-- settles them.
+CREATE TABLE task_sync_conflicts (
+    task_id BLOB PRIMARY KEY NOT NULL,
+    user_id BLOB NOT NULL,
+    detected_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_task_sync_conflicts_user_id ON task_sync_conflicts (user_id);
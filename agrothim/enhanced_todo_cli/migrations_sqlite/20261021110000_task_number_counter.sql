@@ -0,0 +1,6 @@
+-- Next per-user task number, matching the PostgreSQL schema: numbers of
+-- deleted tasks are not handed out again
+ALTER TABLE users ADD COLUMN next_task_number INTEGER NOT NULL DEFAULT 1;
+
+UPDATE users
+SET next_task_number = (SELECT COALESCE(MAX(number), 0) + 1 FROM tasks WHERE tasks.user_id = users.id);
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Request, State},
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::api::{error::ApiError, AppState};
+use crate::error::{Diagnostic, ErrorCode};
+
+/// Authenticate the request's bearer token (a JWT or an API key) and make the
+/// resulting `AuthContext` available to the handler as an extension
+pub async fn require_bearer(State(state): State<AppState>, mut request: Request, next: Next) -> Result<Response, ApiError> {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token)
+        .ok_or_else(|| {
+            ApiError(
+                Diagnostic::new(ErrorCode::NotLoggedIn, "Missing bearer token")
+                    .with_help("Send `Authorization: Bearer <token>` with a token from POST /api/v1/auth/login or an API key"),
+            )
+        })?;
+
+    let context = state.auth_service.authenticate(token).await?;
+    request.extensions_mut().insert(Arc::new(context));
+    Ok(next.run(request).await)
+}
+
+/// The token of an `Authorization: Bearer <token>` header value
+pub(super) fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token("Bearer abc.def"), Some("abc.def"));
+        assert_eq!(bearer_token("bearer todo_123"), Some("todo_123"));
+        assert_eq!(bearer_token("Basic dXNlcjpwYXNz"), None);
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("Bearer"), None);
+    }
+}
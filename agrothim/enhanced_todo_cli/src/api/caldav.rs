@@ -0,0 +1,649 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH, LOCATION, WWW_AUTHENTICATE},
+        HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::api::{
+    auth::bearer_token,
+    calendar::{date, escape, fold, priority, timestamp},
+    error::ApiError,
+    AppState,
+};
+use crate::error::{Diagnostic, ErrorCode};
+use crate::models::{
+    calendar,
+    task::{Task, TaskFilter, TaskStatus, UpdateTaskRequest},
+    user::UserResponse,
+};
+use crate::services::{AuthContext, Scope, TaskServiceError};
+
+pub const ROOT: &str = "/dav/";
+const PRINCIPAL: &str = "/dav/principal/";
+const HOME: &str = "/dav/calendars/";
+const COLLECTION: &str = "/dav/calendars/tasks/";
+
+/// How long completed tasks stay in the collection, so it does not grow forever
+const HISTORY_DAYS: i64 = 90;
+/// Largest request body read, far more than one task or report needs
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+const ICS_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+const ALLOW: &str = "OPTIONS, GET, HEAD, PUT, PROPFIND, REPORT";
+
+/// The resources of the DAV tree: a principal with one calendar holding
+/// every task as a VTODO
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Resource {
+    Root,
+    Principal,
+    Home,
+    Collection,
+    Task(Uuid),
+}
+
+impl Resource {
+    fn parse(path: &str) -> Option<Self> {
+        match path.trim_end_matches('/') {
+            "/dav" => Some(Resource::Root),
+            "/dav/principal" => Some(Resource::Principal),
+            "/dav/calendars" => Some(Resource::Home),
+            "/dav/calendars/tasks" => Some(Resource::Collection),
+            other => {
+                let name = other.strip_prefix(COLLECTION)?.strip_suffix(".ics")?;
+                name.parse().ok().map(Resource::Task)
+            }
+        }
+    }
+
+    fn href(&self) -> String {
+        match self {
+            Resource::Root => ROOT.to_string(),
+            Resource::Principal => PRINCIPAL.to_string(),
+            Resource::Home => HOME.to_string(),
+            Resource::Collection => COLLECTION.to_string(),
+            Resource::Task(id) => task_href(id),
+        }
+    }
+}
+
+fn task_href(id: &Uuid) -> String {
+    format!("{}{}.ics", COLLECTION, id)
+}
+
+/// `/.well-known/caldav`: where clients start discovery (RFC 6764)
+pub async fn well_known() -> Response {
+    (StatusCode::MOVED_PERMANENTLY, [(LOCATION, ROOT)]).into_response()
+}
+
+/// Everything under `/dav/`: a CalDAV server (RFC 4791) so apps such as
+/// Tasks.org, Thunderbird or Apple Reminders can list tasks and tick them
+/// off. Other changes made in those apps are not saved; tasks are added
+/// and edited with `todo-cli`.
+///
+/// Clients sign in with HTTP Basic auth: any username and an API key as
+/// password (see `todo-cli calendar caldav`). A bearer header works too.
+pub async fn handle(State(state): State<AppState>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let Some(resource) = Resource::parse(parts.uri.path()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    // Clients probe for CalDAV support before they have credentials
+    if parts.method == Method::OPTIONS {
+        return options();
+    }
+
+    let auth = match authenticate(&state, &parts.headers).await {
+        Ok(auth) => auth,
+        Err(e) => return challenge(e.into_response()),
+    };
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    let result = match parts.method.as_str() {
+        "PROPFIND" => propfind(&state, &auth, resource, depth(&parts.headers)).await,
+        "REPORT" => report(&state, &auth, resource, &body).await,
+        "GET" | "HEAD" => get(&state, &auth, resource).await,
+        "PUT" => put(&state, &auth, resource, parts.headers.get(IF_MATCH), &body).await,
+        _ => Ok((StatusCode::METHOD_NOT_ALLOWED, [(axum::http::header::ALLOW, ALLOW)]).into_response()),
+    };
+    result.unwrap_or_else(IntoResponse::into_response)
+}
+
+fn options() -> Response {
+    (
+        StatusCode::OK,
+        [
+            (HeaderName::from_static("dav"), "1, 3, calendar-access"),
+            (axum::http::header::ALLOW, ALLOW),
+        ],
+    )
+        .into_response()
+}
+
+/// Ask for Basic credentials on a 401, so clients prompt for them
+fn challenge(mut response: Response) -> Response {
+    if response.status() == StatusCode::UNAUTHORIZED {
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Basic realm=\"todo-cli\""));
+    }
+    response
+}
+
+async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<AuthContext, ApiError> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(credential)
+        .ok_or_else(|| {
+            ApiError(
+                Diagnostic::new(ErrorCode::NotLoggedIn, "Missing credentials")
+                    .with_help("Sign in with the username and password printed by `todo-cli calendar caldav`"),
+            )
+        })?;
+    Ok(state.auth_service.authenticate(&token).await?)
+}
+
+/// The token of an `Authorization` header: a bearer token, or the password
+/// of Basic credentials. The username is not needed, the key names the user.
+fn credential(header: &str) -> Option<String> {
+    if let Some(token) = bearer_token(header) {
+        return Some(token.to_string());
+    }
+    let (scheme, encoded) = header.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (_, password) = decoded.split_once(':')?;
+    (!password.is_empty()).then(|| password.to_string())
+}
+
+/// Depth 0 is the resource alone; 1 and infinity both add its children
+fn depth(headers: &HeaderMap) -> u8 {
+    match headers.get("depth").and_then(|value| value.to_str().ok()) {
+        Some("0") => 0,
+        _ => 1,
+    }
+}
+
+/// Open tasks, and those completed within the last `HISTORY_DAYS`
+async fn collection_tasks(state: &AppState, user: &UserResponse) -> Result<Vec<Task>, ApiError> {
+    let cutoff = Utc::now() - Duration::days(HISTORY_DAYS);
+    let tasks = state.task_service.get_tasks(&user.id, TaskFilter::default()).await?;
+    Ok(tasks
+        .into_iter()
+        .filter(|task| !task.is_completed() || task.completed_at.is_none_or(|at| at >= cutoff))
+        .collect())
+}
+
+async fn propfind(state: &AppState, auth: &AuthContext, resource: Resource, depth: u8) -> Result<Response, ApiError> {
+    let user = auth.require(Scope::TasksRead)?;
+    let mut responses = vec![];
+
+    match resource {
+        Resource::Root | Resource::Principal | Resource::Home => {
+            responses.push(response(&resource.href(), &container_props(resource, user)));
+            if depth > 0 {
+                let children: &[Resource] = match resource {
+                    Resource::Root => &[Resource::Principal, Resource::Home],
+                    Resource::Home => &[Resource::Collection],
+                    _ => &[],
+                };
+                for child in children {
+                    let props = match child {
+                        Resource::Collection => collection_props(&collection_tasks(state, user).await?, user),
+                        _ => container_props(*child, user),
+                    };
+                    responses.push(response(&child.href(), &props));
+                }
+            }
+        }
+        Resource::Collection => {
+            let tasks = collection_tasks(state, user).await?;
+            responses.push(response(COLLECTION, &collection_props(&tasks, user)));
+            if depth > 0 {
+                responses.extend(tasks.iter().map(|task| response(&task_href(&task.id), &task_props(task, false))));
+            }
+        }
+        Resource::Task(id) => {
+            let task = state.task_service.get_task(&user.id, &id).await?;
+            responses.push(response(&task_href(&task.id), &task_props(&task, false)));
+        }
+    }
+
+    Ok(multistatus(responses))
+}
+
+/// `calendar-query` returns every task, since they are all VTODOs; the
+/// time-range filters clients add are not worth applying to a to-do list.
+/// `calendar-multiget` returns the tasks asked for.
+async fn report(state: &AppState, auth: &AuthContext, resource: Resource, body: &str) -> Result<Response, ApiError> {
+    let user = auth.require(Scope::TasksRead)?;
+    if resource != Resource::Collection {
+        return Err(unsupported("Reports are only served for the tasks calendar"));
+    }
+    let tasks = collection_tasks(state, user).await?;
+
+    let responses = match root_element(body).as_deref() {
+        Some("calendar-query") => tasks.iter().map(|task| response(&task_href(&task.id), &task_props(task, true))).collect(),
+        Some("calendar-multiget") => hrefs(body)
+            .into_iter()
+            .map(|href| {
+                let task = match Resource::parse(&href) {
+                    Some(Resource::Task(id)) => tasks.iter().find(|task| task.id == id),
+                    _ => None,
+                };
+                match task {
+                    Some(task) => response(&href, &task_props(task, true)),
+                    None => format!(
+                        "<d:response><d:href>{}</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>",
+                        xml_escape(&href)
+                    ),
+                }
+            })
+            .collect(),
+        _ => return Err(unsupported("Only calendar-query and calendar-multiget reports are supported")),
+    };
+
+    Ok(multistatus(responses))
+}
+
+async fn get(state: &AppState, auth: &AuthContext, resource: Resource) -> Result<Response, ApiError> {
+    let user = auth.require(Scope::TasksRead)?;
+    let Resource::Task(id) = resource else {
+        return Err(unsupported("Only tasks can be downloaded; browse the rest with PROPFIND"));
+    };
+    let task = state.task_service.get_task(&user.id, &id).await?;
+    Ok((
+        [(CONTENT_TYPE, ICS_CONTENT_TYPE.to_string()), (ETAG, etag(&task))],
+        render(&task),
+    )
+        .into_response())
+}
+
+/// Complete or reopen a task according to the STATUS of the uploaded VTODO
+async fn put(
+    state: &AppState,
+    auth: &AuthContext,
+    resource: Resource,
+    if_match: Option<&HeaderValue>,
+    body: &str,
+) -> Result<Response, ApiError> {
+    let user = auth.require(Scope::TasksWrite)?;
+    let Resource::Task(id) = resource else {
+        return Err(unsupported("Only tasks can be uploaded"));
+    };
+    let task = match state.task_service.get_task(&user.id, &id).await {
+        Err(TaskServiceError::TaskNotFound) => {
+            return Err(ApiError(
+                Diagnostic::new(ErrorCode::PermissionDenied, "Tasks cannot be added over CalDAV")
+                    .with_help("Add tasks with `todo-cli task add`; CalDAV apps can complete and reopen them"),
+            ))
+        }
+        result => result?,
+    };
+    // The client's copy is outdated; it refetches and tries again
+    if let Some(expected) = if_match.and_then(|value| value.to_str().ok()) {
+        if expected != "*" && expected != etag(&task) {
+            return Ok(StatusCode::PRECONDITION_FAILED.into_response());
+        }
+    }
+
+    let completed = is_completed(body).ok_or_else(|| {
+        ApiError(Diagnostic::new(ErrorCode::Validation, "Expected a calendar object with a VTODO"))
+    })?;
+    match (completed, task.is_completed()) {
+        (true, false) => {
+            state.task_service.complete_task(&user.id, &id).await?;
+        }
+        (false, true) => {
+            let updates = UpdateTaskRequest { status: Some(TaskStatus::Pending), ..Default::default() };
+            state.task_service.update_task(&user.id, &id, updates).await?;
+        }
+        _ => {}
+    }
+
+    // No ETag: what is stored differs from the upload wherever the client
+    // changed more than the status, so it must fetch the task again
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+fn unsupported(message: &str) -> ApiError {
+    ApiError(Diagnostic::new(ErrorCode::PermissionDenied, message))
+}
+
+/// Changes whenever the task does
+fn etag(task: &Task) -> String {
+    format!("\"{}-{}\"", task.version, task.updated_at.timestamp_millis())
+}
+
+/// Changes whenever a task in the collection does, so clients know to resync
+fn ctag(tasks: &[Task]) -> String {
+    let latest = tasks.iter().map(|task| task.updated_at.timestamp_millis()).max().unwrap_or(0);
+    format!("{}-{}", tasks.len(), latest)
+}
+
+fn principal_props() -> String {
+    format!(
+        "<d:current-user-principal><d:href>{principal}</d:href></d:current-user-principal>\
+         <d:principal-URL><d:href>{principal}</d:href></d:principal-URL>\
+         <c:calendar-home-set><d:href>{home}</d:href></c:calendar-home-set>",
+        principal = PRINCIPAL,
+        home = HOME,
+    )
+}
+
+fn container_props(resource: Resource, user: &UserResponse) -> String {
+    let resource_type = match resource {
+        Resource::Principal => "<d:collection/><d:principal/>",
+        _ => "<d:collection/>",
+    };
+    format!(
+        "<d:resourcetype>{}</d:resourcetype><d:displayname>{}</d:displayname>{}",
+        resource_type,
+        xml_escape(&user.username),
+        principal_props()
+    )
+}
+
+fn collection_props(tasks: &[Task], user: &UserResponse) -> String {
+    format!(
+        "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+         <d:displayname>Tasks of {}</d:displayname>\
+         <c:supported-calendar-component-set><c:comp name=\"VTODO\"/></c:supported-calendar-component-set>\
+         <d:supported-report-set>\
+         <d:supported-report><d:report><c:calendar-query/></d:report></d:supported-report>\
+         <d:supported-report><d:report><c:calendar-multiget/></d:report></d:supported-report>\
+         </d:supported-report-set>\
+         <d:current-user-privilege-set>\
+         <d:privilege><d:read/></d:privilege><d:privilege><d:write-content/></d:privilege>\
+         </d:current-user-privilege-set>\
+         <cs:getctag>{}</cs:getctag>{}",
+        xml_escape(&user.username),
+        ctag(tasks),
+        principal_props()
+    )
+}
+
+fn task_props(task: &Task, with_data: bool) -> String {
+    let mut props = format!(
+        "<d:resourcetype/><d:getetag>{}</d:getetag><d:getcontenttype>{}; component=vtodo</d:getcontenttype>",
+        xml_escape(&etag(task)),
+        ICS_CONTENT_TYPE
+    );
+    if with_data {
+        props.push_str(&format!("<c:calendar-data>{}</c:calendar-data>", xml_escape(&render(task))));
+    }
+    props
+}
+
+fn response(href: &str, props: &str) -> String {
+    format!(
+        "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop>\
+         <d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+        xml_escape(href),
+        props
+    )
+}
+
+fn multistatus(responses: Vec<String>) -> Response {
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\" xmlns:cs=\"http://calendarserver.org/ns/\">{}</d:multistatus>",
+        responses.concat()
+    );
+    (StatusCode::MULTI_STATUS, [(CONTENT_TYPE, XML_CONTENT_TYPE)], Body::from(body)).into_response()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Local name of the first element of an XML body, e.g. `calendar-query`
+fn root_element(body: &str) -> Option<String> {
+    let mut rest = body;
+    loop {
+        let start = rest.find('<')?;
+        rest = &rest[start + 1..];
+        if rest.starts_with('?') || rest.starts_with('!') {
+            continue;
+        }
+        let name: String = rest.chars().take_while(|c| !c.is_whitespace() && *c != '>' && *c != '/').collect();
+        return Some(name.rsplit(':').next().unwrap_or_default().to_string());
+    }
+}
+
+/// The values of the `href` elements of an XML body, whatever their prefix
+fn hrefs(body: &str) -> Vec<String> {
+    lazy_static::lazy_static! {
+        static ref HREF: regex::Regex = regex::Regex::new(r"<(?:[\w-]+:)?href(?:\s[^>]*)?>([^<]*)</").unwrap();
+    }
+    HREF.captures_iter(body)
+        .map(|captures| {
+            let href = captures[1].trim().replace("&amp;", "&");
+            // Some clients send absolute URLs
+            match href.find("/dav/") {
+                Some(start) => href[start..].to_string(),
+                None => href,
+            }
+        })
+        .collect()
+}
+
+/// One task as a calendar object with a single VTODO
+fn render(task: &Task) -> String {
+    let status = match task.status {
+        TaskStatus::Completed => "COMPLETED",
+        TaskStatus::InProgress => "IN-PROCESS",
+        TaskStatus::Pending | TaskStatus::Custom(_) => "NEEDS-ACTION",
+    };
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//todo-cli//Tasks//EN".to_string(),
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}@todo-cli", task.id),
+        format!("DTSTAMP:{}", timestamp(&task.updated_at)),
+        format!("CREATED:{}", timestamp(&task.created_at)),
+        format!("LAST-MODIFIED:{}", timestamp(&task.updated_at)),
+        format!("SUMMARY:{}", escape(&task.title)),
+    ];
+    if let Some(description) = &task.description {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    if let Some(context) = &task.context {
+        lines.push(format!("CATEGORIES:{}", escape(context.trim_start_matches('@'))));
+    }
+    lines.push(format!("PRIORITY:{}", priority(task.priority)));
+    // DTSTART and DUE must share a value type, so a start date makes both date-times
+    match (task.start_date, task.due_date, task.due_day()) {
+        (Some(start_date), due_date, _) => {
+            lines.push(format!("DTSTART:{}", timestamp(&start_date)));
+            if let Some(due_date) = due_date {
+                lines.push(format!("DUE:{}", timestamp(&due_date)));
+            }
+        }
+        (None, Some(due_date), Some(due_day)) if due_date == calendar::end_of_day(due_day) => {
+            lines.push(format!("DUE;VALUE=DATE:{}", date(due_day)));
+        }
+        (None, Some(due_date), _) => lines.push(format!("DUE:{}", timestamp(&due_date))),
+        (None, None, _) => {}
+    }
+    lines.push(format!("STATUS:{}", status));
+    if let (true, Some(completed_at)) = (task.is_completed(), task.completed_at) {
+        lines.push(format!("COMPLETED:{}", timestamp(&completed_at)));
+        lines.push("PERCENT-COMPLETE:100".to_string());
+    }
+    lines.push("END:VTODO".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Whether the VTODO of an uploaded calendar object is done: its STATUS,
+/// or a COMPLETED date for clients that send no STATUS. `None` without a VTODO.
+fn is_completed(ics: &str) -> Option<bool> {
+    let unfolded = ics.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "");
+    let mut in_todo = false;
+    let mut found = false;
+    let mut status = None;
+    let mut has_completed_date = false;
+
+    for line in unfolded.lines() {
+        let (name, value) = line.split_once(':').unwrap_or((line, ""));
+        let name = name.split(';').next().unwrap_or_default().to_ascii_uppercase();
+        match (name.as_str(), value.trim()) {
+            ("BEGIN", "VTODO") => (in_todo, found) = (true, true),
+            ("END", "VTODO") => in_todo = false,
+            ("STATUS", value) if in_todo => status = Some(value.to_ascii_uppercase()),
+            ("COMPLETED", _) if in_todo => has_completed_date = true,
+            _ => {}
+        }
+    }
+
+    found.then(|| match status.as_deref() {
+        Some(status) => status == "COMPLETED",
+        None => has_completed_date,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::database::repositories::{InMemoryTaskRepository, InMemoryUserRepository};
+    use crate::database::{Database, PoolConfig};
+    use crate::models::{task::StoreTaskRequest, user::StoreUserRequest};
+    use crate::services::{AuthService, EventBus, TaskService, UserService, API_KEY_PREFIX};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_resources() {
+        assert_eq!(Resource::parse("/dav"), Some(Resource::Root));
+        assert_eq!(Resource::parse("/dav/calendars/tasks/"), Some(Resource::Collection));
+        let id = Uuid::new_v4();
+        assert_eq!(Resource::parse(&task_href(&id)), Some(Resource::Task(id)));
+        assert_eq!(Resource::parse("/dav/calendars/tasks/not-a-task.ics"), None);
+        assert_eq!(Resource::parse("/dav/calendars/other/"), None);
+    }
+
+    #[test]
+    fn test_credential() {
+        let key = format!("{}abc123", API_KEY_PREFIX);
+        let basic = format!("Basic {}", STANDARD.encode(format!("phone:{}", key)));
+        assert_eq!(credential(&basic).as_deref(), Some(key.as_str()));
+        assert_eq!(credential(&format!("Bearer {}", key)).as_deref(), Some(key.as_str()));
+        assert_eq!(credential(&format!("Basic {}", STANDARD.encode("phone:"))), None);
+        assert_eq!(credential("Basic not-base64!"), None);
+    }
+
+    #[test]
+    fn test_is_completed() {
+        let todo = |props: &str| format!("BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nSUMMARY:Pay rent\r\n{}END:VTODO\r\nEND:VCALENDAR\r\n", props);
+        assert_eq!(is_completed(&todo("STATUS:COMPLETED\r\nCOMPLETED:20261020T090000Z\r\n")), Some(true));
+        assert_eq!(is_completed(&todo("STATUS:NEEDS-ACTION\r\n")), Some(false));
+        assert_eq!(is_completed(&todo("COMPLETED:20261020T090000Z\r\n")), Some(true));
+        assert_eq!(is_completed(&todo("STA\r\n TUS:completed\r\n")), Some(true));
+        assert_eq!(is_completed(&todo("")), Some(false));
+        assert_eq!(is_completed("BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n"), None);
+    }
+
+    #[test]
+    fn test_report_bodies() {
+        let multiget = r#"<?xml version="1.0"?>
+            <C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+              <D:prop><D:getetag/><C:calendar-data/></D:prop>
+              <D:href>/dav/calendars/tasks/a.ics</D:href>
+              <href xmlns="DAV:">https://todo.example.com/dav/calendars/tasks/b.ics</href>
+            </C:calendar-multiget>"#;
+        assert_eq!(root_element(multiget).as_deref(), Some("calendar-multiget"));
+        assert_eq!(hrefs(multiget), vec!["/dav/calendars/tasks/a.ics", "/dav/calendars/tasks/b.ics"]);
+        assert_eq!(root_element("<calendar-query xmlns=\"urn:ietf:params:xml:ns:caldav\"/>").as_deref(), Some("calendar-query"));
+    }
+
+    /// `todo-cli serve` on in-memory repositories, listening on a free port
+    async fn serve(state: AppState) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api::router(state)).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_complete_and_reopen_over_caldav() {
+        let dir = TempDir::new().unwrap();
+        let user_service = Arc::new(UserService::new(Arc::new(InMemoryUserRepository::new())));
+        let auth_service = Arc::new(AuthService::new(user_service.clone(), "test-secret", Some(dir.path().to_path_buf())).unwrap());
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let state = AppState {
+            auth_service: auth_service.clone(),
+            user_service: user_service.clone(),
+            task_service: task_service.clone(),
+            event_bus: EventBus::default(),
+            database: Database::from_url_with("memory://", &PoolConfig::default()).await.unwrap(),
+            metrics: Default::default(),
+            migrations_dir: None,
+            timezone: Default::default(),
+        };
+        let url = serve(state).await;
+
+        let request = StoreUserRequest::new("phone".to_string(), "phone@example.com".to_string(), "kestrel-canyon-42".to_string());
+        let user = user_service.register(request.unwrap()).await.unwrap();
+        let token = auth_service.login_remote("phone", "kestrel-canyon-42", "test").await.unwrap().token;
+        let request = StoreTaskRequest { title: "Pay rent".to_string(), ..Default::default() };
+        let task = task_service.create_task(&user.id, request).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let dav = |method: &str, path: &str| {
+            client
+                .request(reqwest::Method::from_bytes(method.as_bytes()).unwrap(), format!("{}{}", url, path))
+                .basic_auth("phone", Some(&token))
+        };
+
+        let unauthenticated = client.request(reqwest::Method::from_bytes(b"PROPFIND").unwrap(), format!("{}{}", url, COLLECTION));
+        let response = unauthenticated.send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().contains_key(WWW_AUTHENTICATE));
+
+        let response = dav("PROPFIND", COLLECTION).header("Depth", "1").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = response.text().await.unwrap();
+        assert!(body.contains("<c:comp name=\"VTODO\"/>"));
+        assert!(body.contains(&task_href(&task.id)));
+
+        let query = "<c:calendar-query xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\"/>";
+        let body = dav("REPORT", COLLECTION).body(query).send().await.unwrap().text().await.unwrap();
+        assert!(body.contains("SUMMARY:Pay rent"));
+        assert!(body.contains("STATUS:NEEDS-ACTION"));
+
+        let response = dav("GET", &task_href(&task.id)).send().await.unwrap();
+        let etag = response.headers()[ETAG].to_str().unwrap().to_string();
+        let ics = response.text().await.unwrap();
+        let done = ics.replace("STATUS:NEEDS-ACTION", "STATUS:COMPLETED");
+        let response = dav("PUT", &task_href(&task.id)).header("If-Match", &etag).body(done.clone()).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(task_service.get_task(&user.id, &task.id).await.unwrap().is_completed());
+
+        // The stored task changed, so the old ETag no longer matches
+        let response = dav("PUT", &task_href(&task.id)).header("If-Match", &etag).body(ics.clone()).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        let response = dav("PUT", &task_href(&task.id)).body(ics).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!task_service.get_task(&user.id, &task.id).await.unwrap().is_completed());
+
+        let response = dav("PUT", &task_href(&Uuid::new_v4())).body(done).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,282 @@
+use axum::{
+    extract::{Query, State},
+    http::{
+        header::{AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE},
+        HeaderMap,
+    },
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+
+use crate::api::{auth::bearer_token, error::ApiError, AppState};
+use crate::error::{Diagnostic, ErrorCode};
+use crate::models::{
+    calendar,
+    task::{Task, TaskFilter, TaskPriority, TaskStatus},
+};
+use crate::services::Scope;
+
+/// How far back the feed reaches, so old tasks do not grow it forever
+const HISTORY_DAYS: i64 = 90;
+/// How often subscribed calendars are asked to refetch the feed
+const REFRESH_INTERVAL: &str = "PT1H";
+/// Longest content line iCalendar allows, in bytes, before folding
+const MAX_LINE_BYTES: usize = 75;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FeedQuery {
+    /// Calendar apps cannot send headers, so the token may come in the URL
+    pub token: Option<String>,
+}
+
+/// `GET /api/v1/calendar.ics`: the user's tasks with due dates as all-day
+/// events, for subscribing from Google Calendar, Apple Calendar or Outlook.
+/// Authenticated with `?token=` (see `todo-cli calendar url`) or a bearer
+/// header, holding the `calendar:feed` scope: the URL ends up in calendar
+/// apps, proxies and server logs, so its key must not reach the rest of the API.
+pub async fn feed(
+    State(state): State<AppState>,
+    Query(query): Query<FeedQuery>,
+    headers: HeaderMap,
+) -> Result<([(axum::http::HeaderName, &'static str); 2], String), ApiError> {
+    let token = query
+        .token
+        .as_deref()
+        .or_else(|| headers.get(AUTHORIZATION).and_then(|value| value.to_str().ok()).and_then(bearer_token))
+        .ok_or_else(|| {
+            ApiError(
+                Diagnostic::new(ErrorCode::NotLoggedIn, "Missing calendar token")
+                    .with_help("Subscribe to the URL printed by `todo-cli calendar url`"),
+            )
+        })?;
+    let auth = state.auth_service.authenticate(token).await?;
+    let user = auth.require(Scope::CalendarFeed)?;
+
+    let filter = TaskFilter {
+        due_after: Some(calendar::start_of_day(calendar::today() - Duration::days(HISTORY_DAYS))),
+        ..Default::default()
+    };
+    let tasks = state.task_service.get_tasks(&user.id, filter).await?;
+    let body = render(&tasks, &format!("Tasks of {}", user.username));
+
+    Ok((
+        [(CONTENT_TYPE, "text/calendar; charset=utf-8"), (CACHE_CONTROL, "private, no-cache")],
+        body,
+    ))
+}
+
+/// An iCalendar document with an all-day event on the local due day of each
+/// task that has one. Completed tasks stay on the calendar, ticked off.
+pub fn render(tasks: &[Task], name: &str) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//todo-cli//Tasks//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{}", REFRESH_INTERVAL),
+        format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL),
+    ];
+
+    for task in tasks {
+        let Some(due_day) = task.due_day() else {
+            continue;
+        };
+        let summary = match task.status {
+            TaskStatus::Completed => format!("✓ {}", task.title),
+            _ => task.title.clone(),
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@todo-cli", task.id));
+        lines.push(format!("DTSTAMP:{}", timestamp(&task.updated_at)));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", date(due_day)));
+        lines.push(format!("DTEND;VALUE=DATE:{}", date(due_day + Duration::days(1))));
+        lines.push(format!("SUMMARY:{}", escape(&summary)));
+        if let Some(description) = &task.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        if let Some(context) = &task.context {
+            lines.push(format!("CATEGORIES:{}", escape(context.trim_start_matches('@'))));
+        }
+        lines.push(format!("PRIORITY:{}", priority(task.priority)));
+        // A task does not make its day busy
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// iCalendar priorities run from 1 (highest) to 9 (lowest)
+pub(super) fn priority(priority: TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::High => 1,
+        TaskPriority::Medium => 5,
+        TaskPriority::Low => 9,
+    }
+}
+
+pub(super) fn date(day: NaiveDate) -> String {
+    day.format("%Y%m%d").to_string()
+}
+
+pub(super) fn timestamp(at: &DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a text value: backslashes, separators and line breaks
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// End a content line with CRLF, folding it onto continuation lines that
+/// start with a space once it gets too long, without splitting a character
+pub(super) fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_BYTES {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn task(title: &str, due_date: Option<DateTime<Utc>>) -> Task {
+        let created_at = Utc.with_ymd_and_hms(2026, 10, 1, 9, 0, 0).unwrap();
+        Task {
+            id: Uuid::new_v4(),
+            number: 1,
+            title: title.to_string(),
+            description: None,
+            status: TaskStatus::Pending,
+            priority: TaskPriority::Medium,
+            due_date,
+            start_date: None,
+            completed_at: None,
+            estimate_minutes: None,
+            sort_order: None,
+            pinned: false,
+            client: None,
+            context: None,
+            location: None,
+            latitude: None,
+            longitude: None,
+            user_id: Uuid::new_v4(),
+            workspace_id: None,
+            created_at,
+            updated_at: created_at,
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn test_render_feed() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap();
+        let mut report = task("Send report; v2, final", Some(calendar::end_of_day(day)));
+        report.description = Some("Attach the numbers\nand charts".to_string());
+        report.context = Some("@office".to_string());
+        report.priority = TaskPriority::High;
+        let mut paid = task("Pay rent", Some(calendar::end_of_day(day)));
+        paid.status = TaskStatus::Completed;
+        let someday = task("Learn Rust", None);
+
+        let feed = render(&[report.clone(), paid, someday], "Tasks of demo");
+        assert!(feed.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(feed.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(feed.matches("BEGIN:VEVENT").count(), 2);
+        assert!(feed.contains(&format!("UID:{}@todo-cli\r\n", report.id)));
+        assert!(feed.contains("DTSTART;VALUE=DATE:20261020\r\nDTEND;VALUE=DATE:20261021\r\n"));
+        assert!(feed.contains("SUMMARY:Send report\\; v2\\, final\r\n"));
+        assert!(feed.contains("DESCRIPTION:Attach the numbers\\nand charts\r\n"));
+        assert!(feed.contains("CATEGORIES:office\r\n"));
+        assert!(feed.contains("PRIORITY:1\r\n"));
+        assert!(feed.contains("SUMMARY:✓ Pay rent\r\n"));
+        assert!(!feed.contains("Learn Rust"));
+    }
+
+    #[test]
+    fn test_fold_long_lines() {
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold(&line);
+        let parts: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(parts.len(), 2);
+        assert!(parts.iter().all(|part| part.len() <= MAX_LINE_BYTES));
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(parts.concat().replacen(' ', "", 1), line);
+        assert_eq!(fold("VERSION:2.0"), "VERSION:2.0\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_feed_key_reads_the_feed_and_nothing_else() {
+        use crate::api::{self, AppState};
+        use crate::database::repositories::{InMemoryTaskRepository, InMemoryUserRepository};
+        use crate::database::{Database, PoolConfig};
+        use crate::models::user::StoreUserRequest;
+        use crate::services::{AuthService, EventBus, TaskService, UserService};
+        use std::sync::Arc;
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let user_service = Arc::new(UserService::new(Arc::new(InMemoryUserRepository::new())));
+        let auth_service = Arc::new(AuthService::new(user_service.clone(), "test-secret", Some(dir.path().to_path_buf())).unwrap());
+        let state = AppState {
+            auth_service: auth_service.clone(),
+            user_service: user_service.clone(),
+            task_service: Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new()))),
+            event_bus: EventBus::default(),
+            database: Database::from_url_with("memory://", &PoolConfig::default()).await.unwrap(),
+            metrics: Default::default(),
+            migrations_dir: None,
+            timezone: Default::default(),
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v1", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api::router(state)).await });
+
+        let request = StoreUserRequest::new("subscriber".to_string(), "sub@example.com".to_string(), "kestrel-canyon-42".to_string());
+        user_service.register(request.unwrap()).await.unwrap();
+        auth_service.login("subscriber", "kestrel-canyon-42").await.unwrap();
+        let hour = chrono::Duration::hours(1);
+        let feed_key = auth_service.issue_token(&[Scope::CalendarFeed], hour).await.unwrap().token;
+        let read_key = auth_service.issue_token(&[Scope::TasksRead], hour).await.unwrap().token;
+
+        let client = reqwest::Client::new();
+        let feed = |key: &str| client.get(format!("{}/calendar.ics", url)).query(&[("token", key)]).send();
+        let response = feed(&feed_key).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert!(response.text().await.unwrap().starts_with("BEGIN:VCALENDAR"));
+        // General read keys do not open the feed...
+        assert_eq!(feed(&read_key).await.unwrap().status(), reqwest::StatusCode::FORBIDDEN);
+
+        // ...and the feed key opens nothing else
+        for path in ["tasks", "me", "stats"] {
+            let response = client.get(format!("{}/{}", url, path)).bearer_auth(&feed_key).send().await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN, "GET /{}", path);
+        }
+    }
+}
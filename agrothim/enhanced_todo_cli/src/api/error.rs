@@ -0,0 +1,127 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use tracing::error;
+
+use crate::error::{Diagnose, Diagnostic, ErrorCode};
+
+/// An error turned into a JSON response: the diagnostic's code, message,
+/// labels and hint, with an HTTP status derived from the code
+#[derive(Debug)]
+pub struct ApiError(pub Diagnostic);
+
+impl<E: Diagnose> From<E> for ApiError {
+    fn from(error: E) -> Self {
+        ApiError(error.diagnose())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = status_for(self.0.code);
+        (status, Json(json!({ "error": error_json(self.0) }))).into_response()
+    }
+}
+
+/// The `error` object of a response body: code, message, labels and hint.
+/// Server-side failures are logged, not described to the client.
+pub fn error_json(diagnostic: Diagnostic) -> Value {
+    if status_for(diagnostic.code).is_server_error() {
+        error!("API request failed: [{}] {}", diagnostic.code, diagnostic.message);
+        return json!({ "code": diagnostic.code.as_str(), "message": "Internal server error" });
+    }
+
+    let labels: serde_json::Map<String, Value> = diagnostic
+        .labels
+        .into_iter()
+        .map(|(name, value)| (name, value.into()))
+        .collect();
+    json!({
+        "code": diagnostic.code.as_str(),
+        "message": diagnostic.message,
+        "labels": labels,
+        "help": diagnostic.help,
+    })
+}
+
+/// HTTP status for an error code
+pub fn status_for(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::Validation
+        | ErrorCode::AmbiguousTaskId
+        | ErrorCode::UnknownStatus
+        | ErrorCode::UnknownSetting
+        | ErrorCode::InvalidSetting
+        | ErrorCode::UnknownFormat
+        | ErrorCode::InvalidFormatData
+        | ErrorCode::InvalidFields
+        | ErrorCode::AmbiguousWorkspace => StatusCode::BAD_REQUEST,
+        ErrorCode::AuthenticationFailed
+        | ErrorCode::NotLoggedIn
+        | ErrorCode::SessionExpired
+        | ErrorCode::InvalidToken => StatusCode::UNAUTHORIZED,
+        ErrorCode::PermissionDenied
+        | ErrorCode::EmailNotVerified
+        | ErrorCode::AccountSuspended
+        | ErrorCode::TaskAccessDenied
+        | ErrorCode::NotWorkspaceOwner => StatusCode::FORBIDDEN,
+        ErrorCode::ApiKeyNotFound
+        | ErrorCode::UnknownSession
+        | ErrorCode::UserNotFound
+        | ErrorCode::TaskNotFound
+        | ErrorCode::ScheduleNotFound
+        | ErrorCode::WebhookNotFound
+        | ErrorCode::WorkspaceNotFound
+        | ErrorCode::InvitationNotFound => StatusCode::NOT_FOUND,
+        ErrorCode::UsernameTaken
+        | ErrorCode::EmailTaken
+        | ErrorCode::UsernameChangeTooSoon
+        | ErrorCode::InvalidStatusTransition
+        | ErrorCode::StatusInUse
+        | ErrorCode::StatusExists
+        | ErrorCode::TaskChanged
+        | ErrorCode::TimerAlreadyRunning
+        | ErrorCode::NoRunningTimer
+        | ErrorCode::WorkspaceExists
+        | ErrorCode::AlreadyWorkspaceMember
+        | ErrorCode::InvitationNoLongerValid
+        | ErrorCode::DaemonAlreadyRunning
+        | ErrorCode::DaemonNotRunning => StatusCode::CONFLICT,
+        ErrorCode::BulkPartialFailure => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorCode::ExternalLoginFailed
+        | ErrorCode::MailDeliveryFailed
+        | ErrorCode::NotificationFailed
+        | ErrorCode::GitHubRequestFailed => StatusCode::BAD_GATEWAY,
+        ErrorCode::Internal | ErrorCode::Database | ErrorCode::Io | ErrorCode::GitCommandFailed => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_code_has_a_client_or_server_error_status() {
+        for code in ErrorCode::ALL {
+            let status = status_for(code);
+            assert!(status.is_client_error() || status.is_server_error(), "{} maps to {}", code, status);
+        }
+        assert_eq!(status_for(ErrorCode::TaskNotFound), StatusCode::NOT_FOUND);
+        assert_eq!(status_for(ErrorCode::InvalidToken), StatusCode::UNAUTHORIZED);
+        assert_eq!(status_for(ErrorCode::PermissionDenied), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_server_errors_hide_details() {
+        let response = ApiError(Diagnostic::new(ErrorCode::Database, "Database error: connection refused")).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let response = ApiError(Diagnostic::new(ErrorCode::TaskNotFound, "Task not found")).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
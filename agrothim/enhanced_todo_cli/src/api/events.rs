@@ -0,0 +1,48 @@
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use std::sync::Arc;
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+use uuid::Uuid;
+
+use crate::api::{error::ApiError, AppState};
+use crate::models::task_change::TaskChange;
+use crate::services::{AuthContext, Scope};
+
+/// Event sent when the client fell behind and missed changes; it should
+/// refetch its task list
+const RESYNC_EVENT: &str = "resync";
+
+/// Server-sent events stream of changes to the tasks the user can see. Each
+/// event is named after the change kind and carries the change as JSON.
+pub async fn stream(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let user = auth.require(Scope::TasksRead)?;
+    let user_id = user.id;
+
+    let changes = BroadcastStream::new(state.event_bus.subscribe())
+        .filter_map(move |received| to_event(received, &user_id));
+    Ok(Sse::new(changes).keep_alive(KeepAlive::default()))
+}
+
+fn to_event(
+    received: Result<TaskChange, BroadcastStreamRecvError>,
+    user_id: &Uuid,
+) -> Option<Result<Event, axum::Error>> {
+    match received {
+        Ok(change) if change.is_for(user_id) => {
+            Some(Event::default().event(change.kind.as_str()).json_data(&change))
+        }
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            Some(Ok(Event::default().event(RESYNC_EVENT).data(missed.to_string())))
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::AppendHeaders,
+    Extension, Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::api::{
+    error::{error_json, status_for, ApiError},
+    AppState,
+};
+use crate::error::{Diagnose, Diagnostic, ErrorCode};
+use crate::models::{
+    calendar,
+    status::StatusWorkflow,
+    task::{StatisticsFilter, StoreTaskRequest, Task, TaskFilter, TaskStatistics, UpdateTaskRequest},
+    user::{StoreUserRequest, UserResponse},
+};
+use crate::services::{AuthContext, LoginResponse, Scope, TaskServiceError};
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// Name of the device session created by an API login
+const API_SESSION_NAME: &str = "REST API";
+
+/// Header carrying the cursor of the next page of a limited task list
+pub const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+/// Largest page the task list serves
+const MAX_PAGE_SIZE: usize = 200;
+/// Most items a bulk request may carry
+const MAX_BULK_ITEMS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    /// Username or email
+    pub identifier: String,
+    pub password: String,
+}
+
+pub async fn register(
+    State(state): State<AppState>,
+    Json(body): Json<StoreUserRequest>,
+) -> ApiResult<(StatusCode, Json<UserResponse>)> {
+    body.validate().map_err(|e| {
+        ApiError(Diagnostic::new(
+            ErrorCode::Validation,
+            format!("Registration validation failed: {}", e),
+        ))
+    })?;
+    let request = StoreUserRequest::new(body.username, body.email, body.password)
+        .map_err(|e| ApiError(Diagnostic::new(ErrorCode::Internal, e.to_string())))?;
+
+    let user = state.user_service.register(request).await?;
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+pub async fn login(State(state): State<AppState>, Json(body): Json<LoginRequest>) -> ApiResult<Json<LoginResponse>> {
+    let response = state
+        .auth_service
+        .login_remote(&body.identifier, &body.password, API_SESSION_NAME)
+        .await?;
+    Ok(Json(response))
+}
+
+pub async fn me(Extension(auth): Extension<Arc<AuthContext>>) -> ApiResult<Json<UserResponse>> {
+    let user = auth.require(Scope::TasksRead)?;
+    Ok(Json(user.clone()))
+}
+
+/// Query parameters of `GET /api/v1/tasks`. Values are parsed by hand so bad
+/// input gets the regular JSON error body instead of axum's plain-text rejection.
+#[derive(Debug, Default, Deserialize)]
+pub struct TaskListQuery {
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub search: Option<String>,
+    /// RFC 3339 timestamp or YYYY-MM-DD (start of that local day)
+    pub due_before: Option<String>,
+    pub due_after: Option<String>,
+    /// GTD context such as "@home"
+    pub context: Option<String>,
+    /// Rejected: tasks have no tags, clients filter by `context` instead
+    pub tag: Option<String>,
+    /// Field to sort by, prefixed with '-' for descending
+    pub sort: Option<String>,
+    pub limit: Option<String>,
+    /// Value of the previous page's X-Next-Cursor header
+    pub cursor: Option<String>,
+}
+
+impl TaskListQuery {
+    fn to_filter(&self, workflow: &StatusWorkflow) -> ApiResult<TaskFilter> {
+        let mut filter = TaskFilter::new();
+
+        if let Some(status) = &self.status {
+            filter.status = Some(workflow.parse(status).ok_or_else(|| invalid_query(format!("Unknown status '{}'", status)))?);
+        }
+        if let Some(priority) = &self.priority {
+            filter.priority = Some(priority.parse().map_err(invalid_query)?);
+        }
+        filter.search_term = self.search.clone();
+        if self.tag.is_some() {
+            return Err(invalid_query("Tasks have no tags; filter by context, e.g. context=@home"));
+        }
+        filter.context = self.context.clone();
+        filter.due_before = self.due_before.as_deref().map(|value| parse_instant("due_before", value)).transpose()?;
+        filter.due_after = self.due_after.as_deref().map(|value| parse_instant("due_after", value)).transpose()?;
+        if let Some(sort) = &self.sort {
+            filter.sort = Some(sort.parse().map_err(invalid_query)?);
+        }
+        if let Some(limit) = &self.limit {
+            match limit.parse::<usize>() {
+                Ok(limit @ 1..=MAX_PAGE_SIZE) => filter.limit = Some(limit),
+                _ => return Err(invalid_query(format!("limit must be a number from 1 to {}", MAX_PAGE_SIZE))),
+            }
+        }
+        if let Some(cursor) = &self.cursor {
+            filter.after = Some(Uuid::parse_str(cursor).map_err(|_| invalid_query(format!("Invalid cursor '{}'", cursor)))?);
+        }
+
+        Ok(filter)
+    }
+}
+
+/// Query parameters of `GET /api/v1/stats`. Each bound is an RFC 3339
+/// timestamp or YYYY-MM-DD; the `_before` bounds are exclusive.
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsQuery {
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub completed_after: Option<String>,
+    pub completed_before: Option<String>,
+}
+
+impl StatsQuery {
+    fn to_filter(&self) -> ApiResult<StatisticsFilter> {
+        let parse = |name: &str, value: &Option<String>| value.as_deref().map(|value| parse_instant(name, value)).transpose();
+        Ok(StatisticsFilter {
+            created_after: parse("created_after", &self.created_after)?,
+            created_before: parse("created_before", &self.created_before)?,
+            completed_after: parse("completed_after", &self.completed_after)?,
+            completed_before: parse("completed_before", &self.completed_before)?,
+        })
+    }
+}
+
+fn parse_instant(name: &str, value: &str) -> ApiResult<DateTime<Utc>> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+        return Ok(instant.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(calendar::start_of_day)
+        .map_err(|_| invalid_query(format!("{} must be an RFC 3339 timestamp or a YYYY-MM-DD date", name)))
+}
+
+fn invalid_query(message: impl Into<String>) -> ApiError {
+    ApiError(Diagnostic::new(ErrorCode::Validation, message.into()))
+}
+
+pub async fn list_tasks(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Query(query): Query<TaskListQuery>,
+) -> ApiResult<(AppendHeaders<Option<(&'static str, String)>>, Json<Vec<Task>>)> {
+    let user = auth.require(Scope::TasksRead)?;
+    let workflow = state.task_service.get_workflow(&user.id).await?;
+    let mut filter = query.to_filter(&workflow)?;
+
+    // Fetch one extra task to learn whether there is a next page
+    let limit = filter.limit;
+    filter.limit = limit.map(|limit| limit + 1);
+    let mut tasks = state.task_service.get_tasks(&user.id, filter).await?;
+
+    let next_cursor = match limit {
+        Some(limit) if tasks.len() > limit => {
+            tasks.truncate(limit);
+            tasks.last().map(|task| (NEXT_CURSOR_HEADER, task.id.to_string()))
+        }
+        _ => None,
+    };
+    Ok((AppendHeaders(next_cursor), Json(tasks)))
+}
+
+pub async fn create_task(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Json(request): Json<StoreTaskRequest>,
+) -> ApiResult<(StatusCode, Json<Task>)> {
+    let user = auth.require(Scope::TasksWrite)?;
+    let task = state.task_service.create_task(&user.id, request).await?;
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+pub async fn get_task(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Task>> {
+    let user = auth.require(Scope::TasksRead)?;
+    let task = state.task_service.get_task(&user.id, &id).await?;
+    Ok(Json(task))
+}
+
+pub async fn update_task(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Path(id): Path<Uuid>,
+    Json(updates): Json<UpdateTaskRequest>,
+) -> ApiResult<Json<Task>> {
+    let user = auth.require(Scope::TasksWrite)?;
+    let task = state.task_service.update_task(&user.id, &id, updates).await?;
+    Ok(Json(task))
+}
+
+pub async fn delete_task(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<StatusCode> {
+    let user = auth.require(Scope::TasksWrite)?;
+    if state.task_service.delete_task(&user.id, &id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(TaskServiceError::TaskNotFound.into())
+    }
+}
+
+pub async fn complete_task(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<Task>> {
+    let user = auth.require(Scope::TasksWrite)?;
+    let task = state.task_service.complete_task(&user.id, &id).await?;
+    Ok(Json(task))
+}
+
+/// Body of `PATCH /api/v1/tasks/bulk-status`
+#[derive(Debug, Deserialize)]
+pub struct BulkStatusRequest {
+    pub ids: Vec<Uuid>,
+    pub status: String,
+}
+
+/// Outcome of one item of a bulk request, at the index it had in the request
+#[derive(Debug, Serialize)]
+pub struct BulkItemResult {
+    pub index: usize,
+    /// HTTP status the item would have had as a single request
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<Task>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+}
+
+/// Response of the bulk endpoints; sent with 200 even when some items failed
+#[derive(Debug, Serialize)]
+pub struct BulkResponse {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+impl BulkResponse {
+    fn new<E: Diagnose>(results: Vec<Result<Task, E>>, success: StatusCode) -> Self {
+        let results: Vec<BulkItemResult> = results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Ok(task) => BulkItemResult { index, status: success.as_u16(), task: Some(task), error: None },
+                Err(e) => {
+                    let diagnostic = e.diagnose();
+                    BulkItemResult {
+                        index,
+                        status: status_for(diagnostic.code).as_u16(),
+                        task: None,
+                        error: Some(error_json(diagnostic)),
+                    }
+                }
+            })
+            .collect();
+        let succeeded = results.iter().filter(|result| result.task.is_some()).count();
+        Self { succeeded, failed: results.len() - succeeded, results }
+    }
+}
+
+fn check_bulk_size(count: usize) -> ApiResult<()> {
+    if count == 0 || count > MAX_BULK_ITEMS {
+        return Err(ApiError(Diagnostic::new(
+            ErrorCode::Validation,
+            format!("A bulk request must carry from 1 to {} items", MAX_BULK_ITEMS),
+        )));
+    }
+    Ok(())
+}
+
+/// Create several tasks; the valid ones are stored in one transaction
+pub async fn bulk_create_tasks(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Json(requests): Json<Vec<StoreTaskRequest>>,
+) -> ApiResult<Json<BulkResponse>> {
+    let user = auth.require(Scope::TasksWrite)?;
+    check_bulk_size(requests.len())?;
+    let results = state.task_service.bulk_create_tasks(&user.id, requests).await?;
+    Ok(Json(BulkResponse::new(results, StatusCode::CREATED)))
+}
+
+/// Move several tasks to one status with a single update
+pub async fn bulk_update_status(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Json(body): Json<BulkStatusRequest>,
+) -> ApiResult<Json<BulkResponse>> {
+    let user = auth.require(Scope::TasksWrite)?;
+    check_bulk_size(body.ids.len())?;
+    let workflow = state.task_service.get_workflow(&user.id).await?;
+    let status = workflow.parse(&body.status).ok_or_else(|| {
+        ApiError(Diagnostic::new(ErrorCode::UnknownStatus, format!("Unknown status '{}'", body.status)))
+    })?;
+    let results = state.task_service.bulk_update_status(&user.id, body.ids, status).await?;
+    Ok(Json(BulkResponse::new(results, StatusCode::OK)))
+}
+
+pub async fn stats(
+    State(state): State<AppState>,
+    Extension(auth): Extension<Arc<AuthContext>>,
+    Query(query): Query<StatsQuery>,
+) -> ApiResult<Json<TaskStatistics>> {
+    let user = auth.require(Scope::TasksRead)?;
+    let stats = state.task_service.get_task_statistics(&user.id, &query.to_filter()?).await?;
+    Ok(Json(stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::{TaskPriority, TaskSort, TaskSortField, TaskStatus};
+
+    #[test]
+    fn test_task_list_query_to_filter() {
+        let cursor = Uuid::new_v4();
+        let query = TaskListQuery {
+            status: Some("in-progress".to_string()),
+            priority: Some("High".to_string()),
+            due_before: Some("2026-03-01T12:00:00Z".to_string()),
+            due_after: Some("2026-02-01".to_string()),
+            context: Some("@home".to_string()),
+            sort: Some("-created".to_string()),
+            limit: Some("25".to_string()),
+            cursor: Some(cursor.to_string()),
+            ..Default::default()
+        };
+
+        let filter = query.to_filter(&StatusWorkflow::default()).unwrap();
+        assert_eq!(filter.status, Some(TaskStatus::InProgress));
+        assert_eq!(filter.priority, Some(TaskPriority::High));
+        assert_eq!(filter.due_before.unwrap().to_rfc3339(), "2026-03-01T12:00:00+00:00");
+        assert_eq!(filter.due_after, Some(calendar::start_of_day(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap())));
+        assert_eq!(filter.context.as_deref(), Some("@home"));
+        assert_eq!(filter.sort, Some(TaskSort { field: TaskSortField::Created, descending: true }));
+        assert_eq!(filter.limit, Some(25));
+        assert_eq!(filter.after, Some(cursor));
+    }
+
+    #[test]
+    fn test_stats_query_to_filter() {
+        let query = StatsQuery {
+            completed_after: Some("2026-02-01".to_string()),
+            completed_before: Some("2026-03-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        let filter = query.to_filter().unwrap();
+        assert_eq!(filter.completed_after, Some(calendar::start_of_day(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap())));
+        assert_eq!(filter.completed_before.unwrap().to_rfc3339(), "2026-03-01T00:00:00+00:00");
+        assert_eq!(filter.created_after, None);
+
+        let error = StatsQuery { created_after: Some("last week".to_string()), ..Default::default() }
+            .to_filter()
+            .unwrap_err();
+        assert!(error.0.message.contains("created_after"));
+    }
+
+    #[test]
+    fn test_task_list_query_rejects_bad_values() {
+        let workflow = StatusWorkflow::default();
+        for query in [
+            TaskListQuery { status: Some("archived".to_string()), ..Default::default() },
+            TaskListQuery { priority: Some("urgent".to_string()), ..Default::default() },
+            TaskListQuery { due_before: Some("tomorrow".to_string()), ..Default::default() },
+            TaskListQuery { sort: Some("size".to_string()), ..Default::default() },
+            TaskListQuery { limit: Some("0".to_string()), ..Default::default() },
+            TaskListQuery { limit: Some("1000".to_string()), ..Default::default() },
+            TaskListQuery { cursor: Some("page-2".to_string()), ..Default::default() },
+            TaskListQuery { tag: Some("@home".to_string()), ..Default::default() },
+        ] {
+            let error = query.to_filter(&workflow).unwrap_err();
+            assert_eq!(error.0.code, ErrorCode::Validation);
+        }
+    }
+}
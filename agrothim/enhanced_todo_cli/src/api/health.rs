@@ -0,0 +1,84 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+use crate::api::{metrics::PoolStats, AppState};
+use crate::database::{Database, DatabasePool};
+
+/// Probes fail rather than hang when the database does not answer in time
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Liveness probe: the server is up and can reach the database
+pub async fn healthz(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    match ping(&state.database).await {
+        Ok(()) => (StatusCode::OK, Json(json!({ "status": "ok" }))),
+        Err(error) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "unavailable", "error": error })),
+        ),
+    }
+}
+
+/// Readiness probe: the database answers and, when `serve` was given a
+/// migrations directory, every migration in it has been applied
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let mut checks = Map::new();
+    let mut ready = true;
+
+    let mut check = |name: &str, result: Result<(), String>| {
+        ready &= result.is_ok();
+        checks.insert(name.to_string(), Value::String(result.err().unwrap_or_else(|| "ok".to_string())));
+    };
+
+    check("database", ping(&state.database).await);
+    if let Some(migrations_dir) = &state.migrations_dir {
+        let result = match state.database.pending_migrations(Some(migrations_dir)).await {
+            Ok(pending) if pending.is_empty() => Ok(()),
+            Ok(pending) => {
+                let versions: Vec<String> = pending.iter().map(|v| v.to_string()).collect();
+                Err(format!("pending: {}", versions.join(", ")))
+            }
+            Err(e) => Err(format!("{:#}", e)),
+        };
+        check("migrations", result);
+    }
+    // Reads fall back to the primary, so a replica that is down does not make the server unready
+    if let Some(replica) = state.database.replica() {
+        let status = if replica.is_available() { "ok" } else { "unavailable, reading from the primary" };
+        checks.insert("replica".to_string(), Value::String(status.to_string()));
+    }
+
+    let (status, label) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+    (status, Json(json!({ "status": label, "checks": checks })))
+}
+
+/// Request counts, latencies and database pool stats in the Prometheus text format
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let pool = match state.database.pool() {
+        DatabasePool::Postgres(pool) => PoolStats::of(pool),
+        #[cfg(feature = "sqlite")]
+        DatabasePool::Sqlite(pool) => PoolStats::of(pool),
+        #[cfg(feature = "memory")]
+        DatabasePool::Memory => PoolStats { size: 0, idle: 0, max: 0 },
+    };
+    let body = state.metrics.render(pool);
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+async fn ping(database: &Database) -> Result<(), String> {
+    match tokio::time::timeout(PROBE_TIMEOUT, database.health_check()).await {
+        Ok(Ok(true)) => Ok(()),
+        Ok(Ok(false)) => Err("unexpected health check result".to_string()),
+        Ok(Err(e)) => Err(format!("{:#}", e)),
+        Err(_) => Err(format!("no answer within {}s", PROBE_TIMEOUT.as_secs())),
+    }
+}
@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::api::{error::ApiError, AppState};
+use crate::error::{Diagnostic, ErrorCode};
+use crate::models::task::{StoreTaskRequest, Task, TaskPriority};
+use crate::services::Scope;
+
+/// The few fields automation services fill in; everything else is left to
+/// the CLI. Priority is a name such as "high", since those services send text.
+#[derive(Debug, Deserialize)]
+pub struct InboundTask {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+}
+
+impl InboundTask {
+    fn to_request(&self) -> Result<StoreTaskRequest, ApiError> {
+        let priority = match self.priority.as_deref().map(str::trim).filter(|priority| !priority.is_empty()) {
+            Some(priority) => priority
+                .parse::<TaskPriority>()
+                .map_err(|e| ApiError(Diagnostic::new(ErrorCode::Validation, e)))?,
+            None => TaskPriority::default(),
+        };
+        Ok(StoreTaskRequest {
+            title: self.title.clone(),
+            // Empty fields are what unmapped Zapier and IFTTT ingredients send
+            description: self.description.clone().filter(|description| !description.trim().is_empty()),
+            priority,
+            ..Default::default()
+        })
+    }
+}
+
+/// `POST /api/v1/inbound/{token}`: create a task for the token's owner from
+/// IFTTT, Zapier or a phone shortcut, which can send a URL and a body but
+/// not sign in. The token is an API key (see `todo-cli webhook inbound`);
+/// one that may only add tasks is enough.
+pub async fn create_task(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Json(body): Json<InboundTask>,
+) -> Result<(StatusCode, Json<Task>), ApiError> {
+    let auth = state.auth_service.authenticate(&token).await?;
+    let user = auth.require(Scope::TasksAdd)?;
+    let task = state.task_service.create_task(&user.id, body.to_request()?).await?;
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api;
+    use crate::database::repositories::{InMemoryTaskRepository, InMemoryUserRepository};
+    use crate::database::{Database, PoolConfig};
+    use crate::models::user::StoreUserRequest;
+    use crate::services::{AuthService, EventBus, TaskService, UserService};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn inbound(priority: Option<&str>, description: Option<&str>) -> InboundTask {
+        InboundTask {
+            title: "Buy milk".to_string(),
+            description: description.map(str::to_string),
+            priority: priority.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_to_request() {
+        let request = inbound(Some("High"), Some("2 liters")).to_request().unwrap();
+        assert_eq!(request.title, "Buy milk");
+        assert_eq!(request.priority, TaskPriority::High);
+        assert_eq!(request.description.as_deref(), Some("2 liters"));
+
+        let request = inbound(Some(" "), Some("")).to_request().unwrap();
+        assert_eq!(request.priority, TaskPriority::Medium);
+        assert_eq!(request.description, None);
+
+        assert!(inbound(Some("urgent"), None).to_request().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_post_creates_a_task_for_the_token_owner() {
+        let dir = TempDir::new().unwrap();
+        let user_service = Arc::new(UserService::new(Arc::new(InMemoryUserRepository::new())));
+        let auth_service = Arc::new(AuthService::new(user_service.clone(), "test-secret", Some(dir.path().to_path_buf())).unwrap());
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let state = AppState {
+            auth_service: auth_service.clone(),
+            user_service: user_service.clone(),
+            task_service: task_service.clone(),
+            event_bus: EventBus::default(),
+            database: Database::from_url_with("memory://", &PoolConfig::default()).await.unwrap(),
+            metrics: Default::default(),
+            migrations_dir: None,
+            timezone: Default::default(),
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v1/inbound", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api::router(state)).await });
+
+        let request = StoreUserRequest::new("phone".to_string(), "phone@example.com".to_string(), "kestrel-canyon-42".to_string());
+        let user = user_service.register(request.unwrap()).await.unwrap();
+        auth_service.login("phone", "kestrel-canyon-42").await.unwrap();
+        let token = auth_service.issue_token(&[Scope::TasksAdd], chrono::Duration::hours(1)).await.unwrap().token;
+
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({ "title": "Buy milk", "priority": "high" });
+        let response = client.post(format!("{}/{}", url, token)).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let tasks = task_service.get_tasks(&user.id, Default::default()).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!((tasks[0].title.as_str(), tasks[0].priority), ("Buy milk", TaskPriority::High));
+
+        // The token cannot read tasks back
+        let response = client.get(format!("{}/tasks", url.trim_end_matches("/inbound"))).bearer_auth(&token).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = client.post(format!("{}/not-a-token", url)).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client.post(format!("{}/{}", url, token)).json(&serde_json::json!({ "title": "" })).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::Pool;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds in seconds of the request latency histogram buckets
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Route label of requests that matched no route, so unknown paths cannot
+/// blow up the number of series
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Request counters and latency histograms of the API server, kept in memory
+/// and rendered in the Prometheus text format by `/metrics`
+#[derive(Default)]
+pub struct Metrics {
+    routes: Mutex<BTreeMap<(String, String), RouteStats>>,
+}
+
+#[derive(Default)]
+struct RouteStats {
+    by_status: BTreeMap<u16, u64>,
+    /// Cumulative, like the `le` buckets they are rendered as
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum_seconds: f64,
+}
+
+/// Connection counts of the database pool at scrape time
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub max: u32,
+}
+
+impl PoolStats {
+    pub fn of<DB: sqlx::Database>(pool: &Pool<DB>) -> Self {
+        Self {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max: pool.options().get_max_connections(),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn record(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let mut routes = self.routes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let stats = routes.entry((method.to_string(), route.to_string())).or_default();
+
+        *stats.by_status.entry(status).or_default() += 1;
+        for (bucket, bound) in stats.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        stats.count += 1;
+        stats.sum_seconds += seconds;
+    }
+
+    pub fn render(&self, pool: PoolStats) -> String {
+        let routes = self.routes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total HTTP requests served, by route and status.\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((method, route), stats) in routes.iter() {
+            for (status, count) in &stats.by_status {
+                let _ = writeln!(
+                    out,
+                    "http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                    method, route, status, count
+                );
+            }
+        }
+
+        out.push_str("# HELP http_request_duration_seconds Time to produce a response, by route.\n");
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for ((method, route), stats) in routes.iter() {
+            let labels = format!("method=\"{}\",route=\"{}\"", method, route);
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                let _ = writeln!(out, "http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, count);
+            }
+            let _ = writeln!(out, "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, stats.count);
+            let _ = writeln!(out, "http_request_duration_seconds_sum{{{}}} {}", labels, stats.sum_seconds);
+            let _ = writeln!(out, "http_request_duration_seconds_count{{{}}} {}", labels, stats.count);
+        }
+
+        let active = (pool.size as usize).saturating_sub(pool.idle);
+        out.push_str("# HELP db_pool_connections Open database connections, by state.\n");
+        out.push_str("# TYPE db_pool_connections gauge\n");
+        let _ = writeln!(out, "db_pool_connections{{state=\"active\"}} {}", active);
+        let _ = writeln!(out, "db_pool_connections{{state=\"idle\"}} {}", pool.idle);
+        out.push_str("# HELP db_pool_max_connections Connections the pool may open.\n");
+        out.push_str("# TYPE db_pool_max_connections gauge\n");
+        let _ = writeln!(out, "db_pool_max_connections {}", pool.max);
+
+        out
+    }
+}
+
+/// Middleware counting every request and its latency under its route template
+pub async fn track(State(metrics): State<Arc<Metrics>>, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    metrics.record(&method, &route, response.status().as_u16(), started.elapsed());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_and_histogram() {
+        let metrics = Metrics::default();
+        metrics.record("GET", "/api/tasks", 200, Duration::from_millis(3));
+        metrics.record("GET", "/api/tasks", 200, Duration::from_millis(30));
+        metrics.record("GET", "/api/tasks", 401, Duration::from_secs(20));
+
+        let text = metrics.render(PoolStats { size: 3, idle: 1, max: 10 });
+        assert!(text.contains("http_requests_total{method=\"GET\",route=\"/api/tasks\",status=\"200\"} 2\n"));
+        assert!(text.contains("http_requests_total{method=\"GET\",route=\"/api/tasks\",status=\"401\"} 1\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/tasks\",le=\"0.005\"} 1\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/tasks\",le=\"0.05\"} 2\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/tasks\",le=\"10\"} 2\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{method=\"GET\",route=\"/api/tasks\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("http_request_duration_seconds_count{method=\"GET\",route=\"/api/tasks\"} 3\n"));
+        assert!(text.contains("db_pool_connections{state=\"active\"} 2\n"));
+        assert!(text.contains("db_pool_max_connections 10\n"));
+    }
+}
@@ -0,0 +1,111 @@
+pub mod auth;
+pub mod caldav;
+pub mod calendar;
+pub mod error;
+pub mod events;
+pub mod handlers;
+pub mod health;
+pub mod inbound;
+pub mod metrics;
+pub mod timezone;
+pub mod versioning;
+
+use axum::{
+    middleware,
+    routing::{any, get, patch, post},
+    Router,
+};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::info;
+
+use crate::database::Database;
+use crate::models::calendar::UserTimezone;
+use crate::services::{AuthService, EventBus, TaskService, UserService};
+
+/// Services shared by every request handler
+#[derive(Clone)]
+pub struct AppState {
+    pub auth_service: Arc<AuthService>,
+    pub user_service: Arc<UserService>,
+    pub task_service: Arc<TaskService>,
+    /// Task changes published by `task_service`, streamed by `/api/events`
+    pub event_bus: EventBus,
+    pub database: Database,
+    pub metrics: Arc<metrics::Metrics>,
+    /// Migrations `/readyz` requires to be applied, if any
+    pub migrations_dir: Option<PathBuf>,
+    /// Timezone of requests that name none, see `timezone::scope`
+    pub timezone: UserTimezone,
+}
+
+/// REST routes under `/api/v1`. A later version with changed DTOs gets its
+/// own `vN_routes` and handlers, nested next to v1, so v1 clients never see
+/// its changes. The unversioned `/api` paths still serve v1 but announce
+/// their sunset. The probes and `/metrics` sit at the root for Kubernetes
+/// and Prometheus, CalDAV under `/dav/` where clients discover it.
+pub fn router(state: AppState) -> Router {
+    let legacy = v1_routes(&state).layer(middleware::from_fn_with_state(
+        versioning::UNVERSIONED,
+        versioning::deprecate,
+    ));
+
+    Router::new()
+        .nest("/api/v1", v1_routes(&state))
+        .nest("/api", legacy)
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/metrics", get(health::metrics))
+        .route("/.well-known/caldav", any(caldav::well_known))
+        .route("/dav", any(caldav::handle))
+        .route("/dav/", any(caldav::handle))
+        .route("/dav/{*path}", any(caldav::handle))
+        .layer(middleware::from_fn_with_state(state.timezone, timezone::scope))
+        .layer(middleware::from_fn_with_state(state.metrics.clone(), metrics::track))
+        .with_state(state)
+}
+
+/// Everything except registration and login needs an
+/// `Authorization: Bearer <token>` header with a JWT or API key. The
+/// calendar feed and inbound webhook check their own tokens, which come in
+/// the URL.
+fn v1_routes(state: &AppState) -> Router<AppState> {
+    let protected = Router::new()
+        .route("/me", get(handlers::me))
+        .route("/tasks", get(handlers::list_tasks).post(handlers::create_task))
+        .route("/tasks/bulk", post(handlers::bulk_create_tasks))
+        .route("/tasks/bulk-status", patch(handlers::bulk_update_status))
+        .route(
+            "/tasks/{id}",
+            get(handlers::get_task)
+                .patch(handlers::update_task)
+                .delete(handlers::delete_task),
+        )
+        .route("/tasks/{id}/complete", post(handlers::complete_task))
+        .route("/stats", get(handlers::stats))
+        .route("/events", get(events::stream))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::require_bearer));
+
+    let public = Router::new()
+        .route("/auth/register", post(handlers::register))
+        .route("/auth/login", post(handlers::login))
+        .route("/calendar.ics", get(calendar::feed))
+        .route("/inbound/{token}", post(inbound::create_task));
+
+    public.merge(protected)
+}
+
+/// Serve the API until the process receives Ctrl-C
+pub async fn serve(state: AppState, addr: SocketAddr) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("API listening on {}", listener.local_addr()?);
+
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            info!("Shutting down API server");
+        })
+        .await
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::error::ApiError;
+use crate::error::{Diagnostic, ErrorCode};
+use crate::models::calendar::{self, UserTimezone};
+
+/// Header a client names its timezone in, e.g. `X-Timezone: Europe/Berlin`
+pub const TIMEZONE_HEADER: &str = "x-timezone";
+
+/// Query parameter naming the timezone, for clients that cannot set headers
+/// such as calendar apps subscribed to the feed URL
+const TIMEZONE_PARAM: &str = "tz";
+
+/// The timezone a request names, the `tz` query parameter winning over the
+/// `X-Timezone` header; `None` when it names none
+fn requested(request: &Request) -> Result<Option<UserTimezone>, ApiError> {
+    let param = request.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(name, _)| name == TIMEZONE_PARAM)
+            .map(|(_, value)| value.into_owned())
+    });
+    let header = request
+        .headers()
+        .get(TIMEZONE_HEADER)
+        .map(|value| value.to_str().map(str::to_string).unwrap_or_default());
+
+    param
+        .or(header)
+        .map(|name| {
+            name.parse::<UserTimezone>()
+                .map_err(|message| ApiError(Diagnostic::new(ErrorCode::Validation, message).with_label("timezone", name)))
+        })
+        .transpose()
+}
+
+/// Middleware running each request in the timezone its client names, so
+/// "today" and "overdue" follow that user's midnight; requests naming none
+/// run in the server's `timezone` setting
+pub async fn scope(State(default): State<UserTimezone>, request: Request, next: Next) -> Response {
+    match requested(&request) {
+        Ok(timezone) => calendar::with_timezone(timezone.unwrap_or(default), next.run(request)).await,
+        Err(error) => error.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+
+    /// A server answering `/timezone` with the timezone the request ran in
+    async fn serve() -> String {
+        let app = Router::new()
+            .route("/timezone", get(|| async { calendar::timezone().to_string() }))
+            .layer(middleware::from_fn_with_state(UserTimezone::System, scope));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/timezone", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_each_request_runs_in_the_timezone_it_names() {
+        let url = serve().await;
+        let client = reqwest::Client::new();
+        let timezone_of = |request: reqwest::RequestBuilder| async move {
+            let response = request.send().await.unwrap();
+            (response.status().as_u16(), response.text().await.unwrap())
+        };
+
+        assert_eq!(timezone_of(client.get(&url)).await, (200, "local".to_string()));
+        assert_eq!(
+            timezone_of(client.get(&url).header(TIMEZONE_HEADER, "Pacific/Auckland")).await,
+            (200, "Pacific/Auckland".to_string())
+        );
+        assert_eq!(
+            timezone_of(client.get(&url).query(&[("tz", "America/New_York")]).header(TIMEZONE_HEADER, "Pacific/Auckland")).await,
+            (200, "America/New_York".to_string())
+        );
+        assert_eq!(timezone_of(client.get(&url).query(&[("tz", "Mars/Olympus")])).await.0, 400);
+    }
+}
@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use chrono::DateTime;
+
+/// Deprecation of a mounted API version, announced on every response with
+/// the `Deprecation` (RFC 9745), `Sunset` (RFC 8594) and successor `Link` headers
+#[derive(Debug, Clone, Copy)]
+pub struct Deprecation {
+    /// Unix time the version was deprecated
+    pub since: i64,
+    /// Unix time after which the version may be removed
+    pub sunset: i64,
+    /// Prefix of the version replacing this one
+    pub successor: &'static str,
+}
+
+/// The unversioned `/api/...` routes, which predate `/api/v1`
+pub const UNVERSIONED: Deprecation = Deprecation {
+    // 2026-10-16T00:00:00Z
+    since: 1_792_108_800,
+    // 2027-04-16T00:00:00Z
+    sunset: 1_807_833_600,
+    successor: "/api/v1",
+};
+
+impl Deprecation {
+    /// Headers for a response to `path`, relative to the deprecated prefix
+    pub fn headers(&self, path: &str) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = Vec::new();
+        if let Ok(value) = HeaderValue::from_str(&format!("@{}", self.since)) {
+            headers.push((HeaderName::from_static("deprecation"), value));
+        }
+        if let Some(sunset) = DateTime::from_timestamp(self.sunset, 0) {
+            let sunset = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            if let Ok(value) = HeaderValue::from_str(&sunset) {
+                headers.push((HeaderName::from_static("sunset"), value));
+            }
+        }
+        if let Ok(value) = HeaderValue::from_str(&format!("<{}{}>; rel=\"successor-version\"", self.successor, path)) {
+            headers.push((header::LINK, value));
+        }
+        headers
+    }
+}
+
+/// Middleware marking every response of a router as deprecated; layered on
+/// the router before it is nested, so the path it sees has the prefix stripped
+pub async fn deprecate(State(deprecation): State<Deprecation>, request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    let mut response = next.run(request).await;
+    response.headers_mut().extend(deprecation.headers(&path));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecation_headers() {
+        let headers = UNVERSIONED.headers("/tasks/42");
+        let value = |name: &str| {
+            headers
+                .iter()
+                .find(|(header, _)| header.as_str() == name)
+                .map(|(_, value)| value.to_str().unwrap().to_string())
+        };
+
+        assert_eq!(value("deprecation").as_deref(), Some("@1792108800"));
+        assert_eq!(value("sunset").as_deref(), Some("Fri, 16 Apr 2027 00:00:00 GMT"));
+        assert_eq!(value("link").as_deref(), Some("</api/v1/tasks/42>; rel=\"successor-version\""));
+    }
+}
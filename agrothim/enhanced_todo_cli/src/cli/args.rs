@@ -1,5 +1,15 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
 use clap::{Parser, Subcommand, ValueEnum};
 use std::fmt;
+use std::str::FromStr;
+
+use crate::models::calendar;
+use crate::models::issue_link::RepoName;
+use crate::models::location::GeoPoint;
+use crate::models::task_column::TaskColumn;
+use crate::models::user_identity::OAuthProvider;
+
+use crate::utils::validation::{parse_duration, parse_time_of_day};
 
 #[derive(Parser)]
 #[command(name = "todo-cli")]
@@ -15,6 +25,15 @@ pub struct Args {
     #[arg(short, long, global = true)]
     pub config: Option<String>,
 
+    /// Comma-separated fields to keep in JSON output (e.g. id,title,due_date);
+    /// dotted paths select nested values
+    #[arg(long, global = true)]
+    pub fields: Option<String>,
+
+    /// Print long listings straight to the terminal instead of through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -31,25 +50,64 @@ pub enum Commands {
         #[command(subcommand)]
         command: TaskCommands,
     },
+    /// Custom status workflow management
+    Status {
+        #[command(subcommand)]
+        command: StatusCommands,
+    },
+    /// Workspaces whose members share a task list
+    Workspace {
+        #[command(subcommand)]
+        command: WorkspaceCommands,
+    },
+    /// Webhooks notified about account security and task events
+    Webhook {
+        #[command(subcommand)]
+        command: WebhookCommands,
+    },
+    /// Account data management
+    Account {
+        #[command(subcommand)]
+        command: AccountCommands,
+    },
+    /// Manage other users' accounts; requires your user ID to be listed in TODO_ADMIN_USER_IDS
+    Admin {
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
     /// Configuration management
     Config {
         #[command(subcommand)]
         command: ConfigCommands,
     },
+    /// Database schema management
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
     /// Export data
     Export {
-        /// Output format
-        #[arg(short, long, default_value = "json")]
-        format: ExportFormat,
+        /// Output format (json, ndjson, csv, taskwarrior, ...); inferred from the output extension if omitted
+        #[arg(short, long)]
+        format: Option<String>,
         /// Output file path
         #[arg(short, long)]
         output: Option<String>,
+        /// Write a versioned JSON archive with custom statuses and transitions alongside the tasks
+        #[arg(long, conflicts_with = "format")]
+        archive: bool,
     },
     /// Import data
     Import {
         /// Input file path
         #[arg(short, long)]
         file: String,
+        /// Input format; inferred from the file extension if omitted
+        #[arg(long)]
+        format: Option<String>,
+        /// Read a JSON archive written by `export --archive` (plain JSON task exports are accepted too)
+        #[arg(long, conflicts_with = "format")]
+        archive: bool,
         /// Merge with existing data instead of replacing
         #[arg(short, long)]
         merge: bool,
@@ -62,12 +120,100 @@ pub enum Commands {
         #[arg(long)]
         in_description: bool,
     },
+    /// Reports over tracked data
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
     /// Show statistics
     Stats {
         /// Time period for statistics
         #[arg(short, long, default_value = "all")]
         period: StatsPeriod,
+        /// Aggregate the tasks of all members of this workspace (name or ID)
+        #[arg(long)]
+        workspace: Option<String>,
+    },
+    /// Show who created, completed and shared tasks since you last looked
+    Activity {
+        /// Only show activity in this workspace (name or ID)
+        #[arg(long)]
+        workspace: Option<String>,
+        /// Show everything from this long ago instead (e.g. 12h, 7d)
+        #[arg(long, value_parser = parse_duration)]
+        since: Option<std::time::Duration>,
+        /// Maximum number of entries to show
+        #[arg(short, long, default_value = "50")]
+        limit: i64,
+    },
+    /// Sync the local database (DATABASE_LOCAL_URL) with DATABASE_URL now
+    Sync {
+        #[command(subcommand)]
+        command: Option<SyncCommands>,
+    },
+    /// Complete tasks from commit messages such as "closes TODO-3f2a9c1e"
+    Git {
+        #[command(subcommand)]
+        command: GitCommands,
+    },
+    /// Serve the REST API over HTTP until interrupted
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: std::net::IpAddr,
+        /// Port to listen on
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+        /// Report not ready on /readyz until every migration in this directory is applied
+        #[arg(long)]
+        migrations_dir: Option<std::path::PathBuf>,
+    },
+    /// Subscribe to your due dates from a calendar app
+    Calendar {
+        #[command(subcommand)]
+        command: CalendarCommands,
+    },
+    /// Get a morning email of what is due, overdue and done
+    Digest {
+        #[command(subcommand)]
+        command: DigestCommands,
+    },
+    /// Notify about tasks due soon or overdue, once each, through the channels
+    /// turned on with `config set reminders.*`
+    Remind {
+        /// List the reminders without sending any
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Create tasks on a schedule, e.g. "Weekly review" every Monday at 09:00
+    Schedule {
+        #[command(subcommand)]
+        command: ScheduleCommands,
+    },
+    /// Create the tasks of every schedule that fired; run this every minute, e.g. from cron
+    Tick,
+    /// Archive old completed tasks and purge old archives, as the
+    /// `maintenance.*` settings say
+    Maintain {
+        /// List what would be archived and purged without changing anything
+        #[arg(long)]
+        dry_run: bool,
     },
+    /// Keep running: send reminders and daily digests as they come due and
+    /// create scheduled tasks
+    Daemon {
+        #[command(subcommand)]
+        command: Option<DaemonCommands>,
+        /// How often to check for due reminders, digests and schedules (e.g. 30s, 5m)
+        #[arg(long, default_value = "1m", value_parser = parse_duration)]
+        interval: std::time::Duration,
+        /// Start the daemon in the background and return
+        #[arg(long)]
+        detach: bool,
+    },
+    /// Learn the basics on a practice task list (no account needed)
+    #[cfg(feature = "memory")]
+    Tutorial,
 }
 
 #[derive(Subcommand)]
@@ -75,11 +221,102 @@ pub enum AuthCommands {
     /// Register a new user account
     Register,
     /// Login to an existing account
-    Login,
+    Login {
+        /// Log in with an external account instead of a password (github, google)
+        #[arg(long, value_parser = OAuthProvider::from_str)]
+        provider: Option<OAuthProvider>,
+    },
+    /// Link an external account (github, google) to the logged-in account,
+    /// so it can log in with `auth login --provider`
+    Link {
+        #[arg(value_parser = OAuthProvider::from_str)]
+        provider: OAuthProvider,
+    },
     /// Logout from current session
     Logout,
     /// Show current authentication status
     Status,
+    /// Change the password of the logged-in account
+    ChangePassword,
+    /// Change profile details of the logged-in account
+    Update {
+        /// New username; it can be changed once every 30 days
+        #[arg(long)]
+        username: String,
+    },
+    /// Email a password reset token to the account with this address
+    ForgotPassword {
+        /// Email address of the account
+        email: String,
+    },
+    /// Set a new password using an emailed reset token
+    ResetPassword {
+        /// Token from the password reset email
+        token: String,
+    },
+    /// Verify the email address of a new account
+    Verify {
+        /// Token from the verification email
+        token: String,
+    },
+    /// Send a new verification token to an unverified account
+    ResendVerification {
+        /// Email address of the account
+        email: String,
+    },
+    /// Create a limited access token, e.g. a read-only token for a dashboard
+    Token {
+        /// Comma-separated scopes to grant (tasks:read, tasks:add, calendar:feed, tasks:write, admin)
+        #[arg(short, long, value_delimiter = ',', default_value = "tasks:read")]
+        scopes: Vec<String>,
+        /// How long the token stays valid (e.g. 12h, 30d)
+        #[arg(long, default_value = "30d", value_parser = parse_duration)]
+        expires: std::time::Duration,
+    },
+    /// List or revoke the devices you are logged in on
+    Sessions {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+    /// Manage long-lived API keys for scripts
+    #[command(name = "apikey")]
+    ApiKey {
+        #[command(subcommand)]
+        command: ApiKeyCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SessionCommands {
+    /// List active sessions, marking this device's
+    List,
+    /// Log a device out; its tokens stop working within a few minutes
+    Revoke {
+        /// Session ID, or the short ID shown by `auth sessions list`
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ApiKeyCommands {
+    /// Create an API key; it is shown once
+    Create {
+        /// Name to recognise the key by, e.g. the script using it
+        name: String,
+        /// Comma-separated scopes to grant (tasks:read, tasks:add, calendar:feed, tasks:write, admin)
+        #[arg(short, long, value_delimiter = ',', default_value = "tasks:read")]
+        scopes: Vec<String>,
+        /// Expire the key after this long (e.g. 90d); keys never expire by default
+        #[arg(long, value_parser = parse_duration)]
+        expires: Option<std::time::Duration>,
+    },
+    /// List your API keys
+    List,
+    /// Revoke an API key
+    Revoke {
+        /// Key ID or the prefix shown by `auth apikey list`
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -97,28 +334,33 @@ pub enum TaskCommands {
         /// Due date (YYYY-MM-DD format)
         #[arg(long)]
         due: Option<String>,
-    },
-    /// List tasks with optional filtering
-    List {
-        /// Filter by task status
-        #[arg(short, long)]
-        status: Option<TaskStatus>,
-        /// Filter by priority
-        #[arg(short, long)]
-        priority: Option<TaskPriority>,
-        /// Search keyword
+        /// Hide the task from `task list` until this date (YYYY-MM-DD format)
         #[arg(long)]
-        search: Option<String>,
-        /// Show completed tasks only
-        #[arg(short, long)]
-        completed: bool,
-        /// Show pending tasks only
+        start: Option<String>,
+        /// Estimated effort (e.g. 30m, 2h, 1h30m)
+        #[arg(short, long, value_parser = parse_estimate)]
+        estimate: Option<i32>,
+        /// Client the task's time is billed to
+        #[arg(long)]
+        client: Option<String>,
+        /// GTD context the task can be done in (e.g. @home, @errands)
+        #[arg(long)]
+        context: Option<String>,
+        /// Where the task can be done (e.g. office, grocery store)
+        #[arg(long)]
+        location: Option<String>,
+        /// Coordinates of the task's location (LAT,LON)
+        #[arg(long, value_name = "LAT,LON", value_parser = GeoPoint::from_str, allow_hyphen_values = true)]
+        coords: Option<GeoPoint>,
+        /// Share the task with the members of this workspace (name or ID)
         #[arg(long)]
-        pending: bool,
+        workspace: Option<String>,
     },
+    /// List tasks with optional filtering
+    List(TaskListArgs),
     /// Update an existing task
     Update {
-        /// Task ID
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
         id: String,
         /// New title
         #[arg(short, long)]
@@ -132,32 +374,413 @@ pub enum TaskCommands {
         /// New due date (YYYY-MM-DD format)
         #[arg(long)]
         due: Option<String>,
+        /// New start date (YYYY-MM-DD format)
+        #[arg(long)]
+        start: Option<String>,
+        /// New estimated effort (e.g. 30m, 2h, 1h30m)
+        #[arg(short, long, value_parser = parse_estimate)]
+        estimate: Option<i32>,
+        /// New client the task's time is billed to
+        #[arg(long)]
+        client: Option<String>,
+        /// New GTD context
+        #[arg(long)]
+        context: Option<String>,
+        /// New location label
+        #[arg(long)]
+        location: Option<String>,
+        /// New coordinates of the task's location (LAT,LON)
+        #[arg(long, value_name = "LAT,LON", value_parser = GeoPoint::from_str, allow_hyphen_values = true)]
+        coords: Option<GeoPoint>,
+    },
+    /// Move a task to another status
+    SetStatus {
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
+        id: String,
+        /// Target status (built-in or custom status name)
+        status: String,
+    },
+    /// Move several tasks to another status and report the result per task
+    BulkStatus {
+        /// Target status (built-in or custom status name)
+        status: String,
+        /// Task IDs
+        #[arg(required = true)]
+        ids: Vec<String>,
     },
     /// Mark task as completed
     Complete {
-        /// Task ID
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
         id: String,
     },
     /// Mark task as pending (uncomplete)
     Uncomplete {
-        /// Task ID
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
         id: String,
     },
     /// Delete a task
     Delete {
-        /// Task ID
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
         id: String,
         /// Skip confirmation prompt
         #[arg(short, long)]
         force: bool,
     },
+    /// Pin a task to the top of task lists
+    Pin {
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
+        id: String,
+    },
+    /// Unpin a task
+    Unpin {
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
+        id: String,
+    },
+    /// Share one of your tasks with another user; it shows up in their task list
+    Share {
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
+        id: String,
+        /// Username of the user to share the task with
+        #[arg(long = "with", value_name = "USERNAME")]
+        with_user: String,
+        /// Let the user see the task without changing it
+        #[arg(long)]
+        read_only: bool,
+    },
+    /// Start a timer on a task (stops any other running timer)
+    Start {
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
+        id: String,
+    },
+    /// Stop the running timer
+    Stop,
+    /// Rank a task directly before or after another task
+    Move {
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
+        id: String,
+        /// Place the task before this task
+        #[arg(long, value_name = "ID", required_unless_present = "after", conflicts_with = "after")]
+        before: Option<String>,
+        /// Place the task after this task
+        #[arg(long, value_name = "ID")]
+        after: Option<String>,
+    },
+    /// Show remaining estimated work per day, based on due dates
+    Workload {
+        /// Number of days to show, starting today
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Show the number of open tasks per context
+    Contexts,
+    /// Print a short plain-text digest of today's tasks (e.g. for text-to-speech)
+    Summarize,
     /// Show detailed information about a task
     Show {
-        /// Task ID
+        /// Task ID: UUID, hash prefix or number (e.g. #12)
         id: String,
+        /// Print the task as JSON (implied by --fields)
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(clap::Args, Clone)]
+pub struct TaskListArgs {
+    /// Filter by task status (built-in or custom status name)
+    #[arg(short, long)]
+    pub status: Option<String>,
+    /// Filter by priority
+    #[arg(short, long)]
+    pub priority: Option<TaskPriority>,
+    /// Search keyword
+    #[arg(long)]
+    pub search: Option<String>,
+    /// Show completed tasks only
+    #[arg(long)]
+    pub completed: bool,
+    /// Show pending tasks only
+    #[arg(long)]
+    pub pending: bool,
+    /// Only show tasks in this context (e.g. @home)
+    #[arg(long)]
+    pub context: Option<String>,
+    /// Only show open tasks whose due day has passed
+    #[arg(long, conflicts_with = "today")]
+    pub overdue: bool,
+    /// Only show tasks due today
+    #[arg(long)]
+    pub today: bool,
+    /// Include tasks whose start date is still in the future
+    #[arg(short, long)]
+    pub all: bool,
+    /// Only show tasks at a named location, or within --radius of its configured coordinates
+    #[arg(long, value_name = "LOCATION")]
+    pub near: Option<String>,
+    /// Search radius in kilometres for --near
+    #[arg(long, default_value_t = 1.0, requires = "near")]
+    pub radius: f64,
+    /// Comma-separated table columns (id, title, status, priority, due, start,
+    /// created, updated, completed, estimate, context, client, location);
+    /// the display.columns setting or id,title,status,priority,due,created by default
+    #[arg(long, value_delimiter = ',', value_parser = TaskColumn::from_str, conflicts_with = "json")]
+    pub columns: Option<Vec<TaskColumn>>,
+    /// Print tasks as JSON (implied by --fields)
+    #[arg(long)]
+    pub json: bool,
+    /// Print a table, or CSV or TSV with a header row for spreadsheets, `cut` and `awk`
+    #[arg(long, value_enum, default_value = "table", conflicts_with_all = ["json", "watch"])]
+    pub output: ListOutput,
+    /// Keep the table on screen and refresh it periodically
+    #[arg(short, long, conflicts_with = "json")]
+    pub watch: bool,
+    /// Refresh interval for --watch (e.g. 5s, 1m)
+    #[arg(long, default_value = "5s", value_parser = parse_duration, requires = "watch")]
+    pub interval: std::time::Duration,
+}
+
+#[derive(Subcommand)]
+pub enum StatusCommands {
+    /// List built-in and custom statuses with their transition rules
+    List,
+    /// Define a new custom status
+    Add {
+        /// Status name (e.g. "Blocked", "In Review")
+        name: String,
+    },
+    /// Remove an unused custom status
+    Remove {
+        /// Status name
+        name: String,
+    },
+    /// Allow moving tasks from one status to another
+    Allow {
+        /// Source status
+        from: String,
+        /// Target status
+        to: String,
+    },
+    /// Remove a previously allowed transition
+    Disallow {
+        /// Source status
+        from: String,
+        /// Target status
+        to: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WebhookCommands {
+    /// Register a webhook URL (Slack incoming webhooks work as-is)
+    Add {
+        /// URL that receives signed JSON POSTs
+        url: String,
+        /// Comma-separated events to subscribe to (auth.login, auth.password_changed, task.created,
+        /// task.completed, task.deleted); all if omitted
+        #[arg(short, long, value_delimiter = ',')]
+        events: Vec<String>,
+    },
+    /// List registered webhooks
+    List,
+    /// Remove a webhook
+    Remove {
+        /// Webhook ID
+        id: String,
+    },
+    /// Show the latest delivery attempts of a webhook
+    Deliveries {
+        /// Webhook ID
+        id: String,
+        /// Maximum number of deliveries to show
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
+    },
+    /// Create a key that may only add tasks and print the URL IFTTT, Zapier or a shortcut posts them to; shown once
+    Inbound {
+        /// Address those services reach `todo-cli serve` at; api.url or http://127.0.0.1:8080 by default
+        #[arg(long)]
+        server: Option<String>,
+        /// Expire the key after this long (e.g. 365d); it never expires by default
+        #[arg(long, value_parser = parse_duration)]
+        expires: Option<std::time::Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WorkspaceCommands {
+    /// Create a workspace you own
+    Create {
+        /// Workspace name
+        name: String,
+    },
+    /// List the workspaces you are a member of
+    List,
+    /// Add members to a workspace you own, directly or with invite codes
+    Invite {
+        #[command(subcommand)]
+        command: InviteCommands,
+    },
+    /// Join a workspace with an invite code
+    Join {
+        /// Invite code shared by the workspace owner
+        code: String,
+    },
+    /// List the members of a workspace
+    Members {
+        /// Workspace name or ID
+        workspace: String,
+    },
+    /// List the tasks shared in a workspace
+    Tasks {
+        /// Workspace name or ID
+        workspace: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum InviteCommands {
+    /// Add an existing user to the workspace
+    User {
+        /// Workspace name or ID
+        workspace: String,
+        /// Username of the user to add
+        username: String,
+    },
+    /// Create an invite code; it is shown once
+    Create {
+        /// Workspace name or ID
+        workspace: String,
+        /// Expire the code after this long (e.g. 12h, 7d)
+        #[arg(long, default_value = "7d", value_parser = parse_duration)]
+        expires: std::time::Duration,
+        /// Stop accepting the code after this many joins; unlimited by default
+        #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+        max_uses: Option<i32>,
+    },
+    /// List the invite codes of the workspace
+    List {
+        /// Workspace name or ID
+        workspace: String,
+    },
+    /// Revoke an invite code
+    Revoke {
+        /// Workspace name or ID
+        workspace: String,
+        /// Invitation ID or the prefix shown by `workspace invite list`
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AccountCommands {
+    /// Export everything stored about your account (profile, tasks, time entries, logins) as JSON
+    ExportData {
+        /// Output file path; printed to stdout if omitted
+        #[arg(short, long)]
+        output: Option<String>,
     },
 }
 
+#[derive(Subcommand)]
+pub enum AdminCommands {
+    /// User account management
+    User {
+        #[command(subcommand)]
+        command: AdminUserCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AdminUserCommands {
+    /// Suspend an account: its data is kept, but it can no longer log in or use its tokens
+    Suspend {
+        /// Username of the account
+        username: String,
+    },
+    /// Lift the suspension of an account
+    Reactivate {
+        /// Username of the account
+        username: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Time tracked per task
+    Time {
+        /// Time period to report on
+        #[arg(short, long, default_value = "week")]
+        period: StatsPeriod,
+    },
+    /// Billable time per client for one month, priced with the billing.rate settings
+    Billable {
+        /// Only include tasks of this client
+        #[arg(long)]
+        client: Option<String>,
+        /// Month to report on (YYYY-MM); defaults to the current month
+        #[arg(long, value_parser = parse_month)]
+        month: Option<NaiveDate>,
+        /// Write the report as a CSV invoice to this file
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Tasks created and completed in the last 7 days, with the completion rate and top contexts
+    Weekly {
+        /// How to print the report
+        #[arg(short, long, value_enum, default_value = "table")]
+        format: ReportFormat,
+    },
+    /// Chart of your open task count day by day, to see whether the backlog shrinks
+    Burndown {
+        /// How far back the chart goes
+        #[arg(short, long, value_enum, default_value = "month")]
+        period: ChartPeriod,
+        /// Print the daily counts as JSON instead of a chart
+        #[arg(long)]
+        json: bool,
+    },
+    /// Calendar of the tasks you completed each day of a year, shaded by count
+    Heatmap {
+        /// Year to show; defaults to the current year
+        #[arg(short, long, value_parser = clap::value_parser!(i32).range(1970..=9999))]
+        year: Option<i32>,
+        /// Print the daily counts as JSON instead of a calendar
+        #[arg(long)]
+        json: bool,
+    },
+    /// Overdue tasks bucketed by how many days they are late, the oldest first
+    Overdue {
+        /// Print the buckets as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Estimate when your open tasks will be done, from how many you completed recently
+    Forecast {
+        /// Trailing weeks the completion velocity is measured over
+        #[arg(short, long, default_value = "4", value_parser = clap::value_parser!(u32).range(1..=52))]
+        weeks: u32,
+        /// Print the forecast as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// How `task list` prints the tasks
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListOutput {
+    Table,
+    Csv,
+    Tsv,
+}
+
+/// Output of the summary reports
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Table,
+    Markdown,
+    Json,
+}
+
 #[derive(Subcommand)]
 pub enum ConfigCommands {
     /// Show current configuration
@@ -174,8 +797,197 @@ pub enum ConfigCommands {
         /// Configuration key
         key: String,
     },
+    /// Remove a configuration value
+    Unset {
+        /// Configuration key
+        key: String,
+    },
     /// Reset configuration to defaults
     Reset,
+    /// Check the deployment configuration; exits nonzero if any check fails
+    Validate {
+        /// Environment to validate for (defaults to APP_ENV)
+        #[arg(long)]
+        env: Option<String>,
+        /// Check the SQL migrations in this directory instead of the built-in ones
+        #[arg(long)]
+        migrations_dir: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Apply the migrations built into this binary that the database is missing
+    Migrate,
+    /// Check connectivity, migrations, indexes and permissions, and say how to fix what is wrong
+    Doctor,
+    /// Save your account and tasks to a file that any backend can restore
+    Backup {
+        /// File to write the backup to
+        file: String,
+        /// Back up every account (administrators only)
+        #[arg(long)]
+        all: bool,
+    },
+    /// Replace your tasks with those of a backup
+    Restore {
+        /// Backup file to read
+        file: String,
+        /// Restore every account of the backup, creating missing ones (administrators only)
+        #[arg(long)]
+        all: bool,
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Create a demo account with random tasks, for benchmarks and screenshots
+    Seed {
+        /// Number of tasks to create
+        #[arg(long, default_value_t = 500)]
+        tasks: usize,
+        /// Seed of the random generator, to create the same tasks again
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SyncCommands {
+    /// Review tasks changed on both sides and choose the version to keep
+    Conflicts {
+        /// Only list the conflicts
+        #[arg(long, conflicts_with = "keep")]
+        list: bool,
+        /// Keep this side's version of every conflict without asking
+        #[arg(long, value_enum)]
+        keep: Option<SyncSide>,
+    },
+    /// Create tasks from your open GitHub issues, and close the issues whose tasks are completed
+    Github {
+        /// Repository as owner/name
+        #[arg(long, value_parser = RepoName::from_str)]
+        repo: RepoName,
+    },
+    /// Mirror your tasks to Markdown files, one per client, and apply the edits made in them
+    Markdown {
+        /// Folder of the files, e.g. one in your Obsidian vault; created if missing
+        #[arg(long)]
+        dir: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GitCommands {
+    /// Complete the tasks that recent commits close, recording each commit on its task
+    Scan {
+        /// Repository to read the commits of
+        #[arg(long, default_value = ".")]
+        repo: std::path::PathBuf,
+        /// Commit or range to read the log of, e.g. main..feature
+        #[arg(default_value = "HEAD")]
+        revision: String,
+        /// Read at most this many commits
+        #[arg(short = 'n', long, default_value = "100")]
+        limit: usize,
+    },
+    /// Install a post-commit hook that runs `git scan` on every new commit
+    Hook {
+        /// Repository to install the hook in
+        #[arg(long, default_value = ".")]
+        repo: std::path::PathBuf,
+        /// Replace a post-commit hook that was not installed by todo-cli
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CalendarCommands {
+    /// Create a read-only key and print the feed URL to subscribe to; it is shown once
+    Url {
+        /// Address calendar apps reach `todo-cli serve` at; api.url or http://127.0.0.1:8080 by default
+        #[arg(long)]
+        server: Option<String>,
+        /// Expire the feed after this long (e.g. 365d); it never expires by default
+        #[arg(long, value_parser = parse_duration)]
+        expires: Option<std::time::Duration>,
+    },
+    /// Create a key for a CalDAV app such as Tasks.org and print its account details; shown once
+    Caldav {
+        /// Address the app reaches `todo-cli serve` at; api.url or http://127.0.0.1:8080 by default
+        #[arg(long)]
+        server: Option<String>,
+        /// Expire the key after this long (e.g. 365d); it never expires by default
+        #[arg(long, value_parser = parse_duration)]
+        expires: Option<std::time::Duration>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DigestCommands {
+    /// Email me the digest every day
+    Enable {
+        /// Time of day to send it, in the server's timezone
+        #[arg(long, default_value = "07:00", value_parser = parse_time_of_day)]
+        at: NaiveTime,
+    },
+    /// Stop the daily email
+    Disable,
+    /// Show whether and when the digest is sent
+    Status,
+    /// Email me today's digest now
+    Send,
+    /// Send every digest that is due; run this every few minutes, e.g. from cron
+    Run,
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleCommands {
+    /// Create a task each time the schedule fires
+    Add {
+        /// Title of the tasks created
+        title: String,
+        /// When, in the server's timezone (e.g. "monday 09:00", "weekday 8:30", "month on 1 10:00")
+        #[arg(long, required_unless_present = "cron", conflicts_with = "cron")]
+        every: Option<String>,
+        /// When, as five cron fields (e.g. "0 9 * * mon")
+        #[arg(long)]
+        cron: Option<String>,
+        /// Description of the tasks created
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Priority of the tasks created
+        #[arg(short, long, default_value = "medium")]
+        priority: TaskPriority,
+        /// GTD context of the tasks created (e.g. @home)
+        #[arg(long)]
+        context: Option<String>,
+        /// Create the tasks of this many upcoming firings ahead of time, each
+        /// due when it fires, so they show in agenda views (0 to 20)
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(i16).range(0..=20))]
+        ahead: i16,
+    },
+    /// List my schedules, next to fire first
+    List,
+    /// Delete a schedule; the tasks it created stay
+    Remove {
+        /// Schedule ID or the prefix shown by `schedule list`
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DaemonCommands {
+    /// Show whether the daemon is running
+    Status,
+    /// Stop the running daemon
+    Stop,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SyncSide {
+    Local,
+    Remote,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -196,54 +1008,97 @@ impl fmt::Display for TaskPriority {
 }
 
 #[derive(Clone, ValueEnum)]
-pub enum TaskStatus {
-    Pending,
-    InProgress,
-    Completed,
+pub enum StatsPeriod {
+    Day,
+    Week,
+    Month,
+    Year,
+    All,
 }
 
-impl fmt::Display for TaskStatus {
+impl fmt::Display for StatsPeriod {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TaskStatus::Pending => write!(f, "pending"),
-            TaskStatus::InProgress => write!(f, "in_progress"),
-            TaskStatus::Completed => write!(f, "completed"),
+            StatsPeriod::Day => write!(f, "day"),
+            StatsPeriod::Week => write!(f, "week"),
+            StatsPeriod::Month => write!(f, "month"),
+            StatsPeriod::Year => write!(f, "year"),
+            StatsPeriod::All => write!(f, "all"),
         }
     }
 }
 
-#[derive(Clone, ValueEnum)]
-pub enum ExportFormat {
-    Json,
-    Csv,
+/// Parse an effort estimate such as `2h` into whole minutes
+fn parse_estimate(input: &str) -> Result<i32, String> {
+    let minutes = parse_duration(input)?.as_secs() / 60;
+    if minutes == 0 {
+        return Err("Estimate must be at least one minute".to_string());
+    }
+    i32::try_from(minutes).map_err(|_| "Estimate is too large".to_string())
 }
 
-impl fmt::Display for ExportFormat {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ExportFormat::Json => write!(f, "json"),
-            ExportFormat::Csv => write!(f, "csv"),
-        }
+fn parse_month(input: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", input.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid month '{}'. Use YYYY-MM", input))
+}
+
+impl StatsPeriod {
+    /// Start of the current calendar period in local time, or `None` for all time
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        let today = calendar::today();
+        let first_day = match self {
+            StatsPeriod::Day => today,
+            StatsPeriod::Week => today - Duration::days(today.weekday().num_days_from_monday() as i64),
+            StatsPeriod::Month => today.with_day(1)?,
+            StatsPeriod::Year => today.with_ordinal(1)?,
+            StatsPeriod::All => return None,
+        };
+
+        Some(calendar::start_of_day(first_day))
     }
 }
 
-#[derive(Clone, ValueEnum)]
-pub enum StatsPeriod {
-    Day,
+/// Days a chart looks back over, ending today
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ChartPeriod {
     Week,
     Month,
+    Quarter,
     Year,
-    All,
 }
 
-impl fmt::Display for StatsPeriod {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl ChartPeriod {
+    pub fn days(&self) -> u32 {
         match self {
-            StatsPeriod::Day => write!(f, "day"),
-            StatsPeriod::Week => write!(f, "week"),
-            StatsPeriod::Month => write!(f, "month"),
-            StatsPeriod::Year => write!(f, "year"),
-            StatsPeriod::All => write!(f, "all"),
+            ChartPeriod::Week => 7,
+            ChartPeriod::Month => 30,
+            ChartPeriod::Quarter => 91,
+            ChartPeriod::Year => 365,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn test_watch_interval() {
+        let args = Args::try_parse_from(["todo-cli", "task", "list", "--watch", "--interval", "2s"]).unwrap();
+        match args.command {
+            Commands::Task { command: TaskCommands::List(list) } => {
+                assert!(list.watch);
+                assert_eq!(list.interval, std::time::Duration::from_secs(2));
+            }
+            _ => panic!("expected task list"),
+        }
+
+        assert!(Args::try_parse_from(["todo-cli", "task", "list", "--watch", "--json"]).is_err());
+    }
+}
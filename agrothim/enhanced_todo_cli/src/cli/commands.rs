@@ -1,94 +1,590 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use anyhow::{Context, Result};
-use console::{style, Emoji};
-use dialoguer::{Input, Password, Confirm, theme::ColorfulTheme};
-
-use tracing::{error, info, warn};
+use std::time::{Duration, Instant};
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate, Utc};
+use console::{style, Emoji, Term};
+use dialoguer::{Input, Password, Confirm, Select, theme::ColorfulTheme};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+
+use tokio_stream::StreamExt;
+use tracing::{debug_span, error, info, warn};
+use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 use uuid::Uuid;
 
 use crate::{
+    api::{self, AppState},
     cli::args::*,
-    services::{AuthService, UserService, TaskService, UserServiceError, AuthServiceError},
+    error::{is_unreachable, Diagnose},
+    services::{OfflineQueue, QueuedMutation, GitHubService, GitService, install_hook, shell_quote, MarkdownSyncService, DigestService, ReminderService, DesktopChannel, EmailChannel, SlackChannel, DaemonError, DaemonService, PidFile, ScheduleService, ScheduleError, MaintenanceService, running_pid, stop_daemon, shutdown_signal, AuthService, Backup, BackupService, SeedService, DEMO_PASSWORD, DataExportService, EventBus, WorkspaceService, UserService, TaskService, TaskServiceError, TimeService, WebhookService, PasswordResetService, EmailVerificationService, OAuthService, Mailer, OutboxMailer, SendmailMailer, SmtpMailer, UserServiceError, AuthServiceError, PasswordResetError, WorkspaceServiceError, EmailVerificationError, Scope, Side, SyncService, DEFAULT_MAIL_FROM, TOKEN_ENV_VAR},
     models::{
         user::{StoreUserRequest, UserResponse},
-        task::{StoreTaskRequest, UpdateTaskRequest, TaskFilter, TaskPriority as ModelTaskPriority, TaskStatus as ModelTaskStatus},
+        user_identity::OAuthProvider,
+        task::{MovePosition, StatisticsFilter, StoreTaskRequest, Task, TaskGrouping, UpdateTaskRequest, TaskFilter, TaskPriority as ModelTaskPriority, TaskStatus as ModelTaskStatus},
+        task_ref::{IdStyle, TaskRef},
+        task_column::TaskColumn,
+        issue_link::RepoName,
+        calendar::{self, UserTimezone},
+        status::StatusWorkflow,
+        billing::{month_bounds, BillableReport},
+        location::{GeoPoint, NearFilter},
+        password_strength::PasswordStrength,
+        quota::{QuotaLevel, QuotaThresholds, QuotaWarning},
+        webhook::{StoreWebhookRequest, WebhookEvent},
+        schedule::{CronSchedule, StoreScheduleRequest},
+        workspace::StoreWorkspaceRequest,
+    },
+    utils::{
+        formatting::{format_billable_report, format_burndown, format_forecast, format_heatmap, format_weekly_report, format_bulk_status_report, format_context_counts, format_task_statistics, format_workspace_statistics, format_task_table, format_task_table_for, format_task_table_highlighted, format_task_delimited, format_date, format_day, format_duration, format_task_detail, format_time_report, format_workload, format_sync_conflict, set_date_format, DEFAULT_DATE_FORMAT},
+        config::{Config, ConfigCheck},
+        projection::FieldProjection,
+        pager::Pager,
+        settings::Settings,
+        timing::{self, TimingLayer, TIMING_TARGET},
     },
-    utils::formatting::{format_task_table, format_date, format_task_detail},
-    database::{Database, repositories::{PostgresUserRepository, PostgresTaskRepository}},
+    database::{doctor, repositories::ApiClient, ChangeListener, Database, PoolConfig, Repositories, RepositoryFactory},
+    export::{archive::Archive, Format, FormatRegistry, TaskRecord},
 };
+#[cfg(feature = "memory")]
+use crate::{cli::tutorial::Tutorial, database::DatabasePool};
 
-static CHECKMARK: Emoji<'_, '_> = Emoji("✅ ", "");
-static CROSS: Emoji<'_, '_> = Emoji("❌ ", "");
-static WARNING: Emoji<'_, '_> = Emoji("⚠️ ", "");
-static INFO: Emoji<'_, '_> = Emoji("ℹ️ ", "");
-static ROCKET: Emoji<'_, '_> = Emoji("🚀 ", "");
+pub(crate) static CHECKMARK: Emoji<'_, '_> = Emoji("✅ ", "");
+pub(crate) static CROSS: Emoji<'_, '_> = Emoji("❌ ", "");
+pub(crate) static WARNING: Emoji<'_, '_> = Emoji("⚠️ ", "");
+pub(crate) static INFO: Emoji<'_, '_> = Emoji("ℹ️ ", "");
+pub(crate) static ROCKET: Emoji<'_, '_> = Emoji("🚀 ", "");
 
 pub struct CliApp {
+    /// `None` in remote mode (`config set api.url`), where the server owns the database
+    database: Option<Database>,
     auth_service: Arc<AuthService>,
     user_service: Arc<UserService>,
     task_service: Arc<TaskService>,
+    backup_service: Arc<BackupService>,
+    seed_service: Arc<SeedService>,
+    // Services below store their data in tables only the PostgreSQL schema
+    // has; they are `None` on SQLite and reached through `postgres_only`
+    time_service: Option<Arc<TimeService>>,
+    webhook_service: Option<Arc<WebhookService>>,
+    password_reset_service: Option<Arc<PasswordResetService>>,
+    email_verification_service: Option<Arc<EmailVerificationService>>,
+    oauth_service: Option<Arc<OAuthService>>,
+    data_export_service: Option<Arc<DataExportService>>,
+    workspace_service: Option<Arc<WorkspaceService>>,
+    github_service: Option<Arc<GitHubService>>,
+    markdown_sync_service: Arc<MarkdownSyncService>,
+    git_service: Option<Arc<GitService>>,
+    digest_service: Option<Arc<DigestService>>,
+    schedule_service: Option<Arc<ScheduleService>>,
+    maintenance_service: Arc<MaintenanceService>,
+    reminder_service: Arc<ReminderService>,
+    event_bus: EventBus,
+    /// Set in local-first mode (`DATABASE_LOCAL_URL`), where commands use a
+    /// SQLite copy that this keeps in line with DATABASE_URL
+    sync_service: Option<Arc<SyncService>>,
+    /// Directory holding `config.json`, which stays put when the session directory is moved
+    config_dir: PathBuf,
+    /// The `timezone` setting: commands cut days in it, and `serve` in it
+    /// for requests that name no timezone
+    timezone: UserTimezone,
+}
+
+/// One line per check, with the fix under each failed one
+fn print_checks(checks: &[ConfigCheck]) {
+    for check in checks {
+        let mark = if check.passed { CHECKMARK } else { CROSS };
+        let detail = if check.passed { style(&check.detail).dim() } else { style(&check.detail).red() };
+        println!("{}{}: {}", mark, style(check.name).bold(), detail);
+        if let (false, Some(fix)) = (check.passed, &check.fix) {
+            println!("     {} {}", style("fix:").cyan().bold(), fix.replace('\n', "\n          "));
+        }
+    }
+}
+
+/// Whether `command` needs the schema this build expects. The others are how
+/// an outdated database gets fixed, and a server given `--migrations-dir`
+/// reports pending migrations through its readiness probe instead.
+fn needs_current_schema(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Db { command: DbCommands::Migrate | DbCommands::Doctor }
+            | Commands::Config { .. }
+            | Commands::Serve { migrations_dir: Some(_), .. }
+    )
+}
+
+/// Longest a command waits for the sync after it before giving up until the
+/// next one; the remote database may be unreachable
+const SYNC_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often `serve` syncs in local-first mode
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long an offline command waits for the database before queueing its
+/// change instead
+const OFFLINE_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Where `calendar url`, `calendar caldav` and `webhook inbound` point other
+/// apps when api.url is not set: the address `serve` listens on by default
+const DEFAULT_SERVER_URL: &str = "http://127.0.0.1:8080";
+
+/// Name of the API keys `calendar url` creates, as `auth apikey list` shows them
+const CALENDAR_KEY_NAME: &str = "Calendar feed";
+/// Name of the API keys `calendar caldav` creates
+const CALDAV_KEY_NAME: &str = "CalDAV";
+/// Name of the API keys `webhook inbound` creates
+const INBOUND_KEY_NAME: &str = "Inbound webhook";
+
+/// Whether `command` can be queued in the offline journal when the database
+/// or API cannot be reached, to be replayed by `todo-cli sync`
+fn queues_offline(command: &TaskCommands) -> bool {
+    matches!(
+        command,
+        TaskCommands::Add { .. } | TaskCommands::Update { .. } | TaskCommands::Complete { .. } | TaskCommands::Uncomplete { .. }
+    )
+}
+
+/// Whether `command` syncs the session user's tasks when it is done
+fn syncs_after(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Sync { .. } | Commands::Serve { .. } | Commands::Daemon { .. } | Commands::Config { .. } | Commands::Db { .. }
+    )
+}
+
+/// The service, or an error saying `feature` needs the PostgreSQL backend
+fn postgres_only<'a, T>(service: &'a Option<Arc<T>>, feature: &str) -> Result<&'a Arc<T>> {
+    service
+        .as_ref()
+        .with_context(|| format!("{} need a PostgreSQL database; DATABASE_URL points at SQLite or memory://", feature))
 }
 
 impl CliApp {
-    pub async fn new() -> Result<Self> {
+    pub async fn new(command: &Commands) -> Result<Self> {
         dotenv::dotenv().ok();
         
-        let database_url = std::env::var("DATABASE_URL")
-            .context("DATABASE_URL must be set")?;
-        
-        let jwt_secret = std::env::var("JWT_SECRET")
-            .unwrap_or_else(|_| "default-secret-change-in-production".to_string());
+        let config_dir = std::env::current_dir()
+            .context("Failed to determine current directory")?
+            .join(".todo-cli");
+        let settings = Settings::load(&config_dir)
+            .context("Failed to load settings")?;
+        // Dates are shown in the user's format
+        set_date_format(settings.date_format().unwrap_or(DEFAULT_DATE_FORMAT));
+        let remote = settings.api_url().map(ApiClient::new).transpose()?.map(Arc::new);
+
+        let queueable = matches!(command, Commands::Task { command } if queues_offline(command));
+        let mut pool_config = PoolConfig::from_env()?;
+        if queueable {
+            // Unless configured otherwise, give up on a database that is down
+            // quickly and queue the change instead
+            pool_config.acquire_timeout.get_or_insert(OFFLINE_ACQUIRE_TIMEOUT);
+        }
 
-        // Initialize database and repositories
-        let db = Database::from_url(&database_url).await
-            .context("Failed to initialize database")?;
-        
-        let pool = db.pool();
-        let user_repo = Arc::new(PostgresUserRepository::new(pool.clone()));
-        let task_repo = Arc::new(PostgresTaskRepository::new(pool.clone()));
+        let (db, Repositories { users: user_repo, tasks: task_repo, postgres }, sync_service) = match &remote {
+            // Remote mode: every repository call is a request to the server
+            Some(client) => (None, RepositoryFactory::remote(client), None),
+            None => match Self::open_database(&settings, &pool_config).await {
+                Ok((db, repositories, sync_service)) => {
+                    if settings.auto_migrate() {
+                        db.migrate().await?;
+                    } else if needs_current_schema(command) {
+                        db.verify_schema_version().await?;
+                    }
+                    (Some(db), repositories, sync_service)
+                }
+                // The change gets queued when the database is still down
+                // once the command runs
+                Err(e) if queueable && is_unreachable(e.as_ref()) => {
+                    warn!("Database unreachable, working offline: {:#}", e);
+                    let (db, repositories) = Self::open_offline(&pool_config)?;
+                    (Some(db), repositories, None)
+                }
+                Err(e) => return Err(e),
+            },
+        };
+
+        // Token lifetimes and the session location come from `config set auth.*`
+        let mut auth_config = settings.auth_config();
+        let session_dir = auth_config.session_dir.get_or_insert_with(|| config_dir.clone()).clone();
+
+        // SMTP_URL wins over a mail command; without either, emails land in
+        // the outbox next to the session
+        let mail_from = std::env::var("MAIL_FROM")
+            .unwrap_or_else(|_| DEFAULT_MAIL_FROM.to_string());
+        let mailer: Arc<dyn Mailer> = match (std::env::var("SMTP_URL"), std::env::var("MAIL_COMMAND")) {
+            (Ok(url), _) => Arc::new(SmtpMailer::new(&url, &mail_from).context("Invalid SMTP_URL or MAIL_FROM")?),
+            (Err(_), Ok(command)) => Arc::new(SendmailMailer::new(command, mail_from)),
+            (Err(_), Err(_)) => Arc::new(OutboxMailer::new(session_dir.join("outbox"), mail_from)),
+        };
 
         // Initialize services
-        let user_service = Arc::new(UserService::new(user_repo));
-        let task_service = Arc::new(TaskService::new(task_repo));
-        let auth_service = Arc::new(AuthService::new(user_service.clone(), &jwt_secret, None)?);
+        let email_verification_service = postgres.as_ref().map(|repos| {
+            Arc::new(EmailVerificationService::new(
+                user_repo.clone(),
+                repos.email_verification.clone(),
+                mailer.clone(),
+            ))
+        });
+        let mut user_service = UserService::new(user_repo.clone());
+        if let Some(email_verification_service) = &email_verification_service {
+            user_service = user_service.with_email_verification(email_verification_service.clone());
+        }
+        let user_service = Arc::new(user_service);
+        let oauth_service = postgres
+            .as_ref()
+            .map(|repos| Arc::new(OAuthService::new(user_service.clone(), repos.identity.clone())));
+        let webhook_service = postgres.as_ref().map(|repos| {
+            Arc::new(WebhookService::new(repos.webhook.clone()).with_user_service(user_service.clone()))
+        });
+        let event_bus = EventBus::default();
+        let seed_service = Arc::new(SeedService::new(user_repo.clone(), task_repo.clone()));
+        let mut task_service = TaskService::new(task_repo).with_event_bus(event_bus.clone());
+        if let (Some(repos), Some(webhook_service)) = (&postgres, &webhook_service) {
+            task_service = task_service
+                .with_status_repository(repos.status.clone())
+                .with_workspace_repository(repos.workspace.clone())
+                .with_share_repository(repos.task_share.clone())
+                .with_event_repository(repos.task_event.clone())
+                .with_webhook_service(webhook_service.clone());
+        }
+        let task_service = Arc::new(task_service);
+        let backup_service = Arc::new(BackupService::new(user_repo, task_service.clone()));
+        let time_service = postgres
+            .as_ref()
+            .map(|repos| Arc::new(TimeService::new(task_service.clone(), repos.time_entry.clone())));
+        let data_export_service = postgres.as_ref().zip(webhook_service.as_ref()).map(|(repos, webhook_service)| {
+            Arc::new(DataExportService::new(
+                user_service.clone(),
+                task_service.clone(),
+                webhook_service.clone(),
+                repos.time_entry.clone(),
+                repos.session.clone(),
+                repos.api_key.clone(),
+                repos.identity.clone(),
+            ))
+        });
+        let workspace_service = postgres.as_ref().map(|repos| {
+            Arc::new(WorkspaceService::new(
+                user_service.clone(),
+                task_service.clone(),
+                repos.workspace.clone(),
+                repos.invitation.clone(),
+            ))
+        });
+        let github_service = postgres.as_ref().map(|repos| {
+            let mut github_service = GitHubService::new(task_service.clone(), repos.issue_link.clone())
+                .with_token(settings.github_token().map(str::to_string));
+            if let Some(api_url) = settings.github_api_url() {
+                github_service = github_service.with_api_url(api_url);
+            }
+            Arc::new(github_service)
+        });
+        let markdown_sync_service = Arc::new(MarkdownSyncService::new(task_service.clone()));
+        let git_service = postgres
+            .as_ref()
+            .map(|repos| Arc::new(GitService::new(task_service.clone(), repos.commit_link.clone())));
+        let digest_service = postgres.as_ref().map(|repos| {
+            Arc::new(DigestService::new(
+                task_service.clone(),
+                user_service.clone(),
+                repos.digest_subscription.clone(),
+                mailer.clone(),
+            ))
+        });
+        let schedule_service = postgres
+            .as_ref()
+            .map(|repos| Arc::new(ScheduleService::new(task_service.clone(), repos.schedule.clone())));
+        let maintenance_service = Arc::new(MaintenanceService::new(
+            task_service.clone(),
+            &session_dir,
+            settings.maintenance_policy(),
+        ));
+        // Which channels remind is up to `config set reminders.*`
+        let reminder_config = settings.reminder_config();
+        let mut reminder_service =
+            ReminderService::new(task_service.clone(), &session_dir).with_due_soon(reminder_config.due_soon);
+        if reminder_config.desktop {
+            reminder_service = reminder_service.with_channel(Arc::new(DesktopChannel));
+        }
+        if reminder_config.email {
+            reminder_service = reminder_service.with_channel(Arc::new(EmailChannel::new(mailer.clone())));
+        }
+        if let Some(url) = reminder_config.slack {
+            reminder_service = reminder_service.with_channel(Arc::new(SlackChannel::new(url)));
+        }
+        let reminder_service = Arc::new(reminder_service);
+        let mut auth_service = AuthService::with_config(user_service.clone(), auth_config)?;
+        if let Some(client) = remote {
+            auth_service = auth_service.with_remote(client);
+        }
+        if let (Some(repos), Some(webhook_service)) = (&postgres, &webhook_service) {
+            auth_service = auth_service
+                .with_webhook_service(webhook_service.clone())
+                .with_api_keys(repos.api_key.clone())
+                .with_sessions(repos.session.clone())
+                .with_token_revocation(repos.revoked_token.clone());
+        }
+        let auth_service = Arc::new(auth_service);
+        let password_reset_service = postgres.as_ref().zip(webhook_service.as_ref()).map(|(repos, webhook_service)| {
+            Arc::new(
+                PasswordResetService::new(user_service.clone(), repos.password_reset.clone(), mailer)
+                    .with_webhook_service(webhook_service.clone())
+                    .with_session_revocation(auth_service.clone()),
+            )
+        });
 
         Ok(Self {
+            database: db,
             auth_service,
             user_service,
             task_service,
+            backup_service,
+            seed_service,
+            time_service,
+            webhook_service,
+            password_reset_service,
+            email_verification_service,
+            oauth_service,
+            data_export_service,
+            workspace_service,
+            github_service,
+            markdown_sync_service,
+            git_service,
+            digest_service,
+            schedule_service,
+            maintenance_service,
+            reminder_service,
+            event_bus,
+            sync_service,
+            config_dir,
+            timezone: settings.timezone(),
         })
     }
 
-    pub async fn run(&self, args: Args) -> Result<()> {
-        // Setup logging
-        if args.verbose {
-            tracing_subscriber::fmt()
-                .with_env_filter("debug")
-                .init();
-        } else {
-            tracing_subscriber::fmt()
-                .with_env_filter("info")
-                .init();
+    /// Open DATABASE_URL, or in local-first mode (`DATABASE_LOCAL_URL`) the
+    /// SQLite copy with a sync service for DATABASE_URL
+    async fn open_database(settings: &Settings, pool_config: &PoolConfig) -> Result<(Database, Repositories, Option<Arc<SyncService>>)> {
+        let database_url = std::env::var("DATABASE_URL")
+            .context("DATABASE_URL must be set")?;
+        let read_url = std::env::var("DATABASE_READ_URL").ok();
+        let local_url = std::env::var("DATABASE_LOCAL_URL").ok();
+
+        // The URL scheme picks the backend; SQLite and memory:// hold users
+        // and tasks only, so the other services are left out for them
+        match &local_url {
+            None => {
+                let (db, repositories) = RepositoryFactory::connect(&database_url, read_url.as_deref(), pool_config).await
+                    .context("Failed to initialize database")?;
+                Ok((db, repositories, None))
+            }
+            // Local-first: everything runs on the SQLite copy, and the remote
+            // database is only connected to when syncing
+            Some(local_url) => {
+                let (db, local) = RepositoryFactory::connect(local_url, None, pool_config).await
+                    .context("Failed to open the local database")?;
+                let state = RepositoryFactory::sync_state(&db)
+                    .context("DATABASE_LOCAL_URL must be a sqlite:// URL")?;
+                let remote = RepositoryFactory::repositories(&Database::connect_lazy(&database_url, pool_config)?);
+                let sync_service = SyncService::new(local.users.clone(), local.tasks.clone(), remote.users, remote.tasks, state)
+                    .with_conflict_strategy(settings.conflict_strategy());
+                Ok((db, local, Some(Arc::new(sync_service))))
+            }
         }
+    }
+
+    /// DATABASE_URL connected to only when first used, for a command that
+    /// queues its change while the database is down
+    fn open_offline(pool_config: &PoolConfig) -> Result<(Database, Repositories)> {
+        let database_url = std::env::var("DATABASE_URL")
+            .context("DATABASE_URL must be set")?;
+        let db = Database::connect_lazy(&database_url, pool_config)?;
+        let repositories = RepositoryFactory::repositories(&db);
+        Ok((db, repositories))
+    }
+
+    /// Journal of task changes made while the database or API was unreachable
+    fn offline_queue(&self) -> OfflineQueue {
+        OfflineQueue::new(self.auth_service.session_dir())
+    }
+
+    /// Queue a change that could not reach the database, for `todo-cli sync`
+    fn queue_offline(&self, user: &UserResponse, mutation: QueuedMutation) -> Result<()> {
+        self.offline_queue().push(&user.id, mutation)?;
+        println!(
+            "{} Database unreachable; the change was saved offline. Apply it with {}",
+            WARNING,
+            style("todo-cli sync").cyan()
+        );
+        Ok(())
+    }
+
+    /// The database, which only the server has in remote mode
+    fn database(&self) -> Result<&Database> {
+        self.database
+            .as_ref()
+            .context("This command needs the database, but the CLI works through the API at `api.url`; run it on the server or `todo-cli config unset api.url`")
+    }
+
+    fn time_service(&self) -> Result<&Arc<TimeService>> {
+        postgres_only(&self.time_service, "Time tracking and reports")
+    }
+
+    fn webhook_service(&self) -> Result<&Arc<WebhookService>> {
+        postgres_only(&self.webhook_service, "Webhooks")
+    }
+
+    fn password_reset_service(&self) -> Result<&Arc<PasswordResetService>> {
+        postgres_only(&self.password_reset_service, "Password resets")
+    }
+
+    fn email_verification_service(&self) -> Result<&Arc<EmailVerificationService>> {
+        postgres_only(&self.email_verification_service, "Email verification links")
+    }
+
+    fn oauth_service(&self) -> Result<&Arc<OAuthService>> {
+        postgres_only(&self.oauth_service, "OAuth logins")
+    }
+
+    fn data_export_service(&self) -> Result<&Arc<DataExportService>> {
+        postgres_only(&self.data_export_service, "Account exports")
+    }
+
+    fn workspace_service(&self) -> Result<&Arc<WorkspaceService>> {
+        postgres_only(&self.workspace_service, "Workspaces")
+    }
+
+    fn github_service(&self) -> Result<&Arc<GitHubService>> {
+        postgres_only(&self.github_service, "Linked GitHub issues")
+    }
+
+    fn git_service(&self) -> Result<&Arc<GitService>> {
+        postgres_only(&self.git_service, "Completing tasks from commits")
+    }
+
+    fn digest_service(&self) -> Result<&Arc<DigestService>> {
+        postgres_only(&self.digest_service, "Digest emails")
+    }
+
+    fn schedule_service(&self) -> Result<&Arc<ScheduleService>> {
+        postgres_only(&self.schedule_service, "Scheduled tasks")
+    }
+
+    /// Run the command with days ending at the user's midnight
+    pub async fn run(&self, args: Args) -> Result<()> {
+        calendar::with_timezone(self.timezone, self.run_command(args)).await
+    }
 
-        match args.command {
+    async fn run_command(&self, args: Args) -> Result<()> {
+        let started = Instant::now();
+
+        // Setup logging, plus the timing footer if enabled
+        let timing = self
+            .settings()
+            .map(|settings| settings.show_timing())
+            .unwrap_or(false)
+            .then(TimingLayer::new);
+        let log_filter = EnvFilter::new(if args.verbose { "debug" } else { "info" });
+        // Verbose output ends each repository call span with its duration;
+        // the statements run inside it are listed with their row counts
+        let span_events = if args.verbose { FmtSpan::CLOSE } else { FmtSpan::NONE };
+        tracing_subscriber::registry()
+            .with(tracing_subscriber::fmt::layer().with_span_events(span_events).with_filter(log_filter))
+            .with(timing.clone().map(TimingLayer::filtered))
+            .init();
+
+        let fields = args
+            .fields
+            .as_deref()
+            .map(str::parse::<FieldProjection>)
+            .transpose()
+            .context("Invalid --fields value")?;
+        let pager = Pager::new(!args.no_pager && self.settings().map(|settings| settings.pager()).unwrap_or(true));
+
+        let sync_after = syncs_after(&args.command);
+        let result = match args.command {
             Commands::Auth { command } => self.handle_auth_command(command).await,
-            Commands::Task { command } => self.handle_task_command(command).await,
+            Commands::Task { command } => self.handle_task_command(command, fields.as_ref(), &pager).await,
+            Commands::Status { command } => self.handle_status_command(command).await,
+            Commands::Workspace { command } => self.handle_workspace_command(command).await,
+            Commands::Webhook { command } => self.handle_webhook_command(command).await,
+            Commands::Account { command } => self.handle_account_command(command).await,
+            Commands::Admin { command } => self.handle_admin_command(command).await,
             Commands::Config { command } => self.handle_config_command(command).await,
-            Commands::Export { format, output } => self.handle_export_command(format, output).await,
-            Commands::Import { file, merge } => self.handle_import_command(file, merge).await,
+            Commands::Db { command } => self.handle_db_command(command).await,
+            Commands::Export { format, output, archive: false } => self.handle_export_command(format, output, fields.as_ref()).await,
+            Commands::Export { output, archive: true, .. } => self.handle_archive_export(output, fields.as_ref()).await,
+            Commands::Import { file, format, archive: false, merge } => self.handle_import_command(file, format, merge).await,
+            Commands::Import { file, archive: true, merge, .. } => self.handle_archive_import(file, merge).await,
+            Commands::Report { command } => self.handle_report_command(command, fields.as_ref()).await,
             Commands::Search { query, in_description } => self.handle_search_command(query, in_description).await,
-            Commands::Stats { period } => self.handle_stats_command(period).await,
+            Commands::Stats { period, workspace: Some(workspace) } => self.handle_workspace_stats(period, workspace).await,
+            Commands::Stats { period, workspace: None } => self.handle_stats_command(period).await,
+            Commands::Activity { workspace, since, limit } => self.handle_activity(workspace, since, limit, &pager).await,
+            Commands::Sync { command: None } => self.handle_sync().await,
+            Commands::Sync { command: Some(SyncCommands::Conflicts { list, keep }) } => {
+                self.handle_sync_conflicts(list, keep).await
+            }
+            Commands::Sync { command: Some(SyncCommands::Github { repo }) } => self.handle_sync_github(repo).await,
+            Commands::Sync { command: Some(SyncCommands::Markdown { dir }) } => self.handle_sync_markdown(&dir).await,
+            Commands::Git { command: GitCommands::Scan { repo, revision, limit } } => {
+                self.handle_git_scan(&repo, &revision, limit).await
+            }
+            Commands::Git { command: GitCommands::Hook { repo, force } } => self.handle_git_hook(&repo, force).await,
+            Commands::Serve { host, port, migrations_dir } => self.handle_serve(host, port, migrations_dir).await,
+            Commands::Calendar { command: CalendarCommands::Url { server, expires } } => {
+                self.handle_calendar_url(server, expires).await
+            }
+            Commands::Calendar { command: CalendarCommands::Caldav { server, expires } } => {
+                self.handle_calendar_caldav(server, expires).await
+            }
+            Commands::Digest { command } => self.handle_digest_command(command).await,
+            Commands::Schedule { command } => self.handle_schedule_command(command).await,
+            Commands::Tick => self.handle_tick().await,
+            Commands::Maintain { dry_run } => self.handle_maintain(dry_run).await,
+            Commands::Remind { dry_run } => self.handle_remind(dry_run).await,
+            Commands::Daemon { command: None, interval, detach } => self.handle_daemon(interval, detach).await,
+            Commands::Daemon { command: Some(command), .. } => self.handle_daemon_command(command),
+            #[cfg(feature = "memory")]
+            Commands::Tutorial => Tutorial::new().run().await,
+        };
+
+        // Webhooks are delivered in the background; let them finish before the process exits
+        if let Some(webhook_service) = &self.webhook_service {
+            webhook_service.flush().await;
         }
+        result?;
+
+        if sync_after {
+            self.sync_session_user().await;
+        }
+
+        if let Some(footer) = timing.and_then(|timing| timing.summary().footer(started.elapsed())) {
+            println!("{}", style(footer).dim());
+        }
+        Ok(())
     }
 
     // Authentication Commands
     async fn handle_auth_command(&self, command: AuthCommands) -> Result<()> {
         match command {
             AuthCommands::Register => self.handle_register().await,
-            AuthCommands::Login => self.handle_login().await,
+            AuthCommands::Login { provider: None } => self.handle_login().await,
+            AuthCommands::Login { provider: Some(provider) } => self.handle_provider_login(provider).await,
+            AuthCommands::Link { provider } => self.handle_link_provider(provider).await,
             AuthCommands::Logout => self.handle_logout().await,
             AuthCommands::Status => self.handle_auth_status().await,
+            AuthCommands::ChangePassword => self.handle_change_password().await,
+            AuthCommands::Update { username } => self.handle_update_profile(username).await,
+            AuthCommands::ForgotPassword { email } => self.handle_forgot_password(email).await,
+            AuthCommands::ResetPassword { token } => self.handle_reset_password(token).await,
+            AuthCommands::Verify { token } => self.handle_verify_email(token).await,
+            AuthCommands::ResendVerification { email } => self.handle_resend_verification(email).await,
+            AuthCommands::Token { scopes, expires } => self.handle_issue_token(scopes, expires).await,
+            AuthCommands::Sessions { command } => self.handle_session_command(command).await,
+            AuthCommands::ApiKey { command } => self.handle_api_key_command(command).await,
         }
     }
 
@@ -121,17 +617,7 @@ impl CliApp {
             })
             .interact_text()?;
 
-        let password: String = Password::with_theme(&theme)
-            .with_prompt("Password")
-            .with_confirmation("Confirm password", "Passwords don't match")
-            .validate_with(|input: &String| -> Result<(), &str> {
-                if input.len() < 8 {
-                    Err("Password must be at least 8 characters")
-                } else {
-                    Ok(())
-                }
-            })
-            .interact()?;
+        let password = prompt_new_password(&theme, "Password", &[&username, &email])?;
 
         let request = StoreUserRequest::new(username, email, password)
             .context("Failed to create user request")?;
@@ -141,6 +627,12 @@ impl CliApp {
                 println!("{} User registered successfully!", CHECKMARK);
                 println!("Username: {}", style(&user.username).green());
                 println!("Email: {}", style(&user.email).green());
+                println!(
+                    "{} Check your inbox and run {} before logging in",
+                    INFO,
+                    style("todo-cli auth verify <token>").cyan()
+                );
+                self.print_outbox_hint();
                 info!("User {} registered successfully", user.username);
             }
             Err(UserServiceError::UsernameExists { username }) => {
@@ -150,7 +642,7 @@ impl CliApp {
                 println!("{} Email '{}' already exists", CROSS, style(email).red());
             }
             Err(e) => {
-                println!("{} Registration failed: {}", CROSS, style(&e).red());
+                println!("{}", e.diagnose().render("Registration failed"));
                 error!("Registration failed: {}", e);
             }
         }
@@ -171,6 +663,13 @@ impl CliApp {
             .with_prompt("Password")
             .interact()?;
 
+        // An account made elsewhere is not in the local copy until fetched
+        if let Some(sync_service) = &self.sync_service {
+            if let Err(e) = sync_service.fetch_account(&identifier).await {
+                warn!("Could not fetch the account from the remote database: {}", e);
+            }
+        }
+
         match self.auth_service.login(&identifier, &password).await {
             Ok(response) => {
                 println!("{} Login successful!", CHECKMARK);
@@ -182,8 +681,16 @@ impl CliApp {
                 println!("{} Invalid username/email or password", CROSS);
                 warn!("Login failed for user: {}", identifier);
             }
+            Err(AuthServiceError::UserServiceError(UserServiceError::EmailNotVerified)) => {
+                println!("{} Please verify your email address before logging in", WARNING);
+                println!(
+                    "Run {} with the emailed token, or get a new one with: {}",
+                    style("todo-cli auth verify <token>").cyan(),
+                    style("todo-cli auth resend-verification <email>").cyan()
+                );
+            }
             Err(e) => {
-                println!("{} Login failed: {}", CROSS, style(&e).red());
+                println!("{}", e.diagnose().render("Login failed"));
                 error!("Login failed: {}", e);
             }
         }
@@ -191,6 +698,88 @@ impl CliApp {
         Ok(())
     }
 
+    async fn handle_provider_login(&self, provider: OAuthProvider) -> Result<()> {
+        println!(
+            "{} {}",
+            ROCKET,
+            style(format!("Login with {}", provider.display_name())).bold().cyan()
+        );
+
+        let authorization = match self.oauth_service()?.start(provider).await {
+            Ok(authorization) => authorization,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Login failed"));
+                error!("Failed to start {} login: {}", provider, e);
+                return Ok(());
+            }
+        };
+
+        println!("Open {} in your browser", style(&authorization.verification_uri).cyan().underlined());
+        println!("and enter the code: {}", style(&authorization.user_code).bold().yellow());
+        println!(
+            "{} Waiting for you to approve the login (the code expires in {} minutes)...",
+            INFO,
+            authorization.expires_in / 60
+        );
+
+        let result = match self.oauth_service()?.complete(&authorization).await {
+            Ok(user) => self.auth_service.login_external(user).await.map_err(anyhow::Error::from),
+            Err(e) => Err(e.into()),
+        };
+
+        match result {
+            Ok(response) => {
+                println!("{} Login successful!", CHECKMARK);
+                println!("Welcome, {}!", style(&response.user.username).green());
+                println!("Session expires: {}", style(format_date(&response.expires_at)).yellow());
+                info!("User {} logged in with {}", response.user.username, provider);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Login failed"));
+                error!("{} login failed: {}", provider, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_link_provider(&self, provider: OAuthProvider) -> Result<()> {
+        let user = match Self::authorized(self.auth_service.get_current_user().await) {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let authorization = match self.oauth_service()?.start(provider).await {
+            Ok(authorization) => authorization,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Linking failed"));
+                error!("Failed to start {} login: {}", provider, e);
+                return Ok(());
+            }
+        };
+
+        println!("Open {} in your browser", style(&authorization.verification_uri).cyan().underlined());
+        println!("and enter the code: {}", style(&authorization.user_code).bold().yellow());
+        println!(
+            "{} Waiting for you to approve (the code expires in {} minutes)...",
+            INFO,
+            authorization.expires_in / 60
+        );
+
+        match self.oauth_service()?.complete_for(&authorization, &user).await {
+            Ok(()) => {
+                println!("{} Linked your {} account", CHECKMARK, provider.display_name());
+                println!("Log in with {}", style(format!("todo-cli auth login --provider {}", provider)).cyan());
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Linking failed"));
+                error!("Linking {} failed: {}", provider, e);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn handle_logout(&self) -> Result<()> {
         match self.auth_service.logout().await {
             Ok(_) => {
@@ -198,7 +787,7 @@ impl CliApp {
                 info!("User logged out successfully");
             }
             Err(e) => {
-                println!("{} Logout failed: {}", CROSS, style(&e).red());
+                println!("{}", e.diagnose().render("Logout failed"));
                 error!("Logout failed: {}", e);
             }
         }
@@ -207,20 +796,23 @@ impl CliApp {
     }
 
     async fn handle_auth_status(&self) -> Result<()> {
-        match self.auth_service.get_current_session().await {
-            Ok(Some(user)) => {
+        match self.auth_service.get_current_context().await {
+            Ok(Some(context)) => {
+                let user = &context.user;
+                let scopes: Vec<&str> = context.scopes.iter().map(|scope| scope.as_str()).collect();
                 println!("{} {}", INFO, style("Authentication Status").bold().cyan());
                 println!("Status: {}", style("Authenticated").green());
                 println!("Username: {}", style(&user.username).green());
                 println!("Email: {}", style(&user.email).green());
                 println!("User ID: {}", style(&user.id).dim());
+                println!("Scopes: {}", style(scopes.join(", ")).green());
             }
             Ok(None) => {
                 println!("{} {}", WARNING, style("Not authenticated").yellow());
                 println!("Use {} to login", style("todo-cli auth login").cyan());
             }
             Err(e) => {
-                println!("{} Failed to check authentication status: {}", CROSS, style(&e).red());
+                println!("{}", e.diagnose().render("Failed to check authentication status"));
                 error!("Failed to check auth status: {}", e);
             }
         }
@@ -228,156 +820,801 @@ impl CliApp {
         Ok(())
     }
 
-    // Task Commands
-    async fn handle_task_command(&self, command: TaskCommands) -> Result<()> {
-        // Check if user is authenticated
-        let user = match self.auth_service.get_current_user().await {
-            Ok(user) => user,
-            Err(AuthServiceError::SessionNotFound) => {
-                println!("{} Please login first: {}", WARNING, style("todo-cli auth login").cyan());
-                return Ok(());
-            }
-            Err(e) => {
-                println!("{} Authentication error: {}", CROSS, style(e).red());
-                return Ok(());
-            }
+    async fn handle_change_password(&self) -> Result<()> {
+        let user = match self.authorized_user(Scope::Admin).await {
+            Some(user) => user,
+            None => return Ok(()),
         };
 
-        match command {
-            TaskCommands::Add { title, description, priority, due } => {
-                self.handle_add_task(&user, title, description, priority, due).await
+        let theme = ColorfulTheme::default();
+
+        let current_password: String = Password::with_theme(&theme)
+            .with_prompt("Current password")
+            .interact()?;
+
+        let new_password = prompt_new_password(&theme, "New password", &[&user.username, &user.email])?;
+
+        match self.auth_service.change_password(&current_password, &new_password).await {
+            Ok(user) => {
+                println!("{} Password changed successfully!", CHECKMARK);
+                info!("Password changed for user {}", user.username);
             }
-            TaskCommands::List { status, priority, search, completed, pending } => {
-                self.handle_list_tasks(&user, status, priority, search, completed, pending).await
+            Err(AuthServiceError::AuthenticationFailed) => {
+                println!("{} Current password is incorrect", CROSS);
+                warn!("Password change failed: wrong current password");
             }
-            TaskCommands::Update { id, title, description, priority, due } => {
-                self.handle_update_task(&user, id, title, description, priority, due).await
+            Err(e) => {
+                println!("{}", e.diagnose().render("Password change failed"));
+                error!("Password change failed: {}", e);
             }
-            TaskCommands::Complete { id } => {
-                self.handle_complete_task(&user, id, true).await
+        }
+
+        Ok(())
+    }
+
+    async fn handle_forgot_password(&self, email: String) -> Result<()> {
+        match self.password_reset_service()?.request_reset(&email).await {
+            Ok(()) => {
+                println!(
+                    "{} If an account uses {}, a reset token has been sent to it",
+                    CHECKMARK,
+                    style(&email).cyan()
+                );
+                self.print_outbox_hint();
             }
-            TaskCommands::Uncomplete { id } => {
-                self.handle_complete_task(&user, id, false).await
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to send reset email"));
+                error!("Password reset request failed: {}", e);
             }
-            TaskCommands::Delete { id, force } => {
-                self.handle_delete_task(&user, id, force).await
+        }
+
+        Ok(())
+    }
+
+    async fn handle_verify_email(&self, token: String) -> Result<()> {
+        match self.email_verification_service()?.verify(&token).await {
+            Ok(user) => {
+                println!("{} Email {} verified! Login with: {}", CHECKMARK, style(&user.email).green(), style("todo-cli auth login").cyan());
+            }
+            Err(EmailVerificationError::InvalidToken) => {
+                println!("{} Verification token is invalid, expired or already used", CROSS);
+                println!("Request a new one with: {}", style("todo-cli auth resend-verification <email>").cyan());
             }
-            TaskCommands::Show { id } => {
-                self.handle_show_task(&user, id).await
+            Err(e) => {
+                println!("{}", e.diagnose().render("Email verification failed"));
+                error!("Email verification failed: {}", e);
             }
         }
+
+        Ok(())
     }
 
-    async fn handle_add_task(&self, user: &UserResponse, title: String, description: Option<String>, priority: TaskPriority, due: Option<String>) -> Result<()> {
-        let parsed_due = if let Some(due_str) = due {
-            Some(chrono::NaiveDate::parse_from_str(&due_str, "%Y-%m-%d")
-                .context("Invalid date format. Use YYYY-MM-DD")?
-                .and_hms_opt(23, 59, 59)
-                .unwrap()
-                .and_utc())
-        } else {
-            None
-        };
+    async fn handle_resend_verification(&self, email: String) -> Result<()> {
+        match self.email_verification_service()?.resend(&email).await {
+            Ok(()) => {
+                println!(
+                    "{} If an unverified account uses {}, a new verification token has been sent to it",
+                    CHECKMARK,
+                    style(&email).cyan()
+                );
+                self.print_outbox_hint();
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to send verification email"));
+                error!("Verification resend failed: {}", e);
+            }
+        }
 
-        let model_priority = match priority {
-            TaskPriority::Low => ModelTaskPriority::Low,
-            TaskPriority::Medium => ModelTaskPriority::Medium,
-            TaskPriority::High => ModelTaskPriority::High,
-        };
+        Ok(())
+    }
 
-        let request = StoreTaskRequest {
-            title,
-            description,
-            status: ModelTaskStatus::Pending,
-            priority: model_priority,
-            due_date: parsed_due,
-        };
+    async fn handle_update_profile(&self, username: String) -> Result<()> {
+        if self.authorized_user(Scope::Admin).await.is_none() {
+            return Ok(());
+        }
 
-        match self.task_service.create_task(&user.id, request).await {
-            Ok(task) => {
-                println!("{} Task created successfully!", CHECKMARK);
-                println!("ID: {}", style(&task.id).cyan());
-                println!("Title: {}", style(&task.title).green());
-                if let Some(desc) = &task.description {
-                    println!("Description: {}", style(desc).dim());
-                }
-                println!("Priority: {}", style(format!("{:?}", task.priority)).yellow());
-                info!("Task created: {}", task.title);
+        match self.auth_service.change_username(&username).await {
+            Ok(user) => {
+                println!("{} Username changed to {}", CHECKMARK, style(&user.username).green());
+                info!("Username changed to {}", user.username);
             }
             Err(e) => {
-                println!("{} Failed to create task: {}", CROSS, style(&e).red());
-                error!("Failed to create task: {}", e);
+                println!("{}", e.diagnose().render("Failed to update profile"));
+                error!("Failed to update profile: {}", e);
             }
         }
 
         Ok(())
     }
 
-    async fn handle_list_tasks(&self, user: &UserResponse, status: Option<TaskStatus>, priority: Option<TaskPriority>, search: Option<String>, completed: bool, pending: bool) -> Result<()> {
-        let mut filter = TaskFilter::default();
-
-        // Apply status filters
-        if completed {
-            filter.status = Some(ModelTaskStatus::Completed);
-        } else if pending {
-            filter.status = Some(ModelTaskStatus::Pending);
-        } else if let Some(status) = status {
-            filter.status = Some(match status {
-                TaskStatus::Pending => ModelTaskStatus::Pending,
-                TaskStatus::InProgress => ModelTaskStatus::InProgress,
-                TaskStatus::Completed => ModelTaskStatus::Completed,
-            });
+    /// Point at the outbox when emails are not actually sent
+    fn print_outbox_hint(&self) {
+        if std::env::var("SMTP_URL").is_err() && std::env::var("MAIL_COMMAND").is_err() {
+            println!(
+                "{} Neither SMTP_URL nor MAIL_COMMAND is set; emails are written to {}",
+                INFO,
+                style(self.auth_service.session_dir().join("outbox").display()).dim()
+            );
         }
+    }
 
-        // Apply priority filter
-        if let Some(priority) = priority {
-            filter.priority = Some(match priority {
-                TaskPriority::Low => ModelTaskPriority::Low,
-                TaskPriority::Medium => ModelTaskPriority::Medium,
-                TaskPriority::High => ModelTaskPriority::High,
-            });
-        }
+    async fn handle_reset_password(&self, token: String) -> Result<()> {
+        let new_password = prompt_new_password(&ColorfulTheme::default(), "New password", &[])?;
 
-        // Apply search filter
-        if let Some(search) = search {
-            filter.search_term = Some(search);
+        match self.password_reset_service()?.reset_password(&token, &new_password).await {
+            Ok(user) => {
+                self.auth_service.clear_session_cache();
+                println!("{} Password reset! Login with: {}", CHECKMARK, style("todo-cli auth login").cyan());
+                info!("Password reset for user {}", user.username);
+            }
+            Err(PasswordResetError::InvalidToken) => {
+                println!("{} Reset token is invalid, expired or already used", CROSS);
+                println!("Request a new one with: {}", style("todo-cli auth forgot-password <email>").cyan());
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Password reset failed"));
+                error!("Password reset failed: {}", e);
+            }
         }
 
-        match self.task_service.get_tasks(&user.id, filter).await {
-            Ok(tasks) => {
-                if tasks.is_empty() {
-                    println!("{} No tasks found", INFO);
-                } else {
-                    println!("{} {}", INFO, style(format!("Found {} tasks", tasks.len())).bold());
-                    let table = format_task_table(&tasks);
-                    println!("{}", table);
-                }
+        Ok(())
+    }
+
+    async fn handle_issue_token(&self, scopes: Vec<String>, expires: Duration) -> Result<()> {
+        let scopes = scopes
+            .iter()
+            .map(|scope| scope.parse::<Scope>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::msg)?;
+        let lifetime = chrono::Duration::from_std(expires).context("Token lifetime is too long")?;
+
+        match self.auth_service.issue_token(&scopes, lifetime).await {
+            Ok(issued) => {
+                let scopes: Vec<&str> = issued.scopes.iter().map(|scope| scope.as_str()).collect();
+                println!("{} Token created!", CHECKMARK);
+                println!("Scopes: {}", style(scopes.join(", ")).green());
+                println!("Expires: {}", style(format_date(&issued.expires_at)).yellow());
+                println!("{}", issued.token);
+                println!(
+                    "{}",
+                    style(format!("Use it by setting {}=<token>; it is not shown again", TOKEN_ENV_VAR)).dim()
+                );
+            }
+            Err(AuthServiceError::SessionNotFound) => {
+                println!("{} Please login first: {}", WARNING, style("todo-cli auth login").cyan());
             }
             Err(e) => {
-                println!("{} Failed to list tasks: {}", CROSS, style(&e).red());
-                error!("Failed to list tasks: {}", e);
+                println!("{}", e.diagnose().render("Failed to create token"));
+                error!("Failed to create token: {}", e);
             }
         }
 
         Ok(())
     }
 
-    async fn handle_update_task(&self, user: &UserResponse, id: String, title: Option<String>, description: Option<String>, priority: Option<TaskPriority>, due: Option<String>) -> Result<()> {
-        let task_id = Uuid::parse_str(&id).context("Invalid task ID format")?;
-
-        let parsed_due = if let Some(due_str) = due {
-            Some(chrono::NaiveDate::parse_from_str(&due_str, "%Y-%m-%d")
-                .context("Invalid date format. Use YYYY-MM-DD")?
-                .and_hms_opt(23, 59, 59)
-                .unwrap()
-                .and_utc())
-        } else {
-            None
-        };
+    async fn handle_session_command(&self, command: SessionCommands) -> Result<()> {
+        if self.authorized_user(Scope::Admin).await.is_none() {
+            return Ok(());
+        }
 
-        let model_priority = priority.map(|p| match p {
-            TaskPriority::Low => ModelTaskPriority::Low,
-            TaskPriority::Medium => ModelTaskPriority::Medium,
+        match command {
+            SessionCommands::List => match self.auth_service.list_sessions().await {
+                Ok(sessions) if sessions.is_empty() => println!("{} No active sessions", INFO),
+                Ok(sessions) => {
+                    let current = self.auth_service.current_session_id();
+                    println!("{} {}", INFO, style("Active sessions").bold().cyan());
+                    for session in sessions {
+                        let marker = if Some(session.id) == current {
+                            style(" (this device)".to_string()).green()
+                        } else {
+                            style(String::new())
+                        };
+                        println!(
+                            "  {} {}{} {}",
+                            style(session.short_id()).cyan(),
+                            session.name,
+                            marker,
+                            style(format!(
+                                "logged in {}, last used {}",
+                                format_date(&session.created_at),
+                                format_date(&session.last_used_at)
+                            ))
+                            .dim()
+                        );
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list sessions"));
+                    error!("Failed to list sessions: {}", e);
+                }
+            },
+            SessionCommands::Revoke { id } => {
+                let current = self.auth_service.current_session_id();
+                match self.auth_service.revoke_session(&id).await {
+                    Ok(session) if Some(session.id) == current => {
+                        println!("{} Revoked this device's session; you are now logged out", CHECKMARK)
+                    }
+                    Ok(session) => println!(
+                        "{} Revoked session {} ({})",
+                        CHECKMARK,
+                        style(session.short_id()).cyan(),
+                        session.name
+                    ),
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to revoke session"));
+                        error!("Failed to revoke session: {}", e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_api_key_command(&self, command: ApiKeyCommands) -> Result<()> {
+        if self.authorized_user(Scope::Admin).await.is_none() {
+            return Ok(());
+        }
+
+        match command {
+            ApiKeyCommands::Create { name, scopes, expires } => {
+                let scopes = scopes
+                    .iter()
+                    .map(|scope| scope.parse::<Scope>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(anyhow::Error::msg)?;
+                let lifetime = expires
+                    .map(chrono::Duration::from_std)
+                    .transpose()
+                    .context("API key lifetime is too long")?;
+
+                match self.auth_service.create_api_key(&name, &scopes, lifetime).await {
+                    Ok(created) => {
+                        println!("{} API key created!", CHECKMARK);
+                        println!("Name: {}", style(&created.api_key.name).cyan());
+                        println!("Scopes: {}", style(created.api_key.scopes.join(", ")).green());
+                        if let Some(expires_at) = created.api_key.expires_at {
+                            println!("Expires: {}", style(format_date(&expires_at)).yellow());
+                        }
+                        println!("{}", created.key);
+                        println!(
+                            "{}",
+                            style(format!("Use it by setting {}=<key>; it is not shown again", TOKEN_ENV_VAR)).dim()
+                        );
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to create API key"));
+                        error!("Failed to create API key: {}", e);
+                    }
+                }
+            }
+            ApiKeyCommands::List => match self.auth_service.list_api_keys().await {
+                Ok(api_keys) if api_keys.is_empty() => println!("{} No API keys", INFO),
+                Ok(api_keys) => {
+                    println!("{} {}", INFO, style("API keys").bold().cyan());
+                    for api_key in api_keys {
+                        let state = match (api_key.revoked_at, api_key.expires_at) {
+                            (Some(revoked_at), _) => style(format!("revoked {}", format_date(&revoked_at))).red(),
+                            (None, Some(expires_at)) if !api_key.is_active() => {
+                                style(format!("expired {}", format_date(&expires_at))).red()
+                            }
+                            (None, Some(expires_at)) => style(format!("expires {}", format_date(&expires_at))).yellow(),
+                            (None, None) => style("never expires".to_string()).green(),
+                        };
+                        let last_used = api_key
+                            .last_used_at
+                            .map(|used_at| format!("last used {}", format_date(&used_at)))
+                            .unwrap_or_else(|| "never used".to_string());
+                        println!(
+                            "  {} {} {} {} {}",
+                            style(&api_key.prefix).cyan(),
+                            api_key.name,
+                            style(format!("({})", api_key.scopes.join(", "))).dim(),
+                            state,
+                            style(last_used).dim()
+                        );
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list API keys"));
+                    error!("Failed to list API keys: {}", e);
+                }
+            },
+            ApiKeyCommands::Revoke { id } => match self.auth_service.revoke_api_key(&id).await {
+                Ok(api_key) => println!(
+                    "{} Revoked API key {} ({})",
+                    CHECKMARK,
+                    style(&api_key.prefix).cyan(),
+                    api_key.name
+                ),
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to revoke API key"));
+                    error!("Failed to revoke API key: {}", e);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the logged-in user for an operation requiring `scope`, printing a
+    /// hint when there is no valid session or the token lacks the scope
+    async fn authorized_user(&self, scope: Scope) -> Option<UserResponse> {
+        Self::authorized(self.auth_service.authorize(scope).await)
+    }
+
+    /// Like `authorized_user`, for commands that act on other users' accounts
+    async fn authorized_administrator(&self) -> Option<UserResponse> {
+        Self::authorized(self.auth_service.authorize_administrator().await)
+    }
+
+    /// In local-first mode, sync the logged-in user's tasks, giving up
+    /// quietly when the remote database does not answer in time
+    async fn sync_session_user(&self) {
+        let Some(sync_service) = &self.sync_service else {
+            return;
+        };
+        let Ok(user) = self.auth_service.authorize(Scope::TasksRead).await else {
+            return;
+        };
+        match tokio::time::timeout(SYNC_TIMEOUT, sync_service.sync_user(&user.id)).await {
+            Ok(Ok(summary)) => {
+                info!("Synced: {} pushed, {} pulled", summary.pushed, summary.pulled);
+                if summary.unresolved > 0 {
+                    println!(
+                        "{} {} sync conflict(s) to resolve: {}",
+                        WARNING,
+                        style(summary.unresolved).yellow(),
+                        style("todo-cli sync conflicts").cyan()
+                    );
+                }
+            }
+            Ok(Err(e)) => warn!("Sync failed, changes stay local until the next one: {}", e),
+            Err(_) => warn!("Remote database did not answer; changes stay local until the next sync"),
+        }
+    }
+
+    fn authorized(result: Result<UserResponse, AuthServiceError>) -> Option<UserResponse> {
+        match result {
+            Ok(user) => Some(user),
+            Err(AuthServiceError::SessionNotFound) => {
+                println!("{} Please login first: {}", WARNING, style("todo-cli auth login").cyan());
+                None
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Not authorized"));
+                None
+            }
+        }
+    }
+
+    /// Load the user's status workflow for display, falling back to the built-in statuses
+    async fn status_workflow(&self, user: &UserResponse) -> StatusWorkflow {
+        self.task_service.get_workflow(&user.id).await.unwrap_or_else(|e| {
+            warn!("Failed to load status workflow: {}", e);
+            StatusWorkflow::default()
+        })
+    }
+
+    /// How task IDs are displayed, from the `display.ids` setting
+    fn id_style(&self) -> IdStyle {
+        self.settings()
+            .map(|settings| settings.id_style())
+            .unwrap_or_else(|e| {
+                warn!("Failed to load settings: {}", e);
+                IdStyle::default()
+            })
+    }
+
+    /// Resolve a task ID typed by the user (UUID, number or hash prefix) to a UUID
+    async fn resolve_task_id(&self, user: &UserResponse, id: &str) -> Result<Uuid> {
+        let task_ref: TaskRef = id.parse().map_err(anyhow::Error::msg)?;
+        match (self.task_service.resolve_task_ref(&user.id, &task_ref).await, task_ref) {
+            (Ok(task_id), _) => Ok(task_id),
+            // Offline only full IDs can be used; the replay checks the task exists
+            (Err(e), TaskRef::Id(task_id)) if is_unreachable(&e) => Ok(task_id),
+            (Err(e), _) => Err(e).with_context(|| format!("Cannot find task '{}'", id)),
+        }
+    }
+
+    /// Render a task's detail view with the user's status names and tracked time
+    async fn task_detail(&self, user: &UserResponse, task: &Task) -> String {
+        let workflow = self.status_workflow(user).await;
+        let tracked = match &self.time_service {
+            Some(time_service) => time_service
+                .tracked_time(&user.id, &task.id)
+                .await
+                .map_err(|e| warn!("Failed to load tracked time: {}", e))
+                .ok(),
+            None => None,
+        };
+        format_task_detail(task, &workflow, self.id_style(), tracked.as_ref())
+    }
+
+    // Task Commands
+    async fn handle_task_command(&self, command: TaskCommands, fields: Option<&FieldProjection>, pager: &Pager) -> Result<()> {
+        let scope = match command {
+            TaskCommands::List(_)
+            | TaskCommands::Show { .. }
+            | TaskCommands::Workload { .. }
+            | TaskCommands::Contexts
+            | TaskCommands::Summarize => Scope::TasksRead,
+            _ => Scope::TasksWrite,
+        };
+
+        // Check if user is authenticated; offline, changes are queued for
+        // the saved session's user and checked when replayed
+        let user = match self.auth_service.authorize(scope).await {
+            Err(e) if is_unreachable(&e) && queues_offline(&command) => {
+                self.auth_service.offline_user().ok_or(e)?
+            }
+            result => match Self::authorized(result) {
+                Some(user) => user,
+                None => return Ok(()),
+            },
+        };
+
+        match command {
+            TaskCommands::Add { title, description, priority, due, start, estimate, client, context, location, coords, workspace } => {
+                self.handle_add_task(&user, title, description, priority, due, start, estimate, client, context, location, coords, workspace).await
+            }
+            TaskCommands::List(args) => {
+                self.handle_list_tasks(&user, args, fields, pager).await
+            }
+            TaskCommands::Update { id, title, description, priority, due, start, estimate, client, context, location, coords } => {
+                self.handle_update_task(&user, id, title, description, priority, due, start, estimate, client, context, location, coords).await
+            }
+            TaskCommands::SetStatus { id, status } => {
+                self.handle_set_status(&user, id, status).await
+            }
+            TaskCommands::BulkStatus { status, ids } => {
+                self.handle_bulk_status(&user, status, ids).await
+            }
+            TaskCommands::Complete { id } => {
+                self.handle_complete_task(&user, id, true).await
+            }
+            TaskCommands::Uncomplete { id } => {
+                self.handle_complete_task(&user, id, false).await
+            }
+            TaskCommands::Delete { id, force } => {
+                self.handle_delete_task(&user, id, force).await
+            }
+            TaskCommands::Start { id } => {
+                self.handle_start_timer(&user, id).await
+            }
+            TaskCommands::Stop => {
+                self.handle_stop_timer(&user).await
+            }
+            TaskCommands::Move { id, before, after } => {
+                self.handle_move_task(&user, id, before, after).await
+            }
+            TaskCommands::Workload { days } => {
+                self.handle_workload(&user, days).await
+            }
+            TaskCommands::Pin { id } => {
+                self.handle_pin_task(&user, id, true).await
+            }
+            TaskCommands::Unpin { id } => {
+                self.handle_pin_task(&user, id, false).await
+            }
+            TaskCommands::Share { id, with_user, read_only } => {
+                self.handle_share_task(&user, id, with_user, read_only).await
+            }
+            TaskCommands::Contexts => {
+                self.handle_contexts(&user).await
+            }
+            TaskCommands::Summarize => {
+                self.handle_summarize(&user).await
+            }
+            TaskCommands::Show { id, json } => {
+                self.handle_show_task(&user, id, json || fields.is_some(), fields).await
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_add_task(&self, user: &UserResponse, title: String, description: Option<String>, priority: TaskPriority, due: Option<String>, start: Option<String>, estimate: Option<i32>, client: Option<String>, context: Option<String>, location: Option<String>, coords: Option<GeoPoint>, workspace: Option<String>) -> Result<()> {
+        let workspace = match workspace {
+            Some(reference) => match self.workspace_service()?.resolve_workspace(&user.id, &reference).await {
+                Ok(workspace) => Some(workspace),
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to create task"));
+                    error!("Failed to resolve workspace: {}", e);
+                    return Ok(());
+                }
+            },
+            None => None,
+        };
+
+        let parsed_due = if let Some(due_str) = due {
+            Some(calendar::end_of_day(
+                chrono::NaiveDate::parse_from_str(&due_str, "%Y-%m-%d")
+                    .context("Invalid date format. Use YYYY-MM-DD")?,
+            ))
+        } else {
+            None
+        };
+
+        let parsed_start = start
+            .map(|start_str| {
+                chrono::NaiveDate::parse_from_str(&start_str, "%Y-%m-%d")
+                    .context("Invalid start date format. Use YYYY-MM-DD")
+                    .map(calendar::start_of_day)
+            })
+            .transpose()?;
+
+        let model_priority = match priority {
+            TaskPriority::Low => ModelTaskPriority::Low,
+            TaskPriority::Medium => ModelTaskPriority::Medium,
+            TaskPriority::High => ModelTaskPriority::High,
+        };
+
+        let request = StoreTaskRequest {
+            title,
+            description,
+            status: ModelTaskStatus::Pending,
+            priority: model_priority,
+            due_date: parsed_due,
+            start_date: parsed_start,
+            estimate_minutes: estimate,
+            client,
+            context,
+            location,
+            coordinates: coords,
+            workspace_id: workspace.as_ref().map(|w| w.id),
+        };
+
+        match self.task_service.create_task(&user.id, request.clone()).await {
+            Err(e) if is_unreachable(&e) => self.queue_offline(user, QueuedMutation::Create { request })?,
+            Ok(task) => {
+                println!("{} Task created successfully!", CHECKMARK);
+                println!("ID: {}", style(&task.id).cyan());
+                println!("Title: {}", style(&task.title).green());
+                if let Some(desc) = &task.description {
+                    println!("Description: {}", style(desc).dim());
+                }
+                println!("Priority: {}", style(format!("{:?}", task.priority)).yellow());
+                if let Some(estimate) = task.estimate_minutes {
+                    println!("Estimate: {}", style(format_duration(i64::from(estimate) * 60)).cyan());
+                }
+                if let Some(client) = &task.client {
+                    println!("Client: {}", style(client).cyan());
+                }
+                if let Some(context) = &task.context {
+                    println!("Context: {}", style(context).cyan());
+                }
+                if let Some(location) = &task.location {
+                    println!("Location: {}", style(location).cyan());
+                }
+                if let Some(workspace) = &workspace {
+                    println!("Workspace: {}", style(&workspace.name).cyan());
+                }
+                info!("Task created: {}", task.title);
+                self.print_quota_warning(user).await;
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to create task"));
+                error!("Failed to create task: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gently suggest a cleanup once the user's open tasks cross a soft limit
+    async fn print_quota_warning(&self, user: &UserResponse) {
+        let thresholds = self
+            .settings()
+            .map(|settings| settings.quota_thresholds())
+            .unwrap_or_else(|e| {
+                warn!("Failed to load settings: {}", e);
+                QuotaThresholds::default()
+            });
+
+        let stats = match self.task_service.get_task_statistics(&user.id, &StatisticsFilter::default()).await {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!("Failed to check open task count: {}", e);
+                return;
+            }
+        };
+
+        let Some(warning) = QuotaWarning::check(&stats, thresholds) else {
+            return;
+        };
+
+        println!();
+        match warning.level {
+            QuotaLevel::Warn => println!("{} {}", INFO, style(warning.message()).dim()),
+            QuotaLevel::Critical => println!("{} {}", WARNING, style(warning.message()).yellow()),
+        }
+        for suggestion in &warning.suggestions {
+            println!("  • {}", suggestion);
+        }
+        println!("  {}", style("Adjust with `todo-cli config set quota.warn <count>` (0 disables)").dim());
+    }
+
+    async fn handle_list_tasks(&self, user: &UserResponse, args: TaskListArgs, fields: Option<&FieldProjection>, pager: &Pager) -> Result<()> {
+        let mut filter = TaskFilter::default();
+        let workflow = self.status_workflow(user).await;
+
+        // Apply status filters
+        if args.completed {
+            filter.status = Some(ModelTaskStatus::Completed);
+        } else if args.pending {
+            filter.status = Some(ModelTaskStatus::Pending);
+        } else if let Some(status) = &args.status {
+            filter.status = Some(
+                workflow
+                    .parse(status)
+                    .with_context(|| format!("Unknown status '{}'", status))?,
+            );
+        }
+
+        // Apply priority filter
+        if let Some(priority) = args.priority {
+            filter.priority = Some(match priority {
+                TaskPriority::Low => ModelTaskPriority::Low,
+                TaskPriority::Medium => ModelTaskPriority::Medium,
+                TaskPriority::High => ModelTaskPriority::High,
+            });
+        }
+
+        filter.started_only = !args.all;
+        filter.context = args.context;
+        filter.overdue_only = args.overdue;
+        filter.due_today = args.today;
+
+        // Apply search filter
+        if let Some(search) = args.search {
+            filter.search_term = Some(search);
+        }
+
+        if let Some(near) = args.near {
+            let point = self.settings()?.location(&near);
+            if point.is_none() && !args.json && fields.is_none() && args.output == ListOutput::Table {
+                println!(
+                    "{} No coordinates configured for '{}'; matching the location label only (set location.{})",
+                    INFO, near, near.to_lowercase()
+                );
+            }
+            filter.near = Some(NearFilter { label: near, point, radius_km: args.radius });
+        }
+
+        let columns = match args.columns {
+            Some(columns) => columns,
+            None => self.settings()?.task_columns(),
+        };
+
+        if args.watch {
+            if fields.is_some() {
+                bail!("--fields cannot be combined with --watch");
+            }
+            return self.watch_tasks(user, filter, &columns, args.interval).await;
+        }
+
+        match self.task_service.get_tasks(&user.id, filter).await {
+            Ok(tasks) if args.json || fields.is_some() => {
+                let ids = self.id_style();
+                let tasks: Vec<TaskJson> = tasks.iter().map(|task| TaskJson::new(task, ids)).collect();
+                print_json(&tasks, fields)?
+            }
+            Ok(tasks) if args.output != ListOutput::Table => {
+                let delimiter = if args.output == ListOutput::Tsv { b'\t' } else { b',' };
+                let output = format_task_delimited(&tasks, &workflow, self.id_style(), &columns, delimiter)
+                    .context("Failed to write the task list")?;
+                print!("{}", output);
+            }
+            Ok(tasks) => {
+                timing::record_fetched(tasks.len());
+                let _render = debug_span!(target: TIMING_TARGET, "render").entered();
+                if tasks.is_empty() {
+                    println!("{} No tasks found", INFO);
+                } else {
+                    let table = format_task_table_for(&tasks, &workflow, self.id_style(), &columns, &user.id);
+                    pager.print(&format!("{} {}\n{}\n", INFO, style(format!("Found {} tasks", tasks.len())).bold(), table));
+                }
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to list tasks"));
+                error!("Failed to list tasks: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Redraw the task table every `interval` until interrupted, highlighting
+    /// tasks that were added or modified since the previous refresh
+    async fn watch_tasks(&self, user: &UserResponse, filter: TaskFilter, columns: &[TaskColumn], interval: Duration) -> Result<()> {
+        let term = Term::stdout();
+        let ids = self.id_style();
+        let mut previous: Option<HashMap<Uuid, chrono::DateTime<chrono::Utc>>> = None;
+
+        loop {
+            let workflow = self.status_workflow(user).await;
+            let result = self.task_service.get_tasks(&user.id, filter.clone()).await;
+
+            term.clear_screen()?;
+            println!(
+                "{} Every {:?}: {}    {}",
+                INFO,
+                interval,
+                style("todo-cli task list").bold(),
+                style(format_date(&chrono::Utc::now())).dim()
+            );
+
+            match result {
+                Ok(tasks) => {
+                    let mut changed = HashSet::new();
+                    let mut removed = 0;
+                    if let Some(previous) = &previous {
+                        changed = tasks
+                            .iter()
+                            .filter(|task| previous.get(&task.id) != Some(&task.updated_at))
+                            .map(|task| task.id)
+                            .collect();
+                        removed = previous
+                            .keys()
+                            .filter(|id| !tasks.iter().any(|task| task.id == **id))
+                            .count();
+                    }
+
+                    if tasks.is_empty() {
+                        println!("{} No tasks found", INFO);
+                    } else {
+                        println!("{}", format_task_table_highlighted(&tasks, &workflow, ids, columns, &changed, &user.id));
+                    }
+                    if !changed.is_empty() || removed > 0 {
+                        println!(
+                            "{}",
+                            style(format!("{} changed, {} removed since last refresh", changed.len(), removed)).yellow()
+                        );
+                    }
+
+                    previous = Some(tasks.iter().map(|task| (task.id, task.updated_at)).collect());
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list tasks"));
+                    error!("Failed to list tasks: {}", e);
+                }
+            }
+
+            println!("{}", style("Press Ctrl+C to stop watching").dim());
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_update_task(&self, user: &UserResponse, id: String, title: Option<String>, description: Option<String>, priority: Option<TaskPriority>, due: Option<String>, start: Option<String>, estimate: Option<i32>, client: Option<String>, context: Option<String>, location: Option<String>, coords: Option<GeoPoint>) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+
+        let parsed_due = if let Some(due_str) = due {
+            Some(calendar::end_of_day(
+                chrono::NaiveDate::parse_from_str(&due_str, "%Y-%m-%d")
+                    .context("Invalid date format. Use YYYY-MM-DD")?,
+            ))
+        } else {
+            None
+        };
+
+        let parsed_start = start
+            .map(|start_str| {
+                chrono::NaiveDate::parse_from_str(&start_str, "%Y-%m-%d")
+                    .context("Invalid start date format. Use YYYY-MM-DD")
+                    .map(calendar::start_of_day)
+            })
+            .transpose()?;
+
+        let model_priority = priority.map(|p| match p {
+            TaskPriority::Low => ModelTaskPriority::Low,
+            TaskPriority::Medium => ModelTaskPriority::Medium,
             TaskPriority::High => ModelTaskPriority::High,
         });
 
@@ -386,17 +1623,26 @@ impl CliApp {
             description,
             priority: model_priority,
             due_date: parsed_due,
+            start_date: parsed_start,
+            estimate_minutes: estimate,
+            client,
+            context,
+            location,
+            coordinates: coords,
             ..Default::default()
         };
 
-        match self.task_service.update_task(&user.id, &task_id, updates).await {
+        match self.task_service.update_task(&user.id, &task_id, updates.clone()).await {
+            Err(e) if is_unreachable(&e) => {
+                self.queue_offline(user, QueuedMutation::Update { task_id, request: updates })?
+            }
             Ok(task) => {
                 println!("{} Task updated successfully!", CHECKMARK);
-                println!("{}", format_task_detail(&task));
+                println!("{}", self.task_detail(user, &task).await);
                 info!("Task updated: {}", task.title);
             }
             Err(e) => {
-                println!("{} Failed to update task: {}", CROSS, style(&e).red());
+                println!("{}", e.diagnose().render("Failed to update task"));
                 error!("Failed to update task: {}", e);
             }
         }
@@ -405,28 +1651,36 @@ impl CliApp {
     }
 
     async fn handle_complete_task(&self, user: &UserResponse, id: String, complete: bool) -> Result<()> {
-        let task_id = Uuid::parse_str(&id).context("Invalid task ID format")?;
+        let task_id = self.resolve_task_id(user, &id).await?;
 
+        let updates = UpdateTaskRequest {
+            status: Some(ModelTaskStatus::Pending),
+            ..Default::default()
+        };
         let result = if complete {
             self.task_service.complete_task(&user.id, &task_id).await
         } else {
-            let updates = UpdateTaskRequest {
-                status: Some(ModelTaskStatus::Pending),
-                ..Default::default()
-            };
-            self.task_service.update_task(&user.id, &task_id, updates).await
+            self.task_service.update_task(&user.id, &task_id, updates.clone()).await
         };
 
         match result {
+            Err(e) if is_unreachable(&e) => {
+                let mutation = if complete {
+                    QueuedMutation::Complete { task_id }
+                } else {
+                    QueuedMutation::Update { task_id, request: updates }
+                };
+                self.queue_offline(user, mutation)?;
+            }
             Ok(task) => {
                 let action = if complete { "completed" } else { "marked as pending" };
                 println!("{} Task {} successfully!", CHECKMARK, action);
-                println!("{}", format_task_detail(&task));
+                println!("{}", self.task_detail(user, &task).await);
                 info!("Task {}: {}", action, task.title);
             }
             Err(e) => {
                 let action = if complete { "complete" } else { "uncomplete" };
-                println!("{} Failed to {} task: {}", CROSS, action, style(&e).red());
+                println!("{}", e.diagnose().render(&format!("Failed to {} task", action)));
                 error!("Failed to {} task: {}", action, e);
             }
         }
@@ -434,94 +1688,2347 @@ impl CliApp {
         Ok(())
     }
 
-    async fn handle_delete_task(&self, user: &UserResponse, id: String, force: bool) -> Result<()> {
-        let task_id = Uuid::parse_str(&id).context("Invalid task ID format")?;
-
-        // Confirm deletion unless force flag is used
-        if !force {
-            let theme = ColorfulTheme::default();
-            let confirm = Confirm::with_theme(&theme)
-                .with_prompt("Are you sure you want to delete this task?")
-                .default(false)
-                .interact()?;
+    async fn handle_set_status(&self, user: &UserResponse, id: String, status: String) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+        let workflow = self.status_workflow(user).await;
+        let target = workflow
+            .parse(&status)
+            .with_context(|| format!("Unknown status '{}'. See `todo-cli status list`", status))?;
 
-            if !confirm {
-                println!("Task deletion cancelled");
-                return Ok(());
+        match self.task_service.change_status(&user.id, &task_id, target).await {
+            Ok(task) => {
+                println!("{} Task moved to {}", CHECKMARK, style(workflow.name(task.status)).green());
+                println!("{}", self.task_detail(user, &task).await);
+                info!("Task {} moved to status {}", task.id, workflow.name(task.status));
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to change task status"));
+                error!("Failed to change task status: {}", e);
             }
         }
 
-        match self.task_service.delete_task(&user.id, &task_id).await {
-            Ok(_) => {
-                println!("{} Task deleted successfully!", CHECKMARK);
-                info!("Task deleted: {}", task_id);
+        Ok(())
+    }
+
+    async fn handle_bulk_status(&self, user: &UserResponse, status: String, ids: Vec<String>) -> Result<()> {
+        let mut task_ids = Vec::with_capacity(ids.len());
+        for id in &ids {
+            task_ids.push(self.resolve_task_id(user, id).await?);
+        }
+        let workflow = self.status_workflow(user).await;
+        let target = workflow
+            .parse(&status)
+            .with_context(|| format!("Unknown status '{}'. See `todo-cli status list`", status))?;
+
+        match self.task_service.bulk_update_status(&user.id, task_ids, target).await {
+            Ok(results) => {
+                let failed = results.iter().filter(|result| result.is_err()).count();
+                println!("{}", format_bulk_status_report(&results, &workflow, self.id_style()));
+                if failed == 0 {
+                    println!("{} Moved {} tasks to {}", CHECKMARK, results.len(), style(workflow.name(target)).green());
+                } else {
+                    println!(
+                        "{} {} of {} tasks could not be moved to {}",
+                        WARNING, failed, results.len(), style(workflow.name(target)).yellow()
+                    );
+                }
+                info!("Bulk status update: {}/{} tasks failed", failed, results.len());
             }
             Err(e) => {
-                println!("{} Failed to delete task: {}", CROSS, style(&e).red());
-                error!("Failed to delete task: {}", e);
+                println!("{}", e.diagnose().render("Failed to update task statuses"));
+                error!("Failed to update task statuses: {}", e);
             }
         }
 
         Ok(())
     }
 
-    async fn handle_show_task(&self, user: &UserResponse, id: String) -> Result<()> {
-        let task_id = Uuid::parse_str(&id).context("Invalid task ID format")?;
+    async fn handle_pin_task(&self, user: &UserResponse, id: String, pinned: bool) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
 
-        match self.task_service.get_task(&user.id, &task_id).await {
+        match self.task_service.set_pinned(&user.id, &task_id, pinned).await {
             Ok(task) => {
-                println!("{} {}", INFO, style("Task Details").bold().cyan());
-                println!("{}", format_task_detail(&task));
+                let action = if pinned { "pinned" } else { "unpinned" };
+                println!("{} Task {}: {}", CHECKMARK, action, style(&task.title).green());
+                info!("Task {}: {}", action, task.title);
             }
             Err(e) => {
-                println!("{} Failed to get task: {}", CROSS, style(&e).red());
-                error!("Failed to get task: {}", e);
+                println!("{}", e.diagnose().render("Failed to update task"));
+                error!("Failed to pin task: {}", e);
             }
         }
 
         Ok(())
     }
 
-    // Config Commands (placeholder implementation)
-    async fn handle_config_command(&self, command: ConfigCommands) -> Result<()> {
-        match command {
-            ConfigCommands::Show => {
-                println!("{} Configuration management is not yet implemented", WARNING);
-            }
-            ConfigCommands::Set { key: _, value: _ } => {
-                println!("{} Configuration management is not yet implemented", WARNING);
+    async fn handle_share_task(&self, user: &UserResponse, id: String, username: String, read_only: bool) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+
+        let recipient = match self.user_service.find_by_username(&username).await {
+            Ok(Some(recipient)) => recipient,
+            Ok(None) => {
+                println!("{} User not found: {}", CROSS, username);
+                return Ok(());
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to share task"));
+                error!("Failed to look up user {}: {}", username, e);
+                return Ok(());
+            }
+        };
+
+        match self.task_service.share_task(&user.id, &task_id, &recipient.id, read_only).await {
+            Ok(_) => {
+                let task = self.task_service.get_task(&user.id, &task_id).await?;
+                let access = if read_only { "read-only" } else { "read-write" };
+                println!(
+                    "{} Shared {} with {} ({})",
+                    CHECKMARK,
+                    style(&task.title).green(),
+                    style(&recipient.username).cyan(),
+                    access
+                );
+                info!("Shared task {} with {}", task_id, recipient.username);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to share task"));
+                error!("Failed to share task: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_contexts(&self, user: &UserResponse) -> Result<()> {
+        match self.task_service.get_context_counts(&user.id).await {
+            Ok(counts) if counts.is_empty() => {
+                println!("{} No open tasks with a context. Add one with --context @home", INFO);
+            }
+            Ok(counts) => {
+                println!("{} {}", INFO, style("Open tasks per context").bold().cyan());
+                println!("{}", format_context_counts(&counts));
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to count contexts"));
+                error!("Failed to count contexts: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_summarize(&self, user: &UserResponse) -> Result<()> {
+        match self.task_service.get_daily_digest(&user.id).await {
+            // No styling, so the digest can be piped into other tools
+            Ok(digest) => println!("{}", digest.render()),
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to summarize tasks"));
+                error!("Failed to summarize tasks: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_workload(&self, user: &UserResponse, days: u32) -> Result<()> {
+        match self.task_service.get_workload(&user.id, days).await {
+            Ok(workload) => {
+                println!("{} {}", INFO, style(format!("Workload for the next {} days", days)).bold().cyan());
+                println!("{}", format_workload(&workload));
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to build workload"));
+                error!("Failed to build workload: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_move_task(&self, user: &UserResponse, id: String, before: Option<String>, after: Option<String>) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+        let (anchor, position) = match (before, after) {
+            (Some(anchor), _) => (anchor, MovePosition::Before),
+            (None, Some(anchor)) => (anchor, MovePosition::After),
+            (None, None) => bail!("Use --before or --after to say where to move the task"),
+        };
+        let anchor_id = self.resolve_task_id(user, &anchor).await?;
+
+        match self.task_service.move_task(&user.id, &task_id, &anchor_id, position).await {
+            Ok(task) => {
+                let placement = if position == MovePosition::Before { "before" } else { "after" };
+                println!("{} Moved {} {} {}", CHECKMARK, style(&task.title).green(), placement, style(&anchor).dim());
+                info!("Task moved: {}", task.id);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to move task"));
+                error!("Failed to move task: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_start_timer(&self, user: &UserResponse, id: String) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+
+        match self.time_service()?.start_timer(&user.id, &task_id).await {
+            Ok(started) => {
+                if let Some(stopped) = started.stopped {
+                    println!(
+                        "{} Stopped running timer after {}",
+                        INFO,
+                        style(format_duration(stopped.duration().num_seconds())).cyan()
+                    );
+                }
+                println!("{} Timer started on {}", CHECKMARK, style(&started.task.title).green());
+                info!("Timer started on task {}", started.entry.task_id);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to start timer"));
+                error!("Failed to start timer: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_stop_timer(&self, user: &UserResponse) -> Result<()> {
+        match self.time_service()?.stop_timer(&user.id).await {
+            Ok((entry, task)) => {
+                println!(
+                    "{} Timer stopped on {} after {}",
+                    CHECKMARK,
+                    style(&task.title).green(),
+                    style(format_duration(entry.duration().num_seconds())).cyan()
+                );
+                info!("Timer stopped on task {}", task.id);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to stop timer"));
+                error!("Failed to stop timer: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_delete_task(&self, user: &UserResponse, id: String, force: bool) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+
+        // Confirm deletion unless force flag is used
+        if !force {
+            let theme = ColorfulTheme::default();
+            let confirm = Confirm::with_theme(&theme)
+                .with_prompt("Are you sure you want to delete this task?")
+                .default(false)
+                .interact()?;
+
+            if !confirm {
+                println!("Task deletion cancelled");
+                return Ok(());
+            }
+        }
+
+        match self.task_service.delete_task(&user.id, &task_id).await {
+            Ok(_) => {
+                println!("{} Task deleted successfully!", CHECKMARK);
+                info!("Task deleted: {}", task_id);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to delete task"));
+                error!("Failed to delete task: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_show_task(&self, user: &UserResponse, id: String, json: bool, fields: Option<&FieldProjection>) -> Result<()> {
+        let task_id = self.resolve_task_id(user, &id).await?;
+
+        match self.task_service.get_task(&user.id, &task_id).await {
+            Ok(task) if json => print_json(&TaskJson::new(&task, self.id_style()), fields)?,
+            Ok(task) => {
+                println!("{} {}", INFO, style("Task Details").bold().cyan());
+                let mut detail = self.task_detail(user, &task).await;
+                if let Some(git_service) = &self.git_service {
+                    match git_service.commits(&task.id).await {
+                        Ok(commits) if !commits.is_empty() => {
+                            let hashes: Vec<&str> = commits.iter().map(|commit| commit.short_hash()).collect();
+                            detail.push_str(&format!("{}: {}\n", style("Commits").bold(), style(hashes.join(", ")).yellow()));
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Failed to load commits: {}", e),
+                    }
+                }
+                println!("{}", detail);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to get task"));
+                error!("Failed to get task: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Status Workflow Commands
+    async fn handle_status_command(&self, command: StatusCommands) -> Result<()> {
+        let scope = match command {
+            StatusCommands::List => Scope::TasksRead,
+            _ => Scope::TasksWrite,
+        };
+
+        let user = match self.authorized_user(scope).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let result = match command {
+            StatusCommands::List => {
+                let workflow = self.status_workflow(&user).await;
+                println!("{} {}", INFO, style("Task Statuses").bold().cyan());
+                for status in workflow.statuses() {
+                    let kind = if matches!(status, ModelTaskStatus::Custom(_)) { "custom" } else { "built-in" };
+                    let targets: Vec<String> = workflow
+                        .allowed_targets(status)
+                        .into_iter()
+                        .map(|target| workflow.name(target))
+                        .collect();
+                    println!(
+                        "  {} {} -> {}",
+                        style(workflow.name(status)).green(),
+                        style(format!("({})", kind)).dim(),
+                        targets.join(", ")
+                    );
+                }
+                Ok(())
+            }
+            StatusCommands::Add { name } => self
+                .task_service
+                .create_status(&user.id, &name)
+                .await
+                .map(|status| println!("{} Status '{}' created", CHECKMARK, style(&status.name).green())),
+            StatusCommands::Remove { name } => self
+                .task_service
+                .delete_status(&user.id, &name)
+                .await
+                .map(|_| println!("{} Status '{}' removed", CHECKMARK, style(&name).green())),
+            StatusCommands::Allow { from, to } => self
+                .task_service
+                .set_transition(&user.id, &from, &to, true)
+                .await
+                .map(|_| println!("{} Tasks can now move from '{}' to '{}'", CHECKMARK, from, to)),
+            StatusCommands::Disallow { from, to } => self
+                .task_service
+                .set_transition(&user.id, &from, &to, false)
+                .await
+                .map(|_| println!("{} Removed transition '{}' -> '{}'", CHECKMARK, from, to)),
+        };
+
+        if let Err(e) = result {
+            println!("{}", e.diagnose().render("Status command failed"));
+            error!("Status command failed: {}", e);
+        }
+
+        Ok(())
+    }
+
+    // Webhook Commands
+    async fn handle_webhook_command(&self, command: WebhookCommands) -> Result<()> {
+        let user = match self.authorized_user(Scope::Admin).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        match command {
+            WebhookCommands::Add { url, events } => {
+                let events = events
+                    .iter()
+                    .map(|event| event.parse::<WebhookEvent>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(anyhow::Error::msg)?;
+
+                match self.webhook_service()?.create_webhook(&user.id, StoreWebhookRequest { url, events }).await {
+                    Ok(webhook) => {
+                        println!("{} Webhook registered!", CHECKMARK);
+                        println!("ID: {}", style(&webhook.id).cyan());
+                        println!("Signing secret: {}", style(&webhook.secret).yellow());
+                        println!(
+                            "{}",
+                            style("Deliveries carry an X-Todo-Signature header: hex HMAC-SHA256 of the body with this secret").dim()
+                        );
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to register webhook"));
+                        error!("Failed to register webhook: {}", e);
+                    }
+                }
+            }
+            WebhookCommands::List => match self.webhook_service()?.list_webhooks(&user.id).await {
+                Ok(webhooks) if webhooks.is_empty() => println!("{} No webhooks registered", INFO),
+                Ok(webhooks) => {
+                    println!("{} {}", INFO, style("Webhooks").bold().cyan());
+                    for webhook in webhooks {
+                        let events = if webhook.events.is_empty() {
+                            "all events".to_string()
+                        } else {
+                            webhook.events.join(", ")
+                        };
+                        println!("  {} {} {}", style(webhook.id).dim(), webhook.url, style(format!("({})", events)).dim());
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list webhooks"));
+                    error!("Failed to list webhooks: {}", e);
+                }
+            },
+            WebhookCommands::Remove { id } => {
+                let webhook_id = Uuid::parse_str(&id).context("Invalid webhook ID format")?;
+                match self.webhook_service()?.delete_webhook(&user.id, &webhook_id).await {
+                    Ok(()) => println!("{} Webhook removed", CHECKMARK),
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to remove webhook"));
+                        error!("Failed to remove webhook: {}", e);
+                    }
+                }
+            }
+            WebhookCommands::Inbound { server, expires } => self.create_inbound_webhook(server, expires).await?,
+            WebhookCommands::Deliveries { id, limit } => {
+                let webhook_id = Uuid::parse_str(&id).context("Invalid webhook ID format")?;
+                match self.webhook_service()?.list_deliveries(&user.id, &webhook_id, limit).await {
+                    Ok(deliveries) if deliveries.is_empty() => println!("{} No deliveries yet", INFO),
+                    Ok(deliveries) => {
+                        println!("{} {}", INFO, style("Webhook deliveries").bold().cyan());
+                        for delivery in deliveries {
+                            let outcome = match (delivery.succeeded(), delivery.status_code, &delivery.error) {
+                                (true, Some(status), _) => style(format!("{} HTTP {}", CHECKMARK, status)).green(),
+                                (_, _, Some(error)) => style(format!("{} {}", CROSS, error)).red(),
+                                _ => style(format!("{} failed", CROSS)).red(),
+                            };
+                            println!(
+                                "  {} {} {} {}",
+                                style(format_date(&delivery.delivered_at)).dim(),
+                                delivery.event,
+                                outcome,
+                                style(format!("({} attempt{})", delivery.attempts, if delivery.attempts == 1 { "" } else { "s" })).dim()
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to list webhook deliveries"));
+                        error!("Failed to list webhook deliveries: {}", e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn create_inbound_webhook(&self, server: Option<String>, expires: Option<Duration>) -> Result<()> {
+        let server = match server {
+            Some(server) => server,
+            None => self.settings()?.api_url().unwrap_or(DEFAULT_SERVER_URL).to_string(),
+        };
+        let lifetime = expires
+            .map(chrono::Duration::from_std)
+            .transpose()
+            .context("Key lifetime is too long")?;
+
+        // The key sits in the URL, in other services' settings and logs, so it can only add tasks
+        match self.auth_service.create_api_key(INBOUND_KEY_NAME, &[Scope::TasksAdd], lifetime).await {
+            Ok(created) => {
+                println!("{} Inbound webhook created!", CHECKMARK);
+                if let Some(expires_at) = created.api_key.expires_at {
+                    println!("Expires: {}", style(format_date(&expires_at)).yellow());
+                }
+                println!("{}/api/v1/inbound/{}", server.trim_end_matches('/'), created.key);
+                println!(
+                    "{}",
+                    style(format!(
+                        "POST JSON such as {{\"title\": \"Buy milk\", \"description\": \"...\", \"priority\": \"high\"}} to it; it is not shown again. Revoke it with `auth apikey revoke {}`",
+                        created.api_key.prefix
+                    ))
+                    .dim()
+                );
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to create inbound webhook"));
+                error!("Failed to create inbound webhook: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Workspace Commands
+    async fn handle_workspace_command(&self, command: WorkspaceCommands) -> Result<()> {
+        let scope = match command {
+            WorkspaceCommands::List
+            | WorkspaceCommands::Members { .. }
+            | WorkspaceCommands::Tasks { .. }
+            | WorkspaceCommands::Invite { command: InviteCommands::List { .. } } => Scope::TasksRead,
+            WorkspaceCommands::Create { .. } | WorkspaceCommands::Invite { .. } | WorkspaceCommands::Join { .. } => {
+                Scope::TasksWrite
+            }
+        };
+        let user = match self.authorized_user(scope).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        match command {
+            WorkspaceCommands::Create { name } => {
+                match self.workspace_service()?.create_workspace(&user.id, StoreWorkspaceRequest { name }).await {
+                    Ok(workspace) => {
+                        println!("{} Workspace created!", CHECKMARK);
+                        println!("ID: {}", style(workspace.short_id()).cyan());
+                        println!("Name: {}", style(&workspace.name).green());
+                        println!(
+                            "{}",
+                            style(format!("Invite members with `workspace invite create \"{}\"`", workspace.name)).dim()
+                        );
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to create workspace"));
+                        error!("Failed to create workspace: {}", e);
+                    }
+                }
+            }
+            WorkspaceCommands::List => match self.workspace_service()?.list_workspaces(&user.id).await {
+                Ok(workspaces) if workspaces.is_empty() => println!("{} You are not a member of any workspace", INFO),
+                Ok(workspaces) => {
+                    println!("{} {}", INFO, style("Workspaces").bold().cyan());
+                    for workspace in workspaces {
+                        let role = if workspace.owner_id == user.id { "owner" } else { "member" };
+                        println!("  {} {} {}", style(workspace.short_id()).dim(), workspace.name, style(format!("({})", role)).dim());
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list workspaces"));
+                    error!("Failed to list workspaces: {}", e);
+                }
+            },
+            WorkspaceCommands::Invite { command } => return self.handle_invite_command(&user, command).await,
+            WorkspaceCommands::Join { code } => match self.workspace_service()?.join(&user.id, code.trim()).await {
+                Ok(workspace) => {
+                    println!("{} Joined {}", CHECKMARK, style(&workspace.name).cyan());
+                    println!(
+                        "{}",
+                        style(format!("See its tasks with `workspace tasks \"{}\"`", workspace.name)).dim()
+                    );
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to join workspace"));
+                    error!("Failed to join workspace: {}", e);
+                }
+            },
+            WorkspaceCommands::Members { workspace } => match self.workspace_service()?.list_members(&user.id, &workspace).await {
+                Ok((workspace, members)) => {
+                    println!("{} {}", INFO, style(format!("Members of {}", workspace.name)).bold().cyan());
+                    for member in members {
+                        println!(
+                            "  {} {} {}",
+                            member.username,
+                            style(format!("({})", member.role)).dim(),
+                            style(format!("joined {}", format_date(&member.joined_at))).dim()
+                        );
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list members"));
+                    error!("Failed to list workspace members: {}", e);
+                }
+            },
+            WorkspaceCommands::Tasks { workspace } => {
+                let workflow = self.status_workflow(&user).await;
+                match self.workspace_service()?.list_tasks(&user.id, &workspace).await {
+                    Ok((workspace, tasks)) if tasks.is_empty() => {
+                        println!("{} No tasks shared in {}", INFO, workspace.name);
+                    }
+                    Ok((workspace, tasks)) => {
+                        println!(
+                            "{} {}",
+                            INFO,
+                            style(format!("Found {} tasks in {}", tasks.len(), workspace.name)).bold()
+                        );
+                        println!("{}", format_task_table(&tasks, &workflow, self.id_style()));
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to list workspace tasks"));
+                        error!("Failed to list workspace tasks: {}", e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_invite_command(&self, user: &UserResponse, command: InviteCommands) -> Result<()> {
+        match command {
+            InviteCommands::User { workspace, username } => {
+                match self.workspace_service()?.invite_member(&user.id, &workspace, &username).await {
+                    Ok((workspace, member)) => {
+                        println!(
+                            "{} Added {} to {}",
+                            CHECKMARK,
+                            style(&member.username).green(),
+                            style(&workspace.name).cyan()
+                        );
+                        info!("Added {} to workspace {}", member.username, workspace.id);
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to invite member"));
+                        error!("Failed to invite member: {}", e);
+                    }
+                }
+            }
+            InviteCommands::Create { workspace, expires, max_uses } => {
+                let lifetime = chrono::Duration::from_std(expires).context("Invitation lifetime is too long")?;
+
+                match self.workspace_service()?.create_invitation(&user.id, &workspace, lifetime, max_uses).await {
+                    Ok((workspace, created)) => {
+                        println!("{} Invite code created!", CHECKMARK);
+                        println!("Workspace: {}", style(&workspace.name).cyan());
+                        println!("Expires: {}", style(format_date(&created.invitation.expires_at)).yellow());
+                        if let Some(max_uses) = created.invitation.max_uses {
+                            println!("Uses: {}", style(max_uses).green());
+                        }
+                        println!("{}", created.code);
+                        println!(
+                            "{}",
+                            style("Share it to let others run `workspace join <code>`; it is not shown again").dim()
+                        );
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to create invitation"));
+                        error!("Failed to create invitation: {}", e);
+                    }
+                }
+            }
+            InviteCommands::List { workspace } => match self.workspace_service()?.list_invitations(&user.id, &workspace).await {
+                Ok((workspace, invitations)) if invitations.is_empty() => {
+                    println!("{} No invitations for {}", INFO, workspace.name)
+                }
+                Ok((workspace, invitations)) => {
+                    println!("{} {}", INFO, style(format!("Invitations to {}", workspace.name)).bold().cyan());
+                    for invitation in invitations {
+                        let state = if let Some(revoked_at) = invitation.revoked_at {
+                            style(format!("revoked {}", format_date(&revoked_at))).red()
+                        } else if invitation.is_expired() {
+                            style(format!("expired {}", format_date(&invitation.expires_at))).red()
+                        } else if invitation.is_used_up() {
+                            style("used up".to_string()).red()
+                        } else {
+                            style(format!("expires {}", format_date(&invitation.expires_at))).yellow()
+                        };
+                        let uses = match invitation.max_uses {
+                            Some(max_uses) => format!("{}/{} uses", invitation.use_count, max_uses),
+                            None => format!("{} uses", invitation.use_count),
+                        };
+                        println!("  {} {} {}", style(&invitation.prefix).cyan(), state, style(uses).dim());
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list invitations"));
+                    error!("Failed to list invitations: {}", e);
+                }
+            },
+            InviteCommands::Revoke { workspace, id } => {
+                match self.workspace_service()?.revoke_invitation(&user.id, &workspace, &id).await {
+                    Ok(invitation) => println!("{} Revoked invitation {}", CHECKMARK, style(&invitation.prefix).cyan()),
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to revoke invitation"));
+                        error!("Failed to revoke invitation: {}", e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Account Commands
+    async fn handle_account_command(&self, command: AccountCommands) -> Result<()> {
+        match command {
+            AccountCommands::ExportData { output } => self.handle_export_data(output).await,
+        }
+    }
+
+    async fn handle_export_data(&self, output: Option<String>) -> Result<()> {
+        let user = match self.authorized_user(Scope::Admin).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let export = match self.data_export_service()?.export(&user.id).await {
+            Ok(export) => export,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to export account data"));
+                error!("Failed to export account data: {}", e);
+                return Ok(());
+            }
+        };
+        let content = export.to_json().context("Failed to serialize account data")?;
+
+        match output {
+            Some(path) => {
+                std::fs::write(&path, content)
+                    .with_context(|| format!("Failed to write export file '{}'", path))?;
+                println!(
+                    "{} Exported account data ({} tasks, {} time entries, {} sessions) to {}",
+                    CHECKMARK,
+                    export.tasks.tasks.len(),
+                    export.time_entries.len(),
+                    export.auth.sessions.len(),
+                    style(&path).cyan()
+                );
+                info!("Exported account data to {}", path);
+            }
+            None => println!("{}", content),
+        }
+
+        Ok(())
+    }
+
+    // Admin Commands
+    async fn handle_admin_command(&self, command: AdminCommands) -> Result<()> {
+        let admin = match self.authorized_administrator().await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        match command {
+            AdminCommands::User { command } => match command {
+                AdminUserCommands::Suspend { username } => {
+                    if username == admin.username {
+                        println!("{} You cannot suspend your own account", CROSS);
+                        return Ok(());
+                    }
+                    self.handle_set_active(&username, false).await
+                }
+                AdminUserCommands::Reactivate { username } => self.handle_set_active(&username, true).await,
+            },
+        }
+    }
+
+    async fn handle_set_active(&self, username: &str, active: bool) -> Result<()> {
+        let action = if active { "reactivate" } else { "suspend" };
+        match self.user_service.set_active(username, active).await {
+            Ok(user) => {
+                self.auth_service.clear_session_cache();
+                let state = if active { "reactivated" } else { "suspended" };
+                println!("{} Account {} {}", CHECKMARK, style(&user.username).cyan(), state);
+                info!("Account {} {}", user.username, state);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render(&format!("Failed to {} account", action)));
+                error!("Failed to {} account {}: {}", action, username, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load user settings from the config directory
+    fn settings(&self) -> Result<Settings> {
+        Settings::load(&self.config_dir).context("Failed to load settings")
+    }
+
+    // Database Commands
+    async fn handle_db_command(&self, command: DbCommands) -> Result<()> {
+        match command {
+            DbCommands::Migrate => {
+                let applied = self.database()?.migrate().await?;
+                if applied.is_empty() {
+                    println!("{} Database schema is up to date", CHECKMARK);
+                } else {
+                    let versions: Vec<String> = applied.iter().map(|v| v.to_string()).collect();
+                    println!("{} Applied {} migration(s): {}", CHECKMARK, applied.len(), versions.join(", "));
+                }
+            }
+            DbCommands::Doctor => return Self::doctor().await,
+            DbCommands::Backup { file, all } => return self.handle_db_backup(file, all).await,
+            DbCommands::Restore { file, all, force } => return self.handle_db_restore(file, all, force).await,
+            DbCommands::Seed { tasks, seed } => {
+                let mut rng = match seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_entropy(),
+                };
+                let started = Instant::now();
+                match self.seed_service.seed(tasks, &mut rng).await {
+                    Ok(summary) => {
+                        println!(
+                            "{} Created {} demo tasks in {:.1}s",
+                            CHECKMARK,
+                            summary.tasks,
+                            started.elapsed().as_secs_f64()
+                        );
+                        if summary.created_user {
+                            println!(
+                                "{} Log in as {} with password {}",
+                                INFO,
+                                style(&summary.user.username).cyan(),
+                                style(DEMO_PASSWORD).cyan()
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Seeding failed"));
+                        error!("Seeding failed: {}", e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `db doctor`. It needs no `CliApp`, whose construction fails on the very
+    /// problems it diagnoses.
+    pub async fn doctor() -> Result<()> {
+        dotenv::dotenv().ok();
+        println!("{} {}", INFO, style("Checking the database setup").bold().cyan());
+
+        let mut checks = Vec::new();
+        let pool_config = PoolConfig::from_env().unwrap_or_else(|e| {
+            checks.push(ConfigCheck::fail("pool settings", e.to_string()).with_fix("Fix or unset the variable"));
+            PoolConfig::default()
+        });
+        checks.extend(doctor::run_checks(std::env::var("DATABASE_URL").ok().as_deref(), &pool_config).await);
+        print_checks(&checks);
+
+        let failed = checks.iter().filter(|check| !check.passed).count();
+        if failed > 0 {
+            bail!("{} of {} database checks failed", failed, checks.len());
+        }
+
+        println!("{} Database is ready", CHECKMARK);
+        Ok(())
+    }
+
+    /// The caller, who must be an administrator when acting on every account
+    async fn backup_caller(&self, all: bool, scope: Scope) -> Option<UserResponse> {
+        if all {
+            self.authorized_administrator().await
+        } else {
+            self.authorized_user(scope).await
+        }
+    }
+
+    async fn handle_db_backup(&self, file: String, all: bool) -> Result<()> {
+        let user = match self.backup_caller(all, Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let result = if all {
+            self.backup_service.backup_all().await
+        } else {
+            self.backup_service.backup_user(&user.id).await
+        };
+        let backup = match result {
+            Ok(backup) => backup,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Backup failed"));
+                error!("Backup failed: {}", e);
+                return Ok(());
+            }
+        };
+
+        std::fs::write(&file, backup.to_json()?)
+            .with_context(|| format!("Failed to write backup file '{}'", file))?;
+        let tasks: usize = backup.accounts.iter().map(|account| account.tasks.tasks.len()).sum();
+        println!(
+            "{} Backed up {} account(s) with {} tasks to {}",
+            CHECKMARK,
+            backup.accounts.len(),
+            tasks,
+            style(&file).cyan()
+        );
+        info!("Backed up {} accounts to {}", backup.accounts.len(), file);
+        Ok(())
+    }
+
+    async fn handle_db_restore(&self, file: String, all: bool, force: bool) -> Result<()> {
+        let user = match self.backup_caller(all, Scope::TasksWrite).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read backup file '{}'", file))?;
+        let backup = match Backup::parse(&content) {
+            Ok(backup) => backup,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Restore failed"));
+                return Ok(());
+            }
+        };
+
+        if !force {
+            let prompt = if all {
+                format!("Restoring replaces the tasks of {} account(s). Continue?", backup.accounts.len())
+            } else {
+                "Restoring replaces all your tasks. Continue?".to_string()
+            };
+            let confirm = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
+                .default(false)
+                .interact()?;
+            if !confirm {
+                println!("Restore cancelled");
+                return Ok(());
+            }
+        }
+
+        let result = if all {
+            self.backup_service.restore_all(backup).await
+        } else {
+            self.backup_service.restore_user(&user.id, backup).await
+        };
+        match result {
+            Ok(summary) => {
+                println!(
+                    "{} Restored {} account(s) ({} created) with {} tasks from {}",
+                    CHECKMARK,
+                    summary.accounts,
+                    summary.created,
+                    summary.tasks,
+                    style(&file).cyan()
+                );
+                info!("Restored {} accounts from {}", summary.accounts, file);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Restore failed"));
+                error!("Restore failed: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Config Commands
+    async fn handle_config_command(&self, command: ConfigCommands) -> Result<()> {
+        match command {
+            ConfigCommands::Show => {
+                let settings = self.settings()?;
+                println!("{} {}", INFO, style("Configuration").bold().cyan());
+                let mut empty = true;
+                for (key, value) in settings.entries() {
+                    let value = if Settings::is_secret(key) { "********" } else { value };
+                    println!("  {} = {}", style(key).green(), value);
+                    empty = false;
+                }
+                if empty {
+                    println!("  {}", style("No settings configured").dim());
+                }
+                println!("\nAvailable settings:");
+                for key in Settings::available_keys() {
+                    println!("  {}", style(key).dim());
+                }
+            }
+            ConfigCommands::Set { key, value } => {
+                let mut settings = self.settings()?;
+                match settings.set(&key, &value) {
+                    Ok(()) => {
+                        settings.save().context("Failed to save settings")?;
+                        println!("{} {} = {}", CHECKMARK, style(&key).green(), value.trim());
+                        info!("Setting {} changed", key);
+                    }
+                    Err(e) => println!("{}", e.diagnose().render("Failed to change setting")),
+                }
+            }
+            ConfigCommands::Get { key } => match self.settings()?.get(&key) {
+                Some(value) => println!("{}", value),
+                None => println!("{} '{}' is not set", INFO, key),
+            },
+            ConfigCommands::Unset { key } => {
+                let mut settings = self.settings()?;
+                if settings.unset(&key) {
+                    settings.save().context("Failed to save settings")?;
+                    println!("{} '{}' unset", CHECKMARK, key);
+                } else {
+                    println!("{} '{}' is not set", INFO, key);
+                }
+            }
+            ConfigCommands::Reset => {
+                let mut settings = self.settings()?;
+                settings.reset();
+                settings.save().context("Failed to save settings")?;
+                println!("{} Settings reset to defaults", CHECKMARK);
+            }
+            ConfigCommands::Validate { env, migrations_dir } => {
+                return self.handle_config_validate(env, migrations_dir).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_config_validate(&self, env: Option<String>, migrations_dir: Option<String>) -> Result<()> {
+        let config = Config::load(env)?;
+        println!(
+            "{} {}",
+            INFO,
+            style(format!("Validating configuration for {}", config.environment)).bold().cyan()
+        );
+
+        let mut checks = config.deployment_checks(self.auth_service.session_dir());
+        checks.push(match self.database()?.pending_migrations(migrations_dir.as_deref().map(Path::new)).await {
+            Ok(pending) if pending.is_empty() => ConfigCheck::pass("migrations", "all migrations applied"),
+            Ok(pending) => {
+                let versions: Vec<String> = pending.iter().map(|v| v.to_string()).collect();
+                ConfigCheck::fail("migrations", format!("pending: {}", versions.join(", "))).with_fix("todo-cli db migrate")
+            }
+            Err(e) => ConfigCheck::fail("migrations", format!("{:#}", e)),
+        });
+
+        print_checks(&checks);
+
+        let failed = checks.iter().filter(|check| !check.passed).count();
+        if failed > 0 {
+            bail!("{} of {} configuration checks failed", failed, checks.len());
+        }
+
+        println!("{} Configuration is ready for {}", CHECKMARK, config.environment);
+        Ok(())
+    }
+
+    // Export Commands
+    async fn handle_export_command(&self, format: Option<String>, output: Option<String>, fields: Option<&FieldProjection>) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let registry = FormatRegistry::with_defaults();
+        let format = match (format, &output) {
+            (Some(name), _) => registry.get(&name)?,
+            (None, Some(path)) => match registry.for_path(Path::new(path)) {
+                Some(format) => format,
+                None => registry.get("json")?,
+            },
+            (None, None) => registry.get("json")?,
+        };
+
+        let mut out: Box<dyn Write> = match &output {
+            Some(path) => Box::new(BufWriter::new(
+                std::fs::File::create(path).with_context(|| format!("Failed to write export file '{}'", path))?,
+            )),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        let exported = self.write_export(&user.id, format, fields, &mut *out).await;
+        drop(out);
+
+        let count = match exported {
+            Ok(count) => count,
+            Err(e) => {
+                // Don't leave a truncated export behind
+                if let Some(path) = &output {
+                    let _ = std::fs::remove_file(path);
+                }
+                let e = e.downcast::<TaskServiceError>()?;
+                println!("{}", e.diagnose().render("Failed to load tasks for export"));
+                error!("Failed to load tasks for export: {}", e);
+                return Ok(());
+            }
+        };
+
+        if let Some(path) = output {
+            println!(
+                "{} Exported {} tasks as {} to {}",
+                CHECKMARK,
+                count,
+                format.name(),
+                style(&path).cyan()
+            );
+            info!("Exported {} tasks to {}", count, path);
+        }
+
+        Ok(())
+    }
+
+    /// Write the user's tasks to `out`, record by record if the format allows
+    /// it; returns how many were written
+    async fn write_export(
+        &self,
+        user_id: &Uuid,
+        format: &dyn Format,
+        fields: Option<&FieldProjection>,
+        out: &mut dyn Write,
+    ) -> Result<usize> {
+        let mut tasks = std::pin::pin!(self.task_service.stream_tasks(user_id));
+
+        if fields.is_none() {
+            if let Some(mut writer) = format.record_writer(out) {
+                let mut count = 0;
+                while let Some(task) = tasks.next().await {
+                    writer.write(&TaskRecord::from(&task?))?;
+                    count += 1;
+                }
+                writer.finish()?;
+                return Ok(count);
+            }
+        }
+
+        let mut records = Vec::new();
+        while let Some(task) = tasks.next().await {
+            records.push(TaskRecord::from(&task?));
+        }
+        let content = match fields {
+            Some(projection) => format.export_projected(&records, projection)?,
+            None => format.export(&records)?,
+        };
+        out.write_all(content.as_bytes())?;
+        out.flush()?;
+        Ok(records.len())
+    }
+
+    // Import Commands
+    async fn handle_import_command(&self, file: String, format: Option<String>, merge: bool) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksWrite).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let registry = FormatRegistry::with_defaults();
+        let format = match format {
+            Some(name) => registry.get(&name)?,
+            None => registry.for_path(Path::new(&file)).with_context(|| {
+                format!(
+                    "Cannot infer format from '{}'. Use --format with one of: {}",
+                    file,
+                    registry.names().join(", ")
+                )
+            })?,
+        };
+
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read import file '{}'", file))?;
+        let records = format.import(&content)?;
+
+        if !merge && !confirm_replace()? {
+            println!("Import cancelled");
+            return Ok(());
+        }
+
+        let requests = records.into_iter().map(StoreTaskRequest::from).collect();
+
+        match self.task_service.import_tasks(&user.id, requests, !merge).await {
+            Ok(count) => {
+                println!("{} Imported {} tasks from {}", CHECKMARK, count, style(&file).cyan());
+                info!("Imported {} tasks from {}", count, file);
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Import failed"));
+                error!("Import failed: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_archive_export(&self, output: Option<String>, fields: Option<&FieldProjection>) -> Result<()> {
+        if fields.is_some() {
+            bail!("--fields cannot be combined with --archive");
+        }
+        let user = match self.authorized_user(Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let loaded = match self.task_service.get_tasks(&user.id, TaskFilter::new().owned_only()).await {
+            Ok(tasks) => self.task_service.get_workflow(&user.id).await.map(|workflow| (tasks, workflow)),
+            Err(e) => Err(e),
+        };
+        let (tasks, workflow) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to load data for export"));
+                error!("Failed to load data for export: {}", e);
+                return Ok(());
+            }
+        };
+
+        let archive = Archive::new(tasks.iter().map(TaskRecord::from).collect(), &workflow);
+        let content = archive.to_json()?;
+
+        match output {
+            Some(path) => {
+                std::fs::write(&path, content)
+                    .with_context(|| format!("Failed to write export file '{}'", path))?;
+                println!(
+                    "{} Exported {} tasks and {} custom statuses to {}",
+                    CHECKMARK,
+                    archive.tasks.len(),
+                    archive.statuses.len(),
+                    style(&path).cyan()
+                );
+                info!("Exported archive with {} tasks to {}", archive.tasks.len(), path);
+            }
+            None => println!("{}", content),
+        }
+
+        Ok(())
+    }
+
+    async fn handle_archive_import(&self, file: String, merge: bool) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksWrite).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read import file '{}'", file))?;
+        let mut archive = Archive::parse(&content)?;
+
+        if !merge && !confirm_replace()? {
+            println!("Import cancelled");
+            return Ok(());
+        }
+
+        // Statuses first, so tasks and transitions can be mapped onto this account's codes
+        let workflow = match self.task_service.ensure_statuses(&user.id, &archive.status_names()).await {
+            Ok(workflow) => workflow,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Import failed"));
+                error!("Import failed: {}", e);
+                return Ok(());
+            }
+        };
+        archive.remap_statuses(&workflow);
+
+        let requests = archive.tasks.into_iter().map(StoreTaskRequest::from).collect();
+        let count = match self.task_service.import_tasks(&user.id, requests, !merge).await {
+            Ok(count) => count,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Import failed"));
+                error!("Import failed: {}", e);
+                return Ok(());
+            }
+        };
+
+        for transition in &archive.transitions {
+            let (from, to) = (workflow.name(transition.from_status), workflow.name(transition.to_status));
+            if let Err(e) = self.task_service.set_transition(&user.id, &from, &to, true).await {
+                println!("{} Could not allow '{}' -> '{}': {}", WARNING, from, to, e);
+                warn!("Failed to import transition {} -> {}: {}", from, to, e);
+            }
+        }
+
+        println!(
+            "{} Imported {} tasks, {} custom statuses and {} transitions from {}",
+            CHECKMARK,
+            count,
+            archive.statuses.len(),
+            archive.transitions.len(),
+            style(&file).cyan()
+        );
+        info!("Imported archive with {} tasks from {}", count, file);
+        Ok(())
+    }
+
+    // Report Commands
+    async fn handle_report_command(&self, command: ReportCommands, fields: Option<&FieldProjection>) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        match command {
+            ReportCommands::Time { period } => {
+                match self.time_service()?.time_report(&user.id, period.start()).await {
+                    Ok(summaries) if summaries.is_empty() => {
+                        println!("{} No time tracked for period: {}", INFO, period);
+                    }
+                    Ok(summaries) => {
+                        println!("{} {}", INFO, style(format!("Time tracked ({})", period)).bold().cyan());
+                        println!("{}", format_time_report(&summaries, self.id_style()));
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to build time report"));
+                        error!("Failed to build time report: {}", e);
+                    }
+                }
+            }
+            ReportCommands::Billable { client, month, output } => {
+                return self.handle_billable_report(&user, client, month, output).await;
+            }
+            ReportCommands::Burndown { period, json } => match self.task_service.get_burndown(&user.id, period.days()).await {
+                Ok(burndown) if json || fields.is_some() => print_json(&burndown.days, fields)?,
+                Ok(burndown) => {
+                    println!("{} {}", INFO, style(format!("Open tasks over the last {} days", period.days())).bold().cyan());
+                    println!("{}", format_burndown(&burndown));
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to build burndown chart"));
+                    error!("Failed to build burndown chart: {}", e);
+                }
+            },
+            ReportCommands::Heatmap { year, json } => {
+                let year = year.unwrap_or_else(|| calendar::today().year());
+                match self.task_service.get_heatmap(&user.id, year).await {
+                    Ok(heatmap) if json || fields.is_some() => print_json(&heatmap.days, fields)?,
+                    Ok(heatmap) => {
+                        println!("{} {}", INFO, style(format!("Tasks completed in {}", year)).bold().cyan());
+                        println!("{}", format_heatmap(&heatmap));
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to build heatmap"));
+                        error!("Failed to build heatmap: {}", e);
+                    }
+                }
+            }
+            ReportCommands::Overdue { json } => match self.task_service.get_overdue_aging(&user.id).await {
+                Ok(aging) if json || fields.is_some() => print_json(&aging.buckets, fields)?,
+                Ok(aging) if aging.buckets.is_empty() => println!("{} No overdue tasks", CHECKMARK),
+                Ok(aging) => {
+                    println!("{} {}", WARNING, style(format!("{} overdue tasks", aging.total())).bold());
+                    let workflow = self.status_workflow(&user).await;
+                    for bucket in &aging.buckets {
+                        println!(
+                            "\n{} {}",
+                            style(format!("{} overdue", bucket.age.label())).bold().red(),
+                            style(format!("({})", bucket.tasks.len())).dim()
+                        );
+                        println!("{}", format_task_table(&bucket.tasks, &workflow, self.id_style()));
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to build overdue report"));
+                    error!("Failed to build overdue report: {}", e);
+                }
+            },
+            ReportCommands::Forecast { weeks, json } => match self.task_service.get_forecast(&user.id, weeks).await {
+                Ok(forecast) if json || fields.is_some() => print_json(&forecast, fields)?,
+                Ok(forecast) => {
+                    println!("{} {}", INFO, style(format!("Forecast from the last {} weeks", weeks)).bold().cyan());
+                    println!("{}", format_forecast(&forecast, calendar::today()));
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to build forecast"));
+                    error!("Failed to build forecast: {}", e);
+                }
+            },
+            ReportCommands::Weekly { format } => match self.task_service.get_weekly_report(&user.id, Utc::now()).await {
+                Ok(report) if format == ReportFormat::Json || fields.is_some() => print_json(&report, fields)?,
+                Ok(report) => {
+                    if format == ReportFormat::Table {
+                        println!("{} {}", INFO, style("Weekly report").bold().cyan());
+                    }
+                    println!("{}", format_weekly_report(&report, format == ReportFormat::Markdown));
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to build weekly report"));
+                    error!("Failed to build weekly report: {}", e);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    async fn handle_billable_report(&self, user: &UserResponse, client: Option<String>, month: Option<NaiveDate>, output: Option<String>) -> Result<()> {
+        let month = match month {
+            Some(month) => month,
+            None => calendar::today().with_day(1).context("Failed to determine current month")?,
+        };
+        let (since, until) = month_bounds(month);
+
+        let summaries = match self.time_service()?.billable_time(&user.id, client.as_deref(), since, until).await {
+            Ok(summaries) => summaries,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to build billable report"));
+                error!("Failed to build billable report: {}", e);
+                return Ok(());
+            }
+        };
+
+        let settings = self.settings()?;
+        let report = BillableReport::build(&summaries, month, settings.currency(), |client| settings.hourly_rate(client));
+        let label = month.format("%B %Y").to_string();
+
+        for unrated in &report.unrated_clients {
+            println!(
+                "{} No hourly rate for '{}'. Set one with {}",
+                WARNING,
+                unrated,
+                style(format!("todo-cli config set billing.rate.{} <rate>", unrated)).cyan()
+            );
+        }
+
+        match output {
+            Some(path) => {
+                std::fs::write(&path, report.to_csv()?)
+                    .with_context(|| format!("Failed to write invoice file '{}'", path))?;
+                println!("{} Wrote billable report for {} to {}", CHECKMARK, label, style(&path).cyan());
+                info!("Wrote billable report to {}", path);
+            }
+            None if report.lines.is_empty() => {
+                println!("{} No billable time for {}", INFO, label);
+            }
+            None => {
+                println!("{} {}", INFO, style(format!("Billable time ({})", label)).bold().cyan());
+                println!("{}", format_billable_report(&report));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Search Commands (placeholder implementation)
+    async fn handle_search_command(&self, _query: String, _in_description: bool) -> Result<()> {
+        println!("{} Search functionality is not yet implemented", WARNING);
+        Ok(())
+    }
+
+    async fn handle_stats_command(&self, period: StatsPeriod) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let filter = StatisticsFilter { created_after: period.start(), ..Default::default() };
+        let result = async {
+            let stats = self.task_service.get_task_statistics(&user.id, &filter).await?;
+            let groups = vec![("Status", self.task_service.count_tasks_grouped(&user.id, TaskGrouping::Status, &filter).await?)];
+            let detailed = self.task_service.get_detailed_statistics(&user.id, &filter).await?;
+            let streak = self.task_service.get_completion_streak(&user.id).await?;
+            Ok::<_, TaskServiceError>((stats, groups, detailed, streak))
+        }
+        .await;
+
+        match result {
+            Ok((stats, groups, detailed, streak)) => {
+                let title = match period {
+                    StatsPeriod::All => "Statistics".to_string(),
+                    _ => format!("Statistics (tasks created this {})", period),
+                };
+                println!("{} {}", INFO, style(title).bold().cyan());
+                let workflow = self.status_workflow(&user).await;
+                println!("{}", format_task_statistics(&stats, &groups, &detailed, &workflow, &streak));
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to load statistics"));
+                error!("Failed to load statistics: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_activity(&self, workspace: Option<String>, since: Option<std::time::Duration>, limit: i64, pager: &Pager) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+        let since = since
+            .map(chrono::Duration::from_std)
+            .transpose()
+            .context("Activity period is too long")?
+            .map(|period| chrono::Utc::now() - period);
+
+        let result = match &workspace {
+            Some(reference) => self
+                .workspace_service()?
+                .activity(&user.id, reference, since, limit)
+                .await
+                .map(|(workspace, feed)| (format!("Activity in {}", workspace.name), feed)),
+            None => self
+                .task_service
+                .get_activity(&user.id, None, since, limit)
+                .await
+                .map(|feed| ("Activity".to_string(), feed))
+                .map_err(WorkspaceServiceError::from),
+        };
+
+        match result {
+            Ok((_, feed)) if feed.events.is_empty() => match feed.last_seen {
+                _ if since.is_some() => println!("{} No activity in that period", INFO),
+                Some(last_seen) => println!("{} Nothing new since {}", INFO, format_date(&last_seen)),
+                None => println!("{} No activity yet", INFO),
+            },
+            Ok((title, feed)) => {
+                let mut output = format!("{} {}\n", INFO, style(title).bold().cyan());
+                for event in &feed.events {
+                    output.push_str(&format!("  {} {}", style(format_date(&event.created_at)).dim(), event.describe()));
+                    if feed.is_new(event) {
+                        output.push_str(&format!(" {}", style("new").green()));
+                    }
+                    output.push('\n');
+                }
+                pager.print(&output);
             }
-            ConfigCommands::Get { key: _ } => {
-                println!("{} Configuration management is not yet implemented", WARNING);
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to load activity"));
+                error!("Failed to load activity: {}", e);
             }
-            ConfigCommands::Reset => {
-                println!("{} Configuration management is not yet implemented", WARNING);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_sync(&self) -> Result<()> {
+        let queue = self.offline_queue();
+        let queued = !queue.entries()?.is_empty();
+        if queued {
+            self.replay_offline_queue(&queue).await?;
+        }
+
+        let Some(sync_service) = &self.sync_service else {
+            if queued {
+                return Ok(());
+            }
+            println!(
+                "{} Nothing to sync: set {} to a sqlite:// URL to work on a local copy of DATABASE_URL",
+                INFO,
+                style("DATABASE_LOCAL_URL").cyan()
+            );
+            return Ok(());
+        };
+
+        match sync_service.sync_all().await {
+            Ok(summary) => {
+                println!(
+                    "{} Synced: {} pushed, {} pulled",
+                    CHECKMARK,
+                    style(summary.pushed).green(),
+                    style(summary.pulled).green()
+                );
+                let settled = summary.conflicts - summary.unresolved;
+                if settled > 0 {
+                    println!(
+                        "{} {} tasks were changed on both sides; the later change was kept",
+                        WARNING,
+                        style(settled).yellow()
+                    );
+                }
+                if summary.unresolved > 0 {
+                    println!(
+                        "{} {} tasks were changed on both sides; choose the versions to keep with {}",
+                        WARNING,
+                        style(summary.unresolved).yellow(),
+                        style("todo-cli sync conflicts").cyan()
+                    );
+                }
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Sync failed"));
+                error!("Sync failed: {}", e);
             }
         }
+
+        Ok(())
+    }
+
+    /// Apply the task changes queued while offline for the logged-in user
+    async fn replay_offline_queue(&self, queue: &OfflineQueue) -> Result<()> {
+        let Some(user) = self.authorized_user(Scope::TasksWrite).await else {
+            return Ok(());
+        };
+        // Changes other users queued wait for them to log in
+        if queue.pending(&user.id)? == 0 {
+            return Ok(());
+        }
+
+        let summary = queue.replay(&user.id, &self.task_service).await?;
+        println!(
+            "{} Replayed offline changes: {} applied",
+            CHECKMARK,
+            style(summary.applied).green()
+        );
+        for (entry, e) in &summary.rejected {
+            let queued_at = format_date(&entry.queued_at);
+            println!("{}", e.diagnose().render(&format!("Dropped the change queued {}", queued_at)));
+        }
+        if summary.remaining > 0 {
+            println!(
+                "{} Database still unreachable; {} change(s) stay queued",
+                WARNING,
+                style(summary.remaining).yellow()
+            );
+        }
         Ok(())
     }
 
-    // Export Commands (placeholder implementation)
-    async fn handle_export_command(&self, _format: ExportFormat, _output: Option<String>) -> Result<()> {
-        println!("{} Export functionality is not yet implemented", WARNING);
+    async fn handle_sync_github(&self, repo: RepoName) -> Result<()> {
+        let github_service = self.github_service()?;
+        let Some(user) = self.authorized_user(Scope::TasksWrite).await else {
+            return Ok(());
+        };
+
+        match github_service.sync(&user.id, &repo).await {
+            Ok(summary) => {
+                println!(
+                    "{} Synced {}: {} tasks created, {} issues closed",
+                    CHECKMARK,
+                    style(&repo).cyan(),
+                    style(summary.created.len()).green(),
+                    style(summary.closed.len()).green()
+                );
+                let ids = self.id_style();
+                for (number, task) in &summary.created {
+                    println!("  #{} {} {}", number, style(ids.format(task)).cyan(), task.title);
+                }
+                for number in &summary.closed {
+                    println!("  #{} closed", number);
+                }
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("GitHub sync failed"));
+                error!("GitHub sync of {} failed: {}", repo, e);
+            }
+        }
+
         Ok(())
     }
 
-    // Import Commands (placeholder implementation)
-    async fn handle_import_command(&self, _file: String, _merge: bool) -> Result<()> {
-        println!("{} Import functionality is not yet implemented", WARNING);
+    async fn handle_sync_markdown(&self, dir: &Path) -> Result<()> {
+        let Some(user) = self.authorized_user(Scope::TasksWrite).await else {
+            return Ok(());
+        };
+
+        match self.markdown_sync_service.sync(&user.id, dir).await {
+            Ok(summary) => {
+                println!(
+                    "{} Synced {}: {} tasks created, {} updated, {} files written",
+                    CHECKMARK,
+                    style(dir.display()).cyan(),
+                    style(summary.created).green(),
+                    style(summary.updated).green(),
+                    style(summary.written.len()).green()
+                );
+                for (title, e) in &summary.rejected {
+                    println!("{}", e.diagnose().render(&format!("Could not save \"{}\"", title)));
+                }
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Markdown sync failed"));
+                error!("Markdown sync of {} failed: {}", dir.display(), e);
+            }
+        }
+
         Ok(())
     }
 
-    // Search Commands (placeholder implementation)
-    async fn handle_search_command(&self, _query: String, _in_description: bool) -> Result<()> {
-        println!("{} Search functionality is not yet implemented", WARNING);
+    async fn handle_git_scan(&self, repo: &Path, revision: &str, limit: usize) -> Result<()> {
+        let git_service = self.git_service()?;
+        let Some(user) = self.authorized_user(Scope::TasksWrite).await else {
+            return Ok(());
+        };
+
+        match git_service.scan(&user.id, repo, revision, limit).await {
+            Ok(summary) => {
+                println!(
+                    "{} Scanned {} commits: {} tasks completed, {} already done",
+                    CHECKMARK,
+                    style(summary.scanned).cyan(),
+                    style(summary.completed.len()).green(),
+                    style(summary.linked.len()).green()
+                );
+                let ids = self.id_style();
+                for (hash, task) in &summary.completed {
+                    println!("  {} {} {}", style(hash).yellow(), style(ids.format(task)).cyan(), task.title);
+                }
+                for (hash, task_ref, e) in &summary.rejected {
+                    println!("{}", e.diagnose().render(&format!("Could not close TODO-{} from {}", task_ref, hash)));
+                }
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Git scan failed"));
+                error!("Git scan of {} failed: {}", repo.display(), e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_git_hook(&self, repo: &Path, force: bool) -> Result<()> {
+        self.git_service()?;
+        let program = std::env::current_exe().context("Failed to find the todo-cli executable")?;
+        let repo = repo
+            .canonicalize()
+            .with_context(|| format!("Failed to find repository {}", repo.display()))?;
+        // Run from here, so the hook finds this login and configuration
+        let command = format!(
+            "cd {} && exec {} git scan --repo {} -n 1",
+            shell_quote(&self.config_dir.to_string_lossy()),
+            shell_quote(&program.to_string_lossy()),
+            shell_quote(&repo.to_string_lossy())
+        );
+
+        match install_hook(&repo, &command, force).await {
+            Ok(path) => {
+                println!("{} Installed {}", CHECKMARK, style(path.display()).cyan());
+                println!(
+                    "{}",
+                    style("Commits whose message says e.g. \"closes TODO-12\" now complete that task").dim()
+                );
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to install the hook"));
+                error!("Failed to install the git hook in {}: {}", repo.display(), e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_calendar_url(&self, server: Option<String>, expires: Option<Duration>) -> Result<()> {
+        if self.authorized_user(Scope::Admin).await.is_none() {
+            return Ok(());
+        }
+        let server = match server {
+            Some(server) => server,
+            None => self.settings()?.api_url().unwrap_or(DEFAULT_SERVER_URL).to_string(),
+        };
+        let lifetime = expires
+            .map(chrono::Duration::from_std)
+            .transpose()
+            .context("Feed lifetime is too long")?;
+
+        // The key ends up in calendar apps, proxies and logs, so it can only
+        // read the feed, not the rest of the API
+        match self.auth_service.create_api_key(CALENDAR_KEY_NAME, &[Scope::CalendarFeed], lifetime).await {
+            Ok(created) => {
+                println!("{} Calendar feed created!", CHECKMARK);
+                if let Some(expires_at) = created.api_key.expires_at {
+                    println!("Expires: {}", style(format_date(&expires_at)).yellow());
+                }
+                println!("{}/api/v1/calendar.ics?token={}", server.trim_end_matches('/'), created.key);
+                println!(
+                    "{}",
+                    style(format!(
+                        "Subscribe to it by URL in your calendar app; it is not shown again. Revoke it with `auth apikey revoke {}`",
+                        created.api_key.prefix
+                    ))
+                    .dim()
+                );
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to create calendar feed"));
+                error!("Failed to create calendar feed: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_calendar_caldav(&self, server: Option<String>, expires: Option<Duration>) -> Result<()> {
+        let Some(user) = self.authorized_user(Scope::Admin).await else {
+            return Ok(());
+        };
+        let server = match server {
+            Some(server) => server,
+            None => self.settings()?.api_url().unwrap_or(DEFAULT_SERVER_URL).to_string(),
+        };
+        let lifetime = expires
+            .map(chrono::Duration::from_std)
+            .transpose()
+            .context("Key lifetime is too long")?;
+
+        // Completing tasks from the app needs write access, but never admin
+        let scopes = [Scope::TasksRead, Scope::TasksWrite];
+        match self.auth_service.create_api_key(CALDAV_KEY_NAME, &scopes, lifetime).await {
+            Ok(created) => {
+                println!("{} CalDAV account created!", CHECKMARK);
+                if let Some(expires_at) = created.api_key.expires_at {
+                    println!("Expires: {}", style(format_date(&expires_at)).yellow());
+                }
+                println!("Server:   {}{}", server.trim_end_matches('/'), api::caldav::ROOT);
+                println!("Username: {}", user.username);
+                println!("Password: {}", created.key);
+                println!(
+                    "{}",
+                    style(format!(
+                        "Add a CalDAV account with these in Tasks.org, Thunderbird or Apple Reminders; the password is not shown again. Revoke it with `auth apikey revoke {}`",
+                        created.api_key.prefix
+                    ))
+                    .dim()
+                );
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to create CalDAV account"));
+                error!("Failed to create CalDAV account: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_digest_command(&self, command: DigestCommands) -> Result<()> {
+        let digest_service = self.digest_service()?;
+        if let DigestCommands::Run = command {
+            return self.handle_digest_run(digest_service).await;
+        }
+        let Some(user) = self.authorized_user(Scope::TasksRead).await else {
+            return Ok(());
+        };
+
+        match command {
+            DigestCommands::Enable { at } => match digest_service.subscribe(&user.id, at).await {
+                Ok(subscription) => {
+                    println!(
+                        "{} Daily digest enabled: sent to {} at {}",
+                        CHECKMARK,
+                        style(&user.email).cyan(),
+                        style(subscription.send_at.format("%H:%M")).green()
+                    );
+                    println!("{}", style("Digests go out while `todo-cli digest run` runs every few minutes").dim());
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to enable the daily digest"));
+                    error!("Failed to enable the daily digest: {}", e);
+                }
+            },
+            DigestCommands::Disable => match digest_service.unsubscribe(&user.id).await {
+                Ok(true) => println!("{} Daily digest disabled", CHECKMARK),
+                Ok(false) => println!("{} The daily digest was not enabled", INFO),
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to disable the daily digest"));
+                    error!("Failed to disable the daily digest: {}", e);
+                }
+            },
+            DigestCommands::Status => match digest_service.subscription(&user.id).await {
+                Ok(Some(subscription)) => {
+                    println!(
+                        "Daily digest: sent to {} at {}",
+                        style(&user.email).cyan(),
+                        style(subscription.send_at.format("%H:%M")).green()
+                    );
+                    if let Some(sent_on) = subscription.last_sent_on {
+                        println!("Last sent: {}", style(format_day(&sent_on)).dim());
+                    }
+                }
+                Ok(None) => println!("{} The daily digest is off; turn it on with `todo-cli digest enable`", INFO),
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to load the daily digest"));
+                    error!("Failed to load the daily digest: {}", e);
+                }
+            },
+            DigestCommands::Send => match digest_service.send_now(&user).await {
+                Ok(digest) => println!(
+                    "{} Sent today's digest to {}: {} due, {} overdue, {} completed yesterday",
+                    CHECKMARK,
+                    style(&user.email).cyan(),
+                    digest.due_today.len(),
+                    digest.overdue.len(),
+                    digest.completed_yesterday.len()
+                ),
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to send the daily digest"));
+                    error!("Failed to send the daily digest: {}", e);
+                }
+            },
+            DigestCommands::Run => unreachable!("handled above"),
+        }
+
+        Ok(())
+    }
+
+    /// Send the digests due by now, for everyone; needs no login, like `db` commands
+    async fn handle_digest_run(&self, digest_service: &DigestService) -> Result<()> {
+        let run = match digest_service.run_due(calendar::now()).await {
+            Ok(run) => run,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to send daily digests"));
+                error!("Failed to send daily digests: {}", e);
+                return Ok(());
+            }
+        };
+
+        println!("{} Daily digests: {} sent, {} skipped, {} failed", CHECKMARK, run.sent, run.skipped, run.failed.len());
+        for (user_id, e) in &run.failed {
+            println!("{}", e.diagnose().render(&format!("Digest of user {} failed", user_id)));
+        }
+        Ok(())
+    }
+
+    async fn handle_schedule_command(&self, command: ScheduleCommands) -> Result<()> {
+        let schedule_service = self.schedule_service()?;
+        let Some(user) = self.authorized_user(Scope::TasksWrite).await else {
+            return Ok(());
+        };
+
+        match command {
+            ScheduleCommands::Add { title, every, cron, description, priority, context, ahead } => {
+                let cron = match (every, cron) {
+                    (Some(every), _) => match CronSchedule::from_phrase(&every) {
+                        Ok(cron) => cron.to_string(),
+                        Err(e) => {
+                            println!("{}", ScheduleError::InvalidSchedule(e).diagnose().render("Failed to add the schedule"));
+                            return Ok(());
+                        }
+                    },
+                    (None, cron) => cron.unwrap_or_default(),
+                };
+                let request = StoreScheduleRequest {
+                    title,
+                    description,
+                    priority: match priority {
+                        TaskPriority::Low => ModelTaskPriority::Low,
+                        TaskPriority::Medium => ModelTaskPriority::Medium,
+                        TaskPriority::High => ModelTaskPriority::High,
+                    },
+                    context,
+                    cron,
+                    ahead,
+                };
+                match schedule_service.add(&user.id, request).await {
+                    Ok(schedule) => {
+                        println!(
+                            "{} Scheduled '{}' at {}; first task {}",
+                            CHECKMARK,
+                            style(&schedule.title).bold(),
+                            style(&schedule.cron).cyan(),
+                            style(format_date(&schedule.next_run_at)).green()
+                        );
+                        println!("{}", style("Tasks are created while `todo-cli daemon` runs, or `todo-cli tick` every minute").dim());
+                    }
+                    Err(e) => {
+                        println!("{}", e.diagnose().render("Failed to add the schedule"));
+                        error!("Failed to add the schedule: {}", e);
+                    }
+                }
+            }
+            ScheduleCommands::List => match schedule_service.list(&user.id).await {
+                Ok(schedules) if schedules.is_empty() => println!("{} No schedules; add one with `todo-cli schedule add`", INFO),
+                Ok(schedules) => {
+                    println!("{} {}", INFO, style("Schedules").bold().cyan());
+                    for schedule in schedules {
+                        let ahead = match schedule.ahead {
+                            0 => String::new(),
+                            ahead => format!(", {} ahead", ahead),
+                        };
+                        println!(
+                            "  {} {} {} {}",
+                            style(schedule.short_id()).dim(),
+                            schedule.title,
+                            style(format!("({})", schedule.cron)).cyan(),
+                            style(format!("next {}{}", format_date(&schedule.next_run_at), ahead)).dim()
+                        );
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to list schedules"));
+                    error!("Failed to list schedules: {}", e);
+                }
+            },
+            ScheduleCommands::Remove { id } => match schedule_service.remove(&user.id, &id).await {
+                Ok(schedule) => println!("{} Schedule '{}' removed", CHECKMARK, schedule.title),
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to remove the schedule"));
+                    error!("Failed to remove the schedule: {}", e);
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Create the tasks of the schedules due by now, for everyone; needs no
+    /// login, like `digest run`
+    async fn handle_tick(&self) -> Result<()> {
+        let run = match self.schedule_service()?.tick(Utc::now()).await {
+            Ok(run) => run,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to run schedules"));
+                error!("Failed to run schedules: {}", e);
+                return Ok(());
+            }
+        };
+
+        println!("{} Schedules: {} tasks created, {} failed", CHECKMARK, run.created.len(), run.failed.len());
+        for (schedule_id, e) in &run.failed {
+            println!("{}", e.diagnose().render(&format!("Schedule {} failed", schedule_id)));
+        }
+        Ok(())
+    }
+
+    async fn handle_maintain(&self, dry_run: bool) -> Result<()> {
+        if self.maintenance_service.policy().is_off() {
+            println!(
+                "{} Nothing to do: set {} or {} (e.g. 30d) first",
+                INFO,
+                style("maintenance.archive_after").cyan(),
+                style("maintenance.purge_after").cyan()
+            );
+            return Ok(());
+        }
+        let Some(user) = self.authorized_user(Scope::TasksWrite).await else {
+            return Ok(());
+        };
+
+        let report = match self.maintenance_service.run(&user.id, Utc::now(), dry_run).await {
+            Ok(report) => report,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to apply the maintenance policy"));
+                error!("Failed to apply the maintenance policy: {}", e);
+                return Ok(());
+            }
+        };
+
+        if report.is_empty() {
+            println!("{} Nothing to archive or purge", CHECKMARK);
+            return Ok(());
+        }
+        let (archive, purge) = if dry_run { ("Would archive", "Would purge") } else { ("Archived", "Purged") };
+        if !report.archived.is_empty() {
+            println!("{} {} {} completed tasks", CHECKMARK, archive, report.archived.len());
+            for task in &report.archived {
+                let completed = task.completed_at.as_ref().map(format_date).unwrap_or_default();
+                println!("  {} {}", task.title, style(format!("(completed {})", completed)).dim());
+            }
+            if let Some(path) = &report.archive_file {
+                println!("{}", style(format!("Restore them with `todo-cli import --archive {}`", path.display())).dim());
+            }
+        }
+        if !report.purged.is_empty() {
+            println!("{} {} {} archive files", CHECKMARK, purge, report.purged.len());
+            for path in &report.purged {
+                println!("  {}", style(path.display()).dim());
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_remind(&self, dry_run: bool) -> Result<()> {
+        let Some(user) = self.authorized_user(Scope::TasksRead).await else {
+            return Ok(());
+        };
+
+        if dry_run {
+            match self.reminder_service.pending(&user.id, Utc::now()).await {
+                Ok(reminders) if reminders.is_empty() => println!("{} Nothing due soon or overdue", CHECKMARK),
+                Ok(reminders) => {
+                    for reminder in reminders {
+                        println!(
+                            "  {} {} (due {})",
+                            style(format!("{:<8}", reminder.kind)).yellow(),
+                            reminder.title,
+                            format_date(&reminder.due_date)
+                        );
+                    }
+                }
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to check reminders"));
+                    error!("Failed to check reminders: {}", e);
+                }
+            }
+            return Ok(());
+        }
+
+        if self.reminder_service.channel_names().is_empty() {
+            println!(
+                "{} Reminders are off; turn them on with {} or {}",
+                INFO,
+                style("todo-cli config set reminders.desktop true").cyan(),
+                style("todo-cli config set reminders.email true").cyan()
+            );
+            return Ok(());
+        }
+
+        let run = match self.reminder_service.check(&user, Utc::now()).await {
+            Ok(run) => run,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to send reminders"));
+                error!("Failed to send reminders: {}", e);
+                return Ok(());
+            }
+        };
+
+        if run.reminders.is_empty() {
+            println!("{} No new reminders", CHECKMARK);
+        } else if !run.delivered.is_empty() {
+            println!("{} Sent {} reminders by {}", CHECKMARK, run.reminders.len(), run.delivered.join(" and "));
+        }
+        for (channel, e) in &run.failed {
+            println!("{}", e.diagnose().render(&format!("Failed to send {} reminders", channel)));
+        }
+        Ok(())
+    }
+
+    async fn handle_daemon(&self, interval: Duration, detach: bool) -> Result<()> {
+        let session_dir = self.auth_service.session_dir().to_path_buf();
+        if detach {
+            if let Some(pid) = running_pid(&session_dir)? {
+                println!("{}", DaemonError::AlreadyRunning { pid }.diagnose().render("Failed to start the daemon"));
+                return Ok(());
+            }
+            let mut command = std::process::Command::new(std::env::current_exe()?);
+            command
+                .args(["daemon", "--interval", &format!("{}s", interval.as_secs())])
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null());
+            // Out of the terminal's process group, so Ctrl-C there does not reach it
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+            let child = command.spawn().context("Failed to start the daemon")?;
+            println!("{} Daemon started in the background (PID {})", CHECKMARK, child.id());
+            println!("{}", style("Check on it with `todo-cli daemon status`, stop it with `todo-cli daemon stop`").dim());
+            return Ok(());
+        }
+
+        let Some(user) = self.authorized_user(Scope::TasksRead).await else {
+            return Ok(());
+        };
+        let _pid_file = match PidFile::acquire(&session_dir) {
+            Ok(pid_file) => pid_file,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to start the daemon"));
+                return Ok(());
+            }
+        };
+
+        let mut daemon = DaemonService::new(self.reminder_service.clone()).with_user(user);
+        if let Some(digest_service) = &self.digest_service {
+            daemon = daemon.with_digest_service(digest_service.clone());
+        }
+        if let Some(schedule_service) = &self.schedule_service {
+            daemon = daemon.with_schedule_service(schedule_service.clone());
+        }
+        daemon = daemon.with_maintenance_service(self.maintenance_service.clone());
+        let channels = self.reminder_service.channel_names();
+        println!(
+            "{} Daemon running every {}s; reminders by {}{}",
+            ROCKET,
+            interval.as_secs(),
+            if channels.is_empty() { "no channel (see `config set reminders.*`)".to_string() } else { channels.join(", ") },
+            if self.digest_service.is_some() { ", daily digests and schedules on" } else { "" }
+        );
+        println!("{}", style("Press Ctrl-C or run `todo-cli daemon stop` to stop").dim());
+        daemon.run(interval, shutdown_signal()).await;
+
+        println!("{} Daemon stopped", CHECKMARK);
+        Ok(())
+    }
+
+    fn handle_daemon_command(&self, command: DaemonCommands) -> Result<()> {
+        let session_dir = self.auth_service.session_dir();
+        match command {
+            DaemonCommands::Status => match running_pid(session_dir)? {
+                Some(pid) => println!("{} Daemon running (PID {})", CHECKMARK, pid),
+                None => println!("{} Daemon not running", INFO),
+            },
+            DaemonCommands::Stop => match stop_daemon(session_dir) {
+                Ok(pid) => println!("{} Stopping daemon (PID {})", CHECKMARK, pid),
+                Err(e) => println!("{}", e.diagnose().render("Failed to stop the daemon")),
+            },
+        }
+        Ok(())
+    }
+
+    async fn handle_sync_conflicts(&self, list: bool, keep: Option<SyncSide>) -> Result<()> {
+        let Some(sync_service) = &self.sync_service else {
+            println!(
+                "{} No sync conflicts: set {} to a sqlite:// URL to work on a local copy of DATABASE_URL",
+                INFO,
+                style("DATABASE_LOCAL_URL").cyan()
+            );
+            return Ok(());
+        };
+        let user = match self.authorized_user(Scope::TasksWrite).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        let conflicts = match sync_service.conflicts(&user.id).await {
+            Ok(conflicts) => conflicts,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to load sync conflicts"));
+                return Ok(());
+            }
+        };
+        if conflicts.is_empty() {
+            println!("{} No sync conflicts", CHECKMARK);
+            return Ok(());
+        }
+
+        let ids = self.settings()?.id_style();
+        let theme = ColorfulTheme::default();
+        let choices = ["Keep local", "Keep remote", "Decide later"];
+        let mut resolved = 0;
+        for conflict in &conflicts {
+            println!("{}", format_sync_conflict(conflict, ids));
+            if list {
+                continue;
+            }
+
+            let side = match keep {
+                Some(side) => side,
+                None => match Select::with_theme(&theme)
+                    .with_prompt("Which version should both sides keep?")
+                    .items(&choices)
+                    .default(2)
+                    .interact()?
+                {
+                    0 => SyncSide::Local,
+                    1 => SyncSide::Remote,
+                    _ => continue,
+                },
+            };
+            let side = match side {
+                SyncSide::Local => Side::Local,
+                SyncSide::Remote => Side::Remote,
+            };
+            match sync_service.resolve(&conflict.task_id, side).await {
+                Ok(()) => resolved += 1,
+                Err(e) => {
+                    println!("{}", e.diagnose().render("Failed to resolve the conflict"));
+                    error!("Failed to resolve sync conflict of task {}: {}", conflict.task_id, e);
+                }
+            }
+        }
+
+        if !list {
+            println!("{} Resolved {} of {} conflict(s)", CHECKMARK, resolved, conflicts.len());
+        }
+        Ok(())
+    }
+
+    async fn handle_serve(&self, host: std::net::IpAddr, port: u16, migrations_dir: Option<std::path::PathBuf>) -> Result<()> {
+        // The default secret is in the source, so anyone could sign their own bearer tokens
+        if self.auth_service.uses_default_secret() {
+            bail!("Set JWT_SECRET before serving the API; the built-in default lets anyone forge tokens");
+        }
+
+        let addr = std::net::SocketAddr::new(host, port);
+        let state = AppState {
+            auth_service: self.auth_service.clone(),
+            user_service: self.user_service.clone(),
+            task_service: self.task_service.clone(),
+            event_bus: self.event_bus.clone(),
+            database: self.database()?.clone(),
+            metrics: Default::default(),
+            migrations_dir,
+            timezone: self.timezone,
+        };
+
+        println!("{} Serving the REST API on {}", ROCKET, style(format!("http://{}/api/v1", addr)).cyan());
+        #[cfg(feature = "memory")]
+        if matches!(self.database()?.pool(), DatabasePool::Memory) {
+            println!("{} {}", INFO, style("Data is kept in memory and is lost when the server stops").yellow());
+        }
+        // Stream task changes other processes make too, e.g. CLI commands
+        if let Some(pool) = self.database()?.postgres_pool() {
+            match ChangeListener::connect(pool).await {
+                Ok(listener) => {
+                    let task_service = self.task_service.clone();
+                    tokio::spawn(calendar::with_timezone(calendar::timezone(), async move {
+                        if let Err(e) = task_service.relay_changes(listener).await {
+                            warn!("Stopped relaying task changes from the database: {}", e);
+                        }
+                    }));
+                }
+                Err(e) => warn!("Only changes made through the API are streamed: {}", e),
+            }
+        }
+        let sync = self.sync_service.clone().map(|sync_service| sync_service.spawn(SYNC_INTERVAL));
+        println!("{}", style("Press Ctrl-C to stop").dim());
+        let served = api::serve(state, addr).await;
+        if let Some(sync) = sync {
+            sync.abort();
+        }
+        served.with_context(|| format!("Failed to serve the API on {}", addr))?;
+
+        println!("{} API server stopped", CHECKMARK);
         Ok(())
     }
 
-    // Stats Commands (placeholder implementation)
-    async fn handle_stats_command(&self, _period: StatsPeriod) -> Result<()> {
-        println!("{} Statistics functionality is not yet implemented", WARNING);
+    async fn handle_workspace_stats(&self, period: StatsPeriod, workspace: String) -> Result<()> {
+        let user = match self.authorized_user(Scope::TasksRead).await {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+
+        match self.workspace_service()?.statistics(&user.id, &workspace, period.start()).await {
+            Ok((workspace, stats)) => {
+                let title = match period {
+                    StatsPeriod::All => format!("Statistics for {}", workspace.name),
+                    _ => format!("Statistics for {} (completions this {})", workspace.name, period),
+                };
+                println!("{} {}", INFO, style(title).bold().cyan());
+                println!("{}", format_workspace_statistics(&stats));
+            }
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to load workspace statistics"));
+                error!("Failed to load workspace statistics: {}", e);
+            }
+        }
+
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// A task in JSON output, with its ID also in the configured display style
+#[derive(Serialize)]
+struct TaskJson<'a> {
+    display_id: String,
+    #[serde(flatten)]
+    task: &'a Task,
+}
+
+impl<'a> TaskJson<'a> {
+    fn new(task: &'a Task, ids: IdStyle) -> Self {
+        Self { display_id: ids.format(task), task }
+    }
+}
+
+/// Prompt for a new password twice, rejecting very weak ones, then show how
+/// strong the accepted password is
+fn prompt_new_password(theme: &ColorfulTheme, prompt: &str, user_inputs: &[&str]) -> Result<String> {
+    let password: String = Password::with_theme(theme)
+        .with_prompt(prompt)
+        .with_confirmation(format!("Confirm {}", prompt.to_lowercase()), "Passwords don't match")
+        .validate_with(|input: &String| -> Result<(), String> {
+            if input.len() < 8 {
+                return Err("Password must be at least 8 characters".to_string());
+            }
+            let strength = PasswordStrength::estimate(input, user_inputs);
+            if strength.is_acceptable() {
+                Ok(())
+            } else {
+                Err(strength.summary())
+            }
+        })
+        .interact()?;
+
+    let strength = PasswordStrength::estimate(&password, user_inputs);
+    let meter = match strength.score {
+        0 | 1 => style(strength.meter()).red(),
+        2 => style(strength.meter()).yellow(),
+        _ => style(strength.meter()).green(),
+    };
+    println!("Password strength: {}", meter);
+    if let Some(warning) = &strength.warning {
+        println!("{} {}", WARNING, warning);
+    }
+    for suggestion in &strength.suggestions {
+        println!("  • {}", style(suggestion).dim());
+    }
+
+    Ok(password)
+}
+
+/// Replacing an import deletes every existing task, so ask first
+fn confirm_replace() -> Result<bool> {
+    let theme = ColorfulTheme::default();
+    let confirm = Confirm::with_theme(&theme)
+        .with_prompt("Importing without --merge replaces all existing tasks. Continue?")
+        .default(false)
+        .interact()?;
+    Ok(confirm)
+}
+
+/// Print a value as pretty JSON, keeping only the requested fields
+fn print_json<T: Serialize>(value: &T, fields: Option<&FieldProjection>) -> Result<()> {
+    let mut json = serde_json::to_value(value).context("Failed to serialize output")?;
+    if let Some(projection) = fields {
+        json = projection.apply(&json);
+    }
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
@@ -1,5 +1,9 @@
 pub mod commands;
 pub mod args;
+#[cfg(feature = "memory")]
+pub mod tutorial;
 
 pub use commands::*;
-pub use args::*;
\ No newline at end of file
+pub use args::*;
+#[cfg(feature = "memory")]
+pub use tutorial::*;
\ No newline at end of file
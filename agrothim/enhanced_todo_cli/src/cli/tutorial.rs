@@ -0,0 +1,223 @@
+use anyhow::Result;
+use console::style;
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    cli::commands::{CHECKMARK, CROSS, INFO, ROCKET},
+    database::repositories::InMemoryTaskRepository,
+    error::Diagnose,
+    models::{
+        status::StatusWorkflow,
+        task::{StoreTaskRequest, TaskFilter, TaskPriority},
+        task_ref::{IdStyle, TaskRef},
+    },
+    services::TaskService,
+    utils::formatting::{format_task_detail, format_task_table},
+};
+
+const STEPS: usize = 4;
+
+/// Tasks added for the user so that listing and searching have something to show
+const SAMPLE_TASKS: [(&str, Option<&str>, TaskPriority); 3] = [
+    ("Buy groceries", Some("Milk, eggs and bread"), TaskPriority::Medium),
+    ("Call the dentist", None, TaskPriority::High),
+    ("Read a chapter of a book", None, TaskPriority::Low),
+];
+
+/// Interactive walk through creating, listing, completing and searching tasks.
+///
+/// Everything happens in an in-memory task list that is thrown away at the
+/// end, so the tutorial needs neither a database nor an account.
+pub struct Tutorial {
+    task_service: TaskService,
+    user_id: Uuid,
+    workflow: StatusWorkflow,
+    theme: ColorfulTheme,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Self {
+            task_service: TaskService::new(Arc::new(InMemoryTaskRepository::new())),
+            user_id: Uuid::new_v4(),
+            workflow: StatusWorkflow::default(),
+            theme: ColorfulTheme::default(),
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        println!("{} {}", ROCKET, style("Welcome to todo-cli!").bold().cyan());
+        println!("This tutorial shows the everyday commands on a practice task list.");
+        println!(
+            "{} Nothing you do here is saved or touches your real tasks.",
+            INFO
+        );
+        println!();
+
+        if !Confirm::with_theme(&self.theme)
+            .with_prompt("Ready to start?")
+            .default(true)
+            .interact()?
+        {
+            println!("Run {} whenever you like.", style("todo-cli tutorial").cyan());
+            return Ok(());
+        }
+
+        self.create_step().await?;
+        self.list_step().await?;
+        self.complete_step().await?;
+        self.search_step().await?;
+
+        println!();
+        println!("{} {}", CHECKMARK, style("You finished the tutorial!").bold().green());
+        println!("To start with your real task list:");
+        println!("  {}   create an account", style("todo-cli auth register").cyan());
+        println!("  {}      log in", style("todo-cli auth login").cyan());
+        println!("  {}          see every command", style("todo-cli --help").cyan());
+        Ok(())
+    }
+
+    async fn create_step(&self) -> Result<()> {
+        step_header(1, "Create a task");
+
+        let title: String = Input::with_theme(&self.theme)
+            .with_prompt("What do you need to do?")
+            .default("Water the plants".to_string())
+            .interact_text()?;
+
+        let priorities = ["low", "medium", "high"];
+        let choice = Select::with_theme(&self.theme)
+            .with_prompt("How important is it?")
+            .items(&priorities)
+            .default(1)
+            .interact()?;
+        let priority = match choice {
+            0 => TaskPriority::Low,
+            2 => TaskPriority::High,
+            _ => TaskPriority::Medium,
+        };
+
+        let request = StoreTaskRequest {
+            title: title.clone(),
+            priority,
+            ..Default::default()
+        };
+        let task = match self.task_service.create_task(&self.user_id, request).await {
+            Ok(task) => task,
+            Err(e) => {
+                println!("{}", e.diagnose().render("Failed to create task"));
+                return self.create_sample_tasks().await;
+            }
+        };
+
+        println!("{} Task created!", CHECKMARK);
+        println!("{}", format_task_detail(&task, &self.workflow, IdStyle::Number, None));
+        explain_command(&format!("todo-cli task add \"{}\" --priority {}", title, priorities[choice]));
+
+        self.create_sample_tasks().await
+    }
+
+    async fn create_sample_tasks(&self) -> Result<()> {
+        for (title, description, priority) in SAMPLE_TASKS {
+            let request = StoreTaskRequest {
+                title: title.to_string(),
+                description: description.map(str::to_string),
+                priority,
+                ..Default::default()
+            };
+            self.task_service.create_task(&self.user_id, request).await?;
+        }
+        println!(
+            "{} We also added {} example tasks so the list is not empty.",
+            INFO,
+            SAMPLE_TASKS.len()
+        );
+        Ok(())
+    }
+
+    async fn list_step(&self) -> Result<()> {
+        step_header(2, "List your tasks");
+        self.print_tasks().await?;
+        println!("Every task has a number; commands accept it wherever they need a task ID.");
+        explain_command("todo-cli task list");
+        println!(
+            "Filter with e.g. {} or {}.",
+            style("--priority high").cyan(),
+            style("--today").cyan()
+        );
+        Ok(())
+    }
+
+    async fn complete_step(&self) -> Result<()> {
+        step_header(3, "Complete a task");
+
+        let task = loop {
+            let input: String = Input::with_theme(&self.theme)
+                .with_prompt("Which task is done? Enter its number")
+                .default("1".to_string())
+                .interact_text()?;
+
+            let task_ref = match input.parse::<TaskRef>() {
+                Ok(task_ref) => task_ref,
+                Err(e) => {
+                    println!("{} {}", CROSS, style(e).red());
+                    continue;
+                }
+            };
+            let completed = match self.task_service.resolve_task_ref(&self.user_id, &task_ref).await {
+                Ok(task_id) => self.task_service.complete_task(&self.user_id, &task_id).await,
+                Err(e) => Err(e),
+            };
+            match completed {
+                Ok(task) => break task,
+                Err(e) => println!("{}", e.diagnose().render("Failed to complete task")),
+            }
+        };
+
+        println!("{} Completed '{}'", CHECKMARK, style(&task.title).green());
+        self.print_tasks().await?;
+        explain_command(&format!("todo-cli task complete {}", task.number));
+        Ok(())
+    }
+
+    async fn search_step(&self) -> Result<()> {
+        step_header(4, "Search");
+
+        let query: String = Input::with_theme(&self.theme)
+            .with_prompt("Search titles and descriptions for")
+            .default("milk".to_string())
+            .interact_text()?;
+
+        let tasks = self.task_service.search_tasks(&self.user_id, &query, None).await?;
+        if tasks.is_empty() {
+            println!("{} No tasks match '{}'", INFO, query);
+        } else {
+            println!("{}", format_task_table(&tasks, &self.workflow, IdStyle::Number));
+        }
+        explain_command(&format!("todo-cli task list --search \"{}\"", query));
+        Ok(())
+    }
+
+    async fn print_tasks(&self) -> Result<()> {
+        let tasks = self.task_service.get_tasks(&self.user_id, TaskFilter::new()).await?;
+        println!("{}", format_task_table(&tasks, &self.workflow, IdStyle::Number));
+        Ok(())
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn step_header(step: usize, title: &str) {
+    println!();
+    println!("{}", style(format!("Step {}/{}: {}", step, STEPS, title)).bold().cyan());
+}
+
+fn explain_command(command: &str) {
+    println!("Outside the tutorial, the command is: {}", style(command).cyan());
+}
@@ -0,0 +1,76 @@
+use serde::Deserialize;
+use sqlx::postgres::{PgListener, PgPool};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// Channel the `tasks` trigger notifies on
+pub const TASK_CHANGES_CHANNEL: &str = "task_changes";
+
+/// Statement that changed a task row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowOperation {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A task row change announced by PostgreSQL. It names the row only; the
+/// task itself has to be read again.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TaskRowChange {
+    pub operation: RowOperation,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    /// Whether an update moved the task to the completed status
+    pub completed: bool,
+    /// `application_name` of the connection that wrote the row
+    pub origin: String,
+}
+
+impl TaskRowChange {
+    /// Whether this process wrote the row, so its services already know
+    pub fn is_local(&self) -> bool {
+        self.origin == origin()
+    }
+}
+
+/// `application_name` of this process's PostgreSQL connections; the trigger
+/// copies it into every change, which tells listeners who wrote the row
+pub fn origin() -> &'static str {
+    static ORIGIN: OnceLock<String> = OnceLock::new();
+    ORIGIN.get_or_init(|| format!("todo-cli {}", Uuid::new_v4().simple()))
+}
+
+/// Subscription to the task changes of every process writing to the
+/// database, for services that would otherwise poll for them
+pub struct ChangeListener {
+    listener: PgListener,
+}
+
+impl ChangeListener {
+    /// Start listening on a connection of its own from `pool`
+    pub async fn connect(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(TASK_CHANGES_CHANNEL).await?;
+        Ok(Self { listener })
+    }
+
+    /// Wait for the next change. A lost connection is reopened on the next
+    /// call, but changes made in between are missed.
+    pub async fn recv(&mut self) -> Result<TaskRowChange, sqlx::Error> {
+        loop {
+            let notification = self.listener.recv().await?;
+            if let Some(change) = parse(notification.payload()) {
+                return Ok(change);
+            }
+        }
+    }
+}
+
+fn parse(payload: &str) -> Option<TaskRowChange> {
+    serde_json::from_str(payload)
+        .map_err(|e| tracing::warn!("Ignoring malformed task change notification: {}", e))
+        .ok()
+}
@@ -1,9 +1,59 @@
 use anyhow::Context;
-use sqlx::PgPool;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use log::LevelFilter;
+use sqlx::{migrate::Migrator, ConnectOptions, Database as SqlxDatabase, PgPool};
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::pool::PoolOptions;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::database::change_listener;
+use crate::database::Replica;
+
+/// Migrations in `migrations/`, built into the binary for `db migrate`
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Newest migration in `migrations/`, which this build's queries are written
+/// against; bump it with every new migration
+pub const SCHEMA_VERSION: i64 = 20261021110000;
+
+/// Schema of SQLite databases, applied whenever one is opened
+#[cfg(feature = "sqlite")]
+pub(crate) static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+/// A PostgreSQL schema this build cannot work with
+#[derive(Error, Debug)]
+pub enum SchemaVersionError {
+    #[error("Database has no schema yet")]
+    Missing,
+
+    #[error("Database schema is at version {found}, but this todo-cli needs {expected}")]
+    Outdated { found: i64, expected: i64 },
+
+    #[error("Failed to read the schema version: {0}")]
+    Query(#[from] sqlx::Error),
+}
+
+/// Connection pool of the backend `DATABASE_URL` points at
+#[derive(Debug, Clone)]
+pub enum DatabasePool {
+    Postgres(PgPool),
+    /// Local file database; it stores users and tasks only
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqlitePool),
+    /// Nothing to connect to: the repositories keep users and tasks in process memory
+    #[cfg(feature = "memory")]
+    Memory,
+}
 
 #[derive(Debug, Clone)]
 pub struct Database {
-    pool: PgPool,
+    pool: DatabasePool,
+    /// Where read-only task queries go while it is up; PostgreSQL only
+    replica: Option<Replica>,
 }
 
 #[allow(dead_code)]
@@ -13,61 +63,418 @@ pub struct ConnectionConfig {
     pub username: String,
     pub password: String,
     pub database: String,
+    pub pool: PoolConfig,
+}
+
+/// Connection pool limits and timeouts; unset fields keep the sqlx defaults
+/// (at most 10 connections, 30s to acquire one, idle ones closed after 10 minutes)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolConfig {
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+    /// How long to wait for a free connection before failing
+    pub acquire_timeout: Option<Duration>,
+    /// Close connections that stay unused this long
+    pub idle_timeout: Option<Duration>,
+    /// PostgreSQL cancels statements running longer than this
+    pub statement_timeout: Option<Duration>,
+    /// Statements running longer than this are logged as warnings (sqlx
+    /// default: 1s)
+    pub slow_query_threshold: Option<Duration>,
+}
+
+impl PoolConfig {
+    /// Read the `DATABASE_MAX_CONNECTIONS`, `DATABASE_MIN_CONNECTIONS`,
+    /// `DATABASE_{ACQUIRE,IDLE,STATEMENT}_TIMEOUT_SECS` and
+    /// `DATABASE_SLOW_QUERY_MS` environment variables
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let config = PoolConfig {
+            max_connections: env_number("DATABASE_MAX_CONNECTIONS")?,
+            min_connections: env_number("DATABASE_MIN_CONNECTIONS")?,
+            acquire_timeout: env_number("DATABASE_ACQUIRE_TIMEOUT_SECS")?.map(Duration::from_secs),
+            idle_timeout: env_number("DATABASE_IDLE_TIMEOUT_SECS")?.map(Duration::from_secs),
+            statement_timeout: env_number("DATABASE_STATEMENT_TIMEOUT_SECS")?.map(Duration::from_secs),
+            slow_query_threshold: env_number("DATABASE_SLOW_QUERY_MS")?.map(Duration::from_millis),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.max_connections == Some(0) {
+            anyhow::bail!("DATABASE_MAX_CONNECTIONS must be at least 1");
+        }
+        if let (Some(min), Some(max)) = (self.min_connections, self.max_connections) {
+            if min > max {
+                anyhow::bail!(
+                    "DATABASE_MIN_CONNECTIONS ({}) is larger than DATABASE_MAX_CONNECTIONS ({})",
+                    min,
+                    max
+                );
+            }
+        }
+        if self.statement_timeout == Some(Duration::ZERO) {
+            anyhow::bail!("DATABASE_STATEMENT_TIMEOUT_SECS must be at least 1; leave it unset for no limit");
+        }
+        if self.slow_query_threshold == Some(Duration::ZERO) {
+            anyhow::bail!("DATABASE_SLOW_QUERY_MS must be at least 1; use --verbose to see every query");
+        }
+        Ok(())
+    }
+
+    /// `options` with the configured limits applied
+    pub(crate) fn apply<DB: SqlxDatabase>(&self, mut options: PoolOptions<DB>) -> PoolOptions<DB> {
+        if let Some(max) = self.max_connections {
+            options = options.max_connections(max);
+        }
+        if let Some(min) = self.min_connections {
+            options = options.min_connections(min);
+        }
+        if let Some(timeout) = self.acquire_timeout {
+            options = options.acquire_timeout(timeout);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            options = options.idle_timeout(timeout);
+        }
+        options
+    }
+
+    /// Set `statement_timeout` on every connection the pool opens
+    pub(crate) fn apply_statement_timeout(&self, options: PgConnectOptions) -> PgConnectOptions {
+        match self.statement_timeout {
+            Some(timeout) => options.options([("statement_timeout", timeout.as_millis().to_string())]),
+            None => options,
+        }
+    }
+
+    /// Log every statement with its duration and row count at debug level
+    /// (shown by `--verbose`), and the ones slower than the threshold as
+    /// warnings
+    pub(crate) fn apply_query_logging<O: ConnectOptions>(&self, options: O) -> O {
+        let options = options.log_statements(LevelFilter::Debug);
+        match self.slow_query_threshold {
+            Some(threshold) => options.log_slow_statements(LevelFilter::Warn, threshold),
+            None => options,
+        }
+    }
+}
+
+fn env_number<T: FromStr>(name: &str) -> Result<Option<T>, anyhow::Error> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| anyhow::anyhow!("{} must be a whole number, got `{}`", name, value)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Storage backend selected by the scheme of a DATABASE_URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
+impl Backend {
+    /// The backend for `database_url`; only the scheme shows up in the error,
+    /// never the credentials
+    pub fn from_url(database_url: &str) -> Result<Self, anyhow::Error> {
+        let scheme = database_url.split_once(':').map_or("", |(scheme, _)| scheme);
+        match scheme {
+            "postgres" | "postgresql" => Ok(Backend::Postgres),
+            "sqlite" => Ok(Backend::Sqlite),
+            "memory" => Ok(Backend::Memory),
+            _ => anyhow::bail!(
+                "Unsupported DATABASE_URL scheme `{}`; use postgres://, sqlite:// or memory://",
+                scheme
+            ),
+        }
+    }
 }
 
 impl Database {
     #[allow(dead_code)]
     pub async fn new(config: &ConnectionConfig) -> Result<Self, anyhow::Error> {
         tracing::info!("Connecting to database...");
-        let pool = PgPool::connect_with(
-            sqlx::postgres::PgConnectOptions::new()
-                .host(&config.host)
-                .port(config.port)
-                .username(&config.username)
-                .password(&config.password)
-                .database(&config.database),
-        )
-        .await
-        .context("Failed to connect to database")?;
+        let options = PgConnectOptions::new()
+            .host(&config.host)
+            .port(config.port)
+            .username(&config.username)
+            .password(&config.password)
+            .database(&config.database)
+            .application_name(change_listener::origin());
+        let pool = config
+            .pool
+            .apply(PgPoolOptions::new())
+            .connect_with(config.pool.apply_query_logging(config.pool.apply_statement_timeout(options)))
+            .await
+            .context("Failed to connect to database")?;
 
         tracing::info!("Database connected successfully");
-        Ok(Database { pool })
+        Ok(Database { pool: DatabasePool::Postgres(pool), replica: None })
     }
 
+    /// Connect to PostgreSQL, open the SQLite database of a `sqlite://` URL,
+    /// or use in-memory storage for `memory://`
     #[allow(dead_code)]
     pub async fn from_url(database_url: &str) -> Result<Self, anyhow::Error> {
-        tracing::info!("Connecting to database from URL: {}", 
+        Self::from_url_with(database_url, &PoolConfig::default()).await
+    }
+
+    /// [`Database::from_url`] with the given pool limits
+    pub async fn from_url_with(database_url: &str, pool_config: &PoolConfig) -> Result<Self, anyhow::Error> {
+        match Backend::from_url(database_url)? {
+            Backend::Postgres => {}
+            Backend::Sqlite => return Self::open_sqlite(database_url, pool_config).await,
+            Backend::Memory => return Self::in_memory(),
+        }
+
+        tracing::info!("Connecting to database from URL: {}",
             database_url.replace(char::is_alphanumeric, "*")); // Hide credentials
-        let pool = PgPool::connect(database_url)
+        let options = PgConnectOptions::from_str(database_url)
+            .context("Invalid DATABASE_URL")?
+            .application_name(change_listener::origin());
+        let pool = pool_config
+            .apply(PgPoolOptions::new())
+            .connect_with(pool_config.apply_query_logging(pool_config.apply_statement_timeout(options)))
             .await
             .context("Failed to connect to database")?;
 
         tracing::info!("Database connected successfully");
-        Ok(Database { pool })
+        Ok(Database { pool: DatabasePool::Postgres(pool), replica: None })
     }
 
-    #[allow(dead_code)]
-    pub async fn health_check(&self) -> Result<bool, anyhow::Error> {
-        let health_check = sqlx::query!("SELECT 1 as health_check")
-            .fetch_one(&self.pool)
+    /// PostgreSQL pool that only connects when first used, so a server that
+    /// is down does not stop startup; the local-first mode's remote database
+    pub fn connect_lazy(database_url: &str, pool_config: &PoolConfig) -> Result<Self, anyhow::Error> {
+        if Backend::from_url(database_url)? != Backend::Postgres {
+            anyhow::bail!("DATABASE_LOCAL_URL needs a PostgreSQL DATABASE_URL to sync with");
+        }
+        let options = PgConnectOptions::from_str(database_url)
+            .context("Invalid DATABASE_URL")?
+            .application_name(change_listener::origin());
+        let pool = pool_config
+            .apply(PgPoolOptions::new())
+            .connect_lazy_with(pool_config.apply_query_logging(pool_config.apply_statement_timeout(options)));
+        Ok(Database { pool: DatabasePool::Postgres(pool), replica: None })
+    }
+
+    /// Open (creating it if needed) and migrate a SQLite database
+    #[cfg(feature = "sqlite")]
+    async fn open_sqlite(database_url: &str, pool_config: &PoolConfig) -> Result<Self, anyhow::Error> {
+        tracing::info!("Opening SQLite database {}", database_url);
+        let options = SqliteConnectOptions::from_str(database_url)
+            .context("Invalid SQLite database URL")?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let pool = pool_config
+            .apply(SqlitePoolOptions::new())
+            .connect_with(pool_config.apply_query_logging(options))
+            .await
+            .context("Failed to open SQLite database")?;
+
+        SQLITE_MIGRATOR
+            .run(&pool)
             .await
-            .with_context(|| "Failed to perform health check")?;
+            .context("Failed to migrate SQLite database")?;
 
-        tracing::info!("Database health check passed");
-        Ok(health_check.health_check.unwrap_or(0) == 1)
+        tracing::info!("SQLite database ready");
+        Ok(Database { pool: DatabasePool::Sqlite(pool), replica: None })
     }
 
-    #[allow(dead_code)]
-    pub fn pool(&self) -> &PgPool {
+    #[cfg(not(feature = "sqlite"))]
+    async fn open_sqlite(_database_url: &str, _pool_config: &PoolConfig) -> Result<Self, anyhow::Error> {
+        anyhow::bail!("This build has no SQLite support; rebuild with `--features sqlite`")
+    }
+
+    #[cfg(feature = "memory")]
+    fn in_memory() -> Result<Self, anyhow::Error> {
+        tracing::warn!("Using in-memory storage; nothing is saved when the process exits");
+        Ok(Database { pool: DatabasePool::Memory, replica: None })
+    }
+
+    #[cfg(not(feature = "memory"))]
+    fn in_memory() -> Result<Self, anyhow::Error> {
+        anyhow::bail!("This build has no in-memory storage; rebuild with `--features memory`")
+    }
+
+    pub async fn health_check(&self) -> Result<bool, anyhow::Error> {
+        let query = "SELECT 1";
+        let health_check: i32 = match &self.pool {
+            DatabasePool::Postgres(pool) => sqlx::query_scalar(query).fetch_one(pool).await,
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => sqlx::query_scalar(query).fetch_one(pool).await,
+            #[cfg(feature = "memory")]
+            DatabasePool::Memory => Ok(1),
+        }
+        .with_context(|| "Failed to perform health check")?;
+
+        // Debug level: probes call this every few seconds
+        tracing::debug!("Database health check passed");
+        Ok(health_check == 1)
+    }
+
+    /// Apply the built-in migrations that are still pending and return their
+    /// versions. SQLite is already migrated when opened and in-memory storage
+    /// has no schema, so for them this only confirms there is nothing to do.
+    pub async fn migrate(&self) -> Result<Vec<i64>, anyhow::Error> {
+        let pending = self.pending_migrations(None).await?;
+        match &self.pool {
+            DatabasePool::Postgres(pool) => MIGRATOR.run(pool).await,
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await,
+            #[cfg(feature = "memory")]
+            DatabasePool::Memory => Ok(()),
+        }
+        .context("Failed to apply migrations")?;
+
+        Ok(pending)
+    }
+
+    /// Versions of the migrations in `migrations_dir`, or of the built-in ones
+    /// when it is `None`, that sqlx has not recorded as successfully applied.
+    /// SQLite databases are checked against their own embedded schema instead,
+    /// whatever `migrations_dir` is, and in-memory storage has no schema at all.
+    pub async fn pending_migrations(&self, migrations_dir: Option<&Path>) -> Result<Vec<i64>, anyhow::Error> {
+        let query = "SELECT version FROM _sqlx_migrations WHERE success";
+        match &self.pool {
+            DatabasePool::Postgres(pool) => {
+                let migrator = match migrations_dir {
+                    Some(dir) => &Migrator::new(dir)
+                        .await
+                        .with_context(|| format!("Failed to read migrations from {}", dir.display()))?,
+                    None => &MIGRATOR,
+                };
+                let applied: Vec<i64> = match sqlx::query_scalar(query).fetch_all(pool).await {
+                    Ok(versions) => versions,
+                    // undefined_table: no migration has ever been run through sqlx
+                    Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => Vec::new(),
+                    Err(e) => return Err(e).context("Failed to read applied migrations"),
+                };
+                Ok(unapplied(migrator, &applied))
+            }
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => {
+                let applied: Vec<i64> = sqlx::query_scalar(query)
+                    .fetch_all(pool)
+                    .await
+                    .context("Failed to read applied migrations")?;
+                Ok(unapplied(&SQLITE_MIGRATOR, &applied))
+            }
+            #[cfg(feature = "memory")]
+            DatabasePool::Memory => Ok(Vec::new()),
+        }
+    }
+
+    /// Fail unless the migrations table puts the schema at [`SCHEMA_VERSION`]
+    /// or later, so an outdated database is reported up front instead of as a
+    /// missing column halfway through a command. SQLite is migrated whenever
+    /// it is opened and in-memory storage has no schema.
+    pub async fn verify_schema_version(&self) -> Result<(), SchemaVersionError> {
+        let Some(pool) = self.postgres_pool() else {
+            return Ok(());
+        };
+
+        let found = match sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+            .fetch_one(pool)
+            .await
+        {
+            Ok(version) => version,
+            // undefined_table: no migration has ever been run through sqlx
+            Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42P01") => None,
+            Err(e) => return Err(e.into()),
+        };
+        check_schema_version(found)
+    }
+
+    pub fn pool(&self) -> &DatabasePool {
         &self.pool
     }
 
+    /// Send read-only task queries to the replica at `url` while it answers
+    pub fn with_replica(mut self, url: &str, pool_config: &PoolConfig) -> Result<Self, anyhow::Error> {
+        if self.postgres_pool().is_none() {
+            anyhow::bail!("DATABASE_READ_URL needs a PostgreSQL DATABASE_URL");
+        }
+        if Backend::from_url(url)? != Backend::Postgres {
+            anyhow::bail!("DATABASE_READ_URL must be a postgres:// URL");
+        }
+        self.replica = Some(Replica::connect(url, pool_config)?);
+        Ok(self)
+    }
+
+    pub fn replica(&self) -> Option<&Replica> {
+        self.replica.as_ref()
+    }
+
+    /// The PostgreSQL pool, or `None` on the other backends
+    pub fn postgres_pool(&self) -> Option<&PgPool> {
+        match &self.pool {
+            DatabasePool::Postgres(pool) => Some(pool),
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(_) => None,
+            #[cfg(feature = "memory")]
+            DatabasePool::Memory => None,
+        }
+    }
+
     #[allow(dead_code)]
     pub async fn close(&self) -> Result<(), anyhow::Error> {
-        self.pool
-            .close()
-            .await;
+        match &self.pool {
+            DatabasePool::Postgres(pool) => pool.close().await,
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => pool.close().await,
+            #[cfg(feature = "memory")]
+            DatabasePool::Memory => {}
+        }
         tracing::info!("Database connection closed successfully");
         Ok(())
     }
 }
+
+fn check_schema_version(found: Option<i64>) -> Result<(), SchemaVersionError> {
+    match found {
+        None => Err(SchemaVersionError::Missing),
+        Some(found) if found < SCHEMA_VERSION => Err(SchemaVersionError::Outdated { found, expected: SCHEMA_VERSION }),
+        Some(found) => {
+            if found > SCHEMA_VERSION {
+                // Migrations only add to the schema, so an older build keeps working
+                tracing::warn!("Database schema version {} is newer than this todo-cli ({})", found, SCHEMA_VERSION);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn unapplied(migrator: &Migrator, applied: &[i64]) -> Vec<i64> {
+    migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_version_is_the_newest_migration() {
+        assert_eq!(MIGRATOR.iter().map(|migration| migration.version).max(), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_check_schema_version() {
+        assert!(matches!(check_schema_version(None), Err(SchemaVersionError::Missing)));
+        assert!(matches!(
+            check_schema_version(Some(20250827040848)),
+            Err(SchemaVersionError::Outdated { found: 20250827040848, expected: SCHEMA_VERSION })
+        ));
+        assert!(check_schema_version(Some(SCHEMA_VERSION)).is_ok());
+        assert!(check_schema_version(Some(SCHEMA_VERSION + 1)).is_ok());
+    }
+}
@@ -1,4 +1,11 @@
+pub mod change_listener;
 pub mod connection;
+pub mod doctor;
+pub mod factory;
+pub mod replica;
 pub mod repositories;
 
-pub use connection::*;
\ No newline at end of file
+pub use change_listener::*;
+pub use connection::*;
+pub use factory::*;
+pub use replica::*;
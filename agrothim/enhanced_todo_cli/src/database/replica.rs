@@ -0,0 +1,81 @@
+use anyhow::Context;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use crate::database::{change_listener, PoolConfig};
+
+/// How often the replica is probed, both while it is up and while it is down
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A probe the replica does not answer in time counts as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Read-only PostgreSQL replica (`DATABASE_READ_URL`). A background probe
+/// tracks whether it answers; while it does not, reads go to the primary.
+#[derive(Debug, Clone)]
+pub struct Replica {
+    state: Arc<ReplicaState>,
+}
+
+#[derive(Debug)]
+struct ReplicaState {
+    pool: PgPool,
+    available: AtomicBool,
+}
+
+impl Replica {
+    /// Pool for the replica at `url`. It connects lazily, so a replica that is
+    /// down does not stop startup, and counts as unavailable until the first
+    /// probe succeeds.
+    pub fn connect(url: &str, pool_config: &PoolConfig) -> Result<Self, anyhow::Error> {
+        let options = PgConnectOptions::from_str(url)
+            .context("Invalid DATABASE_READ_URL")?
+            .application_name(change_listener::origin());
+        let pool = pool_config
+            .apply(PgPoolOptions::new())
+            .connect_lazy_with(pool_config.apply_query_logging(pool_config.apply_statement_timeout(options)));
+
+        let replica = Self {
+            state: Arc::new(ReplicaState {
+                pool,
+                available: AtomicBool::new(false),
+            }),
+        };
+        tokio::spawn(monitor(Arc::downgrade(&replica.state)));
+        Ok(replica)
+    }
+
+    /// The replica's pool while it answers
+    pub fn pool(&self) -> Option<&PgPool> {
+        self.is_available().then_some(&self.state.pool)
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.state.available.load(Ordering::Relaxed)
+    }
+}
+
+/// Probe the replica until every `Replica` handle is dropped
+async fn monitor(state: Weak<ReplicaState>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+
+        let probe = sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&state.pool);
+        let available = matches!(tokio::time::timeout(CHECK_TIMEOUT, probe).await, Ok(Ok(1)));
+        if state.available.swap(available, Ordering::Relaxed) != available {
+            if available {
+                tracing::info!("Read replica is available; reads go to the replica");
+            } else {
+                tracing::warn!("Read replica is not answering; reads go to the primary");
+            }
+        }
+    }
+}
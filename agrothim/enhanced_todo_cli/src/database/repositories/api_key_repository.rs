@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::api_key::ApiKey;
+
+#[derive(Error, Debug)]
+pub enum ApiKeyRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Data needed to store a new API key
+#[derive(Debug, Clone)]
+pub struct StoreApiKeyRequest {
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// API key repository trait for long-lived script credentials
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn store(&self, user_id: &Uuid, request: StoreApiKeyRequest) -> Result<ApiKey, ApiKeyRepositoryError>;
+    /// The unrevoked, unexpired key with this hash, marking it as used
+    async fn use_key(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError>;
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<ApiKey>, ApiKeyRepositoryError>;
+    /// Revoke the user's key with this ID or prefix; `None` if there is no such active key
+    async fn revoke(&self, user_id: &Uuid, id_or_prefix: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError>;
+}
+
+/// PostgreSQL implementation of ApiKeyRepository
+pub struct PostgresApiKeyRepository {
+    pool: PgPool,
+}
+
+impl PostgresApiKeyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for PostgresApiKeyRepository {
+    #[instrument(level = "debug", name = "ApiKeyRepository::store", skip_all)]
+    async fn store(&self, user_id: &Uuid, request: StoreApiKeyRequest) -> Result<ApiKey, ApiKeyRepositoryError> {
+        let query = r#"
+            INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at, revoked_at, created_at
+        "#;
+
+        let api_key = sqlx::query_as::<_, ApiKey>(query)
+            .bind(user_id)
+            .bind(&request.name)
+            .bind(&request.prefix)
+            .bind(&request.key_hash)
+            .bind(&request.scopes)
+            .bind(request.expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(api_key)
+    }
+
+    #[instrument(level = "debug", name = "ApiKeyRepository::use_key", skip_all)]
+    async fn use_key(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError> {
+        let query = r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE key_hash = $1
+              AND revoked_at IS NULL
+              AND (expires_at IS NULL OR expires_at > NOW())
+            RETURNING id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at, revoked_at, created_at
+        "#;
+
+        let api_key = sqlx::query_as::<_, ApiKey>(query)
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(api_key)
+    }
+
+    #[instrument(level = "debug", name = "ApiKeyRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<ApiKey>, ApiKeyRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at, revoked_at, created_at
+            FROM api_keys
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+        "#;
+
+        let api_keys = sqlx::query_as::<_, ApiKey>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(api_keys)
+    }
+
+    #[instrument(level = "debug", name = "ApiKeyRepository::revoke", skip_all)]
+    async fn revoke(&self, user_id: &Uuid, id_or_prefix: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError> {
+        let query = r#"
+            UPDATE api_keys
+            SET revoked_at = NOW()
+            WHERE user_id = $1
+              AND (id::text = $2 OR prefix = $2)
+              AND revoked_at IS NULL
+            RETURNING id, user_id, name, prefix, key_hash, scopes, expires_at, last_used_at, revoked_at, created_at
+        "#;
+
+        let api_key = sqlx::query_as::<_, ApiKey>(query)
+            .bind(user_id)
+            .bind(id_or_prefix.trim())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(api_key)
+    }
+}
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::commit_link::CommitLink;
+
+#[derive(Error, Debug)]
+pub enum CommitLinkRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Commit link repository trait for tasks completed by commit messages
+#[async_trait]
+pub trait CommitLinkRepository: Send + Sync {
+    /// The user's links to any of `hashes`
+    async fn find_by_commits(&self, user_id: &Uuid, hashes: &[String]) -> Result<Vec<CommitLink>, CommitLinkRepositoryError>;
+    /// The commits that completed a task, oldest first
+    async fn find_by_task(&self, task_id: &Uuid) -> Result<Vec<CommitLink>, CommitLinkRepositoryError>;
+    async fn create(&self, user_id: &Uuid, task_id: &Uuid, commit_hash: &str) -> Result<CommitLink, CommitLinkRepositoryError>;
+}
+
+/// PostgreSQL implementation of CommitLinkRepository
+pub struct PostgresCommitLinkRepository {
+    pool: PgPool,
+}
+
+impl PostgresCommitLinkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CommitLinkRepository for PostgresCommitLinkRepository {
+    #[instrument(level = "debug", name = "CommitLinkRepository::find_by_commits", skip_all)]
+    async fn find_by_commits(&self, user_id: &Uuid, hashes: &[String]) -> Result<Vec<CommitLink>, CommitLinkRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, commit_hash, created_at
+            FROM task_commits
+            WHERE user_id = $1 AND commit_hash = ANY($2)
+        "#;
+
+        let links = sqlx::query_as::<_, CommitLink>(query)
+            .bind(user_id)
+            .bind(hashes)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(links)
+    }
+
+    #[instrument(level = "debug", name = "CommitLinkRepository::find_by_task", skip_all)]
+    async fn find_by_task(&self, task_id: &Uuid) -> Result<Vec<CommitLink>, CommitLinkRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, commit_hash, created_at
+            FROM task_commits
+            WHERE task_id = $1
+            ORDER BY created_at, commit_hash
+        "#;
+
+        let links = sqlx::query_as::<_, CommitLink>(query)
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(links)
+    }
+
+    #[instrument(level = "debug", name = "CommitLinkRepository::create", skip_all)]
+    async fn create(&self, user_id: &Uuid, task_id: &Uuid, commit_hash: &str) -> Result<CommitLink, CommitLinkRepositoryError> {
+        let query = r#"
+            INSERT INTO task_commits (task_id, user_id, commit_hash)
+            VALUES ($1, $2, $3)
+            RETURNING task_id, user_id, commit_hash, created_at
+        "#;
+
+        let link = sqlx::query_as::<_, CommitLink>(query)
+            .bind(task_id)
+            .bind(user_id)
+            .bind(commit_hash)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(link)
+    }
+}
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::digest::DigestSubscription;
+
+#[derive(Error, Debug)]
+pub enum DigestSubscriptionRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Digest subscription repository trait for the daily digest email
+#[async_trait]
+pub trait DigestSubscriptionRepository: Send + Sync {
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Option<DigestSubscription>, DigestSubscriptionRepositoryError>;
+    /// Subscribe the user, or move their digest to `send_at`
+    async fn upsert(&self, user_id: &Uuid, send_at: NaiveTime) -> Result<DigestSubscription, DigestSubscriptionRepositoryError>;
+    async fn delete(&self, user_id: &Uuid) -> Result<bool, DigestSubscriptionRepositoryError>;
+    /// Subscriptions whose time of day has come on `date` and whose digest
+    /// for that day has not been sent
+    async fn find_due(&self, date: NaiveDate, time: NaiveTime) -> Result<Vec<DigestSubscription>, DigestSubscriptionRepositoryError>;
+    /// Record the digest of `date` as sent, unless another run already did;
+    /// returns whether this call claimed it
+    async fn claim(&self, user_id: &Uuid, date: NaiveDate) -> Result<bool, DigestSubscriptionRepositoryError>;
+    /// Undo a claim whose email could not be sent, so the next run retries
+    async fn release(&self, user_id: &Uuid, last_sent_on: Option<NaiveDate>) -> Result<(), DigestSubscriptionRepositoryError>;
+}
+
+/// PostgreSQL implementation of DigestSubscriptionRepository
+pub struct PostgresDigestSubscriptionRepository {
+    pool: PgPool,
+}
+
+impl PostgresDigestSubscriptionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DigestSubscriptionRepository for PostgresDigestSubscriptionRepository {
+    #[instrument(level = "debug", name = "DigestSubscriptionRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Option<DigestSubscription>, DigestSubscriptionRepositoryError> {
+        let query = r#"
+            SELECT user_id, send_at, last_sent_on, created_at
+            FROM digest_subscriptions
+            WHERE user_id = $1
+        "#;
+
+        let subscription = sqlx::query_as::<_, DigestSubscription>(query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(subscription)
+    }
+
+    #[instrument(level = "debug", name = "DigestSubscriptionRepository::upsert", skip_all)]
+    async fn upsert(&self, user_id: &Uuid, send_at: NaiveTime) -> Result<DigestSubscription, DigestSubscriptionRepositoryError> {
+        let query = r#"
+            INSERT INTO digest_subscriptions (user_id, send_at)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET send_at = EXCLUDED.send_at
+            RETURNING user_id, send_at, last_sent_on, created_at
+        "#;
+
+        let subscription = sqlx::query_as::<_, DigestSubscription>(query)
+            .bind(user_id)
+            .bind(send_at)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(subscription)
+    }
+
+    #[instrument(level = "debug", name = "DigestSubscriptionRepository::delete", skip_all)]
+    async fn delete(&self, user_id: &Uuid) -> Result<bool, DigestSubscriptionRepositoryError> {
+        let result = sqlx::query("DELETE FROM digest_subscriptions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "DigestSubscriptionRepository::find_due", skip_all)]
+    async fn find_due(&self, date: NaiveDate, time: NaiveTime) -> Result<Vec<DigestSubscription>, DigestSubscriptionRepositoryError> {
+        let query = r#"
+            SELECT user_id, send_at, last_sent_on, created_at
+            FROM digest_subscriptions
+            WHERE send_at <= $2 AND (last_sent_on IS NULL OR last_sent_on < $1)
+            ORDER BY send_at, user_id
+        "#;
+
+        let subscriptions = sqlx::query_as::<_, DigestSubscription>(query)
+            .bind(date)
+            .bind(time)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(subscriptions)
+    }
+
+    #[instrument(level = "debug", name = "DigestSubscriptionRepository::claim", skip_all)]
+    async fn claim(&self, user_id: &Uuid, date: NaiveDate) -> Result<bool, DigestSubscriptionRepositoryError> {
+        let query = r#"
+            UPDATE digest_subscriptions
+            SET last_sent_on = $2
+            WHERE user_id = $1 AND (last_sent_on IS NULL OR last_sent_on < $2)
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(user_id)
+            .bind(date)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "DigestSubscriptionRepository::release", skip_all)]
+    async fn release(&self, user_id: &Uuid, last_sent_on: Option<NaiveDate>) -> Result<(), DigestSubscriptionRepositoryError> {
+        sqlx::query("UPDATE digest_subscriptions SET last_sent_on = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(last_sent_on)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::email_verification::EmailVerificationToken;
+
+#[derive(Error, Debug)]
+pub enum EmailVerificationRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Email verification repository trait for single-use verification tokens
+#[async_trait]
+pub trait EmailVerificationRepository: Send + Sync {
+    /// Store a new token for `user_id`, discarding the user's earlier unused tokens
+    async fn store(
+        &self,
+        user_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<EmailVerificationToken, EmailVerificationRepositoryError>;
+    /// Mark an unused, unexpired token as used and return its user. Returns
+    /// `None` for unknown, expired or already used tokens, so each token can
+    /// be redeemed at most once even by concurrent callers.
+    async fn consume(&self, token_hash: &str) -> Result<Option<Uuid>, EmailVerificationRepositoryError>;
+}
+
+/// PostgreSQL implementation of EmailVerificationRepository
+pub struct PostgresEmailVerificationRepository {
+    pool: PgPool,
+}
+
+impl PostgresEmailVerificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EmailVerificationRepository for PostgresEmailVerificationRepository {
+    #[instrument(level = "debug", name = "EmailVerificationRepository::store", skip_all)]
+    async fn store(
+        &self,
+        user_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<EmailVerificationToken, EmailVerificationRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let query = r#"
+            INSERT INTO email_verification_tokens (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, token_hash, expires_at, used_at, created_at
+        "#;
+
+        let token = sqlx::query_as::<_, EmailVerificationToken>(query)
+            .bind(user_id)
+            .bind(token_hash)
+            .bind(expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    #[instrument(level = "debug", name = "EmailVerificationRepository::consume", skip_all)]
+    async fn consume(&self, token_hash: &str) -> Result<Option<Uuid>, EmailVerificationRepositoryError> {
+        let query = r#"
+            UPDATE email_verification_tokens
+            SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+        "#;
+
+        let user_id = sqlx::query_scalar::<_, Uuid>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user_id)
+    }
+}
@@ -0,0 +1,204 @@
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+
+use crate::api::timezone::TIMEZONE_HEADER;
+use crate::error::{Diagnose, Diagnostic, ErrorCode};
+use crate::models::calendar;
+use crate::models::user::UserResponse;
+use crate::services::LoginResponse;
+
+/// A request the server does not answer in time counts as failed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum ApiClientError {
+    #[error("Could not reach the API: {0}")]
+    Unreachable(#[from] reqwest::Error),
+
+    /// The error the server answered with, as it diagnosed it
+    #[error("{}", diagnostic.message)]
+    Response { status: u16, diagnostic: Diagnostic },
+
+    #[error("{0} is not available through the REST API")]
+    Unsupported(&'static str),
+}
+
+impl ApiClientError {
+    /// The server's error code, if it answered with one
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            ApiClientError::Response { diagnostic, .. } => Some(diagnostic.code),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnose for ApiClientError {
+    fn diagnose(&self) -> Diagnostic {
+        match self {
+            ApiClientError::Response { diagnostic, .. } => diagnostic.clone(),
+            ApiClientError::Unreachable(_) => Diagnostic::new(ErrorCode::Internal, self.to_string())
+                .with_help("Check that `todo-cli serve` is running at `api.url` (`todo-cli config get api.url`)"),
+            ApiClientError::Unsupported(_) => Diagnostic::new(ErrorCode::Validation, self.to_string())
+                .with_help("Run it against the database instead: `todo-cli config unset api.url`"),
+        }
+    }
+}
+
+/// `error` object of an error response, see `api::error::error_json`
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: ErrorObject,
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: String,
+    message: String,
+    #[serde(default)]
+    labels: serde_json::Map<String, serde_json::Value>,
+    help: Option<String>,
+}
+
+/// Client of the REST API `todo-cli serve` offers under `/api/v1`, for the
+/// remote mode (`config set api.url`). Requests carry the token of the
+/// caller, which `AuthService` sets once it knows it, and the timezone the
+/// command runs in, so the server cuts days at the caller's midnight.
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: Url,
+    token: RwLock<Option<String>>,
+}
+
+impl ApiClient {
+    /// Client of the server at `url`, e.g. `http://localhost:8080`
+    pub fn new(url: &str) -> Result<Self, anyhow::Error> {
+        let mut base_url = Url::parse(url).map_err(|e| anyhow::anyhow!("Invalid API URL '{}': {}", url, e))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            anyhow::bail!("API URL must be an http:// or https:// URL, got '{}'", url);
+        }
+        let path = format!("{}/api/v1/", base_url.path().trim_end_matches('/'));
+        base_url.set_path(&path);
+
+        let http = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+        Ok(Self { http, base_url, token: RwLock::new(None) })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        // `path` is relative, so joining keeps the /api/v1/ prefix
+        let url = self.base_url.join(path).expect("API paths are valid relative URLs");
+        let mut request = self.http.request(method, url);
+        if let Some(timezone) = calendar::timezone().iana_name() {
+            request = request.header(TIMEZONE_HEADER, timezone);
+        }
+        match self.token.read().expect("token lock poisoned").as_deref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Send `token` with every later request
+    pub fn set_token(&self, token: &str) {
+        *self.token.write().expect("token lock poisoned") = Some(token.to_string());
+    }
+
+    /// Send the request and decode the JSON answer, or the server's error
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ApiClientError> {
+        let response = Self::check(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, ApiClientError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let diagnostic = match response.json::<ErrorBody>().await {
+            Ok(ErrorBody { error }) => {
+                let code = ErrorCode::from_code(&error.code).unwrap_or(ErrorCode::Internal);
+                let mut diagnostic = Diagnostic::new(code, error.message);
+                for (name, value) in error.labels {
+                    let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                    diagnostic = diagnostic.with_label(name, value);
+                }
+                match error.help {
+                    Some(help) => diagnostic.with_help(help),
+                    None => diagnostic,
+                }
+            }
+            Err(_) => Diagnostic::new(ErrorCode::Internal, format!("The API answered {}", status)),
+        };
+        Err(ApiClientError::Response { status: status.as_u16(), diagnostic })
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiClientError> {
+        self.send(self.request(Method::GET, path)).await
+    }
+
+    pub(crate) async fn get_query<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, ApiClientError> {
+        self.send(self.request(Method::GET, path).query(query)).await
+    }
+
+    /// `None` when the server answers 404
+    pub(crate) async fn get_optional<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, ApiClientError> {
+        match self.get(path).await {
+            Err(ApiClientError::Response { status, .. }) if status == StatusCode::NOT_FOUND.as_u16() => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
+    pub(crate) async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T, ApiClientError> {
+        self.send(self.request(Method::POST, path).json(body)).await
+    }
+
+    pub(crate) async fn patch<B: Serialize + ?Sized, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T, ApiClientError> {
+        self.send(self.request(Method::PATCH, path).json(body)).await
+    }
+
+    /// Whether there was something to delete; a 404 means there was not
+    pub(crate) async fn delete(&self, path: &str) -> Result<bool, ApiClientError> {
+        let response = self.request(Method::DELETE, path).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        Self::check(response).await?;
+        Ok(true)
+    }
+
+    /// The account the token belongs to
+    pub async fn me(&self) -> Result<UserResponse, ApiClientError> {
+        self.get("me").await
+    }
+
+    /// Log in on the server; the answer carries a token for later requests
+    pub async fn login(&self, identifier: &str, password: &str) -> Result<LoginResponse, ApiClientError> {
+        let body = serde_json::json!({ "identifier": identifier, "password": password });
+        self.post("auth/login", &body).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_are_under_api_v1() {
+        for url in ["http://localhost:8080", "http://localhost:8080/", "https://todo.example.com/app"] {
+            let client = ApiClient::new(url).unwrap();
+            let prefix = client.base_url.as_str().to_string();
+            assert!(prefix.ends_with("/api/v1/"), "{}", prefix);
+            assert_eq!(client.base_url.join("tasks/1").unwrap().as_str(), format!("{}tasks/1", prefix));
+        }
+        assert!(ApiClient::new("ftp://localhost").is_err());
+        assert!(ApiClient::new("localhost:8080").is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Repositories backed by the REST API of a running `todo-cli serve`,
+//! selected by `config set api.url`. The CLI then works on the server's data
+//! without a database connection of its own.
+
+pub mod client;
+pub mod task_repository;
+pub mod user_repository;
+
+pub use client::*;
+pub use task_repository::*;
+pub use user_repository::*;
@@ -0,0 +1,471 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio_stream::wrappers::ReceiverStream;
+use uuid::Uuid;
+
+use crate::database::repositories::{ApiClient, ApiClientError, TaskRepository, TaskRepositoryError, TaskStream, TaskTransaction};
+use crate::error::{Diagnostic, ErrorCode};
+use crate::models::task::{
+    normalize_context, ContextCount, Page, PageRequest, StatisticsFilter, StoreTaskRequest, Task, TaskGroup, TaskGrouping,
+    TaskStatistics, TaskStatus, UpdateTaskRequest,
+};
+use crate::models::calendar::{self, UserTimezone};
+use crate::models::report::{DayCount, TaskTimestamp, Throughput};
+use crate::models::workspace::{MemberTaskCounts, OverdueBreakdown};
+
+/// Most tasks the API takes in one bulk request
+const MAX_BULK_ITEMS: usize = 100;
+
+/// `TaskRepositoryError` for an API error, keeping the kinds callers tell apart
+fn task_error(error: ApiClientError) -> TaskRepositoryError {
+    match error.code() {
+        Some(ErrorCode::TaskNotFound) => TaskRepositoryError::NotFound,
+        Some(ErrorCode::TaskChanged) => TaskRepositoryError::Conflict,
+        _ => TaskRepositoryError::ApiError(error),
+    }
+}
+
+fn unsupported(feature: &'static str) -> TaskRepositoryError {
+    TaskRepositoryError::ApiError(ApiClientError::Unsupported(feature))
+}
+
+/// One item of the answer to `POST /tasks/bulk`
+#[derive(Deserialize)]
+struct BulkItem {
+    status: u16,
+    task: Option<Task>,
+    error: Option<BulkItemError>,
+}
+
+#[derive(Deserialize)]
+struct BulkItemError {
+    code: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct BulkAnswer {
+    results: Vec<BulkItem>,
+}
+
+/// Task repository of the remote mode, backed by the REST API of a
+/// `todo-cli serve` instance. The API only shows the tasks of the account
+/// the token belongs to, so `user_id` is expected to be that account.
+/// Queries the API has no endpoint for filter the full task list here, the
+/// way the in-memory repository does.
+pub struct HttpTaskRepository {
+    client: Arc<ApiClient>,
+}
+
+impl HttpTaskRepository {
+    pub fn new(client: Arc<ApiClient>) -> Self {
+        Self { client }
+    }
+
+    async fn list(&self, query: &[(&str, &str)]) -> Result<Vec<Task>, TaskRepositoryError> {
+        self.client.get_query("tasks", query).await.map_err(task_error)
+    }
+}
+
+#[async_trait]
+impl TaskRepository for HttpTaskRepository {
+    async fn store(&self, task: StoreTaskRequest, _user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        self.client.post("tasks", &task).await.map_err(task_error)
+    }
+
+    async fn store_many(&self, tasks: Vec<StoreTaskRequest>, _user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        // The server stores the valid items of a bulk request; checking them
+        // first keeps this all or nothing
+        for task in &tasks {
+            validator::Validate::validate(task).map_err(|e| TaskRepositoryError::ValidationError(e.into()))?;
+        }
+
+        let mut stored = Vec::with_capacity(tasks.len());
+        for chunk in tasks.chunks(MAX_BULK_ITEMS) {
+            let answer: BulkAnswer = self.client.post("tasks/bulk", chunk).await.map_err(task_error)?;
+            for item in answer.results {
+                match (item.task, item.error) {
+                    (Some(task), _) => stored.push(task),
+                    (None, error) => {
+                        let (code, message) = error
+                            .map(|error| (ErrorCode::from_code(&error.code), error.message))
+                            .unwrap_or((None, format!("The API answered {}", item.status)));
+                        let diagnostic = Diagnostic::new(code.unwrap_or(ErrorCode::Internal), message);
+                        return Err(task_error(ApiClientError::Response { status: item.status, diagnostic }));
+                    }
+                }
+            }
+        }
+        Ok(stored)
+    }
+
+    async fn put_copy(&self, _task: &Task) -> Result<Task, TaskRepositoryError> {
+        Err(unsupported("Copying tasks between databases"))
+    }
+
+    async fn begin(&self) -> Result<Box<dyn TaskTransaction + '_>, TaskRepositoryError> {
+        Err(unsupported("Replacing all tasks at once"))
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>, TaskRepositoryError> {
+        self.client.get_optional(&format!("tasks/{}", id)).await.map_err(task_error)
+    }
+
+    async fn find_by_user_id(&self, _user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        self.list(&[]).await
+    }
+
+    /// The API answers with the whole list at once; it is streamed from
+    /// memory once it arrived
+    fn stream_by_user_id(&self, _user_id: &Uuid) -> TaskStream<'_> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        let client = self.client.clone();
+        // The spawned request is sent in the caller's timezone too
+        let timezone = calendar::timezone();
+        tokio::spawn(calendar::with_timezone(timezone, async move {
+            match client.get_query::<Vec<Task>>("tasks", &[]).await {
+                Ok(tasks) => {
+                    for task in tasks {
+                        if sender.send(Ok(task)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(task_error(e))).await;
+                }
+            }
+        }));
+        Box::pin(ReceiverStream::new(receiver))
+    }
+
+    async fn find_by_user_id_paginated(&self, user_id: &Uuid, page: PageRequest) -> Result<Page<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.sort_by(|a, b| page.sort.compare(a, b));
+
+        let after = match page.after {
+            Some(cursor) => tasks.iter().position(|task| task.id == cursor).ok_or(TaskRepositoryError::NotFound)? + 1,
+            None => 0,
+        };
+        let total = tasks.len() as i64;
+        let skipped = after + page.offset;
+        let items = tasks.into_iter().skip(skipped).take(page.limit.unwrap_or(usize::MAX)).collect();
+        Ok(Page::new(items, skipped, total))
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(task) = self.find_by_id(id).await? {
+                tasks.push(task);
+            }
+        }
+        Ok(tasks)
+    }
+
+    async fn find_by_number(&self, user_id: &Uuid, number: i32) -> Result<Option<Task>, TaskRepositoryError> {
+        let tasks = self.find_by_user_id(user_id).await?;
+        Ok(tasks.into_iter().find(|task| task.number == number))
+    }
+
+    async fn find_by_workspace(&self, _workspace_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        Err(unsupported("Workspaces"))
+    }
+
+    async fn find_by_id_prefix(&self, user_id: &Uuid, prefix: &str, limit: i64) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.id.to_string().starts_with(prefix));
+        tasks.truncate(limit as usize);
+        Ok(tasks)
+    }
+
+    async fn find_overdue_by_user(&self, user_id: &Uuid, due_before: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.due_date.is_some_and(|due| due < due_before) && !task.is_completed());
+        tasks.sort_by_key(|task| (!task.pinned, task.due_date));
+        Ok(tasks)
+    }
+
+    async fn find_due_between(&self, user_id: &Uuid, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.due_date.is_some_and(|due| due >= from && due < until));
+        Ok(tasks)
+    }
+
+    async fn find_by_status(&self, user_id: &Uuid, status: TaskStatus) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.status == status);
+        Ok(tasks)
+    }
+
+    async fn find_by_context(&self, _user_id: &Uuid, context: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        self.list(&[("context", &normalize_context(context))]).await
+    }
+
+    async fn count_open_by_context(&self, user_id: &Uuid) -> Result<Vec<ContextCount>, TaskRepositoryError> {
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        for task in self.find_by_user_id(user_id).await? {
+            if let (false, Some(context)) = (task.is_completed(), task.context) {
+                *counts.entry(context).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<ContextCount> = counts
+            .into_iter()
+            .map(|(context, open_tasks)| ContextCount { context, open_tasks })
+            .collect();
+        counts.sort_by(|a, b| b.open_tasks.cmp(&a.open_tasks).then_with(|| a.context.cmp(&b.context)));
+        Ok(counts)
+    }
+
+    async fn find_started_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(Task::has_started);
+        Ok(tasks)
+    }
+
+    async fn search_tasks(&self, _user_id: &Uuid, search_term: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        self.list(&[("search", search_term)]).await
+    }
+
+    async fn update(&self, id: &Uuid, _user_id: &Uuid, request: UpdateTaskRequest) -> Result<Task, TaskRepositoryError> {
+        self.client.patch(&format!("tasks/{}", id), &request).await.map_err(task_error)
+    }
+
+    async fn delete(&self, id: &Uuid, _user_id: &Uuid) -> Result<bool, TaskRepositoryError> {
+        self.client.delete(&format!("tasks/{}", id)).await.map_err(task_error)
+    }
+
+    async fn delete_bulk(&self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        let mut deleted = 0;
+        for id in ids {
+            if self.delete(id, user_id).await? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn set_sort_order(&self, _id: &Uuid, _user_id: &Uuid, _sort_order: f64) -> Result<Task, TaskRepositoryError> {
+        Err(unsupported("Reordering tasks"))
+    }
+
+    async fn set_pinned(&self, _id: &Uuid, _user_id: &Uuid, _pinned: bool) -> Result<Task, TaskRepositoryError> {
+        Err(unsupported("Pinning tasks"))
+    }
+
+    async fn update_status_if(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        expected: TaskStatus,
+        status: TaskStatus,
+    ) -> Result<Option<Task>, TaskRepositoryError> {
+        let Some(task) = self.find_by_id(id).await?.filter(|task| task.status == expected) else {
+            return Ok(None);
+        };
+        // The version makes the server refuse if the task changed since it was read
+        let request = UpdateTaskRequest { status: Some(status), version: Some(task.version), ..Default::default() };
+        match self.update(id, user_id, request).await {
+            Ok(task) => Ok(Some(task)),
+            Err(TaskRepositoryError::NotFound | TaskRepositoryError::Conflict) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn update_status_many(
+        &self,
+        user_id: &Uuid,
+        expected: &[(Uuid, TaskStatus)],
+        status: TaskStatus,
+    ) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut updated = Vec::new();
+        for (id, expected) in expected {
+            if let Some(task) = self.update_status_if(id, user_id, *expected, status).await? {
+                updated.push(task);
+            }
+        }
+        Ok(updated)
+    }
+
+    async fn renumber(&self, _user_id: &Uuid, _ordered_ids: &[Uuid]) -> Result<(), TaskRepositoryError> {
+        Err(unsupported("Reordering tasks"))
+    }
+
+    async fn count_by_user(&self, user_id: &Uuid) -> Result<i64, TaskRepositoryError> {
+        Ok(self.find_by_user_id(user_id).await?.len() as i64)
+    }
+
+    async fn get_user_statistics(
+        &self,
+        user_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        filter: &StatisticsFilter,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| filter.matches(task));
+        Ok(TaskStatistics::from_tasks(&tasks, due_before, stale_before))
+    }
+
+    async fn count_grouped(
+        &self,
+        user_id: &Uuid,
+        group_by: TaskGrouping,
+        filter: &StatisticsFilter,
+    ) -> Result<HashMap<TaskGroup, i64>, TaskRepositoryError> {
+        let mut counts = HashMap::new();
+        for task in self.find_by_user_id(user_id).await? {
+            if filter.matches(&task) {
+                *counts.entry(TaskGroup::of(&task, group_by)).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn get_throughput(&self, user_id: &Uuid, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Throughput, TaskRepositoryError> {
+        let tasks = self.find_by_user_id(user_id).await?;
+        Ok(Throughput::from_tasks(&tasks, since, until))
+    }
+
+    async fn count_by_day(
+        &self,
+        user_id: &Uuid,
+        timestamp: TaskTimestamp,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Result<Vec<DayCount>, TaskRepositoryError> {
+        let tasks = self.find_by_user_id(user_id).await?;
+        Ok(DayCount::from_tasks(&tasks, timestamp, since, until, timezone))
+    }
+
+    async fn get_workspace_statistics(
+        &self,
+        _workspace_id: &Uuid,
+        _due_before: DateTime<Utc>,
+        _stale_before: DateTime<Utc>,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
+        Err(unsupported("Workspaces"))
+    }
+
+    async fn get_workspace_member_counts(
+        &self,
+        _workspace_id: &Uuid,
+        _due_before: DateTime<Utc>,
+        _completed_since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<MemberTaskCounts>, TaskRepositoryError> {
+        Err(unsupported("Workspaces"))
+    }
+
+    async fn get_workspace_overdue_breakdown(
+        &self,
+        _workspace_id: &Uuid,
+        _due_before: DateTime<Utc>,
+    ) -> Result<OverdueBreakdown, TaskRepositoryError> {
+        Err(unsupported("Workspaces"))
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    use crate::api::{self, AppState};
+    use crate::database::repositories::{HttpUserRepository, InMemoryTaskRepository, InMemoryUserRepository, UserRepository};
+    use crate::database::{Database, PoolConfig};
+    use crate::models::user::StoreUserRequest;
+    use crate::services::{AuthService, EventBus, TaskService, UserService};
+
+    /// `todo-cli serve` on in-memory repositories, listening on a free port
+    async fn serve(session_dir: PathBuf) -> String {
+        let user_service = Arc::new(UserService::new(Arc::new(InMemoryUserRepository::new())));
+        let state = AppState {
+            auth_service: Arc::new(AuthService::new(user_service.clone(), "test-secret", Some(session_dir)).unwrap()),
+            user_service,
+            task_service: Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new()))),
+            event_bus: EventBus::default(),
+            database: Database::from_url_with("memory://", &PoolConfig::default()).await.unwrap(),
+            metrics: Default::default(),
+            migrations_dir: None,
+            timezone: Default::default(),
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api::router(state)).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_tasks_round_trip_through_the_api() {
+        let dir = TempDir::new().unwrap();
+        let client = Arc::new(ApiClient::new(&serve(dir.path().to_path_buf()).await).unwrap());
+
+        let users = HttpUserRepository::new(client.clone());
+        let request = StoreUserRequest::new(
+            "remote_user".to_string(),
+            "remote@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+        users.store(request).await.unwrap();
+        let login = client.login("remote_user", "kestrel-canyon-42").await.unwrap();
+        client.set_token(&login.token);
+        let me = users.find_by_username("remote_user").await.unwrap().unwrap();
+        assert!(users.find_by_username("someone_else").await.unwrap().is_none());
+
+        let tasks = HttpTaskRepository::new(client);
+        let request = StoreTaskRequest { title: "Through the API".to_string(), ..Default::default() };
+        let task = tasks.store(request, &me.id).await.unwrap();
+        assert_eq!(tasks.find_by_id(&task.id).await.unwrap().unwrap().title, "Through the API");
+
+        let completed = UpdateTaskRequest { status: Some(TaskStatus::Completed), ..Default::default() };
+        assert_eq!(tasks.update(&task.id, &me.id, completed).await.unwrap().status, TaskStatus::Completed);
+        assert_eq!(tasks.find_by_user_id(&me.id).await.unwrap().len(), 1);
+
+        assert!(tasks.delete(&task.id, &me.id).await.unwrap());
+        assert!(tasks.find_by_id(&task.id).await.unwrap().is_none());
+        let renamed = UpdateTaskRequest { title: Some("Gone".to_string()), ..Default::default() };
+        assert!(matches!(tasks.update(&task.id, &me.id, renamed).await, Err(TaskRepositoryError::NotFound)));
+        assert!(matches!(
+            tasks.set_pinned(&task.id, &me.id, true).await,
+            Err(TaskRepositoryError::ApiError(ApiClientError::Unsupported(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_export_is_requested_in_the_callers_timezone() {
+        use axum::{http::HeaderMap, routing::get, Json, Router};
+        use std::sync::Mutex;
+        use tokio_stream::StreamExt;
+
+        // A server answering the task list with no tasks, noting the timezone asked in
+        let requested = Arc::new(Mutex::new(None));
+        let noted = requested.clone();
+        let app = Router::new().route(
+            "/api/v1/tasks",
+            get(move |headers: HeaderMap| async move {
+                let timezone = headers.get(api::timezone::TIMEZONE_HEADER).map(|value| value.to_str().unwrap().to_string());
+                *noted.lock().unwrap() = timezone;
+                Json(Vec::<Task>::new())
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let tasks = HttpTaskRepository::new(Arc::new(ApiClient::new(&url).unwrap()));
+        let auckland: UserTimezone = "Pacific/Auckland".parse().unwrap();
+        let streamed = calendar::with_timezone(auckland, async {
+            tasks.stream_by_user_id(&Uuid::new_v4()).collect::<Vec<_>>().await
+        })
+        .await;
+
+        assert!(streamed.is_empty());
+        assert_eq!(requested.lock().unwrap().as_deref(), Some("Pacific/Auckland"));
+    }
+}
@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::database::repositories::{ApiClient, ApiClientError, UserRepository, UserRepositoryError};
+use crate::error::{Diagnostic, ErrorCode};
+use crate::models::user::{StoreUserRequest, UpdateUserRequest, User, UserResponse, UsernameChange};
+
+/// `UserRepositoryError` for an API error, keeping the kinds callers tell apart
+fn user_error(error: ApiClientError) -> UserRepositoryError {
+    match &error {
+        ApiClientError::Response { diagnostic, .. } => match diagnostic.code {
+            ErrorCode::UserNotFound => UserRepositoryError::NotFound,
+            ErrorCode::UsernameTaken => UserRepositoryError::UsernameExists { username: label(diagnostic, "username") },
+            ErrorCode::EmailTaken => UserRepositoryError::EmailExists { email: label(diagnostic, "email") },
+            _ => UserRepositoryError::ApiError(error),
+        },
+        _ => UserRepositoryError::ApiError(error),
+    }
+}
+
+fn label(diagnostic: &Diagnostic, name: &str) -> String {
+    diagnostic
+        .labels
+        .iter()
+        .find(|(label, _)| label == name)
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
+}
+
+fn unsupported(feature: &'static str) -> UserRepositoryError {
+    UserRepositoryError::ApiError(ApiClientError::Unsupported(feature))
+}
+
+/// The account as the API shows it. Password hashes never leave the server,
+/// which is also where logins are checked.
+fn from_response(user: UserResponse) -> User {
+    User {
+        id: user.id,
+        username: user.username,
+        email: user.email,
+        password_hash: String::new(),
+        email_verified_at: None,
+        is_active: user.is_active,
+        created_at: user.created_at,
+        updated_at: user.updated_at,
+    }
+}
+
+/// User repository of the remote mode. The API only shows the account the
+/// token belongs to (`/me`), so lookups of any other account find nothing,
+/// and account management stays on the server.
+pub struct HttpUserRepository {
+    client: Arc<ApiClient>,
+}
+
+impl HttpUserRepository {
+    pub fn new(client: Arc<ApiClient>) -> Self {
+        Self { client }
+    }
+
+    async fn me_if(&self, matches: impl Fn(&UserResponse) -> bool) -> Result<Option<User>, UserRepositoryError> {
+        let me = self.client.me().await.map_err(user_error)?;
+        Ok(matches(&me).then(|| from_response(me)))
+    }
+}
+
+#[async_trait]
+impl UserRepository for HttpUserRepository {
+    async fn store(&self, user: StoreUserRequest) -> Result<User, UserRepositoryError> {
+        let body = serde_json::json!({ "username": user.username, "email": user.email, "password": user.password });
+        let user: UserResponse = self.client.post("auth/register", &body).await.map_err(user_error)?;
+        Ok(from_response(user))
+    }
+
+    async fn put_copy(&self, _user: &User) -> Result<User, UserRepositoryError> {
+        Err(unsupported("Copying accounts between databases"))
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError> {
+        self.me_if(|me| me.id == *id).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, UserRepositoryError> {
+        self.me_if(|me| me.username == username).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError> {
+        self.me_if(|me| me.email.eq_ignore_ascii_case(email)).await
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>, UserRepositoryError> {
+        Ok(self.me_if(|_| true).await?.into_iter().collect())
+    }
+
+    async fn update(&self, _id: &Uuid, _updates: UpdateUserRequest) -> Result<User, UserRepositoryError> {
+        Err(unsupported("Changing account details"))
+    }
+
+    async fn find_username_changes(&self, _id: &Uuid) -> Result<Vec<UsernameChange>, UserRepositoryError> {
+        Err(unsupported("Username history"))
+    }
+
+    async fn delete(&self, _id: &Uuid) -> Result<bool, UserRepositoryError> {
+        Err(unsupported("Deleting accounts"))
+    }
+
+    async fn mark_email_verified(&self, _id: &Uuid) -> Result<User, UserRepositoryError> {
+        Err(unsupported("Email verification"))
+    }
+
+    async fn set_active(&self, _id: &Uuid, _active: bool) -> Result<User, UserRepositoryError> {
+        Err(unsupported("Suspending accounts"))
+    }
+
+    /// The server checks names when registering; only the own one is known here
+    async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError> {
+        match self.client.me().await {
+            Ok(me) => Ok(me.username == username),
+            Err(_) => Ok(false),
+        }
+    }
+
+    async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
+        match self.client.me().await {
+            Ok(me) => Ok(me.email.eq_ignore_ascii_case(email)),
+            Err(_) => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{invitation::Invitation, workspace::MEMBER_ROLE};
+
+#[derive(Error, Debug)]
+pub enum InvitationRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Data needed to store a new invitation
+#[derive(Debug, Clone)]
+pub struct StoreInvitationRequest {
+    pub workspace_id: Uuid,
+    pub created_by: Uuid,
+    pub prefix: String,
+    pub code_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub max_uses: Option<i32>,
+}
+
+/// Invitation repository trait for workspace invite codes
+#[async_trait]
+pub trait InvitationRepository: Send + Sync {
+    async fn store(&self, request: StoreInvitationRequest) -> Result<Invitation, InvitationRepositoryError>;
+    async fn find_by_code_hash(&self, code_hash: &str) -> Result<Option<Invitation>, InvitationRepositoryError>;
+    /// The workspace's invitations, including revoked and expired ones, newest first
+    async fn find_by_workspace(&self, workspace_id: &Uuid) -> Result<Vec<Invitation>, InvitationRepositoryError>;
+    /// Use the active invitation with this hash to make the user a member;
+    /// `None` if it is revoked, expired or used up
+    async fn redeem(&self, code_hash: &str, user_id: &Uuid) -> Result<Option<Invitation>, InvitationRepositoryError>;
+    /// Revoke the workspace's invitation with this ID or prefix; `None` if there is no such active invitation
+    async fn revoke(&self, workspace_id: &Uuid, id_or_prefix: &str) -> Result<Option<Invitation>, InvitationRepositoryError>;
+}
+
+/// PostgreSQL implementation of InvitationRepository
+pub struct PostgresInvitationRepository {
+    pool: PgPool,
+}
+
+impl PostgresInvitationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl InvitationRepository for PostgresInvitationRepository {
+    #[instrument(level = "debug", name = "InvitationRepository::store", skip_all)]
+    async fn store(&self, request: StoreInvitationRequest) -> Result<Invitation, InvitationRepositoryError> {
+        let query = r#"
+            INSERT INTO invitations (workspace_id, created_by, prefix, code_hash, expires_at, max_uses)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, workspace_id, created_by, prefix, code_hash, expires_at, max_uses, use_count, revoked_at, created_at
+        "#;
+
+        let invitation = sqlx::query_as::<_, Invitation>(query)
+            .bind(request.workspace_id)
+            .bind(request.created_by)
+            .bind(&request.prefix)
+            .bind(&request.code_hash)
+            .bind(request.expires_at)
+            .bind(request.max_uses)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(invitation)
+    }
+
+    #[instrument(level = "debug", name = "InvitationRepository::find_by_code_hash", skip_all)]
+    async fn find_by_code_hash(&self, code_hash: &str) -> Result<Option<Invitation>, InvitationRepositoryError> {
+        let query = r#"
+            SELECT id, workspace_id, created_by, prefix, code_hash, expires_at, max_uses, use_count, revoked_at, created_at
+            FROM invitations
+            WHERE code_hash = $1
+        "#;
+
+        let invitation = sqlx::query_as::<_, Invitation>(query)
+            .bind(code_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(invitation)
+    }
+
+    #[instrument(level = "debug", name = "InvitationRepository::find_by_workspace", skip_all)]
+    async fn find_by_workspace(&self, workspace_id: &Uuid) -> Result<Vec<Invitation>, InvitationRepositoryError> {
+        let query = r#"
+            SELECT id, workspace_id, created_by, prefix, code_hash, expires_at, max_uses, use_count, revoked_at, created_at
+            FROM invitations
+            WHERE workspace_id = $1
+            ORDER BY created_at DESC
+        "#;
+
+        let invitations = sqlx::query_as::<_, Invitation>(query)
+            .bind(workspace_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(invitations)
+    }
+
+    #[instrument(level = "debug", name = "InvitationRepository::redeem", skip_all)]
+    async fn redeem(&self, code_hash: &str, user_id: &Uuid) -> Result<Option<Invitation>, InvitationRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        // The conditional update also serializes concurrent redemptions of the last use
+        let query = r#"
+            UPDATE invitations
+            SET use_count = use_count + 1
+            WHERE code_hash = $1
+              AND revoked_at IS NULL
+              AND expires_at > NOW()
+              AND (max_uses IS NULL OR use_count < max_uses)
+            RETURNING id, workspace_id, created_by, prefix, code_hash, expires_at, max_uses, use_count, revoked_at, created_at
+        "#;
+
+        let Some(invitation) = sqlx::query_as::<_, Invitation>(query)
+            .bind(code_hash)
+            .fetch_optional(&mut *tx)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query("INSERT INTO workspace_members (workspace_id, user_id, role) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+            .bind(invitation.workspace_id)
+            .bind(user_id)
+            .bind(MEMBER_ROLE)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(invitation))
+    }
+
+    #[instrument(level = "debug", name = "InvitationRepository::revoke", skip_all)]
+    async fn revoke(&self, workspace_id: &Uuid, id_or_prefix: &str) -> Result<Option<Invitation>, InvitationRepositoryError> {
+        let query = r#"
+            UPDATE invitations
+            SET revoked_at = NOW()
+            WHERE workspace_id = $1
+              AND (id::text = $2 OR prefix = $2)
+              AND revoked_at IS NULL
+            RETURNING id, workspace_id, created_by, prefix, code_hash, expires_at, max_uses, use_count, revoked_at, created_at
+        "#;
+
+        let invitation = sqlx::query_as::<_, Invitation>(query)
+            .bind(workspace_id)
+            .bind(id_or_prefix.trim())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(invitation)
+    }
+}
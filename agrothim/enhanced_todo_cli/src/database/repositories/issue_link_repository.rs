@@ -0,0 +1,97 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::issue_link::IssueLink;
+
+#[derive(Error, Debug)]
+pub enum IssueLinkRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Issue link repository trait for tasks created from GitHub issues
+#[async_trait]
+pub trait IssueLinkRepository: Send + Sync {
+    /// The user's links to issues of `repository` (`owner/name`)
+    async fn find_by_repository(&self, user_id: &Uuid, repository: &str) -> Result<Vec<IssueLink>, IssueLinkRepositoryError>;
+    async fn create(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        repository: &str,
+        issue_number: i32,
+    ) -> Result<IssueLink, IssueLinkRepositoryError>;
+    async fn mark_closed(&self, task_id: &Uuid, at: DateTime<Utc>) -> Result<(), IssueLinkRepositoryError>;
+}
+
+/// PostgreSQL implementation of IssueLinkRepository
+pub struct PostgresIssueLinkRepository {
+    pool: PgPool,
+}
+
+impl PostgresIssueLinkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IssueLinkRepository for PostgresIssueLinkRepository {
+    #[instrument(level = "debug", name = "IssueLinkRepository::find_by_repository", skip_all)]
+    async fn find_by_repository(&self, user_id: &Uuid, repository: &str) -> Result<Vec<IssueLink>, IssueLinkRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, repository, issue_number, closed_at, created_at
+            FROM task_issue_links
+            WHERE user_id = $1 AND repository = $2
+            ORDER BY issue_number
+        "#;
+
+        let links = sqlx::query_as::<_, IssueLink>(query)
+            .bind(user_id)
+            .bind(repository)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(links)
+    }
+
+    #[instrument(level = "debug", name = "IssueLinkRepository::create", skip_all)]
+    async fn create(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        repository: &str,
+        issue_number: i32,
+    ) -> Result<IssueLink, IssueLinkRepositoryError> {
+        let query = r#"
+            INSERT INTO task_issue_links (task_id, user_id, repository, issue_number)
+            VALUES ($1, $2, $3, $4)
+            RETURNING task_id, user_id, repository, issue_number, closed_at, created_at
+        "#;
+
+        let link = sqlx::query_as::<_, IssueLink>(query)
+            .bind(task_id)
+            .bind(user_id)
+            .bind(repository)
+            .bind(issue_number)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(link)
+    }
+
+    #[instrument(level = "debug", name = "IssueLinkRepository::mark_closed", skip_all)]
+    async fn mark_closed(&self, task_id: &Uuid, at: DateTime<Utc>) -> Result<(), IssueLinkRepositoryError> {
+        sqlx::query("UPDATE task_issue_links SET closed_at = $2 WHERE task_id = $1")
+            .bind(task_id)
+            .bind(at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
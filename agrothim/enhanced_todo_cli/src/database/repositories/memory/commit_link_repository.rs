@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::commit_link_repository::{CommitLinkRepository, CommitLinkRepositoryError};
+use crate::models::commit_link::CommitLink;
+
+/// Commit links kept in memory, for tests of `git scan`
+#[derive(Default)]
+pub struct InMemoryCommitLinkRepository {
+    links: Mutex<Vec<CommitLink>>,
+}
+
+impl InMemoryCommitLinkRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CommitLinkRepository for InMemoryCommitLinkRepository {
+    async fn find_by_commits(&self, user_id: &Uuid, hashes: &[String]) -> Result<Vec<CommitLink>, CommitLinkRepositoryError> {
+        let links = self.links.lock().unwrap();
+        Ok(links
+            .iter()
+            .filter(|link| link.user_id == *user_id && hashes.contains(&link.commit_hash))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_task(&self, task_id: &Uuid) -> Result<Vec<CommitLink>, CommitLinkRepositoryError> {
+        let links = self.links.lock().unwrap();
+        Ok(links.iter().filter(|link| link.task_id == *task_id).cloned().collect())
+    }
+
+    async fn create(&self, user_id: &Uuid, task_id: &Uuid, commit_hash: &str) -> Result<CommitLink, CommitLinkRepositoryError> {
+        let link = CommitLink {
+            task_id: *task_id,
+            user_id: *user_id,
+            commit_hash: commit_hash.to_string(),
+            created_at: Utc::now(),
+        };
+        self.links.lock().unwrap().push(link.clone());
+        Ok(link)
+    }
+}
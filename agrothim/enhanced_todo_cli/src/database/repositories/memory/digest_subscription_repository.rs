@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveTime, Utc};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::digest_subscription_repository::{
+    DigestSubscriptionRepository, DigestSubscriptionRepositoryError,
+};
+use crate::models::digest::DigestSubscription;
+
+/// Digest subscriptions kept in memory, for tests of the digest email
+#[derive(Default)]
+pub struct InMemoryDigestSubscriptionRepository {
+    subscriptions: Mutex<Vec<DigestSubscription>>,
+}
+
+impl InMemoryDigestSubscriptionRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DigestSubscriptionRepository for InMemoryDigestSubscriptionRepository {
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Option<DigestSubscription>, DigestSubscriptionRepositoryError> {
+        let subscriptions = self.subscriptions.lock().unwrap();
+        Ok(subscriptions.iter().find(|subscription| subscription.user_id == *user_id).cloned())
+    }
+
+    async fn upsert(&self, user_id: &Uuid, send_at: NaiveTime) -> Result<DigestSubscription, DigestSubscriptionRepositoryError> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if let Some(subscription) = subscriptions.iter_mut().find(|subscription| subscription.user_id == *user_id) {
+            subscription.send_at = send_at;
+            return Ok(subscription.clone());
+        }
+        let subscription = DigestSubscription { user_id: *user_id, send_at, last_sent_on: None, created_at: Utc::now() };
+        subscriptions.push(subscription.clone());
+        Ok(subscription)
+    }
+
+    async fn delete(&self, user_id: &Uuid) -> Result<bool, DigestSubscriptionRepositoryError> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let before = subscriptions.len();
+        subscriptions.retain(|subscription| subscription.user_id != *user_id);
+        Ok(subscriptions.len() < before)
+    }
+
+    async fn find_due(&self, date: NaiveDate, time: NaiveTime) -> Result<Vec<DigestSubscription>, DigestSubscriptionRepositoryError> {
+        let subscriptions = self.subscriptions.lock().unwrap();
+        Ok(subscriptions
+            .iter()
+            .filter(|subscription| subscription.send_at <= time && subscription.last_sent_on.is_none_or(|sent| sent < date))
+            .cloned()
+            .collect())
+    }
+
+    async fn claim(&self, user_id: &Uuid, date: NaiveDate) -> Result<bool, DigestSubscriptionRepositoryError> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        match subscriptions.iter_mut().find(|subscription| subscription.user_id == *user_id) {
+            Some(subscription) if subscription.last_sent_on.is_none_or(|sent| sent < date) => {
+                subscription.last_sent_on = Some(date);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, user_id: &Uuid, last_sent_on: Option<NaiveDate>) -> Result<(), DigestSubscriptionRepositoryError> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if let Some(subscription) = subscriptions.iter_mut().find(|subscription| subscription.user_id == *user_id) {
+            subscription.last_sent_on = last_sent_on;
+        }
+        Ok(())
+    }
+}
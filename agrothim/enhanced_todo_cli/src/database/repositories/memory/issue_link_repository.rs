@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::issue_link_repository::{IssueLinkRepository, IssueLinkRepositoryError};
+use crate::models::issue_link::IssueLink;
+
+/// Issue links kept in memory, for tests of the GitHub sync
+#[derive(Default)]
+pub struct InMemoryIssueLinkRepository {
+    links: Mutex<Vec<IssueLink>>,
+}
+
+impl InMemoryIssueLinkRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IssueLinkRepository for InMemoryIssueLinkRepository {
+    async fn find_by_repository(&self, user_id: &Uuid, repository: &str) -> Result<Vec<IssueLink>, IssueLinkRepositoryError> {
+        let links = self.links.lock().unwrap();
+        Ok(links
+            .iter()
+            .filter(|link| link.user_id == *user_id && link.repository == repository)
+            .cloned()
+            .collect())
+    }
+
+    async fn create(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        repository: &str,
+        issue_number: i32,
+    ) -> Result<IssueLink, IssueLinkRepositoryError> {
+        let link = IssueLink {
+            task_id: *task_id,
+            user_id: *user_id,
+            repository: repository.to_string(),
+            issue_number,
+            closed_at: None,
+            created_at: Utc::now(),
+        };
+        self.links.lock().unwrap().push(link.clone());
+        Ok(link)
+    }
+
+    async fn mark_closed(&self, task_id: &Uuid, at: DateTime<Utc>) -> Result<(), IssueLinkRepositoryError> {
+        let mut links = self.links.lock().unwrap();
+        if let Some(link) = links.iter_mut().find(|link| link.task_id == *task_id) {
+            link.closed_at = Some(at);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+//! Repositories that keep their data in process memory, selected by a
+//! `memory://` DATABASE_URL. They let the CLI run without any database and
+//! stand in for the database repositories in unit tests.
+
+pub mod task_repository;
+pub mod user_repository;
+#[cfg(test)]
+pub mod sync_state_repository;
+#[cfg(test)]
+pub mod issue_link_repository;
+#[cfg(test)]
+pub mod digest_subscription_repository;
+#[cfg(test)]
+pub mod commit_link_repository;
+#[cfg(test)]
+pub mod schedule_repository;
+
+pub use task_repository::*;
+pub use user_repository::*;
+#[cfg(test)]
+pub use sync_state_repository::*;
+#[cfg(test)]
+pub use issue_link_repository::*;
+#[cfg(test)]
+pub use digest_subscription_repository::*;
+#[cfg(test)]
+pub use commit_link_repository::*;
+#[cfg(test)]
+pub use schedule_repository::*;
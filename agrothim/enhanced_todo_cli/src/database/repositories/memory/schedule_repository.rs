@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::schedule_repository::{ScheduleRepository, ScheduleRepositoryError};
+use crate::models::schedule::{Schedule, StoreScheduleRequest};
+
+/// Schedules kept in memory, for tests of scheduled tasks
+#[derive(Default)]
+pub struct InMemoryScheduleRepository {
+    schedules: Mutex<Vec<Schedule>>,
+}
+
+impl InMemoryScheduleRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ScheduleRepository for InMemoryScheduleRepository {
+    async fn create(&self, user_id: &Uuid, request: &StoreScheduleRequest, next_run_at: DateTime<Utc>) -> Result<Schedule, ScheduleRepositoryError> {
+        let schedule = Schedule {
+            id: Uuid::new_v4(),
+            user_id: *user_id,
+            title: request.title.clone(),
+            description: request.description.clone(),
+            priority: request.priority,
+            context: request.context.clone(),
+            cron: request.cron.clone(),
+            ahead: request.ahead,
+            next_run_at,
+            last_run_at: None,
+            created_at: Utc::now(),
+        };
+        self.schedules.lock().unwrap().push(schedule.clone());
+        Ok(schedule)
+    }
+
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Schedule>, ScheduleRepositoryError> {
+        let mut schedules: Vec<Schedule> =
+            self.schedules.lock().unwrap().iter().filter(|schedule| schedule.user_id == *user_id).cloned().collect();
+        schedules.sort_by_key(|schedule| (schedule.next_run_at, schedule.created_at));
+        Ok(schedules)
+    }
+
+    async fn delete(&self, user_id: &Uuid, id: &Uuid) -> Result<bool, ScheduleRepositoryError> {
+        let mut schedules = self.schedules.lock().unwrap();
+        let before = schedules.len();
+        schedules.retain(|schedule| !(schedule.id == *id && schedule.user_id == *user_id));
+        Ok(schedules.len() < before)
+    }
+
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Schedule>, ScheduleRepositoryError> {
+        let mut schedules: Vec<Schedule> =
+            self.schedules.lock().unwrap().iter().filter(|schedule| schedule.next_run_at <= now || schedule.ahead > 0).cloned().collect();
+        schedules.sort_by_key(|schedule| (schedule.next_run_at, schedule.id));
+        Ok(schedules)
+    }
+
+    async fn claim(&self, id: &Uuid, due: DateTime<Utc>, next_run_at: DateTime<Utc>, now: DateTime<Utc>) -> Result<bool, ScheduleRepositoryError> {
+        let mut schedules = self.schedules.lock().unwrap();
+        match schedules.iter_mut().find(|schedule| schedule.id == *id) {
+            Some(schedule) if schedule.next_run_at == due => {
+                schedule.next_run_at = next_run_at;
+                schedule.last_run_at = Some(now);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, released: &Schedule) -> Result<(), ScheduleRepositoryError> {
+        let mut schedules = self.schedules.lock().unwrap();
+        if let Some(schedule) = schedules.iter_mut().find(|schedule| schedule.id == released.id) {
+            schedule.next_run_at = released.next_run_at;
+            schedule.last_run_at = released.last_run_at;
+        }
+        Ok(())
+    }
+}
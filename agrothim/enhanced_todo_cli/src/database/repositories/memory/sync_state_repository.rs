@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::sync_state_repository::{SyncStateRepository, SyncStateRepositoryError};
+use crate::models::sync::{SyncConflict, SyncState};
+
+/// Sync bookkeeping kept in memory, for tests of the sync engine
+#[derive(Default)]
+pub struct InMemorySyncStateRepository {
+    states: Mutex<HashMap<Uuid, SyncState>>,
+    conflicts: Mutex<HashMap<Uuid, SyncConflict>>,
+}
+
+impl InMemorySyncStateRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SyncStateRepository for InMemorySyncStateRepository {
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Vec<SyncState>, SyncStateRepositoryError> {
+        let states = self.states.lock().unwrap();
+        Ok(states.values().filter(|state| state.user_id == *user_id).copied().collect())
+    }
+
+    async fn save(&self, state: &SyncState) -> Result<(), SyncStateRepositoryError> {
+        self.states.lock().unwrap().insert(state.task_id, *state);
+        Ok(())
+    }
+
+    async fn delete(&self, task_id: &Uuid) -> Result<(), SyncStateRepositoryError> {
+        self.states.lock().unwrap().remove(task_id);
+        Ok(())
+    }
+
+    async fn find_conflicts(&self, user_id: &Uuid) -> Result<Vec<SyncConflict>, SyncStateRepositoryError> {
+        let conflicts = self.conflicts.lock().unwrap();
+        let mut found: Vec<SyncConflict> = conflicts.values().filter(|c| c.user_id == *user_id).copied().collect();
+        found.sort_by_key(|conflict| conflict.detected_at);
+        Ok(found)
+    }
+
+    async fn find_conflict(&self, task_id: &Uuid) -> Result<Option<SyncConflict>, SyncStateRepositoryError> {
+        Ok(self.conflicts.lock().unwrap().get(task_id).copied())
+    }
+
+    async fn save_conflict(&self, conflict: &SyncConflict) -> Result<(), SyncStateRepositoryError> {
+        self.conflicts.lock().unwrap().entry(conflict.task_id).or_insert(*conflict);
+        Ok(())
+    }
+
+    async fn delete_conflict(&self, task_id: &Uuid) -> Result<(), SyncStateRepositoryError> {
+        self.conflicts.lock().unwrap().remove(task_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,512 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::{TaskRepository, TaskRepositoryError, TaskStream, TaskTransaction};
+use crate::models::task::{
+    normalize_context, ContextCount, Page, PageRequest, StatisticsFilter, StoreTaskRequest, Task, TaskGroup, TaskGrouping, TaskStatistics,
+    TaskStatus, UpdateTaskRequest, SORT_ORDER_STEP,
+};
+use crate::models::calendar::UserTimezone;
+use crate::models::report::{DayCount, TaskTimestamp, Throughput};
+use crate::models::workspace::{MemberTaskCounts, OverdueBreakdown};
+
+/// Task repository that keeps everything in memory, for the tutorial, the
+/// `memory://` backend and tests. Nothing is persisted; the data is gone when
+/// the repository is dropped.
+#[derive(Default)]
+pub struct InMemoryTaskRepository {
+    tasks: Mutex<HashMap<Uuid, Task>>,
+    /// Next task number per user, like `users.next_task_number`
+    next_numbers: Mutex<HashMap<Uuid, i32>>,
+}
+
+impl InMemoryTaskRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the user's next task number; numbers of deleted tasks are never
+    /// handed out again
+    fn next_number(&self, user_id: &Uuid) -> i32 {
+        let mut next_numbers = self.next_numbers.lock().unwrap();
+        let next = next_numbers.entry(*user_id).or_insert(1);
+        *next += 1;
+        *next - 1
+    }
+
+    /// Same order as the database: pinned, then ranked, then most recently updated
+    fn sort_like_database(tasks: &mut [Task]) {
+        tasks.sort_by(|a, b| {
+            b.pinned.cmp(&a.pinned).then_with(|| match (a.sort_order, b.sort_order) {
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => b.updated_at.cmp(&a.updated_at),
+            })
+        });
+    }
+}
+
+/// Transaction that works on a copy of the tasks and writes back the ones it
+/// changed on commit
+pub struct InMemoryTaskTransaction<'a> {
+    repository: &'a InMemoryTaskRepository,
+    staged: HashMap<Uuid, Task>,
+    changed: HashSet<Uuid>,
+}
+
+#[async_trait]
+impl TaskTransaction for InMemoryTaskTransaction<'_> {
+    async fn store(&mut self, request: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        let mut task = Task::new(request, *user_id).map_err(TaskRepositoryError::ValidationError)?;
+        task.check_dates()?;
+        task.number = self.repository.next_number(user_id);
+        self.staged.insert(task.id, task.clone());
+        self.changed.insert(task.id);
+        Ok(task)
+    }
+
+    async fn delete_bulk(&mut self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        let mut deleted = 0;
+        for id in ids {
+            if self.staged.get(id).is_some_and(|task| task.user_id == *user_id) {
+                self.staged.remove(id);
+                self.changed.insert(*id);
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn commit(mut self: Box<Self>) -> Result<(), TaskRepositoryError> {
+        let mut tasks = self.repository.tasks.lock().unwrap();
+        for id in self.changed.drain() {
+            match self.staged.remove(&id) {
+                Some(task) => tasks.insert(id, task),
+                None => tasks.remove(&id),
+            };
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TaskRepository for InMemoryTaskRepository {
+    async fn store(
+        &self,
+        request: StoreTaskRequest,
+        user_id: &Uuid,
+    ) -> Result<Task, TaskRepositoryError> {
+        let mut task = Task::new(request, *user_id)
+            .map_err(TaskRepositoryError::ValidationError)?;
+        task.check_dates()?;
+
+        task.number = self.next_number(user_id);
+        self.tasks.lock().unwrap().insert(task.id, task.clone());
+        Ok(task)
+    }
+
+    async fn store_many(&self, requests: Vec<StoreTaskRequest>, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = requests
+            .into_iter()
+            .map(|request| Task::new(request, *user_id).and_then(|task| task.check_dates().map(|_| task)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stored = self.tasks.lock().unwrap();
+        Ok(tasks
+            .into_iter()
+            .map(|mut task| {
+                task.number = self.next_number(user_id);
+                stored.insert(task.id, task.clone());
+                task
+            })
+            .collect())
+    }
+
+    async fn put_copy(&self, task: &Task) -> Result<Task, TaskRepositoryError> {
+        task.check_dates()?;
+        let mut tasks = self.tasks.lock().unwrap();
+        let (number, version) = match tasks.get(&task.id) {
+            Some(existing) if existing.user_id != task.user_id => return Err(TaskRepositoryError::NotFound),
+            Some(existing) => (existing.number, existing.version + 1),
+            None => (self.next_number(&task.user_id), 1),
+        };
+        let copy = Task { number, version, ..task.clone() };
+        tasks.insert(copy.id, copy.clone());
+        Ok(copy)
+    }
+
+    async fn begin(&self) -> Result<Box<dyn TaskTransaction + '_>, TaskRepositoryError> {
+        Ok(Box::new(InMemoryTaskTransaction {
+            repository: self,
+            staged: self.tasks.lock().unwrap().clone(),
+            changed: HashSet::new(),
+        }))
+    }
+
+    async fn find_by_number(&self, user_id: &Uuid, number: i32) -> Result<Option<Task>, TaskRepositoryError> {
+        Ok(self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .find(|task| task.user_id == *user_id && task.number == number)
+            .cloned())
+    }
+
+    async fn find_by_id_prefix(&self, user_id: &Uuid, prefix: &str, limit: i64) -> Result<Vec<Task>, TaskRepositoryError> {
+        Ok(self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| task.user_id == *user_id && task.id.to_string().starts_with(prefix))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>, TaskRepositoryError> {
+        Ok(self.tasks.lock().unwrap().get(id).cloned())
+    }
+
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| task.user_id == *user_id)
+            .cloned()
+            .collect();
+        Self::sort_like_database(&mut tasks);
+        Ok(tasks)
+    }
+
+    fn stream_by_user_id(&self, user_id: &Uuid) -> TaskStream<'_> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| task.user_id == *user_id)
+            .cloned()
+            .collect();
+        Self::sort_like_database(&mut tasks);
+        Box::pin(tokio_stream::iter(tasks.into_iter().map(Ok)))
+    }
+
+    async fn find_by_user_id_paginated(&self, user_id: &Uuid, page: PageRequest) -> Result<Page<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.sort_by(|a, b| page.sort.compare(a, b));
+
+        let after = match page.after {
+            Some(cursor) => tasks.iter().position(|task| task.id == cursor).ok_or(TaskRepositoryError::NotFound)? + 1,
+            None => 0,
+        };
+        let total = tasks.len() as i64;
+        let skipped = after + page.offset;
+        let items = tasks.into_iter().skip(skipped).take(page.limit.unwrap_or(usize::MAX)).collect();
+        Ok(Page::new(items, skipped, total))
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| ids.contains(&task.id))
+            .cloned()
+            .collect();
+        Self::sort_like_database(&mut tasks);
+        Ok(tasks)
+    }
+
+    async fn find_by_workspace(&self, workspace_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| task.workspace_id == Some(*workspace_id))
+            .cloned()
+            .collect();
+        Self::sort_like_database(&mut tasks);
+        Ok(tasks)
+    }
+
+    async fn find_overdue_by_user(&self, user_id: &Uuid, due_before: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.due_date.is_some_and(|due| due < due_before) && !task.is_completed());
+        tasks.sort_by_key(|task| (!task.pinned, task.due_date));
+        Ok(tasks)
+    }
+
+    async fn find_due_between(&self, user_id: &Uuid, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.due_date.is_some_and(|due| due >= from && due < until));
+        Ok(tasks)
+    }
+
+    async fn find_by_context(&self, user_id: &Uuid, context: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        let context = normalize_context(context);
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.context.as_ref() == Some(&context));
+        Ok(tasks)
+    }
+
+    async fn count_open_by_context(&self, user_id: &Uuid) -> Result<Vec<ContextCount>, TaskRepositoryError> {
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        for task in self.find_by_user_id(user_id).await? {
+            if task.is_completed() {
+                continue;
+            }
+            if let Some(context) = task.context {
+                *counts.entry(context).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<ContextCount> = counts
+            .into_iter()
+            .map(|(context, open_tasks)| ContextCount { context, open_tasks })
+            .collect();
+        counts.sort_by(|a, b| b.open_tasks.cmp(&a.open_tasks).then_with(|| a.context.cmp(&b.context)));
+        Ok(counts)
+    }
+
+    async fn find_started_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(Task::has_started);
+        Ok(tasks)
+    }
+
+    async fn find_by_status(&self, user_id: &Uuid, status: TaskStatus) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| task.status == status);
+        Ok(tasks)
+    }
+
+    async fn search_tasks(&self, user_id: &Uuid, search_term: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        let search_lower = search_term.to_lowercase();
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| {
+            task.title.to_lowercase().contains(&search_lower)
+                || task
+                    .description
+                    .as_ref()
+                    .is_some_and(|d| d.to_lowercase().contains(&search_lower))
+        });
+        Ok(tasks)
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        request: UpdateTaskRequest,
+    ) -> Result<Task, TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(id) {
+            if task.user_id != *user_id {
+                return Err(TaskRepositoryError::NotFound);
+            }
+            if request.version.is_some_and(|version| version != task.version) {
+                return Err(TaskRepositoryError::Conflict);
+            }
+            // Like the databases' CHECK, a rejected update leaves the task as it was
+            let mut updated = task.clone();
+            updated.update(request);
+            updated.check_dates()?;
+            updated.version += 1;
+            *task = updated.clone();
+            Ok(updated)
+        } else {
+            Err(TaskRepositoryError::NotFound)
+        }
+    }
+
+    async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get(id) {
+            if task.user_id == *user_id {
+                tasks.remove(id);
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        } else {
+            Ok(false)
+        }
+    }
+
+    async fn delete_bulk(&self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let before = tasks.len();
+        tasks.retain(|id, task| task.user_id != *user_id || !ids.contains(id));
+        Ok((before - tasks.len()) as u64)
+    }
+
+    async fn set_pinned(&self, id: &Uuid, user_id: &Uuid, pinned: bool) -> Result<Task, TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        match tasks.get_mut(id) {
+            Some(task) if task.user_id == *user_id => {
+                task.pinned = pinned;
+                task.version += 1;
+                Ok(task.clone())
+            }
+            _ => Err(TaskRepositoryError::NotFound),
+        }
+    }
+
+    async fn update_status_if(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        expected: TaskStatus,
+        status: TaskStatus,
+    ) -> Result<Option<Task>, TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        match tasks.get_mut(id) {
+            Some(task) if task.user_id == *user_id && task.status == expected => {
+                task.status = status;
+                task.completed_at = match status {
+                    TaskStatus::Completed => task.completed_at.or(Some(Utc::now())),
+                    _ => None,
+                };
+                task.version += 1;
+                Ok(Some(task.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn update_status_many(
+        &self,
+        user_id: &Uuid,
+        expected: &[(Uuid, TaskStatus)],
+        status: TaskStatus,
+    ) -> Result<Vec<Task>, TaskRepositoryError> {
+        let mut updated = Vec::new();
+        for (id, expected) in expected {
+            if let Some(task) = self.update_status_if(id, user_id, *expected, status).await? {
+                updated.push(task);
+            }
+        }
+        Ok(updated)
+    }
+
+    async fn set_sort_order(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        sort_order: f64,
+    ) -> Result<Task, TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        match tasks.get_mut(id) {
+            Some(task) if task.user_id == *user_id => {
+                task.sort_order = Some(sort_order);
+                task.version += 1;
+                Ok(task.clone())
+            }
+            _ => Err(TaskRepositoryError::NotFound),
+        }
+    }
+
+    async fn renumber(&self, user_id: &Uuid, ordered_ids: &[Uuid]) -> Result<(), TaskRepositoryError> {
+        let mut tasks = self.tasks.lock().unwrap();
+        for (position, id) in ordered_ids.iter().enumerate() {
+            if let Some(task) = tasks.get_mut(id).filter(|task| task.user_id == *user_id) {
+                task.sort_order = Some((position + 1) as f64 * SORT_ORDER_STEP);
+                task.version += 1;
+            }
+        }
+        Ok(())
+    }
+
+    async fn count_by_user(&self, user_id: &Uuid) -> Result<i64, TaskRepositoryError> {
+        let count = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| task.user_id == *user_id)
+            .count() as i64;
+        Ok(count)
+    }
+
+    async fn get_user_statistics(
+        &self,
+        user_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        filter: &StatisticsFilter,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
+        let mut tasks = self.find_by_user_id(user_id).await?;
+        tasks.retain(|task| filter.matches(task));
+        Ok(TaskStatistics::from_tasks(&tasks, due_before, stale_before))
+    }
+
+    async fn count_grouped(
+        &self,
+        user_id: &Uuid,
+        group_by: TaskGrouping,
+        filter: &StatisticsFilter,
+    ) -> Result<HashMap<TaskGroup, i64>, TaskRepositoryError> {
+        let mut counts = HashMap::new();
+        for task in self.find_by_user_id(user_id).await? {
+            if filter.matches(&task) {
+                *counts.entry(TaskGroup::of(&task, group_by)).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn get_throughput(&self, user_id: &Uuid, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Throughput, TaskRepositoryError> {
+        let tasks = self.find_by_user_id(user_id).await?;
+        Ok(Throughput::from_tasks(&tasks, since, until))
+    }
+
+    async fn count_by_day(
+        &self,
+        user_id: &Uuid,
+        timestamp: TaskTimestamp,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Result<Vec<DayCount>, TaskRepositoryError> {
+        let tasks = self.find_by_user_id(user_id).await?;
+        Ok(DayCount::from_tasks(&tasks, timestamp, since, until, timezone))
+    }
+
+    async fn get_workspace_statistics(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
+        let tasks = self.find_by_workspace(workspace_id).await?;
+        Ok(TaskStatistics::from_tasks(&tasks, due_before, stale_before))
+    }
+
+    async fn get_workspace_member_counts(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        completed_since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<MemberTaskCounts>, TaskRepositoryError> {
+        let tasks = self.find_by_workspace(workspace_id).await?;
+        Ok(MemberTaskCounts::from_tasks(&tasks, due_before, completed_since))
+    }
+
+    async fn get_workspace_overdue_breakdown(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+    ) -> Result<OverdueBreakdown, TaskRepositoryError> {
+        let tasks = self.find_by_workspace(workspace_id).await?;
+        Ok(OverdueBreakdown::from_tasks(&tasks, due_before))
+    }
+}
@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::repositories::user_repository::{UserRepository, UserRepositoryError};
+use crate::models::user::{StoreUserRequest, UpdateUserRequest, User, UsernameChange};
+
+/// User repository that keeps accounts and their rename history in memory.
+/// Usernames and emails are unique, as in the database.
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: Mutex<HashMap<Uuid, User>>,
+    /// Newest first, like `find_username_changes`
+    username_changes: Mutex<Vec<UsernameChange>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the user's renames back in time, to test the rename cooldown
+    #[cfg(test)]
+    pub(crate) fn backdate_username_changes(&self, id: &Uuid, by: chrono::Duration) {
+        for change in self.username_changes.lock().unwrap().iter_mut().filter(|change| change.user_id == *id) {
+            change.changed_at -= by;
+        }
+    }
+
+    fn find_by(&self, matches: impl Fn(&User) -> bool) -> Option<User> {
+        self.users.lock().unwrap().values().find(|user| matches(user)).cloned()
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn store(&self, request: StoreUserRequest) -> Result<User, UserRepositoryError> {
+        let mut users = self.users.lock().unwrap();
+        if users.values().any(|user| user.username == request.username) {
+            return Err(UserRepositoryError::UsernameExists { username: request.username });
+        }
+        if users.values().any(|user| user.email == request.email) {
+            return Err(UserRepositoryError::EmailExists { email: request.email });
+        }
+
+        let now = Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            username: request.username,
+            email: request.email,
+            password_hash: request.password_hash,
+            email_verified_at: None,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        users.insert(user.id, user.clone());
+        Ok(user)
+    }
+
+    async fn put_copy(&self, user: &User) -> Result<User, UserRepositoryError> {
+        self.users.lock().unwrap().insert(user.id, user.clone());
+        Ok(user.clone())
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError> {
+        Ok(self.users.lock().unwrap().get(id).cloned())
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, UserRepositoryError> {
+        Ok(self.find_by(|user| user.username == username))
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError> {
+        Ok(self.find_by(|user| user.email == email))
+    }
+
+    async fn find_all(&self) -> Result<Vec<User>, UserRepositoryError> {
+        let mut users: Vec<User> = self.users.lock().unwrap().values().cloned().collect();
+        users.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.username.cmp(&b.username)));
+        Ok(users)
+    }
+
+    async fn update(&self, id: &Uuid, updates: UpdateUserRequest) -> Result<User, UserRepositoryError> {
+        let mut users = self.users.lock().unwrap();
+        if let Some(username) = &updates.username {
+            if users.values().any(|user| user.id != *id && user.username == *username) {
+                return Err(UserRepositoryError::UsernameExists { username: username.clone() });
+            }
+        }
+        if let Some(email) = &updates.email {
+            if users.values().any(|user| user.id != *id && user.email == *email) {
+                return Err(UserRepositoryError::EmailExists { email: email.clone() });
+            }
+        }
+
+        let user = users.get_mut(id).ok_or(UserRepositoryError::NotFound)?;
+        let now = Utc::now();
+        if let Some(username) = updates.username {
+            if username != user.username {
+                self.username_changes.lock().unwrap().insert(0, UsernameChange {
+                    id: Uuid::new_v4(),
+                    user_id: *id,
+                    old_username: std::mem::replace(&mut user.username, username.clone()),
+                    new_username: username,
+                    changed_at: now,
+                });
+            }
+        }
+        if let Some(email) = updates.email {
+            user.email = email;
+        }
+        if let Some(password_hash) = updates.password_hash {
+            user.password_hash = password_hash;
+        }
+        user.updated_at = now;
+        Ok(user.clone())
+    }
+
+    async fn find_username_changes(&self, id: &Uuid) -> Result<Vec<UsernameChange>, UserRepositoryError> {
+        Ok(self
+            .username_changes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|change| change.user_id == *id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
+        self.username_changes.lock().unwrap().retain(|change| change.user_id != *id);
+        Ok(self.users.lock().unwrap().remove(id).is_some())
+    }
+
+    async fn mark_email_verified(&self, id: &Uuid) -> Result<User, UserRepositoryError> {
+        let mut users = self.users.lock().unwrap();
+        let user = users.get_mut(id).ok_or(UserRepositoryError::NotFound)?;
+        user.email_verified_at.get_or_insert_with(Utc::now);
+        user.updated_at = Utc::now();
+        Ok(user.clone())
+    }
+
+    async fn set_active(&self, id: &Uuid, active: bool) -> Result<User, UserRepositoryError> {
+        let mut users = self.users.lock().unwrap();
+        let user = users.get_mut(id).ok_or(UserRepositoryError::NotFound)?;
+        user.is_active = active;
+        user.updated_at = Utc::now();
+        Ok(user.clone())
+    }
+
+    async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError> {
+        Ok(self.find_by(|user| user.username == username).is_some())
+    }
+
+    async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
+        Ok(self.find_by(|user| user.email == email).is_some())
+    }
+}
@@ -1,5 +1,59 @@
 pub mod user_repository;
 pub mod task_repository;
+pub mod status_repository;
+pub mod time_entry_repository;
+pub mod webhook_repository;
+pub mod password_reset_repository;
+pub mod email_verification_repository;
+pub mod user_identity_repository;
+pub mod api_key_repository;
+pub mod session_repository;
+pub mod revoked_token_repository;
+pub mod workspace_repository;
+pub mod task_share_repository;
+pub mod invitation_repository;
+pub mod task_event_repository;
+pub mod sync_state_repository;
+pub mod issue_link_repository;
+pub mod digest_subscription_repository;
+pub mod commit_link_repository;
+pub mod schedule_repository;
+pub mod http;
+#[cfg(any(test, feature = "memory"))]
+pub mod memory;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_task_repository;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_user_repository;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_sync_state_repository;
 
 pub use user_repository::*;
-pub use task_repository::*;
\ No newline at end of file
+pub use task_repository::*;
+pub use status_repository::*;
+pub use time_entry_repository::*;
+pub use webhook_repository::*;
+pub use password_reset_repository::*;
+pub use email_verification_repository::*;
+pub use user_identity_repository::*;
+pub use api_key_repository::*;
+pub use session_repository::*;
+pub use revoked_token_repository::*;
+pub use workspace_repository::*;
+pub use task_share_repository::*;
+pub use invitation_repository::*;
+pub use task_event_repository::*;
+pub use sync_state_repository::*;
+pub use issue_link_repository::*;
+pub use digest_subscription_repository::*;
+pub use commit_link_repository::*;
+pub use schedule_repository::*;
+pub use http::*;
+#[cfg(any(test, feature = "memory"))]
+pub use memory::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_task_repository::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_user_repository::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_sync_state_repository::*;
\ No newline at end of file
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::password_reset::PasswordResetToken;
+
+#[derive(Error, Debug)]
+pub enum PasswordResetRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Password reset repository trait for single-use reset tokens
+#[async_trait]
+pub trait PasswordResetRepository: Send + Sync {
+    /// Store a new token for `user_id`, discarding the user's earlier unused tokens
+    async fn store(
+        &self,
+        user_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PasswordResetToken, PasswordResetRepositoryError>;
+    /// Mark an unused, unexpired token as used and return its user. Returns
+    /// `None` for unknown, expired or already used tokens, so each token can
+    /// be redeemed at most once even by concurrent callers.
+    async fn consume(&self, token_hash: &str) -> Result<Option<Uuid>, PasswordResetRepositoryError>;
+}
+
+/// PostgreSQL implementation of PasswordResetRepository
+pub struct PostgresPasswordResetRepository {
+    pool: PgPool,
+}
+
+impl PostgresPasswordResetRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PasswordResetRepository for PostgresPasswordResetRepository {
+    #[instrument(level = "debug", name = "PasswordResetRepository::store", skip_all)]
+    async fn store(
+        &self,
+        user_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PasswordResetToken, PasswordResetRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let query = r#"
+            INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, token_hash, expires_at, used_at, created_at
+        "#;
+
+        let token = sqlx::query_as::<_, PasswordResetToken>(query)
+            .bind(user_id)
+            .bind(token_hash)
+            .bind(expires_at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    #[instrument(level = "debug", name = "PasswordResetRepository::consume", skip_all)]
+    async fn consume(&self, token_hash: &str) -> Result<Option<Uuid>, PasswordResetRepositoryError> {
+        let query = r#"
+            UPDATE password_reset_tokens
+            SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+        "#;
+
+        let user_id = sqlx::query_scalar::<_, Uuid>(query)
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user_id)
+    }
+}
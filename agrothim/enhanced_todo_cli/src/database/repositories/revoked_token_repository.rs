@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum RevokedTokenRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Revoked token repository trait, keyed by the tokens' `jti` claim
+#[async_trait]
+pub trait RevokedTokenRepository: Send + Sync {
+    /// Revoke the token with this ID until it expires; revoking it again is a no-op.
+    /// Also forgets tokens that have expired since they were revoked.
+    async fn revoke(
+        &self,
+        jti: &str,
+        user_id: &Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), RevokedTokenRepositoryError>;
+    async fn is_revoked(&self, jti: &str) -> Result<bool, RevokedTokenRepositoryError>;
+    /// Revoke every token of the user issued before `issued_before`
+    async fn revoke_all(&self, user_id: &Uuid, issued_before: DateTime<Utc>) -> Result<(), RevokedTokenRepositoryError>;
+    /// Tokens of the user issued before this time are revoked
+    async fn revoked_before(&self, user_id: &Uuid) -> Result<Option<DateTime<Utc>>, RevokedTokenRepositoryError>;
+}
+
+/// PostgreSQL implementation of RevokedTokenRepository
+pub struct PostgresRevokedTokenRepository {
+    pool: PgPool,
+}
+
+impl PostgresRevokedTokenRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RevokedTokenRepository for PostgresRevokedTokenRepository {
+    #[instrument(level = "debug", name = "RevokedTokenRepository::revoke", skip_all)]
+    async fn revoke(
+        &self,
+        jti: &str,
+        user_id: &Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), RevokedTokenRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&mut *tx)
+            .await?;
+
+        let query = r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (jti) DO NOTHING
+        "#;
+
+        sqlx::query(query)
+            .bind(jti)
+            .bind(user_id)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "RevokedTokenRepository::is_revoked", skip_all)]
+    async fn is_revoked(&self, jti: &str) -> Result<bool, RevokedTokenRepositoryError> {
+        let revoked = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)")
+            .bind(jti)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(revoked)
+    }
+
+    #[instrument(level = "debug", name = "RevokedTokenRepository::revoke_all", skip_all)]
+    async fn revoke_all(&self, user_id: &Uuid, issued_before: DateTime<Utc>) -> Result<(), RevokedTokenRepositoryError> {
+        let query = r#"
+            INSERT INTO revoked_user_tokens (user_id, issued_before)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET issued_before = GREATEST(revoked_user_tokens.issued_before, EXCLUDED.issued_before)
+        "#;
+
+        sqlx::query(query)
+            .bind(user_id)
+            .bind(issued_before)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "RevokedTokenRepository::revoked_before", skip_all)]
+    async fn revoked_before(&self, user_id: &Uuid) -> Result<Option<DateTime<Utc>>, RevokedTokenRepositoryError> {
+        let issued_before = sqlx::query_scalar::<_, DateTime<Utc>>("SELECT issued_before FROM revoked_user_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(issued_before)
+    }
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::schedule::{Schedule, StoreScheduleRequest};
+
+#[derive(Error, Debug)]
+pub enum ScheduleRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Schedule repository trait for tasks created on a schedule
+#[async_trait]
+pub trait ScheduleRepository: Send + Sync {
+    async fn create(&self, user_id: &Uuid, request: &StoreScheduleRequest, next_run_at: DateTime<Utc>) -> Result<Schedule, ScheduleRepositoryError>;
+    /// The user's schedules, next to fire first
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Schedule>, ScheduleRepositoryError>;
+    async fn delete(&self, user_id: &Uuid, id: &Uuid) -> Result<bool, ScheduleRepositoryError>;
+    /// Everyone's schedules due at `now`, and those creating their tasks ahead
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Schedule>, ScheduleRepositoryError>;
+    /// Move a schedule due at `due` on to `next_run_at`, unless another run
+    /// already did; returns whether this call claimed the firing
+    async fn claim(&self, id: &Uuid, due: DateTime<Utc>, next_run_at: DateTime<Utc>, now: DateTime<Utc>) -> Result<bool, ScheduleRepositoryError>;
+    /// Undo a claim whose task could not be created, so the next run retries
+    async fn release(&self, schedule: &Schedule) -> Result<(), ScheduleRepositoryError>;
+}
+
+/// PostgreSQL implementation of ScheduleRepository
+pub struct PostgresScheduleRepository {
+    pool: PgPool,
+}
+
+impl PostgresScheduleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ScheduleRepository for PostgresScheduleRepository {
+    #[instrument(level = "debug", name = "ScheduleRepository::create", skip_all)]
+    async fn create(&self, user_id: &Uuid, request: &StoreScheduleRequest, next_run_at: DateTime<Utc>) -> Result<Schedule, ScheduleRepositoryError> {
+        let query = r#"
+            INSERT INTO schedules (id, user_id, title, description, priority, context, cron, ahead, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, user_id, title, description, priority, context, cron, ahead, next_run_at, last_run_at, created_at
+        "#;
+
+        let schedule = sqlx::query_as::<_, Schedule>(query)
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(&request.title)
+            .bind(&request.description)
+            .bind(request.priority)
+            .bind(&request.context)
+            .bind(&request.cron)
+            .bind(request.ahead)
+            .bind(next_run_at)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(schedule)
+    }
+
+    #[instrument(level = "debug", name = "ScheduleRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Schedule>, ScheduleRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, title, description, priority, context, cron, ahead, next_run_at, last_run_at, created_at
+            FROM schedules
+            WHERE user_id = $1
+            ORDER BY next_run_at, created_at
+        "#;
+
+        let schedules = sqlx::query_as::<_, Schedule>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(schedules)
+    }
+
+    #[instrument(level = "debug", name = "ScheduleRepository::delete", skip_all)]
+    async fn delete(&self, user_id: &Uuid, id: &Uuid) -> Result<bool, ScheduleRepositoryError> {
+        let result = sqlx::query("DELETE FROM schedules WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "ScheduleRepository::find_due", skip_all)]
+    async fn find_due(&self, now: DateTime<Utc>) -> Result<Vec<Schedule>, ScheduleRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, title, description, priority, context, cron, ahead, next_run_at, last_run_at, created_at
+            FROM schedules
+            WHERE next_run_at <= $1 OR ahead > 0
+            ORDER BY next_run_at, id
+        "#;
+
+        let schedules = sqlx::query_as::<_, Schedule>(query)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(schedules)
+    }
+
+    #[instrument(level = "debug", name = "ScheduleRepository::claim", skip_all)]
+    async fn claim(&self, id: &Uuid, due: DateTime<Utc>, next_run_at: DateTime<Utc>, now: DateTime<Utc>) -> Result<bool, ScheduleRepositoryError> {
+        let query = r#"
+            UPDATE schedules
+            SET next_run_at = $3, last_run_at = $4
+            WHERE id = $1 AND next_run_at = $2
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .bind(due)
+            .bind(next_run_at)
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "ScheduleRepository::release", skip_all)]
+    async fn release(&self, schedule: &Schedule) -> Result<(), ScheduleRepositoryError> {
+        sqlx::query("UPDATE schedules SET next_run_at = $2, last_run_at = $3 WHERE id = $1")
+            .bind(schedule.id)
+            .bind(schedule.next_run_at)
+            .bind(schedule.last_run_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
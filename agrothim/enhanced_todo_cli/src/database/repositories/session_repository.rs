@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::device_session::DeviceSession;
+
+#[derive(Error, Debug)]
+pub enum SessionRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Session repository trait for logins on the user's devices
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    async fn create(
+        &self,
+        user_id: &Uuid,
+        name: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<DeviceSession, SessionRepositoryError>;
+    /// The session if it is neither revoked nor expired, marking it as used
+    async fn use_session(&self, id: &Uuid) -> Result<Option<DeviceSession>, SessionRepositoryError>;
+    /// Move the expiry of an active session, e.g. after refreshing its tokens
+    async fn extend(&self, id: &Uuid, expires_at: DateTime<Utc>) -> Result<(), SessionRepositoryError>;
+    /// The user's sessions that are neither revoked nor expired, most recently used first
+    async fn find_active_by_user_id(&self, user_id: &Uuid) -> Result<Vec<DeviceSession>, SessionRepositoryError>;
+    /// Every session of the user including revoked and expired ones, newest first
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<DeviceSession>, SessionRepositoryError>;
+    /// Revoke one of the user's sessions; `None` if there is no such active session
+    async fn revoke(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<DeviceSession>, SessionRepositoryError>;
+}
+
+/// PostgreSQL implementation of SessionRepository
+pub struct PostgresSessionRepository {
+    pool: PgPool,
+}
+
+impl PostgresSessionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SessionRepository for PostgresSessionRepository {
+    #[instrument(level = "debug", name = "SessionRepository::create", skip_all)]
+    async fn create(
+        &self,
+        user_id: &Uuid,
+        name: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<DeviceSession, SessionRepositoryError> {
+        let query = r#"
+            INSERT INTO sessions (user_id, name, expires_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, name, expires_at, last_used_at, revoked_at, created_at
+        "#;
+
+        let session = sqlx::query_as::<_, DeviceSession>(query)
+            .bind(user_id)
+            .bind(name)
+            .bind(expires_at)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    #[instrument(level = "debug", name = "SessionRepository::use_session", skip_all)]
+    async fn use_session(&self, id: &Uuid) -> Result<Option<DeviceSession>, SessionRepositoryError> {
+        let query = r#"
+            UPDATE sessions
+            SET last_used_at = NOW()
+            WHERE id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            RETURNING id, user_id, name, expires_at, last_used_at, revoked_at, created_at
+        "#;
+
+        let session = sqlx::query_as::<_, DeviceSession>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+
+    #[instrument(level = "debug", name = "SessionRepository::extend", skip_all)]
+    async fn extend(&self, id: &Uuid, expires_at: DateTime<Utc>) -> Result<(), SessionRepositoryError> {
+        sqlx::query("UPDATE sessions SET expires_at = $2 WHERE id = $1 AND revoked_at IS NULL")
+            .bind(id)
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "SessionRepository::find_active_by_user_id", skip_all)]
+    async fn find_active_by_user_id(&self, user_id: &Uuid) -> Result<Vec<DeviceSession>, SessionRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, name, expires_at, last_used_at, revoked_at, created_at
+            FROM sessions
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY last_used_at DESC
+        "#;
+
+        let sessions = sqlx::query_as::<_, DeviceSession>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(sessions)
+    }
+
+    #[instrument(level = "debug", name = "SessionRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<DeviceSession>, SessionRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, name, expires_at, last_used_at, revoked_at, created_at
+            FROM sessions
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+        "#;
+
+        let sessions = sqlx::query_as::<_, DeviceSession>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(sessions)
+    }
+
+    #[instrument(level = "debug", name = "SessionRepository::revoke", skip_all)]
+    async fn revoke(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<DeviceSession>, SessionRepositoryError> {
+        let query = r#"
+            UPDATE sessions
+            SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            RETURNING id, user_id, name, expires_at, last_used_at, revoked_at, created_at
+        "#;
+
+        let session = sqlx::query_as::<_, DeviceSession>(query)
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(session)
+    }
+}
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::database::repositories::sync_state_repository::{SyncStateRepository, SyncStateRepositoryError};
+use crate::models::sync::{SyncConflict, SyncState};
+
+/// SQLite implementation of SyncStateRepository, on the local database of
+/// local-first mode
+pub struct SqliteSyncStateRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteSyncStateRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SyncStateRepository for SqliteSyncStateRepository {
+    #[instrument(level = "debug", name = "SyncStateRepository::find_by_user", skip_all)]
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Vec<SyncState>, SyncStateRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, local_version, remote_version, synced_at
+            FROM task_sync_state
+            WHERE user_id = $1
+        "#;
+
+        let states = sqlx::query_as::<_, SyncState>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(states)
+    }
+
+    #[instrument(level = "debug", name = "SyncStateRepository::save", skip_all)]
+    async fn save(&self, state: &SyncState) -> Result<(), SyncStateRepositoryError> {
+        let query = r#"
+            INSERT INTO task_sync_state (task_id, user_id, local_version, remote_version, synced_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (task_id) DO UPDATE
+            SET local_version = excluded.local_version,
+                remote_version = excluded.remote_version,
+                synced_at = excluded.synced_at
+        "#;
+
+        sqlx::query(query)
+            .bind(state.task_id)
+            .bind(state.user_id)
+            .bind(state.local_version)
+            .bind(state.remote_version)
+            .bind(state.synced_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "SyncStateRepository::delete", skip_all)]
+    async fn delete(&self, task_id: &Uuid) -> Result<(), SyncStateRepositoryError> {
+        sqlx::query("DELETE FROM task_sync_state WHERE task_id = $1")
+            .bind(task_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "SyncStateRepository::find_conflicts", skip_all)]
+    async fn find_conflicts(&self, user_id: &Uuid) -> Result<Vec<SyncConflict>, SyncStateRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, detected_at
+            FROM task_sync_conflicts
+            WHERE user_id = $1
+            ORDER BY detected_at
+        "#;
+
+        let conflicts = sqlx::query_as::<_, SyncConflict>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(conflicts)
+    }
+
+    #[instrument(level = "debug", name = "SyncStateRepository::find_conflict", skip_all)]
+    async fn find_conflict(&self, task_id: &Uuid) -> Result<Option<SyncConflict>, SyncStateRepositoryError> {
+        let conflict = sqlx::query_as::<_, SyncConflict>(
+            "SELECT task_id, user_id, detected_at FROM task_sync_conflicts WHERE task_id = $1",
+        )
+        .bind(task_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(conflict)
+    }
+
+    #[instrument(level = "debug", name = "SyncStateRepository::save_conflict", skip_all)]
+    async fn save_conflict(&self, conflict: &SyncConflict) -> Result<(), SyncStateRepositoryError> {
+        let query = r#"
+            INSERT INTO task_sync_conflicts (task_id, user_id, detected_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (task_id) DO NOTHING
+        "#;
+
+        sqlx::query(query)
+            .bind(conflict.task_id)
+            .bind(conflict.user_id)
+            .bind(conflict.detected_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "SyncStateRepository::delete_conflict", skip_all)]
+    async fn delete_conflict(&self, task_id: &Uuid) -> Result<(), SyncStateRepositoryError> {
+        sqlx::query("DELETE FROM task_sync_conflicts WHERE task_id = $1")
+            .bind(task_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,980 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use sqlx::{QueryBuilder, Row, Sqlite, SqliteConnection, SqliteExecutor, SqlitePool, Transaction};
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::database::repositories::task_repository::{
+    task_group_column, task_order_by, task_timestamp_column, TaskRepository, TaskRepositoryError, TaskStream, TaskTransaction,
+};
+use crate::models::task::{
+    normalize_context, ContextCount, Page, PageRequest, StatisticsFilter, StoreTaskRequest, Task, TaskGroup, TaskGrouping, TaskStatus,
+    TaskStatistics, UpdateTaskRequest, SORT_ORDER_STEP,
+};
+use crate::models::calendar::UserTimezone;
+use crate::models::report::{DayCount, TaskTimestamp, Throughput};
+use crate::models::workspace::{MemberTaskCounts, OverdueBreakdown};
+
+/// SQLite implementation of TaskRepository. Timestamps are bound from Rust
+/// rather than taken from the database clock, so they compare as RFC 3339 text.
+///
+/// Statements with `RETURNING` are always run with `fetch_all`: SQLite only
+/// commits a write once its statement has been stepped to the end, which
+/// `fetch_one`/`fetch_optional` leave for later, and until then the other
+/// connections of the pool do not see it.
+pub struct SqliteTaskRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteTaskRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Take the user's next task number; numbers of deleted tasks are never
+    /// handed out again
+    async fn next_number(conn: &mut SqliteConnection, user_id: &Uuid) -> Result<i32, TaskRepositoryError> {
+        let query = r#"
+            UPDATE users SET next_task_number = next_task_number + 1
+            WHERE id = $1
+            RETURNING next_task_number - 1
+        "#;
+
+        let number = sqlx::query_scalar::<_, i32>(query)
+            .bind(user_id)
+            .fetch_all(conn)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?
+            .pop()
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(number)
+    }
+
+    /// Insert a task under the user's next number; run it in a transaction,
+    /// so a failed insert gives the number back
+    async fn insert(conn: &mut SqliteConnection, task: &Task) -> Result<Task, TaskRepositoryError> {
+        let number = Self::next_number(conn, &task.user_id).await?;
+        let query = r#"
+            INSERT INTO tasks (id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at)
+            VALUES ($1, $19, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            RETURNING id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version
+        "#;
+
+        let stored_task = sqlx::query_as::<_, Task>(query)
+            .bind(task.id)
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(task.status)
+            .bind(task.priority)
+            .bind(task.due_date)
+            .bind(task.start_date)
+            .bind(task.completed_at)
+            .bind(task.estimate_minutes)
+            .bind(&task.client)
+            .bind(&task.context)
+            .bind(&task.location)
+            .bind(task.latitude)
+            .bind(task.longitude)
+            .bind(task.user_id)
+            .bind(task.workspace_id)
+            .bind(task.created_at)
+            .bind(task.updated_at)
+            .bind(number)
+            .fetch_all(conn)
+            .await
+            .map_err(TaskRepositoryError::from_write)?
+            .pop()
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(stored_task)
+    }
+
+    async fn update_status_where<'e>(
+        executor: impl SqliteExecutor<'e>,
+        id: &Uuid,
+        user_id: &Uuid,
+        expected: TaskStatus,
+        status: TaskStatus,
+    ) -> Result<Option<Task>, TaskRepositoryError> {
+        let query = r#"
+            UPDATE tasks
+            SET status = $4,
+                completed_at = CASE WHEN $4 = 2 THEN COALESCE(completed_at, $5) ELSE NULL END,
+                updated_at = $5,
+                version = version + 1
+            WHERE id = $1 AND user_id = $2 AND status = $3
+            RETURNING id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version
+        "#;
+
+        let updated = sqlx::query_as::<_, Task>(query)
+            .bind(id)
+            .bind(user_id)
+            .bind(expected)
+            .bind(status)
+            .bind(Utc::now())
+            .fetch_all(executor)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?
+            .pop();
+
+        Ok(updated)
+    }
+
+    fn statistics(row: &sqlx::sqlite::SqliteRow) -> TaskStatistics {
+        TaskStatistics {
+            total_tasks: row.get::<i64, _>("total_tasks"),
+            pending_tasks: row.get::<i64, _>("pending_tasks"),
+            in_progress_tasks: row.get::<i64, _>("in_progress_tasks"),
+            completed_tasks: row.get::<i64, _>("completed_tasks"),
+            overdue_tasks: row.get::<i64, _>("overdue_tasks"),
+            stale_tasks: row.get::<i64, _>("stale_tasks"),
+        }
+    }
+
+    async fn delete_many<'e>(executor: impl SqliteExecutor<'e>, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM tasks WHERE user_id = ");
+        query.push_bind(*user_id).push(" AND id IN (");
+        let mut separated = query.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query.push(")");
+
+        let result = query
+            .build()
+            .execute(executor)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// `TaskTransaction` on a SQLite transaction
+pub struct SqliteTaskTransaction {
+    tx: Transaction<'static, Sqlite>,
+}
+
+#[async_trait]
+impl TaskTransaction for SqliteTaskTransaction {
+    async fn store(&mut self, request: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        let task = Task::new(request, *user_id)?;
+        SqliteTaskRepository::insert(&mut self.tx, &task).await
+    }
+
+    async fn delete_bulk(&mut self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        SqliteTaskRepository::delete_many(&mut *self.tx, ids, user_id).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), TaskRepositoryError> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TaskRepository for SqliteTaskRepository {
+    #[instrument(level = "debug", name = "TaskRepository::store", skip_all)]
+    async fn store(&self, request: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        let task = Task::new(request, *user_id)?;
+        let mut tx = self.pool.begin().await?;
+        let stored = Self::insert(&mut tx, &task).await?;
+        tx.commit().await?;
+
+        Ok(stored)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::store_many", skip_all)]
+    async fn store_many(&self, requests: Vec<StoreTaskRequest>, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = requests
+            .into_iter()
+            .map(|request| Task::new(request, *user_id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut stored = Vec::with_capacity(tasks.len());
+        for task in &tasks {
+            stored.push(Self::insert(&mut tx, task).await?);
+        }
+        tx.commit().await?;
+
+        Ok(stored)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::put_copy", skip_all)]
+    async fn put_copy(&self, task: &Task) -> Result<Task, TaskRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        // Only a new copy takes a number; an existing one keeps its own
+        let existing = sqlx::query_scalar::<_, i32>("SELECT number FROM tasks WHERE id = $1")
+            .bind(task.id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let number = match existing {
+            Some(number) => number,
+            None => Self::next_number(&mut tx, &task.user_id).await?,
+        };
+
+        let query = r#"
+            INSERT INTO tasks (id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at)
+            VALUES ($1, $21, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            ON CONFLICT (id) DO UPDATE
+            SET title = excluded.title,
+                description = excluded.description,
+                status = excluded.status,
+                priority = excluded.priority,
+                due_date = excluded.due_date,
+                start_date = excluded.start_date,
+                completed_at = excluded.completed_at,
+                estimate_minutes = excluded.estimate_minutes,
+                sort_order = excluded.sort_order,
+                pinned = excluded.pinned,
+                client = excluded.client,
+                context = excluded.context,
+                location = excluded.location,
+                latitude = excluded.latitude,
+                longitude = excluded.longitude,
+                workspace_id = excluded.workspace_id,
+                updated_at = excluded.updated_at,
+                version = tasks.version + 1
+            WHERE tasks.user_id = excluded.user_id
+            RETURNING id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version
+        "#;
+
+        let stored = sqlx::query_as::<_, Task>(query)
+            .bind(task.id)
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(task.status)
+            .bind(task.priority)
+            .bind(task.due_date)
+            .bind(task.start_date)
+            .bind(task.completed_at)
+            .bind(task.estimate_minutes)
+            .bind(task.sort_order)
+            .bind(task.pinned)
+            .bind(&task.client)
+            .bind(&task.context)
+            .bind(&task.location)
+            .bind(task.latitude)
+            .bind(task.longitude)
+            .bind(task.user_id)
+            .bind(task.workspace_id)
+            .bind(task.created_at)
+            .bind(task.updated_at)
+            .bind(number)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(TaskRepositoryError::from_write)?
+            .pop();
+        tx.commit().await?;
+
+        stored.ok_or(TaskRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::begin", skip_all)]
+    async fn begin(&self) -> Result<Box<dyn TaskTransaction + '_>, TaskRepositoryError> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(SqliteTaskTransaction { tx }))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_id", skip_all)]
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>, TaskRepositoryError> {
+        let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(task)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    fn stream_by_user_id(&self, user_id: &Uuid) -> TaskStream<'_> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(*user_id)
+            .fetch(&self.pool)
+            .map(|task| task.map_err(TaskRepositoryError::DatabaseError));
+        Box::pin(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_user_id_paginated", skip_all)]
+    async fn find_by_user_id_paginated(&self, user_id: &Uuid, page: PageRequest) -> Result<Page<Task>, TaskRepositoryError> {
+        let ordered = format!(
+            "SELECT *, ROW_NUMBER() OVER (ORDER BY {}) AS position FROM tasks WHERE user_id = $1",
+            task_order_by(page.sort, "lower(title)")
+        );
+
+        let after: i64 = match page.after {
+            Some(cursor) => sqlx::query_scalar(&format!("SELECT position FROM ({ordered}) ordered WHERE id = $2"))
+                .bind(user_id)
+                .bind(cursor)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(TaskRepositoryError::DatabaseError)?
+                .ok_or(TaskRepositoryError::NotFound)?,
+            None => 0,
+        };
+
+        // SQLite takes a negative LIMIT as no limit
+        let query = format!("SELECT * FROM ({ordered}) ordered WHERE position > $2 ORDER BY position LIMIT $3 OFFSET $4");
+        let tasks = sqlx::query_as::<_, Task>(&query)
+            .bind(user_id)
+            .bind(after)
+            .bind(page.limit.map_or(-1, |limit| limit as i64))
+            .bind(page.offset as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        let total = self.count_by_user(user_id).await?;
+        Ok(Page::new(tasks, after as usize + page.offset, total))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_ids", skip_all)]
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Task>, TaskRepositoryError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // No array parameters in SQLite: one placeholder per ID
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM tasks WHERE id IN (");
+        let mut separated = query.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query.push(") ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC");
+
+        let tasks = query
+            .build_query_as::<Task>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_number", skip_all)]
+    async fn find_by_number(&self, user_id: &Uuid, number: i32) -> Result<Option<Task>, TaskRepositoryError> {
+        let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE user_id = $1 AND number = $2")
+            .bind(user_id)
+            .bind(number)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(task)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_workspace", skip_all)]
+    async fn find_by_workspace(&self, workspace_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE workspace_id = $1
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(workspace_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_id_prefix", skip_all)]
+    async fn find_by_id_prefix(&self, user_id: &Uuid, prefix: &str, limit: i64) -> Result<Vec<Task>, TaskRepositoryError> {
+        // IDs are BLOBs here, so match the prefix against their hex digits
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1 AND lower(hex(id)) LIKE $2 || '%'
+            ORDER BY created_at
+            LIMIT $3
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(prefix.replace('-', "").to_lowercase())
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_overdue_by_user", skip_all)]
+    async fn find_overdue_by_user(&self, user_id: &Uuid, due_before: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1
+            AND due_date < $2
+            AND status != 2
+            ORDER BY pinned DESC, due_date ASC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(due_before)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_due_between", skip_all)]
+    async fn find_due_between(&self, user_id: &Uuid, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1
+            AND due_date >= $2
+            AND due_date < $3
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(from)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_status", skip_all)]
+    async fn find_by_status(&self, user_id: &Uuid, status: TaskStatus) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1 AND status = $2
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(status)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_context", skip_all)]
+    async fn find_by_context(&self, user_id: &Uuid, context: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1 AND context = $2
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(normalize_context(context))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::count_open_by_context", skip_all)]
+    async fn count_open_by_context(&self, user_id: &Uuid) -> Result<Vec<ContextCount>, TaskRepositoryError> {
+        let query = r#"
+            SELECT context, COUNT(*) AS open_tasks
+            FROM tasks
+            WHERE user_id = $1 AND context IS NOT NULL AND status != 2
+            GROUP BY context
+            ORDER BY open_tasks DESC, context ASC
+        "#;
+        let counts = sqlx::query_as::<_, ContextCount>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(counts)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_started_by_user", skip_all)]
+    async fn find_started_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1
+            AND (start_date IS NULL OR start_date <= $2)
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    /// Case-insensitive substring match; unlike PostgreSQL there is no
+    /// full-text index to rank by
+    #[instrument(level = "debug", name = "TaskRepository::search_tasks", skip_all)]
+    async fn search_tasks(&self, user_id: &Uuid, search_term: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        // LIKE is case-insensitive for ASCII in SQLite
+        let query = r#"
+            SELECT * FROM tasks
+            WHERE user_id = $1
+            AND (
+                title LIKE '%' || $2 || '%'
+                OR description LIKE '%' || $2 || '%'
+            )
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+        "#;
+        let tasks = sqlx::query_as::<_, Task>(query)
+            .bind(user_id)
+            .bind(search_term)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::update", skip_all)]
+    async fn update(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        request: UpdateTaskRequest,
+    ) -> Result<Task, TaskRepositoryError> {
+        request
+            .validate()
+            .map_err(|e| TaskRepositoryError::ValidationError(e.into()))?;
+
+        // Fields left as None keep their current value; completed_at only
+        // changes when a new status is provided
+        let query = r#"
+            UPDATE tasks
+            SET title = COALESCE($3, title),
+                description = COALESCE($4, description),
+                status = COALESCE($5, status),
+                priority = COALESCE($6, priority),
+                due_date = COALESCE($7, due_date),
+                estimate_minutes = COALESCE($9, estimate_minutes),
+                client = COALESCE($10, client),
+                location = COALESCE($11, location),
+                latitude = COALESCE($12, latitude),
+                longitude = COALESCE($13, longitude),
+                start_date = COALESCE($14, start_date),
+                context = COALESCE($15, context),
+                completed_at = CASE
+                    WHEN $5 IS NULL THEN completed_at
+                    WHEN $5 = 2 THEN COALESCE(completed_at, $8)
+                    ELSE NULL
+                END,
+                updated_at = $8,
+                version = version + 1
+            WHERE id = $1 AND user_id = $2 AND ($16 IS NULL OR version = $16)
+            RETURNING id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version
+        "#;
+
+        let updated = sqlx::query_as::<_, Task>(query)
+            .bind(id)
+            .bind(user_id)
+            .bind(&request.title)
+            .bind(&request.description)
+            .bind(request.status)
+            .bind(request.priority)
+            .bind(request.due_date)
+            .bind(Utc::now())
+            .bind(request.estimate_minutes)
+            .bind(&request.client)
+            .bind(&request.location)
+            .bind(request.coordinates.map(|point| point.latitude))
+            .bind(request.coordinates.map(|point| point.longitude))
+            .bind(request.start_date)
+            .bind(request.context.as_deref().map(normalize_context))
+            .bind(request.version)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::from_write)?
+            .pop();
+
+        match updated {
+            Some(task) => Ok(task),
+            None if request.version.is_some() && self.find_by_id(id).await?.is_some_and(|task| task.user_id == *user_id) => {
+                Err(TaskRepositoryError::Conflict)
+            }
+            None => Err(TaskRepositoryError::NotFound),
+        }
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::delete", skip_all)]
+    async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, TaskRepositoryError> {
+        let result = sqlx::query("DELETE FROM tasks WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::delete_bulk", skip_all)]
+    async fn delete_bulk(&self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        Self::delete_many(&self.pool, ids, user_id).await
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::set_sort_order", skip_all)]
+    async fn set_sort_order(&self, id: &Uuid, user_id: &Uuid, sort_order: f64) -> Result<Task, TaskRepositoryError> {
+        let query = r#"
+            UPDATE tasks
+            SET sort_order = $3, version = version + 1
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version
+        "#;
+
+        let updated = sqlx::query_as::<_, Task>(query)
+            .bind(id)
+            .bind(user_id)
+            .bind(sort_order)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?
+            .pop();
+
+        updated.ok_or(TaskRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::set_pinned", skip_all)]
+    async fn set_pinned(&self, id: &Uuid, user_id: &Uuid, pinned: bool) -> Result<Task, TaskRepositoryError> {
+        let query = r#"
+            UPDATE tasks
+            SET pinned = $3, updated_at = $4, version = version + 1
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version
+        "#;
+
+        let updated = sqlx::query_as::<_, Task>(query)
+            .bind(id)
+            .bind(user_id)
+            .bind(pinned)
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?
+            .pop();
+
+        updated.ok_or(TaskRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::update_status_if", skip_all)]
+    async fn update_status_if(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        expected: TaskStatus,
+        status: TaskStatus,
+    ) -> Result<Option<Task>, TaskRepositoryError> {
+        Self::update_status_where(&self.pool, id, user_id, expected, status).await
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::update_status_many", skip_all)]
+    async fn update_status_many(
+        &self,
+        user_id: &Uuid,
+        expected: &[(Uuid, TaskStatus)],
+        status: TaskStatus,
+    ) -> Result<Vec<Task>, TaskRepositoryError> {
+        // No UNNEST here; one transaction keeps the batch atomic
+        let mut tx = self.pool.begin().await?;
+        let mut updated = Vec::with_capacity(expected.len());
+        for (id, expected_status) in expected {
+            if let Some(task) = Self::update_status_where(&mut *tx, id, user_id, *expected_status, status).await? {
+                updated.push(task);
+            }
+        }
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::renumber", skip_all)]
+    async fn renumber(&self, user_id: &Uuid, ordered_ids: &[Uuid]) -> Result<(), TaskRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        for (position, id) in ordered_ids.iter().enumerate() {
+            sqlx::query("UPDATE tasks SET sort_order = $3, version = version + 1 WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .bind((position + 1) as f64 * SORT_ORDER_STEP)
+                .execute(&mut *tx)
+                .await
+                .map_err(TaskRepositoryError::DatabaseError)?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::count_by_user", skip_all)]
+    async fn count_by_user(&self, user_id: &Uuid) -> Result<i64, TaskRepositoryError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_user_statistics", skip_all)]
+    async fn get_user_statistics(
+        &self,
+        user_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        filter: &StatisticsFilter,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                COUNT(*) as total_tasks,
+                COUNT(CASE WHEN status = 0 THEN 1 END) as pending_tasks,
+                COUNT(CASE WHEN status = 1 THEN 1 END) as in_progress_tasks,
+                COUNT(CASE WHEN status = 2 THEN 1 END) as completed_tasks,
+                COUNT(CASE WHEN due_date < $2 AND status != 2 THEN 1 END) as overdue_tasks,
+                COUNT(CASE WHEN updated_at < $3 AND status != 2 THEN 1 END) as stale_tasks
+            FROM tasks
+            WHERE user_id = $1
+              AND ($4 IS NULL OR created_at >= $4)
+              AND ($5 IS NULL OR created_at < $5)
+              AND ($6 IS NULL OR completed_at >= $6)
+              AND ($7 IS NULL OR completed_at < $7)
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(user_id)
+            .bind(due_before)
+            .bind(stale_before)
+            .bind(filter.created_after)
+            .bind(filter.created_before)
+            .bind(filter.completed_after)
+            .bind(filter.completed_before)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(Self::statistics(&row))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::count_grouped", skip_all)]
+    async fn count_grouped(
+        &self,
+        user_id: &Uuid,
+        group_by: TaskGrouping,
+        filter: &StatisticsFilter,
+    ) -> Result<HashMap<TaskGroup, i64>, TaskRepositoryError> {
+        let column = task_group_column(group_by);
+        let query = format!(
+            r#"
+            SELECT {column} AS grp, COUNT(*) AS count
+            FROM tasks
+            WHERE user_id = $1
+              AND ($2 IS NULL OR created_at >= $2)
+              AND ($3 IS NULL OR created_at < $3)
+              AND ($4 IS NULL OR completed_at >= $4)
+              AND ($5 IS NULL OR completed_at < $5)
+            GROUP BY {column}
+        "#
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(user_id)
+            .bind(filter.created_after)
+            .bind(filter.created_before)
+            .bind(filter.completed_after)
+            .bind(filter.completed_before)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        rows.iter()
+            .map(|row| -> Result<(TaskGroup, i64), sqlx::Error> {
+                let group = match group_by {
+                    TaskGrouping::Status => TaskGroup::Status(row.try_get("grp")?),
+                    TaskGrouping::Priority => TaskGroup::Priority(row.try_get("grp")?),
+                    TaskGrouping::Context => TaskGroup::Context(row.try_get("grp")?),
+                };
+                Ok((group, row.try_get("count")?))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(TaskRepositoryError::DatabaseError)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_throughput", skip_all)]
+    async fn get_throughput(&self, user_id: &Uuid, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Throughput, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                COUNT(CASE WHEN created_at >= $2 AND created_at < $3 THEN 1 END) AS created,
+                COUNT(CASE WHEN created_at >= $2 AND created_at < $3 AND status = 2 THEN 1 END) AS created_completed,
+                COUNT(CASE WHEN completed_at >= $2 AND completed_at < $3 THEN 1 END) AS completed,
+                AVG(CASE WHEN completed_at >= $2 AND completed_at < $3
+                    THEN (julianday(completed_at) - julianday(created_at)) * 86400.0 END) AS average_completion_seconds
+            FROM tasks
+            WHERE user_id = $1
+              AND (created_at >= $2 OR completed_at >= $2)
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(user_id)
+            .bind(since)
+            .bind(until)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(Throughput {
+            created: row.get::<i64, _>("created"),
+            created_completed: row.get::<i64, _>("created_completed"),
+            completed: row.get::<i64, _>("completed"),
+            average_completion_seconds: row.get::<Option<f64>, _>("average_completion_seconds"),
+        })
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::count_by_day", skip_all)]
+    async fn count_by_day(
+        &self,
+        user_id: &Uuid,
+        timestamp: TaskTimestamp,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Result<Vec<DayCount>, TaskRepositoryError> {
+        // SQLite only knows fixed offsets, which are wrong on one side of a
+        // DST change, so the instants are read and the days cut here
+        let column = task_timestamp_column(timestamp);
+        let query = format!("SELECT {column} FROM tasks WHERE user_id = $1 AND {column} >= $2 AND {column} < $3");
+
+        let timestamps = sqlx::query_scalar::<_, DateTime<Utc>>(&query)
+            .bind(user_id)
+            .bind(since)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(DayCount::from_timestamps(timestamps, since, until, timezone))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_workspace_statistics", skip_all)]
+    async fn get_workspace_statistics(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                COUNT(*) as total_tasks,
+                COUNT(CASE WHEN status = 0 THEN 1 END) as pending_tasks,
+                COUNT(CASE WHEN status = 1 THEN 1 END) as in_progress_tasks,
+                COUNT(CASE WHEN status = 2 THEN 1 END) as completed_tasks,
+                COUNT(CASE WHEN due_date < $2 AND status != 2 THEN 1 END) as overdue_tasks,
+                COUNT(CASE WHEN updated_at < $3 AND status != 2 THEN 1 END) as stale_tasks
+            FROM tasks
+            WHERE workspace_id = $1
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(workspace_id)
+            .bind(due_before)
+            .bind(stale_before)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(Self::statistics(&row))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_workspace_member_counts", skip_all)]
+    async fn get_workspace_member_counts(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        completed_since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<MemberTaskCounts>, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                user_id,
+                COUNT(*) as total_tasks,
+                COUNT(CASE WHEN status = 2 AND ($3 IS NULL OR completed_at >= $3) THEN 1 END) as completed_tasks,
+                COUNT(CASE WHEN due_date < $2 AND status != 2 THEN 1 END) as overdue_tasks
+            FROM tasks
+            WHERE workspace_id = $1
+            GROUP BY user_id
+        "#;
+
+        let counts = sqlx::query_as::<_, MemberTaskCounts>(query)
+            .bind(workspace_id)
+            .bind(due_before)
+            .bind(completed_since)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(counts)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_workspace_overdue_breakdown", skip_all)]
+    async fn get_workspace_overdue_breakdown(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+    ) -> Result<OverdueBreakdown, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                COUNT(CASE WHEN due_date >= $3 THEN 1 END) as up_to_week,
+                COUNT(CASE WHEN due_date < $3 AND due_date >= $4 THEN 1 END) as up_to_month,
+                COUNT(CASE WHEN due_date < $4 THEN 1 END) as older
+            FROM tasks
+            WHERE workspace_id = $1 AND due_date < $2 AND status != 2
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(workspace_id)
+            .bind(due_before)
+            .bind(due_before - chrono::Duration::days(OverdueBreakdown::WEEK_DAYS))
+            .bind(due_before - chrono::Duration::days(OverdueBreakdown::MONTH_DAYS))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(OverdueBreakdown {
+            up_to_week: row.get::<i64, _>("up_to_week"),
+            up_to_month: row.get::<i64, _>("up_to_month"),
+            older: row.get::<i64, _>("older"),
+        })
+    }
+}
@@ -0,0 +1,327 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::Utc;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use uuid::Uuid;
+
+use crate::database::repositories::user_repository::{UserRepository, UserRepositoryError};
+use crate::models::user::{StoreUserRequest, UpdateUserRequest, User, UsernameChange};
+
+/// SQLite implementation of UserRepository; like `SqliteTaskRepository`, it
+/// runs `RETURNING` statements with `fetch_all` so their writes commit at once
+pub struct SqliteUserRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteUserRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    async fn user_exists_by_id(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(exists)
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqliteUserRepository {
+    #[instrument(level = "debug", name = "UserRepository::store", skip_all)]
+    async fn store(&self, user: StoreUserRequest) -> Result<User, UserRepositoryError> {
+        if self.exists_by_username(&user.username).await? {
+            return Err(UserRepositoryError::UsernameExists {
+                username: user.username,
+            });
+        }
+
+        if self.exists_by_email(&user.email).await? {
+            return Err(UserRepositoryError::EmailExists { email: user.email });
+        }
+
+        let now = Utc::now();
+        let query = r#"
+            INSERT INTO users (id, username, email, password_hash, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(Uuid::new_v4())
+            .bind(&user.username)
+            .bind(&user.email)
+            .bind(&user.password_hash)
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create user: {}", e);
+                UserRepositoryError::DatabaseError(e)
+            })?
+            .pop()
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::put_copy", skip_all)]
+    async fn put_copy(&self, user: &User) -> Result<User, UserRepositoryError> {
+        let query = r#"
+            INSERT INTO users (id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE
+            SET username = excluded.username,
+                email = excluded.email,
+                password_hash = excluded.password_hash,
+                email_verified_at = excluded.email_verified_at,
+                is_active = excluded.is_active,
+                updated_at = excluded.updated_at
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(user.id)
+            .bind(&user.username)
+            .bind(&user.email)
+            .bind(&user.password_hash)
+            .bind(user.email_verified_at)
+            .bind(user.is_active)
+            .bind(user.created_at)
+            .bind(user.updated_at)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?
+            .pop()
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::find_by_id", skip_all)]
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError> {
+        let query = r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+            FROM users
+            WHERE id = $1
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::find_by_username", skip_all)]
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, UserRepositoryError> {
+        let query = r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+            FROM users
+            WHERE username = $1
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::find_by_email", skip_all)]
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError> {
+        let query = r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+            FROM users
+            WHERE email = $1
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::find_all", skip_all)]
+    async fn find_all(&self) -> Result<Vec<User>, UserRepositoryError> {
+        let query = r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+            FROM users
+            ORDER BY created_at, username
+        "#;
+
+        let users = sqlx::query_as::<_, User>(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(users)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::update", skip_all)]
+    async fn update(
+        &self,
+        id: &Uuid,
+        updates: UpdateUserRequest,
+    ) -> Result<User, UserRepositoryError> {
+        if !self.user_exists_by_id(id).await? {
+            return Err(UserRepositoryError::NotFound);
+        }
+
+        if updates.username.is_none() && updates.email.is_none() && updates.password_hash.is_none() {
+            return self
+                .find_by_id(id)
+                .await?
+                .ok_or(UserRepositoryError::NotFound);
+        }
+
+        let now = Utc::now();
+        let mut query = QueryBuilder::<Sqlite>::new("UPDATE users SET ");
+        let mut set = query.separated(", ");
+        if let Some(username) = &updates.username {
+            set.push("username = ").push_bind_unseparated(username);
+        }
+        if let Some(email) = &updates.email {
+            set.push("email = ").push_bind_unseparated(email);
+        }
+        if let Some(password_hash) = &updates.password_hash {
+            set.push("password_hash = ").push_bind_unseparated(password_hash);
+        }
+        set.push("updated_at = ").push_bind_unseparated(now);
+        query
+            .push(" WHERE id = ")
+            .push_bind(id)
+            .push(" RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at");
+
+        // The rename and its history entry are written together
+        let mut tx = self.pool.begin().await?;
+
+        let old_username: String = sqlx::query_scalar("SELECT username FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let user = query
+            .build_query_as::<User>()
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?
+            .pop()
+            .ok_or(UserRepositoryError::NotFound)?;
+
+        if user.username != old_username {
+            sqlx::query("INSERT INTO username_changes (id, user_id, old_username, new_username, changed_at) VALUES ($1, $2, $3, $4, $5)")
+                .bind(Uuid::new_v4())
+                .bind(id)
+                .bind(&old_username)
+                .bind(&user.username)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::find_username_changes", skip_all)]
+    async fn find_username_changes(&self, id: &Uuid) -> Result<Vec<UsernameChange>, UserRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, old_username, new_username, changed_at
+            FROM username_changes
+            WHERE user_id = $1
+            ORDER BY changed_at DESC
+        "#;
+
+        let changes = sqlx::query_as::<_, UsernameChange>(query)
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(changes)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::delete", skip_all)]
+    async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::mark_email_verified", skip_all)]
+    async fn mark_email_verified(&self, id: &Uuid) -> Result<User, UserRepositoryError> {
+        let query = r#"
+            UPDATE users
+            SET email_verified_at = COALESCE(email_verified_at, $2), updated_at = $2
+            WHERE id = $1
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(id)
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?
+            .pop();
+
+        user.ok_or(UserRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::set_active", skip_all)]
+    async fn set_active(&self, id: &Uuid, active: bool) -> Result<User, UserRepositoryError> {
+        let query = r#"
+            UPDATE users
+            SET is_active = $2, updated_at = $3
+            WHERE id = $1
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at
+        "#;
+
+        let user = sqlx::query_as::<_, User>(query)
+            .bind(id)
+            .bind(active)
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?
+            .pop();
+
+        user.ok_or(UserRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::exists_by_username", skip_all)]
+    async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)")
+            .bind(username)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(exists)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::exists_by_email", skip_all)]
+    async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(exists)
+    }
+}
@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::status::{CustomStatus, StatusTransition, StoreStatusRequest, CUSTOM_STATUS_CODE_START};
+
+#[derive(Error, Debug)]
+pub enum StatusRepositoryError {
+    #[error("Status already exists: {name}")]
+    NameExists { name: String },
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Status repository trait for per-user workflow definitions
+#[async_trait]
+pub trait StatusRepository: Send + Sync {
+    async fn store(&self, user_id: &Uuid, request: StoreStatusRequest) -> Result<CustomStatus, StatusRepositoryError>;
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<CustomStatus>, StatusRepositoryError>;
+    async fn delete(&self, user_id: &Uuid, code: i16) -> Result<bool, StatusRepositoryError>;
+    async fn count_tasks_with_status(&self, user_id: &Uuid, code: i16) -> Result<i64, StatusRepositoryError>;
+    async fn find_transitions(&self, user_id: &Uuid) -> Result<Vec<StatusTransition>, StatusRepositoryError>;
+    async fn add_transition(&self, user_id: &Uuid, transition: StatusTransition) -> Result<(), StatusRepositoryError>;
+    async fn remove_transition(&self, user_id: &Uuid, transition: StatusTransition) -> Result<bool, StatusRepositoryError>;
+}
+
+/// PostgreSQL implementation of StatusRepository
+pub struct PostgresStatusRepository {
+    pool: PgPool,
+}
+
+impl PostgresStatusRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl StatusRepository for PostgresStatusRepository {
+    #[instrument(level = "debug", name = "StatusRepository::store", skip_all)]
+    async fn store(&self, user_id: &Uuid, request: StoreStatusRequest) -> Result<CustomStatus, StatusRepositoryError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM statuses WHERE user_id = $1 AND LOWER(name) = LOWER($2))",
+        )
+        .bind(user_id)
+        .bind(&request.name)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if exists {
+            return Err(StatusRepositoryError::NameExists { name: request.name });
+        }
+
+        // Codes are allocated per user, after the highest one in use
+        let query = r#"
+            INSERT INTO statuses (user_id, code, name)
+            SELECT $1, COALESCE(MAX(code) + 1, $3), $2
+            FROM statuses
+            WHERE user_id = $1
+            RETURNING id, user_id, code, name, created_at
+        "#;
+
+        let status = sqlx::query_as::<_, CustomStatus>(query)
+            .bind(user_id)
+            .bind(request.name.trim())
+            .bind(CUSTOM_STATUS_CODE_START)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(StatusRepositoryError::DatabaseError)?;
+
+        Ok(status)
+    }
+
+    #[instrument(level = "debug", name = "StatusRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<CustomStatus>, StatusRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, code, name, created_at
+            FROM statuses
+            WHERE user_id = $1
+            ORDER BY code ASC
+        "#;
+
+        let statuses = sqlx::query_as::<_, CustomStatus>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(StatusRepositoryError::DatabaseError)?;
+
+        Ok(statuses)
+    }
+
+    #[instrument(level = "debug", name = "StatusRepository::delete", skip_all)]
+    async fn delete(&self, user_id: &Uuid, code: i16) -> Result<bool, StatusRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM status_transitions WHERE user_id = $1 AND (from_status = $2 OR to_status = $2)")
+            .bind(user_id)
+            .bind(code)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query("DELETE FROM statuses WHERE user_id = $1 AND code = $2")
+            .bind(user_id)
+            .bind(code)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "StatusRepository::count_tasks_with_status", skip_all)]
+    async fn count_tasks_with_status(&self, user_id: &Uuid, code: i16) -> Result<i64, StatusRepositoryError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE user_id = $1 AND status = $2")
+            .bind(user_id)
+            .bind(code)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    #[instrument(level = "debug", name = "StatusRepository::find_transitions", skip_all)]
+    async fn find_transitions(&self, user_id: &Uuid) -> Result<Vec<StatusTransition>, StatusRepositoryError> {
+        let query = r#"
+            SELECT from_status, to_status
+            FROM status_transitions
+            WHERE user_id = $1
+            ORDER BY from_status, to_status
+        "#;
+
+        let transitions = sqlx::query_as::<_, StatusTransition>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(StatusRepositoryError::DatabaseError)?;
+
+        Ok(transitions)
+    }
+
+    #[instrument(level = "debug", name = "StatusRepository::add_transition", skip_all)]
+    async fn add_transition(&self, user_id: &Uuid, transition: StatusTransition) -> Result<(), StatusRepositoryError> {
+        let query = r#"
+            INSERT INTO status_transitions (user_id, from_status, to_status)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+        "#;
+
+        sqlx::query(query)
+            .bind(user_id)
+            .bind(transition.from_status)
+            .bind(transition.to_status)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "StatusRepository::remove_transition", skip_all)]
+    async fn remove_transition(&self, user_id: &Uuid, transition: StatusTransition) -> Result<bool, StatusRepositoryError> {
+        let query = r#"
+            DELETE FROM status_transitions
+            WHERE user_id = $1 AND from_status = $2 AND to_status = $3
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(user_id)
+            .bind(transition.from_status)
+            .bind(transition.to_status)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
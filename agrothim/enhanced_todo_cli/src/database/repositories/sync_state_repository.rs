@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::sync::{SyncConflict, SyncState};
+
+#[derive(Error, Debug)]
+pub enum SyncStateRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Bookkeeping of local-first mode, kept in the local database next to the
+/// tasks it describes
+#[async_trait]
+pub trait SyncStateRepository: Send + Sync {
+    async fn find_by_user(&self, user_id: &Uuid) -> Result<Vec<SyncState>, SyncStateRepositoryError>;
+    /// Insert or replace the state of `state.task_id`
+    async fn save(&self, state: &SyncState) -> Result<(), SyncStateRepositoryError>;
+    async fn delete(&self, task_id: &Uuid) -> Result<(), SyncStateRepositoryError>;
+    async fn find_conflicts(&self, user_id: &Uuid) -> Result<Vec<SyncConflict>, SyncStateRepositoryError>;
+    async fn find_conflict(&self, task_id: &Uuid) -> Result<Option<SyncConflict>, SyncStateRepositoryError>;
+    /// Record a conflict; one already recorded for the task keeps its `detected_at`
+    async fn save_conflict(&self, conflict: &SyncConflict) -> Result<(), SyncStateRepositoryError>;
+    async fn delete_conflict(&self, task_id: &Uuid) -> Result<(), SyncStateRepositoryError>;
+}
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::task_event::{TaskEvent, TaskEventKind};
+
+#[derive(Error, Debug)]
+pub enum TaskEventRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Task event repository trait for the activity feed
+#[async_trait]
+pub trait TaskEventRepository: Send + Sync {
+    async fn store(
+        &self,
+        task_id: &Uuid,
+        actor_id: &Uuid,
+        kind: TaskEventKind,
+        subject_id: Option<&Uuid>,
+    ) -> Result<(), TaskEventRepositoryError>;
+    /// Events on tasks the user owns, shares a workspace for or had shared
+    /// with them, newest first
+    async fn find_visible_to(
+        &self,
+        user_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<TaskEvent>, TaskEventRepositoryError>;
+    /// Events on the tasks of a workspace, newest first
+    async fn find_by_workspace(
+        &self,
+        workspace_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<TaskEvent>, TaskEventRepositoryError>;
+    /// When the user last looked at their activity feed
+    async fn last_seen(&self, user_id: &Uuid) -> Result<Option<DateTime<Utc>>, TaskEventRepositoryError>;
+    async fn mark_seen(&self, user_id: &Uuid, seen_at: DateTime<Utc>) -> Result<(), TaskEventRepositoryError>;
+}
+
+/// PostgreSQL implementation of TaskEventRepository
+pub struct PostgresTaskEventRepository {
+    pool: PgPool,
+}
+
+impl PostgresTaskEventRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TaskEventRepository for PostgresTaskEventRepository {
+    #[instrument(level = "debug", name = "TaskEventRepository::store", skip_all)]
+    async fn store(
+        &self,
+        task_id: &Uuid,
+        actor_id: &Uuid,
+        kind: TaskEventKind,
+        subject_id: Option<&Uuid>,
+    ) -> Result<(), TaskEventRepositoryError> {
+        sqlx::query("INSERT INTO task_events (task_id, actor_id, kind, subject_id) VALUES ($1, $2, $3, $4)")
+            .bind(task_id)
+            .bind(actor_id)
+            .bind(kind.as_str())
+            .bind(subject_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "TaskEventRepository::find_visible_to", skip_all)]
+    async fn find_visible_to(
+        &self,
+        user_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<TaskEvent>, TaskEventRepositoryError> {
+        let query = r#"
+            SELECT e.id, e.task_id, t.title AS task_title, t.workspace_id, a.username AS actor,
+                   e.kind, s.username AS subject, e.created_at
+            FROM task_events e
+            JOIN tasks t ON t.id = e.task_id
+            JOIN users a ON a.id = e.actor_id
+            LEFT JOIN users s ON s.id = e.subject_id
+            WHERE (
+                t.user_id = $1
+                OR t.workspace_id IN (SELECT workspace_id FROM workspace_members WHERE user_id = $1)
+                OR t.id IN (SELECT task_id FROM task_shares WHERE user_id = $1)
+            )
+            AND ($2::TIMESTAMPTZ IS NULL OR e.created_at > $2)
+            ORDER BY e.created_at DESC
+            LIMIT $3
+        "#;
+
+        let events = sqlx::query_as::<_, TaskEvent>(query)
+            .bind(user_id)
+            .bind(since)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(events)
+    }
+
+    #[instrument(level = "debug", name = "TaskEventRepository::find_by_workspace", skip_all)]
+    async fn find_by_workspace(
+        &self,
+        workspace_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<TaskEvent>, TaskEventRepositoryError> {
+        let query = r#"
+            SELECT e.id, e.task_id, t.title AS task_title, t.workspace_id, a.username AS actor,
+                   e.kind, s.username AS subject, e.created_at
+            FROM task_events e
+            JOIN tasks t ON t.id = e.task_id
+            JOIN users a ON a.id = e.actor_id
+            LEFT JOIN users s ON s.id = e.subject_id
+            WHERE t.workspace_id = $1
+            AND ($2::TIMESTAMPTZ IS NULL OR e.created_at > $2)
+            ORDER BY e.created_at DESC
+            LIMIT $3
+        "#;
+
+        let events = sqlx::query_as::<_, TaskEvent>(query)
+            .bind(workspace_id)
+            .bind(since)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(events)
+    }
+
+    #[instrument(level = "debug", name = "TaskEventRepository::last_seen", skip_all)]
+    async fn last_seen(&self, user_id: &Uuid) -> Result<Option<DateTime<Utc>>, TaskEventRepositoryError> {
+        let seen_at = sqlx::query_scalar::<_, DateTime<Utc>>("SELECT seen_at FROM activity_views WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(seen_at)
+    }
+
+    #[instrument(level = "debug", name = "TaskEventRepository::mark_seen", skip_all)]
+    async fn mark_seen(&self, user_id: &Uuid, seen_at: DateTime<Utc>) -> Result<(), TaskEventRepositoryError> {
+        let query = r#"
+            INSERT INTO activity_views (user_id, seen_at)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET seen_at = GREATEST(activity_views.seen_at, EXCLUDED.seen_at)
+        "#;
+
+        sqlx::query(query).bind(user_id).bind(seen_at).execute(&self.pool).await?;
+        Ok(())
+    }
+}
@@ -1,30 +1,113 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::pin::Pin;
 use uuid::Uuid;
-use sqlx::{PgPool, Row};
+use sqlx::{PgExecutor, PgPool, Postgres, Row, Transaction};
 use thiserror::Error;
+use tokio_stream::{Stream, StreamExt};
 use validator::Validate;
 
-use crate::models::task::{StoreTaskRequest, Task, TaskStatus, TaskStatistics, UpdateTaskRequest};
+use crate::database::repositories::ApiClientError;
+use crate::database::Replica;
+use crate::models::task::{
+    normalize_context, ContextCount, Page, PageRequest, StatisticsFilter, StoreTaskRequest, Task, TaskGroup, TaskGrouping,
+    TaskPriority, TaskSort, TaskSortField, TaskStatus, TaskStatistics, UpdateTaskRequest, SORT_ORDER_STEP,
+};
+use crate::models::calendar::UserTimezone;
+use crate::models::report::{DayCount, TaskTimestamp, Throughput};
+use crate::models::workspace::{MemberTaskCounts, OverdueBreakdown};
 
 #[derive(Error, Debug)]
 pub enum TaskRepositoryError {
     #[error("Not found")]
     NotFound,
+    /// The task's version is not the one the update expected
+    #[error("Task was changed by someone else")]
+    Conflict,
     #[error("Validation error: {0}")]
     ValidationError(#[from] crate::models::task::TaskError),
     #[error("Database error: {0}")]
     DatabaseError(#[from] sqlx::Error),
+    #[error("API error: {0}")]
+    ApiError(#[source] ApiClientError),
+}
+
+/// Name of the CHECK (a trigger on SQLite) keeping a task from starting
+/// after it is due
+const START_BEFORE_DUE_CHECK: &str = "start_before_due_check";
+
+impl TaskRepositoryError {
+    /// A failed task write, as a validation error when the row broke the
+    /// start date rule
+    pub(crate) fn from_write(error: sqlx::Error) -> Self {
+        let breaks_start_rule = error
+            .as_database_error()
+            .is_some_and(|e| e.message().contains(START_BEFORE_DUE_CHECK));
+        if breaks_start_rule {
+            TaskRepositoryError::ValidationError(crate::models::task::TaskError::StartAfterDue)
+        } else {
+            TaskRepositoryError::DatabaseError(error)
+        }
+    }
+}
+
+/// Tasks read one row at a time, see `TaskRepository::stream_by_user_id`
+pub type TaskStream<'a> = Pin<Box<dyn Stream<Item = Result<Task, TaskRepositoryError>> + Send + 'a>>;
+
+/// Task writes that take effect together on `commit`; dropping the
+/// transaction without committing discards them
+#[async_trait]
+pub trait TaskTransaction: Send {
+    async fn store(&mut self, task: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError>;
+    /// Delete those of the tasks the user owns; the number deleted
+    async fn delete_bulk(&mut self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError>;
+    async fn commit(self: Box<Self>) -> Result<(), TaskRepositoryError>;
 }
 
 /// Task repository trait for data access operations
 #[async_trait]
 pub trait TaskRepository: Send + Sync {
     async fn store(&self, task: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError>;
+    /// Store all tasks in one transaction, numbered in the given order; none
+    /// is stored if any fails
+    async fn store_many(&self, tasks: Vec<StoreTaskRequest>, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Write a copy of a task read from another database, keeping its ID,
+    /// content and timestamps and replacing an earlier copy. Number and
+    /// version stay this database's own: a new copy gets the next number and
+    /// every write bumps the version. `NotFound` if another user owns the ID.
+    async fn put_copy(&self, task: &Task) -> Result<Task, TaskRepositoryError>;
+    /// Start a transaction, for writes that must not be applied partially
+    async fn begin(&self) -> Result<Box<dyn TaskTransaction + '_>, TaskRepositoryError>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>, TaskRepositoryError>;
     async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError>;
-    async fn find_overdue_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// The user's tasks in `find_by_user_id` order, fetched as they are
+    /// consumed rather than loaded all at once
+    fn stream_by_user_id(&self, user_id: &Uuid) -> TaskStream<'_>;
+    /// One page of the user's tasks in `page.sort` order, the same order as
+    /// `TaskSort::compare`; `NotFound` if the cursor is not one of their tasks
+    async fn find_by_user_id_paginated(&self, user_id: &Uuid, page: PageRequest) -> Result<Page<Task>, TaskRepositoryError>;
+    /// Tasks with any of the given IDs, whoever owns them
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Task>, TaskRepositoryError>;
+    async fn find_by_number(&self, user_id: &Uuid, number: i32) -> Result<Option<Task>, TaskRepositoryError>;
+    /// Tasks of every member shared in the workspace
+    async fn find_by_workspace(&self, workspace_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Tasks whose UUID starts with `prefix`, at most `limit` of them
+    async fn find_by_id_prefix(&self, user_id: &Uuid, prefix: &str, limit: i64) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Open tasks due before `due_before`, normally the start of the user's local day, pinned
+    /// first and then the longest overdue
+    async fn find_overdue_by_user(&self, user_id: &Uuid, due_before: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Tasks of any status due in `[from, until)`
+    async fn find_due_between(&self, user_id: &Uuid, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError>;
     async fn find_by_status(&self, user_id: &Uuid, status: TaskStatus) -> Result<Vec<Task>, TaskRepositoryError>;
+    async fn find_by_context(&self, user_id: &Uuid, context: &str) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Number of open tasks per context, tasks without a context excluded
+    async fn count_open_by_context(&self, user_id: &Uuid) -> Result<Vec<ContextCount>, TaskRepositoryError>;
+    /// Tasks without a start date or whose start date has passed
+    async fn find_started_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Tasks whose title or description match `search_term`, best matches
+    /// first on backends that can rank them
     async fn search_tasks(&self, user_id: &Uuid, search_term: &str) -> Result<Vec<Task>, TaskRepositoryError>;
     async fn update(
         &self,
@@ -33,56 +116,340 @@ pub trait TaskRepository: Send + Sync {
         request: UpdateTaskRequest,
     ) -> Result<Task, TaskRepositoryError>;
     async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, TaskRepositoryError>;
+    /// Delete those of the tasks the user owns in one statement; the number deleted
+    async fn delete_bulk(&self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError>;
+    async fn set_sort_order(&self, id: &Uuid, user_id: &Uuid, sort_order: f64) -> Result<Task, TaskRepositoryError>;
+    async fn set_pinned(&self, id: &Uuid, user_id: &Uuid, pinned: bool) -> Result<Task, TaskRepositoryError>;
+    /// Change the status only if it is still `expected`; `None` when the task
+    /// does not exist or its status was changed in the meantime
+    async fn update_status_if(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        expected: TaskStatus,
+        status: TaskStatus,
+    ) -> Result<Option<Task>, TaskRepositoryError>;
+    /// `update_status_if` for many tasks in one statement: each task listed
+    /// with the status it is expected to still have is moved to `status`.
+    /// Returns the updated tasks; the others were missing or changed meanwhile.
+    async fn update_status_many(
+        &self,
+        user_id: &Uuid,
+        expected: &[(Uuid, TaskStatus)],
+        status: TaskStatus,
+    ) -> Result<Vec<Task>, TaskRepositoryError>;
+    /// Rank the given tasks in order, `SORT_ORDER_STEP` apart
+    async fn renumber(&self, user_id: &Uuid, ordered_ids: &[Uuid]) -> Result<(), TaskRepositoryError>;
     #[allow(dead_code)]
     async fn count_by_user(&self, user_id: &Uuid) -> Result<i64, TaskRepositoryError>;
+    /// Task counts per status, plus open tasks due before `due_before` and
+    /// open tasks not updated since `stale_before`, over the tasks `filter` matches
+    async fn get_user_statistics(
+        &self,
+        user_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        filter: &StatisticsFilter,
+    ) -> Result<TaskStatistics, TaskRepositoryError>;
+    /// Number of the user's tasks per status, priority or context, counted by
+    /// one GROUP BY over the tasks `filter` matches
+    async fn count_grouped(
+        &self,
+        user_id: &Uuid,
+        group_by: TaskGrouping,
+        filter: &StatisticsFilter,
+    ) -> Result<HashMap<TaskGroup, i64>, TaskRepositoryError>;
+    /// Tasks created and tasks completed in `[since, until)`, with the mean
+    /// time to completion, counted by one query
+    async fn get_throughput(&self, user_id: &Uuid, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Throughput, TaskRepositoryError>;
+    /// Tasks per day by the date `timestamp` falls on in `[since, until)`,
+    /// with days cut in `timezone`; days without tasks are left out
+    async fn count_by_day(
+        &self,
+        user_id: &Uuid,
+        timestamp: TaskTimestamp,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Result<Vec<DayCount>, TaskRepositoryError>;
+    /// Like `get_user_statistics`, over the tasks of all members shared in the workspace
+    async fn get_workspace_statistics(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<TaskStatistics, TaskRepositoryError>;
+    /// Task counts per creator in the workspace; only completions since `completed_since` are counted
+    async fn get_workspace_member_counts(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        completed_since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<MemberTaskCounts>, TaskRepositoryError>;
+    /// Open workspace tasks due before `due_before`, by how long ago they were due
+    async fn get_workspace_overdue_breakdown(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+    ) -> Result<OverdueBreakdown, TaskRepositoryError>;
+}
+
+/// ORDER BY terms sorting tasks like `TaskSort::compare`, given the
+/// expression comparing titles case-insensitively on the backend
+pub(crate) fn task_order_by(sort: TaskSort, title: &str) -> String {
+    let (asc, desc) = if sort.descending { ("DESC", "ASC") } else { ("ASC", "DESC") };
+    let terms = match sort.field {
+        TaskSortField::Manual => {
+            let nulls = if sort.descending { "FIRST" } else { "LAST" };
+            format!("pinned {desc}, sort_order {asc} NULLS {nulls}, updated_at {desc}")
+        }
+        TaskSortField::Created => format!("created_at {asc}"),
+        TaskSortField::Updated => format!("updated_at {asc}"),
+        // Tasks without a due date stay last in both directions
+        TaskSortField::Due => format!("due_date {asc} NULLS LAST"),
+        TaskSortField::Priority => format!("priority {asc}"),
+        TaskSortField::Title => format!("{title} {asc}"),
+    };
+    format!("{terms}, id ASC")
+}
+
+/// Column `count_grouped` groups by
+pub(crate) fn task_group_column(group_by: TaskGrouping) -> &'static str {
+    match group_by {
+        TaskGrouping::Status => "status",
+        TaskGrouping::Priority => "priority",
+        TaskGrouping::Context => "context",
+    }
+}
+
+/// Column `count_by_day` buckets by
+pub(crate) fn task_timestamp_column(timestamp: TaskTimestamp) -> &'static str {
+    match timestamp {
+        TaskTimestamp::Created => "created_at",
+        TaskTimestamp::Completed => "completed_at",
+    }
+}
+
+/// `sqlx::query_as!` for statements returning `Task` rows, checked against
+/// the schema at compile time. `select_tasks!` takes the part after
+/// `FROM tasks`, `returning_tasks!` a statement up to its RETURNING clause;
+/// both add the full column list.
+macro_rules! select_tasks {
+    ($rest:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_as!(
+            Task,
+            "SELECT id, number, title, description, status AS \"status: TaskStatus\", priority AS \"priority: TaskPriority\", due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version FROM tasks " + $rest
+            $(, $arg)*
+        )
+    };
+}
+
+macro_rules! returning_tasks {
+    ($statement:literal $(, $arg:expr)* $(,)?) => {
+        sqlx::query_as!(
+            Task,
+            $statement + " RETURNING id, number, title, description, status AS \"status: TaskStatus\", priority AS \"priority: TaskPriority\", due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at, version"
+            $(, $arg)*
+        )
+    };
 }
 
 /// PostgreSQL implementation of TaskRepository
 pub struct PostgresTaskRepository {
     pool: PgPool,
+    replica: Option<Replica>,
 }
 
 impl PostgresTaskRepository {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self { pool, replica: None }
+    }
+
+    /// Run list, search, count and statistics queries on `replica` while it
+    /// is up. Single-task lookups stay on the primary: they usually precede
+    /// a write, which must not act on a lagging copy.
+    pub fn with_replica(mut self, replica: Option<Replica>) -> Self {
+        self.replica = replica;
+        self
+    }
+
+    fn read_pool(&self) -> &PgPool {
+        self.replica.as_ref().and_then(Replica::pool).unwrap_or(&self.pool)
+    }
+
+    /// Insert a task under the user's next number. Taking it from the user's
+    /// counter row locks that row, so concurrent creates wait for each other
+    /// instead of colliding, and numbers of deleted tasks are never reused.
+    async fn insert<'e>(executor: impl PgExecutor<'e>, task: &Task) -> Result<Task, TaskRepositoryError> {
+        let stored_task = returning_tasks!(
+            r#"
+            WITH counter AS (
+                UPDATE users SET next_task_number = next_task_number + 1
+                WHERE id = $15
+                RETURNING next_task_number - 1 AS number
+            )
+            INSERT INTO tasks (id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at)
+            VALUES ($1, (SELECT number FROM counter), $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            "#,
+            task.id,
+            &task.title,
+            task.description.as_deref(),
+            i16::from(task.status),
+            i16::from(task.priority),
+            task.due_date,
+            task.start_date,
+            task.completed_at,
+            task.estimate_minutes,
+            task.client.as_deref(),
+            task.context.as_deref(),
+            task.location.as_deref(),
+            task.latitude,
+            task.longitude,
+            task.user_id,
+            task.workspace_id,
+            task.created_at,
+            task.updated_at,
+        )
+        .fetch_one(executor)
+        .await
+        .map_err(TaskRepositoryError::from_write)?;
+
+        Ok(stored_task)
+    }
+
+    async fn delete_many<'e>(executor: impl PgExecutor<'e>, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        let query = r#"
+            DELETE FROM tasks WHERE id = ANY($1) AND user_id = $2
+        "#;
+        let result = sqlx::query(query)
+            .bind(ids)
+            .bind(user_id)
+            .execute(executor)
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// `TaskTransaction` on a PostgreSQL transaction
+pub struct PostgresTaskTransaction {
+    tx: Transaction<'static, Postgres>,
+}
+
+#[async_trait]
+impl TaskTransaction for PostgresTaskTransaction {
+    async fn store(&mut self, request: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        let task = Task::new(request, *user_id)?;
+        PostgresTaskRepository::insert(&mut *self.tx, &task).await
+    }
+
+    async fn delete_bulk(&mut self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        PostgresTaskRepository::delete_many(&mut *self.tx, ids, user_id).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), TaskRepositoryError> {
+        self.tx.commit().await?;
+        Ok(())
     }
 }
 
 #[async_trait]
 impl TaskRepository for PostgresTaskRepository {
+    #[instrument(level = "debug", name = "TaskRepository::store", skip_all)]
     async fn store(&self, request: StoreTaskRequest, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
         let task = Task::new(request, *user_id)?;
+        Self::insert(&self.pool, &task).await
+    }
 
-        let query = r#"
-            INSERT INTO tasks (id, title, description, status, priority, due_date, completed_at, user_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-            RETURNING id, title, description, status, priority, due_date, completed_at, user_id, created_at, updated_at
-        "#;
-        
-        let stored_task = sqlx::query_as::<_, Task>(query)
-            .bind(&task.id)
-            .bind(&task.title)
-            .bind(&task.description)
-            .bind(&task.status)
-            .bind(&task.priority)
-            .bind(&task.due_date)
-            .bind(&task.completed_at)
-            .bind(&task.user_id)
-            .bind(&task.created_at)
-            .bind(&task.updated_at)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(TaskRepositoryError::DatabaseError)?;
+    #[instrument(level = "debug", name = "TaskRepository::store_many", skip_all)]
+    async fn store_many(&self, requests: Vec<StoreTaskRequest>, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = requests
+            .into_iter()
+            .map(|request| Task::new(request, *user_id))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(stored_task)
+        let mut tx = self.pool.begin().await?;
+        let mut stored = Vec::with_capacity(tasks.len());
+        for task in &tasks {
+            stored.push(Self::insert(&mut *tx, task).await?);
+        }
+        tx.commit().await?;
+
+        Ok(stored)
     }
 
+    #[instrument(level = "debug", name = "TaskRepository::put_copy", skip_all)]
+    async fn put_copy(&self, task: &Task) -> Result<Task, TaskRepositoryError> {
+        // Only a new copy takes a number; an existing one proposes its own,
+        // since NOT NULL is checked before the conflict is
+        let stored = returning_tasks!(
+            r#"
+            WITH counter AS (
+                UPDATE users SET next_task_number = next_task_number + 1
+                WHERE id = $17 AND NOT EXISTS (SELECT 1 FROM tasks WHERE id = $1)
+                RETURNING next_task_number - 1 AS number
+            )
+            INSERT INTO tasks (id, number, title, description, status, priority, due_date, start_date, completed_at, estimate_minutes, sort_order, pinned, client, context, location, latitude, longitude, user_id, workspace_id, created_at, updated_at)
+            VALUES ($1, COALESCE((SELECT number FROM tasks WHERE id = $1), (SELECT number FROM counter)), $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            ON CONFLICT (id) DO UPDATE
+            SET title = EXCLUDED.title,
+                description = EXCLUDED.description,
+                status = EXCLUDED.status,
+                priority = EXCLUDED.priority,
+                due_date = EXCLUDED.due_date,
+                start_date = EXCLUDED.start_date,
+                completed_at = EXCLUDED.completed_at,
+                estimate_minutes = EXCLUDED.estimate_minutes,
+                sort_order = EXCLUDED.sort_order,
+                pinned = EXCLUDED.pinned,
+                client = EXCLUDED.client,
+                context = EXCLUDED.context,
+                location = EXCLUDED.location,
+                latitude = EXCLUDED.latitude,
+                longitude = EXCLUDED.longitude,
+                workspace_id = EXCLUDED.workspace_id,
+                updated_at = EXCLUDED.updated_at,
+                version = tasks.version + 1
+            WHERE tasks.user_id = EXCLUDED.user_id
+            "#,
+            task.id,
+            &task.title,
+            task.description.as_deref(),
+            i16::from(task.status),
+            i16::from(task.priority),
+            task.due_date,
+            task.start_date,
+            task.completed_at,
+            task.estimate_minutes,
+            task.sort_order,
+            task.pinned,
+            task.client.as_deref(),
+            task.context.as_deref(),
+            task.location.as_deref(),
+            task.latitude,
+            task.longitude,
+            task.user_id,
+            task.workspace_id,
+            task.created_at,
+            task.updated_at,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::from_write)?;
+
+        stored.ok_or(TaskRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::begin", skip_all)]
+    async fn begin(&self) -> Result<Box<dyn TaskTransaction + '_>, TaskRepositoryError> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(PostgresTaskTransaction { tx }))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_id", skip_all)]
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>, TaskRepositoryError> {
-        let query = r#"
-            SELECT * FROM tasks WHERE id = $1
-        "#;
-        let task = sqlx::query_as::<_, Task>(query)
-            .bind(id)
+        let task = select_tasks!("WHERE id = $1", id)
             .fetch_optional(&self.pool)
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
@@ -90,74 +457,264 @@ impl TaskRepository for PostgresTaskRepository {
         Ok(task)
     }
 
+    #[instrument(level = "debug", name = "TaskRepository::find_by_user_id", skip_all)]
     async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
-        let query = r#"
-            SELECT * FROM tasks 
-            WHERE user_id = $1 
-            ORDER BY updated_at DESC
-        "#;
-        let tasks = sqlx::query_as::<_, Task>(query)
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            user_id,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    fn stream_by_user_id(&self, user_id: &Uuid) -> TaskStream<'_> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            *user_id,
+        )
+        .fetch(self.read_pool())
+        .map(|task| task.map_err(TaskRepositoryError::DatabaseError));
+        Box::pin(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_user_id_paginated", skip_all)]
+    async fn find_by_user_id_paginated(&self, user_id: &Uuid, page: PageRequest) -> Result<Page<Task>, TaskRepositoryError> {
+        let ordered = format!(
+            "SELECT *, ROW_NUMBER() OVER (ORDER BY {}) AS position FROM tasks WHERE user_id = $1",
+            task_order_by(page.sort, r#"lower(title) COLLATE "C""#)
+        );
+
+        let after: i64 = match page.after {
+            Some(cursor) => sqlx::query_scalar(&format!("SELECT position FROM ({ordered}) ordered WHERE id = $2"))
+                .bind(user_id)
+                .bind(cursor)
+                .fetch_optional(self.read_pool())
+                .await
+                .map_err(TaskRepositoryError::DatabaseError)?
+                .ok_or(TaskRepositoryError::NotFound)?,
+            None => 0,
+        };
+
+        let query = format!("SELECT * FROM ({ordered}) ordered WHERE position > $2 ORDER BY position LIMIT $3 OFFSET $4");
+        let tasks = sqlx::query_as::<_, Task>(&query)
             .bind(user_id)
-            .fetch_all(&self.pool)
+            .bind(after)
+            .bind(page.limit.map(|limit| limit as i64))
+            .bind(page.offset as i64)
+            .fetch_all(self.read_pool())
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
+        let total = self.count_by_user(user_id).await?;
+        Ok(Page::new(tasks, after as usize + page.offset, total))
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_ids", skip_all)]
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE id = ANY($1)
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            ids,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
         Ok(tasks)
     }
 
-    async fn find_overdue_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
-        let query = r#"
-            SELECT * FROM tasks 
-            WHERE user_id = $1 
-            AND due_date < NOW() 
-            AND status != 2
-            ORDER BY due_date ASC
-        "#;
-        let tasks = sqlx::query_as::<_, Task>(query)
-            .bind(user_id)
-            .fetch_all(&self.pool)
+    #[instrument(level = "debug", name = "TaskRepository::find_by_workspace", skip_all)]
+    async fn find_by_workspace(&self, workspace_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE workspace_id = $1
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            workspace_id,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_number", skip_all)]
+    async fn find_by_number(&self, user_id: &Uuid, number: i32) -> Result<Option<Task>, TaskRepositoryError> {
+        let task = select_tasks!("WHERE user_id = $1 AND number = $2", user_id, number)
+            .fetch_optional(&self.pool)
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
+        Ok(task)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_id_prefix", skip_all)]
+    async fn find_by_id_prefix(&self, user_id: &Uuid, prefix: &str, limit: i64) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1 AND id::TEXT LIKE $2 || '%'
+            ORDER BY created_at
+            LIMIT $3
+            "#,
+            user_id,
+            prefix,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
         Ok(tasks)
     }
 
-    async fn find_by_status(&self, user_id: &Uuid, status: TaskStatus) -> Result<Vec<Task>, TaskRepositoryError> {
+    #[instrument(level = "debug", name = "TaskRepository::find_overdue_by_user", skip_all)]
+    async fn find_overdue_by_user(&self, user_id: &Uuid, due_before: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1
+            AND due_date < $2
+            AND status != 2
+            ORDER BY pinned DESC, due_date ASC
+            "#,
+            user_id,
+            due_before,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_due_between", skip_all)]
+    async fn find_due_between(&self, user_id: &Uuid, from: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1
+            AND due_date >= $2
+            AND due_date < $3
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            user_id,
+            from,
+            until,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_context", skip_all)]
+    async fn find_by_context(&self, user_id: &Uuid, context: &str) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1 AND context = $2
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            user_id,
+            normalize_context(context),
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::count_open_by_context", skip_all)]
+    async fn count_open_by_context(&self, user_id: &Uuid) -> Result<Vec<ContextCount>, TaskRepositoryError> {
         let query = r#"
-            SELECT * FROM tasks 
-            WHERE user_id = $1 AND status = $2 
-            ORDER BY updated_at DESC
+            SELECT context, COUNT(*) AS open_tasks
+            FROM tasks
+            WHERE user_id = $1 AND context IS NOT NULL AND status != 2
+            GROUP BY context
+            ORDER BY open_tasks DESC, context ASC
         "#;
-        let tasks = sqlx::query_as::<_, Task>(query)
+        let counts = sqlx::query_as::<_, ContextCount>(query)
             .bind(user_id)
-            .bind(status)
-            .fetch_all(&self.pool)
+            .fetch_all(self.read_pool())
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
+        Ok(counts)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_started_by_user", skip_all)]
+    async fn find_started_by_user(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1
+            AND (start_date IS NULL OR start_date <= NOW())
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            user_id,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(tasks)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::find_by_status", skip_all)]
+    async fn find_by_status(&self, user_id: &Uuid, status: TaskStatus) -> Result<Vec<Task>, TaskRepositoryError> {
+        let tasks = select_tasks!(
+            r#"
+            WHERE user_id = $1 AND status = $2
+            ORDER BY pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            user_id,
+            i16::from(status),
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
         Ok(tasks)
     }
 
+    /// Full-text search, best matches first. A term that yields no search
+    /// words (only stop words or punctuation) falls back to a substring match.
+    #[instrument(level = "debug", name = "TaskRepository::search_tasks", skip_all)]
     async fn search_tasks(&self, user_id: &Uuid, search_term: &str) -> Result<Vec<Task>, TaskRepositoryError> {
-        let query = r#"
-            SELECT * FROM tasks 
-            WHERE user_id = $1 
+        let tasks = select_tasks!(
+            r#"
+            , websearch_to_tsquery('english', $2) AS query
+            WHERE user_id = $1
             AND (
-                title ILIKE '%' || $2 || '%' 
-                OR description ILIKE '%' || $2 || '%'
+                search_vector @@ query
+                OR (
+                    numnode(query) = 0
+                    AND (title ILIKE '%' || $2 || '%' OR description ILIKE '%' || $2 || '%')
+                )
             )
-            ORDER BY updated_at DESC
-        "#;
-        let tasks = sqlx::query_as::<_, Task>(query)
-            .bind(user_id)
-            .bind(search_term)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(TaskRepositoryError::DatabaseError)?;
+            ORDER BY ts_rank(search_vector, query) DESC, pinned DESC, sort_order ASC NULLS LAST, updated_at DESC
+            "#,
+            user_id,
+            search_term,
+        )
+        .fetch_all(self.read_pool())
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
 
         Ok(tasks)
     }
 
+    #[instrument(level = "debug", name = "TaskRepository::update", skip_all)]
     async fn update(
         &self,
         id: &Uuid,
@@ -176,134 +733,409 @@ impl TaskRepository for PostgresTaskRepository {
             None
         };
 
-        let query = r#"
+        // Fields left as None keep their current value; completed_at only
+        // changes when a new status is provided
+        let updated = returning_tasks!(
+            r#"
             UPDATE tasks
-            SET title = $3,
-                description = $4,
-                status = $5,
-                priority = $6,
-                due_date = $7,
-                completed_at = $8,
-                updated_at = NOW()
-            WHERE id = $1 AND user_id = $2
-            RETURNING id, title, description, status, priority, due_date, completed_at, user_id, created_at, updated_at
-        "#;
+            SET title = COALESCE($3, title),
+                description = COALESCE($4, description),
+                status = COALESCE($5, status),
+                priority = COALESCE($6, priority),
+                due_date = COALESCE($7, due_date),
+                estimate_minutes = COALESCE($9, estimate_minutes),
+                client = COALESCE($10, client),
+                location = COALESCE($11, location),
+                latitude = COALESCE($12, latitude),
+                longitude = COALESCE($13, longitude),
+                start_date = COALESCE($14, start_date),
+                context = COALESCE($15, context),
+                completed_at = CASE
+                    WHEN $5::SMALLINT IS NULL THEN completed_at
+                    WHEN $5::SMALLINT = 2 THEN COALESCE(completed_at, $8)
+                    ELSE NULL
+                END,
+                updated_at = NOW(),
+                version = version + 1
+            WHERE id = $1 AND user_id = $2 AND ($16::INTEGER IS NULL OR version = $16)
+            "#,
+            id,
+            user_id,
+            request.title.as_deref(),
+            request.description.as_deref(),
+            request.status.map(i16::from),
+            request.priority.map(i16::from),
+            request.due_date,
+            completed_at,
+            request.estimate_minutes,
+            request.client.as_deref(),
+            request.location.as_deref(),
+            request.coordinates.map(|point| point.latitude),
+            request.coordinates.map(|point| point.longitude),
+            request.start_date,
+            request.context.as_deref().map(normalize_context),
+            request.version,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::from_write)?;
+
+        match updated {
+            Some(task) => Ok(task),
+            None if request.version.is_some() && self.find_by_id(id).await?.is_some_and(|task| task.user_id == *user_id) => {
+                Err(TaskRepositoryError::Conflict)
+            }
+            None => Err(TaskRepositoryError::NotFound),
+        }
+    }
 
-        let updated = sqlx::query_as::<_, Task>(query)
+    #[instrument(level = "debug", name = "TaskRepository::delete", skip_all)]
+    async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, TaskRepositoryError> {
+        let query = r#"
+            DELETE FROM tasks WHERE id = $1 AND user_id = $2
+        "#;
+        let result = sqlx::query(query)
             .bind(id)
             .bind(user_id)
-            .bind(&request.title)
-            .bind(&request.description)
-            .bind(&request.status)
-            .bind(&request.priority)
-            .bind(&request.due_date)
-            .bind(&completed_at)
-            .fetch_optional(&self.pool)
+            .execute(&self.pool)
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::delete_bulk", skip_all)]
+    async fn delete_bulk(&self, ids: &[Uuid], user_id: &Uuid) -> Result<u64, TaskRepositoryError> {
+        Self::delete_many(&self.pool, ids, user_id).await
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::set_sort_order", skip_all)]
+    async fn set_sort_order(&self, id: &Uuid, user_id: &Uuid, sort_order: f64) -> Result<Task, TaskRepositoryError> {
+        let updated = returning_tasks!(
+            r#"
+            UPDATE tasks
+            SET sort_order = $3, version = version + 1
+            WHERE id = $1 AND user_id = $2
+            "#,
+            id,
+            user_id,
+            sort_order,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
         updated.ok_or(TaskRepositoryError::NotFound)
     }
 
-    async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, TaskRepositoryError> {
+    #[instrument(level = "debug", name = "TaskRepository::set_pinned", skip_all)]
+    async fn set_pinned(&self, id: &Uuid, user_id: &Uuid, pinned: bool) -> Result<Task, TaskRepositoryError> {
+        let updated = returning_tasks!(
+            r#"
+            UPDATE tasks
+            SET pinned = $3, updated_at = NOW(), version = version + 1
+            WHERE id = $1 AND user_id = $2
+            "#,
+            id,
+            user_id,
+            pinned,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        updated.ok_or(TaskRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::update_status_if", skip_all)]
+    async fn update_status_if(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        expected: TaskStatus,
+        status: TaskStatus,
+    ) -> Result<Option<Task>, TaskRepositoryError> {
+        let updated = returning_tasks!(
+            r#"
+            UPDATE tasks
+            SET status = $4,
+                completed_at = CASE WHEN $4::SMALLINT = 2 THEN COALESCE(completed_at, NOW()) ELSE NULL END,
+                updated_at = NOW(),
+                version = version + 1
+            WHERE id = $1 AND user_id = $2 AND status = $3
+            "#,
+            id,
+            user_id,
+            i16::from(expected),
+            i16::from(status),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(updated)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::update_status_many", skip_all)]
+    async fn update_status_many(
+        &self,
+        user_id: &Uuid,
+        expected: &[(Uuid, TaskStatus)],
+        status: TaskStatus,
+    ) -> Result<Vec<Task>, TaskRepositoryError> {
+        let ids: Vec<Uuid> = expected.iter().map(|(id, _)| *id).collect();
+        let statuses: Vec<i16> = expected.iter().map(|(_, status)| i16::from(*status)).collect();
+        // `expected` has columns named like those of `tasks`, so the
+        // RETURNING list is qualified and `returning_tasks!` does not fit
+        let updated = sqlx::query_as!(
+            Task,
+            r#"
+            UPDATE tasks
+            SET status = $4,
+                completed_at = CASE WHEN $4::SMALLINT = 2 THEN COALESCE(tasks.completed_at, NOW()) ELSE NULL END,
+                updated_at = NOW(),
+                version = tasks.version + 1
+            FROM UNNEST($2::UUID[], $3::SMALLINT[]) AS expected(id, status)
+            WHERE tasks.id = expected.id AND tasks.user_id = $1 AND tasks.status = expected.status
+            RETURNING tasks.id, tasks.number, tasks.title, tasks.description, tasks.status AS "status: TaskStatus", tasks.priority AS "priority: TaskPriority", tasks.due_date, tasks.start_date, tasks.completed_at, tasks.estimate_minutes, tasks.sort_order, tasks.pinned, tasks.client, tasks.context, tasks.location, tasks.latitude, tasks.longitude, tasks.user_id, tasks.workspace_id, tasks.created_at, tasks.updated_at, tasks.version
+            "#,
+            user_id,
+            &ids,
+            &statuses,
+            i16::from(status),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(updated)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::renumber", skip_all)]
+    async fn renumber(&self, user_id: &Uuid, ordered_ids: &[Uuid]) -> Result<(), TaskRepositoryError> {
         let query = r#"
-            DELETE FROM tasks WHERE id = $1 AND user_id = $2
+            UPDATE tasks
+            SET sort_order = ranked.position * $3, version = tasks.version + 1
+            FROM UNNEST($2::UUID[]) WITH ORDINALITY AS ranked(id, position)
+            WHERE tasks.id = ranked.id AND tasks.user_id = $1
         "#;
-        let result = sqlx::query(query)
-            .bind(id)
+
+        sqlx::query(query)
             .bind(user_id)
+            .bind(ordered_ids)
+            .bind(SORT_ORDER_STEP)
             .execute(&self.pool)
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(())
     }
 
     #[allow(dead_code)]
+    #[instrument(level = "debug", name = "TaskRepository::count_by_user", skip_all)]
     async fn count_by_user(&self, user_id: &Uuid) -> Result<i64, TaskRepositoryError> {
         let query = r#"
             SELECT COUNT(*) FROM tasks WHERE user_id = $1
         "#;
         let count: i64 = sqlx::query_scalar(query)
             .bind(user_id)
-            .fetch_one(&self.pool)
+            .fetch_one(self.read_pool())
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
         Ok(count)
     }
-}
 
-impl PostgresTaskRepository {
-    // allow dead_code
-    #[allow(dead_code)]
-    async fn task_exists_by_id(&self, id: &Uuid) -> Result<bool, TaskRepositoryError> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE id = $1")
-            .bind(id)
-            .fetch_one(&self.pool)
-            .await?;
-        Ok(count > 0)
-    }
-
-    // Business Logic: Mark task as completed with completed_at timestamp
-    #[allow(dead_code)]
-    pub async fn mark_complete(&self, id: &Uuid, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+    #[instrument(level = "debug", name = "TaskRepository::get_user_statistics", skip_all)]
+    async fn get_user_statistics(
+        &self,
+        user_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        filter: &StatisticsFilter,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
         let query = r#"
-            UPDATE tasks 
-            SET status = $3, completed_at = NOW(), updated_at = NOW()
-            WHERE id = $1 AND user_id = $2 AND status != 2
-            RETURNING id, title, description, status, priority, due_date, completed_at, user_id, created_at, updated_at
+            SELECT 
+                COUNT(*) as total_tasks,
+                COUNT(CASE WHEN status = 0 THEN 1 END) as pending_tasks,
+                COUNT(CASE WHEN status = 1 THEN 1 END) as in_progress_tasks,
+                COUNT(CASE WHEN status = 2 THEN 1 END) as completed_tasks,
+                COUNT(CASE WHEN due_date < $2 AND status != 2 THEN 1 END) as overdue_tasks,
+                COUNT(CASE WHEN updated_at < $3 AND status != 2 THEN 1 END) as stale_tasks
+            FROM tasks 
+            WHERE user_id = $1
+              AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR completed_at >= $6)
+              AND ($7::TIMESTAMPTZ IS NULL OR completed_at < $7)
         "#;
 
-        let updated = sqlx::query_as::<_, Task>(query)
-            .bind(id)
+        let row = sqlx::query(query)
             .bind(user_id)
-            .bind(TaskStatus::Completed)
-            .fetch_optional(&self.pool)
+            .bind(due_before)
+            .bind(stale_before)
+            .bind(filter.created_after)
+            .bind(filter.created_before)
+            .bind(filter.completed_after)
+            .bind(filter.completed_before)
+            .fetch_one(self.read_pool())
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
-        updated.ok_or(TaskRepositoryError::NotFound)
+        Ok(TaskStatistics {
+            total_tasks: row.get::<i64, _>("total_tasks"),
+            pending_tasks: row.get::<i64, _>("pending_tasks"),
+            in_progress_tasks: row.get::<i64, _>("in_progress_tasks"),
+            completed_tasks: row.get::<i64, _>("completed_tasks"),
+            overdue_tasks: row.get::<i64, _>("overdue_tasks"),
+            stale_tasks: row.get::<i64, _>("stale_tasks"),
+        })
     }
 
-    // Business Logic: Mark task as incomplete (remove completed_at)
-    #[allow(dead_code)]
-    pub async fn mark_incomplete(&self, id: &Uuid, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+    #[instrument(level = "debug", name = "TaskRepository::count_grouped", skip_all)]
+    async fn count_grouped(
+        &self,
+        user_id: &Uuid,
+        group_by: TaskGrouping,
+        filter: &StatisticsFilter,
+    ) -> Result<HashMap<TaskGroup, i64>, TaskRepositoryError> {
+        let column = task_group_column(group_by);
+        let query = format!(
+            r#"
+            SELECT {column} AS grp, COUNT(*) AS count
+            FROM tasks
+            WHERE user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR created_at >= $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR completed_at >= $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR completed_at < $5)
+            GROUP BY {column}
+        "#
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(user_id)
+            .bind(filter.created_after)
+            .bind(filter.created_before)
+            .bind(filter.completed_after)
+            .bind(filter.completed_before)
+            .fetch_all(self.read_pool())
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        rows.iter()
+            .map(|row| -> Result<(TaskGroup, i64), sqlx::Error> {
+                let group = match group_by {
+                    TaskGrouping::Status => TaskGroup::Status(row.try_get("grp")?),
+                    TaskGrouping::Priority => TaskGroup::Priority(row.try_get("grp")?),
+                    TaskGrouping::Context => TaskGroup::Context(row.try_get("grp")?),
+                };
+                Ok((group, row.try_get("count")?))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(TaskRepositoryError::DatabaseError)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_throughput", skip_all)]
+    async fn get_throughput(&self, user_id: &Uuid, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Throughput, TaskRepositoryError> {
         let query = r#"
-            UPDATE tasks 
-            SET status = $3, completed_at = NULL, updated_at = NOW()
-            WHERE id = $1 AND user_id = $2 AND status = 2
-            RETURNING id, title, description, status, priority, due_date, completed_at, user_id, created_at, updated_at
+            SELECT
+                COUNT(CASE WHEN created_at >= $2 AND created_at < $3 THEN 1 END) AS created,
+                COUNT(CASE WHEN created_at >= $2 AND created_at < $3 AND status = 2 THEN 1 END) AS created_completed,
+                COUNT(CASE WHEN completed_at >= $2 AND completed_at < $3 THEN 1 END) AS completed,
+                AVG(CASE WHEN completed_at >= $2 AND completed_at < $3
+                    THEN EXTRACT(EPOCH FROM completed_at - created_at) END)::DOUBLE PRECISION AS average_completion_seconds
+            FROM tasks
+            WHERE user_id = $1
+              AND (created_at >= $2 OR completed_at >= $2)
         "#;
 
-        let updated = sqlx::query_as::<_, Task>(query)
-            .bind(id)
+        let row = sqlx::query(query)
             .bind(user_id)
-            .bind(TaskStatus::Pending)
-            .fetch_optional(&self.pool)
+            .bind(since)
+            .bind(until)
+            .fetch_one(self.read_pool())
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
-        updated.ok_or(TaskRepositoryError::NotFound)
+        Ok(Throughput {
+            created: row.get::<i64, _>("created"),
+            created_completed: row.get::<i64, _>("created_completed"),
+            completed: row.get::<i64, _>("completed"),
+            average_completion_seconds: row.get::<Option<f64>, _>("average_completion_seconds"),
+        })
     }
 
-    // Business Logic: Get task statistics for a user
-    #[allow(dead_code)]
-    pub async fn get_user_statistics(&self, user_id: &Uuid) -> Result<TaskStatistics, TaskRepositoryError> {
+    #[instrument(level = "debug", name = "TaskRepository::count_by_day", skip_all)]
+    async fn count_by_day(
+        &self,
+        user_id: &Uuid,
+        timestamp: TaskTimestamp,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Result<Vec<DayCount>, TaskRepositoryError> {
+        let column = task_timestamp_column(timestamp);
+
+        // Without a name to give Postgres, the days are cut here instead
+        let Some(timezone_name) = timezone.iana_name() else {
+            let query = format!("SELECT {column} FROM tasks WHERE user_id = $1 AND {column} >= $2 AND {column} < $3");
+            let timestamps = sqlx::query_scalar::<_, DateTime<Utc>>(&query)
+                .bind(user_id)
+                .bind(since)
+                .bind(until)
+                .fetch_all(self.read_pool())
+                .await
+                .map_err(TaskRepositoryError::DatabaseError)?;
+            return Ok(DayCount::from_timestamps(timestamps, since, until, timezone));
+        };
+
+        let query = format!(
+            r#"
+            SELECT ({column} AT TIME ZONE $4)::DATE AS date, COUNT(*) AS count
+            FROM tasks
+            WHERE user_id = $1 AND {column} >= $2 AND {column} < $3
+            GROUP BY 1
+            ORDER BY 1
+        "#
+        );
+
+        let days = sqlx::query_as::<_, DayCount>(&query)
+            .bind(user_id)
+            .bind(since)
+            .bind(until)
+            .bind(timezone_name)
+            .fetch_all(self.read_pool())
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(days)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_workspace_statistics", skip_all)]
+    async fn get_workspace_statistics(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+    ) -> Result<TaskStatistics, TaskRepositoryError> {
         let query = r#"
-            SELECT 
+            SELECT
                 COUNT(*) as total_tasks,
                 COUNT(CASE WHEN status = 0 THEN 1 END) as pending_tasks,
                 COUNT(CASE WHEN status = 1 THEN 1 END) as in_progress_tasks,
                 COUNT(CASE WHEN status = 2 THEN 1 END) as completed_tasks,
-                COUNT(CASE WHEN due_date < NOW() AND status != 2 THEN 1 END) as overdue_tasks
-            FROM tasks 
-            WHERE user_id = $1
+                COUNT(CASE WHEN due_date < $2 AND status != 2 THEN 1 END) as overdue_tasks,
+                COUNT(CASE WHEN updated_at < $3 AND status != 2 THEN 1 END) as stale_tasks
+            FROM tasks
+            WHERE workspace_id = $1
         "#;
 
         let row = sqlx::query(query)
-            .bind(user_id)
-            .fetch_one(&self.pool)
+            .bind(workspace_id)
+            .bind(due_before)
+            .bind(stale_before)
+            .fetch_one(self.read_pool())
             .await
             .map_err(TaskRepositoryError::DatabaseError)?;
 
@@ -313,6 +1145,119 @@ impl PostgresTaskRepository {
             in_progress_tasks: row.get::<i64, _>("in_progress_tasks"),
             completed_tasks: row.get::<i64, _>("completed_tasks"),
             overdue_tasks: row.get::<i64, _>("overdue_tasks"),
+            stale_tasks: row.get::<i64, _>("stale_tasks"),
+        })
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_workspace_member_counts", skip_all)]
+    async fn get_workspace_member_counts(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+        completed_since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<MemberTaskCounts>, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                user_id,
+                COUNT(*) as total_tasks,
+                COUNT(CASE WHEN status = 2 AND ($3::timestamptz IS NULL OR completed_at >= $3) THEN 1 END) as completed_tasks,
+                COUNT(CASE WHEN due_date < $2 AND status != 2 THEN 1 END) as overdue_tasks
+            FROM tasks
+            WHERE workspace_id = $1
+            GROUP BY user_id
+        "#;
+
+        let counts = sqlx::query_as::<_, MemberTaskCounts>(query)
+            .bind(workspace_id)
+            .bind(due_before)
+            .bind(completed_since)
+            .fetch_all(self.read_pool())
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(counts)
+    }
+
+    #[instrument(level = "debug", name = "TaskRepository::get_workspace_overdue_breakdown", skip_all)]
+    async fn get_workspace_overdue_breakdown(
+        &self,
+        workspace_id: &Uuid,
+        due_before: DateTime<Utc>,
+    ) -> Result<OverdueBreakdown, TaskRepositoryError> {
+        let query = r#"
+            SELECT
+                COUNT(CASE WHEN due_date >= $3 THEN 1 END) as up_to_week,
+                COUNT(CASE WHEN due_date < $3 AND due_date >= $4 THEN 1 END) as up_to_month,
+                COUNT(CASE WHEN due_date < $4 THEN 1 END) as older
+            FROM tasks
+            WHERE workspace_id = $1 AND due_date < $2 AND status != 2
+        "#;
+
+        let row = sqlx::query(query)
+            .bind(workspace_id)
+            .bind(due_before)
+            .bind(due_before - chrono::Duration::days(OverdueBreakdown::WEEK_DAYS))
+            .bind(due_before - chrono::Duration::days(OverdueBreakdown::MONTH_DAYS))
+            .fetch_one(self.read_pool())
+            .await
+            .map_err(TaskRepositoryError::DatabaseError)?;
+
+        Ok(OverdueBreakdown {
+            up_to_week: row.get::<i64, _>("up_to_week"),
+            up_to_month: row.get::<i64, _>("up_to_month"),
+            older: row.get::<i64, _>("older"),
         })
     }
 }
+
+impl PostgresTaskRepository {
+    // allow dead_code
+    #[allow(dead_code)]
+    async fn task_exists_by_id(&self, id: &Uuid) -> Result<bool, TaskRepositoryError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE id = $1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    // Business Logic: Mark task as completed with completed_at timestamp
+    #[allow(dead_code)]
+    pub async fn mark_complete(&self, id: &Uuid, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        let updated = returning_tasks!(
+            r#"
+            UPDATE tasks
+            SET status = $3, completed_at = NOW(), updated_at = NOW(), version = version + 1
+            WHERE id = $1 AND user_id = $2 AND status != 2
+            "#,
+            id,
+            user_id,
+            i16::from(TaskStatus::Completed),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        updated.ok_or(TaskRepositoryError::NotFound)
+    }
+
+    // Business Logic: Mark task as incomplete (remove completed_at)
+    #[allow(dead_code)]
+    pub async fn mark_incomplete(&self, id: &Uuid, user_id: &Uuid) -> Result<Task, TaskRepositoryError> {
+        let updated = returning_tasks!(
+            r#"
+            UPDATE tasks
+            SET status = $3, completed_at = NULL, updated_at = NOW(), version = version + 1
+            WHERE id = $1 AND user_id = $2 AND status = 2
+            "#,
+            id,
+            user_id,
+            i16::from(TaskStatus::Pending),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(TaskRepositoryError::DatabaseError)?;
+
+        updated.ok_or(TaskRepositoryError::NotFound)
+    }
+}
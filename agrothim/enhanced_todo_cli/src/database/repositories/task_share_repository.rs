@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::task_share::TaskShare;
+
+#[derive(Error, Debug)]
+pub enum TaskShareRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Task share repository trait for tasks shared with individual users
+#[async_trait]
+pub trait TaskShareRepository: Send + Sync {
+    /// Share the task with the user; sharing again only changes `read_only`
+    async fn store(&self, task_id: &Uuid, user_id: &Uuid, read_only: bool) -> Result<TaskShare, TaskShareRepositoryError>;
+    async fn find(&self, task_id: &Uuid, user_id: &Uuid) -> Result<Option<TaskShare>, TaskShareRepositoryError>;
+    /// Shares of other users' tasks with this user, newest first
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<TaskShare>, TaskShareRepositoryError>;
+    /// Users the task is shared with
+    async fn find_by_task_id(&self, task_id: &Uuid) -> Result<Vec<TaskShare>, TaskShareRepositoryError>;
+}
+
+/// PostgreSQL implementation of TaskShareRepository
+pub struct PostgresTaskShareRepository {
+    pool: PgPool,
+}
+
+impl PostgresTaskShareRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TaskShareRepository for PostgresTaskShareRepository {
+    #[instrument(level = "debug", name = "TaskShareRepository::store", skip_all)]
+    async fn store(&self, task_id: &Uuid, user_id: &Uuid, read_only: bool) -> Result<TaskShare, TaskShareRepositoryError> {
+        let query = r#"
+            INSERT INTO task_shares (task_id, user_id, read_only)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (task_id, user_id) DO UPDATE SET read_only = EXCLUDED.read_only
+            RETURNING task_id, user_id, read_only, created_at
+        "#;
+
+        let share = sqlx::query_as::<_, TaskShare>(query)
+            .bind(task_id)
+            .bind(user_id)
+            .bind(read_only)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(share)
+    }
+
+    #[instrument(level = "debug", name = "TaskShareRepository::find", skip_all)]
+    async fn find(&self, task_id: &Uuid, user_id: &Uuid) -> Result<Option<TaskShare>, TaskShareRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, read_only, created_at
+            FROM task_shares
+            WHERE task_id = $1 AND user_id = $2
+        "#;
+
+        let share = sqlx::query_as::<_, TaskShare>(query)
+            .bind(task_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(share)
+    }
+
+    #[instrument(level = "debug", name = "TaskShareRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<TaskShare>, TaskShareRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, read_only, created_at
+            FROM task_shares
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+        "#;
+
+        let shares = sqlx::query_as::<_, TaskShare>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(shares)
+    }
+
+    #[instrument(level = "debug", name = "TaskShareRepository::find_by_task_id", skip_all)]
+    async fn find_by_task_id(&self, task_id: &Uuid) -> Result<Vec<TaskShare>, TaskShareRepositoryError> {
+        let query = r#"
+            SELECT task_id, user_id, read_only, created_at
+            FROM task_shares
+            WHERE task_id = $1
+            ORDER BY created_at
+        "#;
+
+        let shares = sqlx::query_as::<_, TaskShare>(query)
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(shares)
+    }
+}
@@ -0,0 +1,193 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::time_entry::{TaskTimeSummary, TimeEntry, TrackedTime};
+
+#[derive(Error, Debug)]
+pub enum TimeEntryRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Time entry repository trait for task timers
+#[async_trait]
+pub trait TimeEntryRepository: Send + Sync {
+    /// Start a timer on a task, stopping any timer the user already has running.
+    /// Returns the new entry and the entry that was stopped, if any.
+    async fn start(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<(TimeEntry, Option<TimeEntry>), TimeEntryRepositoryError>;
+    async fn stop(&self, user_id: &Uuid, at: DateTime<Utc>) -> Result<Option<TimeEntry>, TimeEntryRepositoryError>;
+    async fn find_running(&self, user_id: &Uuid) -> Result<Option<TimeEntry>, TimeEntryRepositoryError>;
+    /// Every entry of the user, oldest first
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<TimeEntry>, TimeEntryRepositoryError>;
+    async fn tracked_time(&self, user_id: &Uuid, task_id: &Uuid) -> Result<TrackedTime, TimeEntryRepositoryError>;
+    /// Time per task, counting only the part of each entry between `since` and `until`
+    async fn summarize(
+        &self,
+        user_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TaskTimeSummary>, TimeEntryRepositoryError>;
+}
+
+/// PostgreSQL implementation of TimeEntryRepository
+pub struct PostgresTimeEntryRepository {
+    pool: PgPool,
+}
+
+impl PostgresTimeEntryRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const STOP_RUNNING_QUERY: &str = r#"
+    UPDATE time_entries
+    SET ended_at = GREATEST($2, started_at)
+    WHERE user_id = $1 AND ended_at IS NULL
+    RETURNING id, task_id, user_id, started_at, ended_at, created_at
+"#;
+
+#[async_trait]
+impl TimeEntryRepository for PostgresTimeEntryRepository {
+    #[instrument(level = "debug", name = "TimeEntryRepository::start", skip_all)]
+    async fn start(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        at: DateTime<Utc>,
+    ) -> Result<(TimeEntry, Option<TimeEntry>), TimeEntryRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        let stopped = sqlx::query_as::<_, TimeEntry>(STOP_RUNNING_QUERY)
+            .bind(user_id)
+            .bind(at)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let query = r#"
+            INSERT INTO time_entries (task_id, user_id, started_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, task_id, user_id, started_at, ended_at, created_at
+        "#;
+
+        let entry = sqlx::query_as::<_, TimeEntry>(query)
+            .bind(task_id)
+            .bind(user_id)
+            .bind(at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok((entry, stopped))
+    }
+
+    #[instrument(level = "debug", name = "TimeEntryRepository::stop", skip_all)]
+    async fn stop(&self, user_id: &Uuid, at: DateTime<Utc>) -> Result<Option<TimeEntry>, TimeEntryRepositoryError> {
+        let entry = sqlx::query_as::<_, TimeEntry>(STOP_RUNNING_QUERY)
+            .bind(user_id)
+            .bind(at)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    #[instrument(level = "debug", name = "TimeEntryRepository::find_running", skip_all)]
+    async fn find_running(&self, user_id: &Uuid) -> Result<Option<TimeEntry>, TimeEntryRepositoryError> {
+        let query = r#"
+            SELECT id, task_id, user_id, started_at, ended_at, created_at
+            FROM time_entries
+            WHERE user_id = $1 AND ended_at IS NULL
+        "#;
+
+        let entry = sqlx::query_as::<_, TimeEntry>(query)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    #[instrument(level = "debug", name = "TimeEntryRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<TimeEntry>, TimeEntryRepositoryError> {
+        let query = r#"
+            SELECT id, task_id, user_id, started_at, ended_at, created_at
+            FROM time_entries
+            WHERE user_id = $1
+            ORDER BY started_at
+        "#;
+
+        let entries = sqlx::query_as::<_, TimeEntry>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(entries)
+    }
+
+    #[instrument(level = "debug", name = "TimeEntryRepository::tracked_time", skip_all)]
+    async fn tracked_time(&self, user_id: &Uuid, task_id: &Uuid) -> Result<TrackedTime, TimeEntryRepositoryError> {
+        let query = r#"
+            SELECT
+                COALESCE(SUM(EXTRACT(EPOCH FROM (COALESCE(ended_at, NOW()) - started_at))), 0)::BIGINT AS total_seconds,
+                COALESCE(BOOL_OR(ended_at IS NULL), FALSE) AS running
+            FROM time_entries
+            WHERE user_id = $1 AND task_id = $2
+        "#;
+
+        let tracked = sqlx::query_as::<_, TrackedTime>(query)
+            .bind(user_id)
+            .bind(task_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(tracked)
+    }
+
+    #[instrument(level = "debug", name = "TimeEntryRepository::summarize", skip_all)]
+    async fn summarize(
+        &self,
+        user_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TaskTimeSummary>, TimeEntryRepositoryError> {
+        // Entries crossing a period boundary only count the part inside it
+        let query = r#"
+            SELECT
+                t.id AS task_id,
+                t.number AS task_number,
+                t.title,
+                t.client,
+                COUNT(e.id) AS entry_count,
+                SUM(EXTRACT(EPOCH FROM (
+                    LEAST(COALESCE(e.ended_at, NOW()), COALESCE($3, 'infinity'::TIMESTAMPTZ))
+                    - GREATEST(e.started_at, COALESCE($2, e.started_at))
+                )))::BIGINT AS total_seconds
+            FROM time_entries e
+            JOIN tasks t ON t.id = e.task_id
+            WHERE e.user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR COALESCE(e.ended_at, NOW()) > $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR e.started_at < $3)
+            GROUP BY t.id, t.number, t.title, t.client
+            ORDER BY total_seconds DESC, t.title ASC
+        "#;
+
+        let summaries = sqlx::query_as::<_, TaskTimeSummary>(query)
+            .bind(user_id)
+            .bind(since)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(summaries)
+    }
+}
@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::user_identity::{OAuthProvider, UserIdentity};
+
+#[derive(Error, Debug)]
+pub enum UserIdentityRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// User identity repository trait for external accounts linked to users
+#[async_trait]
+pub trait UserIdentityRepository: Send + Sync {
+    async fn find(
+        &self,
+        provider: OAuthProvider,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>, UserIdentityRepositoryError>;
+    /// Link the provider account to `user_id`, or record a new login if it is
+    /// already linked. An account stays linked to the user it was first linked to.
+    async fn link(
+        &self,
+        user_id: &Uuid,
+        provider: OAuthProvider,
+        subject: &str,
+        email: Option<&str>,
+    ) -> Result<UserIdentity, UserIdentityRepositoryError>;
+    #[allow(dead_code)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<UserIdentity>, UserIdentityRepositoryError>;
+}
+
+/// PostgreSQL implementation of UserIdentityRepository
+pub struct PostgresUserIdentityRepository {
+    pool: PgPool,
+}
+
+impl PostgresUserIdentityRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserIdentityRepository for PostgresUserIdentityRepository {
+    #[instrument(level = "debug", name = "UserIdentityRepository::find", skip_all)]
+    async fn find(
+        &self,
+        provider: OAuthProvider,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>, UserIdentityRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, provider, subject, email, created_at, last_login_at
+            FROM user_identities
+            WHERE provider = $1 AND subject = $2
+        "#;
+
+        let identity = sqlx::query_as::<_, UserIdentity>(query)
+            .bind(provider.as_str())
+            .bind(subject)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(identity)
+    }
+
+    #[instrument(level = "debug", name = "UserIdentityRepository::link", skip_all)]
+    async fn link(
+        &self,
+        user_id: &Uuid,
+        provider: OAuthProvider,
+        subject: &str,
+        email: Option<&str>,
+    ) -> Result<UserIdentity, UserIdentityRepositoryError> {
+        let query = r#"
+            INSERT INTO user_identities (user_id, provider, subject, email)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (provider, subject)
+            DO UPDATE SET email = EXCLUDED.email, last_login_at = NOW()
+            RETURNING id, user_id, provider, subject, email, created_at, last_login_at
+        "#;
+
+        let identity = sqlx::query_as::<_, UserIdentity>(query)
+            .bind(user_id)
+            .bind(provider.as_str())
+            .bind(subject)
+            .bind(email)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(identity)
+    }
+
+    #[instrument(level = "debug", name = "UserIdentityRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<UserIdentity>, UserIdentityRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, provider, subject, email, created_at, last_login_at
+            FROM user_identities
+            WHERE user_id = $1
+            ORDER BY created_at
+        "#;
+
+        let identities = sqlx::query_as::<_, UserIdentity>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(identities)
+    }
+}
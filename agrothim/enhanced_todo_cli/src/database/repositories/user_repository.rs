@@ -1,10 +1,12 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use sqlx::{PgPool, Row};
+use tracing::instrument;
+use sqlx::PgPool;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::models::user::{StoreUserRequest, UpdateUserRequest, User};
+use crate::database::repositories::ApiClientError;
+use crate::models::user::{StoreUserRequest, UpdateUserRequest, User, UsernameChange};
 
 #[derive(Error, Debug)]
 pub enum UserRepositoryError {
@@ -16,21 +18,35 @@ pub enum UserRepositoryError {
     EmailExists { email: String },
     #[error("Database error: {0}")]
     DatabaseError(#[from] sqlx::Error),
+    #[error("API error: {0}")]
+    ApiError(#[source] ApiClientError),
 }
 
 /// User repository trait for data access operations
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn store(&self, user: StoreUserRequest) -> Result<User, UserRepositoryError>;
+    /// Write a copy of an account read from another database, keeping its ID,
+    /// credentials and timestamps and replacing an earlier copy
+    async fn put_copy(&self, user: &User) -> Result<User, UserRepositoryError>;
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError>;
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, UserRepositoryError>;
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError>;
+    /// Every account, oldest first
+    async fn find_all(&self) -> Result<Vec<User>, UserRepositoryError>;
+    /// Apply the given changes; a new username is recorded in the rename history
     async fn update(
         &self,
         id: &Uuid,
         updates: UpdateUserRequest,
     ) -> Result<User, UserRepositoryError>;
+    /// The user's renames, newest first
+    async fn find_username_changes(&self, id: &Uuid) -> Result<Vec<UsernameChange>, UserRepositoryError>;
     async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError>;
+    /// Record that the user confirmed their email address; verifying twice keeps the first time
+    async fn mark_email_verified(&self, id: &Uuid) -> Result<User, UserRepositoryError>;
+    /// Suspend (`false`) or reactivate (`true`) the account
+    async fn set_active(&self, id: &Uuid, active: bool) -> Result<User, UserRepositoryError>;
     async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError>;
     async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError>;
 }
@@ -49,6 +65,7 @@ impl PostgresUserRepository {
 
 #[async_trait]
 impl UserRepository for PostgresUserRepository {
+    #[instrument(level = "debug", name = "UserRepository::store", skip_all)]
     async fn store(&self, user: StoreUserRequest) -> Result<User, UserRepositoryError> {
         // Check for existing username
         if self.exists_by_username(&user.username).await? {
@@ -65,77 +82,134 @@ impl UserRepository for PostgresUserRepository {
         let user_id = Uuid::new_v4();
         let now = chrono::Utc::now();
 
-        let query = r#"
+        let user = sqlx::query_as!(
+            User,
+            r#"
             INSERT INTO users (id, username, email, password_hash, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, username, email, password_hash, created_at, updated_at
-        "#;
-
-        let user = sqlx::query_as::<_, User>(query)
-            .bind(&user_id)
-            .bind(&user.username)
-            .bind(&user.email)
-            .bind(&user.password_hash)
-            .bind(now)
-            .bind(now)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to create user: {}", e);
-                UserRepositoryError::DatabaseError(e)
-            })?;
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
+            "#,
+            user_id,
+            &user.username,
+            &user.email,
+            &user.password_hash,
+            now,
+            now,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create user: {}", e);
+            UserRepositoryError::DatabaseError(e)
+        })?;
+
+        Ok(user)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::put_copy", skip_all)]
+    async fn put_copy(&self, user: &User) -> Result<User, UserRepositoryError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            INSERT INTO users (id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE
+            SET username = EXCLUDED.username,
+                email = EXCLUDED.email,
+                password_hash = EXCLUDED.password_hash,
+                email_verified_at = EXCLUDED.email_verified_at,
+                is_active = EXCLUDED.is_active,
+                updated_at = EXCLUDED.updated_at
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
+            "#,
+            user.id,
+            &user.username,
+            &user.email,
+            &user.password_hash,
+            user.email_verified_at,
+            user.is_active,
+            user.created_at,
+            user.updated_at,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
 
         Ok(user)
     }
 
+    #[instrument(level = "debug", name = "UserRepository::find_by_id", skip_all)]
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError> {
-        let query = r#"
-            SELECT id, username, email, password_hash, created_at, updated_at
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
             FROM users
             WHERE id = $1
-        "#;
-
-        let user = sqlx::query_as::<_, User>(query)
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(UserRepositoryError::DatabaseError)?;
+            "#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
 
         Ok(user)
     }
 
+    #[instrument(level = "debug", name = "UserRepository::find_by_username", skip_all)]
     async fn find_by_username(&self, username: &str) -> Result<Option<User>, UserRepositoryError> {
-        let query = r#"
-            SELECT id, username, email, password_hash, created_at, updated_at
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
             FROM users
             WHERE username = $1
-        "#;
-
-        let user = sqlx::query_as::<_, User>(query)
-            .bind(username)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(UserRepositoryError::DatabaseError)?;
+            "#,
+            username,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
 
         Ok(user)
     }
 
+    #[instrument(level = "debug", name = "UserRepository::find_by_email", skip_all)]
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError> {
-        let query = r#"
-            SELECT id, username, email, password_hash, created_at, updated_at
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
             FROM users
             WHERE email = $1
-        "#;
-
-        let user = sqlx::query_as::<_, User>(query)
-            .bind(email)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(UserRepositoryError::DatabaseError)?;
+            "#,
+            email,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
 
         Ok(user)
     }
 
+    #[instrument(level = "debug", name = "UserRepository::find_all", skip_all)]
+    async fn find_all(&self) -> Result<Vec<User>, UserRepositoryError> {
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
+            FROM users
+            ORDER BY created_at, username
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
+
+        Ok(users)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::update", skip_all)]
     async fn update(
         &self,
         id: &Uuid,
@@ -150,6 +224,11 @@ impl UserRepository for PostgresUserRepository {
         let mut param_count = 1;
 
         // Build dynamic query based on what fields are being updated
+        if updates.username.is_some() {
+            set_clauses.push(format!("username = ${}", param_count));
+            param_count += 1;
+        }
+
         if updates.email.is_some() {
             set_clauses.push(format!("email = ${}", param_count));
             param_count += 1;
@@ -171,7 +250,7 @@ impl UserRepository for PostgresUserRepository {
         set_clauses.push(format!("updated_at = ${}", param_count));
 
         let query = format!(
-            "UPDATE users SET {} WHERE id = ${} RETURNING id, username, email, password_hash, created_at, updated_at",
+            "UPDATE users SET {} WHERE id = ${} RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at, updated_at",
             set_clauses.join(", "),
             param_count + 1
         );
@@ -179,6 +258,10 @@ impl UserRepository for PostgresUserRepository {
         let mut query_builder = sqlx::query_as::<_, User>(&query);
 
         // Bind parameters in the same order as set_clauses
+        if let Some(username) = &updates.username {
+            query_builder = query_builder.bind(username);
+        }
+
         if let Some(email) = &updates.email {
             query_builder = query_builder.bind(email);
         }
@@ -190,19 +273,53 @@ impl UserRepository for PostgresUserRepository {
         query_builder = query_builder.bind(chrono::Utc::now());
         query_builder = query_builder.bind(id);
 
+        let mut tx = self.pool.begin().await?;
+
+        let old_username = sqlx::query_scalar!("SELECT username FROM users WHERE id = $1 FOR UPDATE", id)
+            .fetch_one(&mut *tx)
+            .await?;
+
         let user = query_builder
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *tx)
             .await
             .map_err(UserRepositoryError::DatabaseError)?;
 
+        if user.username != old_username {
+            sqlx::query!(
+                "INSERT INTO username_changes (user_id, old_username, new_username) VALUES ($1, $2, $3)",
+                id,
+                &old_username,
+                &user.username,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
         Ok(user)
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
-        let query = "DELETE FROM users WHERE id = $1";
+    #[instrument(level = "debug", name = "UserRepository::find_username_changes", skip_all)]
+    async fn find_username_changes(&self, id: &Uuid) -> Result<Vec<UsernameChange>, UserRepositoryError> {
+        let changes = sqlx::query_as!(
+            UsernameChange,
+            r#"
+            SELECT id, user_id, old_username, new_username, changed_at
+            FROM username_changes
+            WHERE user_id = $1
+            ORDER BY changed_at DESC
+            "#,
+            id,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(changes)
+    }
 
-        let result = sqlx::query(query)
-            .bind(id)
+    #[instrument(level = "debug", name = "UserRepository::delete", skip_all)]
+    async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
+        let result = sqlx::query!("DELETE FROM users WHERE id = $1", id)
             .execute(&self.pool)
             .await
             .map_err(UserRepositoryError::DatabaseError)?;
@@ -210,43 +327,75 @@ impl UserRepository for PostgresUserRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    #[instrument(level = "debug", name = "UserRepository::exists_by_username", skip_all)]
     async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError> {
-        let query = "SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)";
-
-        let row = sqlx::query(query)
-            .bind(username)
+        let exists = sqlx::query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM users WHERE username = $1) AS "exists!""#, username)
             .fetch_one(&self.pool)
             .await
             .map_err(UserRepositoryError::DatabaseError)?;
 
-        Ok(row.get::<bool, _>(0))
+        Ok(exists)
     }
 
-    async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
-        let query = "SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)";
+    #[instrument(level = "debug", name = "UserRepository::mark_email_verified", skip_all)]
+    async fn mark_email_verified(&self, id: &Uuid) -> Result<User, UserRepositoryError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
+            "#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
+
+        user.ok_or(UserRepositoryError::NotFound)
+    }
 
-        let row = sqlx::query(query)
-            .bind(email)
+    #[instrument(level = "debug", name = "UserRepository::set_active", skip_all)]
+    async fn set_active(&self, id: &Uuid, active: bool) -> Result<User, UserRepositoryError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET is_active = $2, updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, username, email, password_hash, email_verified_at, is_active, created_at AS "created_at!", updated_at AS "updated_at!"
+            "#,
+            id,
+            active,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(UserRepositoryError::DatabaseError)?;
+
+        user.ok_or(UserRepositoryError::NotFound)
+    }
+
+    #[instrument(level = "debug", name = "UserRepository::exists_by_email", skip_all)]
+    async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
+        let exists = sqlx::query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM users WHERE email = $1) AS "exists!""#, email)
             .fetch_one(&self.pool)
             .await
             .map_err(UserRepositoryError::DatabaseError)?;
 
-        Ok(row.get::<bool, _>(0))
+        Ok(exists)
     }
 }
 
 impl PostgresUserRepository {
     #[allow(dead_code)]
     async fn user_exists_by_id(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
-        let query = "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)";
-
-        let row = sqlx::query(query)
-            .bind(id)
+        let exists = sqlx::query_scalar!(r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#, id)
             .fetch_one(&self.pool)
             .await
             .map_err(UserRepositoryError::DatabaseError)?;
 
-        Ok(row.get::<bool, _>(0))
+        Ok(exists)
     }
 }
 
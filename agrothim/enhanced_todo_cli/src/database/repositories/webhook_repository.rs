@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::webhook::{Webhook, WebhookDelivery, WebhookEvent};
+
+#[derive(Error, Debug)]
+pub enum WebhookRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Webhook repository trait for per-user webhook subscriptions
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    async fn store(
+        &self,
+        user_id: &Uuid,
+        url: &str,
+        secret: &str,
+        events: &[WebhookEvent],
+    ) -> Result<Webhook, WebhookRepositoryError>;
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Webhook>, WebhookRepositoryError>;
+    async fn delete(&self, user_id: &Uuid, id: &Uuid) -> Result<bool, WebhookRepositoryError>;
+    async fn store_delivery(
+        &self,
+        webhook_id: &Uuid,
+        event: WebhookEvent,
+        attempts: i32,
+        status_code: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<WebhookDelivery, WebhookRepositoryError>;
+    /// Latest deliveries of one of the user's webhooks, newest first
+    async fn find_deliveries(
+        &self,
+        user_id: &Uuid,
+        webhook_id: &Uuid,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, WebhookRepositoryError>;
+}
+
+/// PostgreSQL implementation of WebhookRepository
+pub struct PostgresWebhookRepository {
+    pool: PgPool,
+}
+
+impl PostgresWebhookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for PostgresWebhookRepository {
+    #[instrument(level = "debug", name = "WebhookRepository::store", skip_all)]
+    async fn store(
+        &self,
+        user_id: &Uuid,
+        url: &str,
+        secret: &str,
+        events: &[WebhookEvent],
+    ) -> Result<Webhook, WebhookRepositoryError> {
+        let query = r#"
+            INSERT INTO webhooks (user_id, url, secret, events)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, user_id, url, secret, events, created_at
+        "#;
+
+        let events: Vec<&str> = events.iter().map(WebhookEvent::as_str).collect();
+
+        let webhook = sqlx::query_as::<_, Webhook>(query)
+            .bind(user_id)
+            .bind(url)
+            .bind(secret)
+            .bind(&events)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(webhook)
+    }
+
+    #[instrument(level = "debug", name = "WebhookRepository::find_by_user_id", skip_all)]
+    async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Webhook>, WebhookRepositoryError> {
+        let query = r#"
+            SELECT id, user_id, url, secret, events, created_at
+            FROM webhooks
+            WHERE user_id = $1
+            ORDER BY created_at ASC
+        "#;
+
+        let webhooks = sqlx::query_as::<_, Webhook>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(webhooks)
+    }
+
+    #[instrument(level = "debug", name = "WebhookRepository::delete", skip_all)]
+    async fn delete(&self, user_id: &Uuid, id: &Uuid) -> Result<bool, WebhookRepositoryError> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "WebhookRepository::store_delivery", skip_all)]
+    async fn store_delivery(
+        &self,
+        webhook_id: &Uuid,
+        event: WebhookEvent,
+        attempts: i32,
+        status_code: Option<i32>,
+        error: Option<&str>,
+    ) -> Result<WebhookDelivery, WebhookRepositoryError> {
+        let query = r#"
+            INSERT INTO webhook_deliveries (webhook_id, event, attempts, status_code, error)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, webhook_id, event, attempts, status_code, error, delivered_at
+        "#;
+
+        let delivery = sqlx::query_as::<_, WebhookDelivery>(query)
+            .bind(webhook_id)
+            .bind(event.as_str())
+            .bind(attempts)
+            .bind(status_code)
+            .bind(error)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(delivery)
+    }
+
+    #[instrument(level = "debug", name = "WebhookRepository::find_deliveries", skip_all)]
+    async fn find_deliveries(
+        &self,
+        user_id: &Uuid,
+        webhook_id: &Uuid,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, WebhookRepositoryError> {
+        let query = r#"
+            SELECT d.id, d.webhook_id, d.event, d.attempts, d.status_code, d.error, d.delivered_at
+            FROM webhook_deliveries d
+            JOIN webhooks w ON w.id = d.webhook_id
+            WHERE d.webhook_id = $1 AND w.user_id = $2
+            ORDER BY d.delivered_at DESC
+            LIMIT $3
+        "#;
+
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(query)
+            .bind(webhook_id)
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(deliveries)
+    }
+}
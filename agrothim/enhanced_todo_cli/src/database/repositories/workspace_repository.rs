@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use tracing::instrument;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::workspace::{Workspace, WorkspaceMember, MEMBER_ROLE, OWNER_ROLE};
+
+#[derive(Error, Debug)]
+pub enum WorkspaceRepositoryError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+}
+
+/// Workspace repository trait for shared task lists and their members
+#[async_trait]
+pub trait WorkspaceRepository: Send + Sync {
+    /// Create a workspace with `owner_id` as its first member
+    async fn store(&self, owner_id: &Uuid, name: &str) -> Result<Workspace, WorkspaceRepositoryError>;
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Workspace>, WorkspaceRepositoryError>;
+    /// Workspaces the user is a member of, oldest first
+    async fn find_by_member(&self, user_id: &Uuid) -> Result<Vec<Workspace>, WorkspaceRepositoryError>;
+    /// Add a member; false if the user already was one
+    async fn add_member(&self, workspace_id: &Uuid, user_id: &Uuid) -> Result<bool, WorkspaceRepositoryError>;
+    /// Members with their usernames, the owner first
+    async fn find_members(&self, workspace_id: &Uuid) -> Result<Vec<WorkspaceMember>, WorkspaceRepositoryError>;
+    async fn find_membership(
+        &self,
+        workspace_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<WorkspaceMember>, WorkspaceRepositoryError>;
+}
+
+/// PostgreSQL implementation of WorkspaceRepository
+pub struct PostgresWorkspaceRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkspaceRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkspaceRepository for PostgresWorkspaceRepository {
+    #[instrument(level = "debug", name = "WorkspaceRepository::store", skip_all)]
+    async fn store(&self, owner_id: &Uuid, name: &str) -> Result<Workspace, WorkspaceRepositoryError> {
+        let mut tx = self.pool.begin().await?;
+
+        let workspace = sqlx::query_as::<_, Workspace>(
+            r#"
+            INSERT INTO workspaces (name, owner_id)
+            VALUES ($1, $2)
+            RETURNING id, name, owner_id, created_at
+        "#,
+        )
+        .bind(name)
+        .bind(owner_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO workspace_members (workspace_id, user_id, role) VALUES ($1, $2, $3)")
+            .bind(workspace.id)
+            .bind(owner_id)
+            .bind(OWNER_ROLE)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(workspace)
+    }
+
+    #[instrument(level = "debug", name = "WorkspaceRepository::find_by_id", skip_all)]
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<Workspace>, WorkspaceRepositoryError> {
+        let query = r#"
+            SELECT id, name, owner_id, created_at
+            FROM workspaces
+            WHERE id = $1
+        "#;
+
+        let workspace = sqlx::query_as::<_, Workspace>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(workspace)
+    }
+
+    #[instrument(level = "debug", name = "WorkspaceRepository::find_by_member", skip_all)]
+    async fn find_by_member(&self, user_id: &Uuid) -> Result<Vec<Workspace>, WorkspaceRepositoryError> {
+        let query = r#"
+            SELECT w.id, w.name, w.owner_id, w.created_at
+            FROM workspaces w
+            JOIN workspace_members m ON m.workspace_id = w.id
+            WHERE m.user_id = $1
+            ORDER BY w.created_at
+        "#;
+
+        let workspaces = sqlx::query_as::<_, Workspace>(query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(workspaces)
+    }
+
+    #[instrument(level = "debug", name = "WorkspaceRepository::add_member", skip_all)]
+    async fn add_member(&self, workspace_id: &Uuid, user_id: &Uuid) -> Result<bool, WorkspaceRepositoryError> {
+        let query = r#"
+            INSERT INTO workspace_members (workspace_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(workspace_id)
+            .bind(user_id)
+            .bind(MEMBER_ROLE)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(level = "debug", name = "WorkspaceRepository::find_members", skip_all)]
+    async fn find_members(&self, workspace_id: &Uuid) -> Result<Vec<WorkspaceMember>, WorkspaceRepositoryError> {
+        let query = r#"
+            SELECT m.workspace_id, m.user_id, u.username, m.role, m.joined_at
+            FROM workspace_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.workspace_id = $1
+            ORDER BY m.role = 'owner' DESC, m.joined_at
+        "#;
+
+        let members = sqlx::query_as::<_, WorkspaceMember>(query)
+            .bind(workspace_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(members)
+    }
+
+    #[instrument(level = "debug", name = "WorkspaceRepository::find_membership", skip_all)]
+    async fn find_membership(
+        &self,
+        workspace_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<WorkspaceMember>, WorkspaceRepositoryError> {
+        let query = r#"
+            SELECT m.workspace_id, m.user_id, u.username, m.role, m.joined_at
+            FROM workspace_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.workspace_id = $1 AND m.user_id = $2
+        "#;
+
+        let member = sqlx::query_as::<_, WorkspaceMember>(query)
+            .bind(workspace_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(member)
+    }
+}
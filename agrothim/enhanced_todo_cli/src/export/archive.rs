@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::{FormatError, TaskRecord};
+use crate::models::status::{StatusTransition, StatusWorkflow};
+use crate::models::task::TaskStatus;
+
+/// Current archive schema version, written to every new archive
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const ARCHIVE_FORMAT: &str = "archive";
+
+/// A custom status as it was numbered in the exporting account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveStatus {
+    pub code: i16,
+    pub name: String,
+}
+
+/// Full-fidelity export: tasks together with the status workflow they use.
+///
+/// Custom status codes differ between accounts, so tasks and transitions
+/// keep the exporting account's codes and [`Archive::remap_statuses`]
+/// translates them by name on import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Archive {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub statuses: Vec<ArchiveStatus>,
+    #[serde(default)]
+    pub transitions: Vec<StatusTransition>,
+    #[serde(default)]
+    pub tasks: Vec<TaskRecord>,
+}
+
+impl Archive {
+    pub fn new(tasks: Vec<TaskRecord>, workflow: &StatusWorkflow) -> Self {
+        Self {
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            statuses: workflow
+                .custom_statuses
+                .iter()
+                .map(|status| ArchiveStatus {
+                    code: status.code,
+                    name: status.name.clone(),
+                })
+                .collect(),
+            transitions: workflow.transitions.clone(),
+            tasks,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, FormatError> {
+        serde_json::to_string_pretty(self).map_err(|e| FormatError::SerializeError {
+            format: ARCHIVE_FORMAT.to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Parse an archive of any supported version, migrating it to the
+    /// current schema. A plain JSON task export counts as version 0.
+    pub fn parse(input: &str) -> Result<Self, FormatError> {
+        let parse_error = |message: String| FormatError::ParseError {
+            format: ARCHIVE_FORMAT.to_string(),
+            message,
+        };
+
+        let mut value: Value = serde_json::from_str(input).map_err(|e| parse_error(e.to_string()))?;
+        let mut version = match &value {
+            Value::Array(_) => 0,
+            Value::Object(fields) => fields
+                .get("version")
+                .and_then(Value::as_u64)
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| parse_error("missing archive version".to_string()))?,
+            _ => return Err(parse_error("expected an archive object or a task array".to_string())),
+        };
+
+        if version > ARCHIVE_VERSION {
+            return Err(FormatError::UnsupportedVersion {
+                version,
+                supported: ARCHIVE_VERSION,
+            });
+        }
+
+        while version < ARCHIVE_VERSION {
+            value = migrate(value, version);
+            version += 1;
+        }
+
+        serde_json::from_value(value).map_err(|e| parse_error(e.to_string()))
+    }
+
+    /// Names of the custom statuses the archive's tasks and transitions use
+    pub fn status_names(&self) -> Vec<String> {
+        self.statuses.iter().map(|status| status.name.clone()).collect()
+    }
+
+    /// Translate custom status codes to those of the same-named statuses in
+    /// `workflow`. Tasks in a status the workflow lacks fall back to pending,
+    /// and transitions involving one are dropped.
+    pub fn remap_statuses(&mut self, workflow: &StatusWorkflow) {
+        let codes: HashMap<i16, TaskStatus> = self
+            .statuses
+            .iter()
+            .filter_map(|status| Some((status.code, workflow.parse(&status.name)?)))
+            .collect();
+        let remap = |status: TaskStatus| match status {
+            TaskStatus::Custom(code) => codes.get(&code).copied(),
+            built_in => Some(built_in),
+        };
+
+        for task in &mut self.tasks {
+            task.status = remap(task.status).unwrap_or(TaskStatus::Pending);
+        }
+        self.transitions = self
+            .transitions
+            .iter()
+            .filter_map(|transition| {
+                Some(StatusTransition {
+                    from_status: remap(transition.from_status)?,
+                    to_status: remap(transition.to_status)?,
+                })
+            })
+            .collect();
+        self.statuses = workflow
+            .custom_statuses
+            .iter()
+            .filter(|status| codes.values().any(|mapped| *mapped == status.status()))
+            .map(|status| ArchiveStatus {
+                code: status.code,
+                name: status.name.clone(),
+            })
+            .collect();
+    }
+}
+
+/// Upgrade an archive from `version` to `version + 1`
+fn migrate(value: Value, version: u32) -> Value {
+    match version {
+        // Version 0 is the bare task array written by `export --format json`
+        0 => serde_json::json!({
+            "version": 1,
+            "exported_at": Utc::now(),
+            "tasks": value,
+        }),
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::status::CustomStatus;
+    use uuid::Uuid;
+
+    fn custom_status(code: i16, name: &str) -> CustomStatus {
+        CustomStatus {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            code,
+            name: name.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn task(title: &str, status: TaskStatus) -> TaskRecord {
+        let mut record: TaskRecord = serde_json::from_str(&format!(r#"{{"title":"{}"}}"#, title)).unwrap();
+        record.status = status;
+        record
+    }
+
+    #[test]
+    fn test_round_trip_and_remap() {
+        let exported = StatusWorkflow::new(
+            vec![custom_status(10, "Blocked"), custom_status(11, "In Review")],
+            vec![StatusTransition {
+                from_status: TaskStatus::Custom(11),
+                to_status: TaskStatus::Completed,
+            }],
+        );
+        let archive = Archive::new(
+            vec![task("Waiting on legal", TaskStatus::Custom(10)), task("Ship it", TaskStatus::Custom(11))],
+            &exported,
+        );
+
+        let mut imported = Archive::parse(&archive.to_json().unwrap()).unwrap();
+        assert_eq!(imported, archive);
+        assert_eq!(imported.status_names(), vec!["Blocked", "In Review"]);
+
+        // The importing account numbered its statuses differently and has no "Blocked"
+        let target = StatusWorkflow::new(vec![custom_status(10, "in-review")], Vec::new());
+        imported.remap_statuses(&target);
+
+        assert_eq!(imported.tasks[0].status, TaskStatus::Pending);
+        assert_eq!(imported.tasks[1].status, TaskStatus::Custom(10));
+        assert_eq!(
+            imported.transitions,
+            vec![StatusTransition {
+                from_status: TaskStatus::Custom(10),
+                to_status: TaskStatus::Completed,
+            }]
+        );
+        assert_eq!(imported.statuses, vec![ArchiveStatus { code: 10, name: "in-review".to_string() }]);
+    }
+
+    #[test]
+    fn test_parse_migrates_plain_task_export() {
+        let archive = Archive::parse(r#"[{"title": "Buy milk", "status": "Completed"}]"#).unwrap();
+
+        assert_eq!(archive.version, ARCHIVE_VERSION);
+        assert_eq!(archive.tasks, vec![task("Buy milk", TaskStatus::Completed)]);
+        assert!(archive.statuses.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_newer_and_malformed_archives() {
+        assert!(matches!(
+            Archive::parse(r#"{"version": 99, "exported_at": "2026-01-01T00:00:00Z"}"#),
+            Err(FormatError::UnsupportedVersion { version: 99, supported: ARCHIVE_VERSION })
+        ));
+        assert!(matches!(Archive::parse(r#"{"tasks": []}"#), Err(FormatError::ParseError { .. })));
+        assert!(matches!(Archive::parse("42"), Err(FormatError::ParseError { .. })));
+    }
+}
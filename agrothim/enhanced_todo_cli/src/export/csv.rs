@@ -0,0 +1,77 @@
+use std::io::Write;
+
+use super::{Format, FormatError, RecordWriter, TaskRecord};
+
+/// Comma-separated values with a header row
+pub struct CsvFormat;
+
+impl Format for CsvFormat {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv"]
+    }
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError> {
+        let serialize_error = |message: String| FormatError::SerializeError {
+            format: self.name().to_string(),
+            message,
+        };
+
+        let mut writer = ::csv::Writer::from_writer(Vec::new());
+        for record in records {
+            writer
+                .serialize(record)
+                .map_err(|e| serialize_error(e.to_string()))?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| serialize_error(e.to_string()))?;
+        String::from_utf8(bytes).map_err(|e| serialize_error(e.to_string()))
+    }
+
+    fn record_writer<'w>(&self, output: &'w mut dyn Write) -> Option<Box<dyn RecordWriter + 'w>> {
+        Some(Box::new(CsvWriter {
+            writer: ::csv::Writer::from_writer(output),
+        }))
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError> {
+        let mut reader = ::csv::Reader::from_reader(input.as_bytes());
+        reader
+            .deserialize()
+            .map(|row| {
+                row.map_err(|e| FormatError::ParseError {
+                    format: self.name().to_string(),
+                    message: e.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+struct CsvWriter<'w> {
+    writer: ::csv::Writer<&'w mut dyn Write>,
+}
+
+impl CsvWriter<'_> {
+    fn serialize_error(e: impl ToString) -> FormatError {
+        FormatError::SerializeError {
+            format: "csv".to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl RecordWriter for CsvWriter<'_> {
+    fn write(&mut self, record: &TaskRecord) -> Result<(), FormatError> {
+        self.writer.serialize(record).map_err(Self::serialize_error)
+    }
+
+    fn finish(&mut self) -> Result<(), FormatError> {
+        self.writer.flush().map_err(Self::serialize_error)
+    }
+}
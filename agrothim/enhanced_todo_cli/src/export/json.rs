@@ -0,0 +1,48 @@
+use serde_json::Value;
+
+use super::{Format, FormatError, TaskRecord};
+use crate::utils::projection::FieldProjection;
+
+/// Pretty-printed JSON array of tasks
+pub struct JsonFormat;
+
+impl JsonFormat {
+    fn render(&self, value: &Value) -> Result<String, FormatError> {
+        serde_json::to_string_pretty(value).map_err(|e| FormatError::SerializeError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    fn to_value(&self, records: &[TaskRecord]) -> Result<Value, FormatError> {
+        serde_json::to_value(records).map_err(|e| FormatError::SerializeError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+impl Format for JsonFormat {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError> {
+        self.render(&self.to_value(records)?)
+    }
+
+    fn export_projected(&self, records: &[TaskRecord], projection: &FieldProjection) -> Result<String, FormatError> {
+        self.render(&projection.apply(&self.to_value(records)?))
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError> {
+        serde_json::from_str(input).map_err(|e| FormatError::ParseError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+}
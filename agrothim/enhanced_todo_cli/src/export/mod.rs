@@ -0,0 +1,331 @@
+pub mod archive;
+pub mod csv;
+pub mod json;
+pub mod ndjson;
+pub mod taskwarrior;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::location::GeoPoint;
+use crate::models::task::{StoreTaskRequest, Task, TaskPriority, TaskStatus};
+use crate::utils::projection::FieldProjection;
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("Unknown format '{name}'. Available formats: {available}")]
+    UnknownFormat { name: String, available: String },
+
+    #[error("Failed to serialize {format}: {message}")]
+    SerializeError { format: String, message: String },
+
+    #[error("Failed to parse {format}: {message}")]
+    ParseError { format: String, message: String },
+
+    #[error("--fields is only supported for JSON formats, not {format}")]
+    ProjectionUnsupported { format: String },
+
+    #[error("Archive version {version} is newer than the supported version {supported}")]
+    UnsupportedVersion { version: u32, supported: u32 },
+}
+
+/// Portable representation of a task used by every export/import format.
+///
+/// Ownership is deliberately left out so an export from one account can be
+/// imported into another. The id is only written by formats that identify
+/// tasks across exports, like TaskWarrior's; importing always creates new tasks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRecord {
+    #[serde(default, skip_serializing)]
+    pub id: Option<Uuid>,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    #[serde(default)]
+    pub due_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub estimate_minutes: Option<i32>,
+    #[serde(default)]
+    pub client: Option<String>,
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Task> for TaskRecord {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: Some(task.id),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status,
+            priority: task.priority,
+            due_date: task.due_date,
+            start_date: task.start_date,
+            completed_at: task.completed_at,
+            estimate_minutes: task.estimate_minutes,
+            client: task.client.clone(),
+            context: task.context.clone(),
+            location: task.location.clone(),
+            latitude: task.latitude,
+            longitude: task.longitude,
+            created_at: Some(task.created_at),
+        }
+    }
+}
+
+impl From<TaskRecord> for StoreTaskRequest {
+    fn from(record: TaskRecord) -> Self {
+        Self {
+            title: record.title,
+            description: record.description,
+            status: record.status,
+            priority: record.priority,
+            due_date: record.due_date,
+            start_date: record.start_date,
+            estimate_minutes: record.estimate_minutes,
+            client: record.client,
+            context: record.context,
+            location: record.location,
+            coordinates: record
+                .latitude
+                .zip(record.longitude)
+                .and_then(|(latitude, longitude)| GeoPoint::new(latitude, longitude).ok()),
+            workspace_id: None,
+        }
+    }
+}
+
+/// A serialization format for task export and import.
+///
+/// New formats only need to implement this trait and be registered with a
+/// [`FormatRegistry`]; optional formats live behind cargo features.
+pub trait Format: Send + Sync {
+    /// Name used to select the format on the command line (e.g. `json`)
+    fn name(&self) -> &'static str;
+
+    /// File extensions (without the dot) that map to this format
+    fn extensions(&self) -> &'static [&'static str];
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError>;
+
+    /// Export only the fields selected by `projection`. Formats without a
+    /// JSON object model keep the default, which rejects projection.
+    fn export_projected(&self, _records: &[TaskRecord], _projection: &FieldProjection) -> Result<String, FormatError> {
+        Err(FormatError::ProjectionUnsupported {
+            format: self.name().to_string(),
+        })
+    }
+
+    /// Writer that emits the export one record at a time, so a large export
+    /// is never held in memory; `None` for formats that need every record at once
+    fn record_writer<'w>(&self, _output: &'w mut dyn Write) -> Option<Box<dyn RecordWriter + 'w>> {
+        None
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError>;
+}
+
+/// Incremental export started by [`Format::record_writer`]
+pub trait RecordWriter {
+    fn write(&mut self, record: &TaskRecord) -> Result<(), FormatError>;
+
+    /// Flush buffered output; call after the last record
+    fn finish(&mut self) -> Result<(), FormatError>;
+}
+
+pub struct FormatRegistry {
+    formats: Vec<Box<dyn Format>>,
+}
+
+impl FormatRegistry {
+    pub fn new() -> Self {
+        Self {
+            formats: Vec::new(),
+        }
+    }
+
+    /// Registry containing every format compiled into this build
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(json::JsonFormat));
+        registry.register(Box::new(ndjson::NdjsonFormat));
+        registry.register(Box::new(csv::CsvFormat));
+        registry.register(Box::new(taskwarrior::TaskWarriorFormat));
+        #[cfg(feature = "yaml")]
+        registry.register(Box::new(yaml::YamlFormat));
+        #[cfg(feature = "toml")]
+        registry.register(Box::new(toml::TomlFormat));
+        registry
+    }
+
+    /// Register a format, replacing any existing format with the same name
+    pub fn register(&mut self, format: Box<dyn Format>) {
+        self.formats.retain(|existing| existing.name() != format.name());
+        self.formats.push(format);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn Format, FormatError> {
+        let name = name.to_lowercase();
+        self.formats
+            .iter()
+            .find(|format| format.name() == name || format.extensions().contains(&name.as_str()))
+            .map(|format| format.as_ref())
+            .ok_or_else(|| FormatError::UnknownFormat {
+                name,
+                available: self.names().join(", "),
+            })
+    }
+
+    /// Resolve a format from a file path's extension
+    pub fn for_path(&self, path: &Path) -> Option<&dyn Format> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        self.formats
+            .iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+            .map(|format| format.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.formats.iter().map(|format| format.name()).collect()
+    }
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn sample_records() -> Vec<TaskRecord> {
+        vec![
+            TaskRecord {
+                id: None,
+                title: "Write report, part \"one\"".to_string(),
+                description: Some("Line one\nline two".to_string()),
+                status: TaskStatus::InProgress,
+                priority: TaskPriority::High,
+                due_date: Some(Utc::now() + Duration::days(2)),
+                start_date: Some(Utc::now() + Duration::days(1)),
+                completed_at: None,
+                estimate_minutes: Some(90),
+                client: Some("Acme".to_string()),
+                context: Some("@office".to_string()),
+                location: Some("office".to_string()),
+                latitude: Some(52.52),
+                longitude: Some(13.405),
+                created_at: Some(Utc::now()),
+            },
+            TaskRecord {
+                id: None,
+                title: "Buy milk".to_string(),
+                description: None,
+                status: TaskStatus::Completed,
+                priority: TaskPriority::Low,
+                due_date: None,
+                start_date: None,
+                completed_at: Some(Utc::now()),
+                estimate_minutes: None,
+                client: None,
+                context: None,
+                location: None,
+                latitude: None,
+                longitude: None,
+                created_at: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_default_formats_round_trip() {
+        let registry = FormatRegistry::with_defaults();
+        let records = sample_records();
+
+        // TaskWarrior has no place for every field, see its own tests
+        for name in registry.names().into_iter().filter(|name| *name != "taskwarrior") {
+            let format = registry.get(name).unwrap();
+            let output = format.export(&records).unwrap();
+            let imported = format.import(&output).unwrap();
+            assert_eq!(imported, records, "round trip failed for {}", name);
+        }
+    }
+
+    #[test]
+    fn test_record_writer_matches_export() {
+        let registry = FormatRegistry::with_defaults();
+        let records = sample_records();
+
+        for name in ["ndjson", "csv"] {
+            let format = registry.get(name).unwrap();
+            let mut output = Vec::new();
+            let mut writer = format.record_writer(&mut output).unwrap();
+            for record in &records {
+                writer.write(record).unwrap();
+            }
+            writer.finish().unwrap();
+            drop(writer);
+            assert_eq!(String::from_utf8(output).unwrap(), format.export(&records).unwrap(), "{}", name);
+        }
+        assert!(registry.get("json").unwrap().record_writer(&mut Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_projected_export() {
+        let registry = FormatRegistry::with_defaults();
+        let records = sample_records();
+        let projection: FieldProjection = "title,status".parse().unwrap();
+
+        let output = registry.get("ndjson").unwrap().export_projected(&records, &projection).unwrap();
+        assert_eq!(output.lines().next(), Some(r#"{"title":"Write report, part \"one\"","status":"InProgress"}"#));
+
+        assert!(matches!(
+            registry.get("csv").unwrap().export_projected(&records, &projection),
+            Err(FormatError::ProjectionUnsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lookup_by_name_and_extension() {
+        let registry = FormatRegistry::with_defaults();
+
+        assert_eq!(registry.get("JSON").unwrap().name(), "json");
+        assert_eq!(registry.get("jsonl").unwrap().name(), "ndjson");
+        assert_eq!(
+            registry.for_path(Path::new("backup/tasks.csv")).unwrap().name(),
+            "csv"
+        );
+        assert!(registry.for_path(Path::new("tasks")).is_none());
+        assert!(matches!(
+            registry.get("docx"),
+            Err(FormatError::UnknownFormat { .. })
+        ));
+    }
+}
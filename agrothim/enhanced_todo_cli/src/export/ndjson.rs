@@ -0,0 +1,94 @@
+use serde::Serialize;
+use std::io::Write;
+
+use super::{Format, FormatError, RecordWriter, TaskRecord};
+use crate::utils::projection::FieldProjection;
+
+/// Newline-delimited JSON: one task object per line
+pub struct NdjsonFormat;
+
+impl NdjsonFormat {
+    fn render<T: Serialize>(&self, items: &[T]) -> Result<String, FormatError> {
+        let mut output = String::new();
+        for item in items {
+            let line = serde_json::to_string(item).map_err(|e| FormatError::SerializeError {
+                format: self.name().to_string(),
+                message: e.to_string(),
+            })?;
+            output.push_str(&line);
+            output.push('\n');
+        }
+        Ok(output)
+    }
+}
+
+impl Format for NdjsonFormat {
+    fn name(&self) -> &'static str {
+        "ndjson"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ndjson", "jsonl"]
+    }
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError> {
+        self.render(records)
+    }
+
+    fn export_projected(&self, records: &[TaskRecord], projection: &FieldProjection) -> Result<String, FormatError> {
+        let projected = records
+            .iter()
+            .map(|record| {
+                serde_json::to_value(record)
+                    .map(|value| projection.apply(&value))
+                    .map_err(|e| FormatError::SerializeError {
+                        format: self.name().to_string(),
+                        message: e.to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.render(&projected)
+    }
+
+    fn record_writer<'w>(&self, output: &'w mut dyn Write) -> Option<Box<dyn RecordWriter + 'w>> {
+        Some(Box::new(NdjsonWriter { output }))
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| FormatError::ParseError {
+                    format: self.name().to_string(),
+                    message: format!("line {}: {}", index + 1, e),
+                })
+            })
+            .collect()
+    }
+}
+
+struct NdjsonWriter<'w> {
+    output: &'w mut dyn Write,
+}
+
+impl NdjsonWriter<'_> {
+    fn serialize_error(e: impl ToString) -> FormatError {
+        FormatError::SerializeError {
+            format: "ndjson".to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl RecordWriter for NdjsonWriter<'_> {
+    fn write(&mut self, record: &TaskRecord) -> Result<(), FormatError> {
+        serde_json::to_writer(&mut *self.output, record).map_err(Self::serialize_error)?;
+        self.output.write_all(b"\n").map_err(Self::serialize_error)
+    }
+
+    fn finish(&mut self) -> Result<(), FormatError> {
+        self.output.flush().map_err(Self::serialize_error)
+    }
+}
@@ -0,0 +1,309 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::{Format, FormatError, TaskRecord};
+use crate::models::task::{TaskPriority, TaskStatus};
+
+/// TaskWarrior's `task export` JSON, for moving tasks to and from `task`.
+///
+/// TaskWarrior has no notes, contexts or clients, so the closest attributes
+/// stand in: annotations hold the description, the first tag the context and
+/// the project the client; further tags are dropped with a warning. A started
+/// pending task is in progress. The task id is the uuid, so importing a new
+/// export into TaskWarrior updates tasks instead of duplicating them.
+/// Estimates, locations and custom statuses have no counterpart and are left out.
+pub struct TaskWarriorFormat;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pending,
+    Completed,
+    Deleted,
+    Waiting,
+    Recurring,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Annotation {
+    #[serde(default, with = "date")]
+    entry: Option<DateTime<Utc>>,
+    description: String,
+}
+
+/// One task as TaskWarrior writes it; attributes this format does not map,
+/// like `urgency` or user-defined ones, are ignored on import
+#[derive(Debug, Serialize, Deserialize)]
+struct Task {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<Uuid>,
+    description: String,
+    status: Status,
+    #[serde(default, with = "date", skip_serializing_if = "Option::is_none")]
+    entry: Option<DateTime<Utc>>,
+    #[serde(default, with = "date", skip_serializing_if = "Option::is_none")]
+    start: Option<DateTime<Utc>>,
+    #[serde(default, with = "date", skip_serializing_if = "Option::is_none")]
+    end: Option<DateTime<Utc>>,
+    #[serde(default, with = "date", skip_serializing_if = "Option::is_none")]
+    due: Option<DateTime<Utc>>,
+    #[serde(default, with = "date", skip_serializing_if = "Option::is_none")]
+    scheduled: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+}
+
+impl From<&TaskRecord> for Task {
+    fn from(record: &TaskRecord) -> Self {
+        let status = match record.status {
+            TaskStatus::Completed => Status::Completed,
+            _ => Status::Pending,
+        };
+        let priority = match record.priority {
+            TaskPriority::Low => "L",
+            TaskPriority::Medium => "M",
+            TaskPriority::High => "H",
+        };
+        let entry = record.created_at.unwrap_or_else(Utc::now);
+        Self {
+            // The same task keeps its uuid, so TaskWarrior updates it on a re-import
+            uuid: Some(record.id.unwrap_or_else(Uuid::new_v4)),
+            description: record.title.clone(),
+            // TaskWarrior requires an end date on completed tasks
+            end: match status {
+                Status::Completed => Some(record.completed_at.unwrap_or(entry)),
+                _ => None,
+            },
+            status,
+            entry: Some(entry),
+            // Started tasks are the ones in progress; when is not recorded
+            start: (record.status == TaskStatus::InProgress).then_some(entry),
+            due: record.due_date,
+            scheduled: record.start_date,
+            priority: Some(priority.to_string()),
+            project: record.client.clone(),
+            tags: record.context.iter().map(|context| context.trim_start_matches('@').to_string()).collect(),
+            annotations: record
+                .description
+                .iter()
+                .map(|description| Annotation { entry: Some(entry), description: description.clone() })
+                .collect(),
+        }
+    }
+}
+
+impl Task {
+    /// The record for this task; `None` for deleted tasks and recurrence
+    /// templates, whose instances are exported as tasks of their own
+    fn into_record(self) -> Option<TaskRecord> {
+        let status = match self.status {
+            Status::Deleted | Status::Recurring => return None,
+            Status::Completed => TaskStatus::Completed,
+            Status::Pending | Status::Waiting if self.start.is_some() => TaskStatus::InProgress,
+            Status::Pending | Status::Waiting => TaskStatus::Pending,
+        };
+        let priority = match self.priority.as_deref() {
+            Some("H") => TaskPriority::High,
+            Some("L") => TaskPriority::Low,
+            _ => TaskPriority::Medium,
+        };
+        let description = self
+            .annotations
+            .into_iter()
+            .map(|annotation| annotation.description)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut tags = self.tags.into_iter();
+        // A task has one context; the first tag becomes it
+        let context = tags.next().map(|tag| format!("@{}", tag));
+        let dropped: Vec<String> = tags.collect();
+        if !dropped.is_empty() {
+            warn!("Task '{}' keeps only its first tag as context; dropped: {}", self.description, dropped.join(", "));
+        }
+
+        Some(TaskRecord {
+            id: self.uuid,
+            title: self.description,
+            description: Some(description).filter(|description| !description.is_empty()),
+            completed_at: self.end.filter(|_| status == TaskStatus::Completed),
+            status,
+            priority,
+            due_date: self.due,
+            start_date: self.scheduled,
+            estimate_minutes: None,
+            client: self.project,
+            context,
+            location: None,
+            latitude: None,
+            longitude: None,
+            created_at: self.entry,
+        })
+    }
+}
+
+impl Format for TaskWarriorFormat {
+    fn name(&self) -> &'static str {
+        "taskwarrior"
+    }
+
+    /// TaskWarrior exports are plain `.json` files, so the format is only
+    /// picked with `--format taskwarrior`
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError> {
+        let tasks: Vec<Task> = records.iter().map(Task::from).collect();
+        serde_json::to_string_pretty(&tasks).map_err(|e| FormatError::SerializeError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Reads the JSON array `task export` writes, and the one object per
+    /// line of TaskWarrior before 2.4
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError> {
+        let parse_error = |e: serde_json::Error| FormatError::ParseError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        };
+
+        let tasks: Vec<Task> = if input.trim_start().starts_with('[') {
+            serde_json::from_str(input).map_err(parse_error)?
+        } else {
+            input
+                .lines()
+                .map(|line| line.trim().trim_end_matches(','))
+                .filter(|line| !line.is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()
+                .map_err(parse_error)?
+        };
+        Ok(tasks.into_iter().filter_map(Task::into_record).collect())
+    }
+}
+
+/// TaskWarrior's UTC timestamps, e.g. `20261016T190006Z`
+mod date {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    const FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+    pub fn serialize<S: Serializer>(date: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.serialize_str(&date.format(FORMAT).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+        let Some(value) = Option::<String>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        NaiveDateTime::parse_from_str(&value, FORMAT)
+            .map(|date| date.and_utc())
+            .or_else(|_| DateTime::parse_from_rfc3339(&value).map(|date| date.with_timezone(&Utc)))
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("invalid date '{}', expected e.g. 20261016T190006Z", value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_import_taskwarrior_export() {
+        let input = r#"[
+{"id":1,"description":"Repot the fig","entry":"20261001T080000Z","modified":"20261002T090000Z","priority":"H","project":"Garden","status":"pending","start":"20261002T090000Z","tags":["home","weekend"],"uuid":"5f0b7a4e-2a8e-4f59-9b3c-1d2a3b4c5d6e","annotations":[{"entry":"20261001T081000Z","description":"Bigger pot"},{"entry":"20261001T082000Z","description":"Fresh soil"}],"urgency":9.2},
+{"id":0,"description":"Pay rent","end":"20261003T100000Z","entry":"20261001T080000Z","due":"20261003T000000Z","status":"completed","uuid":"0c9d5b0e-6a2f-4a43-8f63-0e2f3c4d5e6f","urgency":0},
+{"id":0,"description":"Old idea","entry":"20261001T080000Z","end":"20261002T080000Z","status":"deleted","uuid":"7e4a4d0a-0d7c-4b8e-9a0f-1f2e3d4c5b6a"}
+]"#;
+
+        let records = TaskWarriorFormat.import(input).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let fig = &records[0];
+        assert_eq!(fig.id, Some(Uuid::parse_str("5f0b7a4e-2a8e-4f59-9b3c-1d2a3b4c5d6e").unwrap()));
+        assert_eq!(fig.title, "Repot the fig");
+        assert_eq!(fig.status, TaskStatus::InProgress);
+        assert_eq!(fig.priority, TaskPriority::High);
+        assert_eq!(fig.description.as_deref(), Some("Bigger pot\nFresh soil"));
+        assert_eq!(fig.context.as_deref(), Some("@home"));
+        assert_eq!(fig.client.as_deref(), Some("Garden"));
+        assert_eq!(fig.created_at, Some(Utc.with_ymd_and_hms(2026, 10, 1, 8, 0, 0).unwrap()));
+
+        let rent = &records[1];
+        assert_eq!(rent.status, TaskStatus::Completed);
+        assert_eq!(rent.priority, TaskPriority::Medium);
+        assert_eq!(rent.due_date, Some(Utc.with_ymd_and_hms(2026, 10, 3, 0, 0, 0).unwrap()));
+        assert_eq!(rent.completed_at, Some(Utc.with_ymd_and_hms(2026, 10, 3, 10, 0, 0).unwrap()));
+
+        // TaskWarrior before 2.4 wrote one object per line
+        let lines = input.lines().filter(|line| line.starts_with('{')).collect::<Vec<_>>().join("\n");
+        assert_eq!(TaskWarriorFormat.import(&lines).unwrap(), records);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let at = |day| Utc.with_ymd_and_hms(2026, 10, day, 12, 30, 0).unwrap();
+        let records = vec![
+            TaskRecord {
+                id: Some(Uuid::new_v4()),
+                title: "Write report".to_string(),
+                description: Some("First draft".to_string()),
+                status: TaskStatus::InProgress,
+                priority: TaskPriority::High,
+                due_date: Some(at(20)),
+                start_date: Some(at(18)),
+                completed_at: None,
+                estimate_minutes: None,
+                client: Some("Acme".to_string()),
+                context: Some("@office".to_string()),
+                location: None,
+                latitude: None,
+                longitude: None,
+                created_at: Some(at(1)),
+            },
+            TaskRecord {
+                id: Some(Uuid::new_v4()),
+                title: "Buy milk".to_string(),
+                description: None,
+                status: TaskStatus::Completed,
+                priority: TaskPriority::Low,
+                due_date: None,
+                start_date: None,
+                completed_at: Some(at(2)),
+                estimate_minutes: None,
+                client: None,
+                context: None,
+                location: None,
+                latitude: None,
+                longitude: None,
+                created_at: Some(at(1)),
+            },
+        ];
+
+        let output = TaskWarriorFormat.export(&records).unwrap();
+        assert!(output.contains(r#""priority": "H""#));
+        assert!(output.contains(r#""due": "20261020T123000Z""#));
+        assert!(output.contains(r#""tags": [
+      "office"
+    ]"#));
+        assert_eq!(TaskWarriorFormat.import(&output).unwrap(), records);
+
+        // Exporting again keeps the uuids TaskWarrior deduplicates by
+        assert_eq!(TaskWarriorFormat.export(&records).unwrap(), output);
+    }
+}
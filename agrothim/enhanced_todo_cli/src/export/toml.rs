@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Format, FormatError, TaskRecord};
+
+/// TOML document with a `[[tasks]]` array of tables (enabled with the `toml` feature)
+pub struct TomlFormat;
+
+// TOML has no top-level arrays, so tasks are wrapped in a table
+#[derive(Serialize, Deserialize)]
+struct TomlDocument {
+    #[serde(default)]
+    tasks: Vec<TaskRecord>,
+}
+
+impl Format for TomlFormat {
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["toml"]
+    }
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError> {
+        let document = TomlDocument {
+            tasks: records.to_vec(),
+        };
+        ::toml::to_string_pretty(&document).map_err(|e| FormatError::SerializeError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError> {
+        ::toml::from_str::<TomlDocument>(input)
+            .map(|document| document.tasks)
+            .map_err(|e| FormatError::ParseError {
+                format: self.name().to_string(),
+                message: e.to_string(),
+            })
+    }
+}
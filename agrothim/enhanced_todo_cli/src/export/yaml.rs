@@ -0,0 +1,28 @@
+use super::{Format, FormatError, TaskRecord};
+
+/// YAML sequence of tasks (enabled with the `yaml` feature)
+pub struct YamlFormat;
+
+impl Format for YamlFormat {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["yaml", "yml"]
+    }
+
+    fn export(&self, records: &[TaskRecord]) -> Result<String, FormatError> {
+        serde_yaml::to_string(records).map_err(|e| FormatError::SerializeError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    fn import(&self, input: &str) -> Result<Vec<TaskRecord>, FormatError> {
+        serde_yaml::from_str(input).map_err(|e| FormatError::ParseError {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+}
@@ -4,5 +4,7 @@ pub mod services;
 pub mod cli;
 pub mod utils;
 pub mod api;
+pub mod export;
+pub mod error;
 
 pub use anyhow::{Error, Result};
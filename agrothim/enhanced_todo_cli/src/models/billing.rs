@@ -0,0 +1,169 @@
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::models::calendar;
+use crate::models::time_entry::TaskTimeSummary;
+
+/// Time tracked on one task, priced at its client's hourly rate
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BillableLine {
+    pub client: String,
+    pub task: String,
+    pub seconds: i64,
+    pub rate: f64,
+    pub amount: f64,
+}
+
+impl BillableLine {
+    pub fn hours(&self) -> f64 {
+        round_cents(self.seconds as f64 / 3600.0)
+    }
+}
+
+/// Billable time of one calendar month, grouped by client
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BillableReport {
+    /// First day of the reported month
+    pub month: NaiveDate,
+    pub currency: String,
+    /// Sorted by client, then by task title
+    pub lines: Vec<BillableLine>,
+    /// Clients without a configured rate; their time is billed at zero
+    pub unrated_clients: Vec<String>,
+}
+
+impl BillableReport {
+    /// Price the time summaries of tasks that have a client. Tasks without a
+    /// client are not billable and are left out.
+    pub fn build(
+        summaries: &[TaskTimeSummary],
+        month: NaiveDate,
+        currency: &str,
+        rate_for: impl Fn(&str) -> Option<f64>,
+    ) -> Self {
+        let mut unrated_clients = Vec::new();
+        let mut lines: Vec<BillableLine> = summaries
+            .iter()
+            .filter_map(|summary| {
+                let client = summary.client.clone()?;
+                let rate = rate_for(&client).unwrap_or_else(|| {
+                    if !unrated_clients.contains(&client) {
+                        unrated_clients.push(client.clone());
+                    }
+                    0.0
+                });
+
+                Some(BillableLine {
+                    amount: round_cents(summary.total_seconds as f64 / 3600.0 * rate),
+                    client,
+                    task: summary.title.clone(),
+                    seconds: summary.total_seconds,
+                    rate,
+                })
+            })
+            .collect();
+
+        lines.sort_by(|a, b| a.client.cmp(&b.client).then_with(|| a.task.cmp(&b.task)));
+        unrated_clients.sort();
+
+        Self {
+            month,
+            currency: currency.to_string(),
+            lines,
+            unrated_clients,
+        }
+    }
+
+    pub fn total_seconds(&self) -> i64 {
+        self.lines.iter().map(|line| line.seconds).sum()
+    }
+
+    pub fn total_amount(&self) -> f64 {
+        round_cents(self.lines.iter().map(|line| line.amount).sum())
+    }
+
+    /// Invoice as CSV: one row per task followed by a total row
+    pub fn to_csv(&self) -> Result<String, csv::Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(["client", "task", "hours", "rate", "amount", "currency"])?;
+
+        for line in &self.lines {
+            writer.write_record([
+                line.client.clone(),
+                line.task.clone(),
+                format!("{:.2}", line.hours()),
+                format!("{:.2}", line.rate),
+                format!("{:.2}", line.amount),
+                self.currency.clone(),
+            ])?;
+        }
+
+        writer.write_record([
+            "Total".to_string(),
+            String::new(),
+            format!("{:.2}", round_cents(self.total_seconds() as f64 / 3600.0)),
+            String::new(),
+            format!("{:.2}", self.total_amount()),
+            self.currency.clone(),
+        ])?;
+
+        let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Start and end (exclusive) of a calendar month in local time
+pub fn month_bounds(month: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let next_month = month.checked_add_months(Months::new(1)).unwrap_or(NaiveDate::MAX);
+    (calendar::start_of_day(month), calendar::start_of_day(next_month))
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn summary(title: &str, client: Option<&str>, total_seconds: i64) -> TaskTimeSummary {
+        TaskTimeSummary {
+            task_id: Uuid::new_v4(),
+            task_number: 1,
+            title: title.to_string(),
+            client: client.map(str::to_string),
+            entry_count: 1,
+            total_seconds,
+        }
+    }
+
+    #[test]
+    fn test_billable_report() {
+        let summaries = vec![
+            summary("Website", Some("globex"), 5400),
+            summary("Personal", None, 7200),
+            summary("API", Some("acme"), 3600),
+            summary("Support", Some("initech"), 1800),
+        ];
+        let month = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+
+        let report = BillableReport::build(&summaries, month, "EUR", |client| match client {
+            "acme" => Some(120.0),
+            "globex" => Some(80.0),
+            _ => None,
+        });
+
+        let clients: Vec<&str> = report.lines.iter().map(|line| line.client.as_str()).collect();
+        assert_eq!(clients, vec!["acme", "globex", "initech"]);
+        assert_eq!(report.lines[1].amount, 120.0);
+        assert_eq!(report.unrated_clients, vec!["initech"]);
+        assert_eq!(report.total_seconds(), 10800);
+        assert_eq!(report.total_amount(), 240.0);
+
+        let csv = report.to_csv().unwrap();
+        assert!(csv.starts_with("client,task,hours,rate,amount,currency\n"));
+        assert!(csv.contains("globex,Website,1.50,80.00,120.00,EUR\n"));
+        assert!(csv.ends_with("Total,,3.00,,240.00,EUR\n"));
+    }
+}
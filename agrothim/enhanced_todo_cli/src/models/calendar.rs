@@ -0,0 +1,189 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+
+// Due and start dates are entered as calendar days in the user's timezone.
+// They are stored as the last (due) or first (start) instant of that day, and
+// compared by local day so that "overdue" flips at the user's midnight rather
+// than at midnight UTC. The user's timezone travels with the work done for
+// them: the CLI runs each command in its `timezone` setting, the API each
+// request in the timezone the client names, and anything else falls back to
+// the system's.
+
+/// Timezone calendar days are cut in
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UserTimezone {
+    /// The system's timezone
+    #[default]
+    System,
+    /// An IANA timezone, e.g. Europe/Berlin
+    Named(Tz),
+}
+
+impl UserTimezone {
+    /// Wall-clock time of an instant in this timezone
+    pub fn local_time(&self, at: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            UserTimezone::System => at.with_timezone(&Local).fixed_offset(),
+            UserTimezone::Named(tz) => at.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// The local calendar day an instant falls on in this timezone
+    pub fn local_date(&self, at: &DateTime<Utc>) -> NaiveDate {
+        self.local_time(at).date_naive()
+    }
+
+    /// IANA name of this timezone, for databases that cut days themselves;
+    /// `None` when the system's timezone cannot be named
+    pub fn iana_name(&self) -> Option<String> {
+        match self {
+            UserTimezone::System => iana_time_zone::get_timezone().ok().filter(|name| name.parse::<Tz>().is_ok()),
+            UserTimezone::Named(tz) => Some(tz.name().to_string()),
+        }
+    }
+
+    /// The instant a wall-clock time falls on in this timezone, the earlier
+    /// one when a DST change repeats it; `None` when a DST change skips it
+    pub fn resolve(&self, local: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            UserTimezone::System => Local.from_local_datetime(local).earliest().map(|at| at.with_timezone(&Utc)),
+            UserTimezone::Named(tz) => tz.from_local_datetime(local).earliest().map(|at| at.with_timezone(&Utc)),
+        }
+    }
+
+    /// First instant of a day in this timezone
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_time(NaiveTime::MIN);
+        // A DST change can skip local midnight; the day then starts an hour later
+        self.resolve(&midnight)
+            .or_else(|| self.resolve(&(midnight + Duration::hours(1))))
+            .unwrap_or_else(|| midnight.and_utc())
+    }
+}
+
+impl FromStr for UserTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "local" | "system" => Ok(UserTimezone::System),
+            name => name
+                .parse::<Tz>()
+                .map(UserTimezone::Named)
+                .map_err(|_| format!("Unknown timezone '{}'. Use an IANA name like Europe/Berlin or UTC, or local", name)),
+        }
+    }
+}
+
+impl fmt::Display for UserTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserTimezone::System => write!(f, "local"),
+            UserTimezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+tokio::task_local! {
+    static TIMEZONE: UserTimezone;
+}
+
+/// Run `work` with days cut in `timezone`. The timezone is scoped to the
+/// task, so concurrent requests for users in different timezones never see
+/// each other's. Tasks `work` spawns do not inherit it: spawn them wrapped
+/// in `with_timezone(timezone(), ...)`.
+pub async fn with_timezone<F: Future>(timezone: UserTimezone, work: F) -> F::Output {
+    TIMEZONE.scope(timezone, work).await
+}
+
+/// The user's timezone: the one the current work runs in, the system's
+/// outside of any
+pub fn timezone() -> UserTimezone {
+    TIMEZONE.try_with(|timezone| *timezone).unwrap_or_default()
+}
+
+/// The user's current local date
+pub fn today() -> NaiveDate {
+    local_date(&Utc::now())
+}
+
+/// The user's current wall-clock time
+pub fn now() -> NaiveDateTime {
+    local_time(&Utc::now()).naive_local()
+}
+
+/// Wall-clock time of an instant in the user's timezone
+pub fn local_time(at: &DateTime<Utc>) -> DateTime<FixedOffset> {
+    timezone().local_time(at)
+}
+
+/// The local calendar day an instant falls on
+pub fn local_date(at: &DateTime<Utc>) -> NaiveDate {
+    timezone().local_date(at)
+}
+
+/// First instant of a local day
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    timezone().start_of_day(date)
+}
+
+/// Last second of a local day, used as the instant a task is due
+pub fn end_of_day(date: NaiveDate) -> DateTime<Utc> {
+    start_of_day(date + Duration::days(1)) - Duration::seconds(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_bounds_stay_on_the_local_day() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+
+        assert_eq!(local_date(&start_of_day(date)), date);
+        assert_eq!(local_date(&end_of_day(date)), date);
+        assert_eq!(local_date(&(end_of_day(date) + Duration::seconds(1))), date + Duration::days(1));
+        assert_eq!(local_date(&(start_of_day(date) - Duration::seconds(1))), date - Duration::days(1));
+    }
+
+    #[test]
+    fn test_named_timezone_cuts_days_at_its_midnight() {
+        let auckland: UserTimezone = "Pacific/Auckland".parse().unwrap();
+        assert_eq!(auckland.to_string(), "Pacific/Auckland");
+        assert_eq!("local".parse::<UserTimezone>(), Ok(UserTimezone::System));
+        assert!("Mars/Olympus".parse::<UserTimezone>().is_err());
+
+        // Daylight saving time, UTC+13
+        let date = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let start = auckland.start_of_day(date);
+        assert_eq!(start, Utc.with_ymd_and_hms(2026, 3, 13, 11, 0, 0).unwrap());
+        assert_eq!(auckland.local_time(&start).date_naive(), date);
+        assert_eq!(auckland.local_time(&(start - Duration::seconds(1))).date_naive(), date - Duration::days(1));
+    }
+
+    #[tokio::test]
+    async fn test_timezone_is_scoped_to_the_work_it_runs() {
+        let auckland: UserTimezone = "Pacific/Auckland".parse().unwrap();
+        let honolulu: UserTimezone = "Pacific/Honolulu".parse().unwrap();
+        // 12:00 UTC is already the next day in Auckland, still this one in Honolulu
+        let at = Utc.with_ymd_and_hms(2026, 3, 14, 12, 0, 0).unwrap();
+
+        let (east, west) = tokio::join!(
+            with_timezone(auckland, async move {
+                tokio::task::yield_now().await;
+                (timezone(), local_date(&at))
+            }),
+            with_timezone(honolulu, async move {
+                tokio::task::yield_now().await;
+                (timezone(), local_date(&at))
+            }),
+        );
+
+        assert_eq!(east, (auckland, NaiveDate::from_ymd_opt(2026, 3, 15).unwrap()));
+        assert_eq!(west, (honolulu, NaiveDate::from_ymd_opt(2026, 3, 14).unwrap()));
+        assert_eq!(timezone(), UserTimezone::System);
+    }
+}
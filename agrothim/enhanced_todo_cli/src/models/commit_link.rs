@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A commit `git scan` completed a task from
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CommitLink {
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    /// Full hash of the commit
+    pub commit_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CommitLink {
+    /// The abbreviated hash git shows by default
+    pub fn short_hash(&self) -> &str {
+        &self.commit_hash[..self.commit_hash.len().min(7)]
+    }
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A login on one device. The local session file holds its tokens; this
+/// record lets the user see and revoke it from anywhere.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeviceSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Where the login happened, normally the host name
+    pub name: String,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DeviceSession {
+    /// Short form of the ID shown in listings and accepted by `auth sessions revoke`
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+}
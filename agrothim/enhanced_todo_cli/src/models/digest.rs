@@ -0,0 +1,322 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::calendar;
+use crate::models::task::{Task, TaskPriority, TaskStatus};
+
+/// How many task titles a sentence names before summarizing the rest
+const TITLES_PER_SENTENCE: usize = 3;
+
+/// A user's daily digest email, sent at `send_at` server-local time
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct DigestSubscription {
+    pub user_id: Uuid,
+    pub send_at: NaiveTime,
+    /// Day of the last digest sent, so each day gets one
+    pub last_sent_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The tasks that matter for one day, for a spoken or notification-sized summary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyDigest {
+    pub date: NaiveDate,
+    /// Open tasks due on `date`, high priority first
+    pub due_today: Vec<String>,
+    /// How many of `due_today` are high priority
+    pub high_priority_today: usize,
+    /// Open tasks due before `date`, oldest first
+    pub overdue: Vec<String>,
+    /// Tasks in progress that are not already due or overdue
+    pub in_progress: Vec<String>,
+    pub completed_today: usize,
+    /// Tasks completed on the day before `date`, for the morning email
+    pub completed_yesterday: Vec<String>,
+    /// Summed estimates of the open tasks due today or overdue
+    pub estimate_minutes: i64,
+}
+
+impl DailyDigest {
+    pub fn from_tasks(tasks: &[Task], date: NaiveDate) -> Self {
+
+        let mut due_today: Vec<&Task> = Vec::new();
+        let mut overdue: Vec<&Task> = Vec::new();
+        let mut in_progress = Vec::new();
+        let mut completed_today = 0;
+        let mut completed_yesterday = Vec::new();
+
+        for task in tasks {
+            if task.is_completed() {
+                let completed_on = task.completed_at.as_ref().map(calendar::local_date);
+                if completed_on == Some(date) {
+                    completed_today += 1;
+                } else if completed_on == date.pred_opt() {
+                    completed_yesterday.push(task.title.clone());
+                }
+                continue;
+            }
+
+            match task.due_day() {
+                Some(day) if day == date => due_today.push(task),
+                Some(day) if day < date => overdue.push(task),
+                _ if matches!(task.status, TaskStatus::InProgress) => in_progress.push(task.title.clone()),
+                _ => {}
+            }
+        }
+
+        // Stable sorts keep the manual order within each priority
+        due_today.sort_by_key(|task| std::cmp::Reverse(task.priority as i16));
+        overdue.sort_by_key(|task| task.due_date);
+
+        let estimate_minutes = due_today
+            .iter()
+            .chain(&overdue)
+            .filter_map(|task| task.estimate_minutes)
+            .map(i64::from)
+            .sum();
+        let titles = |tasks: Vec<&Task>| tasks.into_iter().map(|task| task.title.clone()).collect();
+
+        Self {
+            date,
+            high_priority_today: due_today.iter().filter(|task| task.priority == TaskPriority::High).count(),
+            due_today: titles(due_today),
+            overdue: titles(overdue),
+            in_progress,
+            completed_today,
+            completed_yesterday,
+            estimate_minutes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.due_today.is_empty() && self.overdue.is_empty() && self.in_progress.is_empty() && self.completed_today == 0
+    }
+
+    /// Whether the morning email would have anything to say
+    pub fn has_email_content(&self) -> bool {
+        !(self.due_today.is_empty() && self.overdue.is_empty() && self.completed_yesterday.is_empty())
+    }
+
+    pub fn email_subject(&self) -> String {
+        format!(
+            "Your tasks for {}: {} due, {} overdue",
+            self.date.format("%a, %b %-d"),
+            self.due_today.len(),
+            self.overdue.len()
+        )
+    }
+
+    /// The morning email: every task due today and overdue, and what got
+    /// done yesterday, as plain-text lists
+    pub fn email_body(&self) -> String {
+        let mut body = format!("Here are your tasks for {}.\n", self.date.format("%A, %B %-d"));
+        let sections = [
+            ("Due today", &self.due_today),
+            ("Overdue", &self.overdue),
+            ("Completed yesterday", &self.completed_yesterday),
+        ];
+        for (heading, titles) in sections {
+            if titles.is_empty() {
+                continue;
+            }
+            body.push_str(&format!("\n{} ({})\n", heading, titles.len()));
+            for title in titles.iter() {
+                body.push_str(&format!("  - {}\n", title));
+            }
+        }
+        if self.estimate_minutes > 0 {
+            body.push_str(&format!("\nEstimated work due: {}.\n", spoken_duration(self.estimate_minutes)));
+        }
+        body.push_str("\nRun `todo-cli digest disable` to stop these emails.");
+        body
+    }
+
+    /// Plain sentences without colors or symbols, so the output can be
+    /// piped into text-to-speech or a notification
+    pub fn render(&self) -> String {
+        let mut sentences = vec![format!("Here is your summary for {}.", self.date.format("%A, %B %-d"))];
+
+        if self.is_empty() {
+            sentences.push("You have nothing scheduled today.".to_string());
+            return sentences.join(" ");
+        }
+
+        match self.due_today.len() {
+            0 => sentences.push("Nothing is due today.".to_string()),
+            count => {
+                let high_priority = match self.high_priority_today {
+                    0 => String::new(),
+                    high if high == count => ", all high priority".to_string(),
+                    high => format!(", {} of them high priority", high),
+                };
+                sentences.push(format!(
+                    "You have {} due today{}: {}.",
+                    count_noun(count, "task"),
+                    high_priority,
+                    list_titles(&self.due_today)
+                ));
+            }
+        }
+
+        if let Some(oldest) = self.overdue.first() {
+            sentences.push(match self.overdue.len() {
+                1 => format!("One task is overdue: {}.", oldest),
+                count => format!("{} tasks are overdue, the oldest is {}.", count, oldest),
+            });
+        }
+
+        if !self.in_progress.is_empty() {
+            sentences.push(format!("Still in progress: {}.", list_titles(&self.in_progress)));
+        }
+
+        if self.estimate_minutes > 0 {
+            sentences.push(format!("That is about {} of estimated work.", spoken_duration(self.estimate_minutes)));
+        }
+
+        if self.completed_today > 0 {
+            sentences.push(format!("You already completed {} today.", count_noun(self.completed_today, "task")));
+        }
+
+        sentences.join(" ")
+    }
+}
+
+/// "A, B and C", naming at most `TITLES_PER_SENTENCE` titles
+fn list_titles(titles: &[String]) -> String {
+    let named = &titles[..titles.len().min(TITLES_PER_SENTENCE)];
+    let remaining = titles.len() - named.len();
+
+    let mut parts: Vec<String> = named.to_vec();
+    if remaining > 0 {
+        parts.push(format!("{} more", remaining));
+    }
+
+    match parts.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
+
+fn count_noun(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("one {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn spoken_duration(minutes: i64) -> String {
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let hours_part = (hours > 0).then(|| if hours == 1 { "1 hour".to_string() } else { format!("{} hours", hours) });
+    let minutes_part = (minutes > 0).then(|| if minutes == 1 { "1 minute".to_string() } else { format!("{} minutes", minutes) });
+
+    match (hours_part, minutes_part) {
+        (Some(hours), Some(minutes)) => format!("{} and {}", hours, minutes),
+        (Some(part), None) | (None, Some(part)) => part,
+        (None, None) => "no time".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::StoreTaskRequest;
+    use chrono::{Duration, Local, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn task(title: &str, due_in_days: Option<i64>, priority: TaskPriority, status: TaskStatus) -> Task {
+        let today = Local::now().date_naive();
+        let mut task = Task::new(
+            StoreTaskRequest {
+                title: title.to_string(),
+                priority,
+                status,
+                estimate_minutes: Some(45),
+                ..Default::default()
+            },
+            Uuid::new_v4(),
+        )
+        .unwrap();
+        task.due_date = due_in_days.map(|days| {
+            let noon = (today + Duration::days(days)).and_hms_opt(12, 0, 0).unwrap();
+            Local.from_local_datetime(&noon).unwrap().with_timezone(&Utc)
+        });
+        task
+    }
+
+    #[test]
+    fn test_daily_digest() {
+        let today = Local::now().date_naive();
+        let mut done = task("Water plants", None, TaskPriority::Low, TaskStatus::Pending);
+        done.complete();
+
+        let tasks = vec![
+            task("Call Bob", Some(0), TaskPriority::Medium, TaskStatus::Pending),
+            task("Pay rent", Some(0), TaskPriority::High, TaskStatus::Pending),
+            task("Renew passport", Some(-5), TaskPriority::Low, TaskStatus::Pending),
+            task("File taxes", Some(-1), TaskPriority::High, TaskStatus::Pending),
+            task("Refactor parser", None, TaskPriority::Medium, TaskStatus::InProgress),
+            task("Plan trip", Some(3), TaskPriority::Medium, TaskStatus::Pending),
+            done,
+        ];
+
+        let digest = DailyDigest::from_tasks(&tasks, today);
+        assert_eq!(digest.due_today, vec!["Pay rent", "Call Bob"]);
+        assert_eq!(digest.overdue, vec!["Renew passport", "File taxes"]);
+        assert_eq!(digest.estimate_minutes, 180);
+
+        let text = digest.render();
+        assert!(text.contains("You have 2 tasks due today, 1 of them high priority: Pay rent and Call Bob."));
+        assert!(text.contains("2 tasks are overdue, the oldest is Renew passport."));
+        assert!(text.contains("Still in progress: Refactor parser."));
+        assert!(text.contains("about 3 hours of estimated work"));
+        assert!(text.contains("You already completed one task today."));
+        assert!(digest.completed_yesterday.is_empty());
+    }
+
+    #[test]
+    fn test_email_digest() {
+        let today = Local::now().date_naive();
+        let mut yesterday = task("Water plants", None, TaskPriority::Low, TaskStatus::Pending);
+        yesterday.complete();
+        yesterday.completed_at = Some(calendar::end_of_day(today - Duration::days(1)));
+        let mut earlier = task("Sweep porch", None, TaskPriority::Low, TaskStatus::Pending);
+        earlier.complete();
+        earlier.completed_at = Some(calendar::end_of_day(today - Duration::days(2)));
+
+        let tasks = vec![
+            task("Pay rent", Some(0), TaskPriority::High, TaskStatus::Pending),
+            task("File taxes", Some(-1), TaskPriority::High, TaskStatus::Pending),
+            task("Plan trip", Some(3), TaskPriority::Medium, TaskStatus::Pending),
+            yesterday,
+            earlier,
+        ];
+
+        let digest = DailyDigest::from_tasks(&tasks, today);
+        assert_eq!(digest.completed_yesterday, vec!["Water plants"]);
+        assert!(digest.has_email_content());
+        assert!(digest.email_subject().ends_with(": 1 due, 1 overdue"));
+
+        let body = digest.email_body();
+        assert!(body.contains("\nDue today (1)\n  - Pay rent\n"));
+        assert!(body.contains("\nOverdue (1)\n  - File taxes\n"));
+        assert!(body.contains("\nCompleted yesterday (1)\n  - Water plants\n"));
+        assert!(!body.contains("Plan trip"));
+        assert!(!body.contains("Sweep porch"));
+
+        assert!(!DailyDigest::from_tasks(&[], today).has_email_content());
+    }
+
+    #[test]
+    fn test_list_titles_and_empty_digest() {
+        let titles: Vec<String> = ["A", "B", "C", "D", "E"].iter().map(|t| t.to_string()).collect();
+        assert_eq!(list_titles(&titles[..1]), "A");
+        assert_eq!(list_titles(&titles[..3]), "A, B and C");
+        assert_eq!(list_titles(&titles), "A, B, C and 2 more");
+
+        let digest = DailyDigest::from_tasks(&[], Local::now().date_naive());
+        assert!(digest.render().ends_with("You have nothing scheduled today."));
+    }
+}
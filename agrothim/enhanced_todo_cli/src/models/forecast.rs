@@ -0,0 +1,106 @@
+use chrono::{Days, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::models::report::DayCount;
+
+/// When the open backlog would be cleared at the completion velocity of the
+/// last weeks, ignoring tasks still to be created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Forecast {
+    /// Open tasks now
+    pub open: i64,
+    /// Tasks completed in each of the trailing weeks, oldest first
+    pub weekly_completions: Vec<i64>,
+    /// Mean tasks completed per day over those weeks
+    pub velocity: f64,
+    /// Day the backlog is cleared at the mean velocity, `None` when nothing was completed
+    pub expected: Option<NaiveDate>,
+    /// At the pace of the best week
+    pub optimistic: Option<NaiveDate>,
+    /// At the pace of the slowest week, `None` when a week had no completions
+    pub pessimistic: Option<NaiveDate>,
+}
+
+impl Forecast {
+    /// First day of the `weeks` weeks ending `today`
+    pub fn first_day(weeks: u32, today: NaiveDate) -> NaiveDate {
+        today - Duration::days(i64::from(weeks.max(1)) * 7 - 1)
+    }
+
+    /// Forecast from `completed`, the completions per day of the `weeks`
+    /// weeks ending `today`; days outside them are ignored
+    pub fn new(open: i64, completed: &[DayCount], weeks: u32, today: NaiveDate) -> Self {
+        let first = Self::first_day(weeks, today);
+        let mut weekly_completions = vec![0; weeks.max(1) as usize];
+        for day in completed {
+            let week = (day.date - first).num_days().div_euclid(7);
+            if let Some(count) = usize::try_from(week).ok().and_then(|week| weekly_completions.get_mut(week)) {
+                *count += day.count;
+            }
+        }
+
+        let per_day = |completions: i64| completions as f64 / 7.0;
+        let total: i64 = weekly_completions.iter().sum();
+        let velocity = total as f64 / (weekly_completions.len() * 7) as f64;
+        let best = weekly_completions.iter().copied().max().unwrap_or(0);
+        let slowest = weekly_completions.iter().copied().min().unwrap_or(0);
+
+        Self {
+            open,
+            expected: cleared_on(open, velocity, today),
+            optimistic: cleared_on(open, per_day(best), today),
+            pessimistic: cleared_on(open, per_day(slowest), today),
+            weekly_completions,
+            velocity,
+        }
+    }
+}
+
+fn cleared_on(open: i64, per_day: f64, today: NaiveDate) -> Option<NaiveDate> {
+    if open <= 0 {
+        return Some(today);
+    }
+    if per_day <= 0.0 {
+        return None;
+    }
+    today.checked_add_days(Days::new((open as f64 / per_day).ceil() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap()
+    }
+
+    #[test]
+    fn test_forecast_bands() {
+        // Two weeks ending the 14th: 7 completions, then 14
+        let completed = [
+            DayCount { date: date(1), count: 7 },
+            DayCount { date: date(10), count: 10 },
+            DayCount { date: date(14), count: 4 },
+            // Before the window
+            DayCount { date: date(1) - Duration::days(1), count: 50 },
+        ];
+        let forecast = Forecast::new(30, &completed, 2, date(14));
+
+        assert_eq!(forecast.weekly_completions, vec![7, 14]);
+        assert_eq!(forecast.velocity, 1.5);
+        assert_eq!(forecast.expected, Some(date(14) + Duration::days(20)));
+        assert_eq!(forecast.optimistic, Some(date(14) + Duration::days(15)));
+        assert_eq!(forecast.pessimistic, Some(date(14) + Duration::days(30)));
+    }
+
+    #[test]
+    fn test_forecast_without_velocity() {
+        let forecast = Forecast::new(3, &[DayCount { date: date(14), count: 2 }], 2, date(14));
+        assert_eq!(forecast.weekly_completions, vec![0, 2]);
+        assert!(forecast.expected.is_some());
+        assert_eq!(forecast.pessimistic, None);
+
+        assert_eq!(Forecast::new(3, &[], 4, date(14)).expected, None);
+        assert_eq!(Forecast::new(0, &[], 4, date(14)).expected, Some(date(14)));
+    }
+}
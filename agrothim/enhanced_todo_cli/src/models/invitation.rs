@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A code that lets any user join a workspace until it expires, is revoked
+/// or runs out of uses. The code is shown once when it is created; only its
+/// SHA-256 hash is stored.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub created_by: Uuid,
+    /// Start of the code, enough to recognise it in listings
+    pub prefix: String,
+    #[allow(dead_code)]
+    #[serde(skip)]
+    pub code_hash: String,
+    pub expires_at: DateTime<Utc>,
+    /// Number of users who may join with the code; unlimited if `None`
+    pub max_uses: Option<i32>,
+    pub use_count: i32,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    pub fn is_used_up(&self) -> bool {
+        self.max_uses.is_some_and(|max_uses| self.use_count >= max_uses)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && !self.is_expired() && !self.is_used_up()
+    }
+}
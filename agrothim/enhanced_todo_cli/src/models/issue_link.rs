@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// A GitHub repository as `owner/name`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepoName(String);
+
+impl RepoName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RepoName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for RepoName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid_part = |part: &str| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        match s.trim().split_once('/') {
+            Some((owner, name)) if valid_part(owner) && valid_part(name) && s.trim().len() <= 140 => {
+                Ok(Self(format!("{}/{}", owner, name)))
+            }
+            _ => Err(format!("invalid repository '{}': expected owner/name, e.g. rust-lang/rust", s)),
+        }
+    }
+}
+
+/// A task `sync github` created from an issue
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IssueLink {
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub repository: String,
+    pub issue_number: i32,
+    /// When the issue was closed because the task was completed
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_name() {
+        assert_eq!("rust-lang/rust".parse::<RepoName>().unwrap().as_str(), "rust-lang/rust");
+        assert_eq!(" owner/some.repo_2 ".parse::<RepoName>().unwrap().to_string(), "owner/some.repo_2");
+        for invalid in ["rust", "/rust", "rust-lang/", "a/b/c", "owner/na me"] {
+            assert!(invalid.parse::<RepoName>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// A WGS84 coordinate pair, written as "lat,lon"
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, String> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(format!("Latitude {} must be between -90 and 90", latitude));
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(format!("Longitude {} must be between -180 and 180", longitude));
+        }
+        Ok(Self { latitude, longitude })
+    }
+
+    /// Great-circle distance using the haversine formula
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.6},{:.6}", self.latitude, self.longitude)
+    }
+}
+
+impl FromStr for GeoPoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid coordinates '{}'. Use LAT,LON (e.g. 52.52,13.405)", s);
+        let (latitude, longitude) = s.split_once(',').ok_or_else(invalid)?;
+        let latitude = latitude.trim().parse().map_err(|_| invalid())?;
+        let longitude = longitude.trim().parse().map_err(|_| invalid())?;
+        Self::new(latitude, longitude)
+    }
+}
+
+/// Filter for tasks at or around a place
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearFilter {
+    /// Location label to match, case-insensitively
+    pub label: String,
+    /// Coordinates of the named location, if configured
+    pub point: Option<GeoPoint>,
+    pub radius_km: f64,
+}
+
+impl NearFilter {
+    /// A task matches when its label equals the filter's label, or when both
+    /// have coordinates and the task lies within the radius
+    pub fn matches(&self, label: Option<&str>, point: Option<GeoPoint>) -> bool {
+        if label.is_some_and(|label| label.eq_ignore_ascii_case(&self.label)) {
+            return true;
+        }
+
+        match (self.point, point) {
+            (Some(center), Some(point)) => center.distance_km(&point) <= self.radius_km,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geo_point_parsing_and_distance() {
+        let berlin: GeoPoint = "52.5200, 13.4050".parse().unwrap();
+        let potsdam: GeoPoint = "52.3906,13.0645".parse().unwrap();
+        assert!((berlin.distance_km(&potsdam) - 26.9).abs() < 0.5);
+
+        assert!("91,0".parse::<GeoPoint>().is_err());
+        assert!("52.52".parse::<GeoPoint>().is_err());
+    }
+
+    #[test]
+    fn test_near_filter() {
+        let office = NearFilter {
+            label: "office".to_string(),
+            point: Some(GeoPoint::new(52.5200, 13.4050).unwrap()),
+            radius_km: 1.0,
+        };
+
+        assert!(office.matches(Some("Office"), None));
+        assert!(office.matches(None, Some(GeoPoint::new(52.5210, 13.4100).unwrap())));
+        assert!(!office.matches(Some("home"), Some(GeoPoint::new(52.3906, 13.0645).unwrap())));
+        assert!(!office.matches(None, None));
+    }
+}
@@ -1,3 +1,31 @@
 pub mod user;
 pub mod task;
-
+pub mod task_ref;
+pub mod task_column;
+pub mod status;
+pub mod time_entry;
+pub mod workload;
+pub mod webhook;
+pub mod billing;
+pub mod calendar;
+pub mod location;
+pub mod digest;
+pub mod password_reset;
+pub mod email_verification;
+pub mod user_identity;
+pub mod api_key;
+pub mod device_session;
+pub mod quota;
+pub mod password_strength;
+pub mod workspace;
+pub mod task_share;
+pub mod invitation;
+pub mod task_event;
+pub mod task_change;
+pub mod sync;
+pub mod issue_link;
+pub mod commit_link;
+pub mod reminder;
+pub mod report;
+pub mod forecast;
+pub mod schedule;
\ No newline at end of file
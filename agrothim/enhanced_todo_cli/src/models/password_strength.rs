@@ -0,0 +1,321 @@
+//! A small zxcvbn-style password strength estimator.
+//!
+//! The password is split into the cheapest sequence of guessable patterns
+//! (common passwords and words, the user's own name or email, keyboard runs,
+//! sequences, repeats, years), with anything left over brute forced. The
+//! estimated number of guesses gives a 0-4 score.
+
+/// Scores below this are rejected outright
+pub const MIN_ACCEPTABLE_SCORE: u8 = 1;
+
+/// Guesses per brute-forced character, as in zxcvbn
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+
+/// Score boundaries in log10(guesses)
+const SCORE_THRESHOLDS: [f64; 4] = [3.0, 6.0, 8.0, 10.0];
+
+const METER_WIDTH: usize = 4;
+
+/// Most common passwords and password words, most common first
+const COMMON_WORDS: &[&str] = &[
+    "password", "123456", "qwerty", "letmein", "welcome", "admin", "iloveyou", "monkey", "dragon", "football",
+    "baseball", "sunshine", "princess", "master", "shadow", "superman", "michael", "trustno", "login", "starwars",
+    "hello", "freedom", "whatever", "charlie", "batman", "secret", "summer", "winter", "spring", "autumn",
+    "flower", "computer", "internet", "soccer", "hockey", "killer", "pepper", "ginger", "cookie", "cheese",
+    "love", "pass", "user", "test", "guest", "root", "changeme", "default", "todo", "access",
+];
+
+const KEYBOARD_ROWS: &[&str] = &["qwertyuiop", "asdfghjkl", "zxcvbnm", "1234567890", "qazwsx", "wsxedc"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternKind {
+    CommonWord { leet: bool, capitalized: bool },
+    UserInput,
+    Keyboard,
+    Sequence,
+    Repeat,
+    Year,
+    Bruteforce,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pattern {
+    kind: PatternKind,
+    start: usize,
+    end: usize,
+    log10_guesses: f64,
+}
+
+/// Estimated strength of a password, with advice for improving it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordStrength {
+    /// 0 (very weak) to 4 (very strong)
+    pub score: u8,
+    pub log10_guesses: f64,
+    /// What makes the password easy to guess, if anything stands out
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl PasswordStrength {
+    /// Estimate `password`'s strength; `user_inputs` are values like the
+    /// username and email that an attacker would try first
+    pub fn estimate(password: &str, user_inputs: &[&str]) -> Self {
+        let chars: Vec<char> = password.chars().collect();
+        let patterns = find_patterns(&chars, user_inputs);
+        let (log10_guesses, cover) = cheapest_cover(chars.len(), &patterns);
+
+        let score = SCORE_THRESHOLDS.iter().filter(|&&threshold| log10_guesses >= threshold).count() as u8;
+        let (warning, suggestions) = feedback(score, chars.len(), &cover);
+
+        Self {
+            score,
+            log10_guesses,
+            warning,
+            suggestions,
+        }
+    }
+
+    pub fn is_acceptable(&self) -> bool {
+        self.score >= MIN_ACCEPTABLE_SCORE
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self.score {
+            0 => "very weak",
+            1 => "weak",
+            2 => "fair",
+            3 => "strong",
+            _ => "very strong",
+        }
+    }
+
+    /// e.g. "[██░░] fair"
+    pub fn meter(&self) -> String {
+        let filled = usize::from(self.score).min(METER_WIDTH);
+        format!("[{}{}] {}", "█".repeat(filled), "░".repeat(METER_WIDTH - filled), self.label())
+    }
+
+    /// One line explaining a rejection, for validation errors and prompts
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("Password is {}", self.label())];
+        parts.extend(self.warning.clone());
+        parts.extend(self.suggestions.first().cloned());
+        parts.join(". ")
+    }
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' => 't',
+        _ => c.to_ascii_lowercase(),
+    }
+}
+
+fn find_patterns(chars: &[char], user_inputs: &[&str]) -> Vec<Pattern> {
+    let lower: String = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let unleeted: String = chars.iter().map(|&c| unleet(c)).collect();
+    let mut patterns = Vec::new();
+    let mut add = |kind, start, end, guesses: f64| {
+        patterns.push(Pattern {
+            kind,
+            start,
+            end,
+            log10_guesses: guesses.max(1.0).log10(),
+        })
+    };
+
+    for start in 0..chars.len() {
+        for end in start + 1..=chars.len() {
+            let len = end - start;
+            let word: String = lower.chars().skip(start).take(len).collect();
+            let plain: String = unleeted.chars().skip(start).take(len).collect();
+            let capitalized = chars[start..end].iter().any(char::is_ascii_uppercase);
+            let variations = if capitalized { 2.0 } else { 1.0 };
+
+            if let Some(rank) = COMMON_WORDS.iter().position(|&common| common == word || common == plain) {
+                let leet = COMMON_WORDS[rank] != word;
+                let leet_factor = if leet { 2.0 } else { 1.0 };
+                add(PatternKind::CommonWord { leet, capitalized }, start, end, (rank + 1) as f64 * variations * leet_factor);
+            }
+
+            if len >= 3
+                && user_inputs
+                    .iter()
+                    .flat_map(|input| input.split(['@', '.', '_', '-', ' ']))
+                    .any(|part| part.eq_ignore_ascii_case(&word) || part.eq_ignore_ascii_case(&plain))
+            {
+                add(PatternKind::UserInput, start, end, variations);
+            }
+
+            if len >= 4 && KEYBOARD_ROWS.iter().any(|row| row.contains(&word) || row.chars().rev().collect::<String>().contains(&word)) {
+                add(PatternKind::Keyboard, start, end, 10.0 * len as f64 * variations);
+            }
+
+            if len >= 3 && is_sequence(&chars[start..end]) {
+                add(PatternKind::Sequence, start, end, 4.0 * len as f64);
+            }
+
+            if len >= 3 && chars[start..end].iter().all(|&c| c == chars[start]) {
+                add(PatternKind::Repeat, start, end, BRUTEFORCE_CARDINALITY * len as f64);
+            }
+
+            if len == 4 && matches!(word.parse::<u32>(), Ok(1900..=2039)) {
+                add(PatternKind::Year, start, end, 20.0);
+            }
+        }
+    }
+
+    patterns
+}
+
+fn is_sequence(chars: &[char]) -> bool {
+    let step = chars[1] as i32 - chars[0] as i32;
+    step.abs() == 1 && chars.windows(2).all(|pair| pair[1] as i32 - pair[0] as i32 == step)
+}
+
+/// Total log10(guesses) of a list of patterns covering part of the password
+type Cover = (f64, Vec<Pattern>);
+
+/// The cheapest way to cover the whole password with patterns and brute
+/// force, in log10(guesses). Like zxcvbn, every extra pattern multiplies the
+/// guesses by the number of patterns so far, since an attacker must also
+/// guess how the patterns are combined.
+fn cheapest_cover(len: usize, patterns: &[Pattern]) -> Cover {
+    if len == 0 {
+        return (0.0, Vec::new());
+    }
+
+    // best[end][count]: cheapest cover of chars[..end] using `count` patterns
+    let mut best: Vec<Vec<Option<Cover>>> = vec![vec![None; len + 1]; len + 1];
+    best[0][0] = Some((0.0, Vec::new()));
+
+    for end in 1..=len {
+        let bruteforce = (0..end).map(|start| Pattern {
+            kind: PatternKind::Bruteforce,
+            start,
+            end,
+            log10_guesses: ((end - start) as f64 * BRUTEFORCE_CARDINALITY.log10()).max(1.0),
+        });
+        let candidates: Vec<Pattern> = patterns.iter().copied().filter(|pattern| pattern.end == end).chain(bruteforce).collect();
+
+        for pattern in candidates {
+            for count in 0..len {
+                let Some((cost, cover)) = &best[pattern.start][count] else {
+                    continue;
+                };
+                let cost = cost + pattern.log10_guesses + ((count + 1) as f64).log10();
+                if best[end][count + 1].as_ref().is_none_or(|(current, _)| cost < *current) {
+                    let mut cover = cover.clone();
+                    cover.push(pattern);
+                    best[end][count + 1] = Some((cost, cover));
+                }
+            }
+        }
+    }
+
+    best[len]
+        .iter()
+        .flatten()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn feedback(score: u8, len: usize, cover: &[Pattern]) -> (Option<String>, Vec<String>) {
+    if score >= 3 {
+        return (None, Vec::new());
+    }
+
+    // The longest guessable pattern says the most about the password
+    let worst = cover
+        .iter()
+        .filter(|pattern| pattern.kind != PatternKind::Bruteforce)
+        .max_by_key(|pattern| pattern.end - pattern.start);
+
+    let mut suggestions = vec!["Add another word or two; uncommon words are better".to_string()];
+    if len < 12 {
+        suggestions.push("Use a longer password; length beats symbols".to_string());
+    }
+
+    let warning = worst.map(|pattern| match pattern.kind {
+        PatternKind::CommonWord { leet, capitalized } => {
+            if leet {
+                suggestions.push("Predictable substitutions like '@' instead of 'a' don't help much".to_string());
+            }
+            if capitalized {
+                suggestions.push("Capitalization doesn't help much".to_string());
+            }
+            if pattern.end - pattern.start == len && !leet && !capitalized {
+                "This is a very common password".to_string()
+            } else {
+                "This is similar to a commonly used password".to_string()
+            }
+        }
+        PatternKind::UserInput => "Passwords containing your username or email are easy to guess".to_string(),
+        PatternKind::Keyboard => {
+            suggestions.push("Avoid straight rows of keys".to_string());
+            "Straight rows of keys like 'qwerty' are easy to guess".to_string()
+        }
+        PatternKind::Sequence => {
+            suggestions.push("Avoid sequences".to_string());
+            "Sequences like 'abc' or '6543' are easy to guess".to_string()
+        }
+        PatternKind::Repeat => {
+            suggestions.push("Avoid repeated characters".to_string());
+            "Repeats like 'aaa' are easy to guess".to_string()
+        }
+        PatternKind::Year => {
+            suggestions.push("Avoid years that are associated with you".to_string());
+            "Recent years are easy to guess".to_string()
+        }
+        PatternKind::Bruteforce => unreachable!("brute force patterns are filtered out"),
+    });
+
+    (warning, suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weak_passwords_are_rejected() {
+        for password in ["password123", "qwerty123", "abc123456", "P@ssw0rd", "letmein2024"] {
+            let strength = PasswordStrength::estimate(password, &[]);
+            assert!(!strength.is_acceptable(), "{} scored {}", password, strength.score);
+            assert!(strength.warning.is_some(), "{} has no warning", password);
+        }
+
+        let strength = PasswordStrength::estimate("password", &[]);
+        assert_eq!(strength.warning.as_deref(), Some("This is a very common password"));
+    }
+
+    #[test]
+    fn test_user_inputs_count_against_the_password() {
+        let password = "octocat1987";
+        let strength = PasswordStrength::estimate(password, &["octocat", "octo@example.com"]);
+        assert!(!strength.is_acceptable());
+        assert_eq!(
+            strength.warning.as_deref(),
+            Some("Passwords containing your username or email are easy to guess")
+        );
+        assert!(PasswordStrength::estimate(password, &[]).score > strength.score);
+    }
+
+    #[test]
+    fn test_strong_passwords() {
+        let strength = PasswordStrength::estimate("kestrel-canyon-42", &[]);
+        assert_eq!(strength.score, 4);
+        assert_eq!(strength.meter(), "[████] very strong");
+        assert!(strength.warning.is_none() && strength.suggestions.is_empty());
+
+        // Long passphrases need no digits or symbols
+        assert!(PasswordStrength::estimate("purple monsoon tractor", &[]).score >= 3);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::models::task::{TaskStatistics, STALE_AFTER_DAYS};
+
+/// Open task count at which `task add` starts suggesting a cleanup
+pub const DEFAULT_WARN_THRESHOLD: i64 = 50;
+/// Open task count at which the suggestion becomes more insistent
+pub const DEFAULT_CRITICAL_THRESHOLD: i64 = 100;
+
+/// Soft limits on the number of open tasks; `None` disables a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaThresholds {
+    pub warn: Option<i64>,
+    pub critical: Option<i64>,
+}
+
+impl Default for QuotaThresholds {
+    fn default() -> Self {
+        Self {
+            warn: Some(DEFAULT_WARN_THRESHOLD),
+            critical: Some(DEFAULT_CRITICAL_THRESHOLD),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaLevel {
+    Warn,
+    Critical,
+}
+
+/// A nudge shown after adding a task once the open task count crosses a threshold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaWarning {
+    pub level: QuotaLevel,
+    pub open_tasks: i64,
+    pub threshold: i64,
+    /// Cleanup steps that would actually help, given the statistics
+    pub suggestions: Vec<String>,
+}
+
+impl QuotaWarning {
+    /// The warning for the highest threshold `stats` has reached, if any
+    pub fn check(stats: &TaskStatistics, thresholds: QuotaThresholds) -> Option<Self> {
+        let open_tasks = stats.open_tasks();
+        let reached = |threshold: Option<i64>| threshold.filter(|&limit| limit > 0 && open_tasks >= limit);
+
+        let (level, threshold) = match (reached(thresholds.critical), reached(thresholds.warn)) {
+            (Some(limit), _) => (QuotaLevel::Critical, limit),
+            (None, Some(limit)) => (QuotaLevel::Warn, limit),
+            (None, None) => return None,
+        };
+
+        let mut suggestions = Vec::new();
+        if stats.stale_tasks > 0 {
+            suggestions.push(format!(
+                "{} open tasks haven't changed in {}+ days; review them with `todo-cli task list --all`",
+                stats.stale_tasks, STALE_AFTER_DAYS
+            ));
+        }
+        if stats.overdue_tasks > 0 {
+            suggestions.push(format!(
+                "{} tasks are overdue; reschedule or drop them with `todo-cli task list --overdue`",
+                stats.overdue_tasks
+            ));
+        }
+        if stats.completed_tasks > 0 {
+            suggestions.push(format!(
+                "{} completed tasks are kept around; clear out old ones from `todo-cli task list --completed`",
+                stats.completed_tasks
+            ));
+        }
+
+        Some(Self {
+            level,
+            open_tasks,
+            threshold,
+            suggestions,
+        })
+    }
+
+    pub fn message(&self) -> String {
+        match self.level {
+            QuotaLevel::Warn => format!(
+                "You have {} open tasks (soft limit {}). A quick cleanup might help:",
+                self.open_tasks, self.threshold
+            ),
+            QuotaLevel::Critical => format!(
+                "You have {} open tasks, well past the soft limit of {}. Consider a cleanup:",
+                self.open_tasks, self.threshold
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(open: i64, completed: i64, stale: i64) -> TaskStatistics {
+        TaskStatistics {
+            total_tasks: open + completed,
+            pending_tasks: open,
+            completed_tasks: completed,
+            stale_tasks: stale,
+            ..TaskStatistics::default()
+        }
+    }
+
+    #[test]
+    fn test_check_picks_highest_threshold() {
+        let thresholds = QuotaThresholds {
+            warn: Some(10),
+            critical: Some(20),
+        };
+
+        assert_eq!(QuotaWarning::check(&stats(9, 50, 0), thresholds), None);
+
+        let warning = QuotaWarning::check(&stats(10, 0, 3), thresholds).unwrap();
+        assert_eq!(warning.level, QuotaLevel::Warn);
+        assert_eq!(warning.threshold, 10);
+        // Only suggestions that apply: there is nothing completed to clear out
+        assert_eq!(warning.suggestions.len(), 1);
+        assert!(warning.suggestions[0].starts_with("3 open tasks"));
+
+        let warning = QuotaWarning::check(&stats(25, 4, 0), thresholds).unwrap();
+        assert_eq!(warning.level, QuotaLevel::Critical);
+        assert_eq!(warning.open_tasks, 25);
+        assert_eq!(warning.suggestions.len(), 1);
+    }
+
+    #[test]
+    fn test_disabled_thresholds() {
+        let only_critical = QuotaThresholds {
+            warn: None,
+            critical: Some(20),
+        };
+        assert_eq!(QuotaWarning::check(&stats(15, 0, 0), only_critical), None);
+
+        let zero = QuotaThresholds {
+            warn: Some(0),
+            critical: Some(0),
+        };
+        assert_eq!(QuotaWarning::check(&stats(500, 0, 0), zero), None);
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::fmt;
+use uuid::Uuid;
+
+use crate::models::{calendar, task::Task};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderKind {
+    DueSoon,
+    Overdue,
+}
+
+impl fmt::Display for ReminderKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReminderKind::DueSoon => write!(f, "due soon"),
+            ReminderKind::Overdue => write!(f, "overdue"),
+        }
+    }
+}
+
+/// An open task worth a nudge: due within the due-soon window, or overdue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reminder {
+    pub kind: ReminderKind,
+    pub task_id: Uuid,
+    pub title: String,
+    pub due_date: DateTime<Utc>,
+}
+
+impl Reminder {
+    /// The open tasks of `tasks` to remind about at `now`, overdue ones first,
+    /// each group by due date. Like `Task::is_overdue`, a task is overdue
+    /// from the day after it is due.
+    pub fn for_tasks(tasks: &[Task], now: DateTime<Utc>, due_soon: Duration) -> Vec<Reminder> {
+        let mut reminders: Vec<Reminder> = tasks
+            .iter()
+            .filter(|task| !task.is_completed())
+            .filter_map(|task| {
+                let due_date = task.due_date?;
+                let kind = if calendar::local_date(&due_date) < calendar::local_date(&now) {
+                    ReminderKind::Overdue
+                } else if due_date - now <= due_soon {
+                    ReminderKind::DueSoon
+                } else {
+                    return None;
+                };
+                Some(Reminder { kind, task_id: task.id, title: task.title.clone(), due_date })
+            })
+            .collect();
+
+        reminders.sort_by_key(|reminder| (reminder.kind != ReminderKind::Overdue, reminder.due_date));
+        reminders
+    }
+
+    /// Identifies the reminder so it is sent once; moving the due date makes
+    /// it a new one
+    pub fn key(&self) -> String {
+        format!("{}:{:?}:{}", self.task_id, self.kind, self.due_date.timestamp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::StoreTaskRequest;
+
+    fn task(title: &str, due_date: Option<DateTime<Utc>>) -> Task {
+        Task::new(StoreTaskRequest { title: title.to_string(), ..Default::default() }, Uuid::new_v4())
+            .map(|task| Task { due_date, ..task })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reminders_for_tasks() {
+        let today = calendar::today();
+        let now = Utc::now();
+        let mut done = task("Water plants", Some(calendar::end_of_day(today - Duration::days(3))));
+        done.complete();
+
+        let tasks = vec![
+            task("Plan trip", Some(calendar::end_of_day(today + Duration::days(5)))),
+            task("Call Bob", Some(now + Duration::hours(2))),
+            task("Pay rent", Some(calendar::end_of_day(today - Duration::days(1)))),
+            task("File taxes", Some(calendar::end_of_day(today - Duration::days(4)))),
+            task("Learn Rust", None),
+            done,
+        ];
+
+        let reminders = Reminder::for_tasks(&tasks, now, Duration::hours(24));
+        let titles: Vec<_> = reminders.iter().map(|reminder| (reminder.title.as_str(), reminder.kind)).collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("File taxes", ReminderKind::Overdue),
+                ("Pay rent", ReminderKind::Overdue),
+                ("Call Bob", ReminderKind::DueSoon),
+            ]
+        );
+
+        // A narrower window leaves the task due in two hours out
+        assert_eq!(Reminder::for_tasks(&tasks, now, Duration::hours(1)).len(), 2);
+
+        let mut moved = reminders[2].clone();
+        assert_eq!(moved.key(), reminders[2].key());
+        moved.due_date += Duration::days(1);
+        assert_ne!(moved.key(), reminders[2].key());
+    }
+}
@@ -0,0 +1,523 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::calendar::UserTimezone;
+use crate::models::task::{Task, TaskStatus};
+
+/// Tasks created and completed in a period, as counted by
+/// `TaskRepository::get_throughput`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Throughput {
+    /// Tasks created in the period
+    pub created: i64,
+    /// Tasks created in the period that are completed by now
+    pub created_completed: i64,
+    /// Tasks completed in the period, whenever they were created
+    pub completed: i64,
+    /// Mean seconds from creation to completion of the tasks completed in the period
+    pub average_completion_seconds: Option<f64>,
+}
+
+impl Throughput {
+    /// Count `tasks` the way the throughput query does, over `[since, until)`
+    pub fn from_tasks(tasks: &[Task], since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        let within = |instant: DateTime<Utc>| instant >= since && instant < until;
+        let mut throughput = Self::default();
+        let mut completion_seconds = Vec::new();
+
+        for task in tasks {
+            if within(task.created_at) {
+                throughput.created += 1;
+                if task.status == TaskStatus::Completed {
+                    throughput.created_completed += 1;
+                }
+            }
+            if let Some(completed_at) = task.completed_at.filter(|at| within(*at)) {
+                throughput.completed += 1;
+                completion_seconds.push((completed_at - task.created_at).num_milliseconds() as f64 / 1000.0);
+            }
+        }
+
+        if !completion_seconds.is_empty() {
+            throughput.average_completion_seconds = Some(completion_seconds.iter().sum::<f64>() / completion_seconds.len() as f64);
+        }
+        throughput
+    }
+}
+
+/// Tasks of one context in a report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextTally {
+    pub context: String,
+    pub tasks: i64,
+}
+
+/// `report weekly`: what happened to the user's tasks in the last seven days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub created: i64,
+    pub completed: i64,
+    /// Share of the tasks created in the week that are completed, from 0 to 1;
+    /// none if no task was created
+    pub completion_rate: Option<f64>,
+    /// Mean seconds from creation to completion of the tasks completed in the week
+    pub average_completion_seconds: Option<f64>,
+    /// Contexts of the tasks completed in the week, most tasks first
+    pub top_contexts: Vec<ContextTally>,
+}
+
+impl WeeklyReport {
+    pub const DAYS: i64 = 7;
+    /// Contexts listed at most
+    pub const TOP_CONTEXTS: usize = 5;
+
+    pub fn new(since: DateTime<Utc>, until: DateTime<Utc>, throughput: &Throughput, mut contexts: Vec<ContextTally>) -> Self {
+        contexts.sort_by(|a, b| b.tasks.cmp(&a.tasks).then_with(|| a.context.cmp(&b.context)));
+        contexts.truncate(Self::TOP_CONTEXTS);
+        Self {
+            since,
+            until,
+            created: throughput.created,
+            completed: throughput.completed,
+            completion_rate: (throughput.created > 0).then(|| throughput.created_completed as f64 / throughput.created as f64),
+            average_completion_seconds: throughput.average_completion_seconds,
+            top_contexts: contexts,
+        }
+    }
+}
+
+/// Timestamp `TaskRepository::count_by_day` buckets tasks by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskTimestamp {
+    Created,
+    Completed,
+}
+
+impl TaskTimestamp {
+    pub fn of(self, task: &Task) -> Option<DateTime<Utc>> {
+        match self {
+            TaskTimestamp::Created => Some(task.created_at),
+            TaskTimestamp::Completed => task.completed_at,
+        }
+    }
+}
+
+/// Number of tasks on one local day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub count: i64,
+}
+
+impl DayCount {
+    /// Bucket `tasks` the way the per-day query does: by the date in
+    /// `timezone` of their `timestamp` in `[since, until)`
+    pub fn from_tasks(
+        tasks: &[Task],
+        timestamp: TaskTimestamp,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Vec<Self> {
+        Self::from_timestamps(tasks.iter().filter_map(|task| timestamp.of(task)), since, until, timezone)
+    }
+
+    /// Bucket instants in `[since, until)` by their date in `timezone`, for
+    /// backends that cannot convert to a named timezone in SQL
+    pub fn from_timestamps(
+        timestamps: impl IntoIterator<Item = DateTime<Utc>>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        timezone: UserTimezone,
+    ) -> Vec<Self> {
+        let mut counts: HashMap<NaiveDate, i64> = HashMap::new();
+        for at in timestamps {
+            if at >= since && at < until {
+                *counts.entry(timezone.local_date(&at)).or_insert(0) += 1;
+            }
+        }
+        let mut days: Vec<Self> = counts.into_iter().map(|(date, count)| Self { date, count }).collect();
+        days.sort_by_key(|day| day.date);
+        days
+    }
+}
+
+/// Open tasks at the end of one day of a burndown, and what changed that day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BurndownDay {
+    pub date: NaiveDate,
+    pub open: i64,
+    pub created: i64,
+    pub completed: i64,
+}
+
+/// `report burndown`: the number of open tasks day by day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Burndown {
+    pub days: Vec<BurndownDay>,
+}
+
+impl Burndown {
+    /// Replay the days from `first` to `last` backwards from the `open_now`
+    /// tasks open at the end of `last`: a day opened the tasks created on it
+    /// and closed those completed on it. Deleted tasks leave no history, so
+    /// the count is kept from going negative.
+    pub fn new(first: NaiveDate, last: NaiveDate, open_now: i64, created: &[DayCount], completed: &[DayCount]) -> Self {
+        let by_date = |counts: &[DayCount]| -> HashMap<NaiveDate, i64> {
+            counts.iter().map(|day| (day.date, day.count)).collect()
+        };
+        let (created, completed) = (by_date(created), by_date(completed));
+
+        let mut days = Vec::new();
+        let mut open = open_now;
+        let mut date = last;
+        while date >= first {
+            let day = BurndownDay {
+                date,
+                open: open.max(0),
+                created: created.get(&date).copied().unwrap_or(0),
+                completed: completed.get(&date).copied().unwrap_or(0),
+            };
+            open = open - day.created + day.completed;
+            days.push(day);
+            date = match date.pred_opt() {
+                Some(previous) => previous,
+                None => break,
+            };
+        }
+        days.reverse();
+        Self { days }
+    }
+
+    pub fn created(&self) -> i64 {
+        self.days.iter().map(|day| day.created).sum()
+    }
+
+    pub fn completed(&self) -> i64 {
+        self.days.iter().map(|day| day.completed).sum()
+    }
+}
+
+/// Runs of consecutive local days on which at least one task was completed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletionStreak {
+    /// Days in the run ending today, or yesterday while nothing is completed today yet
+    pub current: u32,
+    pub longest: u32,
+}
+
+impl CompletionStreak {
+    /// Streaks over `days`, the days with completions in ascending order
+    pub fn from_days(days: &[DayCount], today: NaiveDate) -> Self {
+        let mut streak = Self::default();
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for date in days.iter().filter(|day| day.count > 0).map(|day| day.date) {
+            run = match previous {
+                Some(previous) if previous.succ_opt() == Some(date) => run + 1,
+                Some(previous) if previous == date => run,
+                _ => 1,
+            };
+            streak.longest = streak.longest.max(run);
+            previous = Some(date);
+        }
+
+        let yesterday = today.pred_opt();
+        if previous.is_some_and(|last| last == today || Some(last) == yesterday) {
+            streak.current = run;
+        }
+        streak
+    }
+}
+
+/// `report heatmap`: tasks completed per day of one calendar year
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heatmap {
+    pub year: i32,
+    /// Days with completions, in ascending order
+    pub days: Vec<DayCount>,
+}
+
+impl Heatmap {
+    /// Shades of days with completions; days without have shade 0
+    pub const LEVELS: i64 = 4;
+
+    pub fn count_on(&self, date: NaiveDate) -> i64 {
+        self.days
+            .binary_search_by_key(&date, |day| day.date)
+            .map_or(0, |index| self.days[index].count)
+    }
+
+    pub fn total(&self) -> i64 {
+        self.days.iter().map(|day| day.count).sum()
+    }
+
+    /// Day with the most completions, the earliest of a tie
+    pub fn busiest(&self) -> Option<&DayCount> {
+        self.days.iter().rev().max_by_key(|day| day.count)
+    }
+
+    /// Shade of a day with `count` completions, from 1 up to `LEVELS` for
+    /// the busiest day of the year
+    pub fn level(&self, count: i64) -> i64 {
+        let most = self.busiest().map_or(0, |day| day.count);
+        if count <= 0 || most <= 0 {
+            return 0;
+        }
+        (count * Self::LEVELS + most - 1) / most
+    }
+}
+
+/// How many days past its due day an overdue task is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverdueAge {
+    UpToThreeDays,
+    UpToWeek,
+    UpToMonth,
+    OverMonth,
+}
+
+impl OverdueAge {
+    pub fn of(days_overdue: i64) -> Self {
+        match days_overdue {
+            ..=3 => OverdueAge::UpToThreeDays,
+            4..=7 => OverdueAge::UpToWeek,
+            8..=30 => OverdueAge::UpToMonth,
+            _ => OverdueAge::OverMonth,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OverdueAge::UpToThreeDays => "1–3 days",
+            OverdueAge::UpToWeek => "4–7 days",
+            OverdueAge::UpToMonth => "8–30 days",
+            OverdueAge::OverMonth => "30+ days",
+        }
+    }
+}
+
+/// The overdue tasks of one age, most overdue first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverdueBucket {
+    pub age: OverdueAge,
+    pub tasks: Vec<Task>,
+}
+
+/// `report overdue`: overdue tasks bucketed by age, the oldest bucket first;
+/// buckets without tasks are left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OverdueAging {
+    pub buckets: Vec<OverdueBucket>,
+}
+
+impl OverdueAging {
+    /// Bucket the open `tasks` whose due day is before `today`
+    pub fn from_tasks(tasks: Vec<Task>, today: NaiveDate) -> Self {
+        let mut overdue: Vec<(NaiveDate, Task)> = tasks
+            .into_iter()
+            .filter(|task| !task.is_completed())
+            .filter_map(|task| task.due_day().filter(|day| *day < today).map(|day| (day, task)))
+            .collect();
+        overdue.sort_by_key(|(day, _)| *day);
+
+        let mut buckets: Vec<OverdueBucket> = Vec::new();
+        for (day, task) in overdue {
+            let age = OverdueAge::of((today - day).num_days());
+            match buckets.last_mut() {
+                Some(bucket) if bucket.age == age => bucket.tasks.push(task),
+                _ => buckets.push(OverdueBucket { age, tasks: vec![task] }),
+            }
+        }
+        Self { buckets }
+    }
+
+    pub fn total(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.tasks.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::StoreTaskRequest;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    fn task(created_days_ago: i64, completed_days_ago: Option<i64>) -> Task {
+        let now = Utc::now();
+        let request = StoreTaskRequest { title: "Task".to_string(), ..Default::default() };
+        let mut task = Task::new(request, Uuid::new_v4()).unwrap();
+        task.created_at = now - Duration::days(created_days_ago);
+        if let Some(days) = completed_days_ago {
+            task.status = TaskStatus::Completed;
+            task.completed_at = Some(now - Duration::days(days));
+        }
+        task
+    }
+
+    #[test]
+    fn test_throughput_from_tasks() {
+        let until = Utc::now() + Duration::minutes(1);
+        let since = until - Duration::days(7);
+        let tasks = [task(2, Some(1)), task(3, None), task(20, Some(4)), task(30, Some(10))];
+
+        let throughput = Throughput::from_tasks(&tasks, since, until);
+        assert_eq!((throughput.created, throughput.created_completed, throughput.completed), (2, 1, 2));
+        let average_days = throughput.average_completion_seconds.unwrap() / 86_400.0;
+        assert!((average_days - 8.5).abs() < 0.01, "{}", average_days);
+
+        assert_eq!(Throughput::from_tasks(&[], since, until), Throughput::default());
+    }
+
+    #[test]
+    fn test_weekly_report_rates_and_ranks() {
+        let until = Utc::now();
+        let throughput = Throughput { created: 4, created_completed: 1, completed: 3, average_completion_seconds: None };
+        let tally = |context: &str, tasks| ContextTally { context: context.to_string(), tasks };
+        let contexts = vec![tally("@a", 1), tally("@b", 3), tally("@c", 1), tally("@d", 2), tally("@e", 1), tally("@f", 1)];
+
+        let report = WeeklyReport::new(until - Duration::days(7), until, &throughput, contexts);
+        assert_eq!(report.completion_rate, Some(0.25));
+        let ranked: Vec<&str> = report.top_contexts.iter().map(|tally| tally.context.as_str()).collect();
+        assert_eq!(ranked, ["@b", "@d", "@a", "@c", "@e"]);
+
+        let idle = WeeklyReport::new(until - Duration::days(7), until, &Throughput::default(), Vec::new());
+        assert_eq!(idle.completion_rate, None);
+    }
+
+    #[test]
+    fn test_day_counts_from_tasks() {
+        let since = "2026-10-10T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let until = since + Duration::days(3);
+        let at = |stamp: &str| {
+            let mut task = task(0, None);
+            task.created_at = stamp.parse().unwrap();
+            task
+        };
+        let tasks = [
+            at("2026-10-10T01:00:00Z"),
+            at("2026-10-10T23:30:00Z"),
+            at("2026-10-12T12:00:00Z"),
+            at("2026-10-13T00:00:00Z"),
+        ];
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+
+        let utc: UserTimezone = "UTC".parse().unwrap();
+
+        let days = DayCount::from_tasks(&tasks, TaskTimestamp::Created, since, until, utc);
+        assert_eq!(days, [DayCount { date: date(10), count: 2 }, DayCount { date: date(12), count: 1 }]);
+        // An hour east of UTC, 23:30 is already the next day
+        let days = DayCount::from_tasks(&tasks, TaskTimestamp::Created, since, until, "Etc/GMT-1".parse().unwrap());
+        assert_eq!(days.iter().map(|day| (day.date, day.count)).collect::<Vec<_>>(), [(date(10), 1), (date(11), 1), (date(12), 1)]);
+        assert!(DayCount::from_tasks(&tasks, TaskTimestamp::Completed, since, until, utc).is_empty());
+    }
+
+    #[test]
+    fn test_day_counts_follow_dst_changes() {
+        // Berlin moves from UTC+1 to UTC+2 on 2026-03-29; both instants are
+        // half past midnight local time, on consecutive days
+        let at = |stamp: &str| stamp.parse::<DateTime<Utc>>().unwrap();
+        let since = at("2026-03-01T00:00:00Z");
+        let until = at("2026-04-01T00:00:00Z");
+        let berlin = "Europe/Berlin".parse().unwrap();
+        let days = DayCount::from_timestamps([at("2026-03-28T23:30:00Z"), at("2026-03-29T22:30:00Z")], since, until, berlin);
+        let date = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        assert_eq!(days, [DayCount { date: date(29), count: 1 }, DayCount { date: date(30), count: 1 }]);
+    }
+
+    #[test]
+    fn test_burndown_replays_days_backwards() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        let created = [DayCount { date: date(2), count: 3 }, DayCount { date: date(4), count: 1 }];
+        let completed = [DayCount { date: date(3), count: 2 }, DayCount { date: date(4), count: 2 }];
+
+        let burndown = Burndown::new(date(1), date(4), 5, &created, &completed);
+        let open: Vec<(NaiveDate, i64)> = burndown.days.iter().map(|day| (day.date, day.open)).collect();
+        assert_eq!(open, [(date(1), 5), (date(2), 8), (date(3), 6), (date(4), 5)]);
+        assert_eq!((burndown.created(), burndown.completed()), (4, 4));
+
+        // Tasks deleted since they were created would make the start negative
+        let burndown = Burndown::new(date(1), date(2), 1, &[DayCount { date: date(2), count: 4 }], &[]);
+        assert_eq!(burndown.days[0].open, 0);
+    }
+
+    #[test]
+    fn test_completion_streak() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        let days = |dates: &[u32]| -> Vec<DayCount> { dates.iter().map(|day| DayCount { date: date(*day), count: 1 }).collect() };
+
+        let streak = CompletionStreak::from_days(&days(&[1, 2, 3, 4, 8, 9, 10]), date(10));
+        assert_eq!((streak.current, streak.longest), (3, 4));
+        // Today can still extend yesterday's run
+        assert_eq!(CompletionStreak::from_days(&days(&[8, 9, 10]), date(11)).current, 3);
+        // A day without completions ends it
+        assert_eq!(CompletionStreak::from_days(&days(&[8, 9, 10]), date(12)), CompletionStreak { current: 0, longest: 3 });
+        assert_eq!(CompletionStreak::from_days(&[], date(12)), CompletionStreak::default());
+    }
+
+    #[test]
+    fn test_completion_streak_across_dst_change() {
+        // Completions at half past midnight in Berlin on March 28, 29 and 30,
+        // around the switch to summer time on the 29th
+        let at = |stamp: &str| stamp.parse::<DateTime<Utc>>().unwrap();
+        let completed = [at("2026-03-27T23:30:00Z"), at("2026-03-28T23:30:00Z"), at("2026-03-29T22:30:00Z")];
+        let days = DayCount::from_timestamps(completed, DateTime::UNIX_EPOCH, at("2026-04-01T00:00:00Z"), "Europe/Berlin".parse().unwrap());
+
+        let streak = CompletionStreak::from_days(&days, NaiveDate::from_ymd_opt(2026, 3, 30).unwrap());
+        assert_eq!((streak.current, streak.longest), (3, 3));
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        let days = vec![
+            DayCount { date: date(2), count: 1 },
+            DayCount { date: date(5), count: 8 },
+            DayCount { date: date(9), count: 3 },
+            DayCount { date: date(12), count: 8 },
+        ];
+        let heatmap = Heatmap { year: 2026, days };
+
+        assert_eq!(heatmap.total(), 20);
+        assert_eq!(heatmap.count_on(date(9)), 3);
+        assert_eq!(heatmap.count_on(date(10)), 0);
+        assert_eq!(heatmap.busiest().map(|day| day.date), Some(date(5)));
+        assert_eq!([0, 1, 2, 3, 8].map(|count| heatmap.level(count)), [0, 1, 1, 2, 4]);
+        assert_eq!(Heatmap { year: 2026, days: Vec::new() }.level(1), 0);
+    }
+
+    #[test]
+    fn test_overdue_age() {
+        assert_eq!([1, 3, 4, 7, 8, 30, 31].map(OverdueAge::of), [
+            OverdueAge::UpToThreeDays,
+            OverdueAge::UpToThreeDays,
+            OverdueAge::UpToWeek,
+            OverdueAge::UpToWeek,
+            OverdueAge::UpToMonth,
+            OverdueAge::UpToMonth,
+            OverdueAge::OverMonth,
+        ]);
+    }
+
+    #[test]
+    fn test_overdue_aging_from_tasks() {
+        let today = crate::models::calendar::today();
+        let due = |days_ago: i64, completed: bool| {
+            let mut task = task(60, completed.then_some(0));
+            task.due_date = Some(crate::models::calendar::start_of_day(today - Duration::days(days_ago)));
+            task
+        };
+        let tasks = vec![due(2, false), due(45, false), due(0, false), due(5, true), due(10, false), due(1, false)];
+
+        let aging = OverdueAging::from_tasks(tasks, today);
+        let ages: Vec<(OverdueAge, usize)> = aging.buckets.iter().map(|bucket| (bucket.age, bucket.tasks.len())).collect();
+        assert_eq!(ages, vec![(OverdueAge::OverMonth, 1), (OverdueAge::UpToMonth, 1), (OverdueAge::UpToThreeDays, 2)]);
+        assert_eq!(aging.buckets[2].tasks[0].due_day(), Some(today - Duration::days(2)));
+        assert_eq!(aging.total(), 4);
+    }
+}
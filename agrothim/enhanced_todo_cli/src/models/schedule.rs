@@ -0,0 +1,339 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::models::calendar;
+use crate::models::task::TaskPriority;
+
+/// Days `CronSchedule::next_after` looks ahead; every valid expression fires
+/// within four years (Feb 29)
+const LOOKAHEAD_DAYS: i64 = 4 * 366;
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// A task `tick` creates each time `cron` fires, in server-local time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Schedule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Title of the tasks created
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: TaskPriority,
+    pub context: Option<String>,
+    /// Five-field cron expression
+    pub cron: String,
+    /// Upcoming firings whose tasks are created ahead of time, due when they
+    /// fire; 0 creates each task when it fires
+    pub ahead: i16,
+    /// Next firing whose task is still to be created
+    pub next_run_at: DateTime<Utc>,
+    /// When the last task was created
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Schedule {
+    /// The first 8 hex digits of the ID, as `schedule list` shows it
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+}
+
+/// A new schedule; `cron` is a valid `CronSchedule` expression
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoreScheduleRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: TaskPriority,
+    pub context: Option<String>,
+    pub cron: String,
+    pub ahead: i16,
+}
+
+/// When a schedule fires: a cron expression of minute, hour, day of month,
+/// month and day of week, e.g. `0 9 * * mon` for Mondays at 09:00. Fields
+/// take `*`, numbers, names, ranges (`1-5`), steps (`*/15`) and lists (`1,15`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Both day fields are restricted, so a day matching either one fires, as in cron
+    either_day: bool,
+}
+
+impl CronSchedule {
+    /// Parse a phrase such as "every monday 09:00", "every weekday at 8:30",
+    /// "every day 18:00", "every mon,thu 07:15" or "every month on 1 09:00"
+    pub fn from_phrase(phrase: &str) -> Result<Self, String> {
+        let phrase = phrase.trim().to_lowercase();
+        let words: Vec<&str> = phrase
+            .strip_prefix("every")
+            .unwrap_or(&phrase)
+            .split_whitespace()
+            .filter(|word| !matches!(*word, "at" | "on"))
+            .collect();
+        let (time, days) = match words.split_last() {
+            Some((time, days)) if !days.is_empty() => (*time, days),
+            _ => return Err("expected days and a time, e.g. \"every monday 09:00\"".to_string()),
+        };
+        let (hour, minute) = time
+            .split_once(':')
+            .and_then(|(hour, minute)| Some((hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?)))
+            .filter(|(hour, minute)| *hour < 24 && *minute < 60)
+            .ok_or_else(|| format!("'{}' is not a time like 09:00", time))?;
+
+        let (day_of_month, weekdays) = match days {
+            ["day"] => ("*".to_string(), "*".to_string()),
+            ["weekday" | "weekdays"] => ("*".to_string(), "mon-fri".to_string()),
+            ["weekend" | "weekends"] => ("*".to_string(), "sat,sun".to_string()),
+            ["month"] => ("1".to_string(), "*".to_string()),
+            ["month", day] => (day.trim_end_matches(|c: char| c.is_ascii_alphabetic()).to_string(), "*".to_string()),
+            names => {
+                let names: Vec<String> = names
+                    .iter()
+                    .flat_map(|word| word.split(','))
+                    .filter(|name| !name.is_empty() && *name != "and")
+                    .map(|name| {
+                        let name = name.trim_end_matches('s');
+                        WEEKDAY_NAMES
+                            .iter()
+                            .find(|short| name.starts_with(*short) && weekday_name_matches(name))
+                            .map(|short| short.to_string())
+                            .ok_or_else(|| format!("'{}' is not a day; use day, weekday, weekend, month or day names", name))
+                    })
+                    .collect::<Result<_, _>>()?;
+                ("*".to_string(), names.join(","))
+            }
+        };
+
+        format!("{} {} {} * {}", minute, hour, day_of_month, weekdays).parse()
+    }
+
+    /// First minute after `after` the schedule fires at
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for offset in 0..=LOOKAHEAD_DAYS {
+            let date = start.date() + Duration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            for hour in bits(self.hours) {
+                for minute in bits(self.minutes) {
+                    let candidate = date.and_hms_opt(hour, minute, 0)?;
+                    if candidate >= start {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// First instant after `after` the schedule fires at in the user's
+    /// timezone; local times a DST change skips are left out
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let timezone = calendar::timezone();
+        let mut local = timezone.local_time(&after).naive_local();
+        loop {
+            local = self.next_after(local)?;
+            if let Some(at) = timezone.resolve(&local) {
+                if at > after {
+                    return Some(at);
+                }
+            }
+        }
+    }
+
+    /// Firings whose tasks are to be created at `now` so that the next
+    /// `ahead` exist, given that those before `next_run_at` already do.
+    /// Firings that passed uncreated are left out.
+    pub fn to_materialize(&self, next_run_at: DateTime<Utc>, ahead: usize, now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut at = now;
+        let mut created = 0;
+        let mut firings = Vec::new();
+        while created + firings.len() < ahead {
+            let Some(next) = self.next_run_after(at) else { break };
+            at = next;
+            if at < next_run_at {
+                created += 1;
+            } else {
+                firings.push(at);
+            }
+        }
+        firings
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn weekday_name_matches(name: &str) -> bool {
+    const FULL: [&str; 7] = ["sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday"];
+    FULL.iter().any(|full| full.starts_with(name))
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn bits(set: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |bit| has(set, *bit))
+}
+
+/// The values one field allows, as a bit set
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let text = text.to_lowercase();
+        names
+            .iter()
+            .position(|name| *name == text)
+            .map(|index| index as u32 + min)
+            .or_else(|| text.parse().ok())
+            .ok_or_else(|| format!("'{}' is not a number", text))
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0).ok_or_else(|| format!("bad step in '{}'", part))?),
+            None => (part, 1),
+        };
+        let (first, last) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((first, last)) => (value(first)?, value(last)?),
+                None if step > 1 => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+        if first < min || last > max || first > last {
+            return Err(format!("'{}' is outside {}-{}", part, min, max));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("expected 5 fields (minute hour day month weekday), got {}", fields.len()));
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES)?;
+        // 7 is Sunday too
+        if has(weekdays, 7) {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])?,
+            days: parse_field(day, 1, 31, &[])?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES)?,
+            weekdays,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2026-10-05 is a Monday
+        NaiveDate::from_ymd_opt(2026, 10, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_next_after() {
+        let mondays: CronSchedule = "0 9 * * mon".parse().unwrap();
+        assert_eq!(mondays.next_after(at(5, 8, 59)), Some(at(5, 9, 0)));
+        assert_eq!(mondays.next_after(at(5, 9, 0)), Some(at(12, 9, 0)));
+
+        let quarter_hours: CronSchedule = "*/15 8-9 * * *".parse().unwrap();
+        assert_eq!(quarter_hours.next_after(at(5, 8, 50)), Some(at(5, 9, 0)));
+        assert_eq!(quarter_hours.next_after(at(5, 9, 45)), Some(at(6, 8, 0)));
+
+        // Day of month or Friday, as cron does
+        let either: CronSchedule = "30 7 1 * 5".parse().unwrap();
+        assert_eq!(either.next_after(at(5, 12, 0)), Some(at(9, 7, 30)));
+        let leap_day: CronSchedule = "0 0 29 2 *".parse().unwrap();
+        assert_eq!(leap_day.next_after(at(5, 0, 0)).map(|next| next.date()), NaiveDate::from_ymd_opt(2028, 2, 29));
+        assert_eq!("0 0 31 2 *".parse::<CronSchedule>().unwrap().next_after(at(5, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_to_materialize() {
+        let daily: CronSchedule = "0 9 * * *".parse().unwrap();
+        let now = Utc::now();
+        let firings: Vec<_> = std::iter::successors(daily.next_run_after(now), |at| daily.next_run_after(*at))
+            .take(4)
+            .collect();
+
+        // Nothing created yet
+        assert_eq!(daily.to_materialize(firings[0], 3, now), firings[..3]);
+        // The first two exist
+        assert_eq!(daily.to_materialize(firings[2], 3, now), firings[2..3]);
+        assert!(daily.to_materialize(firings[3], 3, now).is_empty());
+        // Missed firings are skipped
+        assert_eq!(daily.to_materialize(now - Duration::days(7), 2, now), firings[..2]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("0 9 * *".parse::<CronSchedule>().is_err());
+        assert!("60 9 * * *".parse::<CronSchedule>().is_err());
+        assert!("0 9 * * funday".parse::<CronSchedule>().is_err());
+        assert!("0 9 5-1 * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 9 * * *".parse::<CronSchedule>().is_err());
+        // 7 is Sunday, as is 0
+        let sundays: CronSchedule = "0 9 * * 7".parse().unwrap();
+        assert_eq!(sundays.next_after(at(5, 0, 0)), Some(at(11, 9, 0)));
+    }
+
+    #[test]
+    fn test_from_phrase() {
+        let cron = |phrase: &str| CronSchedule::from_phrase(phrase).map(|cron| cron.to_string());
+        assert_eq!(cron("every Monday 09:00").as_deref(), Ok("0 9 * * mon"));
+        assert_eq!(cron("every weekday at 8:30").as_deref(), Ok("30 8 * * mon-fri"));
+        assert_eq!(cron("every day 18:00").as_deref(), Ok("0 18 * * *"));
+        assert_eq!(cron("every mon,thursday 07:15").as_deref(), Ok("15 7 * * mon,thu"));
+        assert_eq!(cron("every tuesday and fridays 12:00").as_deref(), Ok("0 12 * * tue,fri"));
+        assert_eq!(cron("every month on 15th 10:00").as_deref(), Ok("0 10 15 * *"));
+        assert!(cron("every monday").is_err());
+        assert!(cron("every moonday 09:00").is_err());
+        assert!(cron("every day 25:00").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::task::TaskStatus;
+
+/// Codes below this value are reserved for the built-in statuses
+pub const CUSTOM_STATUS_CODE_START: i16 = 10;
+
+/// A user-defined status stored in the `statuses` table
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CustomStatus {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub code: i16,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CustomStatus {
+    pub fn status(&self) -> TaskStatus {
+        TaskStatus::Custom(self.code)
+    }
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct StoreStatusRequest {
+    #[validate(length(min = 1, max = 50, message = "Status name must be 1-50 characters"))]
+    #[validate(regex(
+        path = "STATUS_NAME_REGEX",
+        message = "Status name can only contain letters, numbers, spaces, dashes and underscores"
+    ))]
+    pub name: String,
+}
+
+lazy_static::lazy_static! {
+    static ref STATUS_NAME_REGEX: regex::Regex = regex::Regex::new(r"^[a-zA-Z0-9_\- ]+$").unwrap();
+}
+
+/// An allowed move between two statuses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StatusTransition {
+    pub from_status: TaskStatus,
+    pub to_status: TaskStatus,
+}
+
+/// A user's complete status workflow: built-in and custom statuses plus the
+/// transitions allowed between them.
+///
+/// A status without any configured outgoing transitions may move to any
+/// other known status, so the workflow stays unrestricted until rules are added.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusWorkflow {
+    pub custom_statuses: Vec<CustomStatus>,
+    pub transitions: Vec<StatusTransition>,
+}
+
+impl StatusWorkflow {
+    pub const BUILT_IN: [TaskStatus; 3] = [
+        TaskStatus::Pending,
+        TaskStatus::InProgress,
+        TaskStatus::Completed,
+    ];
+
+    pub fn new(custom_statuses: Vec<CustomStatus>, transitions: Vec<StatusTransition>) -> Self {
+        Self {
+            custom_statuses,
+            transitions,
+        }
+    }
+
+    /// All statuses known to this workflow, built-in first
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        Self::BUILT_IN
+            .iter()
+            .copied()
+            .chain(self.custom_statuses.iter().map(CustomStatus::status))
+            .collect()
+    }
+
+    pub fn contains(&self, status: TaskStatus) -> bool {
+        match status {
+            TaskStatus::Custom(code) => self.custom_statuses.iter().any(|s| s.code == code),
+            _ => true,
+        }
+    }
+
+    /// Display name for a status
+    pub fn name(&self, status: TaskStatus) -> String {
+        match status {
+            TaskStatus::Custom(code) => self
+                .custom_statuses
+                .iter()
+                .find(|s| s.code == code)
+                .map(|s| s.name.clone())
+                .unwrap_or_else(|| status.to_string()),
+            _ => status.to_string(),
+        }
+    }
+
+    /// Resolve a status from user input (case-insensitive)
+    pub fn parse(&self, input: &str) -> Option<TaskStatus> {
+        let normalized = input.trim().to_lowercase().replace(['-', '_'], " ");
+
+        match normalized.as_str() {
+            "pending" => return Some(TaskStatus::Pending),
+            "in progress" | "inprogress" => return Some(TaskStatus::InProgress),
+            "completed" | "done" => return Some(TaskStatus::Completed),
+            _ => {}
+        }
+
+        self.custom_statuses
+            .iter()
+            .find(|s| s.name.to_lowercase().replace(['-', '_'], " ") == normalized)
+            .map(CustomStatus::status)
+    }
+
+    /// Statuses reachable from `from` according to the configured rules
+    pub fn allowed_targets(&self, from: TaskStatus) -> Vec<TaskStatus> {
+        let configured: Vec<TaskStatus> = self
+            .transitions
+            .iter()
+            .filter(|t| t.from_status == from)
+            .map(|t| t.to_status)
+            .collect();
+
+        if configured.is_empty() {
+            self.statuses().into_iter().filter(|s| *s != from).collect()
+        } else {
+            configured
+        }
+    }
+
+    pub fn can_transition(&self, from: TaskStatus, to: TaskStatus) -> bool {
+        if from == to {
+            return true;
+        }
+        self.contains(to) && self.allowed_targets(from).contains(&to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(code: i16, name: &str) -> CustomStatus {
+        CustomStatus {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            code,
+            name: name.to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_builtin_and_custom_names() {
+        let workflow = StatusWorkflow::new(vec![custom(10, "In Review")], Vec::new());
+
+        assert_eq!(workflow.parse("in_progress"), Some(TaskStatus::InProgress));
+        assert_eq!(workflow.parse("in-review"), Some(TaskStatus::Custom(10)));
+        assert_eq!(workflow.parse("blocked"), None);
+        assert_eq!(workflow.name(TaskStatus::Custom(10)), "In Review");
+    }
+
+    #[test]
+    fn test_transitions_restricted_once_configured() {
+        let blocked = TaskStatus::Custom(10);
+        let workflow = StatusWorkflow::new(
+            vec![custom(10, "Blocked")],
+            vec![StatusTransition {
+                from_status: blocked,
+                to_status: TaskStatus::InProgress,
+            }],
+        );
+
+        // No rules from Pending: anything known is allowed
+        assert!(workflow.can_transition(TaskStatus::Pending, blocked));
+        assert!(!workflow.can_transition(TaskStatus::Pending, TaskStatus::Custom(11)));
+
+        // Blocked may only move back to in progress
+        assert!(workflow.can_transition(blocked, TaskStatus::InProgress));
+        assert!(!workflow.can_transition(blocked, TaskStatus::Completed));
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Versions a task had in the local and the remote database right after it
+/// was last synced. A side whose version differs has changed the task since.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SyncState {
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub local_version: i32,
+    pub remote_version: i32,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// A task changed on both sides that waits for `sync conflicts`. Only the
+/// task is recorded; both versions are read when it is reviewed, so later
+/// edits on either side are what gets compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SyncConflict {
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// How sync settles a task changed on both sides, set with `config set sync.conflicts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// Keep the change with the later `updated_at`
+    #[default]
+    Latest,
+    /// Leave both versions alone until the conflict is resolved by hand
+    Ask,
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "latest" => Ok(ConflictStrategy::Latest),
+            "ask" => Ok(ConflictStrategy::Ask),
+            _ => Err("expected one of: latest, ask".to_string()),
+        }
+    }
+}
@@ -1,21 +1,34 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::types::Uuid;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use sqlx::{Decode, Encode, Postgres, Type};
 use validator::{Validate, ValidationError};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
-#[repr(i16)]
+use crate::models::calendar;
+use crate::models::location::{GeoPoint, NearFilter};
+use crate::models::status::CUSTOM_STATUS_CODE_START;
+
+/// Task status stored as a SMALLINT code.
+///
+/// Codes 0-2 are the built-in statuses; user-defined statuses from the
+/// `statuses` table use codes starting at `CUSTOM_STATUS_CODE_START` and are
+/// resolved to names through a `StatusWorkflow`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum TaskStatus {
-    Pending = 0,
-    InProgress = 1,
-    Completed = 2,
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+    Custom(i16),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
 #[repr(i16)]
 pub enum TaskPriority {
     Low = 0,
+    #[default]
     Medium = 1,
     High = 2,
 }
@@ -23,7 +36,12 @@ pub enum TaskPriority {
 // Implement manual From/TryFrom for database conversion
 impl From<TaskStatus> for i16 {
     fn from(status: TaskStatus) -> Self {
-        status as i16
+        match status {
+            TaskStatus::Pending => 0,
+            TaskStatus::InProgress => 1,
+            TaskStatus::Completed => 2,
+            TaskStatus::Custom(code) => code,
+        }
     }
 }
 
@@ -35,11 +53,40 @@ impl TryFrom<i16> for TaskStatus {
             0 => Ok(TaskStatus::Pending),
             1 => Ok(TaskStatus::InProgress),
             2 => Ok(TaskStatus::Completed),
+            code if code >= CUSTOM_STATUS_CODE_START => Ok(TaskStatus::Custom(code)),
             _ => Err(()),
         }
     }
 }
 
+// Built-in statuses keep their variant names; custom ones serialize as "custom:<code>"
+impl Serialize for TaskStatus {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            TaskStatus::Pending => serializer.serialize_str("Pending"),
+            TaskStatus::InProgress => serializer.serialize_str("InProgress"),
+            TaskStatus::Completed => serializer.serialize_str("Completed"),
+            TaskStatus::Custom(code) => serializer.serialize_str(&format!("custom:{}", code)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "Pending" => Ok(TaskStatus::Pending),
+            "InProgress" => Ok(TaskStatus::InProgress),
+            "Completed" => Ok(TaskStatus::Completed),
+            other => other
+                .strip_prefix("custom:")
+                .and_then(|code| code.parse::<i16>().ok())
+                .and_then(|code| TaskStatus::try_from(code).ok())
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid task status '{}'", other))),
+        }
+    }
+}
+
 impl From<TaskPriority> for i16 {
     fn from(priority: TaskPriority) -> Self {
         priority as i16
@@ -75,7 +122,7 @@ impl<'r> Decode<'r, Postgres> for TaskStatus {
 
 impl<'q> Encode<'q, Postgres> for TaskStatus {
     fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
-        <i16 as Encode<Postgres>>::encode_by_ref(&(*self as i16), buf)
+        <i16 as Encode<Postgres>>::encode_by_ref(&i16::from(*self), buf)
     }
 }
 
@@ -99,15 +146,58 @@ impl<'q> Encode<'q, Postgres> for TaskPriority {
     }
 }
 
-impl Default for TaskStatus {
-    fn default() -> Self {
-        TaskStatus::Pending
+// SQLite stores both as INTEGER
+#[cfg(feature = "sqlite")]
+mod sqlite_types {
+    use sqlx::sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef};
+    use sqlx::{Decode, Encode, Sqlite, Type};
+
+    use super::{TaskPriority, TaskStatus};
+
+    impl Type<Sqlite> for TaskStatus {
+        fn type_info() -> SqliteTypeInfo {
+            <i16 as Type<Sqlite>>::type_info()
+        }
+
+        fn compatible(ty: &SqliteTypeInfo) -> bool {
+            <i16 as Type<Sqlite>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Sqlite> for TaskStatus {
+        fn decode(value: SqliteValueRef<'r>) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+            let int_val = <i16 as Decode<Sqlite>>::decode(value)?;
+            TaskStatus::try_from(int_val).map_err(|_| "Invalid TaskStatus value".into())
+        }
     }
-}
 
-impl Default for TaskPriority {
-    fn default() -> Self {
-        TaskPriority::Medium
+    impl<'q> Encode<'q, Sqlite> for TaskStatus {
+        fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> sqlx::encode::IsNull {
+            <i16 as Encode<Sqlite>>::encode_by_ref(&i16::from(*self), buf)
+        }
+    }
+
+    impl Type<Sqlite> for TaskPriority {
+        fn type_info() -> SqliteTypeInfo {
+            <i16 as Type<Sqlite>>::type_info()
+        }
+
+        fn compatible(ty: &SqliteTypeInfo) -> bool {
+            <i16 as Type<Sqlite>>::compatible(ty)
+        }
+    }
+
+    impl<'r> Decode<'r, Sqlite> for TaskPriority {
+        fn decode(value: SqliteValueRef<'r>) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+            let int_val = <i16 as Decode<Sqlite>>::decode(value)?;
+            TaskPriority::try_from(int_val).map_err(|_| "Invalid TaskPriority value".into())
+        }
+    }
+
+    impl<'q> Encode<'q, Sqlite> for TaskPriority {
+        fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> sqlx::encode::IsNull {
+            <i16 as Encode<Sqlite>>::encode_by_ref(&(*self as i16), buf)
+        }
     }
 }
 
@@ -117,6 +207,7 @@ impl std::fmt::Display for TaskStatus {
             TaskStatus::Pending => write!(f, "pending"),
             TaskStatus::InProgress => write!(f, "in progress"),
             TaskStatus::Completed => write!(f, "completed"),
+            TaskStatus::Custom(code) => write!(f, "custom #{}", code),
         }
     }
 }
@@ -131,25 +222,59 @@ impl std::fmt::Display for TaskPriority {
     }
 }
 
+impl std::str::FromStr for TaskPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(TaskPriority::Low),
+            "medium" => Ok(TaskPriority::Medium),
+            "high" => Ok(TaskPriority::High),
+            other => Err(format!("unknown priority '{}' (expected low, medium or high)", other)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Task {
     pub id: Uuid,
+    /// Per-user sequential number, assigned by the repository when the task is stored
+    pub number: i32,
     pub title: String,
     pub description: Option<String>,
     pub status: TaskStatus,
     pub priority: TaskPriority,
     pub due_date: Option<DateTime<Utc>>,
+    /// Before this date the task is not actionable and hidden from default lists
+    pub start_date: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub estimate_minutes: Option<i32>,
+    /// Manual rank set with `task move`; unranked tasks are listed last
+    pub sort_order: Option<f64>,
+    /// Pinned tasks are listed before all others
+    pub pinned: bool,
+    /// Client the task's tracked time is billed to
+    pub client: Option<String>,
+    /// GTD context such as "@home" or "@errands", stored normalized
+    pub context: Option<String>,
+    /// Where the task can be done, e.g. "office" or "hardware store"
+    pub location: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
     pub user_id: Uuid,
+    /// Workspace whose members share the task, if any
+    pub workspace_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Incremented on every write; see `UpdateTaskRequest::version`
+    pub version: i32,
 }
 
 // Type alias for response - in this case it's the same as Task
 pub type TaskResponse = Task;
 
 // request dto
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, Default)]
 pub struct StoreTaskRequest {
     #[validate(length(min = 1, max = 255, message = "Title must be 1-255 characters"))]
     #[validate(custom = "validate_title")]
@@ -158,14 +283,42 @@ pub struct StoreTaskRequest {
     #[validate(length(max = 1000, message = "Description must be less than 1000 characters"))]
     pub description: Option<String>,
 
+    #[serde(default)]
     pub status: TaskStatus,
 
+    #[serde(default)]
     pub priority: TaskPriority,
 
     pub due_date: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    pub start_date: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 525600, message = "Estimate must be between 1 minute and 1 year"))]
+    #[serde(default)]
+    pub estimate_minutes: Option<i32>,
+
+    #[validate(length(min = 1, max = 100, message = "Client must be 1-100 characters"))]
+    #[serde(default)]
+    pub client: Option<String>,
+
+    #[validate(length(min = 1, max = 49, message = "Context must be 1-49 characters"))]
+    #[serde(default)]
+    pub context: Option<String>,
+
+    #[validate(length(min = 1, max = 100, message = "Location must be 1-100 characters"))]
+    #[serde(default)]
+    pub location: Option<String>,
+
+    #[serde(default)]
+    pub coordinates: Option<GeoPoint>,
+
+    /// Share the task in this workspace; the creator must be a member
+    #[serde(default)]
+    pub workspace_id: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, Default)]
 pub struct UpdateTaskRequest {
     #[validate(length(min = 1, max = 255, message = "Title must be 1-255 characters"))]
     pub title: Option<String>,
@@ -178,6 +331,27 @@ pub struct UpdateTaskRequest {
     pub priority: Option<TaskPriority>,
 
     pub due_date: Option<DateTime<Utc>>,
+
+    pub start_date: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 525600, message = "Estimate must be between 1 minute and 1 year"))]
+    pub estimate_minutes: Option<i32>,
+
+    #[validate(length(min = 1, max = 100, message = "Client must be 1-100 characters"))]
+    pub client: Option<String>,
+
+    #[validate(length(min = 1, max = 49, message = "Context must be 1-49 characters"))]
+    pub context: Option<String>,
+
+    #[validate(length(min = 1, max = 100, message = "Location must be 1-100 characters"))]
+    pub location: Option<String>,
+
+    pub coordinates: Option<GeoPoint>,
+
+    /// Only update the task if it still has this version, i.e. nobody
+    /// changed it since it was read
+    #[serde(default)]
+    pub version: Option<i32>,
 }
 
 fn validate_title(title: &str) -> Result<(), ValidationError> {
@@ -192,13 +366,16 @@ fn validate_title(title: &str) -> Result<(), ValidationError> {
 pub enum TaskError {
     #[error("Validation error: {0}")]
     ValidationError(#[from] validator::ValidationErrors),
+    /// The `start_before_due_check` the database schemas enforce
+    #[error("Start date must not be after the due date")]
+    StartAfterDue,
 }
 
 impl Task {
     pub fn new(request: StoreTaskRequest, user_id: Uuid) -> Result<Self, TaskError> {
         request
             .validate()
-            .map_err(|e| TaskError::ValidationError(e))?;
+            .map_err(TaskError::ValidationError)?;
 
         let completed_at = if matches!(request.status, TaskStatus::Completed) {
             Some(Utc::now())
@@ -208,15 +385,27 @@ impl Task {
 
         Ok(Self {
             id: Uuid::new_v4(),
+            number: 0,
             title: request.title.trim().to_string(),
             description: request.description.map(|d| d.trim().to_string()),
             status: request.status,
             priority: request.priority,
             user_id,
+            workspace_id: request.workspace_id,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            version: 1,
             due_date: request.due_date,
+            start_date: request.start_date,
             completed_at,
+            estimate_minutes: request.estimate_minutes,
+            sort_order: None,
+            pinned: false,
+            client: request.client.map(|c| c.trim().to_string()),
+            context: request.context.as_deref().map(normalize_context),
+            location: request.location.map(|l| l.trim().to_string()),
+            latitude: request.coordinates.map(|p| p.latitude),
+            longitude: request.coordinates.map(|p| p.longitude),
         })
     }
     /**
@@ -251,20 +440,46 @@ impl Task {
         }
     }
 
-    #[allow(dead_code)]
+    /// Local calendar day the task is due on
+    pub fn due_day(&self) -> Option<NaiveDate> {
+        self.due_date.as_ref().map(calendar::local_date)
+    }
+
+    /// Whether the task's due day has passed in the user's timezone; a task
+    /// due today is not overdue until local midnight
     pub fn is_overdue(&self) -> bool {
-        if let Some(due_date) = self.due_date {
-            due_date < Utc::now()
-        } else {
-            false
+        self.due_day().is_some_and(|day| day < calendar::today())
+    }
+
+    pub fn is_due_today(&self) -> bool {
+        self.due_day() == Some(calendar::today())
+    }
+
+    /// Whether the start date, if any, is not after the due date, as the
+    /// database schemas require of every row
+    pub fn check_dates(&self) -> Result<(), TaskError> {
+        match (self.start_date, self.due_date) {
+            (Some(start_date), Some(due_date)) if start_date > due_date => Err(TaskError::StartAfterDue),
+            _ => Ok(()),
         }
     }
 
-    #[allow(dead_code)]
+    /// Whether the task's start date, if any, has been reached
+    pub fn has_started(&self) -> bool {
+        self.start_date.is_none_or(|start_date| start_date <= Utc::now())
+    }
+
     pub fn is_completed(&self) -> bool {
         matches!(self.status, TaskStatus::Completed)
     }
 
+    pub fn coordinates(&self) -> Option<GeoPoint> {
+        Some(GeoPoint {
+            latitude: self.latitude?,
+            longitude: self.longitude?,
+        })
+    }
+
     #[allow(dead_code)]
     pub fn is_in_process(&self) -> bool {
         matches!(self.status, TaskStatus::InProgress)
@@ -272,12 +487,7 @@ impl Task {
 
     #[allow(dead_code)]
     pub fn day_until_due(&self) -> Option<i64> {
-        if let Some(due_date) = self.due_date {
-            let days = due_date.signed_duration_since(Utc::now()).num_days();
-            Some(days)
-        } else {
-            None
-        }
+        self.due_day().map(|day| (day - calendar::today()).num_days())
     }
 
     #[allow(dead_code)]
@@ -292,9 +502,11 @@ impl Task {
             }
         }
 
-        if self.description != request.description {
-            self.description = request.description;
-            updated = true;
+        if let Some(description) = request.description {
+            if self.description.as_ref() != Some(&description) {
+                self.description = Some(description);
+                updated = true;
+            }
         }
 
         if let Some(status) = request.status {
@@ -323,9 +535,54 @@ impl Task {
             }
         }
 
-        if self.due_date != request.due_date {
-            self.due_date = request.due_date;
-            updated = true;
+        if let Some(due_date) = request.due_date {
+            if self.due_date != Some(due_date) {
+                self.due_date = Some(due_date);
+                updated = true;
+            }
+        }
+
+        if let Some(start_date) = request.start_date {
+            if self.start_date != Some(start_date) {
+                self.start_date = Some(start_date);
+                updated = true;
+            }
+        }
+
+        if let Some(estimate_minutes) = request.estimate_minutes {
+            if self.estimate_minutes != Some(estimate_minutes) {
+                self.estimate_minutes = Some(estimate_minutes);
+                updated = true;
+            }
+        }
+
+        if let Some(client) = request.client {
+            if self.client.as_ref() != Some(&client) {
+                self.client = Some(client);
+                updated = true;
+            }
+        }
+
+        if let Some(context) = request.context.as_deref().map(normalize_context) {
+            if self.context.as_ref() != Some(&context) {
+                self.context = Some(context);
+                updated = true;
+            }
+        }
+
+        if let Some(location) = request.location {
+            if self.location.as_ref() != Some(&location) {
+                self.location = Some(location);
+                updated = true;
+            }
+        }
+
+        if let Some(point) = request.coordinates {
+            if self.coordinates() != Some(point) {
+                self.latitude = Some(point.latitude);
+                self.longitude = Some(point.longitude);
+                updated = true;
+            }
         }
 
         if updated {
@@ -334,23 +591,184 @@ impl Task {
     }
 }
 
+/// Open tasks not updated for this many days count as stale in the statistics
+pub const STALE_AFTER_DAYS: i64 = 30;
+
 // Task statistics DTO
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TaskStatistics {
     pub total_tasks: i64,
     pub pending_tasks: i64,
     pub in_progress_tasks: i64,
     pub completed_tasks: i64,
     pub overdue_tasks: i64,
+    /// Open tasks nobody has touched for a while
+    pub stale_tasks: i64,
+}
+
+impl TaskStatistics {
+    /// Count `tasks` the way the statistics query does: overdue means due
+    /// before `due_before`, stale means last updated before `stale_before`
+    pub fn from_tasks(tasks: &[Task], due_before: DateTime<Utc>, stale_before: DateTime<Utc>) -> Self {
+        let mut stats = Self {
+            total_tasks: tasks.len() as i64,
+            ..Self::default()
+        };
+
+        for task in tasks {
+            match task.status {
+                TaskStatus::Pending => stats.pending_tasks += 1,
+                TaskStatus::InProgress => stats.in_progress_tasks += 1,
+                TaskStatus::Completed => stats.completed_tasks += 1,
+                // Custom statuses are only reflected in the total
+                TaskStatus::Custom(_) => {}
+            }
+
+            if !task.is_completed() {
+                if task.due_date.is_some_and(|due| due < due_before) {
+                    stats.overdue_tasks += 1;
+                }
+                if task.updated_at < stale_before {
+                    stats.stale_tasks += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Tasks that are not completed, including those in custom statuses
+    pub fn open_tasks(&self) -> i64 {
+        self.total_tasks - self.completed_tasks
+    }
+}
+
+/// Limits task statistics to tasks created, or completed, in a date range.
+/// Lower bounds are inclusive, upper bounds exclusive; a completion bound
+/// leaves out every task that is not completed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatisticsFilter {
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub completed_after: Option<DateTime<Utc>>,
+    pub completed_before: Option<DateTime<Utc>>,
+}
+
+impl StatisticsFilter {
+    /// Whether `task` falls in the ranges, the way the statistics query decides
+    pub fn matches(&self, task: &Task) -> bool {
+        fn within(instant: Option<DateTime<Utc>>, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> bool {
+            match instant {
+                Some(instant) => after.is_none_or(|after| instant >= after) && before.is_none_or(|before| instant < before),
+                None => after.is_none() && before.is_none(),
+            }
+        }
+
+        within(Some(task.created_at), self.created_after, self.created_before)
+            && within(task.completed_at, self.completed_after, self.completed_before)
+    }
+}
+
+/// Field `TaskRepository::count_grouped` groups tasks by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskGrouping {
+    Status,
+    Priority,
+    Context,
+}
+
+/// One group of `TaskRepository::count_grouped`; tasks without a context
+/// form the `Context(None)` group
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaskGroup {
+    Status(TaskStatus),
+    Priority(TaskPriority),
+    Context(Option<String>),
+}
+
+impl TaskGroup {
+    /// The group `task` belongs to
+    pub fn of(task: &Task, grouping: TaskGrouping) -> Self {
+        match grouping {
+            TaskGrouping::Status => TaskGroup::Status(task.status),
+            TaskGrouping::Priority => TaskGroup::Priority(task.priority),
+            TaskGrouping::Context => TaskGroup::Context(task.context.clone()),
+        }
+    }
+}
+
+/// Open and completed tasks of one group, for the breakdowns of `stats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupBreakdown {
+    pub group: TaskGroup,
+    pub open: i64,
+    pub completed: i64,
+}
+
+impl GroupBreakdown {
+    pub fn total(&self) -> i64 {
+        self.open + self.completed
+    }
+}
+
+/// Per-priority and per-context open/completed counts, alongside `TaskStatistics`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetailedStatistics {
+    pub by_priority: Vec<GroupBreakdown>,
+    pub by_context: Vec<GroupBreakdown>,
+}
+
+impl DetailedStatistics {
+    /// Splits the task counts of each group of `totals` into open and the
+    /// `completed` ones
+    pub fn breakdown(totals: HashMap<TaskGroup, i64>, completed: &HashMap<TaskGroup, i64>) -> Vec<GroupBreakdown> {
+        totals
+            .into_iter()
+            .map(|(group, total)| {
+                let completed = completed.get(&group).copied().unwrap_or(0);
+                GroupBreakdown { group, open: total - completed, completed }
+            })
+            .collect()
+    }
+}
+
+/// Open task count of one context, for `task contexts`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ContextCount {
+    pub context: String,
+    pub open_tasks: i64,
+}
+
+/// Contexts are compared case-insensitively and always carry a leading '@',
+/// so "Home", "@home" and " @HOME " are the same context
+pub fn normalize_context(context: &str) -> String {
+    format!("@{}", context.trim().trim_start_matches('@').to_lowercase())
 }
 
 // Task Filter for queries
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct TaskFilter {
     pub status: Option<TaskStatus>,
     pub priority: Option<TaskPriority>,
     pub overdue_only: bool,
+    /// Only tasks due on the user's current local day
+    pub due_today: bool,
     pub search_term: Option<String>,
+    pub near: Option<NearFilter>,
+    pub context: Option<String>,
+    /// Skip tasks whose start date is still in the future
+    pub started_only: bool,
+    /// Leave out tasks other users shared with this one
+    pub owned_only: bool,
+    /// Only tasks due strictly before this instant
+    pub due_before: Option<DateTime<Utc>>,
+    /// Only tasks due at or after this instant
+    pub due_after: Option<DateTime<Utc>>,
+    /// Order of the result; the list order of the repository if not set
+    pub sort: Option<TaskSort>,
+    /// Cursor: only tasks sorted after this one
+    pub after: Option<Uuid>,
+    pub limit: Option<usize>,
 }
 
 impl TaskFilter {
@@ -377,9 +795,302 @@ impl TaskFilter {
         self
     }
 
+    #[allow(dead_code)]
+    pub fn due_today(mut self) -> Self {
+        self.due_today = true;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn started_only(mut self) -> Self {
+        self.started_only = true;
+        self
+    }
+
+    pub fn owned_only(mut self) -> Self {
+        self.owned_only = true;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn with_search(mut self, term: String) -> Self {
         self.search_term = Some(term);
         self
     }
+
+    /// Whether the result is sorted, cut at a cursor or truncated after filtering
+    pub fn is_paginated(&self) -> bool {
+        self.sort.is_some() || self.after.is_some() || self.limit.is_some()
+    }
+
+    /// Whether any condition narrows the tasks down, as opposed to ordering
+    /// or paging them
+    pub fn has_conditions(&self) -> bool {
+        self.status.is_some()
+            || self.priority.is_some()
+            || self.overdue_only
+            || self.due_today
+            || self.search_term.is_some()
+            || self.near.is_some()
+            || self.context.is_some()
+            || self.started_only
+            || self.due_before.is_some()
+            || self.due_after.is_some()
+    }
+}
+
+/// Field a task list can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskSortField {
+    /// Pinned first, then by manual rank, then most recently updated
+    #[default]
+    Manual,
+    Created,
+    Updated,
+    Due,
+    Priority,
+    Title,
+}
+
+/// Sort order of a task list, parsed from e.g. "due" or "-priority"
+/// (a leading '-' sorts descending). Ties are broken by task ID so the order
+/// is total, which cursors rely on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaskSort {
+    pub field: TaskSortField,
+    pub descending: bool,
+}
+
+impl TaskSort {
+    pub fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        let ordering = match self.field {
+            TaskSortField::Manual => b
+                .pinned
+                .cmp(&a.pinned)
+                .then_with(|| nulls_last(a.sort_order, b.sort_order, |x, y| x.total_cmp(&y)))
+                .then_with(|| b.updated_at.cmp(&a.updated_at)),
+            TaskSortField::Created => a.created_at.cmp(&b.created_at),
+            TaskSortField::Updated => a.updated_at.cmp(&b.updated_at),
+            // Tasks without a due date stay last in both directions
+            TaskSortField::Due => {
+                return nulls_last(a.due_date, b.due_date, |x, y| self.direct(x.cmp(&y))).then_with(|| a.id.cmp(&b.id))
+            }
+            TaskSortField::Priority => (a.priority as i16).cmp(&(b.priority as i16)),
+            TaskSortField::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+        };
+        self.direct(ordering).then_with(|| a.id.cmp(&b.id))
+    }
+
+    fn direct(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+fn nulls_last<T: Copy>(a: Option<T>, b: Option<T>, compare: impl Fn(T, T) -> Ordering) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => compare(a, b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl std::str::FromStr for TaskSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+        let field = match name {
+            "manual" => TaskSortField::Manual,
+            "created" => TaskSortField::Created,
+            "updated" => TaskSortField::Updated,
+            "due" => TaskSortField::Due,
+            "priority" => TaskSortField::Priority,
+            "title" => TaskSortField::Title,
+            other => {
+                return Err(format!(
+                    "unknown sort field '{}' (expected manual, created, updated, due, priority or title)",
+                    other
+                ))
+            }
+        };
+        Ok(TaskSort { field, descending })
+    }
+}
+
+/// A slice of a sorted task list: the tasks after the `after` cursor (or
+/// from the start), skipping `offset` more and keeping at most `limit`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PageRequest {
+    pub sort: TaskSort,
+    pub after: Option<Uuid>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of a list, with the size of the whole list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Items in the whole list, across all pages
+    pub total: i64,
+    /// `after` cursor of the following page; `None` on the last one
+    pub next_cursor: Option<Uuid>,
+}
+
+impl Page<Task> {
+    /// Page of `items` that `skipped` tasks of a `total`-long list precede
+    pub fn new(items: Vec<Task>, skipped: usize, total: i64) -> Self {
+        let next_cursor = if ((skipped + items.len()) as i64) < total {
+            items.last().map(|task| task.id)
+        } else {
+            None
+        };
+        Self { items, total, next_cursor }
+    }
+}
+
+/// Gap between neighbouring ranks when tasks are (re)numbered
+pub const SORT_ORDER_STEP: f64 = 1024.0;
+
+/// Where to place a task relative to another one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovePosition {
+    Before,
+    After,
+}
+
+/// Rank for a task placed between two neighbours, or `None` when the gap
+/// between them is exhausted and the list has to be renumbered.
+pub fn sort_order_between(previous: Option<f64>, next: Option<f64>) -> Option<f64> {
+    match (previous, next) {
+        (None, None) => Some(SORT_ORDER_STEP),
+        (Some(previous), None) => Some(previous + SORT_ORDER_STEP),
+        (None, Some(next)) => Some(next - SORT_ORDER_STEP),
+        (Some(previous), Some(next)) => {
+            let middle = previous + (next - previous) / 2.0;
+            (middle > previous && middle < next).then_some(middle)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detailed_statistics_breakdown() {
+        let work = TaskGroup::Context(Some("@work".to_string()));
+        let totals = HashMap::from([(work.clone(), 5), (TaskGroup::Context(None), 2)]);
+        let completed = HashMap::from([(work.clone(), 3)]);
+
+        let mut breakdown = DetailedStatistics::breakdown(totals, &completed);
+        breakdown.sort_by_key(|group| group.total());
+        assert_eq!(breakdown[0], GroupBreakdown { group: TaskGroup::Context(None), open: 2, completed: 0 });
+        assert_eq!(breakdown[1], GroupBreakdown { group: work, open: 2, completed: 3 });
+    }
+
+    #[test]
+    fn test_sort_order_between() {
+        assert_eq!(sort_order_between(None, None), Some(SORT_ORDER_STEP));
+        assert_eq!(sort_order_between(Some(1024.0), None), Some(2048.0));
+        assert_eq!(sort_order_between(None, Some(1024.0)), Some(0.0));
+        assert_eq!(sort_order_between(Some(1024.0), Some(2048.0)), Some(1536.0));
+
+        // Repeated halving eventually runs out of precision
+        let next = 2.0;
+        let mut previous = 1.0;
+        let mut moves = 0;
+        while let Some(middle) = sort_order_between(Some(previous), Some(next)) {
+            previous = middle;
+            moves += 1;
+        }
+        assert!(moves > 40);
+    }
+
+    #[test]
+    fn test_task_sort() {
+        assert_eq!("-priority".parse::<TaskSort>(), Ok(TaskSort { field: TaskSortField::Priority, descending: true }));
+        assert_eq!("due".parse::<TaskSort>(), Ok(TaskSort { field: TaskSortField::Due, descending: false }));
+        assert!("size".parse::<TaskSort>().is_err());
+
+        let task = |title: &str, due_date: Option<DateTime<Utc>>| {
+            Task::new(StoreTaskRequest { title: title.to_string(), due_date, ..Default::default() }, Uuid::new_v4()).unwrap()
+        };
+        let soon = task("b", Some(Utc::now()));
+        let later = task("A", Some(Utc::now() + chrono::Duration::days(1)));
+        let undated = task("c", None);
+
+        for descending in [false, true] {
+            let mut tasks = [undated.clone(), later.clone(), soon.clone()];
+            let sort = TaskSort { field: TaskSortField::Due, descending };
+            tasks.sort_by(|a, b| sort.compare(a, b));
+            let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+            assert_eq!(titles, if descending { ["A", "b", "c"] } else { ["b", "A", "c"] });
+        }
+
+        let sort: TaskSort = "title".parse().unwrap();
+        assert_eq!(sort.compare(&later, &soon), Ordering::Less);
+    }
+
+    #[test]
+    fn test_normalize_context() {
+        assert_eq!(normalize_context("Home"), "@home");
+        assert_eq!(normalize_context(" @Errands "), "@errands");
+        assert_eq!(normalize_context("@home"), normalize_context("HOME"));
+    }
+
+    #[test]
+    fn test_statistics_filter_matches() {
+        let open = Task::new(StoreTaskRequest { title: "Open".to_string(), ..Default::default() }, Uuid::new_v4()).unwrap();
+        let done = Task::new(
+            StoreTaskRequest { title: "Done".to_string(), status: TaskStatus::Completed, ..Default::default() },
+            Uuid::new_v4(),
+        )
+        .unwrap();
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+
+        assert!(StatisticsFilter::default().matches(&open));
+        let created = StatisticsFilter { created_after: Some(hour_ago), ..Default::default() };
+        assert!(created.matches(&open) && created.matches(&done));
+        let created_before = StatisticsFilter { created_before: Some(hour_ago), ..Default::default() };
+        assert!(!created_before.matches(&open));
+
+        let completed = StatisticsFilter { completed_after: Some(hour_ago), ..Default::default() };
+        assert!(completed.matches(&done));
+        assert!(!completed.matches(&open));
+    }
+
+    #[test]
+    fn test_overdue_flips_at_local_midnight() {
+        let today = calendar::today();
+        let due = |date: NaiveDate| {
+            Task::new(
+                StoreTaskRequest {
+                    title: "Task".to_string(),
+                    due_date: Some(calendar::end_of_day(date)),
+                    ..Default::default()
+                },
+                Uuid::new_v4(),
+            )
+            .unwrap()
+        };
+
+        let due_today = due(today);
+        assert!(due_today.is_due_today());
+        assert!(!due_today.is_overdue());
+        assert_eq!(due_today.day_until_due(), Some(0));
+
+        let due_yesterday = due(today - chrono::Duration::days(1));
+        assert!(due_yesterday.is_overdue());
+        assert!(!due_yesterday.is_due_today());
+        assert_eq!(due_yesterday.day_until_due(), Some(-1));
+    }
 }
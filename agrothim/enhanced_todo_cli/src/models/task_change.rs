@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::task::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskChangeKind {
+    Created,
+    Updated,
+    Completed,
+    Deleted,
+}
+
+impl TaskChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskChangeKind::Created => "created",
+            TaskChangeKind::Updated => "updated",
+            TaskChangeKind::Completed => "completed",
+            TaskChangeKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A task change pushed to live API clients
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskChange {
+    pub kind: TaskChangeKind,
+    pub task_id: Uuid,
+    /// The task after the change; absent for deletions
+    pub task: Option<Task>,
+    /// `None` for changes another process wrote to the database
+    pub actor_id: Option<Uuid>,
+    pub occurred_at: DateTime<Utc>,
+    /// Users with access to the task: its owner, the members of its
+    /// workspace and the users it is shared with
+    #[serde(skip)]
+    pub recipients: Vec<Uuid>,
+}
+
+impl TaskChange {
+    pub fn is_for(&self, user_id: &Uuid) -> bool {
+        self.recipients.contains(user_id)
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A column of the `task list` table, chosen with `--columns` or `config set
+/// display.columns`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskColumn {
+    Id,
+    Title,
+    Status,
+    Priority,
+    Due,
+    Start,
+    Created,
+    Updated,
+    Completed,
+    Estimate,
+    Context,
+    Client,
+    Location,
+}
+
+impl TaskColumn {
+    pub const ALL: [TaskColumn; 13] = [
+        TaskColumn::Id,
+        TaskColumn::Title,
+        TaskColumn::Status,
+        TaskColumn::Priority,
+        TaskColumn::Due,
+        TaskColumn::Start,
+        TaskColumn::Created,
+        TaskColumn::Updated,
+        TaskColumn::Completed,
+        TaskColumn::Estimate,
+        TaskColumn::Context,
+        TaskColumn::Client,
+        TaskColumn::Location,
+    ];
+
+    /// Columns shown unless the user picks others
+    pub const DEFAULT: [TaskColumn; 6] = [
+        TaskColumn::Id,
+        TaskColumn::Title,
+        TaskColumn::Status,
+        TaskColumn::Priority,
+        TaskColumn::Due,
+        TaskColumn::Created,
+    ];
+
+    /// Name the column is picked by
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskColumn::Id => "id",
+            TaskColumn::Title => "title",
+            TaskColumn::Status => "status",
+            TaskColumn::Priority => "priority",
+            TaskColumn::Due => "due",
+            TaskColumn::Start => "start",
+            TaskColumn::Created => "created",
+            TaskColumn::Updated => "updated",
+            TaskColumn::Completed => "completed",
+            TaskColumn::Estimate => "estimate",
+            TaskColumn::Context => "context",
+            TaskColumn::Client => "client",
+            TaskColumn::Location => "location",
+        }
+    }
+
+    /// Heading of the column in the table
+    pub fn header(&self) -> &'static str {
+        match self {
+            TaskColumn::Id => "ID",
+            TaskColumn::Title => "Title",
+            TaskColumn::Status => "Status",
+            TaskColumn::Priority => "Priority",
+            TaskColumn::Due => "Due Date",
+            TaskColumn::Start => "Start Date",
+            TaskColumn::Created => "Created",
+            TaskColumn::Updated => "Updated",
+            TaskColumn::Completed => "Completed",
+            TaskColumn::Estimate => "Estimate",
+            TaskColumn::Context => "Context",
+            TaskColumn::Client => "Client",
+            TaskColumn::Location => "Location",
+        }
+    }
+
+    /// Parse a comma-separated list such as `id,title,due,context`
+    pub fn parse_list(input: &str) -> Result<Vec<TaskColumn>, String> {
+        let columns = input
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<TaskColumn>, String>>()?;
+        if columns.is_empty() {
+            return Err("expected at least one column".to_string());
+        }
+        Ok(columns)
+    }
+}
+
+impl FromStr for TaskColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        TaskColumn::ALL
+            .into_iter()
+            .find(|column| column.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = TaskColumn::ALL.iter().map(TaskColumn::name).collect();
+                format!("unknown column '{}' (expected {})", s.trim(), names.join(", "))
+            })
+    }
+}
+
+impl fmt::Display for TaskColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_column_list() {
+        assert_eq!(
+            TaskColumn::parse_list("id, Title,due,context"),
+            Ok(vec![TaskColumn::Id, TaskColumn::Title, TaskColumn::Due, TaskColumn::Context])
+        );
+        assert!(TaskColumn::parse_list(" , ").is_err());
+        assert!(TaskColumn::parse_list("id,tags").unwrap_err().contains("unknown column 'tags'"));
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Things that can happen to a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventKind {
+    Created,
+    Completed,
+    /// The task was shared with another user
+    Shared,
+}
+
+impl TaskEventKind {
+    pub const ALL: [TaskEventKind; 3] = [TaskEventKind::Created, TaskEventKind::Completed, TaskEventKind::Shared];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskEventKind::Created => "created",
+            TaskEventKind::Completed => "completed",
+            TaskEventKind::Shared => "shared",
+        }
+    }
+}
+
+impl fmt::Display for TaskEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for TaskEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("Unknown task event '{}'", s))
+    }
+}
+
+impl TryFrom<String> for TaskEventKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// One entry of the activity feed, with the names it is displayed by
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TaskEvent {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    /// Current title of the task
+    pub task_title: String,
+    pub workspace_id: Option<Uuid>,
+    /// Username of the user who did it
+    pub actor: String,
+    #[sqlx(try_from = "String")]
+    pub kind: TaskEventKind,
+    /// Username of the other user involved, if any
+    pub subject: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaskEvent {
+    /// One-line summary such as `alice shared "Plan sprint" with bob`
+    pub fn describe(&self) -> String {
+        match (self.kind, &self.subject) {
+            (TaskEventKind::Shared, Some(subject)) => {
+                format!("{} shared \"{}\" with {}", self.actor, self.task_title, subject)
+            }
+            (kind, _) => format!("{} {} \"{}\"", self.actor, kind, self.task_title),
+        }
+    }
+}
+
+/// Recent activity, and when the user looked at it before
+#[derive(Debug, Clone)]
+pub struct ActivityFeed {
+    /// Oldest first
+    pub events: Vec<TaskEvent>,
+    /// Events after this are new to the user
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl ActivityFeed {
+    pub fn is_new(&self, event: &TaskEvent) -> bool {
+        self.last_seen.is_none_or(|last_seen| event.created_at > last_seen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: TaskEventKind, subject: Option<&str>) -> TaskEvent {
+        TaskEvent {
+            id: Uuid::new_v4(),
+            task_id: Uuid::new_v4(),
+            task_title: "Plan sprint".to_string(),
+            workspace_id: None,
+            actor: "alice".to_string(),
+            kind,
+            subject: subject.map(str::to_string),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(event(TaskEventKind::Created, None).describe(), "alice created \"Plan sprint\"");
+        assert_eq!(event(TaskEventKind::Completed, None).describe(), "alice completed \"Plan sprint\"");
+        assert_eq!(
+            event(TaskEventKind::Shared, Some("bob")).describe(),
+            "alice shared \"Plan sprint\" with bob"
+        );
+    }
+
+    #[test]
+    fn test_kind_round_trip() {
+        for kind in TaskEventKind::ALL {
+            assert_eq!(kind.as_str().parse::<TaskEventKind>().unwrap(), kind);
+        }
+        assert!("commented".parse::<TaskEventKind>().is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::models::task::Task;
+
+/// Length of the short hash shown for a task, i.e. the first characters of its UUID
+pub const SHORT_ID_LEN: usize = 8;
+
+/// Shortest UUID prefix accepted on input
+const MIN_PREFIX_LEN: usize = 4;
+
+/// A task identifier as typed on the command line.
+///
+/// Accepts a full UUID, a sequential number (`12` or `#12`) or a UUID prefix
+/// of at least four hex digits as shown in the short hash column. Digits-only
+/// input shorter than a short hash is read as a number; prefix it with more
+/// hex digits (or use the full hash) to mean a UUID prefix instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskRef {
+    Id(Uuid),
+    Number(i32),
+    Prefix(String),
+}
+
+impl FromStr for TaskRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Ok(id) = Uuid::parse_str(s) {
+            return Ok(TaskRef::Id(id));
+        }
+
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        if !digits.is_empty()
+            && digits.chars().all(|c| c.is_ascii_digit())
+            && (s.starts_with('#') || digits.len() < SHORT_ID_LEN)
+        {
+            return digits
+                .parse::<i32>()
+                .ok()
+                .filter(|number| *number > 0)
+                .map(TaskRef::Number)
+                .ok_or_else(|| format!("invalid task number '{}'", s));
+        }
+
+        if s.len() >= MIN_PREFIX_LEN && s.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            return Ok(TaskRef::Prefix(s.to_ascii_lowercase()));
+        }
+
+        Err(format!(
+            "invalid task ID '{}': expected a UUID, a task number or at least {} characters of the hash",
+            s, MIN_PREFIX_LEN
+        ))
+    }
+}
+
+impl fmt::Display for TaskRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskRef::Id(id) => write!(f, "{}", id),
+            TaskRef::Number(number) => write!(f, "#{}", number),
+            TaskRef::Prefix(prefix) => write!(f, "{}", prefix),
+        }
+    }
+}
+
+/// How task identifiers are shown, set with `config set display.ids`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdStyle {
+    /// First characters of the UUID
+    #[default]
+    Hash,
+    /// Per-user sequential number
+    Number,
+    /// Full UUID
+    Uuid,
+}
+
+impl IdStyle {
+    pub fn format(&self, task: &Task) -> String {
+        self.format_id(&task.id, task.number)
+    }
+
+    pub fn format_id(&self, id: &Uuid, number: i32) -> String {
+        match self {
+            IdStyle::Hash => short_hash(id),
+            IdStyle::Number => format!("#{}", number),
+            IdStyle::Uuid => id.to_string(),
+        }
+    }
+}
+
+impl FromStr for IdStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hash" => Ok(IdStyle::Hash),
+            "number" => Ok(IdStyle::Number),
+            "uuid" => Ok(IdStyle::Uuid),
+            _ => Err("expected one of: hash, number, uuid".to_string()),
+        }
+    }
+}
+
+pub fn short_hash(id: &Uuid) -> String {
+    format!("{:.*}", SHORT_ID_LEN, id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_task_ref() {
+        let id = Uuid::new_v4();
+        assert_eq!(id.to_string().parse::<TaskRef>(), Ok(TaskRef::Id(id)));
+        assert_eq!("12".parse::<TaskRef>(), Ok(TaskRef::Number(12)));
+        assert_eq!("#12".parse::<TaskRef>(), Ok(TaskRef::Number(12)));
+        assert_eq!("#12345678".parse::<TaskRef>(), Ok(TaskRef::Number(12345678)));
+        // A short hash that happens to be all digits
+        assert_eq!("12345678".parse::<TaskRef>(), Ok(TaskRef::Prefix("12345678".to_string())));
+        assert_eq!("3F2A9C1E".parse::<TaskRef>(), Ok(TaskRef::Prefix("3f2a9c1e".to_string())));
+
+        assert!("#0".parse::<TaskRef>().is_err());
+        assert!("abc".parse::<TaskRef>().is_err());
+        assert!("not-a-task".parse::<TaskRef>().is_err());
+    }
+}
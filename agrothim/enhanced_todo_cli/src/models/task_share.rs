@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One task made visible to a user other than its owner
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TaskShare {
+    pub task_id: Uuid,
+    /// The user the task is shared with
+    pub user_id: Uuid,
+    /// The user may see the task but not change it
+    pub read_only: bool,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A single timer run against a task; `ended_at` is empty while it is running
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TimeEntry {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TimeEntry {
+    /// Elapsed time, counting running entries up to now
+    pub fn duration(&self) -> Duration {
+        self.ended_at.unwrap_or_else(Utc::now) - self.started_at
+    }
+}
+
+/// Total time tracked on one task
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TrackedTime {
+    pub total_seconds: i64,
+    pub running: bool,
+}
+
+/// One row of the time report: time tracked per task within a period
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TaskTimeSummary {
+    pub task_id: Uuid,
+    pub task_number: i32,
+    pub title: String,
+    pub client: Option<String>,
+    pub entry_count: i64,
+    pub total_seconds: i64,
+}
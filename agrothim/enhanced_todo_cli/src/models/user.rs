@@ -5,17 +5,24 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
+use crate::models::password_strength::PasswordStrength;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    /// When the user confirmed their email address; unverified accounts cannot log in
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// False while the account is suspended; suspended accounts cannot log in or use their tokens
+    pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Validate, Deserialize)]
+#[validate(schema(function = "validate_password_against_account", skip_on_field_errors = true))]
 pub struct StoreUserRequest {
     #[validate(length(
         min = 3,
@@ -38,8 +45,19 @@ pub struct StoreUserRequest {
     pub password_hash: String,
 }
 
-#[derive(Debug, Validate, Deserialize)]
+#[derive(Debug, Validate, Deserialize, Default)]
 pub struct UpdateUserRequest {
+    #[validate(length(
+        min = 3,
+        max = 50,
+        message = "Username must be between 3 and 50 characters"
+    ))]
+    #[validate(regex(
+        path = "USERNAME_REGEX",
+        message = "Username can only contain letters, numbers, and underscores"
+    ))]
+    pub username: Option<String>,
+
     #[validate(email(message = "Invalid email format"))]
     pub email: Option<String>,
 
@@ -50,11 +68,22 @@ pub struct UpdateUserRequest {
     pub password_hash: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A rename recorded in the `username_changes` table
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UsernameChange {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub old_username: String,
+    pub new_username: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
     pub username: String,
     pub email: String,
+    pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -72,11 +101,20 @@ fn validate_password(password: &str) -> Result<(), ValidationError> {
         return Err(ValidationError::new("password_too_long"));
     }
 
-    let has_letter = password.chars().any(|c| c.is_alphabetic());
-    let has_number = password.chars().any(|c| c.is_numeric());
+    check_strength(password, &[])
+}
+
+/// Registration also rejects passwords built from the username or email
+fn validate_password_against_account(request: &StoreUserRequest) -> Result<(), ValidationError> {
+    check_strength(&request.password, &[&request.username, &request.email])
+}
 
-    if !has_letter || !has_number {
-        return Err(ValidationError::new("password_complexity"));
+fn check_strength(password: &str, user_inputs: &[&str]) -> Result<(), ValidationError> {
+    let strength = PasswordStrength::estimate(password, user_inputs);
+    if !strength.is_acceptable() {
+        let mut error = ValidationError::new("password_too_weak");
+        error.message = Some(strength.summary().into());
+        return Err(error);
     }
 
     Ok(())
@@ -117,12 +155,18 @@ impl UpdateUserRequest {
     #[allow(dead_code)]
     pub fn new() -> Self {
         Self {
+            username: None,
             email: None,
             password: None,
             password_hash: None,
         }
     }
 
+    pub fn username(mut self, username: String) -> Self {
+        self.username = Some(username);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn email(mut self, email: String) -> Self {
         self.email = Some(email);
@@ -142,12 +186,17 @@ impl User {
         verify(password, &self.password_hash).unwrap_or(false)
     }
 
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
     #[allow(dead_code)]
     pub fn to_response(&self) -> UserResponse {
         UserResponse {
-            id: self.id.clone(),
+            id: self.id,
             username: self.username.clone(),
             email: self.email.clone(),
+            is_active: self.is_active,
             created_at: self.created_at,
             updated_at: self.updated_at,
         }
@@ -164,7 +213,7 @@ mod tests {
         let request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         );
         assert!(request.is_ok());
     }
@@ -174,7 +223,7 @@ mod tests {
         let request = StoreUserRequest::new(
             "ab".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         );
         assert!(request.is_err());
     }
@@ -184,7 +233,24 @@ mod tests {
         let request = StoreUserRequest::new(
             "testuser".to_string(),
             "invalid-email".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
+        );
+        assert!(request.is_err());
+    }
+
+    #[test]
+    fn test_weak_or_personal_password_rejected() {
+        let request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "qwerty123".to_string(),
+        );
+        assert!(request.is_err());
+
+        let request = StoreUserRequest::new(
+            "heronfan".to_string(),
+            "heron@example.com".to_string(),
+            "Heronfan2024".to_string(),
         );
         assert!(request.is_err());
     }
@@ -194,7 +260,7 @@ mod tests {
         let request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         )
         .unwrap();
 
@@ -203,11 +269,13 @@ mod tests {
             username: request.username,
             email: request.email,
             password_hash: request.password_hash,
+            email_verified_at: None,
+            is_active: true,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
 
-        assert!(user.verify_password("password123"));
+        assert!(user.verify_password("kestrel-canyon-42"));
         assert!(!user.verify_password("wrongpassword"));
     }
 }
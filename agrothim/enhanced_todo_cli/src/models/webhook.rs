@@ -0,0 +1,174 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    /// A new session was created with the user's credentials
+    #[serde(rename = "auth.login")]
+    Login,
+    #[serde(rename = "auth.password_changed")]
+    PasswordChanged,
+    #[serde(rename = "task.created")]
+    TaskCreated,
+    #[serde(rename = "task.completed")]
+    TaskCompleted,
+    #[serde(rename = "task.deleted")]
+    TaskDeleted,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 5] = [
+        WebhookEvent::Login,
+        WebhookEvent::PasswordChanged,
+        WebhookEvent::TaskCreated,
+        WebhookEvent::TaskCompleted,
+        WebhookEvent::TaskDeleted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Login => "auth.login",
+            WebhookEvent::PasswordChanged => "auth.password_changed",
+            WebhookEvent::TaskCreated => "task.created",
+            WebhookEvent::TaskCompleted => "task.completed",
+            WebhookEvent::TaskDeleted => "task.deleted",
+        }
+    }
+
+    /// One-line human readable summary, used as the `text` of the payload so
+    /// chat webhooks (e.g. Slack) can display it directly. Task events name
+    /// the task by the `title` in `data`.
+    pub fn describe(&self, username: &str, data: &serde_json::Value) -> String {
+        let title = data.get("title").and_then(|title| title.as_str()).unwrap_or_default();
+        match self {
+            WebhookEvent::Login => format!("New login to todo-cli account '{}'", username),
+            WebhookEvent::PasswordChanged => format!("Password changed for todo-cli account '{}'", username),
+            WebhookEvent::TaskCreated => format!("Task \"{}\" created for '{}'", title, username),
+            WebhookEvent::TaskCompleted => format!("Task \"{}\" of '{}' completed", title, username),
+            WebhookEvent::TaskDeleted => format!("Task \"{}\" of '{}' deleted", title, username),
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|event| event.as_str() == s.trim())
+            .ok_or_else(|| {
+                let available: Vec<&str> = Self::ALL.iter().map(|e| e.as_str()).collect();
+                format!("Unknown event '{}'. Available events: {}", s, available.join(", "))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    /// Shared secret used to sign deliveries
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Subscribed event names; empty means every event
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event.as_str())
+    }
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct StoreWebhookRequest {
+    #[validate(url(message = "Webhook URL must be a valid URL"))]
+    #[validate(length(max = 2048, message = "Webhook URL must be at most 2048 characters"))]
+    pub url: String,
+
+    pub events: Vec<WebhookEvent>,
+}
+
+/// Outcome of delivering one event to one webhook, after any retries
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub attempts: i32,
+    /// HTTP status of the last attempt, if the endpoint answered
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+}
+
+impl WebhookDelivery {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// JSON body POSTed to webhook URLs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub occurred_at: DateTime<Utc>,
+    pub user_id: Uuid,
+    pub username: String,
+    pub text: String,
+    pub data: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filter() {
+        let mut webhook = Webhook {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: Vec::new(),
+            created_at: Utc::now(),
+        };
+        assert!(webhook.subscribes_to(WebhookEvent::Login));
+
+        webhook.events = vec!["auth.password_changed".to_string()];
+        assert!(!webhook.subscribes_to(WebhookEvent::Login));
+        assert!(webhook.subscribes_to(WebhookEvent::PasswordChanged));
+
+        assert_eq!("auth.login".parse::<WebhookEvent>(), Ok(WebhookEvent::Login));
+        assert_eq!("task.created".parse::<WebhookEvent>(), Ok(WebhookEvent::TaskCreated));
+        assert!("task.commented".parse::<WebhookEvent>().is_err());
+    }
+
+    #[test]
+    fn test_describe_task_events() {
+        let data = serde_json::json!({ "title": "Ship release" });
+        assert_eq!(
+            WebhookEvent::TaskCompleted.describe("alice", &data),
+            "Task \"Ship release\" of 'alice' completed"
+        );
+        assert_eq!(
+            WebhookEvent::Login.describe("alice", &data),
+            "New login to todo-cli account 'alice'"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::models::task::Task;
+
+/// Open tasks and their summed estimates for one slice of the calendar
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadBucket {
+    pub task_count: usize,
+    pub estimate_minutes: i64,
+    /// Tasks in this bucket that have no estimate
+    pub unestimated_count: usize,
+}
+
+impl WorkloadBucket {
+    fn add(&mut self, task: &Task) {
+        self.task_count += 1;
+        match task.estimate_minutes {
+            Some(minutes) => self.estimate_minutes += i64::from(minutes),
+            None => self.unestimated_count += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadDay {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub bucket: WorkloadBucket,
+}
+
+/// Remaining estimated work per day, based on due dates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workload {
+    /// Open tasks already past their due date
+    pub overdue: WorkloadBucket,
+    /// One entry per day of the horizon, starting today
+    pub days: Vec<WorkloadDay>,
+    /// Open tasks due after the horizon
+    pub later: WorkloadBucket,
+    /// Open tasks without a due date
+    pub undated: WorkloadBucket,
+    /// First day of the horizon
+    #[serde(skip)]
+    today: NaiveDate,
+}
+
+impl Workload {
+    /// Empty workload for the `days` days starting `today`
+    pub fn new(today: NaiveDate, days: u32) -> Self {
+        Workload {
+            days: (0..days)
+                .map(|offset| WorkloadDay {
+                    date: today + Duration::days(i64::from(offset)),
+                    bucket: WorkloadBucket::default(),
+                })
+                .collect(),
+            today,
+            ..Default::default()
+        }
+    }
+
+    /// Group open tasks by the local date they are due. Completed tasks are
+    /// ignored since their estimate is no longer remaining work.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn from_tasks(tasks: &[Task], today: NaiveDate, days: u32) -> Self {
+        let mut workload = Workload::new(today, days);
+        for task in tasks {
+            workload.add(task);
+        }
+        workload
+    }
+
+    /// Count one task, unless it is completed
+    pub fn add(&mut self, task: &Task) {
+        if task.is_completed() {
+            return;
+        }
+        let Some(due_day) = task.due_day() else {
+            self.undated.add(task);
+            return;
+        };
+
+        if due_day < self.today {
+            self.overdue.add(task);
+        } else {
+            let offset = (due_day - self.today).num_days() as usize;
+            match self.days.get_mut(offset) {
+                Some(day) => day.bucket.add(task),
+                None => self.later.add(task),
+            }
+        }
+    }
+
+    pub fn total_estimate_minutes(&self) -> i64 {
+        self.overdue.estimate_minutes
+            + self.days.iter().map(|day| day.bucket.estimate_minutes).sum::<i64>()
+            + self.later.estimate_minutes
+            + self.undated.estimate_minutes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::{StoreTaskRequest, TaskStatus};
+    use chrono::{Local, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn task_due(days_from_today: Option<i64>, estimate_minutes: Option<i32>, status: TaskStatus) -> Task {
+        let today = Local::now().date_naive();
+        let mut task = Task::new(
+            StoreTaskRequest {
+                title: "Task".to_string(),
+                status,
+                estimate_minutes,
+                ..Default::default()
+            },
+            Uuid::new_v4(),
+        )
+        .unwrap();
+        task.due_date = days_from_today.map(|days| {
+            let noon = (today + Duration::days(days)).and_hms_opt(12, 0, 0).unwrap();
+            Local.from_local_datetime(&noon).unwrap().with_timezone(&Utc)
+        });
+        task
+    }
+
+    #[test]
+    fn test_workload_groups_open_tasks_by_due_day() {
+        let today = Local::now().date_naive();
+        let tasks = vec![
+            task_due(Some(-2), Some(30), TaskStatus::Pending),
+            task_due(Some(0), Some(60), TaskStatus::InProgress),
+            task_due(Some(0), None, TaskStatus::Pending),
+            task_due(Some(1), Some(120), TaskStatus::Completed),
+            task_due(Some(10), Some(45), TaskStatus::Pending),
+            task_due(None, Some(15), TaskStatus::Pending),
+        ];
+
+        let workload = Workload::from_tasks(&tasks, today, 7);
+
+        assert_eq!(workload.days.len(), 7);
+        assert_eq!(workload.overdue.estimate_minutes, 30);
+        assert_eq!(
+            workload.days[0].bucket,
+            WorkloadBucket { task_count: 2, estimate_minutes: 60, unestimated_count: 1 }
+        );
+        // Completed work is not remaining work
+        assert_eq!(workload.days[1].bucket.task_count, 0);
+        assert_eq!(workload.later.estimate_minutes, 45);
+        assert_eq!(workload.undated.estimate_minutes, 15);
+        assert_eq!(workload.total_estimate_minutes(), 150);
+    }
+}
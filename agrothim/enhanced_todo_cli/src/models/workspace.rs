@@ -0,0 +1,214 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::task::{Task, TaskStatistics};
+
+/// Role of the user who created a workspace
+pub const OWNER_ROLE: &str = "owner";
+/// Role of everyone invited to a workspace
+pub const MEMBER_ROLE: &str = "member";
+
+/// A task list shared between its members. Tasks in it stay owned by their
+/// creator, but every member can see and change them.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Workspace {
+    pub id: Uuid,
+    pub name: String,
+    pub owner_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Workspace {
+    /// Short form of the ID shown in listings and accepted wherever a workspace is named
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WorkspaceMember {
+    pub workspace_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    /// `owner` or `member`
+    pub role: String,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct StoreWorkspaceRequest {
+    #[validate(length(min = 1, max = 100, message = "Workspace name must be 1-100 characters"))]
+    pub name: String,
+}
+
+/// Task counts of one member, over the tasks they created in a workspace
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MemberTaskCounts {
+    pub user_id: Uuid,
+    pub total_tasks: i64,
+    /// Tasks completed in the statistics period
+    pub completed_tasks: i64,
+    pub overdue_tasks: i64,
+}
+
+impl MemberTaskCounts {
+    /// Count `tasks` per creator the way the member statistics query does
+    #[cfg_attr(not(feature = "memory"), allow(dead_code))]
+    pub fn from_tasks(tasks: &[Task], due_before: DateTime<Utc>, completed_since: Option<DateTime<Utc>>) -> Vec<Self> {
+        let mut counts: Vec<Self> = Vec::new();
+        for task in tasks {
+            let index = match counts.iter().position(|c| c.user_id == task.user_id) {
+                Some(index) => index,
+                None => {
+                    counts.push(Self {
+                        user_id: task.user_id,
+                        ..Self::default()
+                    });
+                    counts.len() - 1
+                }
+            };
+            let member = &mut counts[index];
+            member.total_tasks += 1;
+            if task.is_completed() {
+                let in_period = match (completed_since, task.completed_at) {
+                    (None, _) => true,
+                    (Some(since), Some(completed_at)) => completed_at >= since,
+                    (Some(_), None) => false,
+                };
+                if in_period {
+                    member.completed_tasks += 1;
+                }
+            } else if task.due_date.is_some_and(|due| due < due_before) {
+                member.overdue_tasks += 1;
+            }
+        }
+        counts
+    }
+}
+
+/// Open overdue tasks by how long ago they were due
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OverdueBreakdown {
+    /// Due at most a week ago
+    pub up_to_week: i64,
+    /// Due one week to a month ago
+    pub up_to_month: i64,
+    /// Due more than a month ago
+    pub older: i64,
+}
+
+impl OverdueBreakdown {
+    pub const WEEK_DAYS: i64 = 7;
+    pub const MONTH_DAYS: i64 = 30;
+
+    /// Sort the open tasks due before `due_before` into buckets
+    #[cfg_attr(not(feature = "memory"), allow(dead_code))]
+    pub fn from_tasks(tasks: &[Task], due_before: DateTime<Utc>) -> Self {
+        let mut breakdown = Self::default();
+        let week_ago = due_before - Duration::days(Self::WEEK_DAYS);
+        let month_ago = due_before - Duration::days(Self::MONTH_DAYS);
+        for due in tasks
+            .iter()
+            .filter(|task| !task.is_completed())
+            .filter_map(|task| task.due_date)
+            .filter(|due| *due < due_before)
+        {
+            if due >= week_ago {
+                breakdown.up_to_week += 1;
+            } else if due >= month_ago {
+                breakdown.up_to_month += 1;
+            } else {
+                breakdown.older += 1;
+            }
+        }
+        breakdown
+    }
+
+    pub fn total(&self) -> i64 {
+        self.up_to_week + self.up_to_month + self.older
+    }
+}
+
+/// One member's line in the workspace statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberStatistics {
+    pub username: String,
+    #[serde(flatten)]
+    pub counts: MemberTaskCounts,
+}
+
+/// Statistics over all tasks shared in a workspace
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceStatistics {
+    pub totals: TaskStatistics,
+    /// Every member, the owner first, including members without tasks
+    pub members: Vec<MemberStatistics>,
+    pub overdue: OverdueBreakdown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::task::{StoreTaskRequest, TaskStatus};
+
+    fn task(user_id: Uuid, status: TaskStatus, due_days_ago: Option<i64>, now: DateTime<Utc>) -> Task {
+        let mut task = Task::new(
+            StoreTaskRequest {
+                title: "Task".to_string(),
+                status,
+                ..Default::default()
+            },
+            user_id,
+        )
+        .unwrap();
+        task.due_date = due_days_ago.map(|days| now - Duration::days(days));
+        task
+    }
+
+    #[test]
+    fn test_overdue_breakdown_buckets() {
+        let now = Utc::now();
+        let user_id = Uuid::new_v4();
+        let tasks = vec![
+            task(user_id, TaskStatus::Pending, Some(1), now),
+            task(user_id, TaskStatus::InProgress, Some(7), now),
+            task(user_id, TaskStatus::Pending, Some(20), now),
+            task(user_id, TaskStatus::Pending, Some(90), now),
+            // Completed and not yet due tasks are not overdue
+            task(user_id, TaskStatus::Completed, Some(3), now),
+            task(user_id, TaskStatus::Pending, Some(-2), now),
+            task(user_id, TaskStatus::Pending, None, now),
+        ];
+
+        let breakdown = OverdueBreakdown::from_tasks(&tasks, now);
+        assert_eq!(breakdown, OverdueBreakdown { up_to_week: 2, up_to_month: 1, older: 1 });
+        assert_eq!(breakdown.total(), 4);
+    }
+
+    #[test]
+    fn test_member_counts_per_creator() {
+        let now = Utc::now();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let mut old_completion = task(bob, TaskStatus::Completed, None, now);
+        old_completion.completed_at = Some(now - Duration::days(40));
+        let tasks = vec![
+            task(alice, TaskStatus::Completed, None, now),
+            task(alice, TaskStatus::Pending, Some(2), now),
+            task(bob, TaskStatus::Completed, None, now),
+            old_completion,
+        ];
+
+        let counts = MemberTaskCounts::from_tasks(&tasks, now, None);
+        assert_eq!(counts.len(), 2);
+        assert_eq!((counts[0].total_tasks, counts[0].completed_tasks, counts[0].overdue_tasks), (2, 1, 1));
+        assert_eq!((counts[1].total_tasks, counts[1].completed_tasks, counts[1].overdue_tasks), (2, 2, 0));
+
+        // Only completions in the period count
+        let counts = MemberTaskCounts::from_tasks(&tasks, now, Some(now - Duration::days(7)));
+        assert_eq!(counts[1].completed_tasks, 1);
+        assert_eq!(counts[1].total_tasks, 2);
+    }
+}
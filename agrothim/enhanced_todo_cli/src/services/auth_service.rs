@@ -4,19 +4,52 @@ use jsonwebtoken::{
     decode, encode, Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    models::user::UserResponse,
-    services::{UserService, UserServiceError},
+    error::{is_unreachable, ErrorCode},
+    database::repositories::{
+        ApiClient, ApiClientError, ApiKeyRepository, ApiKeyRepositoryError, RevokedTokenRepository, RevokedTokenRepositoryError,
+        SessionRepository, SessionRepositoryError, StoreApiKeyRequest,
+    },
+    models::{
+        api_key::ApiKey,
+        device_session::DeviceSession,
+        user::{UpdateUserRequest, UserResponse},
+        webhook::WebhookEvent,
+    },
+    services::{AuthContext, AuthorizationError, Scope, UserService, UserServiceError, WebhookService},
+    utils::{
+        config::DEFAULT_JWT_SECRET,
+        tokens::{generate_token, hash_token},
+    },
 };
 
+/// Environment variable holding a token to use instead of the saved session,
+/// e.g. a read-only token for a dashboard
+pub const TOKEN_ENV_VAR: &str = "TODO_CLI_TOKEN";
+
+/// How long a validated session is trusted without checking it again
+const SESSION_CACHE_TTL_SECONDS: i64 = 300;
+
+/// API keys start with this, which tells them apart from JWTs
+pub const API_KEY_PREFIX: &str = "tdk_";
+
+/// Characters of a key kept in clear to identify it, e.g. `tdk_1a2b3c4d`
+const API_KEY_DISPLAY_LEN: usize = 12;
+
+const MAX_API_KEY_NAME_LEN: usize = 100;
+
+/// Longest session name the `sessions` table accepts
+const MAX_SESSION_NAME_LEN: usize = 100;
+
 #[derive(Error, Debug)]
 pub enum AuthServiceError {
     #[error("Authentication failed: invalid credentials")]
@@ -31,6 +64,30 @@ pub enum AuthServiceError {
     #[error("Session expired")]
     SessionExpired,
 
+    #[error("Validation error: {message}")]
+    ValidationError { message: String },
+
+    #[error("API key not found")]
+    ApiKeyNotFound,
+
+    #[error("Account is suspended")]
+    AccountSuspended,
+
+    #[error("API key error: {0}")]
+    ApiKeyRepositoryError(#[from] ApiKeyRepositoryError),
+
+    #[error("No active session matches '{id}'")]
+    UnknownSession { id: String },
+
+    #[error("Session error: {0}")]
+    SessionRepositoryError(#[from] SessionRepositoryError),
+
+    #[error("Token revocation error: {0}")]
+    RevokedTokenRepositoryError(#[from] RevokedTokenRepositoryError),
+
+    #[error(transparent)]
+    Unauthorized(#[from] AuthorizationError),
+
     #[error("User service error: {0}")]
     UserServiceError(#[from] UserServiceError),
 
@@ -43,6 +100,9 @@ pub enum AuthServiceError {
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("API error: {0}")]
+    ApiError(#[from] ApiClientError),
+
     #[error("Internal error: {0}")]
     InternalError(#[from] anyhow::Error),
 }
@@ -55,6 +115,15 @@ pub struct Claims {
     pub iat: i64,         // Issued at
     pub exp: i64,         // Expiration time
     pub jti: String,      // JWT ID for token revocation
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<Scope>, // Operations the token may perform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>, // Device session, absent for standalone tokens
+}
+
+/// Tokens issued before scopes existed were full session tokens
+fn default_scopes() -> Vec<Scope> {
+    Scope::ALL.to_vec()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -67,6 +136,23 @@ pub struct Session {
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
     pub last_accessed: DateTime<Utc>,
+    /// Row in the `sessions` table; absent for sessions saved before it existed
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+}
+
+/// Outcome of the last successful session validation. Back-to-back commands
+/// reuse it instead of decoding the JWT and loading the user from the
+/// database, as long as the session file is unchanged and the entry is fresh.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionCache {
+    /// SHA-256 of the session token, so the cache never holds the token itself
+    token_fingerprint: String,
+    /// Modification time of the session file when the cache was written
+    session_modified: DateTime<Utc>,
+    valid_until: DateTime<Utc>,
+    user: UserResponse,
+    scopes: Vec<Scope>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,6 +163,21 @@ pub struct LoginResponse {
     pub expires_at: DateTime<Utc>,
 }
 
+/// A standalone access token created with `issue_token`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssuedToken {
+    pub token: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A new API key; `key` is only available here, the database keeps its hash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedApiKey {
+    pub key: String,
+    pub api_key: ApiKey,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TokenRefreshResponse {
     pub token: String,
@@ -88,9 +189,16 @@ pub struct AuthService {
     user_service: Arc<UserService>,
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
+    /// Signed with the public `DEFAULT_JWT_SECRET`, so anyone can forge tokens
+    default_secret: bool,
     session_file_path: PathBuf,
     token_expiry_duration: Duration,
     refresh_token_expiry_duration: Duration,
+    webhook_service: Option<Arc<WebhookService>>,
+    api_keys: Option<Arc<dyn ApiKeyRepository>>,
+    sessions: Option<Arc<dyn SessionRepository>>,
+    revoked_tokens: Option<Arc<dyn RevokedTokenRepository>>,
+    remote: Option<Arc<ApiClient>>,
 }
 
 impl AuthService {
@@ -113,6 +221,13 @@ impl AuthService {
         // Create session directory if it doesn't exist
         if !session_dir.exists() {
             fs::create_dir_all(&session_dir).context("Failed to create session directory")?;
+
+            // Sessions hold bearer tokens, keep the directory private
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&session_dir, fs::Permissions::from_mode(0o700))?;
+            }
         }
 
         let mut session_file_path = session_dir;
@@ -122,12 +237,60 @@ impl AuthService {
             user_service,
             encoding_key,
             decoding_key,
+            default_secret: jwt_secret == DEFAULT_JWT_SECRET,
             session_file_path,
             token_expiry_duration: Duration::hours(24), // 24 hours for access token
             refresh_token_expiry_duration: Duration::days(30), // 30 days for refresh token
+            webhook_service: None,
+            api_keys: None,
+            sessions: None,
+            revoked_tokens: None,
+            remote: None,
         })
     }
 
+    /// Whether tokens are signed with the built-in secret instead of `JWT_SECRET`
+    pub fn uses_default_secret(&self) -> bool {
+        self.default_secret
+    }
+
+    /// Directory holding the session file
+    pub fn session_dir(&self) -> &Path {
+        self.session_file_path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// Send account security events (logins, password changes) to the user's webhooks
+    pub fn with_webhook_service(mut self, webhook_service: Arc<WebhookService>) -> Self {
+        self.webhook_service = Some(webhook_service);
+        self
+    }
+
+    /// Accept API keys as credentials and allow managing them
+    pub fn with_api_keys(mut self, api_keys: Arc<dyn ApiKeyRepository>) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
+    /// Record every login as a device session that can be listed and revoked
+    pub fn with_sessions(mut self, sessions: Arc<dyn SessionRepository>) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Check every token's `jti` against revoked tokens, and revoke the
+    /// session's tokens on logout and password change
+    pub fn with_token_revocation(mut self, revoked_tokens: Arc<dyn RevokedTokenRepository>) -> Self {
+        self.revoked_tokens = Some(revoked_tokens);
+        self
+    }
+
+    /// Log in on the server behind `client` instead, and check tokens there;
+    /// the server also decides what each token may do
+    pub fn with_remote(mut self, client: Arc<ApiClient>) -> Self {
+        self.remote = Some(client);
+        self
+    }
+
     /// Login with username/email and password
     pub async fn login(
         &self,
@@ -136,19 +299,91 @@ impl AuthService {
     ) -> Result<LoginResponse, AuthServiceError> {
         info!("Login attempt for user: {}", identifier);
 
-        // Authenticate user via UserService
-        let user = self
-            .user_service
+        if let Some(client) = &self.remote {
+            return self.login_on_server(client, identifier, password).await;
+        }
+
+        let user = self.check_credentials(identifier, password).await?;
+        self.start_session(user).await
+    }
+
+    /// Login on behalf of a remote client such as an API caller. The tokens
+    /// are only returned, not saved as this machine's session.
+    pub async fn login_remote(
+        &self,
+        identifier: &str,
+        password: &str,
+        client_name: &str,
+    ) -> Result<LoginResponse, AuthServiceError> {
+        info!("Remote login attempt for user: {}", identifier);
+
+        let user = self.check_credentials(identifier, password).await?;
+        let (_, response) = self.open_session(user, client_name).await?;
+        Ok(response)
+    }
+
+    /// Authenticate user via UserService
+    async fn check_credentials(&self, identifier: &str, password: &str) -> Result<UserResponse, AuthServiceError> {
+        self.user_service
             .authenticate(identifier, password)
             .await
             .map_err(|e| match e {
                 UserServiceError::AuthenticationFailed => AuthServiceError::AuthenticationFailed,
                 UserServiceError::UserNotFound => AuthServiceError::AuthenticationFailed,
+                UserServiceError::AccountSuspended => AuthServiceError::AccountSuspended,
                 other => AuthServiceError::UserServiceError(other),
-            })?;
+            })
+    }
+
+    /// Log in a user who was authenticated some other way, e.g. by an
+    /// identity provider, and save the session like a password login
+    pub async fn login_external(&self, user: UserResponse) -> Result<LoginResponse, AuthServiceError> {
+        info!("External login for user: {}", user.username);
+        self.start_session(user).await
+    }
+
+    /// Save the tokens the server issued as this machine's session
+    async fn login_on_server(&self, client: &ApiClient, identifier: &str, password: &str) -> Result<LoginResponse, AuthServiceError> {
+        let response = client.login(identifier, password).await.map_err(|e| match e.code() {
+            Some(ErrorCode::AuthenticationFailed) => AuthServiceError::AuthenticationFailed,
+            _ => AuthServiceError::ApiError(e),
+        })?;
+        client.set_token(&response.token);
+
+        let session = Session {
+            user_id: response.user.id,
+            username: response.user.username.clone(),
+            email: response.user.email.clone(),
+            token: response.token.clone(),
+            refresh_token: response.refresh_token.clone(),
+            created_at: Utc::now(),
+            expires_at: response.expires_at,
+            last_accessed: Utc::now(),
+            session_id: None,
+        };
+        self.save_session(&session)?;
+
+        info!("User {} logged in on the server", response.user.username);
+        Ok(response)
+    }
+
+    async fn start_session(&self, user: UserResponse) -> Result<LoginResponse, AuthServiceError> {
+        let (session, response) = self.open_session(user, &device_name()).await?;
+        self.save_session(&session)?;
+        Ok(response)
+    }
+
+    /// Issue tokens for a new device session named `device`
+    async fn open_session(&self, user: UserResponse, device: &str) -> Result<(Session, LoginResponse), AuthServiceError> {
+        if !user.is_active {
+            warn!("Login refused: account {} is suspended", user.username);
+            return Err(AuthServiceError::AccountSuspended);
+        }
+
+        let session_id = self.create_device_session(&user.id, device).await?;
 
         // Generate tokens
-        let (token, refresh_token, expires_at) = self.generate_tokens(&user)?;
+        let (token, refresh_token, expires_at) = self.generate_tokens(&user, session_id)?;
 
         // Create and save session
         let session = Session {
@@ -160,25 +395,106 @@ impl AuthService {
             created_at: Utc::now(),
             expires_at,
             last_accessed: Utc::now(),
+            session_id,
         };
 
-        self.save_session(&session)?;
-
         info!("User {} logged in successfully", user.username);
 
-        Ok(LoginResponse {
+        self.emit_event(
+            &user,
+            WebhookEvent::Login,
+            serde_json::json!({ "session_expires_at": expires_at }),
+        )
+        .await;
+
+        let response = LoginResponse {
             user,
             token,
             refresh_token,
             expires_at,
-        })
+        };
+        Ok((session, response))
+    }
+
+    /// Change the logged-in user's password after re-checking the current one
+    pub async fn change_password(
+        &self,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<UserResponse, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+
+        let user = self
+            .user_service
+            .change_password(&user.id, current_password, new_password)
+            .await
+            .map_err(|e| match e {
+                UserServiceError::AuthenticationFailed => AuthServiceError::AuthenticationFailed,
+                other => AuthServiceError::UserServiceError(other),
+            })?;
+
+        // Make the next command check the session against the database again
+        self.clear_session_cache();
+
+        // Tokens issued under the old password stop working on every device;
+        // this one gets a new session with a fresh pair
+        self.revoke_all_sessions(&user.id).await?;
+        if let Ok(mut session) = self.load_session() {
+            if session.user_id == user.id {
+                self.revoke_session_tokens(&session).await?;
+                let session_id = self.create_device_session(&user.id, &device_name()).await?;
+                let (token, refresh_token, expires_at) = self.generate_tokens(&user, session_id)?;
+                session.token = token;
+                session.refresh_token = refresh_token;
+                session.expires_at = expires_at;
+                session.session_id = session_id;
+                self.save_session(&session)?;
+            }
+        }
+
+        self.emit_event(&user, WebhookEvent::PasswordChanged, serde_json::json!({}))
+            .await;
+
+        Ok(user)
+    }
+
+    /// Rename the logged-in user; this device stays logged in under the new name
+    pub async fn change_username(&self, username: &str) -> Result<UserResponse, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        let old_username = user.username;
+
+        let updates = UpdateUserRequest::new().username(username.trim().to_string());
+        let user = self.user_service.update_profile(&user.id, updates).await?;
+
+        self.clear_session_cache();
+        if let Ok(mut session) = self.load_session() {
+            if session.user_id == user.id {
+                session.username = user.username.clone();
+                self.save_session(&session)?;
+            }
+        }
+
+        info!("User {} renamed to {}", old_username, user.username);
+        Ok(user)
     }
 
     /// Logout and clear session
     pub async fn logout(&self) -> Result<(), AuthServiceError> {
         info!("Logging out user");
 
+        self.clear_session_cache();
         if self.session_file_path.exists() {
+            if let Ok(session) = self.load_session() {
+                // The local tokens are gone either way; a failure only leaves them usable elsewhere
+                if let Err(e) = self.revoke_session_tokens(&session).await {
+                    warn!("Failed to revoke session tokens: {}", e);
+                }
+                if let (Some(session_id), Some(sessions)) = (session.session_id, &self.sessions) {
+                    if let Err(e) = sessions.revoke(&session.user_id, &session_id).await {
+                        warn!("Failed to revoke session {}: {}", session_id, e);
+                    }
+                }
+            }
             fs::remove_file(&self.session_file_path).context("Failed to remove session file")?;
             info!("Session cleared successfully");
         }
@@ -187,7 +503,22 @@ impl AuthService {
     }
 
     /// Validate token and return user information
+    #[allow(dead_code)]
     pub async fn validate_token(&self, token: &str) -> Result<UserResponse, AuthServiceError> {
+        Ok(self.authenticate(token).await?.user)
+    }
+
+    /// Validate token and return the user with the scopes the token grants.
+    /// Accepts API keys as well as JWTs.
+    pub async fn authenticate(&self, token: &str) -> Result<AuthContext, AuthServiceError> {
+        if let Some(client) = &self.remote {
+            return Self::authenticate_on_server(client, token).await;
+        }
+
+        if token.starts_with(API_KEY_PREFIX) {
+            return self.authenticate_api_key(token).await;
+        }
+
         debug!("Validating token");
 
         // Decode and validate JWT
@@ -204,11 +535,166 @@ impl AuthService {
         // Parse user ID
         let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthServiceError::InvalidToken)?;
 
+        if let Some(revoked_tokens) = &self.revoked_tokens {
+            if revoked_tokens.is_revoked(&claims.jti).await? {
+                warn!("Revoked token used for user: {}", claims.username);
+                return Err(AuthServiceError::InvalidToken);
+            }
+            // `iat` has whole seconds, so compare against the second of the revocation
+            if let Some(issued_before) = revoked_tokens.revoked_before(&user_id).await? {
+                if claims.iat < issued_before.timestamp() {
+                    warn!("Token issued before all tokens were revoked used for user: {}", claims.username);
+                    return Err(AuthServiceError::InvalidToken);
+                }
+            }
+        }
+
+        // Tokens of a revoked device session stop working even before they expire
+        if let (Some(session_id), Some(sessions)) = (claims.sid, &self.sessions) {
+            if sessions.use_session(&session_id).await?.is_none() {
+                warn!("Token of revoked or expired session {} for user: {}", session_id, claims.username);
+                return Err(AuthServiceError::InvalidToken);
+            }
+        }
+
         // Get current user data (for up-to-date information)
         let user = self.user_service.get_profile(&user_id).await?;
+        if !user.is_active {
+            warn!("Token of suspended account used: {}", user.username);
+            return Err(AuthServiceError::AccountSuspended);
+        }
 
         debug!("Token validated successfully for user: {}", user.username);
-        Ok(user)
+        Ok(AuthContext {
+            user,
+            scopes: claims.scopes,
+        })
+    }
+
+    /// Create an access token limited to `scopes`, independent of the session.
+    /// Minting tokens is account management, so it requires the admin scope.
+    pub async fn issue_token(
+        &self,
+        scopes: &[Scope],
+        lifetime: Duration,
+    ) -> Result<IssuedToken, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        let expires_at = Utc::now() + lifetime;
+        let token = self.create_token(&user, scopes, expires_at, None)?;
+
+        info!(
+            "Issued token for user {} with scopes {:?}",
+            user.username, scopes
+        );
+
+        Ok(IssuedToken {
+            token,
+            scopes: scopes.to_vec(),
+            expires_at,
+        })
+    }
+
+    /// Create a long-lived API key limited to `scopes`; it never expires if
+    /// `lifetime` is `None`. Like tokens, keys require the admin scope to create.
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        scopes: &[Scope],
+        lifetime: Option<Duration>,
+    ) -> Result<CreatedApiKey, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        let api_keys = self.api_key_repository()?;
+
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_API_KEY_NAME_LEN {
+            return Err(AuthServiceError::ValidationError {
+                message: format!("API key name must be 1 to {} characters", MAX_API_KEY_NAME_LEN),
+            });
+        }
+        if scopes.is_empty() {
+            return Err(AuthServiceError::ValidationError {
+                message: "API key needs at least one scope".to_string(),
+            });
+        }
+
+        let key = format!("{}{}", API_KEY_PREFIX, generate_token());
+        let request = StoreApiKeyRequest {
+            name: name.to_string(),
+            prefix: key[..API_KEY_DISPLAY_LEN].to_string(),
+            key_hash: hash_token(&key),
+            scopes: scopes.iter().map(|scope| scope.as_str().to_string()).collect(),
+            expires_at: lifetime.map(|lifetime| Utc::now() + lifetime),
+        };
+        let api_key = api_keys.store(&user.id, request).await?;
+
+        info!(
+            "Created API key {} for user {} with scopes {:?}",
+            api_key.prefix, user.username, scopes
+        );
+
+        Ok(CreatedApiKey { key, api_key })
+    }
+
+    /// The logged-in user's API keys, including revoked and expired ones
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        Ok(self.api_key_repository()?.find_by_user_id(&user.id).await?)
+    }
+
+    /// Revoke one of the logged-in user's API keys by ID or prefix
+    pub async fn revoke_api_key(&self, id_or_prefix: &str) -> Result<ApiKey, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        let api_key = self
+            .api_key_repository()?
+            .revoke(&user.id, id_or_prefix)
+            .await?
+            .ok_or(AuthServiceError::ApiKeyNotFound)?;
+
+        info!("Revoked API key {} for user {}", api_key.prefix, user.username);
+        Ok(api_key)
+    }
+
+    /// The logged-in user's active device sessions, most recently used first
+    pub async fn list_sessions(&self) -> Result<Vec<DeviceSession>, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        Ok(self.session_repository()?.find_active_by_user_id(&user.id).await?)
+    }
+
+    /// The device session of this machine's login, if any
+    pub fn current_session_id(&self) -> Option<Uuid> {
+        self.load_session().ok()?.session_id
+    }
+
+    /// Revoke one of the logged-in user's sessions by its full or short ID.
+    /// Revoking this machine's own session logs it out.
+    pub async fn revoke_session(&self, id: &str) -> Result<DeviceSession, AuthServiceError> {
+        let user = self.authorize(Scope::Admin).await?;
+        let sessions = self.session_repository()?;
+
+        let unknown = || AuthServiceError::UnknownSession { id: id.to_string() };
+        // Short IDs are prefixes of the hyphen-less form, which also covers full IDs
+        let prefix = id.trim().to_lowercase().replace('-', "");
+        if prefix.is_empty() {
+            return Err(unknown());
+        }
+        let mut matches = sessions
+            .find_active_by_user_id(&user.id)
+            .await?
+            .into_iter()
+            .filter(|session| session.id.simple().to_string().starts_with(&prefix));
+        let session = match (matches.next(), matches.next()) {
+            (Some(session), None) => session,
+            _ => return Err(unknown()),
+        };
+
+        let revoked = sessions.revoke(&user.id, &session.id).await?.ok_or_else(unknown)?;
+        self.clear_session_cache();
+        info!("Revoked session {} ({}) for user {}", revoked.id, revoked.name, user.username);
+
+        if self.current_session_id() == Some(revoked.id) {
+            self.logout().await?;
+        }
+        Ok(revoked)
     }
 
     /// Refresh access token using refresh token
@@ -240,12 +726,16 @@ impl AuthService {
             id: session.user_id,
             username: session.username.clone(),
             email: session.email.clone(),
+            is_active: true, // Suspension is checked when the new token is used
             created_at: Utc::now(), // This will be ignored in token generation
             updated_at: Utc::now(), // This will be ignored in token generation
         };
 
         // Generate new tokens
-        let (new_token, new_refresh_token, new_expires_at) = self.generate_tokens(&user)?;
+        let (new_token, new_refresh_token, new_expires_at) = self.generate_tokens(&user, session.session_id)?;
+        if let (Some(session_id), Some(sessions)) = (session.session_id, &self.sessions) {
+            sessions.extend(&session_id, new_expires_at).await?;
+        }
 
         // Update session
         session.token = new_token.clone();
@@ -269,6 +759,15 @@ impl AuthService {
 
     /// Get current session if exists and valid
     pub async fn get_current_session(&self) -> Result<Option<UserResponse>, AuthServiceError> {
+        Ok(self.get_current_context().await?.map(|context| context.user))
+    }
+
+    /// Resolve the caller: the token in `TODO_CLI_TOKEN` if set, otherwise the saved session
+    pub async fn get_current_context(&self) -> Result<Option<AuthContext>, AuthServiceError> {
+        if let Some(token) = std::env::var(TOKEN_ENV_VAR).ok().filter(|t| !t.is_empty()) {
+            return self.authenticate(&token).await.map(Some);
+        }
+
         if !self.session_file_path.exists() {
             return Ok(None);
         }
@@ -282,17 +781,25 @@ impl AuthService {
                     return Ok(None);
                 }
 
+                if let Some(context) = self.cached_context(&session) {
+                    debug!("Using cached session for user: {}", context.user.username);
+                    return Ok(Some(context));
+                }
+
                 // Validate token
-                match self.validate_token(&session.token).await {
-                    Ok(user) => {
+                match self.authenticate(&session.token).await {
+                    Ok(context) => {
                         // Update last accessed time
                         let mut updated_session = session;
                         updated_session.last_accessed = Utc::now();
                         self.save_session(&updated_session)?;
+                        self.store_session_cache(&updated_session, &context);
 
-                        debug!("Current session valid for user: {}", user.username);
-                        Ok(Some(user))
+                        debug!("Current session valid for user: {}", context.user.username);
+                        Ok(Some(context))
                     }
+                    // An unreachable server says nothing about the session
+                    Err(e) if matches!(e, AuthServiceError::ApiError(_)) || is_unreachable(&e) => Err(e),
                     Err(_) => {
                         debug!("Invalid session token, clearing session");
                         self.logout().await?;
@@ -307,6 +814,21 @@ impl AuthService {
         }
     }
 
+    /// The user of the saved session, taken from the session file alone. Only
+    /// for queueing changes while the database cannot be reached: nothing
+    /// here is verified, the replay checks it once the database is back.
+    pub fn offline_user(&self) -> Option<UserResponse> {
+        let session = self.load_session().ok().filter(|session| session.expires_at > Utc::now())?;
+        Some(UserResponse {
+            id: session.user_id,
+            username: session.username,
+            email: session.email,
+            is_active: true,
+            created_at: session.created_at,
+            updated_at: session.last_accessed,
+        })
+    }
+
     /// Check if user is currently authenticated
     #[allow(dead_code)]
     pub async fn is_authenticated(&self) -> bool {
@@ -317,13 +839,43 @@ impl AuthService {
     }
 
     /// Get current user if authenticated
-    #[allow(dead_code)]
     pub async fn get_current_user(&self) -> Result<UserResponse, AuthServiceError> {
         self.get_current_session()
             .await?
             .ok_or(AuthServiceError::SessionNotFound)
     }
 
+    /// Get the current user, provided their token grants `required`
+    pub async fn authorize(&self, required: Scope) -> Result<UserResponse, AuthServiceError> {
+        let context = self
+            .get_current_context()
+            .await?
+            .ok_or(AuthServiceError::SessionNotFound)?;
+        context.require(required)?;
+        Ok(context.user)
+    }
+
+    /// Resolve the caller and check that they may manage other accounts
+    pub async fn authorize_administrator(&self) -> Result<UserResponse, AuthServiceError> {
+        let context = self
+            .get_current_context()
+            .await?
+            .ok_or(AuthServiceError::SessionNotFound)?;
+        context.require_administrator()?;
+        Ok(context.user)
+    }
+
+    /// Forget the cached validation, e.g. when the user's profile changes or
+    /// a session, token or account is revoked
+    pub fn clear_session_cache(&self) {
+        let path = self.session_cache_path();
+        if path.exists() {
+            if let Err(e) = fs::remove_file(&path) {
+                warn!("Failed to remove session cache: {}", e);
+            }
+        }
+    }
+
     // Private helper methods
 
     /// Generate JWT access token and refresh token
@@ -331,38 +883,148 @@ impl AuthService {
     fn generate_tokens(
         &self,
         user: &UserResponse,
+        session_id: Option<Uuid>,
     ) -> Result<(String, String, DateTime<Utc>), AuthServiceError> {
         let now = Utc::now();
         let access_token_exp = now + self.token_expiry_duration;
         let refresh_token_exp = now + self.refresh_token_expiry_duration;
 
-        // Generate access token
-        let access_claims = Claims {
-            sub: user.id.to_string(),
-            username: user.username.clone(),
-            email: user.email.clone(),
-            iat: now.timestamp(),
-            exp: access_token_exp.timestamp(),
-            jti: Uuid::new_v4().to_string(),
+        // Session tokens act on behalf of the user, so they carry every scope
+        let access_token = self.create_token(user, &Scope::ALL, access_token_exp, session_id)?;
+
+        // Generate refresh token (longer lived)
+        let refresh_token = self.create_token(user, &Scope::ALL, refresh_token_exp, session_id)?;
+
+        Ok((access_token, refresh_token, refresh_token_exp))
+    }
+
+    /// Revoke the access and refresh token of a saved session. Tokens that no
+    /// longer decode, e.g. because they expired, need no revoking.
+    async fn revoke_session_tokens(&self, session: &Session) -> Result<(), AuthServiceError> {
+        self.clear_session_cache();
+        let Some(revoked_tokens) = &self.revoked_tokens else {
+            return Ok(());
         };
 
-        let access_token = encode(&Header::default(), &access_claims, &self.encoding_key)
-            .map_err(|e| AuthServiceError::TokenCreationFailed(e.to_string()))?;
+        for token in [&session.token, &session.refresh_token] {
+            if let Ok(token_data) = self.decode_token(token) {
+                let expires_at = DateTime::from_timestamp(token_data.claims.exp, 0).unwrap_or_else(Utc::now);
+                revoked_tokens
+                    .revoke(&token_data.claims.jti, &session.user_id, expires_at)
+                    .await?;
+            }
+        }
 
-        // Generate refresh token (longer lived)
-        let refresh_claims = Claims {
+        debug!("Revoked session tokens for user: {}", session.username);
+        Ok(())
+    }
+
+    /// Revoke every device session of the user and every token issued so far,
+    /// e.g. after their password changed. Standalone tokens issued within the
+    /// same second survive, as `iat` has whole seconds.
+    pub async fn revoke_all_sessions(&self, user_id: &Uuid) -> Result<(), AuthServiceError> {
+        self.clear_session_cache();
+        if let Some(revoked_tokens) = &self.revoked_tokens {
+            revoked_tokens.revoke_all(user_id, Utc::now()).await?;
+        }
+        if let Some(sessions) = &self.sessions {
+            for session in sessions.find_active_by_user_id(user_id).await? {
+                sessions.revoke(user_id, &session.id).await?;
+            }
+        }
+
+        info!("Revoked all sessions of user {}", user_id);
+        Ok(())
+    }
+
+    /// Record a device session for the user's new tokens, if sessions are tracked
+    async fn create_device_session(&self, user_id: &Uuid, device: &str) -> Result<Option<Uuid>, AuthServiceError> {
+        let Some(sessions) = &self.sessions else {
+            return Ok(None);
+        };
+
+        let expires_at = Utc::now() + self.refresh_token_expiry_duration;
+        Ok(Some(sessions.create(user_id, device, expires_at).await?.id))
+    }
+
+    fn session_repository(&self) -> Result<&Arc<dyn SessionRepository>, AuthServiceError> {
+        self.sessions
+            .as_ref()
+            .ok_or_else(|| AuthServiceError::InternalError(anyhow::anyhow!("Device sessions are not configured")))
+    }
+
+    fn api_key_repository(&self) -> Result<&Arc<dyn ApiKeyRepository>, AuthServiceError> {
+        self.api_keys
+            .as_ref()
+            .ok_or_else(|| AuthServiceError::InternalError(anyhow::anyhow!("API keys are not configured")))
+    }
+
+    /// The server checks the token on every request, scopes included
+    async fn authenticate_on_server(client: &ApiClient, token: &str) -> Result<AuthContext, AuthServiceError> {
+        client.set_token(token);
+        let user = client.me().await.map_err(|e| match e.code() {
+            Some(ErrorCode::InvalidToken | ErrorCode::NotLoggedIn) => AuthServiceError::InvalidToken,
+            _ => AuthServiceError::ApiError(e),
+        })?;
+        Ok(AuthContext { user, scopes: Scope::ALL.to_vec() })
+    }
+
+    async fn authenticate_api_key(&self, key: &str) -> Result<AuthContext, AuthServiceError> {
+        debug!("Validating API key");
+
+        let api_key = match &self.api_keys {
+            Some(api_keys) => api_keys.use_key(&hash_token(key)).await?,
+            None => None,
+        };
+        let api_key = api_key.ok_or_else(|| {
+            warn!("Unknown, revoked or expired API key");
+            AuthServiceError::InvalidToken
+        })?;
+
+        let user = self.user_service.get_profile(&api_key.user_id).await?;
+        if !user.is_active {
+            warn!("API key {} of suspended account used: {}", api_key.prefix, user.username);
+            return Err(AuthServiceError::AccountSuspended);
+        }
+        // Scopes are stored as text; skip any this version does not know
+        let scopes = api_key
+            .scopes
+            .iter()
+            .filter_map(|scope| scope.parse::<Scope>().ok())
+            .collect();
+
+        debug!("API key {} validated for user: {}", api_key.prefix, user.username);
+        Ok(AuthContext { user, scopes })
+    }
+
+    /// Sign a JWT for `user` carrying the given scopes, tied to a device session if `session_id` is set
+    fn create_token(
+        &self,
+        user: &UserResponse,
+        scopes: &[Scope],
+        expires_at: DateTime<Utc>,
+        session_id: Option<Uuid>,
+    ) -> Result<String, AuthServiceError> {
+        let claims = Claims {
             sub: user.id.to_string(),
             username: user.username.clone(),
             email: user.email.clone(),
-            iat: now.timestamp(),
-            exp: refresh_token_exp.timestamp(),
+            iat: Utc::now().timestamp(),
+            exp: expires_at.timestamp(),
             jti: Uuid::new_v4().to_string(),
+            scopes: scopes.to_vec(),
+            sid: session_id,
         };
 
-        let refresh_token = encode(&Header::default(), &refresh_claims, &self.encoding_key)
-            .map_err(|e| AuthServiceError::TokenCreationFailed(e.to_string()))?;
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AuthServiceError::TokenCreationFailed(e.to_string()))
+    }
 
-        Ok((access_token, refresh_token, refresh_token_exp))
+    /// Deliver an auth event to the user's webhooks, if webhooks are configured
+    async fn emit_event(&self, user: &UserResponse, event: WebhookEvent, data: serde_json::Value) {
+        if let Some(webhook_service) = &self.webhook_service {
+            webhook_service.dispatch(user, event, data).await;
+        }
     }
 
     /// Decode and validate JWT token
@@ -398,6 +1060,68 @@ impl AuthService {
         Ok(())
     }
 
+    fn session_cache_path(&self) -> PathBuf {
+        self.session_file_path.with_file_name("session_cache.json")
+    }
+
+    fn session_modified(&self) -> Option<DateTime<Utc>> {
+        let modified = fs::metadata(&self.session_file_path).ok()?.modified().ok()?;
+        Some(modified.into())
+    }
+
+    /// The cached validation for `session`, if the session file has not been
+    /// touched since and the entry has not expired. Any problem reading the
+    /// cache is treated as a miss.
+    fn cached_context(&self, session: &Session) -> Option<AuthContext> {
+        let json_data = fs::read_to_string(self.session_cache_path()).ok()?;
+        let cache: SessionCache = serde_json::from_str(&json_data).ok()?;
+
+        let fresh = cache.valid_until > Utc::now()
+            && cache.token_fingerprint == token_fingerprint(&session.token)
+            && Some(cache.session_modified) == self.session_modified();
+        if !fresh {
+            debug!("Session cache is stale");
+            return None;
+        }
+
+        Some(AuthContext {
+            user: cache.user,
+            scopes: cache.scopes,
+        })
+    }
+
+    /// Remember a successful validation; failures only cost the next command a database lookup
+    fn store_session_cache(&self, session: &Session, context: &AuthContext) {
+        let Some(session_modified) = self.session_modified() else {
+            return;
+        };
+
+        let cache = SessionCache {
+            token_fingerprint: token_fingerprint(&session.token),
+            session_modified,
+            valid_until: (Utc::now() + Duration::seconds(SESSION_CACHE_TTL_SECONDS)).min(session.expires_at),
+            user: context.user.clone(),
+            scopes: context.scopes.clone(),
+        };
+
+        let result = serde_json::to_string(&cache)
+            .map_err(AuthServiceError::from)
+            .and_then(|json_data| {
+                let mut file = fs::File::create(self.session_cache_path())?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+                }
+                file.write_all(json_data.as_bytes())?;
+                Ok(())
+            });
+
+        if let Err(e) = result {
+            warn!("Failed to write session cache: {}", e);
+        }
+    }
+
     /// Load session from file
     #[allow(dead_code)]
     fn load_session(&self) -> Result<Session, AuthServiceError> {
@@ -413,7 +1137,24 @@ impl AuthService {
     }
 }
 
-// Configuration for AuthService
+fn token_fingerprint(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Name of a new device session: the host name, as far as it can be found
+fn device_name() -> String {
+    let name = ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .chain(fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown device".to_string());
+
+    name.chars().take(MAX_SESSION_NAME_LEN).collect()
+}
+
+// Configuration for AuthService
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
     pub jwt_secret: String,
@@ -422,12 +1163,11 @@ pub struct AuthConfig {
     pub refresh_token_expiry_days: i64,
 }
 
-#[allow(dead_code)]
 impl Default for AuthConfig {
     fn default() -> Self {
         Self {
             jwt_secret: std::env::var("JWT_SECRET")
-                .unwrap_or_else(|_| "default-secret-change-in-production".to_string()),
+                .unwrap_or_else(|_| DEFAULT_JWT_SECRET.to_string()),
             session_dir: None,
             token_expiry_hours: 24,
             refresh_token_expiry_days: 30,
@@ -437,7 +1177,6 @@ impl Default for AuthConfig {
 
 impl AuthService {
     /// Create AuthService with custom configuration
-    #[allow(dead_code)]
     pub fn with_config(
         user_service: Arc<UserService>,
         config: AuthConfig,
@@ -454,116 +1193,19 @@ impl AuthService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::database::repositories::user_repository::{UserRepository, UserRepositoryError};
-    use crate::models::user::{StoreUserRequest, UpdateUserRequest, User};
+    use crate::database::repositories::{InMemoryUserRepository, UserRepository};
+    use crate::models::user::StoreUserRequest;
     use crate::services::user_service::UserService;
     use async_trait::async_trait;
-    use std::collections::HashMap;
     use std::sync::Mutex;
     use tempfile::TempDir;
 
-    // Mock UserRepository for testing
-    struct MockUserRepository {
-        users: Arc<Mutex<HashMap<Uuid, User>>>,
-        usernames: Arc<Mutex<HashMap<String, Uuid>>>,
-        emails: Arc<Mutex<HashMap<String, Uuid>>>,
-    }
-
-    impl MockUserRepository {
-        #[allow(dead_code)]
-        fn new() -> Self {
-            Self {
-                users: Arc::new(Mutex::new(HashMap::new())),
-                usernames: Arc::new(Mutex::new(HashMap::new())),
-                emails: Arc::new(Mutex::new(HashMap::new())),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl UserRepository for MockUserRepository {
-        async fn store(&self, request: StoreUserRequest) -> Result<User, UserRepositoryError> {
-            let user_id = Uuid::new_v4();
-            let now = chrono::Utc::now();
-
-            let user = User {
-                id: user_id,
-                username: request.username.clone(),
-                email: request.email.clone(),
-                password_hash: request.password_hash,
-                created_at: now,
-                updated_at: now,
-            };
-
-            self.users.lock().unwrap().insert(user_id, user.clone());
-            self.usernames
-                .lock()
-                .unwrap()
-                .insert(request.username, user_id);
-            self.emails.lock().unwrap().insert(request.email, user_id);
-
-            Ok(user)
-        }
-
-        async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError> {
-            Ok(self.users.lock().unwrap().get(id).cloned())
-        }
-
-        async fn find_by_username(
-            &self,
-            username: &str,
-        ) -> Result<Option<User>, UserRepositoryError> {
-            if let Some(user_id) = self.usernames.lock().unwrap().get(username) {
-                Ok(self.users.lock().unwrap().get(user_id).cloned())
-            } else {
-                Ok(None)
-            }
-        }
-
-        async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError> {
-            if let Some(user_id) = self.emails.lock().unwrap().get(email) {
-                Ok(self.users.lock().unwrap().get(user_id).cloned())
-            } else {
-                Ok(None)
-            }
-        }
-
-        async fn update(
-            &self,
-            id: &Uuid,
-            updates: UpdateUserRequest,
-        ) -> Result<User, UserRepositoryError> {
-            let mut users = self.users.lock().unwrap();
-            if let Some(user) = users.get_mut(id) {
-                if let Some(email) = updates.email {
-                    user.email = email;
-                }
-                user.updated_at = chrono::Utc::now();
-                Ok(user.clone())
-            } else {
-                Err(UserRepositoryError::NotFound)
-            }
-        }
-
-        async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
-            Ok(self.users.lock().unwrap().remove(id).is_some())
-        }
-
-        async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError> {
-            Ok(self.usernames.lock().unwrap().contains_key(username))
-        }
-
-        async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
-            Ok(self.emails.lock().unwrap().contains_key(email))
-        }
-    }
-
     #[tokio::test]
     async fn test_login_success() {
         let temp_dir = TempDir::new().unwrap();
         let session_path = temp_dir.path().to_path_buf();
 
-        let user_repo = Arc::new(MockUserRepository::new());
+        let user_repo = Arc::new(InMemoryUserRepository::new());
         let user_service = Arc::new(UserService::new(user_repo));
         let auth_service =
             AuthService::new(user_service.clone(), "test-secret", Some(session_path)).unwrap();
@@ -572,14 +1214,14 @@ mod tests {
         let user_request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         )
         .unwrap();
 
         user_service.register(user_request).await.unwrap();
 
         // Test login
-        let login_result = auth_service.login("testuser", "password123").await;
+        let login_result = auth_service.login("testuser", "kestrel-canyon-42").await;
         assert!(login_result.is_ok());
 
         let login_response = login_result.unwrap();
@@ -588,12 +1230,23 @@ mod tests {
         assert!(!login_response.refresh_token.is_empty());
     }
 
+    #[test]
+    fn test_uses_default_secret() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_service = Arc::new(UserService::new(Arc::new(InMemoryUserRepository::new())));
+
+        let service = AuthService::new(user_service.clone(), DEFAULT_JWT_SECRET, Some(temp_dir.path().to_path_buf())).unwrap();
+        assert!(service.uses_default_secret());
+        let service = AuthService::new(user_service, "test-secret", Some(temp_dir.path().to_path_buf())).unwrap();
+        assert!(!service.uses_default_secret());
+    }
+
     #[tokio::test]
     async fn test_login_failure() {
         let temp_dir = TempDir::new().unwrap();
         let session_path = temp_dir.path().to_path_buf();
 
-        let user_repo = Arc::new(MockUserRepository::new());
+        let user_repo = Arc::new(InMemoryUserRepository::new());
         let user_service = Arc::new(UserService::new(user_repo));
         let auth_service =
             AuthService::new(user_service, "test-secret", Some(session_path)).unwrap();
@@ -611,7 +1264,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let session_path = temp_dir.path().to_path_buf();
 
-        let user_repo = Arc::new(MockUserRepository::new());
+        let user_repo = Arc::new(InMemoryUserRepository::new());
         let user_service = Arc::new(UserService::new(user_repo));
         let auth_service =
             AuthService::new(user_service.clone(), "test-secret", Some(session_path)).unwrap();
@@ -620,12 +1273,12 @@ mod tests {
         let user_request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         )
         .unwrap();
 
         user_service.register(user_request).await.unwrap();
-        let login_response = auth_service.login("testuser", "password123").await.unwrap();
+        let login_response = auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
 
         // Validate token
         let validation_result = auth_service.validate_token(&login_response.token).await;
@@ -635,12 +1288,517 @@ mod tests {
         assert_eq!(validated_user.username, "testuser");
     }
 
+    #[tokio::test]
+    async fn test_session_cache_skips_user_lookup_until_session_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_path = temp_dir.path().to_path_buf();
+
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo.clone()));
+        let auth_service =
+            AuthService::new(user_service.clone(), "test-secret", Some(session_path)).unwrap();
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+
+        let user = user_service.register(user_request).await.unwrap();
+        auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        // The first lookup validates against the repository and fills the cache
+        assert!(auth_service.get_current_context().await.unwrap().is_some());
+        assert!(auth_service.session_cache_path().exists());
+
+        // While the cache is fresh the repository is not consulted
+        user_repo.delete(&user.id).await.unwrap();
+        let cached = auth_service.get_current_context().await.unwrap().unwrap();
+        assert_eq!(cached.user.username, "testuser");
+
+        // Rewriting the session invalidates the cache
+        let session = auth_service.load_session().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        auth_service.save_session(&session).unwrap();
+        assert!(auth_service.get_current_context().await.unwrap().is_none());
+        assert!(!auth_service.session_cache_path().exists());
+    }
+
+    #[tokio::test]
+    async fn test_issued_token_is_limited_to_its_scopes() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_path = temp_dir.path().to_path_buf();
+
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let auth_service =
+            AuthService::new(user_service.clone(), "test-secret", Some(session_path)).unwrap();
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+
+        user_service.register(user_request).await.unwrap();
+        let login_response = auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        // Session tokens carry every scope
+        let session_context = auth_service.authenticate(&login_response.token).await.unwrap();
+        assert_eq!(session_context.scopes, Scope::ALL.to_vec());
+
+        let issued = auth_service
+            .issue_token(&[Scope::TasksRead], Duration::hours(1))
+            .await
+            .unwrap();
+
+        let context = auth_service.authenticate(&issued.token).await.unwrap();
+        assert_eq!(context.user.username, "testuser");
+        assert!(context.require(Scope::TasksRead).is_ok());
+        assert!(matches!(
+            context.require(Scope::TasksWrite),
+            Err(AuthorizationError::MissingScope { required: Scope::TasksWrite })
+        ));
+    }
+
+    // Mock ApiKeyRepository for testing
+    #[derive(Default)]
+    struct MockApiKeyRepository {
+        keys: Mutex<Vec<ApiKey>>,
+    }
+
+    #[async_trait]
+    impl ApiKeyRepository for MockApiKeyRepository {
+        async fn store(&self, user_id: &Uuid, request: StoreApiKeyRequest) -> Result<ApiKey, ApiKeyRepositoryError> {
+            let api_key = ApiKey {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                name: request.name,
+                prefix: request.prefix,
+                key_hash: request.key_hash,
+                scopes: request.scopes,
+                expires_at: request.expires_at,
+                last_used_at: None,
+                revoked_at: None,
+                created_at: Utc::now(),
+            };
+            self.keys.lock().unwrap().push(api_key.clone());
+            Ok(api_key)
+        }
+
+        async fn use_key(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError> {
+            let mut keys = self.keys.lock().unwrap();
+            let api_key = keys.iter_mut().find(|key| key.key_hash == key_hash && key.is_active());
+            Ok(api_key.map(|key| {
+                key.last_used_at = Some(Utc::now());
+                key.clone()
+            }))
+        }
+
+        async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<ApiKey>, ApiKeyRepositoryError> {
+            let keys = self.keys.lock().unwrap();
+            Ok(keys.iter().filter(|key| key.user_id == *user_id).cloned().collect())
+        }
+
+        async fn revoke(&self, user_id: &Uuid, id_or_prefix: &str) -> Result<Option<ApiKey>, ApiKeyRepositoryError> {
+            let mut keys = self.keys.lock().unwrap();
+            let api_key = keys.iter_mut().find(|key| {
+                key.user_id == *user_id
+                    && key.revoked_at.is_none()
+                    && (key.id.to_string() == id_or_prefix || key.prefix == id_or_prefix)
+            });
+            Ok(api_key.map(|key| {
+                key.revoked_at = Some(Utc::now());
+                key.clone()
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_authenticates_until_revoked() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_path = temp_dir.path().to_path_buf();
+
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let auth_service = AuthService::new(user_service.clone(), "test-secret", Some(session_path))
+            .unwrap()
+            .with_api_keys(Arc::new(MockApiKeyRepository::default()));
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+
+        user_service.register(user_request).await.unwrap();
+        auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        let created = auth_service
+            .create_api_key("backup script", &[Scope::TasksRead], None)
+            .await
+            .unwrap();
+        assert!(created.key.starts_with(API_KEY_PREFIX));
+        assert!(created.key.starts_with(&created.api_key.prefix));
+        assert_ne!(created.api_key.key_hash, created.key);
+
+        let context = auth_service.authenticate(&created.key).await.unwrap();
+        assert_eq!(context.user.username, "testuser");
+        assert_eq!(context.scopes, vec![Scope::TasksRead]);
+        assert!(auth_service.validate_token(&created.key).await.is_ok());
+
+        let revoked = auth_service.revoke_api_key(&created.api_key.prefix).await.unwrap();
+        assert_eq!(revoked.id, created.api_key.id);
+        assert!(matches!(
+            auth_service.authenticate(&created.key).await,
+            Err(AuthServiceError::InvalidToken)
+        ));
+        assert!(matches!(
+            auth_service.revoke_api_key(&created.api_key.prefix).await,
+            Err(AuthServiceError::ApiKeyNotFound)
+        ));
+    }
+
+    // Mock SessionRepository for testing
+    #[derive(Default)]
+    struct MockSessionRepository {
+        sessions: Mutex<Vec<DeviceSession>>,
+    }
+
+    impl MockSessionRepository {
+        fn active(session: &DeviceSession) -> bool {
+            session.revoked_at.is_none() && session.expires_at > Utc::now()
+        }
+    }
+
+    #[async_trait]
+    impl SessionRepository for MockSessionRepository {
+        async fn create(
+            &self,
+            user_id: &Uuid,
+            name: &str,
+            expires_at: DateTime<Utc>,
+        ) -> Result<DeviceSession, SessionRepositoryError> {
+            let session = DeviceSession {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                name: name.to_string(),
+                expires_at,
+                last_used_at: Utc::now(),
+                revoked_at: None,
+                created_at: Utc::now(),
+            };
+            self.sessions.lock().unwrap().push(session.clone());
+            Ok(session)
+        }
+
+        async fn use_session(&self, id: &Uuid) -> Result<Option<DeviceSession>, SessionRepositoryError> {
+            let sessions = self.sessions.lock().unwrap();
+            Ok(sessions.iter().find(|session| session.id == *id && Self::active(session)).cloned())
+        }
+
+        async fn extend(&self, id: &Uuid, expires_at: DateTime<Utc>) -> Result<(), SessionRepositoryError> {
+            let mut sessions = self.sessions.lock().unwrap();
+            if let Some(session) = sessions.iter_mut().find(|session| session.id == *id) {
+                session.expires_at = expires_at;
+            }
+            Ok(())
+        }
+
+        async fn find_active_by_user_id(&self, user_id: &Uuid) -> Result<Vec<DeviceSession>, SessionRepositoryError> {
+            let sessions = self.sessions.lock().unwrap();
+            Ok(sessions
+                .iter()
+                .filter(|session| session.user_id == *user_id && Self::active(session))
+                .cloned()
+                .collect())
+        }
+
+        async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<DeviceSession>, SessionRepositoryError> {
+            let sessions = self.sessions.lock().unwrap();
+            Ok(sessions.iter().filter(|session| session.user_id == *user_id).cloned().collect())
+        }
+
+        async fn revoke(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<DeviceSession>, SessionRepositoryError> {
+            let mut sessions = self.sessions.lock().unwrap();
+            let session = sessions
+                .iter_mut()
+                .find(|session| session.id == *id && session.user_id == *user_id && session.revoked_at.is_none());
+            Ok(session.map(|session| {
+                session.revoked_at = Some(Utc::now());
+                session.clone()
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revoked_session_tokens_stop_working() {
+        let laptop_dir = TempDir::new().unwrap();
+        let desktop_dir = TempDir::new().unwrap();
+
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let sessions = Arc::new(MockSessionRepository::default());
+        let device = |dir: &TempDir| {
+            AuthService::new(user_service.clone(), "test-secret", Some(dir.path().to_path_buf()))
+                .unwrap()
+                .with_sessions(sessions.clone())
+        };
+        let laptop = device(&laptop_dir);
+        let desktop = device(&desktop_dir);
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+
+        user_service.register(user_request).await.unwrap();
+        let laptop_login = laptop.login("testuser", "kestrel-canyon-42").await.unwrap();
+        desktop.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        let listed = desktop.list_sessions().await.unwrap();
+        assert_eq!(listed.len(), 2);
+        let laptop_session = laptop.current_session_id().unwrap();
+        assert_ne!(desktop.current_session_id(), Some(laptop_session));
+
+        // Revoke the laptop from the desktop by its short ID
+        let short_id = laptop_session.simple().to_string()[..8].to_string();
+        assert!(desktop.get_current_context().await.unwrap().is_some());
+        let revoked = desktop.revoke_session(&short_id).await.unwrap();
+        assert_eq!(revoked.id, laptop_session);
+        assert!(!desktop.session_cache_path().exists());
+
+        assert!(matches!(
+            laptop.authenticate(&laptop_login.token).await,
+            Err(AuthServiceError::InvalidToken)
+        ));
+        assert!(laptop.get_current_context().await.unwrap().is_none());
+        assert!(desktop.get_current_context().await.unwrap().is_some());
+        assert!(matches!(
+            desktop.revoke_session(&short_id).await,
+            Err(AuthServiceError::UnknownSession { .. })
+        ));
+
+        // Logging out revokes the session too
+        desktop.logout().await.unwrap();
+        assert!(sessions.sessions.lock().unwrap().iter().all(|session| session.revoked_at.is_some()));
+    }
+
+    // Mock RevokedTokenRepository for testing
+    #[derive(Default)]
+    struct MockRevokedTokenRepository {
+        jtis: Mutex<Vec<String>>,
+        cutoffs: Mutex<Vec<(Uuid, DateTime<Utc>)>>,
+    }
+
+    #[async_trait]
+    impl RevokedTokenRepository for MockRevokedTokenRepository {
+        async fn revoke(
+            &self,
+            jti: &str,
+            _user_id: &Uuid,
+            _expires_at: DateTime<Utc>,
+        ) -> Result<(), RevokedTokenRepositoryError> {
+            self.jtis.lock().unwrap().push(jti.to_string());
+            Ok(())
+        }
+
+        async fn is_revoked(&self, jti: &str) -> Result<bool, RevokedTokenRepositoryError> {
+            Ok(self.jtis.lock().unwrap().iter().any(|revoked| revoked == jti))
+        }
+
+        async fn revoke_all(&self, user_id: &Uuid, issued_before: DateTime<Utc>) -> Result<(), RevokedTokenRepositoryError> {
+            self.cutoffs.lock().unwrap().push((*user_id, issued_before));
+            Ok(())
+        }
+
+        async fn revoked_before(&self, user_id: &Uuid) -> Result<Option<DateTime<Utc>>, RevokedTokenRepositoryError> {
+            let cutoffs = self.cutoffs.lock().unwrap();
+            Ok(cutoffs.iter().filter(|(id, _)| id == user_id).map(|(_, issued_before)| *issued_before).max())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logout_and_password_change_revoke_tokens() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_path = temp_dir.path().to_path_buf();
+
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let auth_service = AuthService::new(user_service.clone(), "test-secret", Some(session_path))
+            .unwrap()
+            .with_token_revocation(Arc::new(MockRevokedTokenRepository::default()));
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+
+        user_service.register(user_request).await.unwrap();
+
+        // A copied token stops working once its session logs out
+        let stolen = auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+        assert!(auth_service.authenticate(&stolen.token).await.is_ok());
+        auth_service.logout().await.unwrap();
+        assert!(matches!(
+            auth_service.authenticate(&stolen.token).await,
+            Err(AuthServiceError::InvalidToken)
+        ));
+        assert!(matches!(
+            auth_service.authenticate(&stolen.refresh_token).await,
+            Err(AuthServiceError::InvalidToken)
+        ));
+
+        // Changing the password revokes the old pair but keeps this device logged in
+        let stolen = auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+        auth_service.change_password("kestrel-canyon-42", "heron-meadow-77").await.unwrap();
+        assert!(matches!(
+            auth_service.authenticate(&stolen.token).await,
+            Err(AuthServiceError::InvalidToken)
+        ));
+        let context = auth_service.get_current_context().await.unwrap().unwrap();
+        assert_eq!(context.user.username, "testuser");
+    }
+
+    #[tokio::test]
+    async fn test_password_change_revokes_other_devices() {
+        let laptop_dir = TempDir::new().unwrap();
+        let desktop_dir = TempDir::new().unwrap();
+
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let sessions = Arc::new(MockSessionRepository::default());
+        let revoked_tokens = Arc::new(MockRevokedTokenRepository::default());
+        let device = |dir: &TempDir| {
+            AuthService::new(user_service.clone(), "test-secret", Some(dir.path().to_path_buf()))
+                .unwrap()
+                .with_sessions(sessions.clone())
+                .with_token_revocation(revoked_tokens.clone())
+        };
+        let laptop = device(&laptop_dir);
+        let desktop = device(&desktop_dir);
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+
+        user_service.register(user_request).await.unwrap();
+        let stolen = laptop.login("testuser", "kestrel-canyon-42").await.unwrap();
+        desktop.login("testuser", "kestrel-canyon-42").await.unwrap();
+        let old_session = desktop.current_session_id().unwrap();
+
+        desktop.change_password("kestrel-canyon-42", "heron-meadow-77").await.unwrap();
+        for token in [&stolen.token, &stolen.refresh_token] {
+            assert!(matches!(laptop.authenticate(token).await, Err(AuthServiceError::InvalidToken)));
+        }
+        assert!(laptop.get_current_context().await.unwrap().is_none());
+
+        // Only this device carries on, in a new session
+        let context = desktop.get_current_context().await.unwrap().unwrap();
+        assert_eq!(context.user.username, "testuser");
+        let listed = desktop.list_sessions().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_ne!(listed[0].id, old_session);
+    }
+
+    #[tokio::test]
+    async fn test_tokens_issued_before_revoke_all_stop_working() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let revoked_tokens = Arc::new(MockRevokedTokenRepository::default());
+        let auth_service = AuthService::new(user_service.clone(), "test-secret", Some(temp_dir.path().to_path_buf()))
+            .unwrap()
+            .with_token_revocation(revoked_tokens.clone());
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+        let user = user_service.register(user_request).await.unwrap();
+        let login = auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        revoked_tokens.revoke_all(&user.id, Utc::now() + Duration::seconds(1)).await.unwrap();
+        assert!(matches!(
+            auth_service.authenticate(&login.token).await,
+            Err(AuthServiceError::InvalidToken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_suspended_account_tokens_are_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let auth_service =
+            AuthService::new(user_service.clone(), "test-secret", Some(temp_dir.path().to_path_buf())).unwrap();
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+        user_service.register(user_request).await.unwrap();
+        let login = auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        user_service.set_active("testuser", false).await.unwrap();
+        assert!(matches!(
+            auth_service.validate_token(&login.token).await,
+            Err(AuthServiceError::AccountSuspended)
+        ));
+        assert!(matches!(
+            auth_service.login("testuser", "kestrel-canyon-42").await,
+            Err(AuthServiceError::AccountSuspended)
+        ));
+
+        user_service.set_active("testuser", true).await.unwrap();
+        assert!(auth_service.validate_token(&login.token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_change_username_keeps_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_repo = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(user_repo));
+        let auth_service =
+            AuthService::new(user_service.clone(), "test-secret", Some(temp_dir.path().to_path_buf())).unwrap();
+
+        let user_request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        )
+        .unwrap();
+        user_service.register(user_request).await.unwrap();
+        auth_service.login("testuser", "kestrel-canyon-42").await.unwrap();
+
+        let user = auth_service.change_username(" renamed ").await.unwrap();
+        assert_eq!(user.username, "renamed");
+        assert_eq!(auth_service.load_session().unwrap().username, "renamed");
+        let context = auth_service.get_current_context().await.unwrap().unwrap();
+        assert_eq!(context.user.username, "renamed");
+        assert!(auth_service.login("renamed", "kestrel-canyon-42").await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_session_persistence() {
         let temp_dir = TempDir::new().unwrap();
         let session_path = temp_dir.path().to_path_buf();
 
-        let user_repo = Arc::new(MockUserRepository::new());
+        let user_repo = Arc::new(InMemoryUserRepository::new());
         let user_service = Arc::new(UserService::new(user_repo));
 
         // Create first auth service instance
@@ -655,13 +1813,13 @@ mod tests {
         let user_request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         )
         .unwrap();
 
         user_service.register(user_request).await.unwrap();
         auth_service1
-            .login("testuser", "password123")
+            .login("testuser", "kestrel-canyon-42")
             .await
             .unwrap();
 
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::user::UserResponse;
+
+/// Permission carried by a token. Every operation requires exactly one scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    /// Read tasks, statuses and reports
+    #[serde(rename = "tasks:read")]
+    TasksRead,
+    /// Add tasks and nothing else, e.g. for an inbound webhook URL
+    #[serde(rename = "tasks:add")]
+    TasksAdd,
+    /// Read the iCalendar feed and nothing else, for a key that calendar
+    /// apps keep in the feed URL
+    #[serde(rename = "calendar:feed")]
+    CalendarFeed,
+    /// Create, change and delete tasks and statuses
+    #[serde(rename = "tasks:write")]
+    TasksWrite,
+    /// Manage the account itself: password, webhooks and tokens
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 5] = [Scope::TasksRead, Scope::TasksAdd, Scope::CalendarFeed, Scope::TasksWrite, Scope::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::TasksRead => "tasks:read",
+            Scope::TasksAdd => "tasks:add",
+            Scope::CalendarFeed => "calendar:feed",
+            Scope::TasksWrite => "tasks:write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    /// Whether holding this scope is enough for an operation requiring `required`.
+    /// Writing implies reading and adding, and admin implies everything.
+    pub fn grants(&self, required: Scope) -> bool {
+        match self {
+            Scope::Admin => true,
+            Scope::TasksWrite => matches!(required, Scope::TasksWrite | Scope::TasksAdd | Scope::TasksRead),
+            Scope::TasksAdd => required == Scope::TasksAdd,
+            Scope::CalendarFeed => required == Scope::CalendarFeed,
+            Scope::TasksRead => required == Scope::TasksRead,
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|scope| scope.as_str() == s.trim())
+            .ok_or_else(|| {
+                let available: Vec<&str> = Self::ALL.iter().map(|s| s.as_str()).collect();
+                format!("Unknown scope '{}'. Available scopes: {}", s, available.join(", "))
+            })
+    }
+}
+
+/// Environment variable with the comma-separated user IDs allowed to run
+/// `admin` commands on other accounts. IDs, not usernames: a username can be
+/// changed, and whoever took over a listed one would become an administrator.
+pub const ADMIN_USER_IDS_ENV_VAR: &str = "TODO_ADMIN_USER_IDS";
+
+#[derive(Error, Debug)]
+pub enum AuthorizationError {
+    #[error("Token is missing the '{required}' scope")]
+    MissingScope { required: Scope },
+
+    #[error("User '{username}' is not an administrator")]
+    NotAdministrator { username: String },
+}
+
+/// An authenticated user together with the scopes of the token they presented
+#[derive(Debug)]
+pub struct AuthContext {
+    pub user: UserResponse,
+    pub scopes: Vec<Scope>,
+}
+
+impl AuthContext {
+    pub fn allows(&self, required: Scope) -> bool {
+        self.scopes.iter().any(|scope| scope.grants(required))
+    }
+
+    /// Single check every entry point goes through before touching user data
+    pub fn require(&self, required: Scope) -> Result<&UserResponse, AuthorizationError> {
+        if self.allows(required) {
+            Ok(&self.user)
+        } else {
+            Err(AuthorizationError::MissingScope { required })
+        }
+    }
+
+    /// Like `require(Scope::Admin)`, and the user's ID must also be listed in `TODO_ADMIN_USER_IDS`
+    pub fn require_administrator(&self) -> Result<&UserResponse, AuthorizationError> {
+        let administrators = std::env::var(ADMIN_USER_IDS_ENV_VAR).unwrap_or_default();
+        self.require_administrator_among(&administrators)
+    }
+
+    fn require_administrator_among(&self, administrators: &str) -> Result<&UserResponse, AuthorizationError> {
+        let user = self.require(Scope::Admin)?;
+        if administrators.split(',').any(|id| id.trim().parse::<Uuid>().is_ok_and(|id| id == user.id)) {
+            Ok(user)
+        } else {
+            Err(AuthorizationError::NotAdministrator {
+                username: user.username.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn context(scopes: Vec<Scope>) -> AuthContext {
+        AuthContext {
+            user: UserResponse {
+                id: Uuid::new_v4(),
+                username: "testuser".to_string(),
+                email: "test@example.com".to_string(),
+                is_active: true,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            scopes,
+        }
+    }
+
+    #[test]
+    fn test_scope_requirements() {
+        let read_only = context(vec![Scope::TasksRead]);
+        assert!(read_only.require(Scope::TasksRead).is_ok());
+        assert!(matches!(
+            read_only.require(Scope::TasksWrite),
+            Err(AuthorizationError::MissingScope { required: Scope::TasksWrite })
+        ));
+        assert!(read_only.require(Scope::Admin).is_err());
+
+        let writer = context(vec![Scope::TasksWrite]);
+        assert!(writer.allows(Scope::TasksRead));
+        assert!(writer.allows(Scope::TasksAdd));
+        assert!(!writer.allows(Scope::Admin));
+
+        let adder = context(vec![Scope::TasksAdd]);
+        assert!(adder.allows(Scope::TasksAdd));
+        assert!(!adder.allows(Scope::TasksRead));
+        assert!(!adder.allows(Scope::TasksWrite));
+
+        // A feed key reads the feed only, and reading tasks does not read the feed
+        let feed = context(vec![Scope::CalendarFeed]);
+        assert!(feed.allows(Scope::CalendarFeed));
+        assert!(Scope::ALL.iter().filter(|scope| **scope != Scope::CalendarFeed).all(|scope| !feed.allows(*scope)));
+        assert!(!read_only.allows(Scope::CalendarFeed));
+
+        let admin = context(vec![Scope::Admin]);
+        assert!(Scope::ALL.iter().all(|scope| admin.allows(*scope)));
+    }
+
+    #[test]
+    fn test_administrator_requirements() {
+        let admin = context(vec![Scope::Admin]);
+        let listed = format!("{}, {}", Uuid::new_v4(), admin.user.id);
+        assert!(admin.require_administrator_among(&listed).is_ok());
+        assert!(matches!(
+            admin.require_administrator_among(&Uuid::new_v4().to_string()),
+            Err(AuthorizationError::NotAdministrator { .. })
+        ));
+        assert!(admin.require_administrator_among("").is_err());
+        // Usernames are not IDs
+        assert!(admin.require_administrator_among("testuser").is_err());
+
+        // Listed administrators still need an admin-scoped token
+        let writer = context(vec![Scope::TasksWrite]);
+        assert!(matches!(
+            writer.require_administrator_among(&writer.user.id.to_string()),
+            Err(AuthorizationError::MissingScope { required: Scope::Admin })
+        ));
+    }
+
+    #[test]
+    fn test_administrator_rights_stay_with_the_account_when_it_is_renamed() {
+        let listed = context(vec![Scope::Admin]);
+        let administrators = listed.user.id.to_string();
+
+        let mut renamed = context(vec![Scope::Admin]);
+        renamed.user.id = listed.user.id;
+        renamed.user.username = "formerly_testuser".to_string();
+        assert!(renamed.require_administrator_among(&administrators).is_ok());
+
+        // Someone else taking the freed username gets nothing
+        let impostor = context(vec![Scope::Admin]);
+        assert_eq!(impostor.user.username, listed.user.username);
+        assert!(matches!(
+            impostor.require_administrator_among(&administrators),
+            Err(AuthorizationError::NotAdministrator { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scope_serialization() {
+        assert_eq!(serde_json::to_string(&Scope::TasksRead).unwrap(), "\"tasks:read\"");
+        assert_eq!("tasks:write".parse::<Scope>(), Ok(Scope::TasksWrite));
+        assert!("tasks:delete".parse::<Scope>().is_err());
+    }
+}
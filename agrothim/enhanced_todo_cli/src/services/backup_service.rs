@@ -0,0 +1,296 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio_stream::StreamExt;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{UserRepository, UserRepositoryError},
+    export::{archive::Archive, FormatError, TaskRecord},
+    models::{
+        task::StoreTaskRequest,
+        user::{StoreUserRequest, User},
+    },
+    services::{TaskService, TaskServiceError},
+};
+
+/// Current backup schema version
+pub const BACKUP_VERSION: u32 = 1;
+
+const BACKUP_FORMAT: &str = "backup";
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error(transparent)]
+    User(#[from] UserRepositoryError),
+
+    #[error(transparent)]
+    Task(#[from] TaskServiceError),
+
+    #[error(transparent)]
+    Format(#[from] FormatError),
+
+    #[error("The backup holds {count} accounts, not one")]
+    NotSingleAccount { count: usize },
+}
+
+/// One account of a backup. Unlike a data export it keeps the password
+/// hash, so the account can log in again once restored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountBackup {
+    pub username: String,
+    pub email: String,
+    pub password_hash: String,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    /// Tasks with the custom statuses and transitions they use
+    pub tasks: Archive,
+}
+
+/// Accounts and their tasks in a file every backend can restore, for moving
+/// an installation to another machine or database without `pg_dump`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub accounts: Vec<AccountBackup>,
+}
+
+impl Backup {
+    pub fn new(accounts: Vec<AccountBackup>) -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            created_at: Utc::now(),
+            accounts,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, FormatError> {
+        serde_json::to_string_pretty(self).map_err(|e| FormatError::SerializeError {
+            format: BACKUP_FORMAT.to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    pub fn parse(input: &str) -> Result<Self, FormatError> {
+        let backup: Self = serde_json::from_str(input).map_err(|e| FormatError::ParseError {
+            format: BACKUP_FORMAT.to_string(),
+            message: e.to_string(),
+        })?;
+        if backup.version > BACKUP_VERSION {
+            return Err(FormatError::UnsupportedVersion {
+                version: backup.version,
+                supported: BACKUP_VERSION,
+            });
+        }
+        Ok(backup)
+    }
+}
+
+/// Outcome of a restore
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RestoreSummary {
+    /// Accounts whose tasks were replaced
+    pub accounts: usize,
+    /// Accounts the backup added to the database
+    pub created: usize,
+    pub tasks: usize,
+}
+
+pub struct BackupService {
+    user_repository: Arc<dyn UserRepository>,
+    task_service: Arc<TaskService>,
+}
+
+impl BackupService {
+    pub fn new(user_repository: Arc<dyn UserRepository>, task_service: Arc<TaskService>) -> Self {
+        Self {
+            user_repository,
+            task_service,
+        }
+    }
+
+    /// Back up the user's own account
+    pub async fn backup_user(&self, user_id: &Uuid) -> Result<Backup, BackupError> {
+        let user = self
+            .user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(UserRepositoryError::NotFound)?;
+        let backup = Backup::new(vec![self.account_backup(user).await?]);
+
+        info!("Backed up account of user {}", user_id);
+        Ok(backup)
+    }
+
+    /// Back up every account in the database
+    pub async fn backup_all(&self) -> Result<Backup, BackupError> {
+        let mut accounts = Vec::new();
+        for user in self.user_repository.find_all().await? {
+            accounts.push(self.account_backup(user).await?);
+        }
+
+        info!("Backed up {} accounts", accounts.len());
+        Ok(Backup::new(accounts))
+    }
+
+    async fn account_backup(&self, user: User) -> Result<AccountBackup, BackupError> {
+        let mut records = Vec::new();
+        let mut tasks = std::pin::pin!(self.task_service.stream_tasks(&user.id));
+        while let Some(task) = tasks.next().await {
+            records.push(TaskRecord::from(&task?));
+        }
+        let workflow = self.task_service.get_workflow(&user.id).await?;
+
+        Ok(AccountBackup {
+            username: user.username,
+            email: user.email,
+            password_hash: user.password_hash,
+            email_verified_at: user.email_verified_at,
+            is_active: user.is_active,
+            tasks: Archive::new(records, &workflow),
+        })
+    }
+
+    /// Replace the user's tasks with those of a single-account backup,
+    /// whichever account it was taken from
+    pub async fn restore_user(&self, user_id: &Uuid, backup: Backup) -> Result<RestoreSummary, BackupError> {
+        let [account] = <[AccountBackup; 1]>::try_from(backup.accounts)
+            .map_err(|accounts| BackupError::NotSingleAccount { count: accounts.len() })?;
+        let tasks = self.restore_tasks(user_id, account.tasks).await?;
+
+        info!("Restored {} tasks for user {}", tasks, user_id);
+        Ok(RestoreSummary { accounts: 1, created: 0, tasks })
+    }
+
+    /// Restore every account of the backup, matched by username: missing
+    /// accounts are created and the tasks of each one are replaced
+    pub async fn restore_all(&self, backup: Backup) -> Result<RestoreSummary, BackupError> {
+        let mut summary = RestoreSummary::default();
+        for account in backup.accounts {
+            let user = match self.user_repository.find_by_username(&account.username).await? {
+                Some(user) => user,
+                None => {
+                    summary.created += 1;
+                    self.create_account(&account).await?
+                }
+            };
+            summary.tasks += self.restore_tasks(&user.id, account.tasks).await?;
+            summary.accounts += 1;
+        }
+
+        info!("Restored {} accounts ({} created)", summary.accounts, summary.created);
+        Ok(summary)
+    }
+
+    async fn create_account(&self, account: &AccountBackup) -> Result<User, BackupError> {
+        let mut user = self
+            .user_repository
+            .store(StoreUserRequest {
+                username: account.username.clone(),
+                email: account.email.clone(),
+                password: String::new(),
+                password_hash: account.password_hash.clone(),
+            })
+            .await?;
+        if account.email_verified_at.is_some() {
+            user = self.user_repository.mark_email_verified(&user.id).await?;
+        }
+        if !account.is_active {
+            user = self.user_repository.set_active(&user.id, false).await?;
+        }
+        Ok(user)
+    }
+
+    /// Same steps as `import --archive`: statuses first, so tasks and
+    /// transitions can be mapped onto the account's codes
+    async fn restore_tasks(&self, user_id: &Uuid, mut archive: Archive) -> Result<usize, BackupError> {
+        let workflow = self.task_service.ensure_statuses(user_id, &archive.status_names()).await?;
+        archive.remap_statuses(&workflow);
+
+        let requests = archive.tasks.into_iter().map(StoreTaskRequest::from).collect();
+        let count = self.task_service.import_tasks(user_id, requests, true).await?;
+
+        for transition in &archive.transitions {
+            let (from, to) = (workflow.name(transition.from_status), workflow.name(transition.to_status));
+            if let Err(e) = self.task_service.set_transition(user_id, &from, &to, true).await {
+                warn!("Failed to restore transition {} -> {} for user {}: {}", from, to, user_id, e);
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemoryTaskRepository, InMemoryUserRepository};
+
+    async fn store_user(repository: &InMemoryUserRepository, username: &str) -> User {
+        repository
+            .store(StoreUserRequest {
+                username: username.to_string(),
+                email: format!("{}@example.com", username),
+                password: String::new(),
+                password_hash: format!("{}-hash", username),
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_backup_all_restores_into_an_empty_database() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let tasks = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let service = BackupService::new(users.clone(), tasks.clone());
+
+        let alice = store_user(&users, "alice").await;
+        let bob = store_user(&users, "bob").await;
+        users.set_active(&bob.id, false).await.unwrap();
+        for title in ["Write report", "Call plumber"] {
+            let request = StoreTaskRequest { title: title.to_string(), ..Default::default() };
+            tasks.create_task(&alice.id, request).await.unwrap();
+        }
+
+        let backup = Backup::parse(&service.backup_all().await.unwrap().to_json().unwrap()).unwrap();
+        assert_eq!(backup.accounts.len(), 2);
+        assert_eq!(backup.accounts[0].password_hash, "alice-hash");
+
+        let restored_users = Arc::new(InMemoryUserRepository::new());
+        let restored_tasks = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let restored = BackupService::new(restored_users.clone(), restored_tasks.clone());
+        let summary = restored.restore_all(backup.clone()).await.unwrap();
+        assert_eq!(summary, RestoreSummary { accounts: 2, created: 2, tasks: 2 });
+
+        let alice = restored_users.find_by_username("alice").await.unwrap().unwrap();
+        assert_eq!(alice.password_hash, "alice-hash");
+        assert_eq!(restored_tasks.stream_tasks(&alice.id).collect::<Vec<_>>().await.len(), 2);
+        assert!(!restored_users.find_by_username("bob").await.unwrap().unwrap().is_active);
+
+        // Restoring again replaces the tasks instead of duplicating them
+        let summary = restored.restore_all(backup).await.unwrap();
+        assert_eq!(summary, RestoreSummary { accounts: 2, created: 0, tasks: 2 });
+        assert_eq!(restored_tasks.stream_tasks(&alice.id).collect::<Vec<_>>().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_restore_user_needs_a_single_account_backup() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let tasks = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let service = BackupService::new(users.clone(), tasks.clone());
+        let alice = store_user(&users, "alice").await;
+        store_user(&users, "bob").await;
+
+        let result = service.restore_user(&alice.id, service.backup_all().await.unwrap()).await;
+        assert!(matches!(result, Err(BackupError::NotSingleAccount { count: 2 })));
+
+        let request = StoreTaskRequest { title: "Keep me".to_string(), ..Default::default() };
+        tasks.create_task(&alice.id, request).await.unwrap();
+        let backup = service.backup_user(&alice.id).await.unwrap();
+        let summary = service.restore_user(&alice.id, backup).await.unwrap();
+        assert_eq!(summary.tasks, 1);
+        assert_eq!(tasks.stream_tasks(&alice.id).collect::<Vec<_>>().await.len(), 1);
+    }
+}
@@ -0,0 +1,305 @@
+use chrono::{NaiveDate, Utc};
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::{
+    models::{calendar, user::UserResponse},
+    services::{DigestService, MaintenanceService, ReminderService, ScheduleService},
+};
+
+const PID_FILE: &str = "daemon.pid";
+
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error("The daemon is already running (PID {pid})")]
+    AlreadyRunning { pid: u32 },
+
+    #[error("The daemon is not running")]
+    NotRunning,
+
+    #[error("Could not stop the daemon (PID {pid}): {message}")]
+    Signal { pid: u32, message: String },
+
+    #[error("Could not read or write the PID file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// `daemon.pid` in the session directory while a daemon runs; the file is
+/// removed when this is dropped
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Record this process as the daemon. A file left behind by a daemon
+    /// that died is taken over; one of a live daemon is an error.
+    pub fn acquire(dir: &Path) -> Result<Self, DaemonError> {
+        if let Some(pid) = running_pid(dir)? {
+            return Err(DaemonError::AlreadyRunning { pid });
+        }
+        fs::create_dir_all(dir)?;
+        let path = dir.join(PID_FILE);
+        // create_new loses the race to a daemon starting at the same time
+        let mut file = OpenOptions::new().write(true).create_new(true).open(&path).map_err(|e| {
+            match (e.kind(), read_pid(&path)) {
+                (std::io::ErrorKind::AlreadyExists, Some(pid)) => DaemonError::AlreadyRunning { pid },
+                _ => e.into(),
+            }
+        })?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if read_pid(&self.path) == Some(std::process::id()) {
+            if let Err(e) = fs::remove_file(&self.path) {
+                warn!("Failed to remove {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// PID of the daemon running for this session directory. A PID file whose
+/// process is gone is removed.
+pub fn running_pid(dir: &Path) -> Result<Option<u32>, DaemonError> {
+    let path = dir.join(PID_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    match read_pid(&path) {
+        Some(pid) if is_alive(pid) => Ok(Some(pid)),
+        _ => {
+            debug!("Removing stale PID file {}", path.display());
+            fs::remove_file(&path)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Ask the running daemon to finish its round and exit; its PID
+pub fn stop_daemon(dir: &Path) -> Result<u32, DaemonError> {
+    let pid = running_pid(dir)?.ok_or(DaemonError::NotRunning)?;
+    #[cfg(unix)]
+    let mut command = Command::new("kill");
+    #[cfg(unix)]
+    command.args(["-TERM", &pid.to_string()]);
+    #[cfg(windows)]
+    let mut command = Command::new("taskkill");
+    #[cfg(windows)]
+    command.args(["/PID", &pid.to_string()]);
+
+    let output = command.stdin(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(DaemonError::Signal {
+            pid,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    info!("Asked daemon {} to stop", pid);
+    Ok(pid)
+}
+
+fn is_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    let output = Command::new("kill").args(["-0", &pid.to_string()]).stdin(Stdio::null()).output();
+    #[cfg(windows)]
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .stdin(Stdio::null())
+        .output();
+
+    match output {
+        #[cfg(unix)]
+        Ok(output) => output.status.success(),
+        #[cfg(windows)]
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()),
+        Err(_) => false,
+    }
+}
+
+/// What one daemon round sent and created
+#[derive(Debug, Default, PartialEq)]
+pub struct DaemonRound {
+    pub reminders: usize,
+    pub digests: usize,
+    /// Tasks created by schedules
+    pub tasks: usize,
+    /// Tasks moved to the archive and archive files purged, once a day
+    pub archived: usize,
+    pub purged: usize,
+}
+
+/// Runs the periodic jobs: the session user's reminders through the
+/// configured channels and, with PostgreSQL, everyone's daily digests and
+/// scheduled tasks, plus the user's archive and purge policy once a day.
+/// Failures are logged and retried by the next round.
+pub struct DaemonService {
+    reminder_service: Arc<ReminderService>,
+    digest_service: Option<Arc<DigestService>>,
+    schedule_service: Option<Arc<ScheduleService>>,
+    maintenance_service: Option<Arc<MaintenanceService>>,
+    /// Day the maintenance policy last ran
+    maintained_on: Mutex<Option<NaiveDate>>,
+    user: Option<UserResponse>,
+}
+
+impl DaemonService {
+    pub fn new(reminder_service: Arc<ReminderService>) -> Self {
+        Self {
+            reminder_service,
+            digest_service: None,
+            schedule_service: None,
+            maintenance_service: None,
+            maintained_on: Mutex::new(None),
+            user: None,
+        }
+    }
+
+    /// Deliver this user's reminders
+    pub fn with_user(mut self, user: UserResponse) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    pub fn with_digest_service(mut self, digest_service: Arc<DigestService>) -> Self {
+        self.digest_service = Some(digest_service);
+        self
+    }
+
+    pub fn with_schedule_service(mut self, schedule_service: Arc<ScheduleService>) -> Self {
+        self.schedule_service = Some(schedule_service);
+        self
+    }
+
+    /// Apply the user's archive and purge policy on the first round of each day
+    pub fn with_maintenance_service(mut self, maintenance_service: Arc<MaintenanceService>) -> Self {
+        self.maintenance_service = Some(maintenance_service);
+        self
+    }
+
+    pub async fn round(&self) -> DaemonRound {
+        let mut round = DaemonRound::default();
+
+        if let Some(user) = &self.user {
+            if !self.reminder_service.channel_names().is_empty() {
+                match self.reminder_service.check(user, Utc::now()).await {
+                    Ok(run) if !run.delivered.is_empty() => round.reminders = run.reminders.len(),
+                    Ok(_) => {}
+                    Err(e) => warn!("Daemon failed to check reminders: {}", e),
+                }
+            }
+        }
+
+        if let Some(digest_service) = &self.digest_service {
+            match digest_service.run_due(calendar::now()).await {
+                Ok(run) => round.digests = run.sent,
+                Err(e) => warn!("Daemon failed to send daily digests: {}", e),
+            }
+        }
+
+        if let Some(schedule_service) = &self.schedule_service {
+            match schedule_service.tick(Utc::now()).await {
+                Ok(run) => round.tasks = run.created.len(),
+                Err(e) => warn!("Daemon failed to create scheduled tasks: {}", e),
+            }
+        }
+
+        if let (Some(user), Some(maintenance_service)) = (&self.user, &self.maintenance_service) {
+            let today = calendar::today();
+            if !maintenance_service.policy().is_off() && *self.maintained_on.lock().unwrap() != Some(today) {
+                match maintenance_service.run(&user.id, Utc::now(), false).await {
+                    Ok(report) => {
+                        round.archived = report.archived.len();
+                        round.purged = report.purged.len();
+                        *self.maintained_on.lock().unwrap() = Some(today);
+                    }
+                    Err(e) => warn!("Daemon failed to apply the maintenance policy: {}", e),
+                }
+            }
+        }
+
+        debug!("Daemon round done: {:?}", round);
+        round
+    }
+
+    /// A round every `interval` until `shutdown` completes; a round under
+    /// way when it does is finished first
+    pub async fn run(&self, interval: Duration, shutdown: impl Future<Output = ()>) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = interval.tick() => {
+                    self.round().await;
+                }
+            }
+        }
+    }
+}
+
+/// Completes on Ctrl-C, and on SIGTERM from `daemon stop` or a service manager
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(e) => {
+                warn!("Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pid_file_lifecycle() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(running_pid(dir.path()).unwrap(), None);
+
+        let pid_file = PidFile::acquire(dir.path()).unwrap();
+        assert_eq!(running_pid(dir.path()).unwrap(), Some(std::process::id()));
+        assert!(matches!(PidFile::acquire(dir.path()), Err(DaemonError::AlreadyRunning { .. })));
+
+        drop(pid_file);
+        assert!(!dir.path().join(PID_FILE).exists());
+        assert!(matches!(stop_daemon(dir.path()), Err(DaemonError::NotRunning)));
+    }
+
+    #[test]
+    fn test_stale_pid_file_is_taken_over() {
+        let dir = TempDir::new().unwrap();
+        // No process has a PID this high
+        fs::write(dir.path().join(PID_FILE), "4294967294").unwrap();
+
+        assert_eq!(running_pid(dir.path()).unwrap(), None);
+        assert!(PidFile::acquire(dir.path()).is_ok());
+    }
+}
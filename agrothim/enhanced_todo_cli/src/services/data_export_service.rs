@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio_stream::StreamExt;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{
+        ApiKeyRepository, ApiKeyRepositoryError, SessionRepository, SessionRepositoryError, TimeEntryRepository,
+        TimeEntryRepositoryError, UserIdentityRepository, UserIdentityRepositoryError,
+    },
+    export::{archive::Archive, TaskRecord},
+    models::{
+        api_key::ApiKey,
+        device_session::DeviceSession,
+        time_entry::TimeEntry,
+        user::{UserResponse, UsernameChange},
+        user_identity::UserIdentity,
+        webhook::Webhook,
+    },
+    services::{TaskService, TaskServiceError, UserService, UserServiceError, WebhookService, WebhookServiceError},
+};
+
+/// Current data export schema version
+pub const DATA_EXPORT_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum DataExportError {
+    #[error(transparent)]
+    User(#[from] UserServiceError),
+
+    #[error(transparent)]
+    Task(#[from] TaskServiceError),
+
+    #[error(transparent)]
+    Webhook(#[from] WebhookServiceError),
+
+    #[error(transparent)]
+    TimeEntry(#[from] TimeEntryRepositoryError),
+
+    #[error(transparent)]
+    Session(#[from] SessionRepositoryError),
+
+    #[error(transparent)]
+    ApiKey(#[from] ApiKeyRepositoryError),
+
+    #[error(transparent)]
+    Identity(#[from] UserIdentityRepositoryError),
+}
+
+/// Logins, credentials and renames of the account. Secrets (password hash,
+/// API key hashes, webhook signing secrets) are never included.
+#[derive(Debug, Serialize)]
+pub struct AuthHistory {
+    pub username_changes: Vec<UsernameChange>,
+    pub sessions: Vec<DeviceSession>,
+    pub api_keys: Vec<ApiKey>,
+    pub linked_accounts: Vec<UserIdentity>,
+    pub webhooks: Vec<Webhook>,
+}
+
+/// Everything stored about one user, for data portability requests
+#[derive(Debug, Serialize)]
+pub struct DataExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub profile: UserResponse,
+    /// Tasks with the custom statuses and transitions they use, importable with `import --archive`
+    pub tasks: Archive,
+    pub time_entries: Vec<TimeEntry>,
+    pub auth: AuthHistory,
+}
+
+impl DataExport {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+pub struct DataExportService {
+    user_service: Arc<UserService>,
+    task_service: Arc<TaskService>,
+    webhook_service: Arc<WebhookService>,
+    time_entry_repository: Arc<dyn TimeEntryRepository>,
+    session_repository: Arc<dyn SessionRepository>,
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    identity_repository: Arc<dyn UserIdentityRepository>,
+}
+
+impl DataExportService {
+    pub fn new(
+        user_service: Arc<UserService>,
+        task_service: Arc<TaskService>,
+        webhook_service: Arc<WebhookService>,
+        time_entry_repository: Arc<dyn TimeEntryRepository>,
+        session_repository: Arc<dyn SessionRepository>,
+        api_key_repository: Arc<dyn ApiKeyRepository>,
+        identity_repository: Arc<dyn UserIdentityRepository>,
+    ) -> Self {
+        Self {
+            user_service,
+            task_service,
+            webhook_service,
+            time_entry_repository,
+            session_repository,
+            api_key_repository,
+            identity_repository,
+        }
+    }
+
+    /// Collect all data of the user into one document
+    pub async fn export(&self, user_id: &Uuid) -> Result<DataExport, DataExportError> {
+        let profile = self.user_service.get_profile(user_id).await?;
+
+        // Convert tasks as they arrive instead of holding them and their records
+        let mut records = Vec::new();
+        let mut tasks = std::pin::pin!(self.task_service.stream_tasks(user_id));
+        while let Some(task) = tasks.next().await {
+            records.push(TaskRecord::from(&task?));
+        }
+        let workflow = self.task_service.get_workflow(user_id).await?;
+
+        let auth = AuthHistory {
+            username_changes: self.user_service.username_changes(user_id).await?,
+            sessions: self.session_repository.find_by_user_id(user_id).await?,
+            api_keys: self.api_key_repository.find_by_user_id(user_id).await?,
+            linked_accounts: self.identity_repository.find_by_user_id(user_id).await?,
+            webhooks: self.webhook_service.list_webhooks(user_id).await?,
+        };
+
+        let export = DataExport {
+            version: DATA_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            profile,
+            tasks: Archive::new(records, &workflow),
+            time_entries: self.time_entry_repository.find_by_user_id(user_id).await?,
+            auth,
+        };
+
+        info!("Exported account data for user {}", user_id);
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::status::StatusWorkflow;
+
+    #[test]
+    fn test_export_json_leaves_out_secrets() {
+        let user_id = Uuid::new_v4();
+        let export = DataExport {
+            version: DATA_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            profile: UserResponse {
+                id: user_id,
+                username: "testuser".to_string(),
+                email: "test@example.com".to_string(),
+                is_active: true,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            tasks: Archive::new(Vec::new(), &StatusWorkflow::default()),
+            time_entries: Vec::new(),
+            auth: AuthHistory {
+                username_changes: Vec::new(),
+                sessions: Vec::new(),
+                api_keys: vec![ApiKey {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    name: "ci".to_string(),
+                    prefix: "todo_abc".to_string(),
+                    key_hash: "key-hash".to_string(),
+                    scopes: vec!["tasks:read".to_string()],
+                    expires_at: None,
+                    last_used_at: None,
+                    revoked_at: None,
+                    created_at: Utc::now(),
+                }],
+                linked_accounts: Vec::new(),
+                webhooks: vec![Webhook {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    url: "https://example.com/hook".to_string(),
+                    secret: "signing-secret".to_string(),
+                    events: Vec::new(),
+                    created_at: Utc::now(),
+                }],
+            },
+        };
+
+        let json = export.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], DATA_EXPORT_VERSION);
+        assert_eq!(value["profile"]["username"], "testuser");
+        assert_eq!(value["auth"]["api_keys"][0]["prefix"], "todo_abc");
+        assert!(!json.contains("key-hash"));
+        assert!(!json.contains("signing-secret"));
+
+        // The task section is a regular archive
+        assert!(Archive::parse(&value["tasks"].to_string()).is_ok());
+    }
+}
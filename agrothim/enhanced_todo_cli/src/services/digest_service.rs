@@ -0,0 +1,242 @@
+use chrono::{NaiveDateTime, NaiveTime};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{DigestSubscriptionRepository, DigestSubscriptionRepositoryError},
+    models::{
+        calendar,
+        digest::{DailyDigest, DigestSubscription},
+        user::UserResponse,
+    },
+    services::{EmailMessage, Mailer, MailerError, TaskService, TaskServiceError, UserService, UserServiceError},
+};
+
+#[derive(Error, Debug)]
+pub enum DigestError {
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] DigestSubscriptionRepositoryError),
+
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+
+    #[error("User error: {0}")]
+    User(#[from] UserServiceError),
+
+    #[error("Failed to send email: {0}")]
+    MailerError(#[from] MailerError),
+}
+
+/// What one `digest run` did
+#[derive(Debug, Default)]
+pub struct DigestRun {
+    pub sent: usize,
+    /// Digests with nothing to report, and those of suspended accounts
+    pub skipped: usize,
+    /// Digests that could not be built or sent; the next run retries them
+    pub failed: Vec<(Uuid, DigestError)>,
+}
+
+/// Emails subscribed users a morning digest of what is due today, what is
+/// overdue and what they completed yesterday
+pub struct DigestService {
+    task_service: Arc<TaskService>,
+    user_service: Arc<UserService>,
+    subscriptions: Arc<dyn DigestSubscriptionRepository>,
+    mailer: Arc<dyn Mailer>,
+}
+
+impl DigestService {
+    pub fn new(
+        task_service: Arc<TaskService>,
+        user_service: Arc<UserService>,
+        subscriptions: Arc<dyn DigestSubscriptionRepository>,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        Self {
+            task_service,
+            user_service,
+            subscriptions,
+            mailer,
+        }
+    }
+
+    /// Send the user's digest every day at `send_at`, server-local time
+    pub async fn subscribe(&self, user_id: &Uuid, send_at: NaiveTime) -> Result<DigestSubscription, DigestError> {
+        let subscription = self.subscriptions.upsert(user_id, send_at).await?;
+        info!("User {} subscribed to the daily digest at {}", user_id, send_at);
+        Ok(subscription)
+    }
+
+    /// Stop the user's digest; returns whether they were subscribed
+    pub async fn unsubscribe(&self, user_id: &Uuid) -> Result<bool, DigestError> {
+        let removed = self.subscriptions.delete(user_id).await?;
+        if removed {
+            info!("User {} unsubscribed from the daily digest", user_id);
+        }
+        Ok(removed)
+    }
+
+    pub async fn subscription(&self, user_id: &Uuid) -> Result<Option<DigestSubscription>, DigestError> {
+        Ok(self.subscriptions.find_by_user_id(user_id).await?)
+    }
+
+    /// Email `user` today's digest right away, subscribed or not
+    pub async fn send_now(&self, user: &UserResponse) -> Result<DailyDigest, DigestError> {
+        let digest = self.task_service.get_daily_digest_on(&user.id, calendar::today()).await?;
+        self.mailer.send(&message(user, &digest)).await?;
+        info!("Sent daily digest to user {}", user.username);
+        Ok(digest)
+    }
+
+    /// Send the digests whose time has come on the day of `now` and that
+    /// were not sent that day yet. Meant to run every few minutes; a failed
+    /// digest is retried by the next run.
+    pub async fn run_due(&self, now: NaiveDateTime) -> Result<DigestRun, DigestError> {
+        let date = now.date();
+        let mut run = DigestRun::default();
+
+        for subscription in self.subscriptions.find_due(date, now.time()).await? {
+            let user_id = subscription.user_id;
+            // Another run got here first
+            if !self.subscriptions.claim(&user_id, date).await? {
+                continue;
+            }
+
+            match self.send_due(&subscription, now).await {
+                Ok(true) => run.sent += 1,
+                Ok(false) => run.skipped += 1,
+                Err(e) => {
+                    warn!("Failed to send daily digest to user {}: {}", user_id, e);
+                    self.subscriptions.release(&user_id, subscription.last_sent_on).await?;
+                    run.failed.push((user_id, e));
+                }
+            }
+        }
+
+        if run.sent > 0 || !run.failed.is_empty() {
+            info!("Daily digests: {} sent, {} skipped, {} failed", run.sent, run.skipped, run.failed.len());
+        }
+        Ok(run)
+    }
+
+    /// Send one subscriber's digest; `false` if there was nothing to send
+    async fn send_due(&self, subscription: &DigestSubscription, now: NaiveDateTime) -> Result<bool, DigestError> {
+        let user = self.user_service.get_profile(&subscription.user_id).await?;
+        if !user.is_active {
+            debug!("Skipped daily digest of suspended account {}", user.username);
+            return Ok(false);
+        }
+
+        let digest = self.task_service.get_daily_digest_on(&user.id, now.date()).await?;
+        if !digest.has_email_content() {
+            debug!("Nothing to put in the daily digest of {}", user.username);
+            return Ok(false);
+        }
+
+        self.mailer.send(&message(&user, &digest)).await?;
+        Ok(true)
+    }
+}
+
+fn message(user: &UserResponse, digest: &DailyDigest) -> EmailMessage {
+    EmailMessage {
+        to: user.email.clone(),
+        subject: digest.email_subject(),
+        body: digest.email_body(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{
+        InMemoryDigestSubscriptionRepository, InMemoryTaskRepository, InMemoryUserRepository,
+    };
+    use crate::models::{task::StoreTaskRequest, user::StoreUserRequest};
+    use async_trait::async_trait;
+    use chrono::{Duration, Local};
+    use std::sync::Mutex;
+
+    /// Records messages, failing while `fail` is set
+    #[derive(Default)]
+    struct RecordingMailer {
+        sent: Mutex<Vec<EmailMessage>>,
+        fail: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl Mailer for RecordingMailer {
+        async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+            if *self.fail.lock().unwrap() {
+                return Err(MailerError::InvalidMessage("mail server down".to_string()));
+            }
+            self.sent.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_due_sends_each_digest_once() {
+        let user_service = Arc::new(UserService::new(Arc::new(InMemoryUserRepository::new())));
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let mailer = Arc::new(RecordingMailer::default());
+        let service = DigestService::new(
+            task_service.clone(),
+            user_service.clone(),
+            Arc::new(InMemoryDigestSubscriptionRepository::new()),
+            mailer.clone(),
+        );
+
+        let register = |name: &str| {
+            StoreUserRequest::new(name.to_string(), format!("{}@example.com", name), "kestrel-canyon-42".to_string()).unwrap()
+        };
+        let busy = user_service.register(register("busy")).await.unwrap();
+        let idle = user_service.register(register("idle")).await.unwrap();
+        let late = user_service.register(register("late")).await.unwrap();
+
+        let today = Local::now().date_naive();
+        let request = StoreTaskRequest {
+            title: "Pay rent".to_string(),
+            due_date: Some(calendar::end_of_day(today)),
+            ..Default::default()
+        };
+        task_service.create_task(&busy.id, request).await.unwrap();
+
+        let at = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+        service.subscribe(&busy.id, at(7, 0)).await.unwrap();
+        service.subscribe(&idle.id, at(7, 0)).await.unwrap();
+        service.subscribe(&late.id, at(18, 30)).await.unwrap();
+        let now = today.and_time(at(7, 5));
+
+        // The mail server is down: nothing is lost, the next run retries
+        *mailer.fail.lock().unwrap() = true;
+        let run = service.run_due(now).await.unwrap();
+        assert_eq!(run.failed.len(), 1);
+        assert_eq!(run.skipped, 1);
+
+        *mailer.fail.lock().unwrap() = false;
+        let run = service.run_due(now).await.unwrap();
+        assert_eq!((run.sent, run.skipped), (1, 0));
+        let sent = mailer.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "busy@example.com");
+        assert!(sent[0].body.contains("  - Pay rent"));
+
+        // Once a day
+        let run = service.run_due(now + Duration::minutes(10)).await.unwrap();
+        assert_eq!((run.sent, run.skipped), (0, 0));
+
+        // Tomorrow the rent is overdue
+        let run = service.run_due(now + Duration::days(1)).await.unwrap();
+        assert_eq!(run.sent, 1);
+        assert!(mailer.sent.lock().unwrap()[1].body.contains("Overdue (1)"));
+
+        assert!(service.unsubscribe(&busy.id).await.unwrap());
+        assert!(service.subscription(&busy.id).await.unwrap().is_none());
+        let run = service.run_due(now + Duration::days(2)).await.unwrap();
+        assert_eq!(run.sent, 0);
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    database::repositories::{
+        EmailVerificationRepository, EmailVerificationRepositoryError, UserRepository, UserRepositoryError,
+    },
+    models::user::{User, UserResponse},
+    services::{EmailMessage, Mailer, MailerError},
+    utils::tokens::{generate_token, hash_token},
+};
+
+/// How long an emailed verification token can be redeemed
+const VERIFICATION_TOKEN_LIFETIME_HOURS: i64 = 24;
+
+#[derive(Error, Debug)]
+pub enum EmailVerificationError {
+    #[error("Invalid or expired verification token")]
+    InvalidToken,
+
+    #[error("User repository error: {0}")]
+    UserRepositoryError(#[from] UserRepositoryError),
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] EmailVerificationRepositoryError),
+
+    #[error("Failed to send email: {0}")]
+    MailerError(#[from] MailerError),
+}
+
+/// Confirms that new accounts own their email address before they can log in
+pub struct EmailVerificationService {
+    user_repository: Arc<dyn UserRepository>,
+    repository: Arc<dyn EmailVerificationRepository>,
+    mailer: Arc<dyn Mailer>,
+}
+
+impl EmailVerificationService {
+    pub fn new(
+        user_repository: Arc<dyn UserRepository>,
+        repository: Arc<dyn EmailVerificationRepository>,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        Self {
+            user_repository,
+            repository,
+            mailer,
+        }
+    }
+
+    /// Email a new verification token to `user`, replacing any earlier one
+    pub async fn send_verification(&self, user: &User) -> Result<(), EmailVerificationError> {
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::hours(VERIFICATION_TOKEN_LIFETIME_HOURS);
+        self.repository
+            .store(&user.id, &hash_token(&token), expires_at)
+            .await?;
+
+        let message = EmailMessage {
+            to: user.email.clone(),
+            subject: "Verify your todo-cli email address".to_string(),
+            body: format!(
+                "Welcome to todo-cli, {}!\n\n\
+                 To verify your email address and activate your account, run:\n\n    todo-cli auth verify {}\n\n\
+                 The token expires in {} hours. Run `todo-cli auth resend-verification {}` for a new one.",
+                user.username, token, VERIFICATION_TOKEN_LIFETIME_HOURS, user.email
+            ),
+        };
+        self.mailer.send(&message).await?;
+
+        info!("Verification email sent to user {}", user.username);
+        Ok(())
+    }
+
+    /// Send a fresh token to the unverified account registered with `email`.
+    /// Unknown and already verified addresses are ignored without an error,
+    /// so the command does not reveal which addresses have accounts.
+    pub async fn resend(&self, email: &str) -> Result<(), EmailVerificationError> {
+        match self.user_repository.find_by_email(email.trim()).await? {
+            Some(user) if !user.is_email_verified() => self.send_verification(&user).await,
+            _ => {
+                info!("Verification resend requested for unknown or verified email address");
+                Ok(())
+            }
+        }
+    }
+
+    /// Mark the account the token was sent to as verified
+    pub async fn verify(&self, token: &str) -> Result<UserResponse, EmailVerificationError> {
+        let user_id = self
+            .repository
+            .consume(&hash_token(token))
+            .await?
+            .ok_or_else(|| {
+                warn!("Email verification attempted with an invalid token");
+                EmailVerificationError::InvalidToken
+            })?;
+
+        let user = self.user_repository.mark_email_verified(&user_id).await?;
+        info!("Email verified for user {}", user.username);
+        Ok(user.to_response())
+    }
+}
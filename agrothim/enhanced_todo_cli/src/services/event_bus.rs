@@ -0,0 +1,36 @@
+use tokio::sync::broadcast;
+
+use crate::models::task_change::TaskChange;
+
+/// Changes buffered per subscriber before the slowest one starts missing some
+const CHANNEL_CAPACITY: usize = 256;
+
+/// In-process broadcast of task changes to the live API streams. Every
+/// subscriber receives every change and keeps those addressed to its user.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<TaskChange>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskChange> {
+        self.sender.subscribe()
+    }
+
+    /// Whether anyone is listening; publishers skip building changes otherwise
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn publish(&self, change: TaskChange) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(change);
+    }
+}
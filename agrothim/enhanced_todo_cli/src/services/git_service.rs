@@ -0,0 +1,328 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{CommitLinkRepository, CommitLinkRepositoryError},
+    models::{
+        commit_link::CommitLink,
+        task::{Task, TaskStatus},
+        task_ref::TaskRef,
+    },
+    services::{TaskService, TaskServiceError},
+};
+
+/// Words that, followed by `TODO-<id>`, close the task
+const CLOSING_KEYWORDS: [&str; 9] = ["close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved"];
+
+/// Prefix of a task reference in a commit message, matched case-insensitively
+const REF_PREFIX: &str = "todo-";
+
+/// The hook `git hook` installs; it runs after the commit is made, as only
+/// then does the commit have a hash to record
+pub const HOOK_NAME: &str = "post-commit";
+
+/// First comment line of the hook, telling ours apart from the user's own
+const HOOK_MARKER: &str = "# Installed by `todo-cli git hook`";
+
+/// Separators `git log` is asked to put between and within commits
+const RECORD_SEPARATOR: char = '\u{1e}';
+const FIELD_SEPARATOR: char = '\u{1f}';
+
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error("git {command} failed: {message}")]
+    CommandFailed { command: String, message: String },
+
+    #[error("{} already exists and was not installed by todo-cli", path.display())]
+    HookExists { path: PathBuf },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+
+    #[error("Commit link error: {0}")]
+    Link(#[from] CommitLinkRepositoryError),
+}
+
+/// A commit read from `git log`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub hash: String,
+    pub message: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// What one `git scan` did
+#[derive(Debug, Default)]
+pub struct GitScanSummary {
+    /// Commits read from the log
+    pub scanned: usize,
+    /// Tasks completed, with the short hashes of the commits that closed them
+    pub completed: Vec<(String, Task)>,
+    /// Already completed tasks a commit was recorded for
+    pub linked: Vec<(String, Task)>,
+    /// References that could not be applied, with their commits
+    pub rejected: Vec<(String, TaskRef, TaskServiceError)>,
+}
+
+/// Completes the tasks that commit messages close, e.g. with
+/// `Fix login redirect, closes TODO-3f2a9c1e`, and keeps which commit closed
+/// which task in `task_commits`. Commits already recorded are skipped, so a
+/// range can be scanned again.
+pub struct GitService {
+    task_service: Arc<TaskService>,
+    links: Arc<dyn CommitLinkRepository>,
+}
+
+impl GitService {
+    pub fn new(task_service: Arc<TaskService>, links: Arc<dyn CommitLinkRepository>) -> Self {
+        Self { task_service, links }
+    }
+
+    /// Apply the last `limit` commits of `revision` in the repository at `repo`
+    pub async fn scan(&self, user_id: &Uuid, repo: &Path, revision: &str, limit: usize) -> Result<GitScanSummary, GitError> {
+        let limit = limit.to_string();
+        let format = format!("--format=%H{}%B{}", FIELD_SEPARATOR, RECORD_SEPARATOR);
+        let output = git(repo, &["log", "-n", &limit, &format, "--end-of-options", revision]).await?;
+        let commits = parse_log(&output);
+        debug!("Read {} commits of {} from {}", commits.len(), revision, repo.display());
+        self.apply(user_id, &commits).await
+    }
+
+    /// Complete the tasks `commits` close, oldest commit first
+    pub async fn apply(&self, user_id: &Uuid, commits: &[Commit]) -> Result<GitScanSummary, GitError> {
+        let mut summary = GitScanSummary { scanned: commits.len(), ..Default::default() };
+        let hashes: Vec<String> = commits.iter().map(|commit| commit.hash.clone()).collect();
+        let known = self.links.find_by_commits(user_id, &hashes).await?;
+
+        for commit in commits.iter().rev() {
+            for task_ref in closing_refs(&commit.message) {
+                let task = match self.closed_task(user_id, &task_ref).await {
+                    Ok(task) => task,
+                    Err(e) => {
+                        summary.rejected.push((commit.short_hash().to_string(), task_ref, e));
+                        continue;
+                    }
+                };
+                if known.iter().any(|link| link.task_id == task.id && link.commit_hash == commit.hash) {
+                    continue;
+                }
+
+                let short_hash = commit.short_hash().to_string();
+                if task.status == TaskStatus::Completed {
+                    self.links.create(user_id, &task.id, &commit.hash).await?;
+                    summary.linked.push((short_hash, task));
+                    continue;
+                }
+                match self.task_service.complete_task(user_id, &task.id).await {
+                    Ok(task) => {
+                        self.links.create(user_id, &task.id, &commit.hash).await?;
+                        info!("Completed task {} from commit {}", task.id, commit.hash);
+                        summary.completed.push((short_hash, task));
+                    }
+                    Err(e) => summary.rejected.push((short_hash, task_ref, e)),
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// The commits that completed a task, oldest first
+    pub async fn commits(&self, task_id: &Uuid) -> Result<Vec<CommitLink>, GitError> {
+        Ok(self.links.find_by_task(task_id).await?)
+    }
+
+    async fn closed_task(&self, user_id: &Uuid, task_ref: &TaskRef) -> Result<Task, TaskServiceError> {
+        let task_id = self.task_service.resolve_task_ref(user_id, task_ref).await?;
+        self.task_service.get_task(user_id, &task_id).await
+    }
+}
+
+/// Write the hook running `command` after each commit of the repository at
+/// `repo`, replacing one installed earlier. Someone else's hook is only
+/// replaced with `force`.
+pub async fn install_hook(repo: &Path, command: &str, force: bool) -> Result<PathBuf, GitError> {
+    let hooks = git(repo, &["rev-parse", "--git-path", "hooks"]).await?;
+    let path = repo.join(hooks.trim()).join(HOOK_NAME);
+
+    match tokio::fs::read_to_string(&path).await {
+        Ok(existing) if !force && !existing.contains(HOOK_MARKER) => return Err(GitError::HookExists { path }),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let script = format!("#!/bin/sh\n{}: complete the tasks this commit closes\n{}\n", HOOK_MARKER, command);
+    tokio::fs::write(&path, script).await?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).await?;
+    }
+
+    info!("Installed {}", path.display());
+    Ok(path)
+}
+
+/// `value` quoted for a POSIX shell
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+async fn git(repo: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = tokio::process::Command::new("git").arg("-C").arg(repo).args(args).output().await?;
+    if !output.status.success() {
+        return Err(GitError::CommandFailed {
+            command: args[0].to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The commits in `git log` output, newest first
+fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .split(RECORD_SEPARATOR)
+        .filter_map(|record| record.trim_start().split_once(FIELD_SEPARATOR))
+        .map(|(hash, message)| Commit {
+            hash: hash.to_string(),
+            message: message.trim().to_string(),
+        })
+        .collect()
+}
+
+/// The tasks a commit message closes: each `TODO-<id>` after a closing
+/// keyword such as "closes" or "fixes". Several may follow one keyword, as
+/// in `fixes TODO-12, TODO-14 and TODO-3f2a`.
+pub fn closing_refs(message: &str) -> Vec<TaskRef> {
+    let mut refs = Vec::new();
+    let mut closing = false;
+    for word in message.split_whitespace() {
+        let word = word.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '#');
+        let lower = word.to_ascii_lowercase();
+        if CLOSING_KEYWORDS.contains(&lower.as_str()) {
+            closing = true;
+        } else if let Some(reference) = lower.strip_prefix(REF_PREFIX).filter(|_| closing) {
+            match reference.parse::<TaskRef>() {
+                Ok(task_ref) if !refs.contains(&task_ref) => refs.push(task_ref),
+                Ok(_) => {}
+                Err(_) => closing = false,
+            }
+        } else if lower != "and" {
+            closing = false;
+        }
+    }
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemoryCommitLinkRepository, InMemoryTaskRepository};
+    use crate::models::task::StoreTaskRequest;
+    use crate::models::task_ref::SHORT_ID_LEN;
+
+    #[test]
+    fn test_closing_refs() {
+        assert_eq!(closing_refs("Fix login redirect\n\nCloses TODO-12."), vec![TaskRef::Number(12)]);
+        assert_eq!(
+            closing_refs("fixes TODO-12, todo-3F2A9C1E and TODO-12; see TODO-7"),
+            vec![TaskRef::Number(12), TaskRef::Prefix("3f2a9c1e".to_string())]
+        );
+        assert_eq!(closing_refs("resolved: (TODO-#4)"), vec![TaskRef::Number(4)]);
+        assert!(closing_refs("Mention TODO-12 without closing it").is_empty());
+        assert!(closing_refs("closes the TODO-12").is_empty());
+        assert!(closing_refs("closes TODO-").is_empty());
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = format!("aaa111{f}Fix it\n\ncloses TODO-1\n{r}\nbbb222{f}Initial\n{r}\n", f = FIELD_SEPARATOR, r = RECORD_SEPARATOR);
+        let commits = parse_log(&output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0], Commit { hash: "aaa111".to_string(), message: "Fix it\n\ncloses TODO-1".to_string() });
+        assert_eq!(commits[1].hash, "bbb222");
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/usr/bin/todo-cli"), "'/usr/bin/todo-cli'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[tokio::test]
+    async fn test_apply_completes_tasks_once() {
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let links = Arc::new(InMemoryCommitLinkRepository::new());
+        let service = GitService::new(task_service.clone(), links.clone());
+        let user_id = Uuid::new_v4();
+        let request = |title: &str| StoreTaskRequest { title: title.to_string(), ..Default::default() };
+        let login = task_service.create_task(&user_id, request("Fix login")).await.unwrap();
+        let docs = task_service.create_task(&user_id, request("Write docs")).await.unwrap();
+
+        let short_id = &login.id.to_string()[..SHORT_ID_LEN];
+        let commits = vec![
+            Commit { hash: "c2".repeat(20), message: format!("Docs\n\nFixes TODO-{} and TODO-999", short_id) },
+            Commit { hash: "c1".repeat(20), message: format!("Login\n\ncloses TODO-{}", short_id) },
+        ];
+        let summary = service.apply(&user_id, &commits).await.unwrap();
+        assert_eq!(summary.scanned, 2);
+        assert_eq!(summary.completed.len(), 1);
+        assert_eq!((summary.completed[0].0.as_str(), summary.completed[0].1.id), ("c1c1c1c", login.id));
+        assert_eq!(summary.linked.len(), 1);
+        assert_eq!(summary.linked[0].0, "c2c2c2c");
+        assert_eq!(summary.rejected.len(), 1);
+        assert!(matches!(summary.rejected[0].2, TaskServiceError::TaskNotFound));
+
+        let task = task_service.get_task(&user_id, &login.id).await.unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        let commits_of_login = service.commits(&login.id).await.unwrap();
+        assert_eq!(commits_of_login.len(), 2);
+        assert_eq!(commits_of_login[0].short_hash(), "c1c1c1c");
+        assert!(service.commits(&docs.id).await.unwrap().is_empty());
+
+        // Scanning the same commits again records nothing new
+        let summary = service.apply(&user_id, &commits).await.unwrap();
+        assert!(summary.completed.is_empty() && summary.linked.is_empty());
+        assert_eq!(service.commits(&login.id).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_install_hook_keeps_foreign_hooks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        if git(dir.path(), &["init", "-q"]).await.is_err() {
+            // No git to test with
+            return;
+        }
+
+        let path = install_hook(dir.path(), "todo-cli git scan", false).await.unwrap();
+        assert!(path.ends_with(".git/hooks/post-commit"));
+        let script = std::fs::read_to_string(&path).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n") && script.ends_with("todo-cli git scan\n"));
+        // Reinstalling our own hook is fine
+        install_hook(dir.path(), "todo-cli git scan -n 1", false).await.unwrap();
+
+        std::fs::write(&path, "#!/bin/sh\nmake lint\n").unwrap();
+        assert!(matches!(install_hook(dir.path(), "todo-cli git scan", false).await, Err(GitError::HookExists { .. })));
+        install_hook(dir.path(), "todo-cli git scan", true).await.unwrap();
+
+        let not_a_repo = tempfile::TempDir::new().unwrap();
+        let result = install_hook(&not_a_repo.path().join("missing"), "todo-cli git scan", false).await;
+        assert!(matches!(result, Err(GitError::CommandFailed { .. }) | Err(GitError::Io(_))));
+    }
+}
@@ -0,0 +1,325 @@
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{IssueLinkRepository, IssueLinkRepositoryError},
+    models::{
+        issue_link::RepoName,
+        task::{StoreTaskRequest, Task, TaskStatus},
+    },
+    services::{TaskService, TaskServiceError},
+};
+
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+/// GitHub answers 100 issues per page at most
+const PAGE_SIZE: usize = 100;
+
+const MAX_TITLE_CHARS: usize = 255;
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+#[derive(Error, Debug)]
+pub enum GitHubError {
+    #[error("No GitHub token configured")]
+    TokenNotSet,
+
+    #[error("GitHub answered {status}: {message}")]
+    Api { status: u16, message: String },
+
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+
+    #[error("Issue link error: {0}")]
+    Link(#[from] IssueLinkRepositoryError),
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    number: i32,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+    /// Set on pull requests, which the issues API lists too
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// What one `sync github` did
+#[derive(Debug, Default)]
+pub struct GitHubSyncSummary {
+    /// Tasks created from newly assigned issues, with their issue numbers
+    pub created: Vec<(i32, Task)>,
+    /// Issues closed because their tasks were completed
+    pub closed: Vec<i32>,
+}
+
+/// Two-way sync between a user's tasks and the GitHub issues assigned to
+/// them: open issues become tasks, and completing such a task closes its
+/// issue. Which task belongs to which issue is kept in `task_issue_links`.
+pub struct GitHubService {
+    task_service: Arc<TaskService>,
+    links: Arc<dyn IssueLinkRepository>,
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl GitHubService {
+    pub fn new(task_service: Arc<TaskService>, links: Arc<dyn IssueLinkRepository>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent(concat!("todo-cli/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            task_service,
+            links,
+            client,
+            api_url: DEFAULT_GITHUB_API_URL.to_string(),
+            token: None,
+        }
+    }
+
+    /// Personal access token, from the `github.token` setting
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// API of a GitHub Enterprise server instead of github.com
+    pub fn with_api_url(mut self, api_url: &str) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Create tasks from the open issues of `repo` assigned to the token's
+    /// owner, then close the issues whose tasks were completed since
+    pub async fn sync(&self, user_id: &Uuid, repo: &RepoName) -> Result<GitHubSyncSummary, GitHubError> {
+        let mut summary = GitHubSyncSummary::default();
+        let links = self.links.find_by_repository(user_id, repo.as_str()).await?;
+
+        let login = self.get::<GitHubUser>("/user", &[]).await?.login;
+        for issue in self.assigned_issues(repo, &login).await? {
+            if links.iter().any(|link| link.issue_number == issue.number) {
+                continue;
+            }
+            let task = self.task_service.create_task(user_id, task_request(&issue)).await?;
+            self.links.create(user_id, &task.id, repo.as_str(), issue.number).await?;
+            info!("Created task {} from {}#{}", task.id, repo, issue.number);
+            summary.created.push((issue.number, task));
+        }
+
+        for link in links.iter().filter(|link| link.closed_at.is_none()) {
+            let task = match self.task_service.get_task(user_id, &link.task_id).await {
+                Ok(task) => task,
+                // Deleted tasks leave their issues alone
+                Err(TaskServiceError::TaskNotFound) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if task.status != TaskStatus::Completed {
+                continue;
+            }
+            self.close_issue(repo, link.issue_number).await?;
+            self.links.mark_closed(&link.task_id, Utc::now()).await?;
+            info!("Closed {}#{} for completed task {}", repo, link.issue_number, task.id);
+            summary.closed.push(link.issue_number);
+        }
+
+        Ok(summary)
+    }
+
+    async fn assigned_issues(&self, repo: &RepoName, login: &str) -> Result<Vec<Issue>, GitHubError> {
+        let path = format!("/repos/{}/issues", repo);
+        let per_page = PAGE_SIZE.to_string();
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let page = page.to_string();
+            let batch: Vec<Issue> = self
+                .get(&path, &[("assignee", login), ("state", "open"), ("per_page", &per_page), ("page", &page)])
+                .await?;
+            let last = batch.len() < PAGE_SIZE;
+            issues.extend(batch.into_iter().filter(|issue| issue.pull_request.is_none()));
+            if last {
+                break;
+            }
+        }
+        debug!("{} open issues of {} assigned to {}", issues.len(), repo, login);
+        Ok(issues)
+    }
+
+    async fn close_issue(&self, repo: &RepoName, number: i32) -> Result<(), GitHubError> {
+        let response = self
+            .request(reqwest::Method::PATCH, &format!("/repos/{}/issues/{}", repo, number))?
+            .json(&serde_json::json!({ "state": "closed", "state_reason": "completed" }))
+            .send()
+            .await?;
+        Self::check(response).await.map(|_| ())
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<T, GitHubError> {
+        let response = self.request(reqwest::Method::GET, path)?.query(query).send().await?;
+        Ok(Self::check(response).await?.json().await?)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> Result<reqwest::RequestBuilder, GitHubError> {
+        let token = self.token.as_deref().ok_or(GitHubError::TokenNotSet)?;
+        Ok(self
+            .client
+            .request(method, format!("{}{}", self.api_url, path))
+            .bearer_auth(token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28"))
+    }
+
+    async fn check(response: reqwest::Response) -> Result<reqwest::Response, GitHubError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let message = match response.json::<ErrorBody>().await {
+            Ok(body) => body.message,
+            Err(_) => status.canonical_reason().unwrap_or("request failed").to_string(),
+        };
+        Err(GitHubError::Api { status: status.as_u16(), message })
+    }
+}
+
+/// The task for an issue: its title, and its link and body as the description
+fn task_request(issue: &Issue) -> StoreTaskRequest {
+    let mut description = issue.html_url.clone();
+    if let Some(body) = issue.body.as_deref().map(str::trim).filter(|body| !body.is_empty()) {
+        description.push_str("\n\n");
+        description.push_str(body);
+    }
+    StoreTaskRequest {
+        title: truncate(&issue.title, MAX_TITLE_CHARS),
+        description: Some(truncate(&description, MAX_DESCRIPTION_CHARS)),
+        ..Default::default()
+    }
+}
+
+/// `text` cut to `max_chars` characters, ending in an ellipsis if it was cut
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemoryIssueLinkRepository, InMemoryTaskRepository};
+    use axum::{
+        extract::{Path, Query, State},
+        routing::{get, patch},
+        Json, Router,
+    };
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    type Closed = Arc<Mutex<Vec<i32>>>;
+
+    /// Just enough of the GitHub API: one repository with an issue, a pull
+    /// request and an issue assigned to someone else
+    async fn serve_github(closed: Closed) -> String {
+        async fn user() -> Json<serde_json::Value> {
+            Json(serde_json::json!({ "login": "octocat" }))
+        }
+        async fn issues(Query(query): Query<HashMap<String, String>>) -> Json<serde_json::Value> {
+            assert_eq!(query.get("state").map(String::as_str), Some("open"));
+            let mut issues = vec![
+                serde_json::json!({ "number": 7, "title": "Fix login", "body": "Steps to reproduce", "html_url": "https://github.com/acme/app/issues/7" }),
+                serde_json::json!({ "number": 8, "title": "Bump deps", "body": null, "html_url": "https://github.com/acme/app/pull/8", "pull_request": {} }),
+            ];
+            if query.get("assignee").map(String::as_str) != Some("octocat") {
+                issues.push(serde_json::json!({ "number": 9, "title": "Not mine", "html_url": "https://github.com/acme/app/issues/9" }));
+            }
+            Json(serde_json::Value::Array(issues))
+        }
+        async fn close(State(closed): State<Closed>, Path((_, _, number)): Path<(String, String, i32)>) -> Json<serde_json::Value> {
+            closed.lock().unwrap().push(number);
+            Json(serde_json::json!({ "number": number, "state": "closed" }))
+        }
+
+        let app = Router::new()
+            .route("/user", get(user))
+            .route("/repos/{owner}/{name}/issues", get(issues))
+            .route("/repos/{owner}/{name}/issues/{number}", patch(close))
+            .with_state(closed);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_sync_creates_tasks_and_closes_completed_issues() {
+        let closed = Closed::default();
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let service = GitHubService::new(task_service.clone(), Arc::new(InMemoryIssueLinkRepository::new()))
+            .with_token(Some("ghp_test".to_string()))
+            .with_api_url(&serve_github(closed.clone()).await);
+        let user_id = Uuid::new_v4();
+        let repo: RepoName = "acme/app".parse().unwrap();
+
+        let summary = service.sync(&user_id, &repo).await.unwrap();
+        assert_eq!(summary.created.len(), 1);
+        let (number, task) = &summary.created[0];
+        assert_eq!(*number, 7);
+        assert_eq!(task.title, "Fix login");
+        assert_eq!(task.description.as_deref(), Some("https://github.com/acme/app/issues/7\n\nSteps to reproduce"));
+        assert!(summary.closed.is_empty());
+
+        // The issue is linked, so it is not created again
+        let summary = service.sync(&user_id, &repo).await.unwrap();
+        assert!(summary.created.is_empty());
+
+        task_service.complete_task(&user_id, &task.id).await.unwrap();
+        let summary = service.sync(&user_id, &repo).await.unwrap();
+        assert_eq!(summary.closed, vec![7]);
+        assert_eq!(*closed.lock().unwrap(), vec![7]);
+
+        // Closed once only
+        assert!(service.sync(&user_id, &repo).await.unwrap().closed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync_needs_a_token() {
+        let service = GitHubService::new(
+            Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new()))),
+            Arc::new(InMemoryIssueLinkRepository::new()),
+        );
+        let result = service.sync(&Uuid::new_v4(), &"acme/app".parse().unwrap()).await;
+        assert!(matches!(result, Err(GitHubError::TokenNotSet)));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("ééééé", 3).chars().count(), 3);
+    }
+}
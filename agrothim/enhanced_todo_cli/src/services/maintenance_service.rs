@@ -0,0 +1,212 @@
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    export::{archive::Archive, FormatError, TaskRecord},
+    models::task::{Task, TaskFilter, TaskStatus},
+    services::{TaskService, TaskServiceError},
+};
+
+const ARCHIVE_DIR: &str = "archive";
+const ARCHIVE_PREFIX: &str = "tasks-";
+
+/// What `maintain` cleans up, from the `maintenance.*` settings; a policy
+/// left unset is off
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaintenancePolicy {
+    /// Completed tasks older than this are moved to an archive file
+    pub archive_after: Option<Duration>,
+    /// Archive files older than this are deleted for good
+    pub purge_after: Option<Duration>,
+}
+
+impl MaintenancePolicy {
+    pub fn is_off(&self) -> bool {
+        self.archive_after.is_none() && self.purge_after.is_none()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MaintenanceError {
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+
+    #[error("Could not write the archive: {0}")]
+    Format(#[from] FormatError),
+
+    #[error("Could not read or write the archive directory: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// What one `maintain` run archived and purged, or would have with `--dry-run`
+#[derive(Debug, Default)]
+pub struct MaintenanceReport {
+    pub archived: Vec<Task>,
+    /// Where the archived tasks were written
+    pub archive_file: Option<PathBuf>,
+    pub purged: Vec<PathBuf>,
+}
+
+impl MaintenanceReport {
+    pub fn is_empty(&self) -> bool {
+        self.archived.is_empty() && self.purged.is_empty()
+    }
+}
+
+/// Applies the archive and purge policy. Archived tasks are written to a file
+/// in `archive/` under the session directory, in the format `import
+/// --archive` restores, and then deleted; purging deletes those files once
+/// they are old enough.
+pub struct MaintenanceService {
+    task_service: Arc<TaskService>,
+    archive_dir: PathBuf,
+    policy: MaintenancePolicy,
+}
+
+impl MaintenanceService {
+    pub fn new(task_service: Arc<TaskService>, session_dir: &Path, policy: MaintenancePolicy) -> Self {
+        Self {
+            task_service,
+            archive_dir: session_dir.join(ARCHIVE_DIR),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> &MaintenancePolicy {
+        &self.policy
+    }
+
+    /// Archive the user's tasks completed longer than `archive_after` before
+    /// `now` and purge the archive files older than `purge_after`; with
+    /// `dry_run` only report what would go
+    pub async fn run(&self, user_id: &Uuid, now: DateTime<Utc>, dry_run: bool) -> Result<MaintenanceReport, MaintenanceError> {
+        let mut report = MaintenanceReport::default();
+
+        if let Some(archive_after) = self.policy.archive_after {
+            let cutoff = now - archive_after;
+            report.archived = self
+                .task_service
+                .get_tasks(user_id, TaskFilter::new().with_status(TaskStatus::Completed).owned_only())
+                .await?
+                .into_iter()
+                .filter(|task| task.completed_at.is_some_and(|completed_at| completed_at < cutoff))
+                .collect();
+
+            if !report.archived.is_empty() && !dry_run {
+                report.archive_file = Some(self.archive(user_id, &report.archived, now).await?);
+            }
+        }
+
+        if let Some(purge_after) = self.policy.purge_after {
+            report.purged = self.purge(now - purge_after, dry_run)?;
+        }
+
+        if !dry_run && !report.is_empty() {
+            info!("Maintenance: {} tasks archived, {} archive files purged", report.archived.len(), report.purged.len());
+        }
+        Ok(report)
+    }
+
+    /// Write `tasks` to a new archive file, then delete them; the file is
+    /// complete before anything is deleted
+    async fn archive(&self, user_id: &Uuid, tasks: &[Task], now: DateTime<Utc>) -> Result<PathBuf, MaintenanceError> {
+        let workflow = self.task_service.get_workflow(user_id).await?;
+        let archive = Archive::new(tasks.iter().map(TaskRecord::from).collect(), &workflow);
+
+        fs::create_dir_all(&self.archive_dir)?;
+        let path = self
+            .archive_dir
+            .join(format!("{}{}.json", ARCHIVE_PREFIX, now.format("%Y%m%d-%H%M%S")));
+        fs::write(&path, archive.to_json()?)?;
+
+        let ids = tasks.iter().map(|task| task.id).collect();
+        self.task_service.bulk_delete_tasks(user_id, ids).await?;
+        Ok(path)
+    }
+
+    /// Archive files last written before `cutoff`, deleted unless `dry_run`
+    fn purge(&self, cutoff: DateTime<Utc>, dry_run: bool) -> Result<Vec<PathBuf>, MaintenanceError> {
+        let entries = match fs::read_dir(&self.archive_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut purged = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with(ARCHIVE_PREFIX) || !name.ends_with(".json") {
+                continue;
+            }
+            let modified: DateTime<Utc> = entry.metadata()?.modified()?.into();
+            if modified < cutoff {
+                if !dry_run {
+                    fs::remove_file(entry.path())?;
+                }
+                purged.push(entry.path());
+            }
+        }
+        purged.sort();
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::InMemoryTaskRepository;
+    use crate::models::task::StoreTaskRequest;
+    use tempfile::TempDir;
+
+    async fn completed_task(task_service: &TaskService, user_id: &Uuid, title: &str) -> Task {
+        let request = StoreTaskRequest { title: title.to_string(), ..Default::default() };
+        let task = task_service.create_task(user_id, request).await.unwrap();
+        task_service.complete_task(user_id, &task.id).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_archive_completed_tasks_then_purge_the_archive() {
+        let dir = TempDir::new().unwrap();
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let user_id = Uuid::new_v4();
+        let done = completed_task(&task_service, &user_id, "Done").await;
+        let open = task_service
+            .create_task(&user_id, StoreTaskRequest { title: "Open".to_string(), ..Default::default() })
+            .await
+            .unwrap();
+
+        let policy = MaintenancePolicy {
+            archive_after: Some(Duration::days(30)),
+            purge_after: Some(Duration::days(90)),
+        };
+        let service = MaintenanceService::new(task_service.clone(), dir.path(), policy);
+
+        // Not old enough yet
+        assert!(service.run(&user_id, Utc::now(), false).await.unwrap().is_empty());
+
+        let later = Utc::now() + Duration::days(31);
+        let preview = service.run(&user_id, later, true).await.unwrap();
+        assert_eq!(preview.archived.len(), 1);
+        assert!(preview.archive_file.is_none());
+        assert!(task_service.get_task(&user_id, &done.id).await.is_ok());
+
+        let report = service.run(&user_id, later, false).await.unwrap();
+        assert_eq!(report.archived[0].id, done.id);
+        let archive = Archive::parse(&fs::read_to_string(report.archive_file.unwrap()).unwrap()).unwrap();
+        assert_eq!(archive.tasks[0].title, "Done");
+        assert!(task_service.get_task(&user_id, &done.id).await.is_err());
+        assert!(task_service.get_task(&user_id, &open.id).await.is_ok());
+
+        // The archive file was written just now
+        assert!(service.run(&user_id, Utc::now() + Duration::days(89), false).await.unwrap().purged.is_empty());
+        let purged = service.run(&user_id, Utc::now() + Duration::days(91), false).await.unwrap().purged;
+        assert_eq!(purged.len(), 1);
+        assert!(!purged[0].exists());
+    }
+}
@@ -0,0 +1,518 @@
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        calendar,
+        task::{StoreTaskRequest, Task, TaskFilter, TaskPriority, TaskStatus, UpdateTaskRequest},
+    },
+    services::{TaskService, TaskServiceError},
+};
+
+/// What the last sync wrote, kept in the synced folder itself
+const STATE_FILE: &str = ".todo-cli.json";
+/// File of the tasks without a client
+const INBOX: &str = "Inbox";
+/// How long completed tasks stay in the files, so they do not grow forever
+const HISTORY_DAYS: i64 = 14;
+
+// Markers of the Obsidian Tasks plugin, so its queries work on the files
+const DUE_MARKER: &str = "📅";
+const DONE_MARKER: &str = "✅";
+const HIGH_MARKERS: [&str; 2] = ["⏫", "🔺"];
+const MEDIUM_MARKER: &str = "🔼";
+const LOW_MARKERS: [&str; 2] = ["🔽", "⏬"];
+
+#[derive(Error, Debug)]
+pub enum MarkdownSyncError {
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+
+    #[error("Could not read or write the Markdown folder: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The sync state in the Markdown folder is damaged: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// What a checkbox line says about its task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Item {
+    title: String,
+    done: bool,
+    due: Option<NaiveDate>,
+    priority: TaskPriority,
+}
+
+/// A checkbox line; lines typed in the notes app have no ID yet
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    id: Option<Uuid>,
+    item: Item,
+    text: String,
+}
+
+/// One project file: its task lines, and the other lines above and below them
+#[derive(Debug, Default)]
+struct Document {
+    header: Vec<String>,
+    lines: Vec<Line>,
+    notes: Vec<String>,
+}
+
+/// A task as the last sync wrote it, to tell the edits made in the files
+/// from the changes made elsewhere since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Mirrored {
+    project: String,
+    item: Item,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    user_id: Option<Uuid>,
+    tasks: BTreeMap<Uuid, Mirrored>,
+}
+
+/// What one `sync markdown` did
+#[derive(Debug, Default)]
+pub struct MarkdownSyncSummary {
+    /// Tasks created from lines added in the files
+    pub created: usize,
+    /// Tasks changed by edits of their lines
+    pub updated: usize,
+    /// Lines whose edits were refused, with their titles. Added lines stay
+    /// in the file to be fixed; edits of existing tasks are undone.
+    pub rejected: Vec<(String, TaskServiceError)>,
+    /// Files created or rewritten
+    pub written: Vec<PathBuf>,
+}
+
+/// Two-way sync between a user's tasks and a folder of Markdown files, one
+/// per client ("project") plus `Inbox.md`, as used by Obsidian and similar
+/// notes apps. Each task is a checkbox line ending in a hidden ID comment.
+///
+/// Edits made in the files since the last sync are applied first, then the
+/// files are rewritten from the tasks. Which values were written is kept in
+/// `.todo-cli.json` in the folder, so a line left alone never undoes a change
+/// made with the CLI in the meantime.
+pub struct MarkdownSyncService {
+    task_service: Arc<TaskService>,
+}
+
+impl MarkdownSyncService {
+    pub fn new(task_service: Arc<TaskService>) -> Self {
+        Self { task_service }
+    }
+
+    pub async fn sync(&self, user_id: &Uuid, dir: &Path) -> Result<MarkdownSyncSummary, MarkdownSyncError> {
+        fs::create_dir_all(dir)?;
+        let mut state = load_state(dir)?;
+        // A folder synced by someone else only gets their tasks replaced
+        if state.user_id != Some(*user_id) {
+            state = SyncState { user_id: Some(*user_id), ..Default::default() };
+        }
+        let documents = read_documents(dir)?;
+        let mut summary = MarkdownSyncSummary::default();
+
+        let tasks: HashMap<Uuid, Task> = self
+            .owned_tasks(user_id)
+            .await?
+            .into_iter()
+            .map(|task| (task.id, task))
+            .collect();
+        // Where each task's line is, so rewriting keeps the order chosen in the file
+        let mut positions: HashMap<Uuid, usize> = HashMap::new();
+        let mut seen = HashSet::new();
+        // Lines that could not become tasks, left in their files to be fixed
+        let mut unsaved: HashMap<&str, Vec<String>> = HashMap::new();
+
+        for (project, document) in &documents {
+            for (position, line) in document.lines.iter().enumerate() {
+                let result = match line.id {
+                    None => self.create(user_id, project, &line.item).await.map(|task| {
+                        summary.created += 1;
+                        task.id
+                    }),
+                    // A copied line edits its task once
+                    Some(id) if !seen.insert(id) => continue,
+                    Some(id) => {
+                        let edit = state.tasks.get(&id).zip(tasks.get(&id)).map(|(mirrored, task)| {
+                            edits(mirrored, project, &line.item, task)
+                        });
+                        match edit {
+                            Some(Some(updates)) => self.task_service.update_task(user_id, &id, updates).await.map(|_| {
+                                summary.updated += 1;
+                                id
+                            }),
+                            _ => Ok(id),
+                        }
+                    }
+                };
+                match result {
+                    Ok(id) => {
+                        positions.insert(id, position);
+                    }
+                    Err(e) => {
+                        warn!("Markdown line \"{}\" in {}.md was refused: {}", line.item.title, project, e);
+                        if line.id.is_none() {
+                            unsaved.entry(project).or_default().push(line.text.clone());
+                        }
+                        summary.rejected.push((line.item.title.clone(), e));
+                    }
+                }
+            }
+        }
+
+        state.tasks.clear();
+        let cutoff = Utc::now() - Duration::days(HISTORY_DAYS);
+        let mut projects: BTreeMap<String, Vec<Task>> = documents.keys().map(|project| (project.clone(), vec![])).collect();
+        for task in self.owned_tasks(user_id).await? {
+            if task.is_completed() && task.completed_at.is_some_and(|at| at < cutoff) {
+                continue;
+            }
+            projects.entry(project_of(&task)).or_default().push(task);
+        }
+
+        for (project, mut tasks) in projects {
+            tasks.sort_by_key(|task| (positions.get(&task.id).copied().unwrap_or(usize::MAX), task.created_at));
+            let document = documents.get(&project);
+            let unsaved = unsaved.get(project.as_str()).map(Vec::as_slice).unwrap_or_default();
+            let content = render(&project, document, &tasks, unsaved);
+            for line in content.lines().filter_map(parse_line) {
+                if let Some(id) = line.id {
+                    state.tasks.insert(id, Mirrored { project: project.clone(), item: line.item });
+                }
+            }
+
+            let path = dir.join(format!("{}.md", project));
+            if fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+                fs::write(&path, content)?;
+                debug!("Wrote {} tasks to {}", tasks.len(), path.display());
+                summary.written.push(path);
+            }
+        }
+
+        save_state(dir, &state)?;
+        info!(
+            "Synced Markdown folder {}: {} created, {} updated, {} files written",
+            dir.display(),
+            summary.created,
+            summary.updated,
+            summary.written.len()
+        );
+        Ok(summary)
+    }
+
+    async fn owned_tasks(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskServiceError> {
+        self.task_service.get_tasks(user_id, TaskFilter::default().owned_only()).await
+    }
+
+    async fn create(&self, user_id: &Uuid, project: &str, item: &Item) -> Result<Task, TaskServiceError> {
+        let request = StoreTaskRequest {
+            title: item.title.clone(),
+            status: if item.done { TaskStatus::Completed } else { TaskStatus::Pending },
+            priority: item.priority,
+            due_date: item.due.map(calendar::end_of_day),
+            client: (project != INBOX).then(|| project.to_string()),
+            ..Default::default()
+        };
+        self.task_service.create_task(user_id, request).await
+    }
+}
+
+/// The update for what changed in a line since it was written, `None` if
+/// nothing did. Removing a due date, or moving a line to the inbox, cannot
+/// clear the task's value and is undone by the rewrite.
+fn edits(mirrored: &Mirrored, project: &str, item: &Item, task: &Task) -> Option<UpdateTaskRequest> {
+    let before = &mirrored.item;
+    let mut updates = UpdateTaskRequest::default();
+    let mut changed = false;
+
+    if item.title != before.title && item.title != task.title {
+        updates.title = Some(item.title.clone());
+        changed = true;
+    }
+    if item.done != before.done && item.done != task.is_completed() {
+        updates.status = Some(if item.done { TaskStatus::Completed } else { TaskStatus::Pending });
+        changed = true;
+    }
+    if item.priority != before.priority && item.priority != task.priority {
+        updates.priority = Some(item.priority);
+        changed = true;
+    }
+    if let Some(due) = item.due.filter(|due| item.due != before.due && Some(*due) != task.due_day()) {
+        updates.due_date = Some(calendar::end_of_day(due));
+        changed = true;
+    }
+    if project != mirrored.project && project != INBOX && project != project_of(task) {
+        updates.client = Some(project.to_string());
+        changed = true;
+    }
+
+    changed.then_some(updates)
+}
+
+/// Name of the file a task belongs in, without `.md`
+fn project_of(task: &Task) -> String {
+    match task.client.as_deref() {
+        Some(client) => client
+            .chars()
+            .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '-' } else { c })
+            .collect(),
+        None => INBOX.to_string(),
+    }
+}
+
+/// Parse `- [ ] Title ⏫ 📅 2026-10-20 <!-- id:... -->`; `None` for any other line
+fn parse_line(line: &str) -> Option<Line> {
+    let rest = line.trim_start();
+    let rest = rest.strip_prefix("- [").or_else(|| rest.strip_prefix("* ["))?;
+    let mut chars = rest.chars();
+    let done = match chars.next()? {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    let mut rest = chars.as_str().strip_prefix(']')?.trim();
+
+    let mut id = None;
+    if let Some(start) = rest.rfind("<!-- id:").filter(|_| rest.ends_with("-->")) {
+        id = rest[start + "<!-- id:".len()..rest.len() - "-->".len()].trim().parse().ok();
+        rest = rest[..start].trim_end();
+    }
+
+    let mut title = Vec::new();
+    let mut due = None;
+    let mut priority = TaskPriority::Medium;
+    let mut words = rest.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        let date = words.peek().and_then(|next| NaiveDate::parse_from_str(next, "%Y-%m-%d").ok());
+        match word {
+            DUE_MARKER if date.is_some() => {
+                due = date;
+                words.next();
+            }
+            // The completion date is the task's own
+            DONE_MARKER if date.is_some() => {
+                words.next();
+            }
+            _ if HIGH_MARKERS.contains(&word) => priority = TaskPriority::High,
+            _ if LOW_MARKERS.contains(&word) => priority = TaskPriority::Low,
+            MEDIUM_MARKER => priority = TaskPriority::Medium,
+            _ => title.push(word),
+        }
+    }
+    if title.is_empty() {
+        return None;
+    }
+
+    Some(Line {
+        id,
+        item: Item { title: title.join(" "), done, due, priority },
+        text: line.to_string(),
+    })
+}
+
+fn format_line(task: &Task) -> String {
+    let mut line = format!("- [{}] {}", if task.is_completed() { 'x' } else { ' ' }, task.title);
+    match task.priority {
+        TaskPriority::High => line.push_str(&format!(" {}", HIGH_MARKERS[0])),
+        TaskPriority::Low => line.push_str(&format!(" {}", LOW_MARKERS[0])),
+        TaskPriority::Medium => {}
+    }
+    if let Some(due) = task.due_day() {
+        line.push_str(&format!(" {} {}", DUE_MARKER, due.format("%Y-%m-%d")));
+    }
+    if let (true, Some(completed_at)) = (task.is_completed(), task.completed_at) {
+        line.push_str(&format!(" {} {}", DONE_MARKER, calendar::local_date(&completed_at).format("%Y-%m-%d")));
+    }
+    line.push_str(&format!(" <!-- id:{} -->", task.id));
+    line
+}
+
+/// Split a file into the lines above its first task, the task lines, and
+/// every other line below the first task
+fn parse_document(content: &str) -> Document {
+    let mut document = Document::default();
+    for line in content.lines() {
+        match parse_line(line) {
+            Some(parsed) => document.lines.push(parsed),
+            None if document.lines.is_empty() => document.header.push(line.to_string()),
+            None => document.notes.push(line.to_string()),
+        }
+    }
+    document
+}
+
+/// The file for a project's tasks, followed by the `unsaved` lines. Its
+/// header and notes are kept, the notes moving below the task list; a file
+/// without a header gets the project as its heading.
+fn render(project: &str, document: Option<&Document>, tasks: &[Task], unsaved: &[String]) -> String {
+    let header = trim_blank(document.map(|document| document.header.as_slice()).unwrap_or_default());
+    let notes = trim_blank(document.map(|document| document.notes.as_slice()).unwrap_or_default());
+
+    let mut lines = match header {
+        [] => vec![format!("# {}", project)],
+        header => header.to_vec(),
+    };
+    let mut items = tasks.iter().map(format_line).chain(unsaved.iter().cloned()).peekable();
+    if items.peek().is_some() {
+        lines.push(String::new());
+        lines.extend(items);
+    }
+    if !notes.is_empty() {
+        lines.push(String::new());
+        lines.extend(notes.iter().cloned());
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+fn trim_blank(lines: &[String]) -> &[String] {
+    let start = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(start, |end| end + 1);
+    &lines[start..end]
+}
+
+/// The project files of the folder, by name without `.md`
+fn read_documents(dir: &Path) -> Result<BTreeMap<String, Document>, MarkdownSyncError> {
+    let mut documents = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(project) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if project.starts_with('.') || path.extension().is_none_or(|extension| extension != "md") || !path.is_file() {
+            continue;
+        }
+        documents.insert(project.to_string(), parse_document(&fs::read_to_string(&path)?));
+    }
+    Ok(documents)
+}
+
+fn load_state(dir: &Path) -> Result<SyncState, MarkdownSyncError> {
+    let path = dir.join(STATE_FILE);
+    if !path.exists() {
+        return Ok(SyncState::default());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn save_state(dir: &Path, state: &SyncState) -> Result<(), MarkdownSyncError> {
+    fs::write(dir.join(STATE_FILE), serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::InMemoryTaskRepository;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_line() {
+        let id = Uuid::new_v4();
+        let line = parse_line(&format!("  - [x] Repot  the fig ⏫ 📅 2026-10-20 ✅ 2026-10-16 <!-- id:{} -->", id)).unwrap();
+        assert_eq!(line.id, Some(id));
+        assert_eq!(line.item.title, "Repot the fig");
+        assert!(line.item.done);
+        assert_eq!(line.item.due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(line.item.priority, TaskPriority::High);
+
+        let line = parse_line("* [ ] Buy soil 🔽").unwrap();
+        assert_eq!((line.id, line.item.done, line.item.priority), (None, false, TaskPriority::Low));
+        assert_eq!(parse_line("- [ ] Due 📅 someday").unwrap().item.title, "Due 📅 someday");
+        assert_eq!(parse_line("- plain bullet"), None);
+        assert_eq!(parse_line("- [ ] "), None);
+        assert_eq!(parse_line("# Garden"), None);
+    }
+
+    #[test]
+    fn test_render_keeps_header_and_notes() {
+        let document = parse_document("# Garden\nThings for the yard\n\n- [ ] Water roses\nremember gloves\n");
+        assert_eq!(document.lines.len(), 1);
+        let content = render("Garden", Some(&document), &[], &[]);
+        assert_eq!(content, "# Garden\nThings for the yard\n\nremember gloves\n");
+        assert_eq!(render("Garden", None, &[], &[]), "# Garden\n");
+    }
+
+    #[tokio::test]
+    async fn test_sync_writes_tasks_and_applies_edits() {
+        let dir = TempDir::new().unwrap();
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let service = MarkdownSyncService::new(task_service.clone());
+        let user_id = Uuid::new_v4();
+
+        let request = |title: &str, client: Option<&str>| StoreTaskRequest {
+            title: title.to_string(),
+            client: client.map(str::to_string),
+            ..Default::default()
+        };
+        let rent = task_service.create_task(&user_id, request("Pay rent", None)).await.unwrap();
+        let fig = task_service.create_task(&user_id, request("Repot the fig", Some("Garden"))).await.unwrap();
+
+        let summary = service.sync(&user_id, dir.path()).await.unwrap();
+        assert_eq!(summary.written.len(), 2);
+        let inbox = fs::read_to_string(dir.path().join("Inbox.md")).unwrap();
+        assert_eq!(inbox, format!("# Inbox\n\n- [ ] Pay rent <!-- id:{} -->\n", rent.id));
+        let garden = fs::read_to_string(dir.path().join("Garden.md")).unwrap();
+        assert!(garden.contains(&format!("- [ ] Repot the fig <!-- id:{} -->", fig.id)));
+
+        // Nothing changed, nothing is written
+        assert!(service.sync(&user_id, dir.path()).await.unwrap().written.is_empty());
+
+        // Tick one off and add one in the notes app, rename the other with the CLI
+        let edited = inbox.replace("- [ ]", "- [x]") + "- [ ] Call the landlord ⏫\n";
+        fs::write(dir.path().join("Inbox.md"), edited).unwrap();
+        let updates = UpdateTaskRequest { title: Some("Repot the big fig".to_string()), ..Default::default() };
+        task_service.update_task(&user_id, &fig.id, updates).await.unwrap();
+
+        let summary = service.sync(&user_id, dir.path()).await.unwrap();
+        assert_eq!((summary.created, summary.updated), (1, 1));
+        assert!(task_service.get_task(&user_id, &rent.id).await.unwrap().is_completed());
+        assert_eq!(task_service.get_task(&user_id, &fig.id).await.unwrap().title, "Repot the big fig");
+        let tasks = task_service.get_tasks(&user_id, TaskFilter::default()).await.unwrap();
+        let call = tasks.iter().find(|task| task.title == "Call the landlord").unwrap();
+        assert_eq!(call.priority, TaskPriority::High);
+
+        let inbox = fs::read_to_string(dir.path().join("Inbox.md")).unwrap();
+        assert!(inbox.contains("- [x] Pay rent ✅ "));
+        assert!(inbox.contains(&format!("- [ ] Call the landlord ⏫ <!-- id:{} -->", call.id)));
+        assert!(fs::read_to_string(dir.path().join("Garden.md")).unwrap().contains("Repot the big fig"));
+
+        // Moving a line to another file moves the task to that client
+        let line = format!("- [ ] Call the landlord ⏫ <!-- id:{} -->\n", call.id);
+        fs::write(dir.path().join("Inbox.md"), inbox.replace(&line, "")).unwrap();
+        let garden = fs::read_to_string(dir.path().join("Garden.md")).unwrap();
+        fs::write(dir.path().join("Garden.md"), garden + &line).unwrap();
+        service.sync(&user_id, dir.path()).await.unwrap();
+        assert_eq!(task_service.get_task(&user_id, &call.id).await.unwrap().client.as_deref(), Some("Garden"));
+    }
+
+    #[tokio::test]
+    async fn test_refused_edits_are_reported() {
+        let dir = TempDir::new().unwrap();
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let service = MarkdownSyncService::new(task_service.clone());
+        let user_id = Uuid::new_v4();
+
+        let line = "- [ ] Back to the past 📅 2001-01-01";
+        fs::write(dir.path().join("Inbox.md"), format!("{}\n", line)).unwrap();
+        let summary = service.sync(&user_id, dir.path()).await.unwrap();
+        assert_eq!(summary.created, 0);
+        assert_eq!(summary.rejected.len(), 1);
+        assert_eq!(summary.rejected[0].0, "Back to the past");
+        // The line stays to be fixed
+        assert_eq!(fs::read_to_string(dir.path().join("Inbox.md")).unwrap(), format!("# Inbox\n\n{}\n", line));
+    }
+}
@@ -1,7 +1,51 @@
 pub mod user_service;
 pub mod task_service;
 pub mod auth_service;
+pub mod time_service;
+pub mod webhook_service;
+pub mod authorization;
+pub mod mailer;
+pub mod password_reset_service;
+pub mod email_verification_service;
+pub mod oauth_service;
+pub mod data_export_service;
+pub mod backup_service;
+pub mod seed_service;
+pub mod workspace_service;
+pub mod event_bus;
+pub mod sync_service;
+pub mod offline_queue;
+pub mod github_service;
+pub mod digest_service;
+pub mod reminder_service;
+pub mod markdown_sync_service;
+pub mod git_service;
+pub mod daemon_service;
+pub mod schedule_service;
+pub mod maintenance_service;
 
 pub use user_service::*;
 pub use task_service::*;
-pub use auth_service::*;
\ No newline at end of file
+pub use auth_service::*;
+pub use time_service::*;
+pub use webhook_service::*;
+pub use authorization::*;
+pub use mailer::*;
+pub use password_reset_service::*;
+pub use email_verification_service::*;
+pub use oauth_service::*;
+pub use data_export_service::*;
+pub use backup_service::*;
+pub use seed_service::*;
+pub use workspace_service::*;
+pub use event_bus::*;
+pub use sync_service::*;
+pub use offline_queue::*;
+pub use github_service::*;
+pub use digest_service::*;
+pub use reminder_service::*;
+pub use markdown_sync_service::*;
+pub use git_service::*;
+pub use daemon_service::*;
+pub use schedule_service::*;
+pub use maintenance_service::*;
@@ -0,0 +1,507 @@
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::{
+    database::repositories::{UserIdentityRepository, UserIdentityRepositoryError},
+    models::{user::UserResponse, user_identity::OAuthProvider},
+    services::{UserService, UserServiceError},
+};
+
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Extra wait the provider asks for with `slow_down`, per RFC 8628
+const SLOW_DOWN_SECONDS: u64 = 5;
+
+#[derive(Error, Debug)]
+pub enum OAuthError {
+    #[error("{provider} login is not configured: set {variable}")]
+    NotConfigured { provider: OAuthProvider, variable: &'static str },
+
+    #[error("Authorization was denied")]
+    AccessDenied,
+
+    #[error("The login code expired before it was entered")]
+    Expired,
+
+    #[error("{provider} returned an error: {message}")]
+    ProviderError { provider: OAuthProvider, message: String },
+
+    #[error("{provider} account has no verified email address")]
+    NoVerifiedEmail { provider: OAuthProvider },
+
+    #[error("An account with the email address of this {provider} account exists, but has not verified it")]
+    UnverifiedAccount { provider: OAuthProvider },
+
+    #[error("This {provider} account is already linked to another user")]
+    AlreadyLinked { provider: OAuthProvider },
+
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("User service error: {0}")]
+    UserServiceError(#[from] UserServiceError),
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] UserIdentityRepositoryError),
+}
+
+/// Endpoints and credentials of a provider's device flow
+struct ProviderConfig {
+    client_id: String,
+    client_secret: Option<String>,
+    device_code_url: &'static str,
+    token_url: &'static str,
+    scope: &'static str,
+}
+
+impl ProviderConfig {
+    /// Client credentials come from `GITHUB_CLIENT_ID`, or `GOOGLE_CLIENT_ID`
+    /// and `GOOGLE_CLIENT_SECRET` (Google requires the secret even for
+    /// device clients, where it is not actually secret)
+    fn from_env(provider: OAuthProvider) -> Result<Self, OAuthError> {
+        let var = |variable: &'static str| {
+            std::env::var(variable)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or(OAuthError::NotConfigured { provider, variable })
+        };
+
+        match provider {
+            OAuthProvider::GitHub => Ok(Self {
+                client_id: var("GITHUB_CLIENT_ID")?,
+                client_secret: None,
+                device_code_url: "https://github.com/login/device/code",
+                token_url: "https://github.com/login/oauth/access_token",
+                scope: "read:user user:email",
+            }),
+            OAuthProvider::Google => Ok(Self {
+                client_id: var("GOOGLE_CLIENT_ID")?,
+                client_secret: Some(var("GOOGLE_CLIENT_SECRET")?),
+                device_code_url: "https://oauth2.googleapis.com/device/code",
+                token_url: "https://oauth2.googleapis.com/token",
+                scope: "openid email profile",
+            }),
+        }
+    }
+}
+
+/// A pending device login: the user enters `user_code` at `verification_uri`
+#[derive(Debug, Clone)]
+pub struct DeviceAuthorization {
+    pub provider: OAuthProvider,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    device_code: String,
+    interval: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// Token endpoint response; errors come back as `error` with HTTP 400 (Google)
+/// or 200 (GitHub)
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum PollOutcome {
+    Token(String),
+    Pending,
+    SlowDown,
+    Failed(String),
+}
+
+impl TokenResponse {
+    fn outcome(self) -> PollOutcome {
+        if let Some(token) = self.access_token {
+            return PollOutcome::Token(token);
+        }
+        match self.error.as_deref() {
+            Some("authorization_pending") => PollOutcome::Pending,
+            Some("slow_down") => PollOutcome::SlowDown,
+            Some(error) => PollOutcome::Failed(error.to_string()),
+            None => PollOutcome::Failed(self.error_description.unwrap_or_else(|| "no access token".to_string())),
+        }
+    }
+}
+
+/// The provider account that completed a device login
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalIdentity {
+    pub provider: OAuthProvider,
+    /// Stable account ID at the provider
+    pub subject: String,
+    pub email: String,
+    /// Login or display name, used to pick a username for new accounts
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleUserInfo {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+}
+
+/// Logs users in through an OAuth 2.0 device authorization grant (RFC 8628)
+/// and links the provider account to a local user
+pub struct OAuthService {
+    user_service: Arc<UserService>,
+    identities: Arc<dyn UserIdentityRepository>,
+    client: reqwest::Client,
+}
+
+impl OAuthService {
+    pub fn new(user_service: Arc<UserService>, identities: Arc<dyn UserIdentityRepository>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .user_agent(concat!("todo-cli/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            user_service,
+            identities,
+            client,
+        }
+    }
+
+    /// Ask the provider for a code the user enters in their browser
+    pub async fn start(&self, provider: OAuthProvider) -> Result<DeviceAuthorization, OAuthError> {
+        let config = ProviderConfig::from_env(provider)?;
+
+        let response = self
+            .client
+            .post(config.device_code_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[("client_id", config.client_id.as_str()), ("scope", config.scope)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(OAuthError::ProviderError { provider, message });
+        }
+
+        let response: DeviceCodeResponse = response.json().await?;
+        let authorization = DeviceAuthorization {
+            provider,
+            user_code: response.user_code,
+            verification_uri: response.verification_uri,
+            expires_in: response.expires_in,
+            device_code: response.device_code,
+            interval: response.interval,
+        };
+        debug!("Started {} device login, code expires in {}s", provider, authorization.expires_in);
+        Ok(authorization)
+    }
+
+    /// Poll until the user approves or denies the login, or the code expires,
+    /// then link the provider account to a local user
+    pub async fn complete(&self, authorization: &DeviceAuthorization) -> Result<UserResponse, OAuthError> {
+        let access_token = self.wait_for_token(authorization).await?;
+        let identity = self.fetch_identity(authorization.provider, &access_token).await?;
+        self.link(&identity).await
+    }
+
+    /// Like `complete`, but link the provider account to `user`, who is
+    /// logged in already, whatever email address either of them has
+    pub async fn complete_for(&self, authorization: &DeviceAuthorization, user: &UserResponse) -> Result<(), OAuthError> {
+        let access_token = self.wait_for_token(authorization).await?;
+        let identity = self.fetch_identity(authorization.provider, &access_token).await?;
+
+        if let Some(linked) = self.identities.find(identity.provider, &identity.subject).await? {
+            if linked.user_id != user.id {
+                return Err(OAuthError::AlreadyLinked { provider: identity.provider });
+            }
+        }
+
+        self.identities
+            .link(&user.id, identity.provider, &identity.subject, Some(&identity.email))
+            .await?;
+        info!("Linked {} account to user {}", identity.provider, user.username);
+        Ok(())
+    }
+
+    async fn wait_for_token(&self, authorization: &DeviceAuthorization) -> Result<String, OAuthError> {
+        let provider = authorization.provider;
+        let config = ProviderConfig::from_env(provider)?;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(authorization.expires_in);
+        let mut interval = authorization.interval.max(1);
+
+        let mut form = vec![
+            ("client_id", config.client_id.as_str()),
+            ("device_code", authorization.device_code.as_str()),
+            ("grant_type", DEVICE_GRANT_TYPE),
+        ];
+        if let Some(secret) = &config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if tokio::time::Instant::now() >= deadline {
+                return Err(OAuthError::Expired);
+            }
+
+            let response: TokenResponse = self
+                .client
+                .post(config.token_url)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .form(&form)
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            match response.outcome() {
+                PollOutcome::Token(token) => return Ok(token),
+                PollOutcome::Pending => {}
+                PollOutcome::SlowDown => interval += SLOW_DOWN_SECONDS,
+                PollOutcome::Failed(error) => {
+                    return Err(match error.as_str() {
+                        "access_denied" => OAuthError::AccessDenied,
+                        "expired_token" => OAuthError::Expired,
+                        _ => OAuthError::ProviderError { provider, message: error },
+                    })
+                }
+            }
+        }
+    }
+
+    async fn fetch_identity(&self, provider: OAuthProvider, access_token: &str) -> Result<ExternalIdentity, OAuthError> {
+        match provider {
+            OAuthProvider::GitHub => {
+                let user: GitHubUser = self
+                    .client
+                    .get("https://api.github.com/user")
+                    .bearer_auth(access_token)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+
+                // The profile email may be hidden or unverified; ask for the primary verified one
+                let emails: Vec<GitHubEmail> = self
+                    .client
+                    .get("https://api.github.com/user/emails")
+                    .bearer_auth(access_token)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                let email = emails
+                    .into_iter()
+                    .filter(|email| email.verified)
+                    .max_by_key(|email| email.primary)
+                    .ok_or(OAuthError::NoVerifiedEmail { provider })?
+                    .email;
+
+                Ok(ExternalIdentity {
+                    provider,
+                    subject: user.id.to_string(),
+                    email,
+                    name: user.login,
+                })
+            }
+            OAuthProvider::Google => {
+                let info: GoogleUserInfo = self
+                    .client
+                    .get("https://openidconnect.googleapis.com/v1/userinfo")
+                    .bearer_auth(access_token)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+
+                let email = info
+                    .email
+                    .filter(|_| info.email_verified)
+                    .ok_or(OAuthError::NoVerifiedEmail { provider })?;
+
+                Ok(ExternalIdentity {
+                    provider,
+                    subject: info.sub,
+                    name: info.name.unwrap_or_default(),
+                    email,
+                })
+            }
+        }
+    }
+
+    /// Find the local user for a provider account. An unknown account is
+    /// linked to the user with the same (provider-verified) email address,
+    /// or to a new user if there is none. The address has to be verified on
+    /// our side too: anyone can register an account with someone else's
+    /// address, which would otherwise be handed the provider login. Such
+    /// accounts have to link from a logged-in session with `complete_for`.
+    pub async fn link(&self, identity: &ExternalIdentity) -> Result<UserResponse, OAuthError> {
+        let existing = self.identities.find(identity.provider, &identity.subject).await?;
+
+        let user = match existing {
+            Some(linked) => self.user_service.get_profile(&linked.user_id).await?,
+            None => match self.user_service.find_by_email(&identity.email).await? {
+                Some(user) if !self.user_service.is_email_verified(&user.id).await? => {
+                    warn!("Not linking {} account to user {} with an unverified email", identity.provider, user.username);
+                    return Err(OAuthError::UnverifiedAccount { provider: identity.provider });
+                }
+                Some(user) => {
+                    info!("Linking {} account to existing user {}", identity.provider, user.username);
+                    user
+                }
+                None => {
+                    info!("No user for {} account, creating one", identity.provider);
+                    self.user_service
+                        .register_external(&identity.name, &identity.email)
+                        .await?
+                }
+            },
+        };
+
+        self.identities
+            .link(&user.id, identity.provider, &identity.subject, Some(&identity.email))
+            .await?;
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemoryUserRepository, UserRepository};
+    use crate::models::{user::StoreUserRequest, user_identity::UserIdentity};
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    #[derive(Default)]
+    struct InMemoryIdentities {
+        identities: Mutex<Vec<UserIdentity>>,
+    }
+
+    #[async_trait]
+    impl UserIdentityRepository for InMemoryIdentities {
+        async fn find(&self, provider: OAuthProvider, subject: &str) -> Result<Option<UserIdentity>, UserIdentityRepositoryError> {
+            let identities = self.identities.lock().unwrap();
+            Ok(identities.iter().find(|identity| identity.provider == provider.as_str() && identity.subject == subject).cloned())
+        }
+
+        async fn link(
+            &self,
+            user_id: &Uuid,
+            provider: OAuthProvider,
+            subject: &str,
+            email: Option<&str>,
+        ) -> Result<UserIdentity, UserIdentityRepositoryError> {
+            let identity = UserIdentity {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                provider: provider.as_str().to_string(),
+                subject: subject.to_string(),
+                email: email.map(str::to_string),
+                created_at: Utc::now(),
+                last_login_at: Utc::now(),
+            };
+            self.identities.lock().unwrap().push(identity.clone());
+            Ok(identity)
+        }
+
+        async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<UserIdentity>, UserIdentityRepositoryError> {
+            let identities = self.identities.lock().unwrap();
+            Ok(identities.iter().filter(|identity| identity.user_id == *user_id).cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_link_requires_verified_email_of_existing_user() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let user_service = Arc::new(UserService::new(users.clone()));
+        let service = OAuthService::new(user_service.clone(), Arc::new(InMemoryIdentities::default()));
+        let user = user_service
+            .register(StoreUserRequest::new("alice".to_string(), "alice@example.com".to_string(), "kestrel-canyon-42".to_string()).unwrap())
+            .await
+            .unwrap();
+        let identity = ExternalIdentity {
+            provider: OAuthProvider::GitHub,
+            subject: "1234".to_string(),
+            email: "alice@example.com".to_string(),
+            name: "alice".to_string(),
+        };
+
+        // Anyone could have registered the address; the provider login is not handed over
+        assert!(matches!(service.link(&identity).await, Err(OAuthError::UnverifiedAccount { .. })));
+
+        users.mark_email_verified(&user.id).await.unwrap();
+        assert_eq!(service.link(&identity).await.unwrap().id, user.id);
+    }
+
+    fn token_response(json: &str) -> PollOutcome {
+        serde_json::from_str::<TokenResponse>(json).unwrap().outcome()
+    }
+
+    #[test]
+    fn test_token_response_outcome() {
+        assert_eq!(
+            token_response(r#"{"access_token":"gho_abc","token_type":"bearer","scope":"read:user"}"#),
+            PollOutcome::Token("gho_abc".to_string())
+        );
+        assert_eq!(token_response(r#"{"error":"authorization_pending"}"#), PollOutcome::Pending);
+        assert_eq!(
+            token_response(r#"{"error":"slow_down","error_description":"Too many requests","interval":10}"#),
+            PollOutcome::SlowDown
+        );
+        assert_eq!(
+            token_response(r#"{"error":"access_denied"}"#),
+            PollOutcome::Failed("access_denied".to_string())
+        );
+    }
+
+    #[test]
+    fn test_device_authorization_accepts_google_field_names() {
+        let authorization: DeviceCodeResponse = serde_json::from_str(
+            r#"{"device_code":"dc","user_code":"ABCD-EFGH","verification_url":"https://www.google.com/device","expires_in":1800}"#,
+        )
+        .unwrap();
+        assert_eq!(authorization.verification_uri, "https://www.google.com/device");
+        assert_eq!(authorization.interval, 5);
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    error::is_unreachable,
+    models::task::{StoreTaskRequest, UpdateTaskRequest},
+    services::{TaskService, TaskServiceError},
+};
+
+const JOURNAL_FILE: &str = "journal.jsonl";
+
+#[derive(Error, Debug)]
+pub enum OfflineQueueError {
+    #[error("Could not read or write the offline journal: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The offline journal is damaged: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A task change made while the database or API could not be reached
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum QueuedMutation {
+    Create { request: StoreTaskRequest },
+    Update { task_id: Uuid, request: UpdateTaskRequest },
+    Complete { task_id: Uuid },
+}
+
+/// One line of the journal
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueuedEntry {
+    pub user_id: Uuid,
+    pub queued_at: DateTime<Utc>,
+    pub mutation: QueuedMutation,
+}
+
+/// What one replay did
+#[derive(Debug, Default)]
+pub struct ReplaySummary {
+    pub applied: usize,
+    /// Changes the server refused, e.g. an update of a task deleted since;
+    /// they are dropped from the journal
+    pub rejected: Vec<(QueuedEntry, TaskServiceError)>,
+    /// Changes still queued because the database or API is still unreachable
+    pub remaining: usize,
+}
+
+/// Journal of task changes queued while offline, one JSON object per line in
+/// `journal.jsonl` next to the session. `todo-cli sync` replays it in order.
+pub struct OfflineQueue {
+    path: PathBuf,
+}
+
+impl OfflineQueue {
+    pub fn new(dir: &Path) -> Self {
+        Self { path: dir.join(JOURNAL_FILE) }
+    }
+
+    pub fn push(&self, user_id: &Uuid, mutation: QueuedMutation) -> Result<(), OfflineQueueError> {
+        let entry = QueuedEntry { user_id: *user_id, queued_at: Utc::now(), mutation };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Queued changes can hold anything a task holds
+        let mut options = fs::OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&self.path)?.write_all(line.as_bytes())?;
+
+        info!("Queued offline change for user {}", user_id);
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Result<Vec<QueuedEntry>, OfflineQueueError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    /// Number of changes queued for the user
+    pub fn pending(&self, user_id: &Uuid) -> Result<usize, OfflineQueueError> {
+        Ok(self.entries()?.iter().filter(|entry| entry.user_id == *user_id).count())
+    }
+
+    /// Apply the user's queued changes in the order they were made. The
+    /// first one that still cannot reach the database stops the replay, so
+    /// the rest keep their order for the next one. Other users' changes wait
+    /// for them to log in.
+    pub async fn replay(&self, user_id: &Uuid, task_service: &TaskService) -> Result<ReplaySummary, OfflineQueueError> {
+        let mut summary = ReplaySummary::default();
+        let mut kept = Vec::new();
+        let mut offline = false;
+
+        for entry in self.entries()? {
+            if entry.user_id != *user_id || offline {
+                if entry.user_id == *user_id {
+                    summary.remaining += 1;
+                }
+                kept.push(entry);
+                continue;
+            }
+
+            let result = match &entry.mutation {
+                QueuedMutation::Create { request } => task_service
+                    .create_task(user_id, request.clone())
+                    .await
+                    .map(|_| ()),
+                QueuedMutation::Update { task_id, request } => task_service
+                    .update_task(user_id, task_id, request.clone())
+                    .await
+                    .map(|_| ()),
+                QueuedMutation::Complete { task_id } => task_service.complete_task(user_id, task_id).await.map(|_| ()),
+            };
+            match result {
+                Ok(()) => summary.applied += 1,
+                Err(e) if is_unreachable(&e) => {
+                    offline = true;
+                    summary.remaining += 1;
+                    kept.push(entry);
+                }
+                Err(e) => {
+                    warn!("Dropped queued change from {}: {}", entry.queued_at, e);
+                    summary.rejected.push((entry, e));
+                }
+            }
+        }
+
+        self.save(&kept)?;
+        info!(
+            "Replayed offline journal for user {}: {} applied, {} rejected, {} remaining",
+            user_id,
+            summary.applied,
+            summary.rejected.len(),
+            summary.remaining
+        );
+        Ok(summary)
+    }
+
+    /// Replace the journal with `entries`, removing it once empty
+    fn save(&self, entries: &[QueuedEntry]) -> Result<(), OfflineQueueError> {
+        if entries.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        // Written aside and renamed, so a crash never leaves half a journal
+        let temp = self.path.with_extension("jsonl.tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::InMemoryTaskRepository;
+    use crate::models::task::{TaskFilter, TaskStatus};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_replay_applies_changes_in_order() {
+        let dir = TempDir::new().unwrap();
+        let queue = OfflineQueue::new(dir.path());
+        let task_service = TaskService::new(Arc::new(InMemoryTaskRepository::new()));
+        let user_id = Uuid::new_v4();
+        let other_user_id = Uuid::new_v4();
+
+        let existing = task_service
+            .create_task(&user_id, StoreTaskRequest { title: "Water the tomatoes".to_string(), ..Default::default() })
+            .await
+            .unwrap();
+        let request = StoreTaskRequest { title: "Order seeds".to_string(), ..Default::default() };
+        queue.push(&user_id, QueuedMutation::Create { request }).unwrap();
+        queue.push(&user_id, QueuedMutation::Complete { task_id: existing.id }).unwrap();
+        queue.push(&other_user_id, QueuedMutation::Complete { task_id: existing.id }).unwrap();
+        // Deleted on the server since it was queued
+        queue.push(&user_id, QueuedMutation::Complete { task_id: Uuid::new_v4() }).unwrap();
+        assert_eq!(queue.pending(&user_id).unwrap(), 3);
+
+        let summary = queue.replay(&user_id, &task_service).await.unwrap();
+        assert_eq!(summary.applied, 2);
+        assert_eq!(summary.rejected.len(), 1);
+        assert!(matches!(summary.rejected[0].1, TaskServiceError::TaskNotFound));
+        assert_eq!(summary.remaining, 0);
+
+        let tasks = task_service.get_tasks(&user_id, TaskFilter::default()).await.unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.iter().any(|task| task.title == "Order seeds"));
+        assert_eq!(task_service.get_task(&user_id, &existing.id).await.unwrap().status, TaskStatus::Completed);
+
+        // Only the other user's change is left
+        let entries = queue.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].user_id, other_user_id);
+        assert_eq!(queue.pending(&user_id).unwrap(), 0);
+    }
+}
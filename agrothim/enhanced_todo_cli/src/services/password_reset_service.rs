@@ -0,0 +1,134 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    database::repositories::{PasswordResetRepository, PasswordResetRepositoryError},
+    models::{user::UserResponse, webhook::WebhookEvent},
+    services::{AuthService, AuthServiceError, EmailMessage, Mailer, MailerError, UserService, UserServiceError, WebhookService},
+    utils::tokens::{generate_token, hash_token},
+};
+
+/// How long an emailed reset token can be redeemed
+const RESET_TOKEN_LIFETIME_MINUTES: i64 = 30;
+
+#[derive(Error, Debug)]
+pub enum PasswordResetError {
+    #[error("Invalid or expired reset token")]
+    InvalidToken,
+
+    #[error("User service error: {0}")]
+    UserServiceError(#[from] UserServiceError),
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] PasswordResetRepositoryError),
+
+    #[error("Failed to send email: {0}")]
+    MailerError(#[from] MailerError),
+
+    #[error("Auth service error: {0}")]
+    AuthServiceError(#[from] AuthServiceError),
+}
+
+/// Lets users who forgot their password set a new one with a token sent to
+/// their email address
+pub struct PasswordResetService {
+    user_service: Arc<UserService>,
+    repository: Arc<dyn PasswordResetRepository>,
+    mailer: Arc<dyn Mailer>,
+    webhook_service: Option<Arc<WebhookService>>,
+    auth_service: Option<Arc<AuthService>>,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        user_service: Arc<UserService>,
+        repository: Arc<dyn PasswordResetRepository>,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        Self {
+            user_service,
+            repository,
+            mailer,
+            webhook_service: None,
+            auth_service: None,
+        }
+    }
+
+    /// Notify the user's webhooks when a password is reset
+    pub fn with_webhook_service(mut self, webhook_service: Arc<WebhookService>) -> Self {
+        self.webhook_service = Some(webhook_service);
+        self
+    }
+
+    /// Revoke the user's sessions and tokens once their password is reset
+    pub fn with_session_revocation(mut self, auth_service: Arc<AuthService>) -> Self {
+        self.auth_service = Some(auth_service);
+        self
+    }
+
+    /// Email a reset token to the account registered with `email`. Unknown
+    /// addresses are not reported as errors, so the command cannot be used
+    /// to find out which addresses have accounts.
+    pub async fn request_reset(&self, email: &str) -> Result<(), PasswordResetError> {
+        let Some(user) = self.user_service.find_by_email(email.trim()).await? else {
+            info!("Password reset requested for unknown email address");
+            return Ok(());
+        };
+
+        let token = generate_token();
+        let expires_at = Utc::now() + Duration::minutes(RESET_TOKEN_LIFETIME_MINUTES);
+        self.repository
+            .store(&user.id, &hash_token(&token), expires_at)
+            .await?;
+
+        let message = EmailMessage {
+            to: user.email.clone(),
+            subject: "Reset your todo-cli password".to_string(),
+            body: format!(
+                "Someone asked to reset the password of the todo-cli account '{}'.\n\n\
+                 To choose a new password, run:\n\n    todo-cli auth reset-password {}\n\n\
+                 The token expires in {} minutes and can be used once.\n\
+                 If you did not ask for this, you can ignore this email.",
+                user.username, token, RESET_TOKEN_LIFETIME_MINUTES
+            ),
+        };
+        self.mailer.send(&message).await?;
+
+        info!("Password reset token sent to user {}", user.username);
+        Ok(())
+    }
+
+    /// Set a new password using an emailed token. The token is used up even
+    /// if setting the password fails afterwards, so the new password is
+    /// validated first.
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<UserResponse, PasswordResetError> {
+        UserService::validate_new_password(new_password)?;
+
+        let user_id = self
+            .repository
+            .consume(&hash_token(token))
+            .await?
+            .ok_or_else(|| {
+                warn!("Password reset attempted with an invalid token");
+                PasswordResetError::InvalidToken
+            })?;
+
+        let user = self.user_service.set_password(&user_id, new_password).await?;
+        info!("Password reset for user {}", user.username);
+
+        // Whoever had the old password may hold tokens made with it
+        if let Some(auth_service) = &self.auth_service {
+            auth_service.revoke_all_sessions(&user.id).await?;
+        }
+
+        if let Some(webhook_service) = &self.webhook_service {
+            webhook_service
+                .dispatch(&user, WebhookEvent::PasswordChanged, serde_json::json!({ "via": "reset" }))
+                .await;
+        }
+
+        Ok(user)
+    }
+}
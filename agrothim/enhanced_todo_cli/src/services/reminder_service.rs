@@ -0,0 +1,430 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::{
+    models::{
+        calendar,
+        reminder::{Reminder, ReminderKind},
+        task::TaskFilter,
+        user::UserResponse,
+    },
+    services::{EmailMessage, Mailer, MailerError, TaskService, TaskServiceError},
+};
+
+const STATE_FILE: &str = "reminders.json";
+
+/// Reminder channels and timing, from the `reminders.*` settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReminderConfig {
+    pub desktop: bool,
+    pub email: bool,
+    /// Incoming webhook URL of a Slack channel to post reminders to
+    pub slack: Option<String>,
+    /// How long before its due date a task counts as due soon
+    pub due_soon: Duration,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            desktop: true,
+            email: false,
+            slack: None,
+            due_soon: Duration::hours(24),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReminderError {
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+
+    #[error("Desktop notification failed: {0}")]
+    Desktop(String),
+
+    #[error("Failed to send email: {0}")]
+    MailerError(#[from] MailerError),
+
+    #[error("Slack notification failed: {0}")]
+    Slack(String),
+
+    #[error("Could not read or write the reminder state: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("The reminder state is damaged: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A way of delivering reminders to the user
+#[async_trait]
+pub trait ReminderChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn deliver(&self, user: &UserResponse, reminders: &[Reminder]) -> Result<(), ReminderError>;
+}
+
+/// Native notifications: D-Bus on Linux, Notification Center on macOS and
+/// toasts on Windows. One notification per kind keeps a long list from
+/// flooding the screen.
+pub struct DesktopChannel;
+
+#[async_trait]
+impl ReminderChannel for DesktopChannel {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    #[cfg(feature = "desktop-notifications")]
+    async fn deliver(&self, _user: &UserResponse, reminders: &[Reminder]) -> Result<(), ReminderError> {
+        for (summary, body) in notifications(reminders) {
+            // The platform APIs block, and on Linux run their own executor
+            tokio::task::spawn_blocking(move || {
+                notify_rust::Notification::new()
+                    .appname("todo-cli")
+                    .summary(&summary)
+                    .body(&body)
+                    .show()
+                    .map(|_| ())
+            })
+            .await
+            .map_err(|e| ReminderError::Desktop(e.to_string()))?
+            .map_err(|e| ReminderError::Desktop(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "desktop-notifications"))]
+    async fn deliver(&self, _user: &UserResponse, _reminders: &[Reminder]) -> Result<(), ReminderError> {
+        Err(ReminderError::Desktop(
+            "this build has no desktop notifications; rebuild with the desktop-notifications feature".to_string(),
+        ))
+    }
+}
+
+/// One email listing every reminder, to the account's address
+pub struct EmailChannel {
+    mailer: Arc<dyn Mailer>,
+}
+
+impl EmailChannel {
+    pub fn new(mailer: Arc<dyn Mailer>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl ReminderChannel for EmailChannel {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    async fn deliver(&self, user: &UserResponse, reminders: &[Reminder]) -> Result<(), ReminderError> {
+        let mut body = String::new();
+        for (summary, lines) in notifications(reminders) {
+            body.push_str(&format!("{}\n{}\n\n", summary, lines));
+        }
+        let overdue = reminders.iter().filter(|reminder| reminder.kind == ReminderKind::Overdue).count();
+        let message = EmailMessage {
+            to: user.email.clone(),
+            subject: format!("Reminder: {} due soon, {} overdue", reminders.len() - overdue, overdue),
+            body: body.trim_end().to_string(),
+        };
+        self.mailer.send(&message).await?;
+        Ok(())
+    }
+}
+
+/// One message listing every reminder, posted to a Slack incoming webhook
+pub struct SlackChannel {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl SlackChannel {
+    pub fn new(url: String) -> Self {
+        Self { client: reqwest::Client::new(), url }
+    }
+}
+
+#[async_trait]
+impl ReminderChannel for SlackChannel {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn deliver(&self, _user: &UserResponse, reminders: &[Reminder]) -> Result<(), ReminderError> {
+        let text = notifications(reminders)
+            .into_iter()
+            .map(|(summary, lines)| format!("*{}*\n{}", summary, lines))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let response = self
+            .client
+            .post(&self.url)
+            .timeout(std::time::Duration::from_secs(10))
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await
+            .map_err(|e| ReminderError::Slack(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ReminderError::Slack(format!("the webhook answered {}", response.status())));
+        }
+        Ok(())
+    }
+}
+
+/// Summary and body of one notification per reminder kind present
+fn notifications(reminders: &[Reminder]) -> Vec<(String, String)> {
+    [ReminderKind::Overdue, ReminderKind::DueSoon]
+        .into_iter()
+        .filter_map(|kind| {
+            let group: Vec<&Reminder> = reminders.iter().filter(|reminder| reminder.kind == kind).collect();
+            let summary = match group.as_slice() {
+                [] => return None,
+                [reminder] => format!("{}: {}", capitalize(&kind.to_string()), reminder.title),
+                _ => format!("{} tasks {}", group.len(), kind),
+            };
+            let body = group
+                .iter()
+                .map(|reminder| {
+                    format!("{} (due {})", reminder.title, calendar::local_date(&reminder.due_date).format("%a %b %-d"))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some((summary, body))
+        })
+        .collect()
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// What one reminder check did
+#[derive(Debug, Default)]
+pub struct ReminderRun {
+    /// Reminders not sent before; they are recorded as sent once a channel delivers them
+    pub reminders: Vec<Reminder>,
+    pub delivered: Vec<&'static str>,
+    pub failed: Vec<(&'static str, ReminderError)>,
+}
+
+/// Sends each due-soon and overdue reminder once through the enabled
+/// channels. Which ones went out is kept in `reminders.json` next to the
+/// session, since desktop notifications belong to this machine.
+pub struct ReminderService {
+    task_service: Arc<TaskService>,
+    channels: Vec<Arc<dyn ReminderChannel>>,
+    due_soon: Duration,
+    state_path: PathBuf,
+}
+
+impl ReminderService {
+    pub fn new(task_service: Arc<TaskService>, state_dir: &Path) -> Self {
+        Self {
+            task_service,
+            channels: Vec::new(),
+            due_soon: ReminderConfig::default().due_soon,
+            state_path: state_dir.join(STATE_FILE),
+        }
+    }
+
+    pub fn with_channel(mut self, channel: Arc<dyn ReminderChannel>) -> Self {
+        self.channels.push(channel);
+        self
+    }
+
+    pub fn with_due_soon(mut self, due_soon: Duration) -> Self {
+        self.due_soon = due_soon;
+        self
+    }
+
+    pub fn channel_names(&self) -> Vec<&'static str> {
+        self.channels.iter().map(|channel| channel.name()).collect()
+    }
+
+    /// Reminders for the user's tasks at `now`, sent or not
+    pub async fn pending(&self, user_id: &uuid::Uuid, now: DateTime<Utc>) -> Result<Vec<Reminder>, ReminderError> {
+        let filter = TaskFilter { due_before: Some(now + self.due_soon), ..Default::default() };
+        let tasks = self.task_service.get_tasks(user_id, filter).await?;
+        Ok(Reminder::for_tasks(&tasks, now, self.due_soon))
+    }
+
+    /// Deliver the reminders not sent yet. A reminder counts as sent once any
+    /// channel delivered it; if all fail, the next check tries again.
+    pub async fn check(&self, user: &UserResponse, now: DateTime<Utc>) -> Result<ReminderRun, ReminderError> {
+        let current = self.pending(&user.id, now).await?;
+        let mut sent = self.load_state()?;
+        // Forget reminders that no longer apply, e.g. of completed tasks
+        sent.retain(|key, _| current.iter().any(|reminder| reminder.key() == *key));
+
+        let mut run = ReminderRun {
+            reminders: current.into_iter().filter(|reminder| !sent.contains_key(&reminder.key())).collect(),
+            ..Default::default()
+        };
+        if run.reminders.is_empty() || self.channels.is_empty() {
+            debug!("No reminders to send for user {}", user.username);
+            self.save_state(&sent)?;
+            return Ok(run);
+        }
+
+        for channel in &self.channels {
+            match channel.deliver(user, &run.reminders).await {
+                Ok(()) => run.delivered.push(channel.name()),
+                Err(e) => {
+                    warn!("Failed to deliver reminders by {}: {}", channel.name(), e);
+                    run.failed.push((channel.name(), e));
+                }
+            }
+        }
+        if !run.delivered.is_empty() {
+            for reminder in &run.reminders {
+                sent.insert(reminder.key(), now);
+            }
+            info!(
+                "Sent {} reminders to user {} by {}",
+                run.reminders.len(),
+                user.username,
+                run.delivered.join(", ")
+            );
+        }
+        self.save_state(&sent)?;
+        Ok(run)
+    }
+
+    fn load_state(&self) -> Result<BTreeMap<String, DateTime<Utc>>, ReminderError> {
+        if !self.state_path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(&self.state_path)?)?)
+    }
+
+    fn save_state(&self, sent: &BTreeMap<String, DateTime<Utc>>) -> Result<(), ReminderError> {
+        if sent.is_empty() {
+            if self.state_path.exists() {
+                fs::remove_file(&self.state_path)?;
+            }
+            return Ok(());
+        }
+        if let Some(dir) = self.state_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.state_path, serde_json::to_string_pretty(sent)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::InMemoryTaskRepository;
+    use crate::models::task::StoreTaskRequest;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    /// Records what it was asked to deliver, failing while `fail` is set
+    #[derive(Default)]
+    struct RecordingChannel {
+        delivered: Mutex<Vec<Vec<String>>>,
+        fail: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl ReminderChannel for RecordingChannel {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn deliver(&self, _user: &UserResponse, reminders: &[Reminder]) -> Result<(), ReminderError> {
+            if *self.fail.lock().unwrap() {
+                return Err(ReminderError::Desktop("no notification daemon".to_string()));
+            }
+            let titles = reminders.iter().map(|reminder| reminder.title.clone()).collect();
+            self.delivered.lock().unwrap().push(titles);
+            Ok(())
+        }
+    }
+
+    fn user() -> UserResponse {
+        UserResponse {
+            id: Uuid::new_v4(),
+            username: "tester".to_string(),
+            email: "tester@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_sends_each_reminder_once() {
+        let dir = TempDir::new().unwrap();
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let channel = Arc::new(RecordingChannel::default());
+        let service = ReminderService::new(task_service.clone(), dir.path()).with_channel(channel.clone());
+        let user = user();
+        let now = Utc::now();
+
+        let add = |title: &str, due_date| StoreTaskRequest {
+            title: title.to_string(),
+            due_date: Some(due_date),
+            ..Default::default()
+        };
+        task_service.create_task(&user.id, add("Pay rent", calendar::end_of_day(calendar::today() + Duration::days(1)))).await.unwrap();
+        let call = task_service.create_task(&user.id, add("Call Bob", now + Duration::hours(3))).await.unwrap();
+        task_service.create_task(&user.id, add("Plan trip", now + Duration::days(5))).await.unwrap();
+
+        // Nothing is recorded while no channel delivers
+        let at = now + Duration::days(3);
+        *channel.fail.lock().unwrap() = true;
+        let run = service.check(&user, at).await.unwrap();
+        assert_eq!(run.reminders.len(), 2);
+        assert_eq!(run.failed.len(), 1);
+
+        *channel.fail.lock().unwrap() = false;
+        let run = service.check(&user, at).await.unwrap();
+        assert_eq!(run.delivered, vec!["recording"]);
+        assert_eq!(*channel.delivered.lock().unwrap(), vec![vec!["Call Bob".to_string(), "Pay rent".to_string()]]);
+        assert!(service.check(&user, at).await.unwrap().reminders.is_empty());
+
+        // Only the newly due task is sent; the completed one is forgotten
+        task_service.complete_task(&user.id, &call.id).await.unwrap();
+        let at = now + Duration::days(4) + Duration::hours(1);
+        let run = service.check(&user, at).await.unwrap();
+        let titles: Vec<_> = run.reminders.iter().map(|reminder| reminder.title.as_str()).collect();
+        assert_eq!(titles, vec!["Plan trip"]);
+        assert_eq!(service.pending(&user.id, at).await.unwrap().len(), 2);
+        assert_eq!(service.load_state().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_notifications_group_by_kind() {
+        let due_date = Utc::now();
+        let reminder = |title: &str, kind| Reminder { kind, task_id: Uuid::new_v4(), title: title.to_string(), due_date };
+
+        let single = notifications(&[reminder("Pay rent", ReminderKind::Overdue)]);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].0, "Overdue: Pay rent");
+
+        let grouped = notifications(&[
+            reminder("Pay rent", ReminderKind::Overdue),
+            reminder("File taxes", ReminderKind::Overdue),
+            reminder("Call Bob", ReminderKind::DueSoon),
+        ]);
+        assert_eq!(grouped[0].0, "2 tasks overdue");
+        assert!(grouped[0].1.starts_with("Pay rent (due "));
+        assert_eq!(grouped[0].1.lines().count(), 2);
+        assert_eq!(grouped[1].0, "Due soon: Call Bob");
+    }
+}
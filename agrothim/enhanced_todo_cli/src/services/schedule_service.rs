@@ -0,0 +1,267 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    database::repositories::{ScheduleRepository, ScheduleRepositoryError},
+    models::{
+        schedule::{CronSchedule, Schedule, StoreScheduleRequest},
+        task::{StoreTaskRequest, Task},
+    },
+    services::{TaskService, TaskServiceError},
+};
+
+/// Most upcoming firings a schedule creates tasks for ahead of time
+pub const MAX_AHEAD: i16 = 20;
+
+#[derive(Error, Debug)]
+pub enum ScheduleError {
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(String),
+
+    #[error("No schedule matches '{reference}'")]
+    NotFound { reference: String },
+
+    #[error("More than one schedule starts with '{reference}'")]
+    Ambiguous { reference: String },
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] ScheduleRepositoryError),
+
+    #[error("Task error: {0}")]
+    Task(#[from] TaskServiceError),
+}
+
+/// What one `tick` did
+#[derive(Debug, Default)]
+pub struct ScheduleRun {
+    /// Tasks created by schedules that fired
+    pub created: Vec<Task>,
+    /// Schedules whose task could not be created; the next run retries them
+    pub failed: Vec<(Uuid, ScheduleError)>,
+}
+
+/// Creates tasks on a schedule, e.g. "Weekly review" every Monday at 09:00.
+/// `tick` creates the tasks of the schedules that fired, from the daemon or
+/// from system cron.
+pub struct ScheduleService {
+    task_service: Arc<TaskService>,
+    schedules: Arc<dyn ScheduleRepository>,
+}
+
+impl ScheduleService {
+    pub fn new(task_service: Arc<TaskService>, schedules: Arc<dyn ScheduleRepository>) -> Self {
+        Self { task_service, schedules }
+    }
+
+    /// Create `request`'s task each time its cron expression fires, from now on
+    pub async fn add(&self, user_id: &Uuid, request: StoreScheduleRequest) -> Result<Schedule, ScheduleError> {
+        let cron: CronSchedule = request.cron.parse().map_err(ScheduleError::InvalidSchedule)?;
+        if !(0..=MAX_AHEAD).contains(&request.ahead) {
+            return Err(ScheduleError::InvalidSchedule(format!("ahead must be between 0 and {}", MAX_AHEAD)));
+        }
+        task_request(&request)
+            .validate()
+            .map_err(|e| ScheduleError::InvalidSchedule(e.to_string()))?;
+        let next_run_at = cron
+            .next_run_after(Utc::now())
+            .ok_or_else(|| ScheduleError::InvalidSchedule(format!("'{}' never fires", cron)))?;
+
+        let request = StoreScheduleRequest { cron: cron.to_string(), ..request };
+        let schedule = self.schedules.create(user_id, &request, next_run_at).await?;
+        info!("User {} scheduled '{}' at '{}'", user_id, schedule.title, schedule.cron);
+        Ok(schedule)
+    }
+
+    pub async fn list(&self, user_id: &Uuid) -> Result<Vec<Schedule>, ScheduleError> {
+        Ok(self.schedules.find_by_user_id(user_id).await?)
+    }
+
+    /// Delete the user's schedule whose ID starts with `reference`; tasks it
+    /// already created stay
+    pub async fn remove(&self, user_id: &Uuid, reference: &str) -> Result<Schedule, ScheduleError> {
+        let reference = reference.trim();
+        let needle = reference.to_lowercase().replace('-', "");
+        let mut matches: Vec<Schedule> = self
+            .list(user_id)
+            .await?
+            .into_iter()
+            .filter(|schedule| !needle.is_empty() && schedule.id.simple().to_string().starts_with(&needle))
+            .collect();
+
+        let schedule = match matches.len() {
+            0 => return Err(ScheduleError::NotFound { reference: reference.to_string() }),
+            1 => matches.remove(0),
+            _ => return Err(ScheduleError::Ambiguous { reference: reference.to_string() }),
+        };
+        if !self.schedules.delete(user_id, &schedule.id).await? {
+            return Err(ScheduleError::NotFound { reference: reference.to_string() });
+        }
+        info!("User {} removed schedule '{}'", user_id, schedule.title);
+        Ok(schedule)
+    }
+
+    /// Create the task of every schedule due at `now`. A schedule that
+    /// missed several firings, e.g. while nothing ran, creates one task and
+    /// moves on to its next firing after `now`. A schedule creating its
+    /// tasks `ahead` gets the tasks of its next firings instead, each due
+    /// when it fires.
+    pub async fn tick(&self, now: DateTime<Utc>) -> Result<ScheduleRun, ScheduleError> {
+        let mut run = ScheduleRun::default();
+
+        for schedule in self.schedules.find_due(now).await? {
+            let Ok(cron) = schedule.cron.parse::<CronSchedule>() else {
+                warn!("Schedule {} has an invalid expression: '{}'", schedule.id, schedule.cron);
+                run.failed.push((schedule.id, ScheduleError::InvalidSchedule(schedule.cron.clone())));
+                continue;
+            };
+            let (due_dates, next_run_at) = if schedule.ahead > 0 {
+                let firings = cron.to_materialize(schedule.next_run_at, schedule.ahead as usize, now);
+                // Enough upcoming tasks exist
+                let Some(last) = firings.last() else { continue };
+                (firings.iter().copied().map(Some).collect(), cron.next_run_after(*last))
+            } else {
+                (vec![None], cron.next_run_after(now))
+            };
+            let Some(next_run_at) = next_run_at else {
+                warn!("Schedule {} no longer fires: '{}'", schedule.id, schedule.cron);
+                run.failed.push((schedule.id, ScheduleError::InvalidSchedule(format!("'{}' never fires", schedule.cron))));
+                continue;
+            };
+            // Another run got here first
+            if !self.schedules.claim(&schedule.id, schedule.next_run_at, next_run_at, now).await? {
+                continue;
+            }
+
+            let request = StoreScheduleRequest {
+                title: schedule.title.clone(),
+                description: schedule.description.clone(),
+                priority: schedule.priority,
+                context: schedule.context.clone(),
+                cron: schedule.cron.clone(),
+                ahead: schedule.ahead,
+            };
+            for due_date in due_dates {
+                let task = StoreTaskRequest { due_date, ..task_request(&request) };
+                match self.task_service.create_task(&schedule.user_id, task).await {
+                    Ok(task) => run.created.push(task),
+                    Err(e) => {
+                        warn!("Schedule {} failed to create its task: {}", schedule.id, e);
+                        // The next run resumes at the firing that failed
+                        let failed = Schedule { next_run_at: due_date.unwrap_or(schedule.next_run_at), ..schedule.clone() };
+                        self.schedules.release(&failed).await?;
+                        run.failed.push((schedule.id, e.into()));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !run.created.is_empty() || !run.failed.is_empty() {
+            info!("Schedules: {} tasks created, {} failed", run.created.len(), run.failed.len());
+        }
+        Ok(run)
+    }
+}
+
+fn task_request(schedule: &StoreScheduleRequest) -> StoreTaskRequest {
+    StoreTaskRequest {
+        title: schedule.title.clone(),
+        description: schedule.description.clone(),
+        priority: schedule.priority,
+        context: schedule.context.clone(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemoryScheduleRepository, InMemoryTaskRepository};
+    use chrono::Duration;
+
+    fn service() -> (ScheduleService, Arc<InMemoryScheduleRepository>) {
+        let task_service = Arc::new(TaskService::new(Arc::new(InMemoryTaskRepository::new())));
+        let schedules = Arc::new(InMemoryScheduleRepository::new());
+        (ScheduleService::new(task_service, schedules.clone()), schedules)
+    }
+
+    fn weekly_review() -> StoreScheduleRequest {
+        StoreScheduleRequest {
+            title: "Weekly review".to_string(),
+            cron: "0  9 * * mon".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_validates_and_removes_by_prefix() {
+        let (service, _) = service();
+        let user_id = Uuid::new_v4();
+
+        let schedule = service.add(&user_id, weekly_review()).await.unwrap();
+        assert_eq!(schedule.cron, "0 9 * * mon");
+        assert!(schedule.next_run_at > Utc::now());
+
+        let invalid = StoreScheduleRequest { cron: "0 9 * *".to_string(), ..weekly_review() };
+        assert!(matches!(service.add(&user_id, invalid).await, Err(ScheduleError::InvalidSchedule(_))));
+        let never = StoreScheduleRequest { cron: "0 9 31 2 *".to_string(), ..weekly_review() };
+        assert!(matches!(service.add(&user_id, never).await, Err(ScheduleError::InvalidSchedule(_))));
+        let untitled = StoreScheduleRequest { title: String::new(), ..weekly_review() };
+        assert!(matches!(service.add(&user_id, untitled).await, Err(ScheduleError::InvalidSchedule(_))));
+
+        // Only the owner can remove it
+        assert!(matches!(
+            service.remove(&Uuid::new_v4(), &schedule.short_id()).await,
+            Err(ScheduleError::NotFound { .. })
+        ));
+        assert_eq!(service.remove(&user_id, &schedule.short_id()).await.unwrap().id, schedule.id);
+        assert!(service.list(&user_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tick_creates_each_firing_once() {
+        let (service, schedules) = service();
+        let user_id = Uuid::new_v4();
+        let schedule = service.add(&user_id, weekly_review()).await.unwrap();
+
+        assert!(service.tick(schedule.next_run_at - Duration::minutes(1)).await.unwrap().created.is_empty());
+
+        // Three missed weeks create one task
+        let late = schedule.next_run_at + Duration::weeks(3) + Duration::minutes(5);
+        let run = service.tick(late).await.unwrap();
+        assert_eq!(run.created.len(), 1);
+        assert_eq!(run.created[0].title, "Weekly review");
+        assert_eq!(run.created[0].user_id, user_id);
+        assert!(service.tick(late).await.unwrap().created.is_empty());
+
+        let moved = &schedules.find_by_user_id(&user_id).await.unwrap()[0];
+        assert_eq!(moved.last_run_at, Some(late));
+        assert!(moved.next_run_at > late && moved.next_run_at <= late + Duration::weeks(1));
+    }
+
+    #[tokio::test]
+    async fn test_tick_creates_upcoming_tasks_ahead() {
+        let (service, _) = service();
+        let user_id = Uuid::new_v4();
+        let request = StoreScheduleRequest { ahead: 3, ..weekly_review() };
+        let schedule = service.add(&user_id, request).await.unwrap();
+        let now = Utc::now();
+
+        let run = service.tick(now).await.unwrap();
+        let due: Vec<_> = run.created.iter().map(|task| task.due_date.unwrap()).collect();
+        assert_eq!(due, [0, 1, 2].map(|week| schedule.next_run_at + Duration::weeks(week)));
+        assert!(service.tick(now).await.unwrap().created.is_empty());
+
+        // Once the first firing passed, the fourth week is created
+        let run = service.tick(schedule.next_run_at + Duration::minutes(1)).await.unwrap();
+        assert_eq!(run.created.len(), 1);
+        assert_eq!(run.created[0].due_date, Some(schedule.next_run_at + Duration::weeks(3)));
+
+        let too_many = StoreScheduleRequest { ahead: MAX_AHEAD + 1, ..weekly_review() };
+        assert!(matches!(service.add(&user_id, too_many).await, Err(ScheduleError::InvalidSchedule(_))));
+    }
+}
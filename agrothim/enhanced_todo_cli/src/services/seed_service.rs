@@ -0,0 +1,165 @@
+use chrono::{Duration, Utc};
+use rand::{seq::SliceRandom, Rng};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    database::repositories::{TaskRepository, TaskRepositoryError, UserRepository, UserRepositoryError},
+    models::{
+        task::{StoreTaskRequest, TaskPriority, TaskStatus},
+        user::{StoreUserRequest, User},
+    },
+};
+
+/// Account the demo data belongs to
+pub const DEMO_USERNAME: &str = "demo";
+pub const DEMO_EMAIL: &str = "demo@example.com";
+pub const DEMO_PASSWORD: &str = "kestrel-canyon-42";
+
+/// Tasks stored per transaction
+const BATCH_SIZE: usize = 100;
+
+const VERBS: &[&str] = &[
+    "Write", "Review", "Update", "Fix", "Plan", "Call", "Email", "Prepare", "Book", "Clean up", "Draft", "Test",
+];
+const SUBJECTS: &[&str] = &[
+    "quarterly report", "release notes", "dentist appointment", "team offsite", "invoice #{n}", "login page",
+    "grocery list", "budget spreadsheet", "onboarding guide", "garage", "conference talk", "flaky test #{n}",
+];
+const DESCRIPTIONS: &[&str] = &[
+    "Check with the team before sending.",
+    "Blocked until the numbers are final.",
+    "Keep it short, one page at most.",
+    "Follow up if there is no answer by Friday.",
+];
+const CONTEXTS: &[&str] = &["@home", "@office", "@phone", "@errands", "@computer"];
+const CLIENTS: &[&str] = &["Acme", "Globex", "Initech"];
+
+#[derive(Error, Debug)]
+pub enum SeedError {
+    #[error(transparent)]
+    User(#[from] UserRepositoryError),
+
+    #[error(transparent)]
+    Task(#[from] TaskRepositoryError),
+}
+
+/// Outcome of a seed run
+#[derive(Debug)]
+pub struct SeedSummary {
+    pub user: User,
+    /// False when the demo account already existed and got more tasks
+    pub created_user: bool,
+    pub tasks: usize,
+}
+
+/// Fills the database with a demo account and varied tasks, for benchmarks
+/// and screenshots
+pub struct SeedService {
+    user_repository: Arc<dyn UserRepository>,
+    task_repository: Arc<dyn TaskRepository>,
+}
+
+impl SeedService {
+    pub fn new(user_repository: Arc<dyn UserRepository>, task_repository: Arc<dyn TaskRepository>) -> Self {
+        Self {
+            user_repository,
+            task_repository,
+        }
+    }
+
+    /// Give the demo account `count` random tasks, creating the account first
+    /// if needed. The same `rng` seed produces the same tasks.
+    pub async fn seed(&self, count: usize, rng: &mut impl Rng) -> Result<SeedSummary, SeedError> {
+        let (user, created_user) = match self.user_repository.find_by_username(DEMO_USERNAME).await? {
+            Some(user) => (user, false),
+            None => (self.create_demo_user().await?, true),
+        };
+
+        let mut requests: Vec<StoreTaskRequest> = (1..=count).map(|n| demo_task(n, rng)).collect();
+        let mut tasks = 0;
+        while !requests.is_empty() {
+            let batch: Vec<StoreTaskRequest> = requests.drain(..requests.len().min(BATCH_SIZE)).collect();
+            tasks += self.task_repository.store_many(batch, &user.id).await?.len();
+        }
+
+        info!("Seeded {} demo tasks for user {}", tasks, user.id);
+        Ok(SeedSummary { user, created_user, tasks })
+    }
+
+    async fn create_demo_user(&self) -> Result<User, SeedError> {
+        let request = StoreUserRequest::new(DEMO_USERNAME.to_string(), DEMO_EMAIL.to_string(), DEMO_PASSWORD.to_string())
+            .expect("demo credentials are valid");
+        let user = self.user_repository.store(request).await?;
+        // Seeded data should be usable right away, without a verification email
+        Ok(self.user_repository.mark_email_verified(&user.id).await?)
+    }
+}
+
+/// A task with a mix of statuses, priorities and dates; `n` keeps titles apart
+fn demo_task(n: usize, rng: &mut impl Rng) -> StoreTaskRequest {
+    let subject = SUBJECTS.choose(rng).unwrap().replace("{n}", &n.to_string());
+    let status = *[TaskStatus::Pending, TaskStatus::Pending, TaskStatus::InProgress, TaskStatus::Completed]
+        .choose(rng)
+        .unwrap();
+    let priority = *[TaskPriority::Low, TaskPriority::Medium, TaskPriority::High].choose(rng).unwrap();
+
+    // Due dates from a month ago to two months ahead, so some are overdue
+    let due_date = rng
+        .gen_bool(0.7)
+        .then(|| Utc::now() + Duration::hours(rng.gen_range(-30 * 24..60 * 24)));
+    let start_date = due_date
+        .filter(|_| rng.gen_bool(0.3))
+        .map(|due| due - Duration::days(rng.gen_range(1..14)));
+
+    StoreTaskRequest {
+        title: format!("{} {}", VERBS.choose(rng).unwrap(), subject),
+        description: rng.gen_bool(0.4).then(|| DESCRIPTIONS.choose(rng).unwrap().to_string()),
+        status,
+        priority,
+        due_date,
+        start_date,
+        estimate_minutes: rng.gen_bool(0.5).then(|| rng.gen_range(1..=16) * 15),
+        client: rng.gen_bool(0.2).then(|| CLIENTS.choose(rng).unwrap().to_string()),
+        context: rng.gen_bool(0.6).then(|| CONTEXTS.choose(rng).unwrap().to_string()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemoryTaskRepository, InMemoryUserRepository};
+    use rand::{rngs::StdRng, SeedableRng};
+    use validator::Validate;
+
+    #[test]
+    fn test_demo_tasks_are_valid_and_reproducible() {
+        let tasks: Vec<StoreTaskRequest> = (1..=200).map(|n| demo_task(n, &mut StdRng::seed_from_u64(n as u64))).collect();
+        assert!(tasks.iter().all(|task| task.validate().is_ok()));
+        assert!(tasks.iter().any(|task| task.status == TaskStatus::Completed));
+        assert!(tasks.iter().any(|task| task.due_date.is_some_and(|due| due < Utc::now())));
+
+        let again = demo_task(7, &mut StdRng::seed_from_u64(7));
+        assert_eq!(again.title, tasks[6].title);
+    }
+
+    #[tokio::test]
+    async fn test_seed_reuses_the_demo_account() {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let tasks = Arc::new(InMemoryTaskRepository::new());
+        let service = SeedService::new(users.clone(), tasks.clone());
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let first = service.seed(250, &mut rng).await.unwrap();
+        assert!(first.created_user);
+        assert!(first.user.email_verified_at.is_some());
+        assert_eq!(first.tasks, 250);
+
+        let second = service.seed(10, &mut rng).await.unwrap();
+        assert!(!second.created_user);
+        assert_eq!(second.user.id, first.user.id);
+        assert_eq!(tasks.count_by_user(&first.user.id).await.unwrap(), 260);
+    }
+}
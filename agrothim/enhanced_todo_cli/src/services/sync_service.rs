@@ -0,0 +1,509 @@
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{
+        SyncStateRepository, SyncStateRepositoryError, TaskRepository, TaskRepositoryError, UserRepository,
+        UserRepositoryError,
+    },
+    models::{
+        calendar,
+        sync::{ConflictStrategy, SyncConflict, SyncState},
+        task::Task,
+        user::User,
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("Account '{identifier}' does not exist in the remote database")]
+    AccountNotFound { identifier: String },
+
+    #[error("Task {task_id} has no unresolved sync conflict")]
+    ConflictNotFound { task_id: Uuid },
+
+    #[error(transparent)]
+    User(#[from] UserRepositoryError),
+
+    #[error(transparent)]
+    Task(#[from] TaskRepositoryError),
+
+    #[error(transparent)]
+    State(#[from] SyncStateRepositoryError),
+}
+
+/// What one sync changed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncSummary {
+    /// Local changes written to the remote database
+    pub pushed: usize,
+    /// Remote changes written to the local database
+    pub pulled: usize,
+    /// Tasks changed on both sides
+    pub conflicts: usize,
+    /// Conflicts left alone for `sync conflicts` (`sync.conflicts = ask`)
+    pub unresolved: usize,
+}
+
+impl std::ops::AddAssign for SyncSummary {
+    fn add_assign(&mut self, other: Self) {
+        self.pushed += other.pushed;
+        self.pulled += other.pulled;
+        self.conflicts += other.conflicts;
+        self.unresolved += other.unresolved;
+    }
+}
+
+/// One database of local-first mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Local,
+    Remote,
+}
+
+/// A task changed on both sides, as it is now in each database; `None`
+/// where it was deleted
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub task_id: Uuid,
+    pub detected_at: chrono::DateTime<Utc>,
+    pub local: Option<Task>,
+    pub remote: Option<Task>,
+}
+
+/// What brings one task in line on both sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Nothing,
+    Push,
+    Pull,
+    DeleteLocal,
+    DeleteRemote,
+    /// Deleted on both sides; only the bookkeeping is left
+    Forget,
+}
+
+/// The action for a task as it is locally and remotely, given its state at
+/// the last sync, and whether both sides changed it since
+fn plan(local: Option<&Task>, remote: Option<&Task>, state: Option<&SyncState>) -> (Action, bool) {
+    match (local, remote, state) {
+        (Some(local), Some(remote), Some(state)) => {
+            match (local.version != state.local_version, remote.version != state.remote_version) {
+                (false, false) => (Action::Nothing, false),
+                (true, false) => (Action::Push, false),
+                (false, true) => (Action::Pull, false),
+                (true, true) => (later(local, remote), true),
+            }
+        }
+        // Known on both sides but never synced, e.g. after the local database was replaced
+        (Some(local), Some(remote), None) => (later(local, remote), false),
+        // An edit wins over a delete on the other side
+        (Some(local), None, Some(state)) if local.version != state.local_version => (Action::Push, true),
+        (Some(_), None, Some(_)) => (Action::DeleteLocal, false),
+        (Some(_), None, None) => (Action::Push, false),
+        (None, Some(remote), Some(state)) if remote.version != state.remote_version => (Action::Pull, true),
+        (None, Some(_), Some(_)) => (Action::DeleteRemote, false),
+        (None, Some(_), None) => (Action::Pull, false),
+        (None, None, _) => (Action::Forget, false),
+    }
+}
+
+/// Keep whichever side was updated last; the remote one on a tie
+fn later(local: &Task, remote: &Task) -> Action {
+    if local.updated_at > remote.updated_at {
+        Action::Push
+    } else {
+        Action::Pull
+    }
+}
+
+/// Local-first mode: commands read and write a local SQLite database, and
+/// this reconciles it with the remote database whenever that answers.
+/// Tasks are matched by ID; their numbers are given by each database, so a
+/// task created offline may show another number once synced.
+pub struct SyncService {
+    local_users: Arc<dyn UserRepository>,
+    local_tasks: Arc<dyn TaskRepository>,
+    remote_users: Arc<dyn UserRepository>,
+    remote_tasks: Arc<dyn TaskRepository>,
+    state: Arc<dyn SyncStateRepository>,
+    strategy: ConflictStrategy,
+}
+
+impl SyncService {
+    pub fn new(
+        local_users: Arc<dyn UserRepository>,
+        local_tasks: Arc<dyn TaskRepository>,
+        remote_users: Arc<dyn UserRepository>,
+        remote_tasks: Arc<dyn TaskRepository>,
+        state: Arc<dyn SyncStateRepository>,
+    ) -> Self {
+        Self {
+            local_users,
+            local_tasks,
+            remote_users,
+            remote_tasks,
+            state,
+            strategy: ConflictStrategy::default(),
+        }
+    }
+
+    /// Settle tasks changed on both sides this way instead of keeping the later change
+    pub fn with_conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Copy the remote account with this username or email to the local
+    /// database, so it can log in there
+    pub async fn fetch_account(&self, identifier: &str) -> Result<User, SyncError> {
+        let user = match self.remote_users.find_by_username(identifier).await? {
+            Some(user) => user,
+            None => self
+                .remote_users
+                .find_by_email(identifier)
+                .await?
+                .ok_or_else(|| SyncError::AccountNotFound { identifier: identifier.to_string() })?,
+        };
+        Ok(self.local_users.put_copy(&user).await?)
+    }
+
+    /// Bring the account and tasks of the user in line on both sides
+    pub async fn sync_user(&self, user_id: &Uuid) -> Result<SyncSummary, SyncError> {
+        // The remote database owns accounts; one registered offline is copied up
+        match self.remote_users.find_by_id(user_id).await? {
+            Some(user) => {
+                self.local_users.put_copy(&user).await?;
+            }
+            None => match self.local_users.find_by_id(user_id).await? {
+                Some(user) => {
+                    self.remote_users.put_copy(&user).await?;
+                }
+                None => return Ok(SyncSummary::default()),
+            },
+        }
+
+        let local: HashMap<Uuid, Task> = by_id(self.local_tasks.find_by_user_id(user_id).await?);
+        let remote: HashMap<Uuid, Task> = by_id(self.remote_tasks.find_by_user_id(user_id).await?);
+        let states: HashMap<Uuid, SyncState> = self
+            .state
+            .find_by_user(user_id)
+            .await?
+            .into_iter()
+            .map(|state| (state.task_id, state))
+            .collect();
+        let ids: HashSet<Uuid> = local.keys().chain(remote.keys()).chain(states.keys()).copied().collect();
+
+        let mut summary = SyncSummary::default();
+        for id in ids {
+            let (action, conflict) = plan(local.get(&id), remote.get(&id), states.get(&id));
+            if conflict {
+                summary.conflicts += 1;
+                match self.strategy {
+                    ConflictStrategy::Ask => {
+                        let conflict = SyncConflict { task_id: id, user_id: *user_id, detected_at: Utc::now() };
+                        self.state.save_conflict(&conflict).await?;
+                        summary.unresolved += 1;
+                        continue;
+                    }
+                    // Recorded while the strategy was `ask`; the action below settles it
+                    ConflictStrategy::Latest => self.state.delete_conflict(&id).await?,
+                }
+            }
+            match action {
+                Action::Nothing => {}
+                Action::Push => {
+                    let local = &local[&id];
+                    let copy = self.remote_tasks.put_copy(local).await?;
+                    self.save_state(local, &copy).await?;
+                    summary.pushed += 1;
+                }
+                Action::Pull => {
+                    let remote = &remote[&id];
+                    let copy = self.local_tasks.put_copy(remote).await?;
+                    self.save_state(&copy, remote).await?;
+                    summary.pulled += 1;
+                }
+                Action::DeleteLocal => {
+                    self.local_tasks.delete(&id, user_id).await?;
+                    self.state.delete(&id).await?;
+                    summary.pulled += 1;
+                }
+                Action::DeleteRemote => {
+                    self.remote_tasks.delete(&id, user_id).await?;
+                    self.state.delete(&id).await?;
+                    summary.pushed += 1;
+                }
+                Action::Forget => {
+                    self.state.delete(&id).await?;
+                    self.state.delete_conflict(&id).await?;
+                }
+            }
+        }
+
+        info!(
+            "Synced user {}: {} pushed, {} pulled, {} conflicts, {} unresolved",
+            user_id, summary.pushed, summary.pulled, summary.conflicts, summary.unresolved
+        );
+        Ok(summary)
+    }
+
+    /// `sync_user` for every account in the local database
+    pub async fn sync_all(&self) -> Result<SyncSummary, SyncError> {
+        let mut summary = SyncSummary::default();
+        for user in self.local_users.find_all().await? {
+            summary += self.sync_user(&user.id).await?;
+        }
+        Ok(summary)
+    }
+
+    /// The user's unresolved conflicts, oldest first, with both current versions
+    pub async fn conflicts(&self, user_id: &Uuid) -> Result<Vec<Conflict>, SyncError> {
+        let mut conflicts = Vec::new();
+        for conflict in self.state.find_conflicts(user_id).await? {
+            conflicts.push(Conflict {
+                task_id: conflict.task_id,
+                detected_at: conflict.detected_at,
+                local: self.local_tasks.find_by_id(&conflict.task_id).await?,
+                remote: self.remote_tasks.find_by_id(&conflict.task_id).await?,
+            });
+        }
+        Ok(conflicts)
+    }
+
+    /// Settle a conflict by making the other side match `keep`, deleting the
+    /// task there if it was deleted on the kept side
+    pub async fn resolve(&self, task_id: &Uuid, keep: Side) -> Result<(), SyncError> {
+        let conflict = self
+            .state
+            .find_conflict(task_id)
+            .await?
+            .ok_or(SyncError::ConflictNotFound { task_id: *task_id })?;
+        let local = self.local_tasks.find_by_id(task_id).await?;
+        let remote = self.remote_tasks.find_by_id(task_id).await?;
+
+        match (keep, local, remote) {
+            (Side::Local, Some(local), _) => {
+                let copy = self.remote_tasks.put_copy(&local).await?;
+                self.save_state(&local, &copy).await?;
+            }
+            (Side::Remote, _, Some(remote)) => {
+                let copy = self.local_tasks.put_copy(&remote).await?;
+                self.save_state(&copy, &remote).await?;
+            }
+            (Side::Local, None, _) => {
+                self.remote_tasks.delete(task_id, &conflict.user_id).await?;
+                self.state.delete(task_id).await?;
+            }
+            (Side::Remote, _, None) => {
+                self.local_tasks.delete(task_id, &conflict.user_id).await?;
+                self.state.delete(task_id).await?;
+            }
+        }
+        self.state.delete_conflict(task_id).await?;
+
+        info!("Resolved sync conflict of task {} keeping the {:?} version", task_id, keep);
+        Ok(())
+    }
+
+    /// Sync every `interval` until the returned task is aborted, in the
+    /// caller's timezone. Failures are only logged: the remote database may
+    /// just be unreachable for now.
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let timezone = calendar::timezone();
+        tokio::spawn(calendar::with_timezone(timezone, async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                match self.sync_all().await {
+                    Ok(summary) => debug!("Background sync done: {:?}", summary),
+                    Err(e) => warn!("Background sync failed: {}", e),
+                }
+            }
+        }))
+    }
+
+    async fn save_state(&self, local: &Task, remote: &Task) -> Result<(), SyncError> {
+        let state = SyncState {
+            task_id: local.id,
+            user_id: local.user_id,
+            local_version: local.version,
+            remote_version: remote.version,
+            synced_at: Utc::now(),
+        };
+        Ok(self.state.save(&state).await?)
+    }
+}
+
+fn by_id(tasks: Vec<Task>) -> HashMap<Uuid, Task> {
+    tasks.into_iter().map(|task| (task.id, task)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::repositories::{InMemorySyncStateRepository, InMemoryTaskRepository, InMemoryUserRepository};
+    use crate::models::task::{StoreTaskRequest, TaskStatus, UpdateTaskRequest};
+    use crate::models::user::StoreUserRequest;
+
+    struct Fixture {
+        local_tasks: Arc<InMemoryTaskRepository>,
+        remote_users: Arc<InMemoryUserRepository>,
+        remote_tasks: Arc<InMemoryTaskRepository>,
+        service: SyncService,
+        user: User,
+    }
+
+    async fn fixture() -> Fixture {
+        let local_users = Arc::new(InMemoryUserRepository::new());
+        let local_tasks = Arc::new(InMemoryTaskRepository::new());
+        let remote_users = Arc::new(InMemoryUserRepository::new());
+        let remote_tasks = Arc::new(InMemoryTaskRepository::new());
+        let service = SyncService::new(
+            local_users,
+            local_tasks.clone(),
+            remote_users.clone(),
+            remote_tasks.clone(),
+            Arc::new(InMemorySyncStateRepository::new()),
+        );
+        let user = remote_users
+            .store(StoreUserRequest {
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                password: String::new(),
+                password_hash: "hash".to_string(),
+            })
+            .await
+            .unwrap();
+        service.fetch_account("alice").await.unwrap();
+        Fixture { local_tasks, remote_users, remote_tasks, service, user }
+    }
+
+    fn task(title: &str) -> StoreTaskRequest {
+        StoreTaskRequest { title: title.to_string(), ..Default::default() }
+    }
+
+    fn rename(title: &str) -> UpdateTaskRequest {
+        UpdateTaskRequest { title: Some(title.to_string()), ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn test_sync_copies_new_tasks_both_ways() {
+        let f = fixture().await;
+        let offline = f.local_tasks.store(task("Written offline"), &f.user.id).await.unwrap();
+        let elsewhere = f.remote_tasks.store(task("Written elsewhere"), &f.user.id).await.unwrap();
+
+        let summary = f.service.sync_user(&f.user.id).await.unwrap();
+        assert_eq!(summary, SyncSummary { pushed: 1, pulled: 1, ..Default::default() });
+        assert_eq!(f.remote_tasks.find_by_id(&offline.id).await.unwrap().unwrap().title, "Written offline");
+        assert_eq!(f.local_tasks.find_by_id(&elsewhere.id).await.unwrap().unwrap().title, "Written elsewhere");
+
+        // Nothing changed since, so nothing moves
+        assert_eq!(f.service.sync_user(&f.user.id).await.unwrap(), SyncSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_sync_applies_edits_and_deletes() {
+        let f = fixture().await;
+        let a = f.local_tasks.store(task("A"), &f.user.id).await.unwrap();
+        let b = f.local_tasks.store(task("B"), &f.user.id).await.unwrap();
+        f.service.sync_user(&f.user.id).await.unwrap();
+
+        f.local_tasks.update(&a.id, &f.user.id, rename("A, edited here")).await.unwrap();
+        f.remote_tasks.delete(&b.id, &f.user.id).await.unwrap();
+        let summary = f.service.sync_user(&f.user.id).await.unwrap();
+        assert_eq!(summary, SyncSummary { pushed: 1, pulled: 1, ..Default::default() });
+        assert_eq!(f.remote_tasks.find_by_id(&a.id).await.unwrap().unwrap().title, "A, edited here");
+        assert!(f.local_tasks.find_by_id(&b.id).await.unwrap().is_none());
+
+        f.local_tasks.delete(&a.id, &f.user.id).await.unwrap();
+        f.service.sync_user(&f.user.id).await.unwrap();
+        assert!(f.remote_tasks.find_by_id(&a.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_edits_keep_the_later_one() {
+        let f = fixture().await;
+        let a = f.local_tasks.store(task("A"), &f.user.id).await.unwrap();
+        f.service.sync_user(&f.user.id).await.unwrap();
+
+        f.local_tasks.update(&a.id, &f.user.id, rename("Local edit")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let complete = UpdateTaskRequest { status: Some(TaskStatus::Completed), ..Default::default() };
+        f.remote_tasks.update(&a.id, &f.user.id, complete).await.unwrap();
+
+        let summary = f.service.sync_user(&f.user.id).await.unwrap();
+        assert_eq!(summary.conflicts, 1);
+        let local = f.local_tasks.find_by_id(&a.id).await.unwrap().unwrap();
+        assert_eq!(local.status, TaskStatus::Completed);
+        assert_eq!(local.title, "A");
+    }
+
+    #[tokio::test]
+    async fn test_conflicts_wait_for_resolution_when_asked() {
+        let mut f = fixture().await;
+        f.service = f.service.with_conflict_strategy(ConflictStrategy::Ask);
+        let a = f.local_tasks.store(task("A"), &f.user.id).await.unwrap();
+        let b = f.local_tasks.store(task("B"), &f.user.id).await.unwrap();
+        f.service.sync_user(&f.user.id).await.unwrap();
+
+        f.local_tasks.update(&a.id, &f.user.id, rename("A, local")).await.unwrap();
+        f.remote_tasks.update(&a.id, &f.user.id, rename("A, remote")).await.unwrap();
+        f.local_tasks.update(&b.id, &f.user.id, rename("B, local")).await.unwrap();
+        f.remote_tasks.delete(&b.id, &f.user.id).await.unwrap();
+
+        // Both sides keep their version, also on the next sync
+        for _ in 0..2 {
+            let summary = f.service.sync_user(&f.user.id).await.unwrap();
+            assert_eq!(summary, SyncSummary { conflicts: 2, unresolved: 2, ..Default::default() });
+        }
+        assert_eq!(f.remote_tasks.find_by_id(&a.id).await.unwrap().unwrap().title, "A, remote");
+
+        let conflicts = f.service.conflicts(&f.user.id).await.unwrap();
+        assert_eq!(conflicts.len(), 2);
+        let conflict = conflicts.iter().find(|conflict| conflict.task_id == a.id).unwrap();
+        assert_eq!(conflict.local.as_ref().unwrap().title, "A, local");
+        assert_eq!(conflict.remote.as_ref().unwrap().title, "A, remote");
+
+        f.service.resolve(&a.id, Side::Local).await.unwrap();
+        assert_eq!(f.remote_tasks.find_by_id(&a.id).await.unwrap().unwrap().title, "A, local");
+        f.service.resolve(&b.id, Side::Remote).await.unwrap();
+        assert!(f.local_tasks.find_by_id(&b.id).await.unwrap().is_none());
+        assert!(matches!(
+            f.service.resolve(&a.id, Side::Remote).await,
+            Err(SyncError::ConflictNotFound { .. })
+        ));
+
+        assert!(f.service.conflicts(&f.user.id).await.unwrap().is_empty());
+        assert_eq!(f.service.sync_user(&f.user.id).await.unwrap(), SyncSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_account_registered_offline_is_copied_up() {
+        let f = fixture().await;
+        let offline = InMemoryUserRepository::new();
+        let user = offline
+            .store(StoreUserRequest {
+                username: "bob".to_string(),
+                email: "bob@example.com".to_string(),
+                password: String::new(),
+                password_hash: "hash".to_string(),
+            })
+            .await
+            .unwrap();
+        f.service.local_users.put_copy(&user).await.unwrap();
+
+        f.service.sync_all().await.unwrap();
+        assert!(f.remote_users.find_by_id(&user.id).await.unwrap().is_some());
+        assert!(matches!(
+            f.service.fetch_account("carol").await,
+            Err(SyncError::AccountNotFound { .. })
+        ));
+    }
+}
@@ -1,15 +1,38 @@
-use chrono::Utc;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
+use tokio_stream::{Stream, StreamExt};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    database::repositories::{TaskRepository, TaskRepositoryError},
-    models::task::{
-        StoreTaskRequest, Task, TaskFilter, TaskStatistics, TaskStatus, UpdateTaskRequest,
+    database::change_listener::{ChangeListener, RowOperation, TaskRowChange},
+    database::repositories::{
+        StatusRepository, StatusRepositoryError, TaskRepository, TaskRepositoryError, WorkspaceRepository,
+        TaskShareRepository, TaskShareRepositoryError, WorkspaceRepositoryError, TaskEventRepository,
+        TaskEventRepositoryError,
     },
+    models::{
+        status::{CustomStatus, StatusTransition, StatusWorkflow, StoreStatusRequest},
+        task::{
+            normalize_context, sort_order_between, ContextCount, DetailedStatistics, GroupBreakdown, MovePosition, PageRequest, StatisticsFilter, StoreTaskRequest, Task,
+            TaskFilter, TaskGroup, TaskGrouping, TaskStatistics, TaskStatus, UpdateTaskRequest, STALE_AFTER_DAYS,
+        },
+        calendar,
+        task_ref::TaskRef,
+        task_share::TaskShare,
+        task_event::{ActivityFeed, TaskEventKind},
+        task_change::{TaskChange, TaskChangeKind},
+        workspace::{MemberTaskCounts, OverdueBreakdown},
+        workload::Workload,
+        forecast::Forecast,
+        report::{Burndown, CompletionStreak, ContextTally, Heatmap, OverdueAging, TaskTimestamp, WeeklyReport},
+        digest::DailyDigest,
+        webhook::WebhookEvent,
+    },
+    services::{EventBus, WebhookService},
 };
 
 #[derive(Error, Debug)]
@@ -20,30 +43,137 @@ pub enum TaskServiceError {
     #[error("Task not found")]
     TaskNotFound,
 
+    #[error("Task ID '{prefix}' matches more than one task; type more of it")]
+    AmbiguousTaskId { prefix: String },
+
     #[error("Task access denied for user")]
     AccessDenied,
 
+    #[error("Task is shared with you read-only")]
+    ReadOnlyTask,
+
     #[error("Bulk operation failed: {failed_count} out of {total_count} operations failed")]
     BulkOperationPartialFailure {
         failed_count: usize,
         total_count: usize,
     },
 
+    #[error("Unknown status: {name}")]
+    UnknownStatus { name: String },
+
+    #[error("Cannot move task from '{from}' to '{to}'")]
+    InvalidStatusTransition { from: String, to: String },
+
+    #[error("Status '{name}' is still used by {task_count} tasks")]
+    StatusInUse { name: String, task_count: i64 },
+
     #[error("Internal service error: {0}")]
     InternalError(#[from] anyhow::Error),
 
     #[error("Repository error: {0}")]
     RepositoryError(#[from] TaskRepositoryError),
+
+    #[error("Status repository error: {0}")]
+    StatusRepositoryError(#[from] StatusRepositoryError),
+
+    #[error("Workspace repository error: {0}")]
+    WorkspaceRepositoryError(#[from] WorkspaceRepositoryError),
+
+    #[error("Task share repository error: {0}")]
+    TaskShareRepositoryError(#[from] TaskShareRepositoryError),
+
+    #[error("Task event repository error: {0}")]
+    TaskEventRepositoryError(#[from] TaskEventRepositoryError),
+}
+
+/// Why one task of a bulk operation was not updated
+#[derive(Error, Debug)]
+pub enum BulkItemError {
+    #[error("Task not found")]
+    NotFound { task_id: Uuid },
+
+    #[error("Cannot move task from '{from}' to '{to}'")]
+    InvalidTransition { task_id: Uuid, from: String, to: String },
+
+    #[error("Task was changed by another command, try again")]
+    Conflict { task_id: Uuid },
+}
+
+impl BulkItemError {
+    pub fn task_id(&self) -> Uuid {
+        match self {
+            BulkItemError::NotFound { task_id }
+            | BulkItemError::InvalidTransition { task_id, .. }
+            | BulkItemError::Conflict { task_id } => *task_id,
+        }
+    }
+}
+
+/// What a user who can see a task may do with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskAccess {
+    ReadWrite,
+    ReadOnly,
 }
 
 pub struct TaskService {
     task_repository: Arc<dyn TaskRepository>,
+    status_repository: Option<Arc<dyn StatusRepository>>,
+    workspace_repository: Option<Arc<dyn WorkspaceRepository>>,
+    share_repository: Option<Arc<dyn TaskShareRepository>>,
+    event_repository: Option<Arc<dyn TaskEventRepository>>,
+    webhook_service: Option<Arc<WebhookService>>,
+    event_bus: Option<EventBus>,
 }
 
 impl TaskService {
     #[allow(dead_code)]
     pub fn new(task_repository: Arc<dyn TaskRepository>) -> Self {
-        Self { task_repository }
+        Self {
+            task_repository,
+            status_repository: None,
+            workspace_repository: None,
+            share_repository: None,
+            event_repository: None,
+            webhook_service: None,
+            event_bus: None,
+        }
+    }
+
+    /// Enable user-defined statuses and transition rules
+    pub fn with_status_repository(mut self, status_repository: Arc<dyn StatusRepository>) -> Self {
+        self.status_repository = Some(status_repository);
+        self
+    }
+
+    /// Let workspace members see and work on the tasks shared in their workspaces
+    pub fn with_workspace_repository(mut self, workspace_repository: Arc<dyn WorkspaceRepository>) -> Self {
+        self.workspace_repository = Some(workspace_repository);
+        self
+    }
+
+    /// Let owners share single tasks with other users
+    pub fn with_share_repository(mut self, share_repository: Arc<dyn TaskShareRepository>) -> Self {
+        self.share_repository = Some(share_repository);
+        self
+    }
+
+    /// Record who created, completed and shared tasks for the activity feed
+    pub fn with_event_repository(mut self, event_repository: Arc<dyn TaskEventRepository>) -> Self {
+        self.event_repository = Some(event_repository);
+        self
+    }
+
+    /// Notify the task owner's webhooks when tasks are created, completed or deleted
+    pub fn with_webhook_service(mut self, webhook_service: Arc<WebhookService>) -> Self {
+        self.webhook_service = Some(webhook_service);
+        self
+    }
+
+    /// Publish task changes to the live API streams
+    pub fn with_event_bus(mut self, event_bus: EventBus) -> Self {
+        self.event_bus = Some(event_bus);
+        self
     }
 
     /// Create a new task with comprehensive validation and business rules
@@ -57,6 +187,70 @@ impl TaskService {
             user_id, request.title
         );
 
+        self.validate_new_task(user_id, &request).await?;
+
+        let task = self
+            .task_repository
+            .store(request, user_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to create task in repository: {}", e);
+                TaskServiceError::RepositoryError(e)
+            })?;
+        self.task_created(&task, user_id).await;
+
+        info!("Successfully created task with ID: {}", task.id);
+        Ok(task)
+    }
+
+    /// Create several tasks, reporting the outcome of each in the order given.
+    /// Invalid requests are reported and skipped; the valid ones are stored in
+    /// one transaction, so a database failure stores none of them.
+    pub async fn bulk_create_tasks(
+        &self,
+        user_id: &Uuid,
+        requests: Vec<StoreTaskRequest>,
+    ) -> Result<Vec<Result<Task, TaskServiceError>>, TaskServiceError> {
+        info!("Bulk creating {} tasks for user {}", requests.len(), user_id);
+
+        // The validation error of each request, `None` for the valid ones
+        let mut rejections = Vec::with_capacity(requests.len());
+        let mut valid = Vec::new();
+        for request in requests {
+            match self.validate_new_task(user_id, &request).await {
+                Ok(()) => {
+                    valid.push(request);
+                    rejections.push(None);
+                }
+                Err(e) => rejections.push(Some(e)),
+            }
+        }
+
+        let stored = self.task_repository.store_many(valid, user_id).await?;
+        for task in &stored {
+            self.task_created(task, user_id).await;
+        }
+
+        let mut stored = stored.into_iter();
+        let results: Vec<Result<Task, TaskServiceError>> = rejections
+            .into_iter()
+            .map(|rejection| match rejection {
+                Some(e) => Err(e),
+                None => stored.next().ok_or(TaskServiceError::InternalError(anyhow::anyhow!(
+                    "Repository stored fewer tasks than requested"
+                ))),
+            })
+            .collect();
+        info!(
+            "Bulk create completed: {}/{} tasks created",
+            results.iter().filter(|result| result.is_ok()).count(),
+            results.len()
+        );
+        Ok(results)
+    }
+
+    /// Business rules a new task has to pass before it is stored
+    async fn validate_new_task(&self, user_id: &Uuid, request: &StoreTaskRequest) -> Result<(), TaskServiceError> {
         // Validate request
         request
             .validate()
@@ -73,17 +267,29 @@ impl TaskService {
             }
         }
 
-        let task = self
-            .task_repository
-            .store(request, user_id)
-            .await
-            .map_err(|e| {
-                error!("Failed to create task in repository: {}", e);
-                TaskServiceError::RepositoryError(e)
-            })?;
+        // Business rule: a task cannot start after it is due
+        if let (Some(start_date), Some(due_date)) = (request.start_date, request.due_date) {
+            if start_date > due_date {
+                return Err(TaskServiceError::ValidationError {
+                    message: "Start date must not be after the due date".to_string(),
+                });
+            }
+        }
 
-        info!("Successfully created task with ID: {}", task.id);
-        Ok(task)
+        if let Some(workspace_id) = request.workspace_id {
+            if !self.is_workspace_member(&workspace_id, user_id).await? {
+                warn!("Access denied: User {} is not a member of workspace {}", user_id, workspace_id);
+                return Err(TaskServiceError::AccessDenied);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn task_created(&self, task: &Task, user_id: &Uuid) {
+        self.record_event(&task.id, user_id, TaskEventKind::Created, None).await;
+        self.notify_webhooks(task, user_id, WebhookEvent::TaskCreated).await;
+        self.publish_change(task, user_id, TaskChangeKind::Created).await;
     }
 
     /// Get tasks for a user with advanced filtering and performance optimization
@@ -97,12 +303,47 @@ impl TaskService {
             user_id, filter
         );
 
-        let tasks = match filter {
+        let shared_ids = if filter.owned_only {
+            Vec::new()
+        } else {
+            self.shared_task_ids(user_id).await?
+        };
+        if filter.is_paginated() && !filter.has_conditions() && shared_ids.is_empty() {
+            // Only the user's own tasks, unfiltered: the database pages them
+            let page = PageRequest {
+                sort: filter.sort.unwrap_or_default(),
+                after: filter.after,
+                offset: 0,
+                limit: filter.limit,
+            };
+            let page = self
+                .task_repository
+                .find_by_user_id_paginated(user_id, page)
+                .await
+                .map_err(|e| match e {
+                    TaskRepositoryError::NotFound => Self::stale_cursor(),
+                    e => e.into(),
+                })?;
+            debug!("Retrieved {} of {} tasks for user: {}", page.items.len(), page.total, user_id);
+            return Ok(page.items);
+        }
+
+        let mut tasks = match filter {
             TaskFilter {
                 status: Some(status),
                 priority: None,
                 overdue_only: false,
+                due_today: false,
                 search_term: None,
+                near: None,
+                context: None,
+                started_only: false,
+                owned_only: _,
+                due_before: None,
+                due_after: None,
+                sort: _,
+                after: _,
+                limit: _,
             } => {
                 // Optimized path for status-only filtering
                 self.task_repository.find_by_status(user_id, status).await?
@@ -111,16 +352,107 @@ impl TaskService {
                 status: None,
                 priority: None,
                 overdue_only: true,
+                due_today: false,
                 search_term: None,
+                near: None,
+                context: None,
+                started_only: false,
+                owned_only: _,
+                due_before: None,
+                due_after: None,
+                sort: _,
+                after: _,
+                limit: _,
             } => {
                 // Optimized path for overdue tasks
-                self.task_repository.find_overdue_by_user(user_id).await?
+                self.task_repository
+                    .find_overdue_by_user(user_id, calendar::start_of_day(calendar::today()))
+                    .await?
+            }
+            TaskFilter {
+                status: None,
+                priority: None,
+                overdue_only: false,
+                due_today: true,
+                search_term: None,
+                near: None,
+                context: None,
+                started_only,
+                owned_only: _,
+                due_before: None,
+                due_after: None,
+                sort: _,
+                after: _,
+                limit: _,
+            } => {
+                // Optimized path for today's agenda
+                let today = calendar::today();
+                let mut tasks = self
+                    .task_repository
+                    .find_due_between(user_id, calendar::start_of_day(today), calendar::start_of_day(today + chrono::Duration::days(1)))
+                    .await?;
+                if started_only {
+                    tasks.retain(Task::has_started);
+                }
+                tasks
             }
             TaskFilter {
                 status: None,
                 priority: None,
                 overdue_only: false,
+                due_today: false,
+                search_term: None,
+                near: None,
+                context: Some(ref context),
+                started_only,
+                owned_only: _,
+                due_before: None,
+                due_after: None,
+                sort: _,
+                after: _,
+                limit: _,
+            } => {
+                // Optimized path for context lists, which are served by an index
+                let mut tasks = self.task_repository.find_by_context(user_id, context).await?;
+                if started_only {
+                    tasks.retain(Task::has_started);
+                }
+                tasks
+            }
+            TaskFilter {
+                status: None,
+                priority: None,
+                overdue_only: false,
+                due_today: false,
+                search_term: None,
+                near: None,
+                context: None,
+                started_only: true,
+                owned_only: _,
+                due_before: None,
+                due_after: None,
+                sort: _,
+                after: _,
+                limit: _,
+            } => {
+                // Optimized path for the default list of actionable tasks
+                self.task_repository.find_started_by_user(user_id).await?
+            }
+            TaskFilter {
+                status: None,
+                priority: None,
+                overdue_only: false,
+                due_today: false,
                 search_term: Some(ref term),
+                near: None,
+                context: None,
+                started_only: false,
+                owned_only: _,
+                due_before: None,
+                due_after: None,
+                sort: _,
+                after: _,
+                limit: _,
             } => {
                 // Optimized path for search
                 self.task_repository.search_tasks(user_id, term).await?
@@ -133,28 +465,167 @@ impl TaskService {
             }
         };
 
+        if !shared_ids.is_empty() {
+            tasks.extend(self.shared_tasks(&shared_ids, &filter).await?);
+        }
+        if filter.is_paginated() {
+            self.paginate(&mut tasks, &filter).await?;
+        }
+
         debug!("Retrieved {} tasks for user: {}", tasks.len(), user_id);
         Ok(tasks)
     }
 
-    /// Get a specific task with authorization check
+    /// Sort the tasks, drop those up to and including the cursor task and
+    /// apply the limit
+    async fn paginate(&self, tasks: &mut Vec<Task>, filter: &TaskFilter) -> Result<(), TaskServiceError> {
+        let sort = filter.sort.unwrap_or_default();
+        tasks.sort_by(|a, b| sort.compare(a, b));
+
+        if let Some(after) = filter.after {
+            let cursor = self
+                .task_repository
+                .find_by_id(&after)
+                .await?
+                .ok_or_else(Self::stale_cursor)?;
+            tasks.retain(|task| sort.compare(task, &cursor) == std::cmp::Ordering::Greater);
+        }
+        if let Some(limit) = filter.limit {
+            tasks.truncate(limit);
+        }
+        Ok(())
+    }
+
+    fn stale_cursor() -> TaskServiceError {
+        TaskServiceError::ValidationError {
+            message: "The cursor refers to a task that no longer exists".to_string(),
+        }
+    }
+
+    /// IDs of the other users' tasks shared with the user
+    async fn shared_task_ids(&self, user_id: &Uuid) -> Result<Vec<Uuid>, TaskServiceError> {
+        let Some(share_repository) = &self.share_repository else {
+            return Ok(Vec::new());
+        };
+
+        Ok(share_repository
+            .find_by_user_id(user_id)
+            .await?
+            .into_iter()
+            .map(|share| share.task_id)
+            .collect())
+    }
+
+    /// The shared tasks with the given IDs that match the filter
+    async fn shared_tasks(&self, ids: &[Uuid], filter: &TaskFilter) -> Result<Vec<Task>, TaskServiceError> {
+        let mut tasks = self.task_repository.find_by_ids(ids).await?;
+        self.apply_complex_filter(&mut tasks, filter);
+        Ok(tasks)
+    }
+
+    /// The user's own tasks in list order, fetched as they are consumed, for
+    /// exports too large to hold in memory
+    pub fn stream_tasks(&self, user_id: &Uuid) -> impl Stream<Item = Result<Task, TaskServiceError>> + '_ {
+        self.task_repository
+            .stream_by_user_id(user_id)
+            .map(|task| task.map_err(TaskServiceError::from))
+    }
+
+    /// Get a specific task with authorization check: the creator, every member
+    /// of the task's workspace and the users it is shared with have access
     pub async fn get_task(&self, user_id: &Uuid, task_id: &Uuid) -> Result<Task, TaskServiceError> {
+        self.get_task_with_access(user_id, task_id).await.map(|(task, _)| task)
+    }
+
+    /// Like `get_task`, but read-only shares are refused
+    async fn get_writable_task(&self, user_id: &Uuid, task_id: &Uuid) -> Result<Task, TaskServiceError> {
+        match self.get_task_with_access(user_id, task_id).await? {
+            (task, TaskAccess::ReadWrite) => Ok(task),
+            (_, TaskAccess::ReadOnly) => Err(TaskServiceError::ReadOnlyTask),
+        }
+    }
+
+    async fn get_task_with_access(&self, user_id: &Uuid, task_id: &Uuid) -> Result<(Task, TaskAccess), TaskServiceError> {
         let task = self
             .task_repository
             .find_by_id(task_id)
             .await?
             .ok_or(TaskServiceError::TaskNotFound)?;
 
+        if task.user_id == *user_id {
+            return Ok((task, TaskAccess::ReadWrite));
+        }
+
         // Authorization check
+        if let Some(workspace_id) = task.workspace_id {
+            if self.is_workspace_member(&workspace_id, user_id).await? {
+                return Ok((task, TaskAccess::ReadWrite));
+            }
+        }
+        if let Some(share_repository) = &self.share_repository {
+            if let Some(share) = share_repository.find(task_id, user_id).await? {
+                let access = if share.read_only { TaskAccess::ReadOnly } else { TaskAccess::ReadWrite };
+                return Ok((task, access));
+            }
+        }
+
+        warn!(
+            "Access denied: User {} tried to access task {} owned by {}",
+            user_id, task_id, task.user_id
+        );
+        Err(TaskServiceError::AccessDenied)
+    }
+
+    /// Share one of the user's own tasks with another user
+    pub async fn share_task(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        with_user_id: &Uuid,
+        read_only: bool,
+    ) -> Result<TaskShare, TaskServiceError> {
+        let Some(share_repository) = &self.share_repository else {
+            return Err(TaskServiceError::ValidationError {
+                message: "Task sharing is not enabled".to_string(),
+            });
+        };
+
+        let task = self.get_task(user_id, task_id).await?;
         if task.user_id != *user_id {
-            warn!(
-                "Access denied: User {} tried to access task {} owned by {}",
-                user_id, task_id, task.user_id
-            );
+            warn!("User {} tried to share task {} owned by {}", user_id, task_id, task.user_id);
             return Err(TaskServiceError::AccessDenied);
         }
+        if with_user_id == user_id {
+            return Err(TaskServiceError::ValidationError {
+                message: "You cannot share a task with yourself".to_string(),
+            });
+        }
 
-        Ok(task)
+        let share = share_repository.store(task_id, with_user_id, read_only).await?;
+        self.record_event(task_id, user_id, TaskEventKind::Shared, Some(with_user_id)).await;
+        info!("Shared task {} with user {} (read-only: {})", task_id, with_user_id, read_only);
+        Ok(share)
+    }
+
+    /// Resolve a task number or UUID prefix to the UUID of one of the user's tasks
+    pub async fn resolve_task_ref(&self, user_id: &Uuid, task_ref: &TaskRef) -> Result<Uuid, TaskServiceError> {
+        match task_ref {
+            TaskRef::Id(id) => Ok(*id),
+            TaskRef::Number(number) => self
+                .task_repository
+                .find_by_number(user_id, *number)
+                .await?
+                .map(|task| task.id)
+                .ok_or(TaskServiceError::TaskNotFound),
+            TaskRef::Prefix(prefix) => {
+                let matches = self.task_repository.find_by_id_prefix(user_id, prefix, 2).await?;
+                match matches.as_slice() {
+                    [] => Err(TaskServiceError::TaskNotFound),
+                    [task] => Ok(task.id),
+                    _ => Err(TaskServiceError::AmbiguousTaskId { prefix: prefix.clone() }),
+                }
+            }
+        }
     }
 
     /// Update a task with authorization and validation
@@ -162,7 +633,7 @@ impl TaskService {
         &self,
         user_id: &Uuid,
         task_id: &Uuid,
-        updates: UpdateTaskRequest,
+        mut updates: UpdateTaskRequest,
     ) -> Result<Task, TaskServiceError> {
         info!("Updating task {} for user {}", task_id, user_id);
 
@@ -182,14 +653,43 @@ impl TaskService {
             }
         }
 
+        // Shared tasks follow their creator's workflow and stay owned by them
+        let task = self.get_writable_task(user_id, task_id).await?;
+
+        // Business rule: a task cannot start after it is due, counting the
+        // date the update leaves as it is
+        let start_date = updates.start_date.or(task.start_date);
+        let due_date = updates.due_date.or(task.due_date);
+        if let (Some(start_date), Some(due_date)) = (start_date, due_date) {
+            if start_date > due_date {
+                return Err(TaskServiceError::ValidationError {
+                    message: "Start date must not be after the due date".to_string(),
+                });
+            }
+        }
+
+        if let Some(status) = updates.status {
+            let workflow = self.get_workflow(&task.user_id).await?;
+            Self::ensure_transition(&workflow, task.status, status)?;
+        }
+        // The checks above hold only for the version just read
+        updates.version.get_or_insert(task.version);
+
         let updated_task = self
             .task_repository
-            .update(task_id, user_id, updates)
+            .update(task_id, &task.user_id, updates)
             .await
             .map_err(|e| match e {
                 TaskRepositoryError::NotFound => TaskServiceError::TaskNotFound,
                 other => TaskServiceError::RepositoryError(other),
             })?;
+        if updated_task.status == TaskStatus::Completed && task.status != TaskStatus::Completed {
+            self.record_event(task_id, user_id, TaskEventKind::Completed, None).await;
+            self.notify_webhooks(&updated_task, user_id, WebhookEvent::TaskCompleted).await;
+            self.publish_change(&updated_task, user_id, TaskChangeKind::Completed).await;
+        } else {
+            self.publish_change(&updated_task, user_id, TaskChangeKind::Updated).await;
+        }
 
         info!("Successfully updated task: {}", task_id);
         Ok(updated_task)
@@ -203,9 +703,26 @@ impl TaskService {
     ) -> Result<bool, TaskServiceError> {
         info!("Deleting task {} for user {}", task_id, user_id);
 
+        // Webhook payloads and change recipients need the task, which is gone once deleted
+        let live = self.event_bus.as_ref().is_some_and(EventBus::has_subscribers);
+        let task = if self.webhook_service.is_some() || live {
+            self.task_repository.find_by_id(task_id).await?
+        } else {
+            None
+        };
+        let recipients = match &task {
+            Some(task) => self.change_recipients(task).await,
+            None => None,
+        };
         let deleted = self.task_repository.delete(task_id, user_id).await?;
 
         if deleted {
+            if let Some(task) = task {
+                self.notify_webhooks(&task, user_id, WebhookEvent::TaskDeleted).await;
+                if let Some(recipients) = recipients {
+                    self.send_change(&task, user_id, TaskChangeKind::Deleted, recipients);
+                }
+            }
             info!("Successfully deleted task: {}", task_id);
         } else {
             warn!("Task not found or access denied: {}", task_id);
@@ -214,6 +731,83 @@ impl TaskService {
         Ok(deleted)
     }
 
+    /// Rank a task directly before or after another one. Only the moved task
+    /// gets a new rank unless its neighbours leave no room, in which case the
+    /// whole list is renumbered once.
+    pub async fn move_task(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        anchor_id: &Uuid,
+        position: MovePosition,
+    ) -> Result<Task, TaskServiceError> {
+        if task_id == anchor_id {
+            return Err(TaskServiceError::ValidationError {
+                message: "A task cannot be moved relative to itself".to_string(),
+            });
+        }
+
+        // Ranks order the owner's list, so only tasks in it can be moved or
+        // moved next to; shared ones stay where their owner put them
+        let task = self.get_task(user_id, task_id).await?;
+        let anchor = self.get_task(user_id, anchor_id).await?;
+        if task.user_id != *user_id || anchor.user_id != *user_id {
+            return Err(TaskServiceError::ValidationError {
+                message: "Only your own tasks can be reordered; tasks shared with you keep their owner's order".to_string(),
+            });
+        }
+
+        let mut ordered = self.task_repository.find_by_user_id(user_id).await?;
+        ordered.retain(|t| t.id != task.id);
+        let anchor_index = ordered
+            .iter()
+            .position(|t| t.id == *anchor_id)
+            .ok_or(TaskServiceError::TaskNotFound)?;
+        let index = match position {
+            MovePosition::Before => anchor_index,
+            MovePosition::After => anchor_index + 1,
+        };
+
+        // Unranked tasks sort after every ranked one, so an unranked successor
+        // is no constraint, but an unranked predecessor needs a rank first
+        let previous = index.checked_sub(1).map(|i| ordered[i].sort_order);
+        let next = ordered.get(index).and_then(|t| t.sort_order);
+        let sort_order = match previous {
+            Some(None) => None,
+            Some(Some(previous)) => sort_order_between(Some(previous), next),
+            None => sort_order_between(None, next),
+        };
+
+        let moved = match sort_order {
+            Some(sort_order) => {
+                self.task_repository
+                    .set_sort_order(task_id, user_id, sort_order)
+                    .await?
+            }
+            None => {
+                info!("Renumbering tasks for user {}", user_id);
+                let mut ids: Vec<Uuid> = ordered.iter().map(|t| t.id).collect();
+                ids.insert(index, task.id);
+                self.task_repository.renumber(user_id, &ids).await?;
+                self.get_task(user_id, task_id).await?
+            }
+        };
+
+        self.publish_change(&moved, user_id, TaskChangeKind::Updated).await;
+        info!("Moved task {} for user {}", task_id, user_id);
+        Ok(moved)
+    }
+
+    /// Pin a task to the top of task lists, or unpin it
+    pub async fn set_pinned(&self, user_id: &Uuid, task_id: &Uuid, pinned: bool) -> Result<Task, TaskServiceError> {
+        let task = self.get_writable_task(user_id, task_id).await?;
+
+        let task = self.task_repository.set_pinned(task_id, &task.user_id, pinned).await?;
+        self.publish_change(&task, user_id, TaskChangeKind::Updated).await;
+        info!("{} task {} for user {}", if pinned { "Pinned" } else { "Unpinned" }, task_id, user_id);
+        Ok(task)
+    }
+
     /// Complete a task (optimized operation)
     pub async fn complete_task(
         &self,
@@ -222,6 +816,10 @@ impl TaskService {
     ) -> Result<Task, TaskServiceError> {
         info!("Completing task {} for user {}", task_id, user_id);
 
+        let task = self.get_writable_task(user_id, task_id).await?;
+        let workflow = self.get_workflow(&task.user_id).await?;
+        Self::ensure_transition(&workflow, task.status, TaskStatus::Completed)?;
+
         // Use general update method for task completion
         let update_request = UpdateTaskRequest {
             title: None, // Will be ignored in specialized update
@@ -229,16 +827,26 @@ impl TaskService {
             status: Some(TaskStatus::Completed),
             priority: None, // Will be ignored
             due_date: None,
+            start_date: None,
+            estimate_minutes: None,
+            client: None,
+            context: None,
+            location: None,
+            coordinates: None,
+            version: Some(task.version),
         };
 
         let completed_task = self
             .task_repository
-            .update(task_id, user_id, update_request)
+            .update(task_id, &task.user_id, update_request)
             .await
             .map_err(|e| match e {
                 TaskRepositoryError::NotFound => TaskServiceError::TaskNotFound,
                 other => TaskServiceError::RepositoryError(other),
             })?;
+        self.record_event(task_id, user_id, TaskEventKind::Completed, None).await;
+        self.notify_webhooks(&completed_task, user_id, WebhookEvent::TaskCompleted).await;
+        self.publish_change(&completed_task, user_id, TaskChangeKind::Completed).await;
 
         info!("Successfully completed task: {}", task_id);
         Ok(completed_task)
@@ -249,23 +857,30 @@ impl TaskService {
     pub async fn get_overdue_tasks(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskServiceError> {
         debug!("Fetching overdue tasks for user: {}", user_id);
 
-        let tasks = self.task_repository.find_overdue_by_user(user_id).await?;
+        let tasks = self
+            .task_repository
+            .find_overdue_by_user(user_id, calendar::start_of_day(calendar::today()))
+            .await?;
 
         debug!("Found {} overdue tasks for user: {}", tasks.len(), user_id);
         Ok(tasks)
     }
 
-    /// Get comprehensive task statistics for a user
-    #[allow(dead_code)]
+    /// Get comprehensive task statistics for a user, over the tasks created
+    /// or completed in the ranges of `filter`
     pub async fn get_task_statistics(
         &self,
         user_id: &Uuid,
+        filter: &StatisticsFilter,
     ) -> Result<TaskStatistics, TaskServiceError> {
         debug!("Calculating task statistics for user: {}", user_id);
 
-        // Calculate stats from all tasks
-        let tasks = self.task_repository.find_by_user_id(user_id).await?;
-        let stats = self.calculate_statistics(&tasks);
+        let due_before = calendar::start_of_day(calendar::today());
+        let stale_before = Utc::now() - chrono::Duration::days(STALE_AFTER_DAYS);
+        let stats = self
+            .task_repository
+            .get_user_statistics(user_id, due_before, stale_before, filter)
+            .await?;
 
         debug!(
             "Statistics for user {}: {} total, {} completed, {} overdue",
@@ -275,104 +890,290 @@ impl TaskService {
         Ok(stats)
     }
 
-    /// Bulk operations for better performance when dealing with multiple tasks
-    #[allow(dead_code)]
-    pub async fn bulk_update_status(
-        &self,
-        user_id: &Uuid,
-        task_ids: Vec<Uuid>,
-        new_status: TaskStatus,
-    ) -> Result<Vec<Task>, TaskServiceError> {
-        info!(
-            "Bulk updating status for {} tasks to {:?} for user {}",
-            task_ids.len(),
-            new_status,
-            user_id
-        );
-
-        let mut updated_tasks = Vec::new();
-        let mut failed_count = 0;
-        let total_count = task_ids.len();
-
-        for task_id in task_ids {
-            // Create minimal update request focusing only on status
-            let update_request = UpdateTaskRequest {
-                title: None, // Will use existing value in optimized update
-                description: None,
-                status: Some(new_status),
-                priority: None, // Will use existing value
-                due_date: None,
-            };
-
-            match self
-                .task_repository
-                .update(&task_id, user_id, update_request)
-                .await
-            {
-                Ok(task) => updated_tasks.push(task),
-                Err(e) => {
-                    warn!("Failed to update task {}: {}", task_id, e);
-                    failed_count += 1;
-                }
-            }
-        }
+    /// Remaining estimated work per day for the next `days` days
+    pub async fn get_workload(&self, user_id: &Uuid, days: u32) -> Result<Workload, TaskServiceError> {
+        debug!("Building {}-day workload for user: {}", days, user_id);
 
-        if failed_count > 0 {
-            if failed_count == total_count {
-                return Err(TaskServiceError::BulkOperationPartialFailure {
-                    failed_count,
-                    total_count,
-                });
-            }
-            warn!(
-                "Bulk operation partially failed: {}/{} operations failed",
-                failed_count, total_count
-            );
+        let mut workload = Workload::new(calendar::today(), days);
+        let mut tasks = self.task_repository.stream_by_user_id(user_id);
+        while let Some(task) = tasks.next().await {
+            workload.add(&task?);
         }
+        Ok(workload)
+    }
 
-        info!(
-            "Bulk update completed: {}/{} tasks updated successfully",
-            updated_tasks.len(),
-            total_count
-        );
-
-        Ok(updated_tasks)
+    /// Task counts per status, priority or context, over the tasks `filter` matches
+    pub async fn count_tasks_grouped(
+        &self,
+        user_id: &Uuid,
+        group_by: TaskGrouping,
+        filter: &StatisticsFilter,
+    ) -> Result<HashMap<TaskGroup, i64>, TaskServiceError> {
+        Ok(self.task_repository.count_grouped(user_id, group_by, filter).await?)
     }
 
-    /// Bulk delete multiple tasks
-    #[allow(dead_code)]
-    pub async fn bulk_delete_tasks(
+    /// Open and completed tasks per priority and per context, over the tasks
+    /// in the ranges of `filter`; each grouping is counted twice in SQL, once
+    /// limited to completed tasks
+    pub async fn get_detailed_statistics(
         &self,
         user_id: &Uuid,
-        task_ids: Vec<Uuid>,
-    ) -> Result<usize, TaskServiceError> {
+        filter: &StatisticsFilter,
+    ) -> Result<DetailedStatistics, TaskServiceError> {
+        let completed_filter = StatisticsFilter {
+            completed_after: Some(filter.completed_after.unwrap_or(DateTime::UNIX_EPOCH)),
+            ..*filter
+        };
+        Ok(DetailedStatistics {
+            by_priority: self.group_breakdown(user_id, TaskGrouping::Priority, filter, &completed_filter).await?,
+            by_context: self.group_breakdown(user_id, TaskGrouping::Context, filter, &completed_filter).await?,
+        })
+    }
+
+    async fn group_breakdown(
+        &self,
+        user_id: &Uuid,
+        grouping: TaskGrouping,
+        filter: &StatisticsFilter,
+        completed_filter: &StatisticsFilter,
+    ) -> Result<Vec<GroupBreakdown>, TaskServiceError> {
+        let totals = self.task_repository.count_grouped(user_id, grouping, filter).await?;
+        let completed = self.task_repository.count_grouped(user_id, grouping, completed_filter).await?;
+        Ok(DetailedStatistics::breakdown(totals, &completed))
+    }
+
+    /// Created and completed tasks of the seven days before `until`, and the
+    /// contexts the completed ones were in
+    pub async fn get_weekly_report(&self, user_id: &Uuid, until: DateTime<Utc>) -> Result<WeeklyReport, TaskServiceError> {
+        debug!("Building weekly report for user: {}", user_id);
+
+        let since = until - chrono::Duration::days(WeeklyReport::DAYS);
+        let throughput = self.task_repository.get_throughput(user_id, since, until).await?;
+        let filter = StatisticsFilter { completed_after: Some(since), completed_before: Some(until), ..Default::default() };
+        let contexts = self
+            .task_repository
+            .count_grouped(user_id, TaskGrouping::Context, &filter)
+            .await?
+            .into_iter()
+            .filter_map(|(group, tasks)| match group {
+                TaskGroup::Context(Some(context)) => Some(ContextTally { context, tasks }),
+                _ => None,
+            })
+            .collect();
+
+        Ok(WeeklyReport::new(since, until, &throughput, contexts))
+    }
+
+    /// Open tasks at the end of each of the last `days` local days, today included
+    pub async fn get_burndown(&self, user_id: &Uuid, days: u32) -> Result<Burndown, TaskServiceError> {
+        debug!("Building {}-day burndown for user: {}", days, user_id);
+
+        let last = calendar::today();
+        let first = last - chrono::Duration::days(i64::from(days.max(1)) - 1);
+        let (since, until) = (calendar::start_of_day(first), Utc::now());
+        let timezone = calendar::timezone();
+
+        let open_now = self
+            .task_repository
+            .get_user_statistics(user_id, until, until, &StatisticsFilter::default())
+            .await?
+            .open_tasks();
+        let created = self.task_repository.count_by_day(user_id, TaskTimestamp::Created, since, until, timezone).await?;
+        let completed = self.task_repository.count_by_day(user_id, TaskTimestamp::Completed, since, until, timezone).await?;
+
+        Ok(Burndown::new(first, last, open_now, &created, &completed))
+    }
+
+    /// When the open tasks would be done at the pace of the last `weeks` weeks
+    pub async fn get_forecast(&self, user_id: &Uuid, weeks: u32) -> Result<Forecast, TaskServiceError> {
+        debug!("Forecasting over {} weeks for user: {}", weeks, user_id);
+
+        let today = calendar::today();
+        let since = calendar::start_of_day(Forecast::first_day(weeks, today));
+        let until = Utc::now();
+        let timezone = calendar::timezone();
+
+        let open = self
+            .task_repository
+            .get_user_statistics(user_id, until, until, &StatisticsFilter::default())
+            .await?
+            .open_tasks();
+        let completed = self.task_repository.count_by_day(user_id, TaskTimestamp::Completed, since, until, timezone).await?;
+
+        Ok(Forecast::new(open, &completed, weeks, today))
+    }
+
+    /// Tasks completed on each local day of `year`, counted per day by the database
+    pub async fn get_heatmap(&self, user_id: &Uuid, year: i32) -> Result<Heatmap, TaskServiceError> {
+        let first_of = |year| {
+            NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| TaskServiceError::ValidationError {
+                message: format!("Year {} is out of range", year),
+            })
+        };
+        let since = calendar::start_of_day(first_of(year)?);
+        let until = calendar::start_of_day(first_of(year + 1)?);
+        let timezone = calendar::timezone();
+
+        let days = self.task_repository.count_by_day(user_id, TaskTimestamp::Completed, since, until, timezone).await?;
+        Ok(Heatmap { year, days })
+    }
+
+    /// Overdue tasks, own and shared, bucketed by how long ago they were due
+    pub async fn get_overdue_aging(&self, user_id: &Uuid) -> Result<OverdueAging, TaskServiceError> {
+        let tasks = self.get_tasks(user_id, TaskFilter::new().overdue_only()).await?;
+        Ok(OverdueAging::from_tasks(tasks, calendar::today()))
+    }
+
+    /// Current and longest run of local days with a completed task. Days are
+    /// cut in the user's timezone, so a DST change neither breaks nor extends
+    /// a run; Postgres counts them per day, so only one row per such day is read.
+    pub async fn get_completion_streak(&self, user_id: &Uuid) -> Result<CompletionStreak, TaskServiceError> {
+        let timezone = calendar::timezone();
+        let days = self
+            .task_repository
+            .count_by_day(user_id, TaskTimestamp::Completed, DateTime::UNIX_EPOCH, Utc::now(), timezone)
+            .await?;
+        Ok(CompletionStreak::from_days(&days, calendar::today()))
+    }
+
+    /// Open task counts per context
+    pub async fn get_context_counts(&self, user_id: &Uuid) -> Result<Vec<ContextCount>, TaskServiceError> {
+        Ok(self.task_repository.count_open_by_context(user_id).await?)
+    }
+
+    /// Digest of today's due, overdue and in-progress tasks
+    pub async fn get_daily_digest(&self, user_id: &Uuid) -> Result<DailyDigest, TaskServiceError> {
+        self.get_daily_digest_on(user_id, calendar::today()).await
+    }
+
+    /// Digest of the tasks due, overdue and in progress on `date`
+    pub async fn get_daily_digest_on(&self, user_id: &Uuid, date: NaiveDate) -> Result<DailyDigest, TaskServiceError> {
+        debug!("Building daily digest of {} for user: {}", date, user_id);
+
+        let tasks = self.task_repository.find_by_user_id(user_id).await?;
+        Ok(DailyDigest::from_tasks(&tasks, date))
+    }
+
+    /// Move several tasks to `new_status`, reporting the outcome of each task
+    /// in the order the IDs were given. Each task is only updated if its status
+    /// is still the one the transition was checked against, so a concurrent
+    /// change is reported as a conflict instead of being overwritten.
+    pub async fn bulk_update_status(
+        &self,
+        user_id: &Uuid,
+        task_ids: Vec<Uuid>,
+        new_status: TaskStatus,
+    ) -> Result<Vec<Result<Task, BulkItemError>>, TaskServiceError> {
         info!(
-            "Bulk deleting {} tasks for user {}",
+            "Bulk updating status for {} tasks to {:?} for user {}",
             task_ids.len(),
+            new_status,
             user_id
         );
 
-        let mut deleted_count = 0;
-        let mut failed_count = 0;
-        let total_count = task_ids.len();
+        let workflow = self.get_workflow(user_id).await?;
+        if !workflow.contains(new_status) {
+            return Err(TaskServiceError::UnknownStatus {
+                name: new_status.to_string(),
+            });
+        }
 
-        for task_id in task_ids {
-            match self.task_repository.delete(&task_id, user_id).await {
-                Ok(true) => deleted_count += 1,
-                Ok(false) => {
-                    warn!(
-                        "Task {} not found or access denied for user {}",
-                        task_id, user_id
-                    );
-                    failed_count += 1;
+        // Check every task first, then update the eligible ones in one statement
+        let tasks: HashMap<Uuid, Task> = self
+            .task_repository
+            .find_by_ids(&task_ids)
+            .await?
+            .into_iter()
+            .filter(|task| task.user_id == *user_id)
+            .map(|task| (task.id, task))
+            .collect();
+        let checked: Vec<Result<&Task, BulkItemError>> = task_ids
+            .iter()
+            .map(|task_id| match tasks.get(task_id) {
+                None => Err(BulkItemError::NotFound { task_id: *task_id }),
+                Some(task) if !workflow.can_transition(task.status, new_status) => Err(BulkItemError::InvalidTransition {
+                    task_id: *task_id,
+                    from: workflow.name(task.status),
+                    to: workflow.name(new_status),
+                }),
+                Some(task) => Ok(task),
+            })
+            .collect();
+
+        let expected: Vec<(Uuid, TaskStatus)> = checked
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|task| (task.id, task.status))
+            .collect();
+        let mut updated: HashMap<Uuid, Task> = self
+            .task_repository
+            .update_status_many(user_id, &expected, new_status)
+            .await?
+            .into_iter()
+            .map(|task| (task.id, task))
+            .collect();
+
+        let mut results = Vec::with_capacity(checked.len());
+        for result in checked {
+            let result = result.and_then(|task| {
+                updated.remove(&task.id).map(|updated| (task.status, updated)).ok_or(BulkItemError::Conflict { task_id: task.id })
+            });
+            match result {
+                Ok((previous, task)) => {
+                    self.status_changed(user_id, previous, &task).await;
+                    results.push(Ok(task));
                 }
                 Err(e) => {
-                    error!("Failed to delete task {}: {}", task_id, e);
-                    failed_count += 1;
+                    warn!("Bulk status update skipped task {}: {}", e.task_id(), e);
+                    results.push(Err(e));
                 }
             }
         }
 
+        info!(
+            "Bulk update completed: {}/{} tasks updated successfully",
+            results.iter().filter(|result| result.is_ok()).count(),
+            results.len()
+        );
+
+        Ok(results)
+    }
+
+    /// Record, deliver and publish a status change made by a bulk update
+    async fn status_changed(&self, user_id: &Uuid, previous: TaskStatus, task: &Task) {
+        if task.status == TaskStatus::Completed && previous != TaskStatus::Completed {
+            self.record_event(&task.id, user_id, TaskEventKind::Completed, None).await;
+            self.notify_webhooks(task, user_id, WebhookEvent::TaskCompleted).await;
+            self.publish_change(task, user_id, TaskChangeKind::Completed).await;
+        } else {
+            self.publish_change(task, user_id, TaskChangeKind::Updated).await;
+        }
+    }
+
+    /// Bulk delete multiple tasks in one statement; IDs of missing tasks or
+    /// of other users' tasks are skipped
+    #[allow(dead_code)]
+    pub async fn bulk_delete_tasks(
+        &self,
+        user_id: &Uuid,
+        task_ids: Vec<Uuid>,
+    ) -> Result<usize, TaskServiceError> {
+        info!(
+            "Bulk deleting {} tasks for user {}",
+            task_ids.len(),
+            user_id
+        );
+
+        let total_count = task_ids.len();
+        let deleted_count = self.task_repository.delete_bulk(&task_ids, user_id).await? as usize;
+        let failed_count = total_count.saturating_sub(deleted_count);
+        if failed_count > 0 {
+            warn!(
+                "{} of the tasks were not found or not owned by user {}",
+                failed_count, user_id
+            );
+        }
+
         if failed_count > 0 && deleted_count == 0 {
             return Err(TaskServiceError::BulkOperationPartialFailure {
                 failed_count,
@@ -388,6 +1189,52 @@ impl TaskService {
         Ok(deleted_count)
     }
 
+    /// Import tasks for a user, optionally replacing all existing tasks first.
+    /// The import is applied completely or, if any task fails, not at all.
+    ///
+    /// Imported tasks skip the future due date rule since exports routinely
+    /// contain historical data.
+    pub async fn import_tasks(
+        &self,
+        user_id: &Uuid,
+        requests: Vec<StoreTaskRequest>,
+        replace: bool,
+    ) -> Result<usize, TaskServiceError> {
+        info!(
+            "Importing {} tasks for user {} (replace: {})",
+            requests.len(),
+            user_id,
+            replace
+        );
+
+        for request in &requests {
+            request
+                .validate()
+                .map_err(|e| TaskServiceError::ValidationError {
+                    message: format!("Import validation failed for '{}': {}", request.title, e),
+                })?;
+        }
+
+        let existing: Vec<Uuid> = if replace {
+            self.task_repository.find_by_user_id(user_id).await?.iter().map(|task| task.id).collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut tx = self.task_repository.begin().await?;
+        tx.delete_bulk(&existing, user_id).await?;
+
+        let mut imported_count = 0;
+        for request in requests {
+            tx.store(request, user_id).await?;
+            imported_count += 1;
+        }
+        tx.commit().await?;
+
+        info!("Imported {} tasks for user {}", imported_count, user_id);
+        Ok(imported_count)
+    }
+
     /// Search tasks with performance optimization
     #[allow(dead_code)]
     pub async fn search_tasks(
@@ -419,8 +1266,382 @@ impl TaskService {
         Ok(tasks)
     }
 
+    /// Move a task to another status, enforcing the user's workflow rules
+    pub async fn change_status(
+        &self,
+        user_id: &Uuid,
+        task_id: &Uuid,
+        status: TaskStatus,
+    ) -> Result<Task, TaskServiceError> {
+        let updates = UpdateTaskRequest {
+            status: Some(status),
+            ..Default::default()
+        };
+        self.update_task(user_id, task_id, updates).await
+    }
+
+    // Status workflow management
+
+    /// Load the user's status workflow (built-in statuses only when custom statuses are disabled)
+    pub async fn get_workflow(&self, user_id: &Uuid) -> Result<StatusWorkflow, TaskServiceError> {
+        let Some(status_repository) = &self.status_repository else {
+            return Ok(StatusWorkflow::default());
+        };
+
+        let custom_statuses = status_repository.find_by_user_id(user_id).await?;
+        let transitions = status_repository.find_transitions(user_id).await?;
+
+        Ok(StatusWorkflow::new(custom_statuses, transitions))
+    }
+
+    /// Define a new custom status for the user
+    pub async fn create_status(
+        &self,
+        user_id: &Uuid,
+        name: &str,
+    ) -> Result<CustomStatus, TaskServiceError> {
+        let status_repository = self.require_status_repository()?;
+
+        let request = StoreStatusRequest {
+            name: name.trim().to_string(),
+        };
+        request
+            .validate()
+            .map_err(|e| TaskServiceError::ValidationError {
+                message: format!("Status validation failed: {}", e),
+            })?;
+
+        // Built-in names cannot be shadowed
+        if StatusWorkflow::default().parse(&request.name).is_some() {
+            return Err(TaskServiceError::ValidationError {
+                message: format!("'{}' is a built-in status", request.name),
+            });
+        }
+
+        let status = status_repository.store(user_id, request).await?;
+        info!("Created status '{}' ({}) for user {}", status.name, status.code, user_id);
+        Ok(status)
+    }
+
+    /// Create whichever of `names` the user has no status for yet, and return
+    /// the resulting workflow
+    pub async fn ensure_statuses(&self, user_id: &Uuid, names: &[String]) -> Result<StatusWorkflow, TaskServiceError> {
+        let workflow = self.get_workflow(user_id).await?;
+        let missing: Vec<&String> = names.iter().filter(|name| workflow.parse(name).is_none()).collect();
+        if missing.is_empty() {
+            return Ok(workflow);
+        }
+
+        for name in missing {
+            self.create_status(user_id, name).await?;
+        }
+        self.get_workflow(user_id).await
+    }
+
+    /// Remove a custom status that is no longer used by any task
+    pub async fn delete_status(&self, user_id: &Uuid, name: &str) -> Result<(), TaskServiceError> {
+        let status_repository = self.require_status_repository()?;
+        let workflow = self.get_workflow(user_id).await?;
+
+        let code = match workflow.parse(name) {
+            Some(TaskStatus::Custom(code)) => code,
+            Some(_) => {
+                return Err(TaskServiceError::ValidationError {
+                    message: "Built-in statuses cannot be removed".to_string(),
+                })
+            }
+            None => {
+                return Err(TaskServiceError::UnknownStatus {
+                    name: name.to_string(),
+                })
+            }
+        };
+
+        let task_count = status_repository.count_tasks_with_status(user_id, code).await?;
+        if task_count > 0 {
+            return Err(TaskServiceError::StatusInUse {
+                name: name.to_string(),
+                task_count,
+            });
+        }
+
+        status_repository.delete(user_id, code).await?;
+        info!("Deleted status '{}' for user {}", name, user_id);
+        Ok(())
+    }
+
+    /// Allow (or, with `allowed = false`, forbid) moving tasks between two statuses
+    pub async fn set_transition(
+        &self,
+        user_id: &Uuid,
+        from: &str,
+        to: &str,
+        allowed: bool,
+    ) -> Result<StatusTransition, TaskServiceError> {
+        let status_repository = self.require_status_repository()?;
+        let workflow = self.get_workflow(user_id).await?;
+
+        let transition = StatusTransition {
+            from_status: Self::resolve_status(&workflow, from)?,
+            to_status: Self::resolve_status(&workflow, to)?,
+        };
+
+        if allowed {
+            status_repository.add_transition(user_id, transition).await?;
+        } else {
+            status_repository.remove_transition(user_id, transition).await?;
+        }
+
+        Ok(transition)
+    }
+
+    /// Tasks shared in a workspace the user is a member of
+    pub async fn get_workspace_tasks(&self, user_id: &Uuid, workspace_id: &Uuid) -> Result<Vec<Task>, TaskServiceError> {
+        if !self.is_workspace_member(workspace_id, user_id).await? {
+            warn!("Access denied: User {} is not a member of workspace {}", user_id, workspace_id);
+            return Err(TaskServiceError::AccessDenied);
+        }
+
+        let tasks = self.task_repository.find_by_workspace(workspace_id).await?;
+        debug!("Retrieved {} tasks for workspace: {}", tasks.len(), workspace_id);
+        Ok(tasks)
+    }
+
+    /// Activity on the tasks the user can see, or on the tasks of one of their
+    /// workspaces, oldest first. Without `since`, the feed starts where the
+    /// user last looked; looking moves that point to now.
+    pub async fn get_activity(
+        &self,
+        user_id: &Uuid,
+        workspace_id: Option<&Uuid>,
+        since: Option<chrono::DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<ActivityFeed, TaskServiceError> {
+        let Some(event_repository) = &self.event_repository else {
+            return Err(TaskServiceError::ValidationError {
+                message: "The activity feed is not enabled".to_string(),
+            });
+        };
+        if let Some(workspace_id) = workspace_id {
+            if !self.is_workspace_member(workspace_id, user_id).await? {
+                warn!("Access denied: User {} is not a member of workspace {}", user_id, workspace_id);
+                return Err(TaskServiceError::AccessDenied);
+            }
+        }
+
+        let now = Utc::now();
+        let last_seen = event_repository.last_seen(user_id).await?;
+        let since = since.or(last_seen);
+        let mut events = match workspace_id {
+            Some(workspace_id) => event_repository.find_by_workspace(workspace_id, since, limit).await?,
+            None => event_repository.find_visible_to(user_id, since, limit).await?,
+        };
+        events.reverse();
+        event_repository.mark_seen(user_id, now).await?;
+
+        debug!("Retrieved {} activity events for user: {}", events.len(), user_id);
+        Ok(ActivityFeed { events, last_seen })
+    }
+
+    /// Stream a change another process wrote to the database, such as a CLI
+    /// command run while the API serves. Changes made through this service
+    /// were published when they were made and are skipped.
+    pub async fn relay_change(&self, change: &TaskRowChange) -> Result<(), TaskServiceError> {
+        let Some(event_bus) = self.event_bus.as_ref().filter(|bus| bus.has_subscribers()) else {
+            return Ok(());
+        };
+        if change.is_local() {
+            return Ok(());
+        }
+
+        let (kind, task) = match change.operation {
+            RowOperation::Delete => (TaskChangeKind::Deleted, None),
+            operation => {
+                // Gone again before the notification arrived
+                let Some(task) = self.task_repository.find_by_id(&change.task_id).await? else {
+                    return Ok(());
+                };
+                let kind = match operation {
+                    RowOperation::Insert => TaskChangeKind::Created,
+                    _ if change.completed => TaskChangeKind::Completed,
+                    _ => TaskChangeKind::Updated,
+                };
+                (kind, Some(task))
+            }
+        };
+
+        // Shares are deleted along with the task, so only the owner and the
+        // workspace hear about a deletion
+        let recipients = self.recipients_of(&change.task_id, &change.user_id, change.workspace_id).await;
+        event_bus.publish(TaskChange {
+            kind,
+            task_id: change.task_id,
+            task,
+            actor_id: None,
+            occurred_at: Utc::now(),
+            recipients,
+        });
+        Ok(())
+    }
+
+    /// Relay every change `listener` receives until the listener fails
+    pub async fn relay_changes(&self, mut listener: ChangeListener) -> Result<(), sqlx::Error> {
+        loop {
+            let change = listener.recv().await?;
+            if let Err(e) = self.relay_change(&change).await {
+                warn!("Failed to relay the change of task {}: {}", change.task_id, e);
+            }
+        }
+    }
+
+    /// Totals, per-member counts and overdue breakdown of a workspace the user
+    /// is a member of; member completions are counted from `completed_since`
+    pub async fn get_workspace_statistics(
+        &self,
+        user_id: &Uuid,
+        workspace_id: &Uuid,
+        completed_since: Option<chrono::DateTime<Utc>>,
+    ) -> Result<(TaskStatistics, Vec<MemberTaskCounts>, OverdueBreakdown), TaskServiceError> {
+        if !self.is_workspace_member(workspace_id, user_id).await? {
+            warn!("Access denied: User {} is not a member of workspace {}", user_id, workspace_id);
+            return Err(TaskServiceError::AccessDenied);
+        }
+
+        let due_before = calendar::start_of_day(calendar::today());
+        let stale_before = Utc::now() - chrono::Duration::days(STALE_AFTER_DAYS);
+        let totals = self
+            .task_repository
+            .get_workspace_statistics(workspace_id, due_before, stale_before)
+            .await?;
+        let members = self
+            .task_repository
+            .get_workspace_member_counts(workspace_id, due_before, completed_since)
+            .await?;
+        let overdue = self
+            .task_repository
+            .get_workspace_overdue_breakdown(workspace_id, due_before)
+            .await?;
+
+        Ok((totals, members, overdue))
+    }
+
     // Private helper methods
 
+    /// Log a task event; the change it describes has already been made, so a
+    /// failure here is only reported
+    async fn record_event(&self, task_id: &Uuid, actor_id: &Uuid, kind: TaskEventKind, subject_id: Option<&Uuid>) {
+        if let Some(event_repository) = &self.event_repository {
+            if let Err(e) = event_repository.store(task_id, actor_id, kind, subject_id).await {
+                warn!("Failed to record {} event for task {}: {}", kind, task_id, e);
+            }
+        }
+    }
+
+    /// Deliver a task event to the owner's webhooks; like `record_event`, a
+    /// failed delivery never fails the change itself, and the delivery runs in
+    /// the background so it never holds up the change either
+    async fn notify_webhooks(&self, task: &Task, actor_id: &Uuid, event: WebhookEvent) {
+        if let Some(webhook_service) = &self.webhook_service {
+            let data = serde_json::json!({
+                "title": task.title,
+                "actor_id": actor_id,
+                "task": task,
+            });
+            webhook_service.dispatch_to(&task.user_id, event, data).await;
+        }
+    }
+
+    /// Push a change to the live streams of everyone with access to the task
+    async fn publish_change(&self, task: &Task, actor_id: &Uuid, kind: TaskChangeKind) {
+        if let Some(recipients) = self.change_recipients(task).await {
+            self.send_change(task, actor_id, kind, recipients);
+        }
+    }
+
+    /// Users a change of the task is streamed to, or `None` if nobody is
+    /// listening. Lookup failures narrow the audience instead of failing.
+    async fn change_recipients(&self, task: &Task) -> Option<Vec<Uuid>> {
+        self.event_bus.as_ref().filter(|bus| bus.has_subscribers())?;
+        Some(self.recipients_of(&task.id, &task.user_id, task.workspace_id).await)
+    }
+
+    async fn recipients_of(&self, task_id: &Uuid, owner_id: &Uuid, workspace_id: Option<Uuid>) -> Vec<Uuid> {
+        let mut recipients = vec![*owner_id];
+        if let (Some(workspace_id), Some(workspace_repository)) = (workspace_id, &self.workspace_repository) {
+            match workspace_repository.find_members(&workspace_id).await {
+                Ok(members) => recipients.extend(members.into_iter().map(|member| member.user_id)),
+                Err(e) => warn!("Failed to load members of workspace {}: {}", workspace_id, e),
+            }
+        }
+        if let Some(share_repository) = &self.share_repository {
+            match share_repository.find_by_task_id(task_id).await {
+                Ok(shares) => recipients.extend(shares.into_iter().map(|share| share.user_id)),
+                Err(e) => warn!("Failed to load shares of task {}: {}", task_id, e),
+            }
+        }
+        recipients.sort();
+        recipients.dedup();
+        recipients
+    }
+
+    fn send_change(&self, task: &Task, actor_id: &Uuid, kind: TaskChangeKind, recipients: Vec<Uuid>) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(TaskChange {
+                kind,
+                task_id: task.id,
+                task: (kind != TaskChangeKind::Deleted).then(|| task.clone()),
+                actor_id: Some(*actor_id),
+                occurred_at: Utc::now(),
+                recipients,
+            });
+        }
+    }
+
+    /// Without a workspace repository nobody is a member of any workspace
+    async fn is_workspace_member(&self, workspace_id: &Uuid, user_id: &Uuid) -> Result<bool, TaskServiceError> {
+        let Some(workspace_repository) = &self.workspace_repository else {
+            return Ok(false);
+        };
+
+        Ok(workspace_repository.find_membership(workspace_id, user_id).await?.is_some())
+    }
+
+    fn require_status_repository(&self) -> Result<&Arc<dyn StatusRepository>, TaskServiceError> {
+        self.status_repository.as_ref().ok_or_else(|| {
+            TaskServiceError::InternalError(anyhow::anyhow!("Custom statuses are not enabled"))
+        })
+    }
+
+    fn resolve_status(workflow: &StatusWorkflow, name: &str) -> Result<TaskStatus, TaskServiceError> {
+        workflow
+            .parse(name)
+            .ok_or_else(|| TaskServiceError::UnknownStatus {
+                name: name.to_string(),
+            })
+    }
+
+    /// Reject status changes the workflow does not allow
+    fn ensure_transition(
+        workflow: &StatusWorkflow,
+        from: TaskStatus,
+        to: TaskStatus,
+    ) -> Result<(), TaskServiceError> {
+        if !workflow.contains(to) {
+            return Err(TaskServiceError::UnknownStatus {
+                name: to.to_string(),
+            });
+        }
+
+        if !workflow.can_transition(from, to) {
+            return Err(TaskServiceError::InvalidStatusTransition {
+                from: workflow.name(from),
+                to: workflow.name(to),
+            });
+        }
+
+        Ok(())
+    }
+
     /// Apply complex filtering in memory (for cases where database filtering is not optimal)
     fn apply_complex_filter(&self, tasks: &mut Vec<Task>, filter: &TaskFilter) {
         tasks.retain(|task| {
@@ -443,6 +1664,11 @@ impl TaskService {
                 return false;
             }
 
+            // Due today filter
+            if filter.due_today && !task.is_due_today() {
+                return false;
+            }
+
             // Search term filter
             if let Some(ref term) = filter.search_term {
                 let term_lower = term.to_lowercase();
@@ -458,198 +1684,136 @@ impl TaskService {
                 }
             }
 
-            true
-        });
-    }
+            // Context filter
+            if let Some(ref context) = filter.context {
+                if task.context.as_deref() != Some(normalize_context(context).as_str()) {
+                    return false;
+                }
+            }
 
-    /// Calculate statistics from a collection of tasks (fallback method)
-    fn calculate_statistics(&self, tasks: &[Task]) -> TaskStatistics {
-        let total_tasks = tasks.len() as i64;
-        let mut pending_tasks = 0;
-        let mut in_progress_tasks = 0;
-        let mut completed_tasks = 0;
-        let mut overdue_tasks = 0;
+            // Start date filter
+            if filter.started_only && !task.has_started() {
+                return false;
+            }
 
-        for task in tasks {
-            match task.status {
-                TaskStatus::Pending => pending_tasks += 1,
-                TaskStatus::InProgress => in_progress_tasks += 1,
-                TaskStatus::Completed => completed_tasks += 1,
+            // Due date range filter; undated tasks never match a range
+            if filter.due_before.is_some_and(|before| task.due_date.is_none_or(|due| due >= before)) {
+                return false;
+            }
+            if filter.due_after.is_some_and(|after| task.due_date.is_none_or(|due| due < after)) {
+                return false;
             }
 
-            if task.is_overdue() && !task.is_completed() {
-                overdue_tasks += 1;
+            // Location filter
+            if let Some(ref near) = filter.near {
+                if !near.matches(task.location.as_deref(), task.coordinates()) {
+                    return false;
+                }
             }
-        }
 
-        TaskStatistics {
-            total_tasks,
-            pending_tasks,
-            in_progress_tasks,
-            completed_tasks,
-            overdue_tasks,
-        }
+            true
+        });
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::repositories::InMemoryTaskRepository;
     use crate::models::task::{StoreTaskRequest, TaskPriority};
+    use crate::models::task_event::TaskEvent;
+    use crate::models::workspace::{Workspace, WorkspaceMember};
     use async_trait::async_trait;
-    use std::collections::HashMap;
     use std::sync::Mutex;
 
-    // Mock repository for testing
-    struct MockTaskRepository {
-        tasks: Arc<Mutex<HashMap<Uuid, Task>>>,
-        next_id: Arc<Mutex<usize>>,
+    // Mock status repository for testing workflow rules
+    struct MockStatusRepository {
+        statuses: Mutex<Vec<CustomStatus>>,
+        transitions: Mutex<Vec<StatusTransition>>,
     }
 
-    impl MockTaskRepository {
+    impl MockStatusRepository {
         fn new() -> Self {
             Self {
-                tasks: Arc::new(Mutex::new(HashMap::new())),
-                next_id: Arc::new(Mutex::new(0)),
+                statuses: Mutex::new(Vec::new()),
+                transitions: Mutex::new(Vec::new()),
             }
         }
     }
 
     #[async_trait]
-    impl TaskRepository for MockTaskRepository {
+    impl StatusRepository for MockStatusRepository {
         async fn store(
             &self,
-            request: StoreTaskRequest,
             user_id: &Uuid,
-        ) -> Result<Task, TaskRepositoryError> {
-            let task = Task::new(request, *user_id)
-                .map_err(|e| TaskRepositoryError::ValidationError(e))?;
-
-            self.tasks.lock().unwrap().insert(task.id, task.clone());
-            Ok(task)
-        }
-
-        async fn find_by_id(&self, id: &Uuid) -> Result<Option<Task>, TaskRepositoryError> {
-            Ok(self.tasks.lock().unwrap().get(id).cloned())
-        }
-
-        async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Task>, TaskRepositoryError> {
-            let tasks: Vec<Task> = self
-                .tasks
-                .lock()
-                .unwrap()
-                .values()
-                .filter(|task| task.user_id == *user_id)
-                .cloned()
-                .collect();
-            Ok(tasks)
+            request: StoreStatusRequest,
+        ) -> Result<CustomStatus, StatusRepositoryError> {
+            let mut statuses = self.statuses.lock().unwrap();
+            let status = CustomStatus {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                code: crate::models::status::CUSTOM_STATUS_CODE_START + statuses.len() as i16,
+                name: request.name,
+                created_at: Utc::now(),
+            };
+            statuses.push(status.clone());
+            Ok(status)
         }
 
-        async fn find_overdue_by_user(
+        async fn find_by_user_id(
             &self,
             user_id: &Uuid,
-        ) -> Result<Vec<Task>, TaskRepositoryError> {
-            let tasks: Vec<Task> = self
-                .tasks
-                .lock()
-                .unwrap()
-                .values()
-                .filter(|task| {
-                    task.user_id == *user_id && task.is_overdue() && !task.is_completed()
-                })
-                .cloned()
-                .collect();
-            Ok(tasks)
+        ) -> Result<Vec<CustomStatus>, StatusRepositoryError> {
+            let statuses = self.statuses.lock().unwrap();
+            Ok(statuses.iter().filter(|s| s.user_id == *user_id).cloned().collect())
         }
 
-        async fn find_by_status(
-            &self,
-            user_id: &Uuid,
-            status: TaskStatus,
-        ) -> Result<Vec<Task>, TaskRepositoryError> {
-            let tasks: Vec<Task> = self
-                .tasks
-                .lock()
-                .unwrap()
-                .values()
-                .filter(|task| task.user_id == *user_id && task.status == status)
-                .cloned()
-                .collect();
-            Ok(tasks)
+        async fn delete(&self, user_id: &Uuid, code: i16) -> Result<bool, StatusRepositoryError> {
+            let mut statuses = self.statuses.lock().unwrap();
+            let before = statuses.len();
+            statuses.retain(|s| !(s.user_id == *user_id && s.code == code));
+            Ok(statuses.len() < before)
         }
 
-        async fn search_tasks(
+        async fn count_tasks_with_status(
             &self,
-            user_id: &Uuid,
-            search_term: &str,
-        ) -> Result<Vec<Task>, TaskRepositoryError> {
-            let search_lower = search_term.to_lowercase();
-            let tasks: Vec<Task> = self
-                .tasks
-                .lock()
-                .unwrap()
-                .values()
-                .filter(|task| {
-                    task.user_id == *user_id
-                        && (task.title.to_lowercase().contains(&search_lower)
-                            || task
-                                .description
-                                .as_ref()
-                                .map(|d| d.to_lowercase().contains(&search_lower))
-                                .unwrap_or(false))
-                })
-                .cloned()
-                .collect();
-            Ok(tasks)
+            _user_id: &Uuid,
+            _code: i16,
+        ) -> Result<i64, StatusRepositoryError> {
+            Ok(0)
         }
 
-        async fn update(
+        async fn find_transitions(
             &self,
-            id: &Uuid,
-            user_id: &Uuid,
-            request: UpdateTaskRequest,
-        ) -> Result<Task, TaskRepositoryError> {
-            let mut tasks = self.tasks.lock().unwrap();
-            if let Some(task) = tasks.get_mut(id) {
-                if task.user_id != *user_id {
-                    return Err(TaskRepositoryError::NotFound);
-                }
-                task.update(request);
-                Ok(task.clone())
-            } else {
-                Err(TaskRepositoryError::NotFound)
-            }
+            _user_id: &Uuid,
+        ) -> Result<Vec<StatusTransition>, StatusRepositoryError> {
+            Ok(self.transitions.lock().unwrap().clone())
         }
 
-        async fn delete(&self, id: &Uuid, user_id: &Uuid) -> Result<bool, TaskRepositoryError> {
-            let mut tasks = self.tasks.lock().unwrap();
-            if let Some(task) = tasks.get(id) {
-                if task.user_id == *user_id {
-                    tasks.remove(id);
-                    Ok(true)
-                } else {
-                    Ok(false)
-                }
-            } else {
-                Ok(false)
-            }
+        async fn add_transition(
+            &self,
+            _user_id: &Uuid,
+            transition: StatusTransition,
+        ) -> Result<(), StatusRepositoryError> {
+            self.transitions.lock().unwrap().push(transition);
+            Ok(())
         }
 
-        async fn count_by_user(&self, user_id: &Uuid) -> Result<i64, TaskRepositoryError> {
-            let count = self
-                .tasks
-                .lock()
-                .unwrap()
-                .values()
-                .filter(|task| task.user_id == *user_id)
-                .count() as i64;
-            Ok(count)
+        async fn remove_transition(
+            &self,
+            _user_id: &Uuid,
+            transition: StatusTransition,
+        ) -> Result<bool, StatusRepositoryError> {
+            let mut transitions = self.transitions.lock().unwrap();
+            let before = transitions.len();
+            transitions.retain(|t| *t != transition);
+            Ok(transitions.len() < before)
         }
     }
 
     #[tokio::test]
     async fn test_create_task_success() {
-        let repo = Arc::new(MockTaskRepository::new());
+        let repo = Arc::new(InMemoryTaskRepository::new());
         let service = TaskService::new(repo);
         let user_id = Uuid::new_v4();
 
@@ -659,6 +1823,7 @@ mod tests {
             status: TaskStatus::Pending,
             priority: TaskPriority::Medium,
             due_date: Some(Utc::now() + chrono::Duration::days(1)),
+            ..Default::default()
         };
 
         let result = service.create_task(&user_id, request).await;
@@ -669,9 +1834,474 @@ mod tests {
         assert_eq!(task.user_id, user_id);
     }
 
+    #[tokio::test]
+    async fn test_future_start_tasks_are_hidden_until_started() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo);
+        let user_id = Uuid::new_v4();
+
+        for (title, start_date) in [
+            ("Actionable", None),
+            ("Started", Some(Utc::now() - chrono::Duration::hours(1))),
+            ("Tickler", Some(Utc::now() + chrono::Duration::days(3))),
+        ] {
+            let request = StoreTaskRequest {
+                title: title.to_string(),
+                start_date,
+                ..Default::default()
+            };
+            service.create_task(&user_id, request).await.unwrap();
+        }
+
+        let started = service.get_tasks(&user_id, TaskFilter::new().started_only()).await.unwrap();
+        let mut titles: Vec<&str> = started.iter().map(|task| task.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Actionable", "Started"]);
+
+        let filter = TaskFilter::new().started_only().with_priority(TaskPriority::Medium);
+        assert_eq!(service.get_tasks(&user_id, filter).await.unwrap().len(), 2);
+        assert_eq!(service.get_tasks(&user_id, TaskFilter::new()).await.unwrap().len(), 3);
+
+        let invalid = StoreTaskRequest {
+            title: "Backwards".to_string(),
+            start_date: Some(Utc::now() + chrono::Duration::days(5)),
+            due_date: Some(Utc::now() + chrono::Duration::days(2)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            service.create_task(&user_id, invalid).await,
+            Err(TaskServiceError::ValidationError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_start_before_the_stored_due_date() {
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()));
+        let user_id = Uuid::new_v4();
+        let request = StoreTaskRequest {
+            title: "Dated".to_string(),
+            start_date: Some(Utc::now() + chrono::Duration::days(3)),
+            due_date: Some(Utc::now() + chrono::Duration::days(5)),
+            ..Default::default()
+        };
+        let task = service.create_task(&user_id, request).await.unwrap();
+
+        let earlier_due = UpdateTaskRequest { due_date: Some(Utc::now() + chrono::Duration::days(1)), ..Default::default() };
+        assert!(matches!(
+            service.update_task(&user_id, &task.id, earlier_due).await,
+            Err(TaskServiceError::ValidationError { .. })
+        ));
+        let later_start = UpdateTaskRequest { start_date: Some(Utc::now() + chrono::Duration::days(7)), ..Default::default() };
+        assert!(matches!(
+            service.update_task(&user_id, &task.id, later_start).await,
+            Err(TaskServiceError::ValidationError { .. })
+        ));
+
+        let later_due = UpdateTaskRequest { due_date: Some(Utc::now() + chrono::Duration::days(4)), ..Default::default() };
+        service.update_task(&user_id, &task.id, later_due).await.unwrap();
+    }
+
+    // Mock workspace repository: members are (workspace, user) pairs
+    #[derive(Default)]
+    struct MockWorkspaceRepository {
+        members: Mutex<Vec<(Uuid, Uuid)>>,
+    }
+
+    #[async_trait]
+    impl WorkspaceRepository for MockWorkspaceRepository {
+        async fn store(&self, owner_id: &Uuid, name: &str) -> Result<Workspace, WorkspaceRepositoryError> {
+            let workspace = Workspace {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                owner_id: *owner_id,
+                created_at: Utc::now(),
+            };
+            self.members.lock().unwrap().push((workspace.id, *owner_id));
+            Ok(workspace)
+        }
+
+        async fn find_by_id(&self, _id: &Uuid) -> Result<Option<Workspace>, WorkspaceRepositoryError> {
+            Ok(None)
+        }
+
+        async fn find_by_member(&self, _user_id: &Uuid) -> Result<Vec<Workspace>, WorkspaceRepositoryError> {
+            Ok(Vec::new())
+        }
+
+        async fn add_member(&self, workspace_id: &Uuid, user_id: &Uuid) -> Result<bool, WorkspaceRepositoryError> {
+            self.members.lock().unwrap().push((*workspace_id, *user_id));
+            Ok(true)
+        }
+
+        async fn find_members(&self, _workspace_id: &Uuid) -> Result<Vec<WorkspaceMember>, WorkspaceRepositoryError> {
+            Ok(Vec::new())
+        }
+
+        async fn find_membership(
+            &self,
+            workspace_id: &Uuid,
+            user_id: &Uuid,
+        ) -> Result<Option<WorkspaceMember>, WorkspaceRepositoryError> {
+            let members = self.members.lock().unwrap();
+            Ok(members
+                .iter()
+                .find(|(w, u)| w == workspace_id && u == user_id)
+                .map(|(workspace_id, user_id)| WorkspaceMember {
+                    workspace_id: *workspace_id,
+                    user_id: *user_id,
+                    username: "member".to_string(),
+                    role: "member".to_string(),
+                    joined_at: Utc::now(),
+                }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_due_range_sort_and_cursor() {
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()));
+        let user_id = Uuid::new_v4();
+
+        for (title, days) in [("C", Some(3)), ("A", Some(1)), ("B", Some(2)), ("Undated", None)] {
+            let request = StoreTaskRequest {
+                title: title.to_string(),
+                due_date: days.map(|days| Utc::now() + chrono::Duration::days(days)),
+                ..Default::default()
+            };
+            service.create_task(&user_id, request).await.unwrap();
+        }
+
+        let filter = TaskFilter {
+            due_before: Some(Utc::now() + chrono::Duration::hours(60)),
+            sort: Some("-due".parse().unwrap()),
+            ..Default::default()
+        };
+        let titles = |tasks: Vec<Task>| tasks.into_iter().map(|task| task.title).collect::<Vec<_>>();
+        assert_eq!(titles(service.get_tasks(&user_id, filter).await.unwrap()), ["B", "A"]);
+
+        let page = |after| TaskFilter {
+            sort: Some("due".parse().unwrap()),
+            after,
+            limit: Some(2),
+            ..Default::default()
+        };
+        let first = service.get_tasks(&user_id, page(None)).await.unwrap();
+        let cursor = first.last().map(|task| task.id);
+        assert_eq!(titles(first), ["A", "B"]);
+        assert_eq!(titles(service.get_tasks(&user_id, page(cursor)).await.unwrap()), ["C", "Undated"]);
+
+        let result = service.get_tasks(&user_id, page(Some(Uuid::new_v4()))).await;
+        assert!(matches!(result, Err(TaskServiceError::ValidationError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_workspace_members_share_tasks() {
+        let workspaces = Arc::new(MockWorkspaceRepository::default());
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()))
+            .with_workspace_repository(workspaces.clone());
+
+        let owner = Uuid::new_v4();
+        let member = Uuid::new_v4();
+        let outsider = Uuid::new_v4();
+        let workspace = workspaces.store(&owner, "Team").await.unwrap();
+        workspaces.add_member(&workspace.id, &member).await.unwrap();
+
+        let shared = service
+            .create_task(&owner, StoreTaskRequest {
+                title: "Shared".to_string(),
+                workspace_id: Some(workspace.id),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // Members can see and complete the task; it stays owned by its creator
+        assert_eq!(service.get_task(&member, &shared.id).await.unwrap().id, shared.id);
+        let completed = service.complete_task(&member, &shared.id).await.unwrap();
+        assert_eq!(completed.status, TaskStatus::Completed);
+        assert_eq!(completed.user_id, owner);
+        assert_eq!(service.get_workspace_tasks(&member, &workspace.id).await.unwrap().len(), 1);
+
+        assert!(matches!(
+            service.get_task(&outsider, &shared.id).await,
+            Err(TaskServiceError::AccessDenied)
+        ));
+        assert!(matches!(
+            service.get_workspace_tasks(&outsider, &workspace.id).await,
+            Err(TaskServiceError::AccessDenied)
+        ));
+
+        // Only members can add tasks to the workspace
+        let request = StoreTaskRequest {
+            title: "Intruder".to_string(),
+            workspace_id: Some(workspace.id),
+            ..Default::default()
+        };
+        assert!(matches!(
+            service.create_task(&outsider, request).await,
+            Err(TaskServiceError::AccessDenied)
+        ));
+    }
+
+    // Mock task share repository
+    #[derive(Default)]
+    struct MockTaskShareRepository {
+        shares: Mutex<Vec<TaskShare>>,
+    }
+
+    #[async_trait]
+    impl TaskShareRepository for MockTaskShareRepository {
+        async fn store(&self, task_id: &Uuid, user_id: &Uuid, read_only: bool) -> Result<TaskShare, TaskShareRepositoryError> {
+            let mut shares = self.shares.lock().unwrap();
+            shares.retain(|s| !(s.task_id == *task_id && s.user_id == *user_id));
+            let share = TaskShare {
+                task_id: *task_id,
+                user_id: *user_id,
+                read_only,
+                created_at: Utc::now(),
+            };
+            shares.push(share.clone());
+            Ok(share)
+        }
+
+        async fn find(&self, task_id: &Uuid, user_id: &Uuid) -> Result<Option<TaskShare>, TaskShareRepositoryError> {
+            let shares = self.shares.lock().unwrap();
+            Ok(shares.iter().find(|s| s.task_id == *task_id && s.user_id == *user_id).cloned())
+        }
+
+        async fn find_by_user_id(&self, user_id: &Uuid) -> Result<Vec<TaskShare>, TaskShareRepositoryError> {
+            let shares = self.shares.lock().unwrap();
+            Ok(shares.iter().filter(|s| s.user_id == *user_id).cloned().collect())
+        }
+
+        async fn find_by_task_id(&self, task_id: &Uuid) -> Result<Vec<TaskShare>, TaskShareRepositoryError> {
+            let shares = self.shares.lock().unwrap();
+            Ok(shares.iter().filter(|s| s.task_id == *task_id).cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changes_reach_owner_and_shared_users() {
+        let event_bus = EventBus::default();
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()))
+            .with_share_repository(Arc::new(MockTaskShareRepository::default()))
+            .with_event_bus(event_bus.clone());
+
+        let owner = Uuid::new_v4();
+        let friend = Uuid::new_v4();
+        // Nobody is listening yet, so nothing is published
+        let task = service
+            .create_task(&owner, StoreTaskRequest { title: "Plan trip".to_string(), ..Default::default() })
+            .await
+            .unwrap();
+        service.share_task(&owner, &task.id, &friend, true).await.unwrap();
+
+        let mut changes = event_bus.subscribe();
+        service.complete_task(&owner, &task.id).await.unwrap();
+        service.delete_task(&owner, &task.id).await.unwrap();
+
+        let completed = changes.try_recv().unwrap();
+        assert_eq!(completed.kind, TaskChangeKind::Completed);
+        assert!(completed.is_for(&owner) && completed.is_for(&friend));
+        assert!(!completed.is_for(&Uuid::new_v4()));
+
+        let deleted = changes.try_recv().unwrap();
+        assert_eq!(deleted.kind, TaskChangeKind::Deleted);
+        assert!(deleted.task.is_none());
+        assert!(deleted.is_for(&friend));
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_relay_change_streams_changes_of_other_processes() {
+        let event_bus = EventBus::default();
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new())).with_event_bus(event_bus.clone());
+        let owner = Uuid::new_v4();
+        let task = service
+            .create_task(&owner, StoreTaskRequest { title: "Written elsewhere".to_string(), ..Default::default() })
+            .await
+            .unwrap();
+
+        let mut changes = event_bus.subscribe();
+        let mut change = TaskRowChange {
+            operation: RowOperation::Update,
+            task_id: task.id,
+            user_id: owner,
+            workspace_id: None,
+            completed: true,
+            origin: crate::database::change_listener::origin().to_string(),
+        };
+        // Already published when this service made it
+        service.relay_change(&change).await.unwrap();
+        assert!(changes.try_recv().is_err());
+
+        change.origin = "todo-cli elsewhere".to_string();
+        service.relay_change(&change).await.unwrap();
+        let relayed = changes.try_recv().unwrap();
+        assert_eq!(relayed.kind, TaskChangeKind::Completed);
+        assert_eq!(relayed.task.as_ref().unwrap().id, task.id);
+        assert!(relayed.actor_id.is_none() && relayed.is_for(&owner));
+
+        change.operation = RowOperation::Delete;
+        service.relay_change(&change).await.unwrap();
+        let deleted = changes.try_recv().unwrap();
+        assert_eq!(deleted.kind, TaskChangeKind::Deleted);
+        assert!(deleted.task.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shared_tasks_honor_read_only() {
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()))
+            .with_share_repository(Arc::new(MockTaskShareRepository::default()));
+
+        let owner = Uuid::new_v4();
+        let friend = Uuid::new_v4();
+        let task = service
+            .create_task(&owner, StoreTaskRequest {
+                title: "Plan trip".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service.get_task(&friend, &task.id).await,
+            Err(TaskServiceError::AccessDenied)
+        ));
+        assert!(matches!(
+            service.share_task(&friend, &task.id, &friend, false).await,
+            Err(TaskServiceError::AccessDenied)
+        ));
+        assert!(matches!(
+            service.share_task(&owner, &task.id, &owner, false).await,
+            Err(TaskServiceError::ValidationError { .. })
+        ));
+
+        service.share_task(&owner, &task.id, &friend, true).await.unwrap();
+        assert_eq!(service.get_task(&friend, &task.id).await.unwrap().id, task.id);
+        assert!(matches!(
+            service.complete_task(&friend, &task.id).await,
+            Err(TaskServiceError::ReadOnlyTask)
+        ));
+
+        // Shared tasks show up in the recipient's list, but not in their exports
+        let listed = service.get_tasks(&friend, TaskFilter::new()).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(service.get_tasks(&friend, TaskFilter::new().owned_only()).await.unwrap().is_empty());
+        let filtered = TaskFilter::new().with_status(TaskStatus::Completed);
+        assert!(service.get_tasks(&friend, filtered).await.unwrap().is_empty());
+
+        // Sharing again without --read-only lets the user change the task
+        service.share_task(&owner, &task.id, &friend, false).await.unwrap();
+        let completed = service.complete_task(&friend, &task.id).await.unwrap();
+        assert_eq!(completed.status, TaskStatus::Completed);
+        assert_eq!(completed.user_id, owner);
+    }
+
+    // Mock task event repository; the actor's ID stands in for their username
+    #[derive(Default)]
+    struct MockTaskEventRepository {
+        events: Mutex<Vec<TaskEvent>>,
+        seen_at: Mutex<Option<chrono::DateTime<Utc>>>,
+    }
+
+    #[async_trait]
+    impl TaskEventRepository for MockTaskEventRepository {
+        async fn store(
+            &self,
+            task_id: &Uuid,
+            actor_id: &Uuid,
+            kind: TaskEventKind,
+            subject_id: Option<&Uuid>,
+        ) -> Result<(), TaskEventRepositoryError> {
+            self.events.lock().unwrap().push(TaskEvent {
+                id: Uuid::new_v4(),
+                task_id: *task_id,
+                task_title: String::new(),
+                workspace_id: None,
+                actor: actor_id.to_string(),
+                kind,
+                subject: subject_id.map(Uuid::to_string),
+                created_at: Utc::now(),
+            });
+            Ok(())
+        }
+
+        async fn find_visible_to(
+            &self,
+            _user_id: &Uuid,
+            since: Option<chrono::DateTime<Utc>>,
+            limit: i64,
+        ) -> Result<Vec<TaskEvent>, TaskEventRepositoryError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .iter()
+                .rev()
+                .filter(|e| since.is_none_or(|since| e.created_at > since))
+                .take(limit as usize)
+                .cloned()
+                .collect())
+        }
+
+        async fn find_by_workspace(
+            &self,
+            _workspace_id: &Uuid,
+            _since: Option<chrono::DateTime<Utc>>,
+            _limit: i64,
+        ) -> Result<Vec<TaskEvent>, TaskEventRepositoryError> {
+            Ok(Vec::new())
+        }
+
+        async fn last_seen(&self, _user_id: &Uuid) -> Result<Option<chrono::DateTime<Utc>>, TaskEventRepositoryError> {
+            Ok(*self.seen_at.lock().unwrap())
+        }
+
+        async fn mark_seen(&self, _user_id: &Uuid, seen_at: chrono::DateTime<Utc>) -> Result<(), TaskEventRepositoryError> {
+            *self.seen_at.lock().unwrap() = Some(seen_at);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_activity_records_events_and_remembers_last_look() {
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()))
+            .with_share_repository(Arc::new(MockTaskShareRepository::default()))
+            .with_event_repository(Arc::new(MockTaskEventRepository::default()));
+
+        let owner = Uuid::new_v4();
+        let friend = Uuid::new_v4();
+        let task = service
+            .create_task(&owner, StoreTaskRequest {
+                title: "Plan trip".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        service.share_task(&owner, &task.id, &friend, false).await.unwrap();
+        service.complete_task(&friend, &task.id).await.unwrap();
+
+        let feed = service.get_activity(&owner, None, None, 50).await.unwrap();
+        let kinds: Vec<TaskEventKind> = feed.events.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![TaskEventKind::Created, TaskEventKind::Shared, TaskEventKind::Completed]);
+        assert_eq!(feed.events[1].subject, Some(friend.to_string()));
+        assert_eq!(feed.events[2].actor, friend.to_string());
+        assert!(feed.last_seen.is_none());
+        assert!(feed.events.iter().all(|e| feed.is_new(e)));
+
+        // Looking again only shows what happened since
+        let feed = service.get_activity(&owner, None, None, 50).await.unwrap();
+        assert!(feed.events.is_empty());
+        assert!(feed.last_seen.is_some());
+
+        // An explicit period shows older events, no longer marked new
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+        let feed = service.get_activity(&owner, None, Some(hour_ago), 50).await.unwrap();
+        assert_eq!(feed.events.len(), 3);
+        assert!(!feed.events.iter().any(|e| feed.is_new(e)));
+    }
+
     #[tokio::test]
     async fn test_get_task_authorization() {
-        let repo = Arc::new(MockTaskRepository::new());
+        let repo = Arc::new(InMemoryTaskRepository::new());
         let service = TaskService::new(repo.clone());
 
         let user1 = Uuid::new_v4();
@@ -684,6 +2314,7 @@ mod tests {
             status: TaskStatus::Pending,
             priority: TaskPriority::Medium,
             due_date: None,
+            ..Default::default()
         };
 
         let task = service.create_task(&user1, request).await.unwrap();
@@ -697,9 +2328,42 @@ mod tests {
         assert!(matches!(result2, Err(TaskServiceError::AccessDenied)));
     }
 
+    #[tokio::test]
+    async fn test_resolve_task_ref() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo.clone());
+        let user1 = Uuid::new_v4();
+        let user2 = Uuid::new_v4();
+
+        let request = |title: &str| StoreTaskRequest {
+            title: title.to_string(),
+            ..Default::default()
+        };
+        let first = service.create_task(&user1, request("First")).await.unwrap();
+        let second = service.create_task(&user1, request("Second")).await.unwrap();
+        let other = service.create_task(&user2, request("Other")).await.unwrap();
+        assert_eq!((first.number, second.number, other.number), (1, 2, 1));
+
+        let resolve = |input: &str| {
+            let task_ref: TaskRef = input.parse().unwrap();
+            let service = &service;
+            async move { service.resolve_task_ref(&user1, &task_ref).await }
+        };
+
+        assert_eq!(resolve("#2").await.unwrap(), second.id);
+        assert_eq!(resolve(&first.id.to_string()[..8]).await.unwrap(), first.id);
+        assert_eq!(resolve(&second.id.to_string()).await.unwrap(), second.id);
+        assert!(matches!(resolve("3").await, Err(TaskServiceError::TaskNotFound)));
+        // Other users' tasks are not matched by prefix
+        assert!(matches!(
+            resolve(&other.id.to_string()[..8]).await,
+            Err(TaskServiceError::TaskNotFound)
+        ));
+    }
+
     #[tokio::test]
     async fn test_bulk_operations() {
-        let repo = Arc::new(MockTaskRepository::new());
+        let repo = Arc::new(InMemoryTaskRepository::new());
         let service = TaskService::new(repo.clone());
         let user_id = Uuid::new_v4();
 
@@ -712,27 +2376,236 @@ mod tests {
                 status: TaskStatus::Pending,
                 priority: TaskPriority::Medium,
                 due_date: None,
+                ..Default::default()
             };
             let task = service.create_task(&user_id, request).await.unwrap();
             task_ids.push(task.id);
         }
 
-        // Bulk update status
-        let result = service
-            .bulk_update_status(&user_id, task_ids.clone(), TaskStatus::Completed)
-            .await;
-        assert!(result.is_ok());
-        let updated_tasks = result.unwrap();
-        assert_eq!(updated_tasks.len(), 3);
+        // Bulk update status, with one unknown ID mixed in
+        let missing_id = Uuid::new_v4();
+        let mut ids = task_ids.clone();
+        ids.insert(1, missing_id);
+        let results = service
+            .bulk_update_status(&user_id, ids, TaskStatus::Completed)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(&results[1], Err(BulkItemError::NotFound { task_id }) if *task_id == missing_id));
 
-        // Verify all tasks are completed
-        for task in updated_tasks {
+        // Verify all existing tasks are completed
+        for task in results.iter().filter_map(|result| result.as_ref().ok()) {
             assert_eq!(task.status, TaskStatus::Completed);
+            assert!(task.completed_at.is_some());
         }
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 3);
 
         // Bulk delete
         let delete_result = service.bulk_delete_tasks(&user_id, task_ids).await;
         assert!(delete_result.is_ok());
         assert_eq!(delete_result.unwrap(), 3);
     }
+
+    #[tokio::test]
+    async fn test_bulk_create_tasks_reports_each_item() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo.clone());
+        let user_id = Uuid::new_v4();
+
+        let requests = vec![
+            StoreTaskRequest { title: "Valid".to_string(), ..Default::default() },
+            StoreTaskRequest {
+                title: "Overdue".to_string(),
+                due_date: Some(Utc::now() - chrono::Duration::days(1)),
+                ..Default::default()
+            },
+            StoreTaskRequest { title: "Also valid".to_string(), ..Default::default() },
+        ];
+        let results = service.bulk_create_tasks(&user_id, requests).await.unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().title, "Valid");
+        assert!(matches!(results[1], Err(TaskServiceError::ValidationError { .. })));
+        assert_eq!(results[2].as_ref().unwrap().title, "Also valid");
+        assert_eq!(repo.count_by_user(&user_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_task_with_stale_version_conflicts() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo);
+        let user_id = Uuid::new_v4();
+
+        let request = StoreTaskRequest { title: "Draft".to_string(), ..Default::default() };
+        let read = service.create_task(&user_id, request).await.unwrap();
+        service.set_pinned(&user_id, &read.id, true).await.unwrap();
+
+        let stale = UpdateTaskRequest {
+            title: Some("Overwrite".to_string()),
+            version: Some(read.version),
+            ..Default::default()
+        };
+        let result = service.update_task(&user_id, &read.id, stale).await;
+        assert!(matches!(result, Err(TaskServiceError::RepositoryError(TaskRepositoryError::Conflict))));
+
+        // Without a version the task read just before the write is the base
+        let unversioned = UpdateTaskRequest { title: Some("Final".to_string()), ..Default::default() };
+        let updated = service.update_task(&user_id, &read.id, unversioned).await.unwrap();
+        assert_eq!(updated.title, "Final");
+        assert!(updated.pinned);
+        assert_eq!(updated.version, read.version + 2);
+    }
+
+    #[tokio::test]
+    async fn test_move_task() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo.clone());
+        let user_id = Uuid::new_v4();
+
+        let mut ids = Vec::new();
+        for title in ["A", "B", "C"] {
+            let request = StoreTaskRequest {
+                title: title.to_string(),
+                ..Default::default()
+            };
+            ids.push(service.create_task(&user_id, request).await.unwrap().id);
+        }
+        let (a, b, c) = (ids[0], ids[1], ids[2]);
+
+        service.move_task(&user_id, &b, &a, MovePosition::Before).await.unwrap();
+        service.move_task(&user_id, &c, &b, MovePosition::After).await.unwrap();
+
+        let titles: Vec<String> = service
+            .get_tasks(&user_id, TaskFilter::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| task.title)
+            .collect();
+        assert_eq!(titles, vec!["B", "C", "A"]);
+
+        let result = service.move_task(&user_id, &a, &a, MovePosition::After).await;
+        assert!(matches!(result, Err(TaskServiceError::ValidationError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_move_task_refuses_shared_tasks() {
+        let service = TaskService::new(Arc::new(InMemoryTaskRepository::new()))
+            .with_share_repository(Arc::new(MockTaskShareRepository::default()));
+        let owner = Uuid::new_v4();
+        let friend = Uuid::new_v4();
+
+        let create = |user_id: Uuid, title: &str| {
+            let request = StoreTaskRequest { title: title.to_string(), ..Default::default() };
+            let service = &service;
+            async move { service.create_task(&user_id, request).await.unwrap().id }
+        };
+        let shared = create(owner, "Plan trip").await;
+        let own = create(friend, "Pack bags").await;
+        let other_own = create(friend, "Book hotel").await;
+        service.share_task(&owner, &shared, &friend, false).await.unwrap();
+
+        // Moving a shared task, even a writable one
+        let result = service.move_task(&friend, &shared, &own, MovePosition::Before).await;
+        assert!(matches!(result, Err(TaskServiceError::ValidationError { .. })));
+        // Moving next to a shared task
+        let result = service.move_task(&friend, &own, &shared, MovePosition::After).await;
+        assert!(matches!(result, Err(TaskServiceError::ValidationError { .. })));
+
+        service.move_task(&friend, &other_own, &own, MovePosition::Before).await.unwrap();
+        assert_eq!(service.get_task(&owner, &shared).await.unwrap().sort_order, None);
+    }
+
+    #[tokio::test]
+    async fn test_custom_status_transitions_enforced() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo)
+            .with_status_repository(Arc::new(MockStatusRepository::new()));
+        let user_id = Uuid::new_v4();
+
+        let blocked = service.create_status(&user_id, "Blocked").await.unwrap();
+        service
+            .set_transition(&user_id, "Blocked", "in-progress", true)
+            .await
+            .unwrap();
+
+        let request = StoreTaskRequest {
+            title: "Workflow task".to_string(),
+            description: None,
+            status: TaskStatus::Pending,
+            priority: TaskPriority::Medium,
+            due_date: None,
+            ..Default::default()
+        };
+        let task = service.create_task(&user_id, request).await.unwrap();
+
+        // Pending has no rules, so it can move into the custom status
+        let task = service
+            .change_status(&user_id, &task.id, blocked.status())
+            .await
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::Custom(blocked.code));
+
+        // Blocked may only move to in progress
+        let result = service.complete_task(&user_id, &task.id).await;
+        assert!(matches!(
+            result,
+            Err(TaskServiceError::InvalidStatusTransition { .. })
+        ));
+
+        let result = service
+            .change_status(&user_id, &task.id, TaskStatus::InProgress)
+            .await;
+        assert!(result.is_ok());
+
+        // Unknown custom codes are rejected
+        let result = service
+            .change_status(&user_id, &task.id, TaskStatus::Custom(99))
+            .await;
+        assert!(matches!(result, Err(TaskServiceError::UnknownStatus { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_import_tasks_replace() {
+        let repo = Arc::new(InMemoryTaskRepository::new());
+        let service = TaskService::new(repo.clone());
+        let user_id = Uuid::new_v4();
+
+        let existing = StoreTaskRequest {
+            title: "Existing".to_string(),
+            description: None,
+            status: TaskStatus::Pending,
+            priority: TaskPriority::Medium,
+            due_date: None,
+            ..Default::default()
+        };
+        service.create_task(&user_id, existing).await.unwrap();
+
+        // One invalid task rejects the whole import, existing tasks included
+        let invalid = vec![
+            StoreTaskRequest { title: "Fine".to_string(), ..Default::default() },
+            StoreTaskRequest { title: String::new(), ..Default::default() },
+        ];
+        assert!(service.import_tasks(&user_id, invalid, true).await.is_err());
+        let tasks = service.get_tasks(&user_id, TaskFilter::default()).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Existing");
+
+        // Past due dates are accepted on import
+        let imported = vec![StoreTaskRequest {
+            title: "Imported".to_string(),
+            description: None,
+            status: TaskStatus::Completed,
+            priority: TaskPriority::High,
+            due_date: Some(Utc::now() - chrono::Duration::days(3)),
+            ..Default::default()
+        }];
+
+        let count = service.import_tasks(&user_id, imported, true).await.unwrap();
+        assert_eq!(count, 1);
+
+        let tasks = service.get_tasks(&user_id, TaskFilter::default()).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Imported");
+    }
 }
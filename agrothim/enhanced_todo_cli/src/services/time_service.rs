@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    database::repositories::{TimeEntryRepository, TimeEntryRepositoryError},
+    models::{
+        task::Task,
+        time_entry::{TaskTimeSummary, TimeEntry, TrackedTime},
+    },
+    services::{TaskService, TaskServiceError},
+};
+
+#[derive(Error, Debug)]
+pub enum TimeServiceError {
+    #[error("A timer is already running for '{title}'")]
+    TimerAlreadyRunning { title: String },
+
+    #[error("No timer is running")]
+    NoRunningTimer,
+
+    #[error(transparent)]
+    TaskError(#[from] TaskServiceError),
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] TimeEntryRepositoryError),
+}
+
+/// Result of starting a timer
+#[derive(Debug)]
+pub struct StartedTimer {
+    pub task: Task,
+    pub entry: TimeEntry,
+    /// Timer that was running on another task and got stopped
+    pub stopped: Option<TimeEntry>,
+}
+
+pub struct TimeService {
+    task_service: Arc<TaskService>,
+    time_entry_repository: Arc<dyn TimeEntryRepository>,
+}
+
+impl TimeService {
+    pub fn new(task_service: Arc<TaskService>, time_entry_repository: Arc<dyn TimeEntryRepository>) -> Self {
+        Self {
+            task_service,
+            time_entry_repository,
+        }
+    }
+
+    /// Start tracking time on a task. Only one timer runs per user, so a
+    /// timer on a different task is stopped first.
+    pub async fn start_timer(&self, user_id: &Uuid, task_id: &Uuid) -> Result<StartedTimer, TimeServiceError> {
+        let task = self.task_service.get_task(user_id, task_id).await?;
+
+        if let Some(running) = self.time_entry_repository.find_running(user_id).await? {
+            if running.task_id == task.id {
+                return Err(TimeServiceError::TimerAlreadyRunning { title: task.title });
+            }
+        }
+
+        let (entry, stopped) = self
+            .time_entry_repository
+            .start(user_id, task_id, Utc::now())
+            .await?;
+
+        info!("Started timer on task {} for user {}", task_id, user_id);
+        Ok(StartedTimer { task, entry, stopped })
+    }
+
+    /// Stop the running timer and return the finished entry with its task
+    pub async fn stop_timer(&self, user_id: &Uuid) -> Result<(TimeEntry, Task), TimeServiceError> {
+        let entry = self
+            .time_entry_repository
+            .stop(user_id, Utc::now())
+            .await?
+            .ok_or(TimeServiceError::NoRunningTimer)?;
+
+        let task = self.task_service.get_task(user_id, &entry.task_id).await?;
+
+        info!("Stopped timer on task {} for user {}", entry.task_id, user_id);
+        Ok((entry, task))
+    }
+
+    pub async fn tracked_time(&self, user_id: &Uuid, task_id: &Uuid) -> Result<TrackedTime, TimeServiceError> {
+        Ok(self.time_entry_repository.tracked_time(user_id, task_id).await?)
+    }
+
+    /// Time tracked per task since the given instant (or ever, if `None`)
+    pub async fn time_report(
+        &self,
+        user_id: &Uuid,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TaskTimeSummary>, TimeServiceError> {
+        Ok(self.time_entry_repository.summarize(user_id, since, None).await?)
+    }
+
+    /// Time tracked on tasks that have a client, within `[since, until)`,
+    /// optionally limited to one client
+    pub async fn billable_time(
+        &self,
+        user_id: &Uuid,
+        client: Option<&str>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<TaskTimeSummary>, TimeServiceError> {
+        let summaries = self
+            .time_entry_repository
+            .summarize(user_id, Some(since), Some(until))
+            .await?;
+
+        Ok(summaries
+            .into_iter()
+            .filter(|summary| match (&summary.client, client) {
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(task_client), Some(client)) => task_client.eq_ignore_ascii_case(client),
+            })
+            .collect())
+    }
+}
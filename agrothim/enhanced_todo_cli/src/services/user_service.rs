@@ -1,3 +1,4 @@
+use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
 use thiserror::Error;
 use tracing::{error, info, warn};
@@ -6,9 +7,18 @@ use uuid::Uuid;
 
 use crate::{
     database::repositories::{UserRepository, UserRepositoryError},
-    models::user::{StoreUserRequest, UpdateUserRequest, User, UserResponse},
+    models::user::{StoreUserRequest, UpdateUserRequest, User, UserResponse, UsernameChange},
+    services::EmailVerificationService,
+    utils::tokens::generate_token,
 };
 
+/// Longest username derived from an external account, leaving room for a
+/// numeric suffix within the 50 character limit
+const MAX_EXTERNAL_USERNAME_LEN: usize = 40;
+
+/// Minimum time between two username changes of one account
+pub const USERNAME_CHANGE_COOLDOWN_DAYS: i64 = 30;
+
 #[derive(Error, Debug)]
 pub enum UserServiceError {
     #[error("Validation error: {message}")]
@@ -19,12 +29,21 @@ pub enum UserServiceError {
     
     #[error("Authentication failed")]
     AuthenticationFailed,
+
+    #[error("Email address not verified")]
+    EmailNotVerified,
     
     #[error("Username already exists: {username}")]
     UsernameExists { username: String },
     
     #[error("Email already exists: {email}")]
     EmailExists { email: String },
+
+    #[error("Account is suspended")]
+    AccountSuspended,
+
+    #[error("Username was changed recently; it can be changed again after {next_allowed}")]
+    UsernameChangeTooSoon { next_allowed: DateTime<Utc> },
     
     #[error("Internal service error: {0}")]
     InternalError(#[from] anyhow::Error),
@@ -35,12 +54,23 @@ pub enum UserServiceError {
 
 pub struct UserService {
     user_repository: Arc<dyn UserRepository>,
+    email_verification: Option<Arc<EmailVerificationService>>,
 }
 
 impl UserService {
     #[allow(dead_code)]
     pub fn new(user_repository: Arc<dyn UserRepository>) -> Self {
-        Self { user_repository }
+        Self {
+            user_repository,
+            email_verification: None,
+        }
+    }
+
+    /// Email a verification token on registration and refuse logins until
+    /// the address is verified
+    pub fn with_email_verification(mut self, email_verification: Arc<EmailVerificationService>) -> Self {
+        self.email_verification = Some(email_verification);
+        self
     }
 
     /// Register a new user with comprehensive validation
@@ -63,10 +93,44 @@ impl UserService {
                 UserServiceError::RepositoryError(e)
             })?;
 
+        // The account exists either way; a failed email can be resent
+        if let Some(email_verification) = &self.email_verification {
+            if let Err(e) = email_verification.send_verification(&user).await {
+                warn!("Failed to send verification email to {}: {}", user.username, e);
+            }
+        }
+
         info!("Successfully registered user with ID: {}", user.id);
         Ok(user.to_response())
     }
 
+    /// Create an account for someone logging in through an identity provider
+    /// that has verified `email`. The account gets a free username based on
+    /// `preferred_username` and a random password nobody knows; the user can
+    /// set a real one with `auth forgot-password`.
+    pub async fn register_external(&self, preferred_username: &str, email: &str) -> Result<UserResponse, UserServiceError> {
+        let base = external_username(preferred_username, email);
+        let mut username = base.clone();
+        let mut suffix = 1;
+        while self.user_repository.exists_by_username(&username).await? {
+            suffix += 1;
+            username = format!("{}_{}", base, suffix);
+        }
+
+        let password = generate_token();
+        let request = StoreUserRequest::new(username, email.to_string(), password)
+            .map_err(|e| UserServiceError::ValidationError {
+                message: format!("Registration validation failed: {}", e),
+            })?;
+        self.check_user_uniqueness(&request).await?;
+
+        let user = self.user_repository.store(request).await?;
+        let user = self.user_repository.mark_email_verified(&user.id).await?;
+
+        info!("Registered user {} through an identity provider", user.username);
+        Ok(user.to_response())
+    }
+
     /// Authenticate user with username/email and password
     #[allow(dead_code)]
     pub async fn authenticate(&self, identifier: &str, password: &str) -> Result<UserResponse, UserServiceError> {
@@ -86,6 +150,16 @@ impl UserService {
             return Err(UserServiceError::AuthenticationFailed);
         }
 
+        if self.email_verification.is_some() && !user.is_email_verified() {
+            warn!("Authentication refused: email not verified for user {}", user.username);
+            return Err(UserServiceError::EmailNotVerified);
+        }
+
+        if !user.is_active {
+            warn!("Authentication refused: account {} is suspended", user.username);
+            return Err(UserServiceError::AccountSuspended);
+        }
+
         info!("Successfully authenticated user: {}", user.username);
         Ok(user.to_response())
     }
@@ -102,11 +176,10 @@ impl UserService {
     }
 
     /// Update user profile
-    #[allow(dead_code)]
     pub async fn update_profile(
         &self, 
         user_id: &Uuid, 
-        updates: UpdateUserRequest
+        mut updates: UpdateUserRequest
     ) -> Result<UserResponse, UserServiceError> {
         info!("Updating profile for user ID: {}", user_id);
         
@@ -116,6 +189,24 @@ impl UserService {
                 message: format!("Profile update validation failed: {}", e) 
             })?;
 
+        // Check if username is being changed, and whether that is allowed yet
+        if let Some(ref username) = updates.username {
+            let user = self.user_repository
+                .find_by_id(user_id)
+                .await?
+                .ok_or(UserServiceError::UserNotFound)?;
+
+            if *username == user.username {
+                updates.username = None;
+            } else if self.user_repository.exists_by_username(username).await? {
+                return Err(UserServiceError::UsernameExists {
+                    username: username.clone()
+                });
+            } else {
+                self.check_username_cooldown(user_id).await?;
+            }
+        }
+
         // Check if email is being updated and if it's already taken
         if let Some(ref email) = updates.email {
             if self.user_repository.exists_by_email(email).await? {
@@ -133,6 +224,82 @@ impl UserService {
         Ok(updated_user.to_response())
     }
 
+    /// Change a user's password after verifying the current one
+    pub async fn change_password(
+        &self,
+        user_id: &Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<UserResponse, UserServiceError> {
+        let user = self.user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(UserServiceError::UserNotFound)?;
+
+        if !user.verify_password(current_password) {
+            warn!("Password change rejected for user {}: wrong current password", user.username);
+            return Err(UserServiceError::AuthenticationFailed);
+        }
+
+        let updated_user = self.set_password(user_id, new_password).await?;
+        info!("Password changed for user: {}", updated_user.username);
+        Ok(updated_user)
+    }
+
+    /// Replace a user's password without checking the current one; callers
+    /// must have verified the user some other way, e.g. a reset token
+    pub async fn set_password(&self, user_id: &Uuid, new_password: &str) -> Result<UserResponse, UserServiceError> {
+        Self::validate_new_password(new_password)?;
+
+        let mut updates = UpdateUserRequest::new().password(new_password.to_string());
+        updates.password_hash = Some(
+            bcrypt::hash(new_password, bcrypt::DEFAULT_COST)
+                .map_err(|e| UserServiceError::InternalError(e.into()))?,
+        );
+
+        let updated_user = self.user_repository
+            .update(user_id, updates)
+            .await?;
+
+        Ok(updated_user.to_response())
+    }
+
+    /// Check a new password against the same rules as registration
+    pub fn validate_new_password(new_password: &str) -> Result<(), UserServiceError> {
+        UpdateUserRequest::new()
+            .password(new_password.to_string())
+            .validate()
+            .map_err(|e| UserServiceError::ValidationError {
+                message: format!("Password validation failed: {}", e)
+            })
+    }
+
+    /// Look up a user by email address
+    pub async fn find_by_email(&self, email: &str) -> Result<Option<UserResponse>, UserServiceError> {
+        Ok(self.user_repository
+            .find_by_email(email)
+            .await?
+            .map(|user| user.to_response()))
+    }
+
+    /// Whether the user has confirmed their email address
+    pub async fn is_email_verified(&self, user_id: &Uuid) -> Result<bool, UserServiceError> {
+        let user = self.user_repository
+            .find_by_id(user_id)
+            .await?
+            .ok_or(UserServiceError::UserNotFound)?;
+
+        Ok(user.is_email_verified())
+    }
+
+    /// Look up a user by username
+    pub async fn find_by_username(&self, username: &str) -> Result<Option<UserResponse>, UserServiceError> {
+        Ok(self.user_repository
+            .find_by_username(username)
+            .await?
+            .map(|user| user.to_response()))
+    }
+
     /// Check if username exists
     #[allow(dead_code)]
     pub async fn username_exists(&self, username: &str) -> Result<bool, UserServiceError> {
@@ -161,6 +328,23 @@ impl UserService {
         Ok(deleted)
     }
 
+    /// Suspend (`active = false`) or reactivate the account with this username
+    pub async fn set_active(&self, username: &str, active: bool) -> Result<UserResponse, UserServiceError> {
+        let user = self.user_repository
+            .find_by_username(username)
+            .await?
+            .ok_or(UserServiceError::UserNotFound)?;
+
+        let user = self.user_repository.set_active(&user.id, active).await?;
+        info!("{} account {}", if active { "Reactivated" } else { "Suspended" }, user.username);
+        Ok(user.to_response())
+    }
+
+    /// Previous usernames of the account, newest first
+    pub async fn username_changes(&self, user_id: &Uuid) -> Result<Vec<UsernameChange>, UserServiceError> {
+        Ok(self.user_repository.find_username_changes(user_id).await?)
+    }
+
     // Private helper methods
 
     /// Validate registration request
@@ -172,6 +356,19 @@ impl UserService {
         Ok(())
     }
 
+    /// Renames are limited to one per [`USERNAME_CHANGE_COOLDOWN_DAYS`] so
+    /// names cannot churn
+    async fn check_username_cooldown(&self, user_id: &Uuid) -> Result<(), UserServiceError> {
+        let changes = self.user_repository.find_username_changes(user_id).await?;
+        if let Some(last) = changes.first() {
+            let next_allowed = last.changed_at + Duration::days(USERNAME_CHANGE_COOLDOWN_DAYS);
+            if next_allowed > Utc::now() {
+                return Err(UserServiceError::UsernameChangeTooSoon { next_allowed });
+            }
+        }
+        Ok(())
+    }
+
     /// Check if username and email are unique
     async fn check_user_uniqueness(&self, request: &StoreUserRequest) -> Result<(), UserServiceError> {
         // Check username uniqueness
@@ -207,108 +404,43 @@ impl UserService {
     }
 }
 
+/// A valid username from a provider's login or display name, falling back
+/// to the local part of the email address
+fn external_username(preferred: &str, email: &str) -> String {
+    let sanitize = |name: &str| -> String {
+        let name: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        name.trim_matches('_').chars().take(MAX_EXTERNAL_USERNAME_LEN).collect()
+    };
+
+    [preferred, email.split('@').next().unwrap_or_default()]
+        .into_iter()
+        .map(sanitize)
+        .find(|name| name.len() >= 3)
+        .unwrap_or_else(|| "user".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::user::StoreUserRequest;
+    use crate::database::repositories::{EmailVerificationRepository, EmailVerificationRepositoryError, InMemoryUserRepository};
+    use crate::models::{email_verification::EmailVerificationToken, user::StoreUserRequest};
+    use crate::services::{EmailMessage, EmailVerificationError, Mailer, MailerError};
     use async_trait::async_trait;
     use std::collections::HashMap;
     use std::sync::Mutex;
 
-    // Mock repository for testing
-    struct MockUserRepository {
-        users: Arc<Mutex<HashMap<Uuid, User>>>,
-        usernames: Arc<Mutex<HashMap<String, Uuid>>>,
-        emails: Arc<Mutex<HashMap<String, Uuid>>>,
-    }
-
-    impl MockUserRepository {
-        fn new() -> Self {
-            Self {
-                users: Arc::new(Mutex::new(HashMap::new())),
-                usernames: Arc::new(Mutex::new(HashMap::new())),
-                emails: Arc::new(Mutex::new(HashMap::new())),
-            }
-        }
-    }
-
-    #[async_trait]
-    impl UserRepository for MockUserRepository {
-        async fn store(&self, request: StoreUserRequest) -> Result<User, UserRepositoryError> {
-            let user_id = Uuid::new_v4();
-            let now = chrono::Utc::now();
-            
-            let user = User {
-                id: user_id,
-                username: request.username.clone(),
-                email: request.email.clone(),
-                password_hash: request.password_hash,
-                created_at: now,
-                updated_at: now,
-            };
-
-            self.users.lock().unwrap().insert(user_id, user.clone());
-            self.usernames.lock().unwrap().insert(request.username, user_id);
-            self.emails.lock().unwrap().insert(request.email, user_id);
-
-            Ok(user)
-        }
-
-        async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>, UserRepositoryError> {
-            Ok(self.users.lock().unwrap().get(id).cloned())
-        }
-
-        async fn find_by_username(&self, username: &str) -> Result<Option<User>, UserRepositoryError> {
-            if let Some(user_id) = self.usernames.lock().unwrap().get(username) {
-                Ok(self.users.lock().unwrap().get(user_id).cloned())
-            } else {
-                Ok(None)
-            }
-        }
-
-        async fn find_by_email(&self, email: &str) -> Result<Option<User>, UserRepositoryError> {
-            if let Some(user_id) = self.emails.lock().unwrap().get(email) {
-                Ok(self.users.lock().unwrap().get(user_id).cloned())
-            } else {
-                Ok(None)
-            }
-        }
-
-        async fn update(&self, id: &Uuid, updates: UpdateUserRequest) -> Result<User, UserRepositoryError> {
-            let mut users = self.users.lock().unwrap();
-            if let Some(user) = users.get_mut(id) {
-                if let Some(email) = updates.email {
-                    user.email = email;
-                }
-                user.updated_at = chrono::Utc::now();
-                Ok(user.clone())
-            } else {
-                Err(UserRepositoryError::NotFound)
-            }
-        }
-
-        async fn delete(&self, id: &Uuid) -> Result<bool, UserRepositoryError> {
-            Ok(self.users.lock().unwrap().remove(id).is_some())
-        }
-
-        async fn exists_by_username(&self, username: &str) -> Result<bool, UserRepositoryError> {
-            Ok(self.usernames.lock().unwrap().contains_key(username))
-        }
-
-        async fn exists_by_email(&self, email: &str) -> Result<bool, UserRepositoryError> {
-            Ok(self.emails.lock().unwrap().contains_key(email))
-        }
-    }
-
     #[tokio::test]
     async fn test_user_registration_success() {
-        let repo = Arc::new(MockUserRepository::new());
+        let repo = Arc::new(InMemoryUserRepository::new());
         let service = UserService::new(repo);
 
         let request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         ).unwrap();
 
         let result = service.register(request).await;
@@ -321,19 +453,19 @@ mod tests {
 
     #[tokio::test]
     async fn test_user_authentication_success() {
-        let repo = Arc::new(MockUserRepository::new());
+        let repo = Arc::new(InMemoryUserRepository::new());
         let service = UserService::new(repo);
 
         // Register user first
         let request = StoreUserRequest::new(
             "testuser".to_string(),
             "test@example.com".to_string(),
-            "password123".to_string(),
+            "kestrel-canyon-42".to_string(),
         ).unwrap();
         service.register(request).await.unwrap();
 
         // Test authentication
-        let result = service.authenticate("testuser", "password123").await;
+        let result = service.authenticate("testuser", "kestrel-canyon-42").await;
         assert!(result.is_ok());
         
         let user_response = result.unwrap();
@@ -342,10 +474,196 @@ mod tests {
 
     #[tokio::test]
     async fn test_authentication_failure() {
-        let repo = Arc::new(MockUserRepository::new());
+        let repo = Arc::new(InMemoryUserRepository::new());
         let service = UserService::new(repo);
 
         let result = service.authenticate("nonexistent", "password").await;
         assert!(matches!(result, Err(UserServiceError::UserNotFound)));
     }
+
+    #[tokio::test]
+    async fn test_change_password() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = UserService::new(repo);
+
+        let request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        ).unwrap();
+        let user = service.register(request).await.unwrap();
+
+        // Wrong current password is rejected
+        let result = service.change_password(&user.id, "wrongpass1", "heron-meadow-77").await;
+        assert!(matches!(result, Err(UserServiceError::AuthenticationFailed)));
+
+        // Weak new password is rejected
+        let result = service.change_password(&user.id, "kestrel-canyon-42", "short").await;
+        assert!(matches!(result, Err(UserServiceError::ValidationError { .. })));
+        let result = service.change_password(&user.id, "kestrel-canyon-42", "Password1234").await;
+        assert!(matches!(result, Err(UserServiceError::ValidationError { .. })));
+
+        service.change_password(&user.id, "kestrel-canyon-42", "heron-meadow-77").await.unwrap();
+        assert!(service.authenticate("testuser", "heron-meadow-77").await.is_ok());
+        assert!(service.authenticate("testuser", "kestrel-canyon-42").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_suspended_account_cannot_authenticate() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = UserService::new(repo);
+
+        service.register(StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        ).unwrap()).await.unwrap();
+
+        let user = service.set_active("testuser", false).await.unwrap();
+        assert!(!user.is_active);
+        let result = service.authenticate("testuser", "kestrel-canyon-42").await;
+        assert!(matches!(result, Err(UserServiceError::AccountSuspended)));
+        // A wrong password does not reveal the suspension
+        let result = service.authenticate("testuser", "wrong-password").await;
+        assert!(matches!(result, Err(UserServiceError::AuthenticationFailed)));
+
+        service.set_active("testuser", true).await.unwrap();
+        assert!(service.authenticate("testuser", "kestrel-canyon-42").await.is_ok());
+        assert!(matches!(service.set_active("nobody", false).await, Err(UserServiceError::UserNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_change_username() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = UserService::new(repo.clone());
+
+        let user = service.register(StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        ).unwrap()).await.unwrap();
+        service.register(StoreUserRequest::new(
+            "taken".to_string(),
+            "taken@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        ).unwrap()).await.unwrap();
+
+        let result = service.update_profile(&user.id, UpdateUserRequest::new().username("taken".to_string())).await;
+        assert!(matches!(result, Err(UserServiceError::UsernameExists { .. })));
+        let result = service.update_profile(&user.id, UpdateUserRequest::new().username("no spaces".to_string())).await;
+        assert!(matches!(result, Err(UserServiceError::ValidationError { .. })));
+
+        let renamed = service.update_profile(&user.id, UpdateUserRequest::new().username("renamed".to_string())).await.unwrap();
+        assert_eq!(renamed.username, "renamed");
+        assert!(service.authenticate("renamed", "kestrel-canyon-42").await.is_ok());
+
+        // A second rename has to wait for the cooldown; keeping the name is fine
+        let result = service.update_profile(&user.id, UpdateUserRequest::new().username("again".to_string())).await;
+        assert!(matches!(result, Err(UserServiceError::UsernameChangeTooSoon { .. })));
+        service.update_profile(&user.id, UpdateUserRequest::new().username("renamed".to_string())).await.unwrap();
+
+        repo.backdate_username_changes(&user.id, Duration::days(USERNAME_CHANGE_COOLDOWN_DAYS + 1));
+        let renamed = service.update_profile(&user.id, UpdateUserRequest::new().username("again".to_string())).await.unwrap();
+        assert_eq!(renamed.username, "again");
+    }
+
+    #[derive(Default)]
+    struct MockEmailVerificationRepository {
+        tokens: Mutex<HashMap<String, Uuid>>,
+    }
+
+    #[async_trait]
+    impl EmailVerificationRepository for MockEmailVerificationRepository {
+        async fn store(
+            &self,
+            user_id: &Uuid,
+            token_hash: &str,
+            expires_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<EmailVerificationToken, EmailVerificationRepositoryError> {
+            self.tokens.lock().unwrap().insert(token_hash.to_string(), *user_id);
+            Ok(EmailVerificationToken {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                token_hash: token_hash.to_string(),
+                expires_at,
+                used_at: None,
+                created_at: chrono::Utc::now(),
+            })
+        }
+
+        async fn consume(&self, token_hash: &str) -> Result<Option<Uuid>, EmailVerificationRepositoryError> {
+            Ok(self.tokens.lock().unwrap().remove(token_hash))
+        }
+    }
+
+    #[derive(Default)]
+    struct MockMailer {
+        sent: Mutex<Vec<EmailMessage>>,
+    }
+
+    #[async_trait]
+    impl Mailer for MockMailer {
+        async fn send(&self, message: &EmailMessage) -> Result<(), MailerError> {
+            self.sent.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_requires_verified_email() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let mailer = Arc::new(MockMailer::default());
+        let verification = Arc::new(EmailVerificationService::new(
+            repo.clone(),
+            Arc::new(MockEmailVerificationRepository::default()),
+            mailer.clone(),
+        ));
+        let service = UserService::new(repo).with_email_verification(verification.clone());
+
+        let request = StoreUserRequest::new(
+            "testuser".to_string(),
+            "test@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        ).unwrap();
+        service.register(request).await.unwrap();
+
+        let result = service.authenticate("testuser", "kestrel-canyon-42").await;
+        assert!(matches!(result, Err(UserServiceError::EmailNotVerified)));
+
+        let body = mailer.sent.lock().unwrap()[0].body.clone();
+        let token = body
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("todo-cli auth verify "))
+            .unwrap()
+            .to_string();
+
+        assert!(matches!(verification.verify("bogus").await, Err(EmailVerificationError::InvalidToken)));
+        verification.verify(&token).await.unwrap();
+        assert!(service.authenticate("testuser", "kestrel-canyon-42").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_register_external_picks_free_username() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = UserService::new(repo);
+
+        let request = StoreUserRequest::new(
+            "octo_cat".to_string(),
+            "first@example.com".to_string(),
+            "kestrel-canyon-42".to_string(),
+        ).unwrap();
+        service.register(request).await.unwrap();
+
+        let user = service.register_external("octo-cat", "octo@example.com").await.unwrap();
+        assert_eq!(user.username, "octo_cat_2");
+        assert_eq!(user.email, "octo@example.com");
+
+        // Same email again
+        let result = service.register_external("octo-cat", "octo@example.com").await;
+        assert!(matches!(result, Err(UserServiceError::EmailExists { .. })));
+
+        assert_eq!(external_username("Jane Doe", "jane@example.com"), "Jane_Doe");
+        assert_eq!(external_username("李", "jd@example.com"), "user");
+        assert_eq!(external_username("", "jane.doe@example.com"), "jane_doe");
+    }
 }
@@ -0,0 +1,397 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    database::repositories::{WebhookRepository, WebhookRepositoryError},
+    models::{
+        user::UserResponse,
+        webhook::{StoreWebhookRequest, Webhook, WebhookDelivery, WebhookEvent, WebhookPayload},
+    },
+    services::UserService,
+};
+
+/// Header carrying the hex HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "X-Todo-Signature";
+pub const EVENT_HEADER: &str = "X-Todo-Event";
+
+/// Attempts per delivery before it is logged as failed
+const MAX_DELIVERY_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for every further one
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Error, Debug)]
+pub enum WebhookServiceError {
+    #[error("Validation error: {message}")]
+    ValidationError { message: String },
+
+    #[error("Webhook not found")]
+    WebhookNotFound,
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] WebhookRepositoryError),
+}
+
+pub struct WebhookService {
+    webhook_repository: Arc<dyn WebhookRepository>,
+    user_service: Option<Arc<UserService>>,
+    client: reqwest::Client,
+    /// Deliveries still being sent, so a short-lived CLI process can wait for
+    /// them with `flush` before it exits
+    in_flight: Mutex<JoinSet<()>>,
+}
+
+impl WebhookService {
+    pub fn new(webhook_repository: Arc<dyn WebhookRepository>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            webhook_repository,
+            user_service: None,
+            client,
+            in_flight: Mutex::new(JoinSet::new()),
+        }
+    }
+
+    /// Let services that only know a user's ID dispatch events with `dispatch_to`
+    pub fn with_user_service(mut self, user_service: Arc<UserService>) -> Self {
+        self.user_service = Some(user_service);
+        self
+    }
+
+    /// Register a webhook; a signing secret is generated for it
+    pub async fn create_webhook(
+        &self,
+        user_id: &Uuid,
+        request: StoreWebhookRequest,
+    ) -> Result<Webhook, WebhookServiceError> {
+        request
+            .validate()
+            .map_err(|e| WebhookServiceError::ValidationError {
+                message: format!("Webhook validation failed: {}", e),
+            })?;
+
+        let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let webhook = self
+            .webhook_repository
+            .store(user_id, &request.url, &secret, &request.events)
+            .await?;
+
+        info!("Registered webhook {} for user {}", webhook.id, user_id);
+        Ok(webhook)
+    }
+
+    pub async fn list_webhooks(&self, user_id: &Uuid) -> Result<Vec<Webhook>, WebhookServiceError> {
+        Ok(self.webhook_repository.find_by_user_id(user_id).await?)
+    }
+
+    pub async fn delete_webhook(&self, user_id: &Uuid, id: &Uuid) -> Result<(), WebhookServiceError> {
+        if !self.webhook_repository.delete(user_id, id).await? {
+            return Err(WebhookServiceError::WebhookNotFound);
+        }
+        info!("Deleted webhook {} for user {}", id, user_id);
+        Ok(())
+    }
+
+    /// Latest deliveries of one of the user's webhooks, newest first
+    pub async fn list_deliveries(
+        &self,
+        user_id: &Uuid,
+        id: &Uuid,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, WebhookServiceError> {
+        let webhooks = self.webhook_repository.find_by_user_id(user_id).await?;
+        if !webhooks.iter().any(|webhook| webhook.id == *id) {
+            return Err(WebhookServiceError::WebhookNotFound);
+        }
+        Ok(self.webhook_repository.find_deliveries(user_id, id, limit).await?)
+    }
+
+    /// Deliver an event to every subscribed webhook of the user.
+    ///
+    /// Delivery is best effort and runs in the background, retries included:
+    /// failures are logged and never fail or hold up the action that
+    /// triggered the event.
+    pub async fn dispatch(&self, user: &UserResponse, event: WebhookEvent, data: serde_json::Value) {
+        let webhooks = self.subscribed_webhooks(&user.id, event).await;
+        self.deliver_all(webhooks, user, event, data).await;
+    }
+
+    /// Like `dispatch`, for callers that only know the user's ID. The profile
+    /// is only loaded if one of the user's webhooks subscribes to the event.
+    pub async fn dispatch_to(&self, user_id: &Uuid, event: WebhookEvent, data: serde_json::Value) {
+        let webhooks = self.subscribed_webhooks(user_id, event).await;
+        if webhooks.is_empty() {
+            return;
+        }
+
+        let Some(user_service) = &self.user_service else {
+            warn!("Cannot deliver {} without a user service", event);
+            return;
+        };
+        match user_service.get_profile(user_id).await {
+            Ok(user) => self.deliver_all(webhooks, &user, event, data).await,
+            Err(e) => warn!("Failed to load user {} for {}: {}", user_id, event, e),
+        }
+    }
+
+    async fn subscribed_webhooks(&self, user_id: &Uuid, event: WebhookEvent) -> Vec<Webhook> {
+        match self.webhook_repository.find_by_user_id(user_id).await {
+            Ok(webhooks) => webhooks.into_iter().filter(|w| w.subscribes_to(event)).collect(),
+            Err(e) => {
+                warn!("Failed to load webhooks for {}: {}", event, e);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn deliver_all(&self, webhooks: Vec<Webhook>, user: &UserResponse, event: WebhookEvent, data: serde_json::Value) {
+        if webhooks.is_empty() {
+            return;
+        }
+
+        let payload = WebhookPayload {
+            event,
+            occurred_at: Utc::now(),
+            user_id: user.id,
+            username: user.username.clone(),
+            text: event.describe(&user.username, &data),
+            data,
+        };
+
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize {} payload: {}", event, e);
+                return;
+            }
+        };
+
+        let client = self.client.clone();
+        let webhook_repository = self.webhook_repository.clone();
+        let delivery = async move {
+            for webhook in webhooks {
+                let (attempts, status_code, error) = deliver(&client, &webhook, event, &body).await;
+                match &error {
+                    None => debug!("Delivered {} to webhook {}", event, webhook.id),
+                    Some(e) => warn!("Failed to deliver {} to webhook {} after {} attempts: {}", event, webhook.id, attempts, e),
+                }
+
+                let logged = webhook_repository
+                    .store_delivery(&webhook.id, event, attempts as i32, status_code, error.as_deref())
+                    .await;
+                if let Err(e) = logged {
+                    warn!("Failed to log delivery of {} to webhook {}: {}", event, webhook.id, e);
+                }
+            }
+        };
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        // Drop the deliveries that are done, so a long-running server does not pile them up
+        while in_flight.try_join_next().is_some() {}
+        in_flight.spawn(delivery);
+    }
+
+    /// Wait for the deliveries still being sent; the CLI calls this before it
+    /// exits, since the runtime drops unfinished tasks
+    pub async fn flush(&self) {
+        let mut in_flight = std::mem::take(&mut *self.in_flight.lock().unwrap());
+        while in_flight.join_next().await.is_some() {}
+    }
+}
+
+/// POST the body, retrying connection failures and retryable statuses with
+/// exponential backoff. Returns the attempts made, the last HTTP status and
+/// the error if the delivery failed.
+async fn deliver(client: &reqwest::Client, webhook: &Webhook, event: WebhookEvent, body: &[u8]) -> (u32, Option<i32>, Option<String>) {
+    let mut backoff = RETRY_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let result = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.as_str())
+            .header(SIGNATURE_HEADER, sign_payload(&webhook.secret, body))
+            .body(body.to_vec())
+            .send()
+            .await;
+
+        let (status_code, error, retryable) = match result {
+            Ok(response) if response.status().is_success() => {
+                return (attempt, Some(i32::from(response.status().as_u16())), None)
+            }
+            Ok(response) => {
+                let status = response.status();
+                (Some(i32::from(status.as_u16())), format!("HTTP {}", status), is_retryable(status))
+            }
+            Err(e) => (None, e.to_string(), true),
+        };
+
+        if !retryable || attempt >= MAX_DELIVERY_ATTEMPTS {
+            return (attempt, status_code, Some(error));
+        }
+        debug!("Retrying {} to webhook {} in {:?}: {}", event, webhook.id, backoff, error);
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// Server errors, rate limiting and timeouts may go away on their own; other
+/// client errors will not
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+}
+
+/// Hex-encoded HMAC-SHA256 of `body`, so receivers can verify deliveries
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serves the one webhook of a user and records the deliveries logged
+    #[derive(Default)]
+    struct RecordingRepository {
+        webhooks: Vec<Webhook>,
+        deliveries: Mutex<Vec<WebhookDelivery>>,
+    }
+
+    #[async_trait]
+    impl WebhookRepository for RecordingRepository {
+        async fn store(
+            &self,
+            user_id: &Uuid,
+            url: &str,
+            secret: &str,
+            events: &[WebhookEvent],
+        ) -> Result<Webhook, WebhookRepositoryError> {
+            Ok(Webhook {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                url: url.to_string(),
+                secret: secret.to_string(),
+                events: events.iter().map(|event| event.as_str().to_string()).collect(),
+                created_at: Utc::now(),
+            })
+        }
+
+        async fn find_by_user_id(&self, _: &Uuid) -> Result<Vec<Webhook>, WebhookRepositoryError> {
+            Ok(self.webhooks.clone())
+        }
+
+        async fn delete(&self, _: &Uuid, _: &Uuid) -> Result<bool, WebhookRepositoryError> {
+            Ok(false)
+        }
+
+        async fn store_delivery(
+            &self,
+            webhook_id: &Uuid,
+            event: WebhookEvent,
+            attempts: i32,
+            status_code: Option<i32>,
+            error: Option<&str>,
+        ) -> Result<WebhookDelivery, WebhookRepositoryError> {
+            let delivery = WebhookDelivery {
+                id: Uuid::new_v4(),
+                webhook_id: *webhook_id,
+                event: event.as_str().to_string(),
+                attempts,
+                status_code,
+                error: error.map(str::to_string),
+                delivered_at: Utc::now(),
+            };
+            self.deliveries.lock().unwrap().push(delivery.clone());
+            Ok(delivery)
+        }
+
+        async fn find_deliveries(&self, _: &Uuid, _: &Uuid, _: i64) -> Result<Vec<WebhookDelivery>, WebhookRepositoryError> {
+            Ok(self.deliveries.lock().unwrap().clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_delivers_in_the_background() {
+        // An endpoint that only answers once the test lets it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let _ = socket.read(&mut request).await;
+            let _ = released.await;
+            let _ = socket.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").await;
+        });
+
+        let user = UserResponse {
+            id: Uuid::new_v4(),
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let repository = Arc::new(RecordingRepository {
+            webhooks: vec![Webhook {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                url,
+                secret: "secret".to_string(),
+                events: Vec::new(),
+                created_at: Utc::now(),
+            }],
+            ..Default::default()
+        });
+        let service = WebhookService::new(repository.clone());
+
+        // Dispatch returns while the endpoint is still holding the request
+        let dispatch = service.dispatch(&user, WebhookEvent::TaskCreated, serde_json::json!({ "title": "Write report" }));
+        tokio::time::timeout(Duration::from_secs(5), dispatch)
+            .await
+            .expect("dispatch waited for the delivery");
+        assert!(repository.deliveries.lock().unwrap().is_empty());
+
+        release.send(()).unwrap();
+        service.flush().await;
+        let deliveries = repository.deliveries.lock().unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status_code, Some(204));
+        assert!(deliveries[0].succeeded());
+    }
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(is_retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(reqwest::StatusCode::NOT_FOUND));
+        assert!(!is_retryable(reqwest::StatusCode::UNAUTHORIZED));
+    }
+}
@@ -0,0 +1,466 @@
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{info, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    database::repositories::{
+        InvitationRepository, InvitationRepositoryError, StoreInvitationRequest, WorkspaceRepository,
+        WorkspaceRepositoryError,
+    },
+    models::{
+        invitation::Invitation,
+        task_event::ActivityFeed,
+        task::Task,
+        workspace::{MemberStatistics, MemberTaskCounts, StoreWorkspaceRequest, Workspace, WorkspaceMember, WorkspaceStatistics},
+    },
+    services::{TaskService, TaskServiceError, UserService, UserServiceError},
+    utils::tokens::{generate_token, hash_token},
+};
+
+/// Start of every invite code, so a pasted code is recognisable
+pub const INVITE_CODE_PREFIX: &str = "tdi_";
+/// Random hex characters after the prefix (96 bits)
+const INVITE_CODE_RANDOM_LEN: usize = 24;
+/// Characters of the code kept as its visible prefix
+const INVITE_CODE_DISPLAY_LEN: usize = 10;
+
+#[derive(Error, Debug)]
+pub enum WorkspaceServiceError {
+    #[error("Validation error: {message}")]
+    ValidationError { message: String },
+
+    #[error("Workspace not found: {reference}")]
+    WorkspaceNotFound { reference: String },
+
+    #[error("'{reference}' matches more than one workspace; use its ID")]
+    AmbiguousWorkspace { reference: String },
+
+    #[error("You already have a workspace named '{name}'")]
+    WorkspaceExists { name: String },
+
+    #[error("Only the owner of workspace '{name}' can manage its members")]
+    NotOwner { name: String },
+
+    #[error("User not found: {username}")]
+    UserNotFound { username: String },
+
+    #[error("{username} is already a member of '{workspace}'")]
+    AlreadyMember { username: String, workspace: String },
+
+    #[error("Invitation not found")]
+    InvitationNotFound,
+
+    #[error("This invitation expired on {expires_at}")]
+    InvitationExpired { expires_at: DateTime<Utc> },
+
+    #[error("This invitation was revoked")]
+    InvitationRevoked,
+
+    #[error("This invitation has already been used {max_uses} times")]
+    InvitationUsedUp { max_uses: i32 },
+
+    #[error(transparent)]
+    User(#[from] UserServiceError),
+
+    #[error(transparent)]
+    Task(#[from] TaskServiceError),
+
+    #[error("Repository error: {0}")]
+    RepositoryError(#[from] WorkspaceRepositoryError),
+
+    #[error("Invitation repository error: {0}")]
+    InvitationRepositoryError(#[from] InvitationRepositoryError),
+}
+
+/// A freshly created invitation and its code, which cannot be shown again
+#[derive(Debug)]
+pub struct CreatedInvitation {
+    pub code: String,
+    pub invitation: Invitation,
+}
+
+pub struct WorkspaceService {
+    user_service: Arc<UserService>,
+    task_service: Arc<TaskService>,
+    workspace_repository: Arc<dyn WorkspaceRepository>,
+    invitation_repository: Arc<dyn InvitationRepository>,
+}
+
+impl WorkspaceService {
+    pub fn new(
+        user_service: Arc<UserService>,
+        task_service: Arc<TaskService>,
+        workspace_repository: Arc<dyn WorkspaceRepository>,
+        invitation_repository: Arc<dyn InvitationRepository>,
+    ) -> Self {
+        Self {
+            user_service,
+            task_service,
+            workspace_repository,
+            invitation_repository,
+        }
+    }
+
+    /// Create a workspace owned by the user
+    pub async fn create_workspace(&self, user_id: &Uuid, request: StoreWorkspaceRequest) -> Result<Workspace, WorkspaceServiceError> {
+        let request = StoreWorkspaceRequest {
+            name: request.name.trim().to_string(),
+        };
+        request.validate().map_err(|e| WorkspaceServiceError::ValidationError {
+            message: format!("Workspace validation failed: {}", e),
+        })?;
+
+        let workspaces = self.workspace_repository.find_by_member(user_id).await?;
+        if workspaces
+            .iter()
+            .any(|w| w.owner_id == *user_id && w.name.eq_ignore_ascii_case(&request.name))
+        {
+            return Err(WorkspaceServiceError::WorkspaceExists { name: request.name });
+        }
+
+        let workspace = self.workspace_repository.store(user_id, &request.name).await?;
+        info!("Created workspace {} for user {}", workspace.id, user_id);
+        Ok(workspace)
+    }
+
+    /// Workspaces the user is a member of, oldest first
+    pub async fn list_workspaces(&self, user_id: &Uuid) -> Result<Vec<Workspace>, WorkspaceServiceError> {
+        Ok(self.workspace_repository.find_by_member(user_id).await?)
+    }
+
+    /// Find one of the user's workspaces by name or short ID
+    pub async fn resolve_workspace(&self, user_id: &Uuid, reference: &str) -> Result<Workspace, WorkspaceServiceError> {
+        let workspaces = self.workspace_repository.find_by_member(user_id).await?;
+        resolve_reference(workspaces, reference)
+    }
+
+    /// Add a user to the workspace; only its owner may invite
+    pub async fn invite_member(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+        username: &str,
+    ) -> Result<(Workspace, WorkspaceMember), WorkspaceServiceError> {
+        let workspace = self.resolve_owned_workspace(user_id, reference).await?;
+
+        let invitee = self
+            .user_service
+            .find_by_username(username)
+            .await?
+            .ok_or_else(|| WorkspaceServiceError::UserNotFound {
+                username: username.to_string(),
+            })?;
+
+        if !self.workspace_repository.add_member(&workspace.id, &invitee.id).await? {
+            return Err(WorkspaceServiceError::AlreadyMember {
+                username: invitee.username,
+                workspace: workspace.name,
+            });
+        }
+
+        let member = self
+            .workspace_repository
+            .find_membership(&workspace.id, &invitee.id)
+            .await?
+            .ok_or_else(|| WorkspaceServiceError::UserNotFound {
+                username: username.to_string(),
+            })?;
+
+        info!("Added user {} to workspace {}", invitee.id, workspace.id);
+        Ok((workspace, member))
+    }
+
+    /// Create an invite code for a workspace the user owns
+    pub async fn create_invitation(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+        lifetime: Duration,
+        max_uses: Option<i32>,
+    ) -> Result<(Workspace, CreatedInvitation), WorkspaceServiceError> {
+        if lifetime <= Duration::zero() {
+            return Err(WorkspaceServiceError::ValidationError {
+                message: "Invitations must expire in the future".to_string(),
+            });
+        }
+        if max_uses.is_some_and(|max_uses| max_uses < 1) {
+            return Err(WorkspaceServiceError::ValidationError {
+                message: "An invitation needs at least one use".to_string(),
+            });
+        }
+
+        let workspace = self.resolve_owned_workspace(user_id, reference).await?;
+
+        let code = format!("{}{}", INVITE_CODE_PREFIX, &generate_token()[..INVITE_CODE_RANDOM_LEN]);
+        let request = StoreInvitationRequest {
+            workspace_id: workspace.id,
+            created_by: *user_id,
+            prefix: code[..INVITE_CODE_DISPLAY_LEN].to_string(),
+            code_hash: hash_token(&code),
+            expires_at: Utc::now() + lifetime,
+            max_uses,
+        };
+        let invitation = self.invitation_repository.store(request).await?;
+
+        info!("Created invitation {} for workspace {}", invitation.prefix, workspace.id);
+        Ok((workspace, CreatedInvitation { code, invitation }))
+    }
+
+    /// Invitations of a workspace the user owns, newest first
+    pub async fn list_invitations(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+    ) -> Result<(Workspace, Vec<Invitation>), WorkspaceServiceError> {
+        let workspace = self.resolve_owned_workspace(user_id, reference).await?;
+        let invitations = self.invitation_repository.find_by_workspace(&workspace.id).await?;
+        Ok((workspace, invitations))
+    }
+
+    /// Revoke an invitation of a workspace the user owns by ID or prefix
+    pub async fn revoke_invitation(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+        id_or_prefix: &str,
+    ) -> Result<Invitation, WorkspaceServiceError> {
+        let workspace = self.resolve_owned_workspace(user_id, reference).await?;
+        let invitation = self
+            .invitation_repository
+            .revoke(&workspace.id, id_or_prefix)
+            .await?
+            .ok_or(WorkspaceServiceError::InvitationNotFound)?;
+
+        info!("Revoked invitation {} of workspace {}", invitation.prefix, workspace.id);
+        Ok(invitation)
+    }
+
+    /// Join the workspace an invite code belongs to
+    pub async fn join(&self, user_id: &Uuid, code: &str) -> Result<Workspace, WorkspaceServiceError> {
+        let code_hash = hash_token(code);
+        let invitation = self
+            .invitation_repository
+            .find_by_code_hash(&code_hash)
+            .await?
+            .ok_or(WorkspaceServiceError::InvitationNotFound)?;
+        if !invitation.is_active() {
+            return Err(unusable_invitation_error(&invitation));
+        }
+        let workspace = self
+            .workspace_repository
+            .find_by_id(&invitation.workspace_id)
+            .await?
+            .ok_or(WorkspaceServiceError::InvitationNotFound)?;
+
+        // Joining twice should not use up the code
+        if let Some(member) = self.workspace_repository.find_membership(&workspace.id, user_id).await? {
+            return Err(WorkspaceServiceError::AlreadyMember {
+                username: member.username,
+                workspace: workspace.name,
+            });
+        }
+
+        if self.invitation_repository.redeem(&code_hash, user_id).await?.is_none() {
+            // Find out why the code could not be used, as of now
+            let invitation = self
+                .invitation_repository
+                .find_by_code_hash(&code_hash)
+                .await?
+                .ok_or(WorkspaceServiceError::InvitationNotFound)?;
+            return Err(unusable_invitation_error(&invitation));
+        }
+
+        info!("User {} joined workspace {} with invitation {}", user_id, workspace.id, invitation.prefix);
+        Ok(workspace)
+    }
+
+    /// One of the user's workspaces that they also own
+    async fn resolve_owned_workspace(&self, user_id: &Uuid, reference: &str) -> Result<Workspace, WorkspaceServiceError> {
+        let workspace = self.resolve_workspace(user_id, reference).await?;
+        if workspace.owner_id != *user_id {
+            warn!("User {} tried to manage members of workspace {}", user_id, workspace.id);
+            return Err(WorkspaceServiceError::NotOwner { name: workspace.name });
+        }
+        Ok(workspace)
+    }
+
+    /// Members of one of the user's workspaces, the owner first
+    pub async fn list_members(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+    ) -> Result<(Workspace, Vec<WorkspaceMember>), WorkspaceServiceError> {
+        let workspace = self.resolve_workspace(user_id, reference).await?;
+        let members = self.workspace_repository.find_members(&workspace.id).await?;
+        Ok((workspace, members))
+    }
+
+    /// Tasks shared in one of the user's workspaces
+    pub async fn list_tasks(&self, user_id: &Uuid, reference: &str) -> Result<(Workspace, Vec<Task>), WorkspaceServiceError> {
+        let workspace = self.resolve_workspace(user_id, reference).await?;
+        let tasks = self.task_service.get_workspace_tasks(user_id, &workspace.id).await?;
+        Ok((workspace, tasks))
+    }
+
+    /// Activity on the tasks of one of the user's workspaces
+    pub async fn activity(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<(Workspace, ActivityFeed), WorkspaceServiceError> {
+        let workspace = self.resolve_workspace(user_id, reference).await?;
+        let feed = self
+            .task_service
+            .get_activity(user_id, Some(&workspace.id), since, limit)
+            .await?;
+        Ok((workspace, feed))
+    }
+
+    /// Statistics over the tasks of all members of one of the user's workspaces
+    pub async fn statistics(
+        &self,
+        user_id: &Uuid,
+        reference: &str,
+        completed_since: Option<DateTime<Utc>>,
+    ) -> Result<(Workspace, WorkspaceStatistics), WorkspaceServiceError> {
+        let workspace = self.resolve_workspace(user_id, reference).await?;
+        let (totals, counts, overdue) = self
+            .task_service
+            .get_workspace_statistics(user_id, &workspace.id, completed_since)
+            .await?;
+        let members = self.workspace_repository.find_members(&workspace.id).await?;
+
+        let members = members
+            .into_iter()
+            .map(|member| MemberStatistics {
+                counts: counts
+                    .iter()
+                    .find(|c| c.user_id == member.user_id)
+                    .cloned()
+                    .unwrap_or_else(|| MemberTaskCounts {
+                        user_id: member.user_id,
+                        ..MemberTaskCounts::default()
+                    }),
+                username: member.username,
+            })
+            .collect();
+
+        Ok((workspace, WorkspaceStatistics { totals, members, overdue }))
+    }
+}
+
+/// Why an invitation can no longer be redeemed
+fn unusable_invitation_error(invitation: &Invitation) -> WorkspaceServiceError {
+    if invitation.revoked_at.is_some() {
+        WorkspaceServiceError::InvitationRevoked
+    } else if invitation.is_expired() {
+        WorkspaceServiceError::InvitationExpired {
+            expires_at: invitation.expires_at,
+        }
+    } else {
+        WorkspaceServiceError::InvitationUsedUp {
+            max_uses: invitation.max_uses.unwrap_or(invitation.use_count),
+        }
+    }
+}
+
+/// Pick the workspace named `reference` (case-insensitive), or the one whose
+/// ID starts with it
+fn resolve_reference(workspaces: Vec<Workspace>, reference: &str) -> Result<Workspace, WorkspaceServiceError> {
+    let reference = reference.trim();
+    let needle = reference.to_lowercase().replace('-', "");
+
+    let mut matches: Vec<Workspace> = workspaces
+        .iter()
+        .filter(|w| w.name.eq_ignore_ascii_case(reference))
+        .cloned()
+        .collect();
+    if matches.is_empty() && !needle.is_empty() {
+        matches = workspaces
+            .into_iter()
+            .filter(|w| w.id.simple().to_string().starts_with(&needle))
+            .collect();
+    }
+
+    match matches.len() {
+        0 => Err(WorkspaceServiceError::WorkspaceNotFound {
+            reference: reference.to_string(),
+        }),
+        1 => Ok(matches.remove(0)),
+        _ => Err(WorkspaceServiceError::AmbiguousWorkspace {
+            reference: reference.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(name: &str, owner_id: Uuid) -> Workspace {
+        Workspace {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            owner_id,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_reference_by_name_or_id() {
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let team = workspace("Team", alice);
+        let home = workspace("Home", alice);
+        let workspaces = vec![team.clone(), home.clone()];
+
+        assert_eq!(resolve_reference(workspaces.clone(), "team").unwrap().id, team.id);
+        assert_eq!(resolve_reference(workspaces.clone(), &home.short_id()).unwrap().id, home.id);
+        assert!(matches!(
+            resolve_reference(workspaces.clone(), "work"),
+            Err(WorkspaceServiceError::WorkspaceNotFound { .. })
+        ));
+
+        // Two members' workspaces can share a name
+        let other_team = workspace("Team", bob);
+        assert!(matches!(
+            resolve_reference(vec![team, other_team], "Team"),
+            Err(WorkspaceServiceError::AmbiguousWorkspace { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unusable_invitation_error_explains_why() {
+        let mut invitation = Invitation {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            created_by: Uuid::new_v4(),
+            prefix: "tdi_012345".to_string(),
+            code_hash: "hash".to_string(),
+            expires_at: Utc::now() + Duration::days(1),
+            max_uses: Some(2),
+            use_count: 2,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+        assert!(matches!(
+            unusable_invitation_error(&invitation),
+            WorkspaceServiceError::InvitationUsedUp { max_uses: 2 }
+        ));
+
+        invitation.expires_at = Utc::now() - Duration::days(1);
+        assert!(matches!(
+            unusable_invitation_error(&invitation),
+            WorkspaceServiceError::InvitationExpired { .. }
+        ));
+
+        // Revocation wins over everything else
+        invitation.revoked_at = Some(Utc::now());
+        assert!(matches!(unusable_invitation_error(&invitation), WorkspaceServiceError::InvitationRevoked));
+    }
+}
@@ -1,54 +1,284 @@
 use std::env;
+use std::path::Path;
+
+use crate::database::{Backend, PoolConfig};
+
+/// JWT secret used when `JWT_SECRET` is not set; never acceptable in production
+pub const DEFAULT_JWT_SECRET: &str = "default-secret-change-in-production";
+
+/// Shortest JWT secret accepted in production (256 bits of hex or base64)
+const MIN_PRODUCTION_SECRET_LEN: usize = 32;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
+    /// `DATABASE_READ_URL`: PostgreSQL replica for read-only task queries
+    pub read_url: Option<String>,
+    /// `DATABASE_LOCAL_URL`: SQLite copy commands use, synced with `database_url`
+    pub local_url: Option<String>,
     pub jwt_secret: String,
     pub environment: String,
+    pub pool: PoolConfig,
+}
+
+/// Outcome of one deployment check
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    /// What to do about a failed check, when there is something to do
+    pub fix: Option<String>,
+}
+
+impl ConfigCheck {
+    pub fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, passed: true, detail: detail.into(), fix: None }
+    }
+
+    pub fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, passed: false, detail: detail.into(), fix: None }
+    }
+
+    pub fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
 }
 
 impl Config {
     #[allow(dead_code)]
     pub fn from_env() -> anyhow::Result<Self> {
+        let config = Self::load(None)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read the configuration without validating it. `environment` overrides `APP_ENV`.
+    pub fn load(environment: Option<String>) -> anyhow::Result<Self> {
         dotenv::dotenv().ok();
         let config = Config {
             database_url: env::var("DATABASE_URL")
                 .map_err(|_| anyhow::anyhow!("DATABASE_URL is not set"))?
                 .to_string(),
+            read_url: env::var("DATABASE_READ_URL").ok(),
+            local_url: env::var("DATABASE_LOCAL_URL").ok(),
             jwt_secret: env::var("JWT_SECRET")
-                .unwrap_or("secret".to_string())
-                .to_string(),
-            environment: env::var("APP_ENV")
-                .unwrap_or("development".to_string())
+                .unwrap_or(DEFAULT_JWT_SECRET.to_string())
                 .to_string(),
+            environment: environment
+                .or_else(|| env::var("APP_ENV").ok())
+                .unwrap_or("development".to_string()),
+            pool: PoolConfig::from_env()?,
         };
 
         tracing::info!("Config: successfully loaded for {} environment", config.environment);
-        config.validate()?;
         Ok(config)
     }
 
-    #[allow(dead_code)]
-    fn validate(&self) -> Result<(), anyhow::Error> {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
         if self.database_url.is_empty() {
             return Err(anyhow::anyhow!("DATABASE_URL is not set"));
         }
 
-        if !self.database_url.starts_with("postgres://") {
-            return Err(anyhow::anyhow!(
-                "DATABASE_URL must start with 'postgres://'"
-            ));
+        let backend = Backend::from_url(&self.database_url)?;
+        if let Some(read_url) = &self.read_url {
+            if backend != Backend::Postgres || Backend::from_url(read_url)? != Backend::Postgres {
+                return Err(anyhow::anyhow!("DATABASE_READ_URL needs PostgreSQL for both it and DATABASE_URL"));
+            }
+        }
+        if let Some(local_url) = &self.local_url {
+            if backend != Backend::Postgres || Backend::from_url(local_url)? != Backend::Sqlite {
+                return Err(anyhow::anyhow!("DATABASE_LOCAL_URL needs SQLite for it and PostgreSQL for DATABASE_URL"));
+            }
         }
+        self.pool.validate()?;
 
-        if self.is_production() && self.jwt_secret == "default-secret-change-me" {
+        if self.is_production() && self.jwt_secret == DEFAULT_JWT_SECRET {
             return Err(anyhow::anyhow!("JWT_SECRET is not set in production"));
         }
 
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn is_production(&self) -> bool {
         self.environment == "production"
     }
+
+    /// Checks that only need the configuration and the local filesystem.
+    /// Secret strength and database TLS are only enforced in production.
+    pub fn deployment_checks(&self, session_dir: &Path) -> Vec<ConfigCheck> {
+        let mut checks = vec![match self.validate() {
+            Ok(()) => ConfigCheck::pass("configuration", "required settings are valid"),
+            Err(e) => ConfigCheck::fail("configuration", e.to_string()),
+        }];
+
+        if self.is_production() {
+            checks.push(self.check_jwt_secret());
+            if matches!(Backend::from_url(&self.database_url), Ok(Backend::Postgres)) {
+                checks.push(self.check_database_tls());
+            }
+        }
+
+        checks.push(check_session_dir(session_dir));
+        checks
+    }
+
+    fn check_jwt_secret(&self) -> ConfigCheck {
+        const NAME: &str = "jwt secret";
+        if self.jwt_secret == DEFAULT_JWT_SECRET {
+            ConfigCheck::fail(NAME, "JWT_SECRET is the built-in default")
+        } else if self.jwt_secret.len() < MIN_PRODUCTION_SECRET_LEN {
+            ConfigCheck::fail(
+                NAME,
+                format!("JWT_SECRET must be at least {} characters", MIN_PRODUCTION_SECRET_LEN),
+            )
+        } else {
+            ConfigCheck::pass(NAME, "custom secret of sufficient length")
+        }
+    }
+
+    fn check_database_tls(&self) -> ConfigCheck {
+        const NAME: &str = "database tls";
+        let url = match url::Url::parse(&self.database_url) {
+            Ok(url) => url,
+            Err(e) => return ConfigCheck::fail(NAME, format!("DATABASE_URL is not a valid URL: {}", e)),
+        };
+
+        let sslmode = url
+            .query_pairs()
+            .find(|(key, _)| key == "sslmode")
+            .map(|(_, value)| value.into_owned());
+
+        match sslmode.as_deref() {
+            Some(mode @ ("require" | "verify-ca" | "verify-full")) => {
+                ConfigCheck::pass(NAME, format!("sslmode={}", mode))
+            }
+            Some(mode) => ConfigCheck::fail(NAME, format!("sslmode={} does not enforce TLS", mode)),
+            None => ConfigCheck::fail(NAME, "add sslmode=require (or verify-full) to DATABASE_URL"),
+        }
+    }
+}
+
+/// The session directory holds bearer tokens, so only its owner may access it
+fn check_session_dir(session_dir: &Path) -> ConfigCheck {
+    const NAME: &str = "session permissions";
+
+    if !session_dir.exists() {
+        return ConfigCheck::pass(NAME, format!("{} does not exist yet", session_dir.display()));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let session_file = session_dir.join("session.json");
+        for path in [session_dir, session_file.as_path()] {
+            let Ok(metadata) = std::fs::metadata(path) else {
+                continue;
+            };
+            let mode = metadata.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return ConfigCheck::fail(
+                    NAME,
+                    format!("{} is accessible by other users (mode {:o})", path.display(), mode),
+                );
+            }
+        }
+
+        ConfigCheck::pass(NAME, format!("{} is private to its owner", session_dir.display()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        ConfigCheck::pass(NAME, "not checked on this platform")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn production(database_url: &str, jwt_secret: &str) -> Config {
+        Config {
+            database_url: database_url.to_string(),
+            read_url: None,
+            local_url: None,
+            jwt_secret: jwt_secret.to_string(),
+            environment: "production".to_string(),
+            pool: PoolConfig::default(),
+        }
+    }
+
+    fn failed(checks: &[ConfigCheck]) -> Vec<&'static str> {
+        checks.iter().filter(|c| !c.passed).map(|c| c.name).collect()
+    }
+
+    #[test]
+    fn test_production_checks() {
+        let session_dir = tempfile::TempDir::new().unwrap();
+        let session_dir = session_dir.path().join("missing");
+
+        let insecure = production("postgres://app@db/todo", DEFAULT_JWT_SECRET);
+        assert_eq!(
+            failed(&insecure.deployment_checks(&session_dir)),
+            vec!["configuration", "jwt secret", "database tls"]
+        );
+
+        let secure = production(
+            "postgres://app@db/todo?sslmode=verify-full",
+            "a-sufficiently-long-random-production-secret",
+        );
+        let checks = secure.deployment_checks(&session_dir);
+        assert!(failed(&checks).is_empty());
+    }
+
+    #[test]
+    fn test_validate_backend_and_pool_limits() {
+        let mut config = production("sqlite://todo.db", "a-sufficiently-long-random-production-secret");
+        assert!(config.validate().is_ok());
+        // TLS only applies to PostgreSQL
+        assert!(failed(&config.deployment_checks(Path::new("/nonexistent"))).is_empty());
+
+        config.read_url = Some("postgres://app@replica/todo".to_string());
+        assert!(config.validate().is_err());
+
+        config.database_url = "mysql://app@db/todo".to_string();
+        assert!(config.validate().is_err());
+
+        config.database_url = "postgres://app@db/todo".to_string();
+        config.pool = PoolConfig { min_connections: Some(5), max_connections: Some(2), ..Default::default() };
+        assert!(config.validate().unwrap_err().to_string().contains("DATABASE_MIN_CONNECTIONS"));
+        config.pool = PoolConfig { max_connections: Some(0), ..Default::default() };
+        assert!(config.validate().is_err());
+        config.pool = PoolConfig { slow_query_threshold: Some(std::time::Duration::ZERO), ..Default::default() };
+        assert!(config.validate().unwrap_err().to_string().contains("DATABASE_SLOW_QUERY_MS"));
+        config.pool = PoolConfig {
+            max_connections: Some(2),
+            statement_timeout: Some(std::time::Duration::from_secs(5)),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        config.read_url = Some("sqlite://replica.db".to_string());
+        assert!(config.validate().is_err());
+
+        config.read_url = None;
+        config.local_url = Some("sqlite://local.db".to_string());
+        assert!(config.validate().is_ok());
+        config.local_url = Some("postgres://app@laptop/todo".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_session_dir_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let session_dir = tempfile::TempDir::new().unwrap();
+        std::fs::set_permissions(session_dir.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(!check_session_dir(session_dir.path()).passed);
+
+        std::fs::set_permissions(session_dir.path(), std::fs::Permissions::from_mode(0o700)).unwrap();
+        assert!(check_session_dir(session_dir.path()).passed);
+    }
 }
@@ -1,72 +1,236 @@
-use chrono::{DateTime, Utc, Local};
-use console::style;
-use tabled::{Table, Tabled, settings::{Style, Alignment}};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use uuid::Uuid;
+use console::{measure_text_width, style, Term};
+use tabled::{builder::Builder, Table, Tabled, settings::{peaker::PriorityMax, Style, Alignment, Width}};
 
-use crate::models::task::{TaskResponse, TaskPriority, TaskStatus};
+use crate::services::{BulkItemError, Conflict};
+use crate::models::{
+    billing::BillableReport,
+    calendar,
+    forecast::Forecast,
+    report::{Burndown, CompletionStreak, Heatmap, WeeklyReport},
+    status::StatusWorkflow,
+    task::{ContextCount, DetailedStatistics, GroupBreakdown, Task, TaskGroup, TaskResponse, TaskPriority, TaskStatistics, TaskStatus},
+    task_column::TaskColumn,
+    task_ref::{short_hash, IdStyle},
+    time_entry::{TaskTimeSummary, TrackedTime},
+    workload::{Workload, WorkloadBucket},
+    workspace::WorkspaceStatistics,
+};
 
-#[derive(Tabled)]
-struct TaskTableRow {
-    #[tabled(rename = "ID")]
-    id: String,
-    #[tabled(rename = "Title")]
-    title: String,
-    #[tabled(rename = "Status")]
-    status: String,
-    #[tabled(rename = "Priority")]
-    priority: String,
-    #[tabled(rename = "Due Date")]
-    due_date: String,
-    #[tabled(rename = "Created")]
-    created: String,
+/// Titles are truncated down to this width before columns are hidden to fit
+/// the terminal
+const MIN_TITLE_WIDTH: usize = 16;
+
+pub fn format_task_table(tasks: &[TaskResponse], workflow: &StatusWorkflow, ids: IdStyle) -> String {
+    render_task_table(tasks, workflow, ids, &TaskColumn::DEFAULT, &HashSet::new(), None, terminal_width())
+}
+
+/// Task table with the chosen `columns`, as seen by `viewer`: tasks other
+/// users shared with them are marked
+pub fn format_task_table_for(tasks: &[TaskResponse], workflow: &StatusWorkflow, ids: IdStyle, columns: &[TaskColumn], viewer: &Uuid) -> String {
+    render_task_table(tasks, workflow, ids, columns, &HashSet::new(), Some(viewer), terminal_width())
 }
 
-pub fn format_task_table(tasks: &[TaskResponse]) -> String {
+/// Task table with the rows in `highlighted` marked, used by watch mode to
+/// show what changed since the previous refresh
+pub fn format_task_table_highlighted(
+    tasks: &[TaskResponse],
+    workflow: &StatusWorkflow,
+    ids: IdStyle,
+    columns: &[TaskColumn],
+    highlighted: &HashSet<Uuid>,
+    viewer: &Uuid,
+) -> String {
+    render_task_table(tasks, workflow, ids, columns, highlighted, Some(viewer), terminal_width())
+}
+
+fn render_task_table(
+    tasks: &[TaskResponse],
+    workflow: &StatusWorkflow,
+    ids: IdStyle,
+    columns: &[TaskColumn],
+    highlighted: &HashSet<Uuid>,
+    viewer: Option<&Uuid>,
+    width: Option<usize>,
+) -> String {
     if tasks.is_empty() {
         return String::new();
     }
 
-    let rows: Vec<TaskTableRow> = tasks
+    let rows: Vec<Vec<String>> = tasks
         .iter()
-        .map(|task| TaskTableRow {
-            id: format!("{:.8}", task.id.to_string()),
-            title: if task.title.len() > 30 {
-                format!("{}...", &task.title[..27])
+        .map(|task| columns.iter().map(|column| task_cell(task, *column, workflow, ids, highlighted, viewer)).collect())
+        .collect();
+
+    // Indexes into `columns` of the columns that fit
+    let mut shown: Vec<usize> = (0..columns.len()).collect();
+    let table = loop {
+        let mut builder = Builder::default();
+        builder.push_record(shown.iter().map(|&i| columns[i].header()));
+        for row in &rows {
+            builder.push_record(shown.iter().map(|&i| row[i].clone()));
+        }
+        let mut table = builder.build();
+        table
+            .with(Style::rounded())
+            .with(Alignment::left());
+
+        let total = table.total_width();
+        let Some(width) = width.filter(|width| total > *width) else {
+            break table;
+        };
+        // Titles give way first, down to MIN_TITLE_WIDTH; then the columns
+        // listed last are hidden, and ID and title are truncated as a last resort
+        let title_slack = shown
+            .iter()
+            .filter(|&&i| columns[i] == TaskColumn::Title)
+            .flat_map(|&i| rows.iter().map(move |row| measure_text_width(&row[i])))
+            .max()
+            .map_or(0, |title_width| title_width.saturating_sub(MIN_TITLE_WIDTH));
+        match shown.iter().rposition(|&i| !matches!(columns[i], TaskColumn::Id | TaskColumn::Title)) {
+            Some(last) if total - title_slack > width => {
+                shown.remove(last);
+            }
+            _ => {
+                table.with(Width::truncate(width).priority::<PriorityMax>().suffix("…"));
+                break table;
+            }
+        }
+    };
+
+    let hidden: Vec<&str> = (0..columns.len())
+        .filter(|i| !shown.contains(i))
+        .map(|i| columns[i].name())
+        .collect();
+    if hidden.is_empty() {
+        table.to_string()
+    } else {
+        format!("{}\n{}", table, style(format!("Hidden to fit the terminal: {}", hidden.join(", "))).dim())
+    }
+}
+
+/// Width of the terminal stdout writes to, or `None` when it is not one, e.g.
+/// when piped; tables are then as wide as their content
+fn terminal_width() -> Option<usize> {
+    Term::stdout().size_checked().map(|(_, columns)| usize::from(columns))
+}
+
+fn task_cell(
+    task: &TaskResponse,
+    column: TaskColumn,
+    workflow: &StatusWorkflow,
+    ids: IdStyle,
+    highlighted: &HashSet<Uuid>,
+    viewer: Option<&Uuid>,
+) -> String {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+
+    match column {
+        TaskColumn::Id if highlighted.contains(&task.id) => style(ids.format(task)).black().on_yellow().to_string(),
+        TaskColumn::Id => ids.format(task),
+        TaskColumn::Title => {
+            let title = if viewer.is_some_and(|viewer| task.user_id != *viewer) {
+                format!("{} {}", style("⇄").cyan(), task.title)
             } else {
                 task.title.clone()
-            },
-            status: format_status(&task.status),
-            priority: format_priority(&task.priority),
-            due_date: task.due_date
-                .map(|d| format_date_short(&d))
-                .unwrap_or_else(|| "-".to_string()),
-            created: format_date_short(&task.created_at),
-        })
-        .collect();
+            };
+            if task.pinned {
+                format!("{} {}", style("★").yellow(), title)
+            } else {
+                title
+            }
+        }
+        TaskColumn::Status => format_status(&task.status, workflow),
+        TaskColumn::Priority => format_priority(&task.priority),
+        TaskColumn::Due => or_dash(task.due_date.as_ref().map(format_date_short)),
+        TaskColumn::Start => or_dash(task.start_date.as_ref().map(format_date_short)),
+        TaskColumn::Created => format_date_short(&task.created_at),
+        TaskColumn::Updated => format_date_short(&task.updated_at),
+        TaskColumn::Completed => or_dash(task.completed_at.as_ref().map(format_date_short)),
+        TaskColumn::Estimate => or_dash(task.estimate_minutes.map(|minutes| format_duration(i64::from(minutes) * 60))),
+        TaskColumn::Context => or_dash(task.context.clone()),
+        TaskColumn::Client => or_dash(task.client.clone()),
+        TaskColumn::Location => or_dash(task.location.clone()),
+    }
+}
 
-    let mut table = Table::new(rows);
-    table
-        .with(Style::rounded())
-        .with(Alignment::left());
+/// Tasks as CSV, or TSV with a tab `delimiter`, with a header row of column
+/// names: plain values without colors or truncation, and empty fields for
+/// values a task does not have
+pub fn format_task_delimited(tasks: &[TaskResponse], workflow: &StatusWorkflow, ids: IdStyle, columns: &[TaskColumn], delimiter: u8) -> Result<String, csv::Error> {
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(Vec::new());
+    writer.write_record(columns.iter().map(TaskColumn::name))?;
 
-    table.to_string()
+    let date = |date: Option<&DateTime<Utc>>| date.map(format_date).unwrap_or_default();
+    for task in tasks {
+        writer.write_record(columns.iter().map(|column| match column {
+            TaskColumn::Id => ids.format(task),
+            TaskColumn::Title => task.title.clone(),
+            TaskColumn::Status => workflow.name(task.status),
+            TaskColumn::Priority => task.priority.to_string(),
+            TaskColumn::Due => date(task.due_date.as_ref()),
+            TaskColumn::Start => date(task.start_date.as_ref()),
+            TaskColumn::Created => date(Some(&task.created_at)),
+            TaskColumn::Updated => date(Some(&task.updated_at)),
+            TaskColumn::Completed => date(task.completed_at.as_ref()),
+            // Minutes, so spreadsheets can sum them
+            TaskColumn::Estimate => task.estimate_minutes.map(|minutes| minutes.to_string()).unwrap_or_default(),
+            TaskColumn::Context => task.context.clone().unwrap_or_default(),
+            TaskColumn::Client => task.client.clone().unwrap_or_default(),
+            TaskColumn::Location => task.location.clone().unwrap_or_default(),
+        }))?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
 }
 
-pub fn format_task_detail(task: &TaskResponse) -> String {
+pub fn format_task_detail(task: &TaskResponse, workflow: &StatusWorkflow, ids: IdStyle, tracked: Option<&TrackedTime>) -> String {
     let mut output = String::new();
     
-    output.push_str(&format!("{}: {}\n", style("ID").bold(), style(&task.id).cyan()));
+    if ids == IdStyle::Uuid {
+        output.push_str(&format!("{}: {}\n", style("ID").bold(), style(&task.id).cyan()));
+    } else {
+        output.push_str(&format!("{}: {} {}\n", style("ID").bold(), style(ids.format(task)).cyan(), style(&task.id).dim()));
+    }
     output.push_str(&format!("{}: {}\n", style("Title").bold(), style(&task.title).green()));
+
+    if task.pinned {
+        output.push_str(&format!("{}: {}\n", style("Pinned").bold(), style("★ yes").yellow()));
+    }
     
     if let Some(description) = &task.description {
         output.push_str(&format!("{}: {}\n", style("Description").bold(), style(description).dim()));
     }
     
-    output.push_str(&format!("{}: {}\n", style("Status").bold(), format_status(&task.status)));
+    output.push_str(&format!("{}: {}\n", style("Status").bold(), format_status(&task.status, workflow)));
     output.push_str(&format!("{}: {}\n", style("Priority").bold(), format_priority(&task.priority)));
+
+    if let Some(estimate) = task.estimate_minutes {
+        output.push_str(&format!("{}: {}\n", style("Estimate").bold(), format_duration(i64::from(estimate) * 60)));
+    }
+
+    if let Some(client) = &task.client {
+        output.push_str(&format!("{}: {}\n", style("Client").bold(), client));
+    }
+
+    if let Some(context) = &task.context {
+        output.push_str(&format!("{}: {}\n", style("Context").bold(), context));
+    }
+
+    match (&task.location, task.coordinates()) {
+        (Some(label), Some(point)) => output.push_str(&format!("{}: {} ({})\n", style("Location").bold(), label, point)),
+        (Some(label), None) => output.push_str(&format!("{}: {}\n", style("Location").bold(), label)),
+        (None, Some(point)) => output.push_str(&format!("{}: {}\n", style("Location").bold(), point)),
+        (None, None) => {}
+    }
     
     if let Some(due_date) = task.due_date {
         let formatted_due = format_date(&due_date);
-        let color = if due_date < Utc::now() {
+        let color = if task.is_overdue() {
             style(formatted_due).red()
         } else {
             style(formatted_due).yellow()
@@ -74,9 +238,21 @@ pub fn format_task_detail(task: &TaskResponse) -> String {
         output.push_str(&format!("{}: {}\n", style("Due Date").bold(), color));
     }
     
+    if let Some(start_date) = task.start_date {
+        output.push_str(&format!("{}: {}\n", style("Start Date").bold(), format_date(&start_date)));
+    }
+
     if let Some(completed_at) = task.completed_at {
         output.push_str(&format!("{}: {}\n", style("Completed At").bold(), style(format_date(&completed_at)).green()));
     }
+
+    if let Some(tracked) = tracked.filter(|t| t.total_seconds > 0 || t.running) {
+        let mut time = format_duration(tracked.total_seconds);
+        if tracked.running {
+            time.push_str(" (timer running)");
+        }
+        output.push_str(&format!("{}: {}\n", style("Time Tracked").bold(), style(time).cyan()));
+    }
     
     output.push_str(&format!("{}: {}\n", style("Created").bold(), style(format_date(&task.created_at)).dim()));
     output.push_str(&format!("{}: {}\n", style("Updated").bold(), style(format_date(&task.updated_at)).dim()));
@@ -84,19 +260,668 @@ pub fn format_task_detail(task: &TaskResponse) -> String {
     output
 }
 
+#[derive(Tabled)]
+struct TimeReportRow {
+    #[tabled(rename = "ID")]
+    id: String,
+    #[tabled(rename = "Task")]
+    title: String,
+    #[tabled(rename = "Entries")]
+    entries: i64,
+    #[tabled(rename = "Time")]
+    time: String,
+}
+
+pub fn format_time_report(summaries: &[TaskTimeSummary], ids: IdStyle) -> String {
+    let total: i64 = summaries.iter().map(|s| s.total_seconds).sum();
+
+    let rows: Vec<TimeReportRow> = summaries
+        .iter()
+        .map(|summary| TimeReportRow {
+            id: ids.format_id(&summary.task_id, summary.task_number),
+            title: summary.title.clone(),
+            entries: summary.entry_count,
+            time: format_duration(summary.total_seconds),
+        })
+        .collect();
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+
+    format!("{}\n{}: {}", table, style("Total").bold(), style(format_duration(total)).cyan())
+}
+
+#[derive(Tabled)]
+struct BulkStatusRow {
+    #[tabled(rename = "ID")]
+    id: String,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Result")]
+    result: String,
+}
+
+/// One row per task of a bulk status update, in the order the IDs were given
+pub fn format_bulk_status_report(results: &[Result<TaskResponse, BulkItemError>], workflow: &StatusWorkflow, ids: IdStyle) -> String {
+    let rows: Vec<BulkStatusRow> = results
+        .iter()
+        .map(|result| match result {
+            Ok(task) => BulkStatusRow {
+                id: ids.format(task),
+                title: task.title.clone(),
+                result: style(format!("moved to {}", workflow.name(task.status))).green().to_string(),
+            },
+            Err(e) => BulkStatusRow {
+                // Tasks that failed may not exist, so their number is unknown
+                id: match ids {
+                    IdStyle::Uuid => e.task_id().to_string(),
+                    _ => short_hash(&e.task_id()),
+                },
+                title: "-".to_string(),
+                result: style(e).red().to_string(),
+            },
+        })
+        .collect();
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+
+    table.to_string()
+}
+
+#[derive(Tabled)]
+struct ContextRow {
+    #[tabled(rename = "Context")]
+    context: String,
+    #[tabled(rename = "Open Tasks")]
+    open_tasks: i64,
+}
+
+pub fn format_context_counts(counts: &[ContextCount]) -> String {
+    let rows: Vec<ContextRow> = counts
+        .iter()
+        .map(|count| ContextRow {
+            context: count.context.clone(),
+            open_tasks: count.open_tasks,
+        })
+        .collect();
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+
+    table.to_string()
+}
+
+#[derive(Tabled)]
+struct GroupBreakdownRow {
+    #[tabled(rename = "")]
+    group: String,
+    #[tabled(rename = "Open")]
+    open: i64,
+    #[tabled(rename = "Completed")]
+    completed: i64,
+    #[tabled(rename = "Total")]
+    total: i64,
+}
+
+/// Totals for `task stats`, then one line per grouping, largest groups first,
+/// the completion streak and open/completed tables by priority and context
+pub fn format_task_statistics(
+    stats: &TaskStatistics,
+    groups: &[(&str, HashMap<TaskGroup, i64>)],
+    detailed: &DetailedStatistics,
+    workflow: &StatusWorkflow,
+    streak: &CompletionStreak,
+) -> String {
+    let mut output = format!(
+        "{}: {}  {}: {}  {}: {}  {}: {}\n",
+        style("Tasks").bold(),
+        stats.total_tasks,
+        style("Open").bold(),
+        stats.open_tasks(),
+        style("Overdue").bold(),
+        if stats.overdue_tasks > 0 { style(stats.overdue_tasks).red() } else { style(0).green() },
+        style("Stale").bold(),
+        stats.stale_tasks
+    );
+
+    for (title, counts) in groups {
+        let mut counts: Vec<(String, i64)> = counts
+            .iter()
+            .map(|(group, count)| (group_label(group, workflow), *count))
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let line = if counts.is_empty() {
+            "none".to_string()
+        } else {
+            counts.iter().map(|(label, count)| format!("{} {}", label, count)).collect::<Vec<_>>().join(", ")
+        };
+        output.push_str(&format!("{}: {}\n", style(title).bold(), line));
+    }
+
+    let days = |count: u32| if count == 1 { "1 day".to_string() } else { format!("{} days", count) };
+    output.push_str(&format!(
+        "{}: {} (longest {})",
+        style("Streak").bold(),
+        if streak.current > 0 { style(days(streak.current)).green() } else { style(days(0)).dim() },
+        days(streak.longest)
+    ));
+
+    for (title, breakdown) in [("By priority", &detailed.by_priority), ("By context", &detailed.by_context)] {
+        if !breakdown.is_empty() {
+            output.push_str(&format!("\n\n{}\n{}", style(title).bold(), format_group_breakdown(breakdown, workflow)));
+        }
+    }
+
+    output
+}
+
+fn format_group_breakdown(breakdown: &[GroupBreakdown], workflow: &StatusWorkflow) -> String {
+    let mut rows: Vec<GroupBreakdownRow> = breakdown
+        .iter()
+        .map(|group| GroupBreakdownRow {
+            group: group_label(&group.group, workflow),
+            open: group.open,
+            completed: group.completed,
+            total: group.total(),
+        })
+        .collect();
+    rows.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.group.cmp(&b.group)));
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+
+    table.to_string()
+}
+
+fn group_label(group: &TaskGroup, workflow: &StatusWorkflow) -> String {
+    match group {
+        TaskGroup::Status(status) => workflow.name(*status),
+        TaskGroup::Priority(priority) => priority.to_string(),
+        TaskGroup::Context(Some(context)) => context.clone(),
+        TaskGroup::Context(None) => "no context".to_string(),
+    }
+}
+
+#[derive(Tabled)]
+struct MemberStatisticsRow {
+    #[tabled(rename = "Member")]
+    member: String,
+    #[tabled(rename = "Tasks")]
+    tasks: i64,
+    #[tabled(rename = "Completed")]
+    completed: i64,
+    #[tabled(rename = "Overdue")]
+    overdue: i64,
+}
+
+pub fn format_workspace_statistics(stats: &WorkspaceStatistics) -> String {
+    let totals = &stats.totals;
+    let mut output = format!(
+        "{}: {}  {}: {}  {}: {}  {}: {}\n",
+        style("Tasks").bold(),
+        totals.total_tasks,
+        style("Pending").bold(),
+        totals.pending_tasks,
+        style("In progress").bold(),
+        totals.in_progress_tasks,
+        style("Completed").bold(),
+        style(totals.completed_tasks).green()
+    );
+
+    let overdue = &stats.overdue;
+    if overdue.total() == 0 {
+        output.push_str(&format!("{}: {}\n", style("Overdue").bold(), style("none").green()));
+    } else {
+        output.push_str(&format!(
+            "{}: {} ({} up to a week, {} up to a month, {} older)\n",
+            style("Overdue").bold(),
+            style(overdue.total()).red(),
+            overdue.up_to_week,
+            overdue.up_to_month,
+            overdue.older
+        ));
+    }
+
+    let rows: Vec<MemberStatisticsRow> = stats
+        .members
+        .iter()
+        .map(|member| MemberStatisticsRow {
+            member: member.username.clone(),
+            tasks: member.counts.total_tasks,
+            completed: member.counts.completed_tasks,
+            overdue: member.counts.overdue_tasks,
+        })
+        .collect();
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+    output.push_str(&table.to_string());
+
+    output
+}
+
+#[derive(Tabled)]
+struct ConflictRow {
+    #[tabled(rename = "")]
+    field: &'static str,
+    #[tabled(rename = "Local")]
+    local: String,
+    #[tabled(rename = "Remote")]
+    remote: String,
+}
+
+/// Both versions of a task changed on both sides, field by field
+pub fn format_sync_conflict(conflict: &Conflict, ids: IdStyle) -> String {
+    let id = match conflict.local.as_ref().or(conflict.remote.as_ref()) {
+        Some(task) => ids.format(task),
+        None => short_hash(&conflict.task_id),
+    };
+    let mut output = format!(
+        "{} {} {}\n",
+        style("Task").bold(),
+        style(id).cyan(),
+        style(format!("(conflict since {})", format_date(&conflict.detected_at))).dim()
+    );
+
+    let field = |field: &'static str, value: fn(&Task) -> String| ConflictRow {
+        field,
+        local: conflict.local.as_ref().map(value).unwrap_or_else(|| "(deleted)".to_string()),
+        remote: conflict.remote.as_ref().map(value).unwrap_or_else(|| "(deleted)".to_string()),
+    };
+    let rows = vec![
+        field("Title", |task| task.title.clone()),
+        field("Description", |task| task.description.clone().unwrap_or_else(|| "-".to_string())),
+        field("Status", |task| task.status.to_string()),
+        field("Priority", |task| task.priority.to_string()),
+        field("Due", |task| task.due_date.as_ref().map(format_date).unwrap_or_else(|| "-".to_string())),
+        field("Updated", |task| format_date(&task.updated_at)),
+    ];
+
+    let mut table = Table::new(rows);
+    table.with(Style::rounded()).with(Alignment::left());
+    output.push_str(&table.to_string());
+    output
+}
+
+#[derive(Tabled)]
+struct BillableRow {
+    #[tabled(rename = "Client")]
+    client: String,
+    #[tabled(rename = "Task")]
+    task: String,
+    #[tabled(rename = "Hours")]
+    hours: String,
+    #[tabled(rename = "Rate")]
+    rate: String,
+    #[tabled(rename = "Amount")]
+    amount: String,
+}
+
+pub fn format_billable_report(report: &BillableReport) -> String {
+    let rows: Vec<BillableRow> = report
+        .lines
+        .iter()
+        .map(|line| BillableRow {
+            client: line.client.clone(),
+            task: line.task.clone(),
+            hours: format!("{:.2}", line.hours()),
+            rate: format!("{:.2}", line.rate),
+            amount: format!("{:.2} {}", line.amount, report.currency),
+        })
+        .collect();
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+
+    format!(
+        "{}\n{}: {} ({})",
+        table,
+        style("Total").bold(),
+        style(format!("{:.2} {}", report.total_amount(), report.currency)).cyan(),
+        format_duration(report.total_seconds())
+    )
+}
+
+#[derive(Tabled)]
+struct WorkloadRow {
+    #[tabled(rename = "Day")]
+    day: String,
+    #[tabled(rename = "Tasks")]
+    tasks: usize,
+    #[tabled(rename = "Estimated")]
+    estimated: String,
+    #[tabled(rename = "Unestimated")]
+    unestimated: usize,
+}
+
+impl WorkloadRow {
+    fn new(day: String, bucket: &WorkloadBucket) -> Self {
+        Self {
+            day,
+            tasks: bucket.task_count,
+            estimated: if bucket.estimate_minutes > 0 {
+                format_duration(bucket.estimate_minutes * 60)
+            } else {
+                "-".to_string()
+            },
+            unestimated: bucket.unestimated_count,
+        }
+    }
+}
+
+pub fn format_workload(workload: &Workload) -> String {
+    let mut rows = Vec::new();
+
+    if workload.overdue.task_count > 0 {
+        rows.push(WorkloadRow::new(style("Overdue").red().to_string(), &workload.overdue));
+    }
+    for day in &workload.days {
+        rows.push(WorkloadRow::new(day.date.format("%a %m/%d").to_string(), &day.bucket));
+    }
+    if workload.later.task_count > 0 {
+        rows.push(WorkloadRow::new("Later".to_string(), &workload.later));
+    }
+    if workload.undated.task_count > 0 {
+        rows.push(WorkloadRow::new(style("No due date").dim().to_string(), &workload.undated));
+    }
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::rounded())
+        .with(Alignment::left());
+
+    format!(
+        "{}\n{}: {}",
+        table,
+        style("Total remaining").bold(),
+        style(format_duration(workload.total_estimate_minutes() * 60)).cyan()
+    )
+}
+
+#[derive(Tabled)]
+struct MetricRow {
+    #[tabled(rename = "Metric")]
+    metric: &'static str,
+    #[tabled(rename = "Value")]
+    value: String,
+}
+
+#[derive(Tabled)]
+struct ContextTallyRow {
+    #[tabled(rename = "Context")]
+    context: String,
+    #[tabled(rename = "Completed")]
+    tasks: i64,
+}
+
+/// `report weekly` as terminal tables, or as Markdown to paste into notes
+pub fn format_weekly_report(report: &WeeklyReport, markdown: bool) -> String {
+    let rows = vec![
+        MetricRow { metric: "Created", value: report.created.to_string() },
+        MetricRow { metric: "Completed", value: report.completed.to_string() },
+        MetricRow {
+            metric: "Completion rate",
+            value: report.completion_rate.map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0)),
+        },
+        MetricRow {
+            metric: "Average age at completion",
+            value: report
+                .average_completion_seconds
+                .map_or("-".to_string(), |seconds| format_age(seconds.round() as i64)),
+        },
+    ];
+    let contexts: Vec<ContextTallyRow> = report
+        .top_contexts
+        .iter()
+        .map(|tally| ContextTallyRow { context: tally.context.clone(), tasks: tally.tasks })
+        .collect();
+
+    let render = |mut table: Table| {
+        if markdown {
+            table.with(Style::markdown());
+        } else {
+            table.with(Style::rounded()).with(Alignment::left());
+        }
+        table.to_string()
+    };
+
+    let period = format!(
+        "{} - {}",
+        format_day(&calendar::local_date(&report.since)),
+        format_day(&calendar::local_date(&report.until))
+    );
+    let mut output = if markdown { format!("## Week {}\n\n", period) } else { format!("{}\n", style(period).dim()) };
+    output.push_str(&render(Table::new(rows)));
+    if !contexts.is_empty() {
+        output.push_str(if markdown { "\n\n### Top contexts\n\n" } else { "\n" });
+        output.push_str(&render(Table::new(contexts)));
+    }
+    output
+}
+
+/// Rows of the burndown chart
+const CHART_HEIGHT: i64 = 8;
+/// Most columns of the burndown chart; longer periods put several days in a column
+const CHART_WIDTH: usize = 60;
+
+/// `report burndown` as a bar chart of the open tasks, one column per day
+/// (or per few days), with the totals below
+pub fn format_burndown(burndown: &Burndown) -> String {
+    let (Some(first), Some(last)) = (burndown.days.first(), burndown.days.last()) else {
+        return String::new();
+    };
+
+    // A column shows the open count at the end of its last day
+    let per_column = burndown.days.len().div_ceil(CHART_WIDTH);
+    let columns: Vec<i64> = burndown
+        .days
+        .chunks(per_column)
+        .map(|chunk| chunk.last().map_or(0, |day| day.open))
+        .collect();
+    let peak = columns.iter().copied().max().unwrap_or(0).max(1);
+    let label_width = peak.to_string().len();
+
+    let mut output = String::new();
+    for row in (1..=CHART_HEIGHT).rev() {
+        let label = if row == CHART_HEIGHT { peak.to_string() } else { String::new() };
+        let bars: String = columns
+            .iter()
+            .map(|open| {
+                // Round to the nearest row, but show any open task at all
+                let height = (open * CHART_HEIGHT * 2 + peak) / (peak * 2);
+                if height >= row || (row == 1 && *open > 0) { '█' } else { ' ' }
+            })
+            .collect();
+        output.push_str(&format!("{:>width$} │{}\n", label, style(bars).cyan(), width = label_width));
+    }
+    output.push_str(&format!("{:>width$} └{}\n", 0, "─".repeat(columns.len()), width = label_width));
+
+    let first_label = first.date.format("%m/%d").to_string();
+    let last_label = last.date.format("%m/%d").to_string();
+    let gap = (columns.len() + 1).saturating_sub(first_label.len() + last_label.len()).max(1);
+    output.push_str(&format!("{:width$}  {}{}{}\n", "", first_label, " ".repeat(gap), last_label, width = label_width));
+
+    let change = last.open - first.open;
+    let trend = match change {
+        change if change < 0 => style(format!("{}", change)).green(),
+        0 => style("±0".to_string()).dim(),
+        change => style(format!("+{}", change)).red(),
+    };
+    output.push_str(&format!(
+        "{}: {} → {} ({})  {}: {}  {}: {}",
+        style("Open").bold(),
+        first.open,
+        last.open,
+        trend,
+        style("Created").bold(),
+        burndown.created(),
+        style("Completed").bold(),
+        burndown.completed()
+    ));
+    output
+}
+
+/// Row labels of the heatmap, Monday first
+const HEATMAP_WEEKDAYS: [&str; 7] = ["Mon", "", "Wed", "", "Fri", "", ""];
+/// 256-color greens of heatmap levels 1 to `Heatmap::LEVELS`
+const HEATMAP_COLORS: [u8; 4] = [22, 28, 34, 46];
+
+fn heatmap_cell(level: i64) -> String {
+    match level {
+        level if level <= 0 => style("·").dim().to_string(),
+        level => style("■").color256(HEATMAP_COLORS[(level.min(Heatmap::LEVELS) - 1) as usize]).to_string(),
+    }
+}
+
+/// Calendar of `report heatmap`: one column per week, one row per weekday,
+/// each day shaded by its completions relative to the busiest day
+pub fn format_heatmap(heatmap: &Heatmap) -> String {
+    let (Some(first), Some(last)) = (
+        NaiveDate::from_ymd_opt(heatmap.year, 1, 1),
+        NaiveDate::from_ymd_opt(heatmap.year, 12, 31),
+    ) else {
+        return String::new();
+    };
+    let grid_start = first - chrono::Duration::days(i64::from(first.weekday().num_days_from_monday()));
+    let column = |date: NaiveDate| ((date - grid_start).num_days() / 7) as usize;
+    let weeks = column(last) + 1;
+
+    let mut months = vec![' '; weeks];
+    for month in 1..=12 {
+        let Some(date) = NaiveDate::from_ymd_opt(heatmap.year, month, 1) else { continue };
+        for (offset, letter) in date.format("%b").to_string().chars().enumerate() {
+            if let Some(slot) = months.get_mut(column(date) + offset) {
+                *slot = letter;
+            }
+        }
+    }
+
+    let mut output = format!("    {}\n", months.iter().collect::<String>());
+    for (weekday, label) in HEATMAP_WEEKDAYS.iter().enumerate() {
+        let cells: String = (0..weeks)
+            .map(|week| {
+                let date = grid_start + chrono::Duration::days((week * 7 + weekday) as i64);
+                if date < first || date > last {
+                    " ".to_string()
+                } else {
+                    heatmap_cell(heatmap.level(heatmap.count_on(date)))
+                }
+            })
+            .collect();
+        output.push_str(&format!("{:<3} {}\n", label, cells));
+    }
+
+    let legend: String = (0..=Heatmap::LEVELS).map(heatmap_cell).collect();
+    output.push_str(&format!("    Less {} More\n", legend));
+    let busiest = heatmap
+        .busiest()
+        .map(|day| format!("  {}: {} ({})", style("Busiest").bold(), day.date.format("%b %-d"), day.count))
+        .unwrap_or_default();
+    output.push_str(&format!("{}: {}{}", style("Completed").bold(), heatmap.total(), busiest));
+    output
+}
+
+/// The forecast of `report forecast`, with the clearing dates counted from `today`
+pub fn format_forecast(forecast: &Forecast, today: NaiveDate) -> String {
+    let completed: i64 = forecast.weekly_completions.iter().sum();
+    let weekly = forecast.weekly_completions.iter().map(|count| count.to_string()).collect::<Vec<_>>().join(", ");
+    let mut output = format!(
+        "{}: {}  {}: {:.1}/day ({} completed; per week {})\n",
+        style("Open").bold(),
+        forecast.open,
+        style("Velocity").bold(),
+        forecast.velocity,
+        completed,
+        weekly
+    );
+
+    let cleared = |date: Option<NaiveDate>| match date {
+        Some(date) if date <= today => style("now".to_string()).green(),
+        Some(date) => style(format!("{} (in {} days)", format_day(&date), (date - today).num_days())),
+        None => style("never at this pace".to_string()).red(),
+    };
+    for (label, date) in [
+        ("Expected", forecast.expected),
+        ("Optimistic", forecast.optimistic),
+        ("Pessimistic", forecast.pessimistic),
+    ] {
+        output.push_str(&format!("{} {}\n", style(format!("{:<12}", format!("{}:", label))).bold(), cleared(date)));
+    }
+
+    output.trim_end().to_string()
+}
+
+/// Format a number of seconds as e.g. `3d 04h`, or like `format_duration` below a day
+pub fn format_age(total_seconds: i64) -> String {
+    let days = total_seconds / 86_400;
+    if days > 0 {
+        format!("{}d {:02}h", days, (total_seconds % 86_400) / 3600)
+    } else {
+        format_duration(total_seconds)
+    }
+}
+
+/// Format a number of seconds as e.g. `2h 05m`, `12m` or `40s`
+pub fn format_duration(total_seconds: i64) -> String {
+    let total_seconds = total_seconds.max(0);
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", total_seconds)
+    }
+}
+
+/// Dates are shown like this unless the `date_format` setting says otherwise
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+static DATE_FORMAT: RwLock<String> = RwLock::new(String::new());
+
+/// Show dates in `format`, a strftime pattern such as `%d.%m.%Y`; the CLI sets
+/// it from the `date_format` setting on startup
+pub fn set_date_format(format: &str) {
+    *DATE_FORMAT.write().unwrap() = format.to_string();
+}
+
+/// A calendar day in the user's date format
+pub fn format_day(date: &NaiveDate) -> String {
+    let format = DATE_FORMAT.read().unwrap();
+    date.format(if format.is_empty() { DEFAULT_DATE_FORMAT } else { &format }).to_string()
+}
+
+/// An instant as the user's date and local time of day, in the user's timezone
 pub fn format_date(dt: &DateTime<Utc>) -> String {
-    dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()
+    let local = calendar::local_time(dt);
+    format!("{} {}", format_day(&local.date_naive()), local.format("%H:%M:%S"))
 }
 
 pub fn format_date_short(dt: &DateTime<Utc>) -> String {
-    dt.with_timezone(&Local).format("%m/%d").to_string()
+    calendar::local_time(dt).format("%m/%d").to_string()
 }
 
-fn format_status(status: &TaskStatus) -> String {
+fn format_status(status: &TaskStatus, workflow: &StatusWorkflow) -> String {
     match status {
         TaskStatus::Pending => style("Pending").yellow().to_string(),
         TaskStatus::InProgress => style("In Progress").cyan().to_string(),
         TaskStatus::Completed => style("Completed").green().to_string(),
+        TaskStatus::Custom(_) => style(workflow.name(*status)).magenta().to_string(),
     }
 }
 
@@ -107,4 +932,3 @@ fn format_priority(priority: &TaskPriority) -> String {
         TaskPriority::High => style("High").red().to_string(),
     }
 }
-
@@ -1,5 +1,10 @@
 pub mod validation;
 pub mod formatting;
 pub mod config;
+pub mod projection;
+pub mod pager;
+pub mod settings;
+pub mod tokens;
+pub mod timing;
 
 // Re-exports
@@ -0,0 +1,67 @@
+use console::Term;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// Pager used when `$PAGER` is not set
+const DEFAULT_PAGER: &str = "less";
+
+/// `less` options used when `$LESS` is not set, as git does: quit when the
+/// output fits after all, keep colors and leave the output on screen
+const DEFAULT_LESS: &str = "FRX";
+
+/// Shows listings taller than the terminal a page at a time, through `$PAGER`
+/// or `less`. Output that fits, or that goes to a file or pipe, is printed as is.
+#[derive(Debug, Clone, Copy)]
+pub struct Pager {
+    enabled: bool,
+}
+
+impl Pager {
+    /// A pager that pages only when `enabled`, i.e. without `--no-pager` and
+    /// with the `ui.pager` setting on
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Print `output`, through the pager when it does not fit the terminal
+    pub fn print(&self, output: &str) {
+        let too_tall = self.enabled
+            && Term::stdout()
+                .size_checked()
+                .is_some_and(|(rows, _)| output.lines().count() >= usize::from(rows));
+        // When the pager cannot be started or fed, the output is printed after all
+        if !too_tall || page(output).is_err() {
+            print!("{}", output);
+        }
+    }
+}
+
+fn page(output: &str) -> std::io::Result<()> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+    let mut words = pager.split_whitespace();
+    let Some(program) = words.next() else {
+        // PAGER= turns paging off, as for git
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+
+    let mut child = Command::new(program)
+        .args(words)
+        .env("LESS", std::env::var("LESS").unwrap_or_else(|_| DEFAULT_LESS.to_string()))
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let written = match child.stdin.take() {
+        Some(mut stdin) => match stdin.write_all(output.as_bytes()) {
+            // Quitting the pager before the end closes the pipe; that is not an error
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+            _ => Ok(()),
+        },
+        None => Ok(()),
+    };
+    // Only a failed write makes the caller print the output itself; once it
+    // reached the pager, a failed wait must not show it a second time
+    if let Err(e) = child.wait() {
+        warn!("Failed to wait for the pager: {}", e);
+    }
+    written
+}
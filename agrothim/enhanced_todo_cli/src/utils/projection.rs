@@ -0,0 +1,119 @@
+use serde_json::{Map, Value};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ProjectionError {
+    #[error("Field list is empty")]
+    Empty,
+    #[error("Invalid field path '{path}'")]
+    InvalidPath { path: String },
+}
+
+/// A list of field paths to keep in JSON output, parsed from `--fields`.
+///
+/// Paths are comma-separated and use dots to reach nested values
+/// (`id,title,owner.name`); numeric segments index into arrays. Projection is
+/// applied to every element when the value is an array, and fields that are
+/// missing from the input come out as `null` so the output shape is stable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldProjection {
+    paths: Vec<Vec<String>>,
+}
+
+impl FromStr for FieldProjection {
+    type Err = ProjectionError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut paths = Vec::new();
+
+        for raw in input.split(',').map(str::trim).filter(|raw| !raw.is_empty()) {
+            let segments: Vec<String> = raw.split('.').map(|s| s.trim().to_string()).collect();
+            if segments.iter().any(String::is_empty) {
+                return Err(ProjectionError::InvalidPath { path: raw.to_string() });
+            }
+            paths.push(segments);
+        }
+
+        if paths.is_empty() {
+            return Err(ProjectionError::Empty);
+        }
+
+        Ok(Self { paths })
+    }
+}
+
+impl FieldProjection {
+    pub fn apply(&self, value: &Value) -> Value {
+        match value {
+            Value::Array(items) => Value::Array(items.iter().map(|item| self.project(item)).collect()),
+            other => self.project(other),
+        }
+    }
+
+    fn project(&self, value: &Value) -> Value {
+        let mut output = Map::new();
+        for path in &self.paths {
+            let selected = lookup(value, path).cloned().unwrap_or(Value::Null);
+            insert(&mut output, path, selected);
+        }
+        Value::Object(output)
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |current, segment| match current {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => None,
+    })
+}
+
+/// Insert `value` at `path`, creating intermediate objects so nested paths
+/// keep their structure in the output
+fn insert(output: &mut Map<String, Value>, path: &[String], value: Value) {
+    let (last, parents) = path.split_last().expect("paths are never empty");
+    let mut target = output;
+    for segment in parents {
+        let entry = target
+            .entry(segment.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        target = entry.as_object_mut().expect("entry was just made an object");
+    }
+    target.insert(last.clone(), value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_projects_top_level_and_nested_fields() {
+        let projection: FieldProjection = "id, title,owner.name,tags.0".parse().unwrap();
+        let input = json!([
+            { "id": 1, "title": "A", "extra": true, "owner": { "name": "sam", "email": "x" }, "tags": ["a", "b"] },
+            { "id": 2, "title": "B" }
+        ]);
+
+        assert_eq!(
+            projection.apply(&input),
+            json!([
+                { "id": 1, "title": "A", "owner": { "name": "sam" }, "tags": { "0": "a" } },
+                { "id": 2, "title": "B", "owner": { "name": null }, "tags": { "0": null } }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_field_lists() {
+        assert_eq!("".parse::<FieldProjection>(), Err(ProjectionError::Empty));
+        assert_eq!(
+            "id,owner..name".parse::<FieldProjection>(),
+            Err(ProjectionError::InvalidPath { path: "owner..name".to_string() })
+        );
+    }
+}
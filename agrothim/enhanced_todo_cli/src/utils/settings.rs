@@ -0,0 +1,582 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::models::calendar::UserTimezone;
+use crate::models::location::GeoPoint;
+use crate::models::quota::QuotaThresholds;
+use crate::models::sync::ConflictStrategy;
+use crate::models::task_column::TaskColumn;
+use crate::models::task_ref::IdStyle;
+use crate::services::{AuthConfig, MaintenancePolicy, ReminderConfig};
+use crate::utils::validation::parse_duration;
+
+const SETTINGS_FILE: &str = "config.json";
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Unknown setting '{key}'. Available settings: {available}")]
+    UnknownKey { key: String, available: String },
+
+    #[error("Invalid value for '{key}': {message}")]
+    InvalidValue { key: String, message: String },
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// A setting that can be changed with `config set`
+struct SettingSpec {
+    /// Full key, or a prefix ending in '.' for per-name keys like `billing.rate.<client>`
+    key: &'static str,
+    description: &'static str,
+    validate: fn(&str) -> Result<(), String>,
+}
+
+const SETTINGS: &[SettingSpec] = &[
+    SettingSpec {
+        key: "billing.rate",
+        description: "default hourly rate for billable time",
+        validate: validate_rate,
+    },
+    SettingSpec {
+        key: "billing.rate.",
+        description: "hourly rate for one client, e.g. billing.rate.acme",
+        validate: validate_rate,
+    },
+    SettingSpec {
+        key: "billing.currency",
+        description: "currency shown on billable reports",
+        validate: validate_currency,
+    },
+    SettingSpec {
+        key: "location.",
+        description: "coordinates of a named place, e.g. location.office = 52.52,13.405",
+        validate: validate_location,
+    },
+    SettingSpec {
+        key: "display.ids",
+        description: "how task IDs are shown: hash, number or uuid",
+        validate: validate_id_style,
+    },
+    SettingSpec {
+        key: "display.columns",
+        description: "comma-separated columns of `task list`, e.g. id,title,due,context,priority",
+        validate: validate_columns,
+    },
+    SettingSpec {
+        key: "timezone",
+        description: "IANA timezone dates are shown and days end in, e.g. Europe/Berlin; local (the system's) by default",
+        validate: validate_timezone,
+    },
+    SettingSpec {
+        key: "date_format",
+        description: "strftime pattern dates are shown in, e.g. %d.%m.%Y; %Y-%m-%d by default",
+        validate: validate_date_format,
+    },
+    SettingSpec {
+        key: "ui.show_timing",
+        description: "print how long list commands spent on the database and rendering: true or false",
+        validate: validate_bool,
+    },
+    SettingSpec {
+        key: "ui.pager",
+        description: "page long listings through $PAGER or less when they do not fit the terminal: true or false",
+        validate: validate_bool,
+    },
+    SettingSpec {
+        key: "quota.warn",
+        description: "open task count at which `task add` suggests a cleanup, 0 to disable",
+        validate: validate_count,
+    },
+    SettingSpec {
+        key: "quota.critical",
+        description: "open task count at which the cleanup suggestion becomes urgent, 0 to disable",
+        validate: validate_count,
+    },
+    SettingSpec {
+        key: "database.auto_migrate",
+        description: "apply pending migrations whenever the CLI connects: true or false",
+        validate: validate_bool,
+    },
+    SettingSpec {
+        key: "auth.token_expiry_hours",
+        description: "lifetime of access tokens in hours, 1 to 8760",
+        validate: validate_token_expiry_hours,
+    },
+    SettingSpec {
+        key: "auth.refresh_token_expiry_days",
+        description: "lifetime of refresh tokens and device sessions in days, 1 to 365",
+        validate: validate_refresh_token_expiry_days,
+    },
+    SettingSpec {
+        key: "auth.session_dir",
+        description: "directory holding the login session, outbox and offline journal instead of .todo-cli",
+        validate: validate_path,
+    },
+    SettingSpec {
+        key: "sync.conflicts",
+        description: "how sync settles tasks changed on both sides: latest (the later change wins) or ask (keep both until `todo-cli sync conflicts`)",
+        validate: validate_conflict_strategy,
+    },
+    SettingSpec {
+        key: "api.url",
+        description: "URL of a running `todo-cli serve` to work through instead of DATABASE_URL",
+        validate: validate_api_url,
+    },
+    SettingSpec {
+        key: "github.token",
+        description: "personal access token `sync github` reads and closes issues with",
+        validate: validate_token,
+    },
+    SettingSpec {
+        key: "github.api_url",
+        description: "GitHub Enterprise API URL, e.g. https://github.example.com/api/v3; api.github.com by default",
+        validate: validate_api_url,
+    },
+    SettingSpec {
+        key: "reminders.desktop",
+        description: "pop desktop notifications for due-soon and overdue tasks: true or false",
+        validate: validate_bool,
+    },
+    SettingSpec {
+        key: "reminders.email",
+        description: "email reminders for due-soon and overdue tasks: true or false",
+        validate: validate_bool,
+    },
+    SettingSpec {
+        key: "reminders.slack",
+        description: "Slack incoming webhook URL to post due-soon and overdue reminders to",
+        validate: validate_api_url,
+    },
+    SettingSpec {
+        key: "reminders.due_soon",
+        description: "how long before its due date a task counts as due soon, e.g. 24h or 90m",
+        validate: validate_duration,
+    },
+    SettingSpec {
+        key: "maintenance.archive_after",
+        description: "move tasks completed longer ago than this to an archive file on `maintain`, e.g. 30d; off when unset",
+        validate: validate_duration,
+    },
+    SettingSpec {
+        key: "maintenance.purge_after",
+        description: "delete archive files older than this on `maintain`, e.g. 90d; off when unset",
+        validate: validate_duration,
+    },
+];
+
+/// Settings `config show` masks
+const SECRET_SETTINGS: &[&str] = &["github.token", "reminders.slack"];
+
+fn validate_rate(value: &str) -> Result<(), String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate >= 0.0 => Ok(()),
+        _ => Err("expected a non-negative number".to_string()),
+    }
+}
+
+fn validate_currency(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.len() > 10 {
+        return Err("expected 1-10 characters".to_string());
+    }
+    Ok(())
+}
+
+fn validate_location(value: &str) -> Result<(), String> {
+    value.parse::<GeoPoint>().map(|_| ())
+}
+
+fn validate_id_style(value: &str) -> Result<(), String> {
+    value.parse::<IdStyle>().map(|_| ())
+}
+
+fn validate_columns(value: &str) -> Result<(), String> {
+    TaskColumn::parse_list(value).map(|_| ())
+}
+
+fn validate_timezone(value: &str) -> Result<(), String> {
+    value.parse::<UserTimezone>().map(|_| ())
+}
+
+fn validate_date_format(value: &str) -> Result<(), String> {
+    // Formatting fails on unknown specifiers and on times of day, which a
+    // date does not have
+    let mut sample = String::new();
+    if value.is_empty() || write!(sample, "{}", NaiveDate::MIN.format(value)).is_err() {
+        return Err("expected a strftime date pattern, e.g. %d.%m.%Y or %b %-d, %Y".to_string());
+    }
+    Ok(())
+}
+
+fn validate_conflict_strategy(value: &str) -> Result<(), String> {
+    value.parse::<ConflictStrategy>().map(|_| ())
+}
+
+fn validate_bool(value: &str) -> Result<(), String> {
+    value.parse::<bool>().map(|_| ()).map_err(|_| "expected true or false".to_string())
+}
+
+fn validate_count(value: &str) -> Result<(), String> {
+    value.parse::<u32>().map(|_| ()).map_err(|_| "expected a non-negative whole number".to_string())
+}
+
+fn validate_token_expiry_hours(value: &str) -> Result<(), String> {
+    match value.parse::<i64>() {
+        Ok(hours) if (1..=8760).contains(&hours) => Ok(()),
+        _ => Err("expected a whole number of hours between 1 and 8760".to_string()),
+    }
+}
+
+fn validate_refresh_token_expiry_days(value: &str) -> Result<(), String> {
+    match value.parse::<i64>() {
+        Ok(days) if (1..=365).contains(&days) => Ok(()),
+        _ => Err("expected a whole number of days between 1 and 365".to_string()),
+    }
+}
+
+fn validate_path(value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err("expected a directory path".to_string());
+    }
+    Ok(())
+}
+
+fn validate_api_url(value: &str) -> Result<(), String> {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        _ => Err("expected an http:// or https:// URL, e.g. http://localhost:8080".to_string()),
+    }
+}
+
+fn validate_duration(value: &str) -> Result<(), String> {
+    match parse_duration(value)? {
+        duration if duration.is_zero() => Err("expected a duration longer than zero".to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn validate_token(value: &str) -> Result<(), String> {
+    if value.is_empty() || value.chars().any(char::is_whitespace) {
+        return Err("expected a token without spaces".to_string());
+    }
+    Ok(())
+}
+
+fn spec_for(key: &str) -> Option<&'static SettingSpec> {
+    SETTINGS.iter().find(|spec| {
+        if spec.key.ends_with('.') {
+            key.len() > spec.key.len() && key.starts_with(spec.key)
+        } else {
+            key == spec.key
+        }
+    })
+}
+
+/// User settings stored as a flat map of dotted keys in `.todo-cli/config.json`
+#[derive(Debug)]
+pub struct Settings {
+    path: PathBuf,
+    values: BTreeMap<String, String>,
+}
+
+impl Settings {
+    /// Load settings from `dir`; a missing file means every setting is unset
+    pub fn load(dir: &Path) -> Result<Self, SettingsError> {
+        let path = dir.join(SETTINGS_FILE);
+        let values = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { path, values })
+    }
+
+    pub fn save(&self) -> Result<(), SettingsError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.values)?)?;
+        // The file may hold a token
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Set a known setting after validating its value
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), SettingsError> {
+        let spec = spec_for(key).ok_or_else(|| SettingsError::UnknownKey {
+            key: key.to_string(),
+            available: Self::available_keys().join(", "),
+        })?;
+
+        let value = value.trim();
+        (spec.validate)(value).map_err(|message| SettingsError::InvalidValue {
+            key: key.to_string(),
+            message,
+        })?;
+
+        self.values.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Remove one setting; whether it was set
+    pub fn unset(&mut self, key: &str) -> bool {
+        self.values.remove(key).is_some()
+    }
+
+    pub fn reset(&mut self) {
+        self.values.clear();
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Whether the setting holds a credential that should not be printed
+    pub fn is_secret(key: &str) -> bool {
+        SECRET_SETTINGS.contains(&key)
+    }
+
+    /// Keys accepted by `set`, with a short description of each
+    pub fn available_keys() -> Vec<String> {
+        SETTINGS
+            .iter()
+            .map(|spec| match spec.key.strip_suffix('.') {
+                Some(prefix) => format!("{}.<name> ({})", prefix, spec.description),
+                None => format!("{} ({})", spec.key, spec.description),
+            })
+            .collect()
+    }
+
+    /// Hourly rate for a client, falling back to the default rate
+    pub fn hourly_rate(&self, client: &str) -> Option<f64> {
+        self.get(&format!("billing.rate.{}", client))
+            .or_else(|| self.get("billing.rate"))
+            .and_then(|rate| rate.parse().ok())
+    }
+
+    pub fn currency(&self) -> &str {
+        self.get("billing.currency").unwrap_or("USD")
+    }
+
+    /// Coordinates of a named location such as "office"
+    pub fn location(&self, name: &str) -> Option<GeoPoint> {
+        self.get(&format!("location.{}", name.to_lowercase()))
+            .and_then(|point| point.parse().ok())
+    }
+
+    pub fn id_style(&self) -> IdStyle {
+        self.get("display.ids")
+            .and_then(|style| style.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Columns of `task list` unless `--columns` picks others
+    pub fn task_columns(&self) -> Vec<TaskColumn> {
+        self.get("display.columns")
+            .and_then(|columns| TaskColumn::parse_list(columns).ok())
+            .unwrap_or_else(|| TaskColumn::DEFAULT.to_vec())
+    }
+
+    pub fn timezone(&self) -> UserTimezone {
+        self.get("timezone")
+            .and_then(|timezone| timezone.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// The `date_format` setting, if set to a pattern that formats dates
+    pub fn date_format(&self) -> Option<&str> {
+        self.get("date_format").filter(|format| validate_date_format(format).is_ok())
+    }
+
+    pub fn show_timing(&self) -> bool {
+        self.get("ui.show_timing") == Some("true")
+    }
+
+    /// Whether long listings are paged; on unless set to false
+    pub fn pager(&self) -> bool {
+        self.get("ui.pager") != Some("false")
+    }
+
+    pub fn auto_migrate(&self) -> bool {
+        self.get("database.auto_migrate") == Some("true")
+    }
+
+    /// Soft limits for open tasks, falling back to the defaults for unset keys
+    pub fn quota_thresholds(&self) -> QuotaThresholds {
+        let defaults = QuotaThresholds::default();
+        let threshold = |key: &str, default: Option<i64>| match self.get(key).and_then(|value| value.parse().ok()) {
+            Some(0) => None,
+            Some(count) => Some(count),
+            None => default,
+        };
+
+        QuotaThresholds {
+            warn: threshold("quota.warn", defaults.warn),
+            critical: threshold("quota.critical", defaults.critical),
+        }
+    }
+
+    pub fn conflict_strategy(&self) -> ConflictStrategy {
+        self.get("sync.conflicts")
+            .and_then(|strategy| strategy.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Server the CLI works through in remote mode
+    pub fn api_url(&self) -> Option<&str> {
+        self.get("api.url")
+    }
+
+    pub fn github_token(&self) -> Option<&str> {
+        self.get("github.token")
+    }
+
+    pub fn github_api_url(&self) -> Option<&str> {
+        self.get("github.api_url")
+    }
+
+    /// Reminder channels and due-soon window, falling back to the defaults for unset keys
+    pub fn reminder_config(&self) -> ReminderConfig {
+        let defaults = ReminderConfig::default();
+        let flag = |key: &str, default: bool| self.get(key).map_or(default, |value| value == "true");
+
+        ReminderConfig {
+            desktop: flag("reminders.desktop", defaults.desktop),
+            email: flag("reminders.email", defaults.email),
+            slack: self.get("reminders.slack").map(str::to_string),
+            due_soon: self
+                .get("reminders.due_soon")
+                .and_then(|value| parse_duration(value).ok())
+                .and_then(|duration| chrono::Duration::from_std(duration).ok())
+                .unwrap_or(defaults.due_soon),
+        }
+    }
+
+    /// Archive and purge ages; an unset key leaves that step off
+    pub fn maintenance_policy(&self) -> MaintenancePolicy {
+        let age = |key: &str| {
+            self.get(key)
+                .and_then(|value| parse_duration(value).ok())
+                .and_then(|duration| chrono::Duration::from_std(duration).ok())
+        };
+
+        MaintenancePolicy {
+            archive_after: age("maintenance.archive_after"),
+            purge_after: age("maintenance.purge_after"),
+        }
+    }
+
+    /// Token lifetimes and session directory, falling back to the defaults for unset keys
+    pub fn auth_config(&self) -> AuthConfig {
+        let defaults = AuthConfig::default();
+
+        AuthConfig {
+            session_dir: self.get("auth.session_dir").map(PathBuf::from),
+            token_expiry_hours: self
+                .get("auth.token_expiry_hours")
+                .and_then(|hours| hours.parse().ok())
+                .unwrap_or(defaults.token_expiry_hours),
+            refresh_token_expiry_days: self
+                .get("auth.refresh_token_expiry_days")
+                .and_then(|days| days.parse().ok())
+                .unwrap_or(defaults.refresh_token_expiry_days),
+            ..defaults
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = TempDir::new().unwrap();
+
+        let mut settings = Settings::load(dir.path()).unwrap();
+        settings.set("billing.rate", "100").unwrap();
+        settings.set("billing.rate.acme", "150.5").unwrap();
+        settings.set("location.office", "52.52, 13.405").unwrap();
+        settings.set("display.ids", "Number").unwrap();
+        settings.set("ui.show_timing", "true").unwrap();
+        assert!(matches!(settings.set("ui.show_timing", "yes"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("ui.pager", "false").unwrap();
+        settings.set("quota.warn", "0").unwrap();
+        settings.set("quota.critical", "80").unwrap();
+        assert!(matches!(settings.set("quota.warn", "-5"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("display.ids", "emoji"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("location.home", "north"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("billing.rate", "cheap"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("billing.rate.", "1"), Err(SettingsError::UnknownKey { .. })));
+        assert!(matches!(settings.set("theme", "dark"), Err(SettingsError::UnknownKey { .. })));
+        settings.set("auth.token_expiry_hours", "2").unwrap();
+        settings.set("auth.session_dir", "/tmp/todo-session").unwrap();
+        assert!(matches!(settings.set("auth.token_expiry_hours", "0"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("auth.refresh_token_expiry_days", "1y"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("api.url", "http://localhost:8080").unwrap();
+        settings.set("sync.conflicts", "Ask").unwrap();
+        assert!(matches!(settings.set("sync.conflicts", "oldest"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("api.url", "localhost:8080"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("github.token", "ghp_abc123").unwrap();
+        assert!(matches!(settings.set("github.token", "ghp abc"), Err(SettingsError::InvalidValue { .. })));
+        assert!(Settings::is_secret("github.token"));
+        assert!(!Settings::is_secret("github.api_url"));
+        settings.set("reminders.desktop", "false").unwrap();
+        settings.set("reminders.due_soon", "2h30m").unwrap();
+        assert!(matches!(settings.set("reminders.due_soon", "0s"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("reminders.email", "on"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("display.columns", "id,title,due,context").unwrap();
+        assert!(matches!(settings.set("display.columns", "id,tags"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("timezone", "Asia/Ho_Chi_Minh").unwrap();
+        assert!(matches!(settings.set("timezone", "GMT+7"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("date_format", "%d.%m.%Y").unwrap();
+        assert!(matches!(settings.set("date_format", "%d.%m.%Y %H:%M"), Err(SettingsError::InvalidValue { .. })));
+        assert!(matches!(settings.set("date_format", "%Q"), Err(SettingsError::InvalidValue { .. })));
+        settings.set("billing.currency", "EUR").unwrap();
+        assert!(settings.unset("billing.currency"));
+        assert!(!settings.unset("billing.currency"));
+        settings.save().unwrap();
+
+        let settings = Settings::load(dir.path()).unwrap();
+        assert_eq!(settings.api_url(), Some("http://localhost:8080"));
+        assert_eq!(settings.github_token(), Some("ghp_abc123"));
+        assert_eq!(settings.github_api_url(), None);
+        let reminders = settings.reminder_config();
+        assert!(!reminders.desktop && !reminders.email);
+        assert_eq!(reminders.due_soon, chrono::Duration::minutes(150));
+        assert_eq!(settings.conflict_strategy(), ConflictStrategy::Ask);
+        assert_eq!(settings.hourly_rate("acme"), Some(150.5));
+        assert_eq!(settings.hourly_rate("globex"), Some(100.0));
+        assert_eq!(settings.currency(), "USD");
+        assert_eq!(settings.location("Office"), Some(GeoPoint::new(52.52, 13.405).unwrap()));
+        assert_eq!(settings.location("home"), None);
+        assert_eq!(settings.id_style(), IdStyle::Number);
+        assert!(settings.show_timing());
+        assert!(!settings.pager());
+        assert_eq!(settings.task_columns(), [TaskColumn::Id, TaskColumn::Title, TaskColumn::Due, TaskColumn::Context]);
+        assert_eq!(settings.timezone(), UserTimezone::Named(chrono_tz::Asia::Ho_Chi_Minh));
+        assert_eq!(settings.date_format(), Some("%d.%m.%Y"));
+        assert_eq!(settings.quota_thresholds(), QuotaThresholds { warn: None, critical: Some(80) });
+        let auth = settings.auth_config();
+        assert_eq!(auth.token_expiry_hours, 2);
+        assert_eq!(auth.refresh_token_expiry_days, 30);
+        assert_eq!(auth.session_dir, Some(PathBuf::from("/tmp/todo-session")));
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{Filtered, Targets};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Target of the spans and events that feed the timing summary
+pub const TIMING_TARGET: &str = "todo_cli::timing";
+
+/// sqlx reports every statement as a debug event on this target, with its duration
+const SQLX_QUERY_TARGET: &str = "sqlx::query";
+
+/// Time spent per phase of one command, collected from tracing data by [`TimingLayer`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TimingSummary {
+    /// Tasks the command fetched, reported with [`record_fetched`]
+    pub fetched: Option<usize>,
+    pub db: Duration,
+    pub queries: usize,
+    pub render: Duration,
+}
+
+impl TimingSummary {
+    /// e.g. "fetched 132 tasks in 48ms (db 31ms, render 9ms)"
+    pub fn footer(&self, total: Duration) -> Option<String> {
+        let count = self.fetched?;
+        Some(format!(
+            "fetched {} tasks in {} (db {}, render {})",
+            count,
+            format_millis(total),
+            format_millis(self.db),
+            format_millis(self.render)
+        ))
+    }
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{}ms", duration.as_millis())
+}
+
+/// Report how many tasks the running command fetched; the timing footer is
+/// only printed for commands that do
+pub fn record_fetched(count: usize) {
+    tracing::debug!(target: TIMING_TARGET, fetched = count as u64);
+}
+
+/// Tracing layer adding up database time from sqlx's query events and
+/// rendering time from `render` spans on [`TIMING_TARGET`]
+#[derive(Clone, Default)]
+pub struct TimingLayer {
+    summary: Arc<Mutex<TimingSummary>>,
+}
+
+impl TimingLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The layer, listening only to the targets it needs so that sqlx's debug
+    /// events are not enabled for other layers
+    pub fn filtered<S>(self) -> Filtered<Self, Targets, S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let targets = Targets::new()
+            .with_target(SQLX_QUERY_TARGET, Level::DEBUG)
+            .with_target(TIMING_TARGET, Level::DEBUG);
+        self.with_filter(targets)
+    }
+
+    pub fn summary(&self) -> TimingSummary {
+        self.summary.lock().map(|summary| summary.clone()).unwrap_or_default()
+    }
+}
+
+/// When a `render` span was created
+struct RenderStart(Instant);
+
+#[derive(Default)]
+struct TimingVisitor {
+    elapsed_secs: Option<f64>,
+    fetched: Option<u64>,
+}
+
+impl Visit for TimingVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "fetched" {
+            self.fetched = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() == "render" {
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().insert(RenderStart(Instant::now()));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let elapsed = span.extensions().get::<RenderStart>().map(|RenderStart(start)| start.elapsed());
+        if let (Some(elapsed), Ok(mut summary)) = (elapsed, self.summary.lock()) {
+            summary.render += elapsed;
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = TimingVisitor::default();
+        event.record(&mut visitor);
+
+        let Ok(mut summary) = self.summary.lock() else {
+            return;
+        };
+        match event.metadata().target() {
+            SQLX_QUERY_TARGET => {
+                if let Some(secs) = visitor.elapsed_secs {
+                    summary.db += Duration::from_secs_f64(secs);
+                    summary.queries += 1;
+                }
+            }
+            TIMING_TARGET => {
+                if let Some(fetched) = visitor.fetched {
+                    summary.fetched = Some(fetched as usize);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_layer_collects_query_and_render_time() {
+        let layer = TimingLayer::new();
+        let subscriber = tracing_subscriber::registry().with(layer.clone().filtered());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "sqlx::query", summary = "SELECT * FROM tasks", elapsed_secs = 0.031);
+            tracing::debug!(target: "sqlx::query", summary = "SELECT * FROM statuses", elapsed_secs = 0.002);
+            // Ignored: not a query event
+            tracing::debug!(elapsed_secs = 5.0, "unrelated");
+
+            let render = tracing::debug_span!(target: TIMING_TARGET, "render");
+            render.in_scope(|| std::thread::sleep(Duration::from_millis(2)));
+            drop(render);
+
+            record_fetched(132);
+        });
+
+        let summary = layer.summary();
+        assert_eq!(summary.queries, 2);
+        assert_eq!(summary.db, Duration::from_secs_f64(0.031) + Duration::from_secs_f64(0.002));
+        assert!(summary.render >= Duration::from_millis(2));
+        assert_eq!(summary.fetched, Some(132));
+    }
+
+    #[test]
+    fn test_footer() {
+        let summary = TimingSummary {
+            fetched: Some(132),
+            db: Duration::from_millis(31),
+            queries: 3,
+            render: Duration::from_micros(9400),
+        };
+        assert_eq!(
+            summary.footer(Duration::from_millis(48)).as_deref(),
+            Some("fetched 132 tasks in 48ms (db 31ms, render 9ms)")
+        );
+
+        // Commands that fetch nothing print no footer
+        assert_eq!(TimingSummary::default().footer(Duration::from_millis(5)), None);
+    }
+}
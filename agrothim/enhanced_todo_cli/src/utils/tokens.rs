@@ -0,0 +1,13 @@
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// A random single-use token for emailed links: 244 random bits from two v4
+/// UUIDs, hex encoded
+pub fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Hash stored in place of an emailed token
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
@@ -0,0 +1,78 @@
+use chrono::NaiveTime;
+use std::time::Duration;
+
+/// Parse a human-friendly duration such as `5s`, `90m`, `1h30m` or `500ms`.
+/// A bare number is read as seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return Err("Duration cannot be empty".to_string());
+    }
+
+    if let Ok(seconds) = input.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = input.as_str();
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("Invalid duration '{}'. Use e.g. 5s, 90m or 1h30m", input));
+        }
+        let value: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| format!("Duration '{}' is too large", input))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "ms" => Duration::from_millis(value),
+            "s" => Duration::from_secs(value),
+            "m" => Duration::from_secs(value * 60),
+            "h" => Duration::from_secs(value * 3600),
+            "d" => Duration::from_secs(value * 86400),
+            other => {
+                return Err(format!(
+                    "Unknown duration unit '{}' in '{}'. Use ms, s, m, h or d",
+                    other, input
+                ))
+            }
+        };
+        total += unit;
+        rest = &rest[unit_len..];
+    }
+
+    Ok(total)
+}
+
+/// Parse a 24-hour time of day such as `7:30` or `18:00`
+pub fn parse_time_of_day(input: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(input.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}'. Use HH:MM on a 24-hour clock, e.g. 07:30", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("10"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("m5").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("7:30"), Ok(NaiveTime::from_hms_opt(7, 30, 0).unwrap()));
+        assert_eq!(parse_time_of_day("18:00"), Ok(NaiveTime::from_hms_opt(18, 0, 0).unwrap()));
+        assert!(parse_time_of_day("25:00").is_err());
+        assert!(parse_time_of_day("7pm").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use uuid::Uuid;
+
+use chrono::{Duration, Utc};
+use enhanced_todo_cli::database::repositories::api_key_repository::{
+    ApiKeyRepository, PostgresApiKeyRepository, StoreApiKeyRequest,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+fn key_request(name: &str, hash: &str) -> StoreApiKeyRequest {
+    StoreApiKeyRequest {
+        name: name.to_string(),
+        prefix: format!("tdk_{}", &hash[..8]),
+        key_hash: hash.to_string(),
+        scopes: vec!["tasks:read".to_string()],
+        expires_at: None,
+    }
+}
+
+#[tokio::test]
+async fn test_use_key_marks_it_used() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresApiKeyRepository::new(pool);
+
+    let stored = repo
+        .store(&user_id, key_request("backup", "aaaaaaaa1111"))
+        .await
+        .expect("store failed");
+    assert!(stored.last_used_at.is_none());
+    assert_eq!(stored.scopes, vec!["tasks:read".to_string()]);
+
+    let used = repo.use_key("aaaaaaaa1111").await.unwrap().expect("key not found");
+    assert_eq!(used.id, stored.id);
+    assert!(used.last_used_at.is_some());
+    assert!(repo.use_key("bbbbbbbb2222").await.unwrap().is_none());
+
+    // Expired keys are not accepted
+    let mut expired = key_request("old", "cccccccc3333");
+    expired.expires_at = Some(Utc::now() - Duration::hours(1));
+    repo.store(&user_id, expired).await.unwrap();
+    assert!(repo.use_key("cccccccc3333").await.unwrap().is_none());
+
+    assert_eq!(repo.find_by_user_id(&user_id).await.unwrap().len(), 2);
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_revoke_by_prefix_or_id() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresApiKeyRepository::new(pool);
+
+    let first = repo.store(&user_id, key_request("first", "dddddddd4444")).await.unwrap();
+    let second = repo.store(&user_id, key_request("second", "eeeeeeee5555")).await.unwrap();
+
+    // Another user's keys cannot be revoked
+    assert!(repo.revoke(&Uuid::new_v4(), &first.prefix).await.unwrap().is_none());
+
+    let revoked = repo.revoke(&user_id, &first.prefix).await.unwrap().unwrap();
+    assert_eq!(revoked.id, first.id);
+    assert!(revoked.revoked_at.is_some());
+    assert!(repo.use_key("dddddddd4444").await.unwrap().is_none());
+    // Already revoked
+    assert!(repo.revoke(&user_id, &first.prefix).await.unwrap().is_none());
+
+    let revoked = repo.revoke(&user_id, &second.id.to_string()).await.unwrap().unwrap();
+    assert_eq!(revoked.id, second.id);
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use sqlx::{Executor, PgPool};
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::commit_link_repository::{
+    CommitLinkRepository, PostgresCommitLinkRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+async fn insert_task(pool: &PgPool, user_id: Uuid, title: &str) -> Uuid {
+    let task_id = Uuid::new_v4();
+    pool.execute(&*format!(
+        "INSERT INTO tasks (id, user_id, number, title) VALUES ('{0}', '{1}', (SELECT COALESCE(MAX(number), 0) + 1 FROM tasks WHERE user_id = '{1}'), '{2}')",
+        task_id, user_id, title
+    ))
+    .await
+    .unwrap();
+    task_id
+}
+
+#[tokio::test]
+async fn test_create_and_find_links() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresCommitLinkRepository::new(pool.clone());
+    let task_id = insert_task(&pool, user_id, "Fix login").await;
+    let first = "a".repeat(40);
+    let second = "b".repeat(40);
+
+    assert!(repo.find_by_task(&task_id).await.unwrap().is_empty());
+
+    let link = repo.create(&user_id, &task_id, &first).await.expect("create failed");
+    assert_eq!(link.task_id, task_id);
+    assert_eq!(link.short_hash(), "aaaaaaa");
+    repo.create(&user_id, &task_id, &second).await.unwrap();
+
+    // A commit is recorded once per task
+    assert!(repo.create(&user_id, &task_id, &first).await.is_err());
+
+    let links = repo.find_by_task(&task_id).await.unwrap();
+    assert_eq!(links.len(), 2);
+    let found = repo.find_by_commits(&user_id, &[first.clone(), "c".repeat(40)]).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].commit_hash, first);
+    assert!(repo.find_by_commits(&Uuid::new_v4(), &[first]).await.unwrap().is_empty());
+
+    drop_test_schema(&schema).await;
+}
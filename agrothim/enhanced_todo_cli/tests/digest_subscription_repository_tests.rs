@@ -0,0 +1,39 @@
+mod common;
+
+use chrono::{NaiveDate, NaiveTime};
+
+use enhanced_todo_cli::database::repositories::digest_subscription_repository::{
+    DigestSubscriptionRepository, PostgresDigestSubscriptionRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_subscribe_claim_and_release() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresDigestSubscriptionRepository::new(pool);
+    let at = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+    let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+
+    assert!(repo.find_by_user_id(&user_id).await.unwrap().is_none());
+    repo.upsert(&user_id, at(9)).await.expect("upsert failed");
+    let subscription = repo.upsert(&user_id, at(7)).await.expect("upsert failed");
+    assert_eq!(subscription.send_at, at(7));
+    assert!(subscription.last_sent_on.is_none());
+
+    assert!(repo.find_due(today, at(6)).await.unwrap().is_empty());
+    assert_eq!(repo.find_due(today, at(8)).await.unwrap().len(), 1);
+
+    // A day is claimed once
+    assert!(repo.claim(&user_id, today).await.unwrap());
+    assert!(!repo.claim(&user_id, today).await.unwrap());
+    assert!(repo.find_due(today, at(8)).await.unwrap().is_empty());
+
+    repo.release(&user_id, None).await.unwrap();
+    assert_eq!(repo.find_due(today, at(8)).await.unwrap().len(), 1);
+
+    assert!(repo.delete(&user_id).await.unwrap());
+    assert!(!repo.delete(&user_id).await.unwrap());
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use sqlx::{Executor, PgPool};
+use uuid::Uuid;
+
+use chrono::{Duration, Utc};
+use enhanced_todo_cli::database::repositories::invitation_repository::{
+    InvitationRepository, PostgresInvitationRepository, StoreInvitationRequest,
+};
+use enhanced_todo_cli::database::repositories::workspace_repository::{
+    PostgresWorkspaceRepository, WorkspaceRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+async fn insert_user(pool: &PgPool, username: &str) -> Uuid {
+    let user_id = Uuid::new_v4();
+    pool.execute(&*format!(
+        r#"
+        INSERT INTO users (id, username, email, password_hash)
+        VALUES ('{}', '{}', '{}@example.com', '$2b$12$abcdefghijklmnopqrstuv')
+    "#,
+        user_id, username, username
+    ))
+    .await
+    .unwrap();
+    user_id
+}
+
+fn invitation_request(workspace_id: Uuid, created_by: Uuid, code: &str, max_uses: Option<i32>) -> StoreInvitationRequest {
+    StoreInvitationRequest {
+        workspace_id,
+        created_by,
+        prefix: code[..10].to_string(),
+        code_hash: format!("hash-{}", code),
+        expires_at: Utc::now() + Duration::days(7),
+        max_uses,
+    }
+}
+
+#[tokio::test]
+async fn test_redeem_adds_member_until_used_up() {
+    let (pool, schema, owner_id) = setup_test_db().await;
+    let first = insert_user(&pool, "first").await;
+    let second = insert_user(&pool, "second").await;
+    let workspaces = PostgresWorkspaceRepository::new(pool.clone());
+    let repo = PostgresInvitationRepository::new(pool);
+
+    let workspace = workspaces.store(&owner_id, "Team").await.unwrap();
+    let invitation = repo
+        .store(invitation_request(workspace.id, owner_id, "tdi_0123456789", Some(1)))
+        .await
+        .expect("store failed");
+    assert_eq!(invitation.use_count, 0);
+    assert_eq!(invitation.prefix, "tdi_012345");
+
+    let redeemed = repo.redeem(&invitation.code_hash, &first).await.unwrap().unwrap();
+    assert_eq!(redeemed.use_count, 1);
+    assert!(workspaces.find_membership(&workspace.id, &first).await.unwrap().is_some());
+
+    // The single use is gone
+    assert!(repo.redeem(&invitation.code_hash, &second).await.unwrap().is_none());
+    assert!(workspaces.find_membership(&workspace.id, &second).await.unwrap().is_none());
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_revoked_and_expired_invitations_cannot_be_redeemed() {
+    let (pool, schema, owner_id) = setup_test_db().await;
+    let member_id = insert_user(&pool, "teammate").await;
+    let workspaces = PostgresWorkspaceRepository::new(pool.clone());
+    let repo = PostgresInvitationRepository::new(pool);
+
+    let workspace = workspaces.store(&owner_id, "Team").await.unwrap();
+    let revoked = repo
+        .store(invitation_request(workspace.id, owner_id, "tdi_aaaaaaaaaa", None))
+        .await
+        .unwrap();
+    let mut expired_request = invitation_request(workspace.id, owner_id, "tdi_bbbbbbbbbb", None);
+    expired_request.expires_at = Utc::now() - Duration::hours(1);
+    let expired = repo.store(expired_request).await.unwrap();
+
+    let by_prefix = repo.revoke(&workspace.id, &revoked.prefix).await.unwrap().unwrap();
+    assert_eq!(by_prefix.id, revoked.id);
+    assert!(by_prefix.revoked_at.is_some());
+    // Revoking twice finds nothing left to revoke
+    assert!(repo.revoke(&workspace.id, &revoked.id.to_string()).await.unwrap().is_none());
+
+    assert!(repo.redeem(&revoked.code_hash, &member_id).await.unwrap().is_none());
+    assert!(repo.redeem(&expired.code_hash, &member_id).await.unwrap().is_none());
+    assert!(workspaces.find_membership(&workspace.id, &member_id).await.unwrap().is_none());
+
+    let listed = repo.find_by_workspace(&workspace.id).await.unwrap();
+    assert_eq!(listed.len(), 2);
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use uuid::Uuid;
+use chrono::Utc;
+
+use enhanced_todo_cli::database::repositories::issue_link_repository::{
+    IssueLinkRepository, PostgresIssueLinkRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_create_find_and_close_links() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresIssueLinkRepository::new(pool);
+    let task_id = Uuid::new_v4();
+
+    assert!(repo.find_by_repository(&user_id, "acme/app").await.unwrap().is_empty());
+
+    let link = repo.create(&user_id, &task_id, "acme/app", 7).await.expect("create failed");
+    assert_eq!(link.task_id, task_id);
+    assert_eq!(link.issue_number, 7);
+    assert!(link.closed_at.is_none());
+    repo.create(&user_id, &Uuid::new_v4(), "acme/other", 7).await.unwrap();
+
+    // One task per issue
+    assert!(repo.create(&user_id, &Uuid::new_v4(), "acme/app", 7).await.is_err());
+
+    repo.mark_closed(&task_id, Utc::now()).await.unwrap();
+    let links = repo.find_by_repository(&user_id, "acme/app").await.unwrap();
+    assert_eq!(links.len(), 1);
+    assert!(links[0].closed_at.is_some());
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use chrono::{Duration, Utc};
+
+use enhanced_todo_cli::database::repositories::password_reset_repository::{
+    PasswordResetRepository, PostgresPasswordResetRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_consume_token_once() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresPasswordResetRepository::new(pool);
+
+    let token = repo
+        .store(&user_id, "hash-1", Utc::now() + Duration::minutes(30))
+        .await
+        .expect("store failed");
+    assert_eq!(token.user_id, user_id);
+    assert!(token.used_at.is_none());
+
+    assert_eq!(repo.consume("unknown").await.unwrap(), None);
+    assert_eq!(repo.consume("hash-1").await.unwrap(), Some(user_id));
+    // Already used
+    assert_eq!(repo.consume("hash-1").await.unwrap(), None);
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_expired_and_replaced_tokens_are_rejected() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresPasswordResetRepository::new(pool);
+
+    repo.store(&user_id, "expired", Utc::now() - Duration::minutes(1))
+        .await
+        .unwrap();
+    assert_eq!(repo.consume("expired").await.unwrap(), None);
+
+    // Requesting a new token invalidates the previous one
+    repo.store(&user_id, "first", Utc::now() + Duration::minutes(30))
+        .await
+        .unwrap();
+    repo.store(&user_id, "second", Utc::now() + Duration::minutes(30))
+        .await
+        .unwrap();
+    assert_eq!(repo.consume("first").await.unwrap(), None);
+    assert_eq!(repo.consume("second").await.unwrap(), Some(user_id));
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use uuid::Uuid;
+
+use chrono::{Duration, SubsecRound, Utc};
+use enhanced_todo_cli::database::repositories::revoked_token_repository::{
+    PostgresRevokedTokenRepository, RevokedTokenRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_revoke_token() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresRevokedTokenRepository::new(pool);
+
+    let jti = Uuid::new_v4().to_string();
+    assert!(!repo.is_revoked(&jti).await.unwrap());
+
+    repo.revoke(&jti, &user_id, Utc::now() + Duration::hours(24))
+        .await
+        .expect("revoke failed");
+    assert!(repo.is_revoked(&jti).await.unwrap());
+
+    // Revoking twice is fine
+    repo.revoke(&jti, &user_id, Utc::now() + Duration::hours(24)).await.unwrap();
+    assert!(repo.is_revoked(&jti).await.unwrap());
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_expired_revocations_are_purged() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresRevokedTokenRepository::new(pool.clone());
+
+    let expired = Uuid::new_v4().to_string();
+    repo.revoke(&expired, &user_id, Utc::now() - Duration::hours(1)).await.unwrap();
+    repo.revoke(&Uuid::new_v4().to_string(), &user_id, Utc::now() + Duration::hours(1))
+        .await
+        .unwrap();
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM revoked_tokens")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert!(!repo.is_revoked(&expired).await.unwrap());
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_revoke_all_only_moves_forward() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresRevokedTokenRepository::new(pool);
+
+    assert_eq!(repo.revoked_before(&user_id).await.unwrap(), None);
+
+    let now = Utc::now().trunc_subsecs(0);
+    repo.revoke_all(&user_id, now).await.expect("revoke_all failed");
+    assert_eq!(repo.revoked_before(&user_id).await.unwrap(), Some(now));
+
+    repo.revoke_all(&user_id, now - Duration::hours(1)).await.unwrap();
+    assert_eq!(repo.revoked_before(&user_id).await.unwrap(), Some(now));
+    assert_eq!(repo.revoked_before(&Uuid::new_v4()).await.unwrap(), None);
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use uuid::Uuid;
+use chrono::{Duration, TimeZone, Utc};
+
+use enhanced_todo_cli::models::schedule::StoreScheduleRequest;
+use enhanced_todo_cli::database::repositories::schedule_repository::{
+    PostgresScheduleRepository, ScheduleRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_schedule_claim_and_release() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresScheduleRepository::new(pool);
+    let monday = Utc.with_ymd_and_hms(2026, 10, 19, 9, 0, 0).unwrap();
+    let request = StoreScheduleRequest {
+        title: "Weekly review".to_string(),
+        cron: "0 9 * * mon".to_string(),
+        ..Default::default()
+    };
+
+    let schedule = repo.create(&user_id, &request, monday).await.expect("create failed");
+    assert_eq!(schedule.title, "Weekly review");
+    assert!(schedule.last_run_at.is_none());
+    assert_eq!(repo.find_by_user_id(&user_id).await.unwrap().len(), 1);
+
+    assert!(repo.find_due(monday - Duration::minutes(1)).await.unwrap().is_empty());
+    assert_eq!(repo.find_due(monday).await.unwrap().len(), 1);
+
+    // A firing is claimed once
+    let next = monday + Duration::weeks(1);
+    assert!(repo.claim(&schedule.id, monday, next, monday).await.unwrap());
+    assert!(!repo.claim(&schedule.id, monday, next, monday).await.unwrap());
+    assert!(repo.find_due(monday).await.unwrap().is_empty());
+
+    repo.release(&schedule).await.unwrap();
+    assert_eq!(repo.find_due(monday).await.unwrap()[0].next_run_at, monday);
+
+    assert!(!repo.delete(&Uuid::new_v4(), &schedule.id).await.unwrap());
+    assert!(repo.delete(&user_id, &schedule.id).await.unwrap());
+    assert!(repo.find_by_user_id(&user_id).await.unwrap().is_empty());
+
+    drop_test_schema(&schema).await;
+}
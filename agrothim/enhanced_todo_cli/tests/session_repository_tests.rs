@@ -0,0 +1,60 @@
+mod common;
+
+use uuid::Uuid;
+
+use chrono::{Duration, Utc};
+use enhanced_todo_cli::database::repositories::session_repository::{
+    PostgresSessionRepository, SessionRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_use_and_list_active_sessions() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresSessionRepository::new(pool);
+
+    let laptop = repo
+        .create(&user_id, "laptop", Utc::now() + Duration::days(30))
+        .await
+        .expect("create failed");
+    let desktop = repo.create(&user_id, "desktop", Utc::now() + Duration::days(30)).await.unwrap();
+    let expired = repo.create(&user_id, "old phone", Utc::now() - Duration::hours(1)).await.unwrap();
+
+    let used = repo.use_session(&laptop.id).await.unwrap().expect("session not found");
+    assert!(used.last_used_at >= laptop.last_used_at);
+    assert!(repo.use_session(&expired.id).await.unwrap().is_none());
+
+    // Most recently used first, expired sessions left out
+    let active = repo.find_active_by_user_id(&user_id).await.unwrap();
+    let names: Vec<&str> = active.iter().map(|session| session.name.as_str()).collect();
+    assert_eq!(names, vec!["laptop", "desktop"]);
+
+    repo.extend(&expired.id, Utc::now() + Duration::days(1)).await.unwrap();
+    assert!(repo.use_session(&expired.id).await.unwrap().is_some());
+    assert!(repo.use_session(&desktop.id).await.unwrap().is_some());
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_revoke_session() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresSessionRepository::new(pool);
+
+    let session = repo.create(&user_id, "laptop", Utc::now() + Duration::days(30)).await.unwrap();
+
+    // Another user's sessions cannot be revoked
+    assert!(repo.revoke(&Uuid::new_v4(), &session.id).await.unwrap().is_none());
+
+    let revoked = repo.revoke(&user_id, &session.id).await.unwrap().unwrap();
+    assert!(revoked.revoked_at.is_some());
+    assert!(repo.use_session(&session.id).await.unwrap().is_none());
+    assert!(repo.find_active_by_user_id(&user_id).await.unwrap().is_empty());
+    // The full history still has it
+    assert_eq!(repo.find_by_user_id(&user_id).await.unwrap().len(), 1);
+    // Already revoked
+    assert!(repo.revoke(&user_id, &session.id).await.unwrap().is_none());
+
+    drop_test_schema(&schema).await;
+}
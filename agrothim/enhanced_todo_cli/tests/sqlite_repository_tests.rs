@@ -0,0 +1,340 @@
+#![cfg(feature = "sqlite")]
+
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use tempfile::TempDir;
+use tokio_stream::StreamExt;
+
+use enhanced_todo_cli::database::repositories::{
+    SqliteSyncStateRepository, SqliteTaskRepository, SqliteUserRepository, SyncStateRepository, TaskRepository,
+    TaskRepositoryError, UserRepository,
+};
+use enhanced_todo_cli::database::{Database, DatabasePool};
+use enhanced_todo_cli::models::task::{
+    PageRequest, StatisticsFilter, StoreTaskRequest, Task, TaskError, TaskGroup, TaskGrouping, TaskStatus,
+    UpdateTaskRequest,
+};
+use enhanced_todo_cli::models::calendar::UserTimezone;
+use enhanced_todo_cli::models::report::TaskTimestamp;
+use enhanced_todo_cli::models::sync::{SyncConflict, SyncState};
+use enhanced_todo_cli::models::user::{StoreUserRequest, UpdateUserRequest, User};
+
+/// A fresh, migrated database file; the directory is removed when the guard drops
+async fn setup_test_db() -> (SqlitePool, TempDir) {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let url = format!("sqlite://{}", dir.path().join("todo.db").display());
+    let database = Database::from_url(&url).await.expect("Failed to open SQLite database");
+    match database.pool() {
+        DatabasePool::Sqlite(pool) => (pool.clone(), dir),
+        _ => panic!("sqlite:// URL should open a SQLite database"),
+    }
+}
+
+async fn create_user(pool: &SqlitePool, username: &str) -> User {
+    SqliteUserRepository::new(pool.clone())
+        .store(StoreUserRequest {
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            password: String::new(),
+            password_hash: "hash".to_string(),
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_user_store_update_and_rename_history() {
+    let (pool, _dir) = setup_test_db().await;
+    let repo = SqliteUserRepository::new(pool.clone());
+
+    let user = create_user(&pool, "local_user").await;
+    assert!(user.is_active);
+    assert!(user.email_verified_at.is_none());
+    assert_eq!(repo.find_by_email("local_user@example.com").await.unwrap().unwrap().id, user.id);
+    assert!(repo.exists_by_username("local_user").await.unwrap());
+
+    let renamed = repo
+        .update(&user.id, UpdateUserRequest { username: Some("renamed".to_string()), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(renamed.username, "renamed");
+    assert_eq!(renamed.email, user.email);
+
+    let changes = repo.find_username_changes(&user.id).await.unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].old_username, "local_user");
+
+    let verified = repo.mark_email_verified(&user.id).await.unwrap();
+    assert!(verified.email_verified_at.is_some());
+    assert!(!repo.set_active(&user.id, false).await.unwrap().is_active);
+    assert!(repo.delete(&user.id).await.unwrap());
+    assert!(repo.find_by_id(&user.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_task_lifecycle() {
+    let (pool, _dir) = setup_test_db().await;
+    let repo = SqliteTaskRepository::new(pool.clone());
+    let user = create_user(&pool, "task_owner").await;
+
+    let tasks = repo
+        .store_many(
+            ["Buy milk", "Write report", "Call plumber"]
+                .into_iter()
+                .map(|title| StoreTaskRequest { title: title.to_string(), ..Default::default() })
+                .collect(),
+            &user.id,
+        )
+        .await
+        .unwrap();
+    let numbers: Vec<i32> = tasks.iter().map(|task| task.number).collect();
+    assert_eq!(numbers, [1, 2, 3]);
+
+    let found = repo.find_by_id(&tasks[0].id).await.unwrap().unwrap();
+    assert_eq!(found.title, "Buy milk");
+    assert_eq!(found.created_at, tasks[0].created_at);
+
+    let prefix = &tasks[1].id.to_string()[..10];
+    let matches = repo.find_by_id_prefix(&user.id, prefix, 2).await.unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].id, tasks[1].id);
+
+    let search = repo.search_tasks(&user.id, "MILK").await.unwrap();
+    assert_eq!(search.len(), 1);
+
+    let updated = repo
+        .update(
+            &tasks[2].id,
+            &user.id,
+            UpdateTaskRequest {
+                due_date: Some(Utc::now() - Duration::days(1)),
+                context: Some("Home".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(updated.title, "Call plumber");
+    assert_eq!(updated.context.as_deref(), Some("@home"));
+    assert_eq!(repo.find_overdue_by_user(&user.id, Utc::now()).await.unwrap().len(), 1);
+
+    let expected: Vec<_> = tasks[..2].iter().map(|task| (task.id, TaskStatus::Pending)).collect();
+    let completed = repo.update_status_many(&user.id, &expected, TaskStatus::Completed).await.unwrap();
+    assert_eq!(completed.len(), 2);
+    assert!(completed.iter().all(|task| task.completed_at.is_some()));
+    assert!(repo
+        .update_status_if(&tasks[0].id, &user.id, TaskStatus::Pending, TaskStatus::InProgress)
+        .await
+        .unwrap()
+        .is_none());
+
+    let stats = repo
+        .get_user_statistics(&user.id, Utc::now(), Utc::now() - Duration::days(7), &StatisticsFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(stats.total_tasks, 3);
+    assert_eq!(stats.completed_tasks, 2);
+    assert_eq!(stats.overdue_tasks, 1);
+    let completed_since = StatisticsFilter { completed_after: Some(Utc::now() - Duration::hours(1)), ..Default::default() };
+    let stats = repo
+        .get_user_statistics(&user.id, Utc::now(), Utc::now(), &completed_since)
+        .await
+        .unwrap();
+    assert_eq!((stats.total_tasks, stats.completed_tasks), (2, 2));
+    let by_status = repo
+        .count_grouped(&user.id, TaskGrouping::Status, &StatisticsFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(by_status[&TaskGroup::Status(TaskStatus::Completed)], 2);
+    assert_eq!(by_status.values().sum::<i64>(), 3);
+
+    repo.renumber(&user.id, &[tasks[2].id, tasks[0].id]).await.unwrap();
+    let pinned = repo.set_pinned(&tasks[1].id, &user.id, true).await.unwrap();
+    assert!(pinned.pinned);
+    let order: Vec<_> = repo.find_by_user_id(&user.id).await.unwrap().iter().map(|task| task.id).collect();
+    assert_eq!(order, [tasks[1].id, tasks[2].id, tasks[0].id]);
+    let streamed: Vec<_> = repo.stream_by_user_id(&user.id).map(|task| task.unwrap().id).collect().await;
+    assert_eq!(streamed, order);
+
+    let page = PageRequest { sort: "-title".parse().unwrap(), limit: Some(2), ..Default::default() };
+    let first = repo.find_by_user_id_paginated(&user.id, page).await.unwrap();
+    assert_eq!(first.items.iter().map(|task| task.id).collect::<Vec<_>>(), [tasks[1].id, tasks[2].id]);
+    assert_eq!(first.total, 3);
+    let rest = repo
+        .find_by_user_id_paginated(&user.id, PageRequest { after: first.next_cursor, ..page })
+        .await
+        .unwrap();
+    assert_eq!(rest.items.iter().map(|task| task.id).collect::<Vec<_>>(), [tasks[0].id]);
+    assert_eq!(rest.next_cursor, None);
+
+    assert!(repo.delete(&tasks[0].id, &user.id).await.unwrap());
+    assert_eq!(repo.count_by_user(&user.id).await.unwrap(), 2);
+    assert_eq!(repo.delete_bulk(&[tasks[0].id, tasks[1].id], &user.id).await.unwrap(), 1);
+    assert_eq!(repo.count_by_user(&user.id).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_throughput() {
+    let (pool, _dir) = setup_test_db().await;
+    let repo = SqliteTaskRepository::new(pool.clone());
+    let user = create_user(&pool, "busy").await;
+    let now = Utc::now();
+
+    for (created_days_ago, completed_days_ago) in [(2, Some(1)), (3, None), (20, Some(4)), (30, Some(10))] {
+        let request = StoreTaskRequest { title: "Task".to_string(), ..Default::default() };
+        let mut task = Task::new(request, user.id).unwrap();
+        task.created_at = now - Duration::days(created_days_ago);
+        if let Some(days) = completed_days_ago {
+            task.status = TaskStatus::Completed;
+            task.completed_at = Some(now - Duration::days(days));
+        }
+        repo.put_copy(&task).await.unwrap();
+    }
+
+    let throughput = repo.get_throughput(&user.id, now - Duration::days(7), now).await.unwrap();
+    assert_eq!((throughput.created, throughput.created_completed, throughput.completed), (2, 1, 2));
+    let average_days = throughput.average_completion_seconds.unwrap() / 86_400.0;
+    assert!((average_days - 8.5).abs() < 0.01, "{}", average_days);
+
+    let idle = repo.get_throughput(&user.id, now - Duration::days(60), now - Duration::days(40)).await.unwrap();
+    assert_eq!((idle.created, idle.completed, idle.average_completion_seconds), (0, 0, None));
+}
+
+#[tokio::test]
+async fn test_count_by_day() {
+    let (pool, _dir) = setup_test_db().await;
+    let repo = SqliteTaskRepository::new(pool.clone());
+    let user = create_user(&pool, "daily").await;
+    let user_id = user.id;
+    let day = |date: &str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+
+    for (created, completed) in [
+        ("2026-10-10T01:00:00Z", None),
+        ("2026-10-10T23:30:00Z", Some("2026-10-12T08:00:00Z")),
+        ("2026-10-12T12:00:00Z", Some("2026-10-12T13:00:00Z")),
+    ] {
+        let request = StoreTaskRequest { title: "Task".to_string(), ..Default::default() };
+        let mut task = Task::new(request, user_id).unwrap();
+        task.created_at = created.parse().unwrap();
+        if let Some(completed) = completed {
+            task.status = TaskStatus::Completed;
+            task.completed_at = Some(completed.parse().unwrap());
+        }
+        repo.put_copy(&task).await.unwrap();
+    }
+    let since = "2026-10-10T00:00:00Z".parse().unwrap();
+    let until = "2026-10-13T00:00:00Z".parse().unwrap();
+    let utc: UserTimezone = "UTC".parse().unwrap();
+
+    let created = repo.count_by_day(&user_id, TaskTimestamp::Created, since, until, utc).await.unwrap();
+    let counts: Vec<_> = created.iter().map(|bucket| (bucket.date, bucket.count)).collect();
+    assert_eq!(counts, [(day("2026-10-10"), 2), (day("2026-10-12"), 1)]);
+
+    // An hour east of UTC, 23:30 falls on the next day
+    let created = repo.count_by_day(&user_id, TaskTimestamp::Created, since, until, "Etc/GMT-1".parse().unwrap()).await.unwrap();
+    let counts: Vec<_> = created.iter().map(|bucket| (bucket.date, bucket.count)).collect();
+    assert_eq!(counts, [(day("2026-10-10"), 1), (day("2026-10-11"), 1), (day("2026-10-12"), 1)]);
+
+    let completed = repo.count_by_day(&user_id, TaskTimestamp::Completed, since, until, "Etc/GMT+1".parse().unwrap()).await.unwrap();
+    let counts: Vec<_> = completed.iter().map(|bucket| (bucket.date, bucket.count)).collect();
+    assert_eq!(counts, [(day("2026-10-12"), 2)]);
+}
+
+#[tokio::test]
+async fn test_task_numbers_are_never_reused() {
+    let (pool, _dir) = setup_test_db().await;
+    let repo = SqliteTaskRepository::new(pool.clone());
+    let user = create_user(&pool, "numbered").await;
+
+    let first = repo.store(StoreTaskRequest { title: "First".to_string(), ..Default::default() }, &user.id).await.unwrap();
+    let second = repo.store(StoreTaskRequest { title: "Second".to_string(), ..Default::default() }, &user.id).await.unwrap();
+    assert_eq!((first.number, second.number), (1, 2));
+
+    assert!(repo.delete(&second.id, &user.id).await.unwrap());
+    let third = repo.store(StoreTaskRequest { title: "Third".to_string(), ..Default::default() }, &user.id).await.unwrap();
+    assert_eq!(third.number, 3);
+}
+
+#[tokio::test]
+async fn test_tasks_cannot_start_after_their_due_date() {
+    let (pool, _dir) = setup_test_db().await;
+    let repo = SqliteTaskRepository::new(pool.clone());
+    let user = create_user(&pool, "dated").await;
+
+    let backwards = StoreTaskRequest {
+        title: "Backwards".to_string(),
+        start_date: Some(Utc::now() + Duration::days(3)),
+        due_date: Some(Utc::now() + Duration::days(1)),
+        ..Default::default()
+    };
+    let result = repo.store(backwards, &user.id).await;
+    assert!(matches!(result, Err(TaskRepositoryError::ValidationError(TaskError::StartAfterDue))));
+
+    let request = StoreTaskRequest {
+        title: "Dated".to_string(),
+        start_date: Some(Utc::now() + Duration::days(1)),
+        due_date: Some(Utc::now() + Duration::days(3)),
+        ..Default::default()
+    };
+    let task = repo.store(request, &user.id).await.unwrap();
+    let earlier_due = UpdateTaskRequest { due_date: Some(Utc::now()), ..Default::default() };
+    let result = repo.update(&task.id, &user.id, earlier_due).await;
+    assert!(matches!(result, Err(TaskRepositoryError::ValidationError(TaskError::StartAfterDue))));
+
+    let later_due = UpdateTaskRequest { due_date: Some(Utc::now() + Duration::days(2)), ..Default::default() };
+    repo.update(&task.id, &user.id, later_due).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_copies_from_another_database() {
+    let (source, _source_dir) = setup_test_db().await;
+    let (pool, _dir) = setup_test_db().await;
+    let users = SqliteUserRepository::new(pool.clone());
+    let tasks = SqliteTaskRepository::new(pool.clone());
+    let state = SqliteSyncStateRepository::new(pool.clone());
+
+    let user = create_user(&source, "synced").await;
+    let copied_user = users.put_copy(&user).await.unwrap();
+    assert_eq!((copied_user.id, copied_user.username.as_str()), (user.id, "synced"));
+    assert_eq!(users.put_copy(&user).await.unwrap().id, user.id);
+
+    let source_tasks = SqliteTaskRepository::new(source.clone());
+    let skipped = source_tasks.store(StoreTaskRequest { title: "Numbered first".to_string(), ..Default::default() }, &user.id).await.unwrap();
+    let task = source_tasks.store(StoreTaskRequest { title: "Copied".to_string(), ..Default::default() }, &user.id).await.unwrap();
+    assert_eq!((skipped.number, task.number), (1, 2));
+
+    // Numbers and versions are the copy's own; the content is the original's
+    let copy = tasks.put_copy(&task).await.unwrap();
+    assert_eq!((copy.id, copy.number, copy.version), (task.id, 1, 1));
+    assert_eq!((copy.title.as_str(), copy.created_at), ("Copied", task.created_at));
+    let edited = source_tasks
+        .update(&task.id, &user.id, UpdateTaskRequest { title: Some("Edited".to_string()), ..Default::default() })
+        .await
+        .unwrap();
+    let copy = tasks.put_copy(&edited).await.unwrap();
+    assert_eq!((copy.number, copy.version, copy.title.as_str()), (1, 2, "Edited"));
+
+    let saved = SyncState {
+        task_id: task.id,
+        user_id: user.id,
+        local_version: copy.version,
+        remote_version: edited.version,
+        synced_at: Utc::now(),
+    };
+    state.save(&saved).await.unwrap();
+    state.save(&SyncState { local_version: 3, ..saved }).await.unwrap();
+    let found = state.find_by_user(&user.id).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].local_version, found[0].remote_version), (3, edited.version));
+    state.delete(&task.id).await.unwrap();
+    assert!(state.find_by_user(&user.id).await.unwrap().is_empty());
+
+    // A conflict recorded again keeps the time it was first seen
+    let conflict = SyncConflict { task_id: task.id, user_id: user.id, detected_at: Utc::now() - Duration::hours(1) };
+    state.save_conflict(&conflict).await.unwrap();
+    state.save_conflict(&SyncConflict { detected_at: Utc::now(), ..conflict }).await.unwrap();
+    assert_eq!(state.find_conflicts(&user.id).await.unwrap(), vec![conflict]);
+    assert_eq!(state.find_conflict(&task.id).await.unwrap(), Some(conflict));
+    state.delete_conflict(&task.id).await.unwrap();
+    assert!(state.find_conflict(&task.id).await.unwrap().is_none());
+}
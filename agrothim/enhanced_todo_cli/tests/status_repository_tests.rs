@@ -0,0 +1,79 @@
+mod common;
+
+use enhanced_todo_cli::database::repositories::status_repository::{
+    PostgresStatusRepository, StatusRepository, StatusRepositoryError,
+};
+use enhanced_todo_cli::models::status::{StatusTransition, StoreStatusRequest};
+use enhanced_todo_cli::models::task::TaskStatus;
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_store_allocates_sequential_codes() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresStatusRepository::new(pool);
+
+    let blocked = repo
+        .store(&user_id, StoreStatusRequest { name: "Blocked".to_string() })
+        .await
+        .expect("store failed");
+    let review = repo
+        .store(&user_id, StoreStatusRequest { name: "In Review".to_string() })
+        .await
+        .expect("store failed");
+
+    assert_eq!(blocked.code, 10);
+    assert_eq!(review.code, 11);
+
+    let statuses = repo.find_by_user_id(&user_id).await.unwrap();
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0].name, "Blocked");
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_store_duplicate_name() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresStatusRepository::new(pool);
+
+    repo.store(&user_id, StoreStatusRequest { name: "Blocked".to_string() })
+        .await
+        .unwrap();
+    let result = repo
+        .store(&user_id, StoreStatusRequest { name: "blocked".to_string() })
+        .await;
+
+    assert!(matches!(result, Err(StatusRepositoryError::NameExists { .. })));
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_transitions_and_delete() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresStatusRepository::new(pool);
+
+    let blocked = repo
+        .store(&user_id, StoreStatusRequest { name: "Blocked".to_string() })
+        .await
+        .unwrap();
+
+    let transition = StatusTransition {
+        from_status: blocked.status(),
+        to_status: TaskStatus::InProgress,
+    };
+    repo.add_transition(&user_id, transition).await.unwrap();
+    // Adding the same rule twice is a no-op
+    repo.add_transition(&user_id, transition).await.unwrap();
+
+    let transitions = repo.find_transitions(&user_id).await.unwrap();
+    assert_eq!(transitions, vec![transition]);
+
+    // Deleting the status also removes its transitions
+    assert!(repo.delete(&user_id, blocked.code).await.unwrap());
+    assert!(repo.find_transitions(&user_id).await.unwrap().is_empty());
+    assert_eq!(repo.count_tasks_with_status(&user_id, blocked.code).await.unwrap(), 0);
+
+    drop_test_schema(&schema).await;
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use sqlx::{Executor, PgPool};
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::task_repository::{PostgresTaskRepository, TaskRepository};
+use enhanced_todo_cli::database::repositories::task_event_repository::{
+    PostgresTaskEventRepository, TaskEventRepository,
+};
+use enhanced_todo_cli::database::repositories::task_share_repository::{
+    PostgresTaskShareRepository, TaskShareRepository,
+};
+use enhanced_todo_cli::database::repositories::workspace_repository::{
+    PostgresWorkspaceRepository, WorkspaceRepository,
+};
+use enhanced_todo_cli::models::task::StoreTaskRequest;
+use enhanced_todo_cli::models::task_event::TaskEventKind;
+
+use common::{drop_test_schema, setup_test_db};
+
+async fn insert_user(pool: &PgPool, username: &str) -> Uuid {
+    let user_id = Uuid::new_v4();
+    pool.execute(&*format!(
+        r#"
+        INSERT INTO users (id, username, email, password_hash)
+        VALUES ('{}', '{}', '{}@example.com', '$2b$12$abcdefghijklmnopqrstuv')
+    "#,
+        user_id, username, username
+    ))
+    .await
+    .unwrap();
+    user_id
+}
+
+#[tokio::test]
+async fn test_events_are_visible_to_owner_members_and_share_recipients() {
+    let (pool, schema, owner_id) = setup_test_db().await;
+    let teammate_id = insert_user(&pool, "teammate").await;
+    let friend_id = insert_user(&pool, "friend").await;
+    let stranger_id = insert_user(&pool, "stranger").await;
+
+    let tasks = PostgresTaskRepository::new(pool.clone());
+    let workspaces = PostgresWorkspaceRepository::new(pool.clone());
+    let shares = PostgresTaskShareRepository::new(pool.clone());
+    let repo = PostgresTaskEventRepository::new(pool);
+
+    let workspace = workspaces.store(&owner_id, "Team").await.unwrap();
+    workspaces.add_member(&workspace.id, &teammate_id).await.unwrap();
+    let team_task = tasks.store(StoreTaskRequest {
+        title: "Team task".to_string(),
+        workspace_id: Some(workspace.id),
+        ..Default::default()
+    }, &owner_id).await.unwrap();
+    let private_task = tasks.store(StoreTaskRequest {
+        title: "Private task".to_string(),
+        ..Default::default()
+    }, &owner_id).await.unwrap();
+
+    repo.store(&team_task.id, &owner_id, TaskEventKind::Created, None).await.expect("store failed");
+    repo.store(&private_task.id, &owner_id, TaskEventKind::Created, None).await.unwrap();
+    shares.store(&private_task.id, &friend_id, false).await.unwrap();
+    repo.store(&private_task.id, &owner_id, TaskEventKind::Shared, Some(&friend_id)).await.unwrap();
+    repo.store(&team_task.id, &teammate_id, TaskEventKind::Completed, None).await.unwrap();
+
+    let events = repo.find_visible_to(&owner_id, None, 50).await.unwrap();
+    assert_eq!(events.len(), 4);
+    // Newest first, with usernames resolved
+    assert_eq!(events[0].kind, TaskEventKind::Completed);
+    assert_eq!(events[0].actor, "teammate");
+    assert_eq!(events[1].subject.as_deref(), Some("friend"));
+
+    let teammate_events = repo.find_visible_to(&teammate_id, None, 50).await.unwrap();
+    assert!(teammate_events.iter().all(|e| e.task_id == team_task.id));
+    assert_eq!(teammate_events.len(), 2);
+    let friend_events = repo.find_visible_to(&friend_id, None, 50).await.unwrap();
+    assert!(friend_events.iter().all(|e| e.task_id == private_task.id));
+    assert!(repo.find_visible_to(&stranger_id, None, 50).await.unwrap().is_empty());
+
+    let workspace_events = repo.find_by_workspace(&workspace.id, None, 1).await.unwrap();
+    assert_eq!(workspace_events.len(), 1);
+    assert_eq!(workspace_events[0].task_title, "Team task");
+
+    let since = events[1].created_at;
+    assert_eq!(repo.find_visible_to(&owner_id, Some(since), 50).await.unwrap().len(), 1);
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_mark_seen_only_moves_forward() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresTaskEventRepository::new(pool);
+
+    assert!(repo.last_seen(&user_id).await.unwrap().is_none());
+
+    let now = chrono::Utc::now();
+    repo.mark_seen(&user_id, now).await.unwrap();
+    repo.mark_seen(&user_id, now - chrono::Duration::hours(1)).await.unwrap();
+    let seen_at = repo.last_seen(&user_id).await.unwrap().unwrap();
+    assert_eq!(seen_at.timestamp_micros(), now.timestamp_micros());
+
+    drop_test_schema(&schema).await;
+}
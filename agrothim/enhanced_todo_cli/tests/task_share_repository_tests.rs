@@ -0,0 +1,55 @@
+mod common;
+
+use sqlx::Executor;
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::task_repository::{PostgresTaskRepository, TaskRepository};
+use enhanced_todo_cli::database::repositories::task_share_repository::{
+    PostgresTaskShareRepository, TaskShareRepository,
+};
+use enhanced_todo_cli::models::task::StoreTaskRequest;
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_share_task_and_change_access() {
+    let (pool, schema, owner_id) = setup_test_db().await;
+    let friend_id = Uuid::new_v4();
+    pool.execute(&*format!(
+        r#"
+        INSERT INTO users (id, username, email, password_hash)
+        VALUES ('{}', 'friend', 'friend@example.com', '$2b$12$abcdefghijklmnopqrstuv')
+    "#,
+        friend_id
+    ))
+    .await
+    .unwrap();
+
+    let tasks = PostgresTaskRepository::new(pool.clone());
+    let repo = PostgresTaskShareRepository::new(pool);
+
+    let task = tasks.store(StoreTaskRequest {
+        title: "Shared".to_string(),
+        ..Default::default()
+    }, &owner_id).await.unwrap();
+    assert!(repo.find(&task.id, &friend_id).await.unwrap().is_none());
+
+    let share = repo.store(&task.id, &friend_id, true).await.expect("store failed");
+    assert!(share.read_only);
+
+    // Sharing again only changes the access
+    let share = repo.store(&task.id, &friend_id, false).await.unwrap();
+    assert!(!share.read_only);
+    let shares = repo.find_by_user_id(&friend_id).await.unwrap();
+    assert_eq!(shares.len(), 1);
+    assert!(repo.find_by_user_id(&owner_id).await.unwrap().is_empty());
+    let recipients = repo.find_by_task_id(&task.id).await.unwrap();
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].user_id, friend_id);
+
+    let shared = tasks.find_by_ids(&[shares[0].task_id]).await.unwrap();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].user_id, owner_id);
+
+    drop_test_schema(&schema).await;
+}
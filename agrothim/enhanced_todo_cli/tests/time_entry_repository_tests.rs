@@ -0,0 +1,103 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::task_repository::{PostgresTaskRepository, TaskRepository};
+use enhanced_todo_cli::database::repositories::time_entry_repository::{
+    PostgresTimeEntryRepository, TimeEntryRepository,
+};
+use enhanced_todo_cli::models::task::{StoreTaskRequest, Task, TaskPriority, TaskStatus};
+
+use common::{drop_test_schema, setup_test_db};
+
+async fn create_task(pool: &PgPool, user_id: &Uuid, title: &str) -> Task {
+    PostgresTaskRepository::new(pool.clone())
+        .store(StoreTaskRequest {
+            title: title.to_string(),
+            description: None,
+            status: TaskStatus::Pending,
+            priority: TaskPriority::Medium,
+            due_date: None,
+            ..Default::default()
+        }, user_id)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_start_stops_running_timer() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let first = create_task(&pool, &user_id, "First").await;
+    let second = create_task(&pool, &user_id, "Second").await;
+    let repo = PostgresTimeEntryRepository::new(pool);
+
+    let started_at = Utc::now() - Duration::minutes(30);
+    let (entry, stopped) = repo.start(&user_id, &first.id, started_at).await.unwrap();
+    assert!(entry.ended_at.is_none());
+    assert!(stopped.is_none());
+
+    let (entry, stopped) = repo.start(&user_id, &second.id, Utc::now()).await.unwrap();
+    let stopped = stopped.expect("previous timer should be stopped");
+    assert_eq!(stopped.task_id, first.id);
+    assert!(stopped.duration() >= Duration::minutes(29));
+
+    let running = repo.find_running(&user_id).await.unwrap().unwrap();
+    assert_eq!(running.id, entry.id);
+
+    let tracked = repo.tracked_time(&user_id, &first.id).await.unwrap();
+    assert!(tracked.total_seconds >= 29 * 60);
+    assert!(!tracked.running);
+    assert!(repo.tracked_time(&user_id, &second.id).await.unwrap().running);
+
+    let entries = repo.find_by_user_id(&user_id).await.unwrap();
+    let task_ids: Vec<Uuid> = entries.iter().map(|entry| entry.task_id).collect();
+    assert_eq!(task_ids, vec![first.id, second.id]);
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_stop_without_running_timer() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresTimeEntryRepository::new(pool);
+
+    assert!(repo.stop(&user_id, Utc::now()).await.unwrap().is_none());
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_summarize_clamps_to_period() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let task = create_task(&pool, &user_id, "Report").await;
+    let repo = PostgresTimeEntryRepository::new(pool);
+
+    // Two hours, of which only the last hour falls inside the period
+    let now = Utc::now();
+    repo.start(&user_id, &task.id, now - Duration::hours(3)).await.unwrap();
+    repo.stop(&user_id, now - Duration::hours(1)).await.unwrap();
+
+    let all_time = repo.summarize(&user_id, None, None).await.unwrap();
+    assert_eq!(all_time.len(), 1);
+    assert_eq!(all_time[0].entry_count, 1);
+    assert_eq!(all_time[0].total_seconds, 2 * 3600);
+
+    let recent = repo.summarize(&user_id, Some(now - Duration::hours(2)), None).await.unwrap();
+    assert_eq!(recent[0].total_seconds, 3600);
+
+    let empty = repo.summarize(&user_id, Some(now - Duration::minutes(30)), None).await.unwrap();
+    assert!(empty.is_empty());
+
+    let first_half = repo
+        .summarize(&user_id, Some(now - Duration::hours(4)), Some(now - Duration::hours(2)))
+        .await
+        .unwrap();
+    assert_eq!(first_half[0].total_seconds, 3600);
+
+    let before = repo.summarize(&user_id, None, Some(now - Duration::hours(4))).await.unwrap();
+    assert!(before.is_empty());
+
+    drop_test_schema(&schema).await;
+}
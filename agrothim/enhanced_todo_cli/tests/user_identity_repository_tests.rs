@@ -0,0 +1,55 @@
+mod common;
+
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::user_identity_repository::{
+    PostgresUserIdentityRepository, UserIdentityRepository,
+};
+use enhanced_todo_cli::models::user_identity::OAuthProvider;
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_link_and_find_identity() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresUserIdentityRepository::new(pool);
+
+    assert!(repo.find(OAuthProvider::GitHub, "583231").await.unwrap().is_none());
+
+    let identity = repo
+        .link(&user_id, OAuthProvider::GitHub, "583231", Some("tester@example.com"))
+        .await
+        .expect("link failed");
+    assert_eq!(identity.user_id, user_id);
+    assert_eq!(identity.provider, "github");
+
+    let found = repo.find(OAuthProvider::GitHub, "583231").await.unwrap().unwrap();
+    assert_eq!(found.id, identity.id);
+    // Same subject at another provider is a different account
+    assert!(repo.find(OAuthProvider::Google, "583231").await.unwrap().is_none());
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_relinking_keeps_original_user() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresUserIdentityRepository::new(pool);
+
+    let first = repo
+        .link(&user_id, OAuthProvider::Google, "1090", Some("old@example.com"))
+        .await
+        .unwrap();
+    let again = repo
+        .link(&Uuid::new_v4(), OAuthProvider::Google, "1090", Some("new@example.com"))
+        .await
+        .unwrap();
+
+    assert_eq!(again.id, first.id);
+    assert_eq!(again.user_id, user_id);
+    assert_eq!(again.email.as_deref(), Some("new@example.com"));
+    assert!(again.last_login_at >= first.last_login_at);
+    assert_eq!(repo.find_by_user_id(&user_id).await.unwrap().len(), 1);
+
+    drop_test_schema(&schema).await;
+}
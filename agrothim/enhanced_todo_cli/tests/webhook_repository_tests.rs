@@ -0,0 +1,72 @@
+mod common;
+
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::webhook_repository::{
+    PostgresWebhookRepository, WebhookRepository,
+};
+use enhanced_todo_cli::models::webhook::WebhookEvent;
+
+use common::{drop_test_schema, setup_test_db};
+
+#[tokio::test]
+async fn test_store_list_and_delete_webhooks() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresWebhookRepository::new(pool);
+
+    let all_events = repo
+        .store(&user_id, "https://example.com/all", "secret-1", &[])
+        .await
+        .expect("store failed");
+    let login_only = repo
+        .store(&user_id, "https://example.com/login", "secret-2", &[WebhookEvent::Login])
+        .await
+        .expect("store failed");
+
+    assert!(all_events.events.is_empty());
+    assert_eq!(login_only.events, vec!["auth.login".to_string()]);
+
+    let webhooks = repo.find_by_user_id(&user_id).await.unwrap();
+    assert_eq!(webhooks.len(), 2);
+    assert_eq!(webhooks[1].secret, "secret-2");
+
+    assert!(repo.delete(&user_id, &all_events.id).await.unwrap());
+    assert!(!repo.delete(&Uuid::new_v4(), &login_only.id).await.unwrap());
+    assert_eq!(repo.find_by_user_id(&user_id).await.unwrap().len(), 1);
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_store_and_find_deliveries() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresWebhookRepository::new(pool);
+
+    let webhook = repo
+        .store(&user_id, "https://example.com/tasks", "secret", &[WebhookEvent::TaskCreated])
+        .await
+        .expect("store failed");
+
+    let failed = repo
+        .store_delivery(&webhook.id, WebhookEvent::TaskCreated, 3, Some(503), Some("HTTP 503 Service Unavailable"))
+        .await
+        .expect("store_delivery failed");
+    assert!(!failed.succeeded());
+    assert_eq!(failed.event, "task.created");
+
+    repo.store_delivery(&webhook.id, WebhookEvent::TaskCompleted, 1, Some(200), None)
+        .await
+        .expect("store_delivery failed");
+
+    let deliveries = repo.find_deliveries(&user_id, &webhook.id, 10).await.unwrap();
+    assert_eq!(deliveries.len(), 2);
+    assert_eq!(deliveries[0].event, "task.completed");
+    assert!(deliveries[0].succeeded());
+    assert_eq!(deliveries[1].attempts, 3);
+
+    assert_eq!(repo.find_deliveries(&user_id, &webhook.id, 1).await.unwrap().len(), 1);
+    // Other users never see the log of someone else's webhook
+    assert!(repo.find_deliveries(&Uuid::new_v4(), &webhook.id, 10).await.unwrap().is_empty());
+
+    drop_test_schema(&schema).await;
+}
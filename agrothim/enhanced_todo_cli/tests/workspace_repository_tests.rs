@@ -0,0 +1,65 @@
+mod common;
+
+use sqlx::{Executor, PgPool};
+use uuid::Uuid;
+
+use enhanced_todo_cli::database::repositories::workspace_repository::{
+    PostgresWorkspaceRepository, WorkspaceRepository,
+};
+
+use common::{drop_test_schema, setup_test_db};
+
+async fn insert_user(pool: &PgPool, username: &str) -> Uuid {
+    let user_id = Uuid::new_v4();
+    pool.execute(&*format!(
+        r#"
+        INSERT INTO users (id, username, email, password_hash)
+        VALUES ('{}', '{}', '{}@example.com', '$2b$12$abcdefghijklmnopqrstuv')
+    "#,
+        user_id, username, username
+    ))
+    .await
+    .unwrap();
+    user_id
+}
+
+#[tokio::test]
+async fn test_store_makes_owner_a_member() {
+    let (pool, schema, user_id) = setup_test_db().await;
+    let repo = PostgresWorkspaceRepository::new(pool);
+
+    let workspace = repo.store(&user_id, "Team").await.expect("store failed");
+    assert_eq!(workspace.name, "Team");
+    assert_eq!(workspace.owner_id, user_id);
+
+    let membership = repo.find_membership(&workspace.id, &user_id).await.unwrap().unwrap();
+    assert_eq!(membership.role, "owner");
+    assert_eq!(membership.username, "tester");
+
+    let workspaces = repo.find_by_member(&user_id).await.unwrap();
+    assert_eq!(workspaces.len(), 1);
+    assert_eq!(workspaces[0].id, workspace.id);
+
+    drop_test_schema(&schema).await;
+}
+
+#[tokio::test]
+async fn test_add_member_once() {
+    let (pool, schema, owner_id) = setup_test_db().await;
+    let member_id = insert_user(&pool, "teammate").await;
+    let repo = PostgresWorkspaceRepository::new(pool);
+
+    let workspace = repo.store(&owner_id, "Team").await.unwrap();
+    assert!(repo.find_membership(&workspace.id, &member_id).await.unwrap().is_none());
+
+    assert!(repo.add_member(&workspace.id, &member_id).await.unwrap());
+    assert!(!repo.add_member(&workspace.id, &member_id).await.unwrap());
+
+    let members = repo.find_members(&workspace.id).await.unwrap();
+    let usernames: Vec<&str> = members.iter().map(|m| m.username.as_str()).collect();
+    assert_eq!(usernames, vec!["tester", "teammate"]);
+    assert_eq!(members[1].role, "member");
+    assert_eq!(repo.find_by_member(&member_id).await.unwrap().len(), 1);
+
+    drop_test_schema(&schema).await;
+}